@@ -28,6 +28,9 @@ pub fn create_egui_context(storage: Option<&dyn crate::Storage>) -> egui::Contex
     let memory = crate::native::epi_integration::load_egui_memory(storage).unwrap_or_default();
     egui_ctx.memory_mut(|mem| *mem = memory);
 
+    // Both the glow and wgpu renderers can blur backdrops:
+    egui_ctx.tessellation_options_mut(|o| o.backdrop_blur = true);
+
     egui_ctx
 }
 
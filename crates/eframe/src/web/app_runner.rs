@@ -68,6 +68,8 @@ impl AppRunner {
             o.zoom_with_keyboard = false;
             o.zoom_factor = 1.0;
         });
+        // Both the glow and wgpu renderers can blur backdrops:
+        egui_ctx.tessellation_options_mut(|o| o.backdrop_blur = true);
 
         let theme = system_theme.unwrap_or(web_options.default_theme);
         egui_ctx.set_visuals(theme.egui_visuals());
@@ -249,8 +251,11 @@ impl AppRunner {
             cursor_icon,
             open_url,
             copied_text,
-            events: _, // already handled
+            copied_text_primary: _, // the web has no primary selection
+            copied_rich_text: _,    // `copied_text` has the same plain text
+            events: _,              // already handled
             mutable_text_under_cursor,
+            accepts_pasted_images: _, // pasting images isn't supported on web
             ime,
             #[cfg(feature = "accesskit")]
                 accesskit_update: _, // not currently implemented
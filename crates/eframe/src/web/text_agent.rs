@@ -66,7 +66,12 @@ impl TextAgent {
         let on_composition_update = {
             move |event: web_sys::CompositionEvent, runner: &mut AppRunner| {
                 let Some(text) = event.data() else { return };
-                let event = egui::Event::Ime(egui::ImeEvent::Preedit(text));
+                // The browser doesn't tell us where the cursor is, so put it at the end:
+                let num_chars = text.chars().count();
+                let event = egui::Event::Ime(egui::ImeEvent::Preedit {
+                    text,
+                    cursor: Some(num_chars..num_chars),
+                });
                 runner.input.raw.events.push(event);
                 runner.needs_repaint.repaint_asap();
             }
@@ -174,7 +174,18 @@ impl WebPainterWgpu {
                 .await
                 .map_err(|err| err.to_string())?;
 
+        let mut usage = wgpu::TextureUsages::RENDER_ATTACHMENT;
+        // For copying what's behind a backdrop, to blur it:
+        if surface
+            .get_capabilities(&render_state.adapter)
+            .usages
+            .contains(wgpu::TextureUsages::COPY_SRC)
+        {
+            usage |= wgpu::TextureUsages::COPY_SRC;
+        }
+
         let surface_configuration = wgpu::SurfaceConfiguration {
+            usage,
             format: render_state.target_format,
             present_mode: options.wgpu_options.present_mode,
             view_formats: vec![render_state.target_format],
@@ -294,38 +305,24 @@ impl WebPainter for WebPainterWgpu {
                 let frame_view = frame
                     .texture
                     .create_view(&wgpu::TextureViewDescriptor::default());
-                let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                        view: &frame_view,
-                        resolve_target: None,
-                        ops: wgpu::Operations {
-                            load: wgpu::LoadOp::Clear(wgpu::Color {
-                                r: clear_color[0] as f64,
-                                g: clear_color[1] as f64,
-                                b: clear_color[2] as f64,
-                                a: clear_color[3] as f64,
-                            }),
-                            store: wgpu::StoreOp::Store,
-                        },
-                    })],
-                    depth_stencil_attachment: self.depth_texture_view.as_ref().map(|view| {
-                        wgpu::RenderPassDepthStencilAttachment {
-                            view,
-                            depth_ops: Some(wgpu::Operations {
-                                load: wgpu::LoadOp::Clear(1.0),
-                                // It is very unlikely that the depth buffer is needed after egui finished rendering
-                                // so no need to store it. (this can improve performance on tiling GPUs like mobile chips or Apple Silicon)
-                                store: wgpu::StoreOp::Discard,
-                            }),
-                            stencil_ops: None,
-                        }
+                let target = egui_wgpu::RenderTarget {
+                    view: &frame_view,
+                    resolve_target: None,
+                    texture: &frame.texture,
+                    depth_view: self.depth_texture_view.as_ref(),
+                    clear_color: Some(wgpu::Color {
+                        r: clear_color[0] as f64,
+                        g: clear_color[1] as f64,
+                        b: clear_color[2] as f64,
+                        a: clear_color[3] as f64,
                     }),
-                    label: Some("egui_render"),
-                    occlusion_query_set: None,
-                    timestamp_writes: None,
-                });
-
-                renderer.render(&mut render_pass, clipped_primitives, &screen_descriptor);
+                };
+                renderer.render_to_target(
+                    &mut encoder,
+                    &target,
+                    clipped_primitives,
+                    &screen_descriptor,
+                );
             }
 
             Some(frame)
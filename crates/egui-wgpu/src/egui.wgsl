@@ -89,3 +89,94 @@ fn fs_main_gamma_framebuffer(in: VertexOutput) -> @location(0) vec4<f32> {
     let out_color_gamma = in.color * tex_gamma;
     return out_color_gamma;
 }
+
+// For textures that are signed distance fields (see `TextureOptions::sdf`),
+// where the alpha is the distance to the outline, which is at 0.5.
+fn sdf_coverage(distance: f32) -> f32 {
+    let smoothing = 0.5 * fwidth(distance); // ≈ half a pixel
+    return smoothstep(0.5 - smoothing, 0.5 + smoothing, distance);
+}
+
+@fragment
+fn fs_main_sdf_linear_framebuffer(in: VertexOutput) -> @location(0) vec4<f32> {
+    let distance = textureSample(r_tex_color, r_tex_sampler, in.tex_coord).a;
+    let out_color_gamma = in.color * sdf_coverage(distance);
+    return vec4<f32>(linear_from_gamma_rgb(out_color_gamma.rgb), out_color_gamma.a);
+}
+
+@fragment
+fn fs_main_sdf_gamma_framebuffer(in: VertexOutput) -> @location(0) vec4<f32> {
+    let distance = textureSample(r_tex_color, r_tex_sampler, in.tex_coord).a;
+    return in.color * sdf_coverage(distance);
+}
+
+// Blurring what's behind an `epaint::Primitive::Backdrop`, see `Renderer::render_to_target`.
+// It is blurred in two passes: first horizontally into a texture with `fs_blur`,
+// then vertically when it is painted in the backdrop mesh with `fs_backdrop_*`.
+// `egui_glow` blurs the same way.
+
+struct BlurLocals {
+    // Between two samples, in texture coordinates.
+    step: vec2<f32>,
+    // The standard deviation of the blur, in samples.
+    sigma: f32,
+    _padding: f32,
+    // What was copied from behind the backdrop, in texture coordinates.
+    // Nothing outside of it is sampled.
+    tex_coord_min: vec2<f32>,
+    tex_coord_max: vec2<f32>,
+};
+@group(1) @binding(2) var<uniform> r_blur: BlurLocals;
+
+// At most this many samples on each side. For wider blurs, the samples are spread out.
+const MAX_BLUR_SAMPLES: i32 = 32;
+
+fn blur_sample(tex_coord: vec2<f32>) -> vec4<f32> {
+    let clamped = clamp(tex_coord, r_blur.tex_coord_min, r_blur.tex_coord_max);
+    // The backdrop textures are not sRGB aware, so this is in gamma space, like the framebuffer:
+    return textureSampleLevel(r_tex_color, r_tex_sampler, clamped, 0.0);
+}
+
+fn blur(tex_coord: vec2<f32>) -> vec4<f32> {
+    let sigma = r_blur.sigma;
+    var sum = blur_sample(tex_coord);
+    var total_weight = 1.0;
+    for (var i = 1; i <= MAX_BLUR_SAMPLES; i++) {
+        let x = f32(i);
+        if 3.0 * sigma < x {
+            break;
+        }
+        let weight = exp(-x * x / (2.0 * sigma * sigma));
+        let offset = x * r_blur.step;
+        sum += weight * (blur_sample(tex_coord - offset) + blur_sample(tex_coord + offset));
+        total_weight += 2.0 * weight;
+    }
+    return sum / total_weight;
+}
+
+// A single triangle covering the whole target.
+@vertex
+fn vs_fullscreen(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    let tex_coord = vec2<f32>(f32((vertex_index << 1u) & 2u), f32(vertex_index & 2u));
+    var out: VertexOutput;
+    out.tex_coord = tex_coord;
+    out.color = vec4<f32>(1.0);
+    out.position = vec4<f32>(2.0 * tex_coord.x - 1.0, 1.0 - 2.0 * tex_coord.y, 0.0, 1.0);
+    return out;
+}
+
+@fragment
+fn fs_blur(in: VertexOutput) -> @location(0) vec4<f32> {
+    return blur(in.tex_coord);
+}
+
+@fragment
+fn fs_backdrop_linear_framebuffer(in: VertexOutput) -> @location(0) vec4<f32> {
+    let out_color_gamma = in.color * blur(in.tex_coord);
+    return vec4<f32>(linear_from_gamma_rgb(out_color_gamma.rgb), out_color_gamma.a);
+}
+
+@fragment
+fn fs_backdrop_gamma_framebuffer(in: VertexOutput) -> @location(0) vec4<f32> {
+    return in.color * blur(in.tex_coord);
+}
@@ -2,8 +2,8 @@
 
 use std::{borrow::Cow, num::NonZeroU64, ops::Range};
 
-use ahash::HashMap;
-use epaint::{emath::NumExt, PaintCallbackInfo, Primitive, Vertex};
+use ahash::{HashMap, HashSet};
+use epaint::{emath::NumExt, Backdrop, PaintCallbackInfo, Primitive, Vertex};
 
 use wgpu::util::DeviceExt as _;
 
@@ -144,6 +144,73 @@ impl PartialEq for UniformBuffer {
     }
 }
 
+/// Uniform buffer for one pass of the backdrop blur, see `BlurLocals` in `egui.wgsl`.
+#[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+#[repr(C)]
+struct BlurUniform {
+    step: [f32; 2],
+    sigma: f32,
+    _padding: f32,
+    tex_coord_min: [f32; 2],
+    tex_coord_max: [f32; 2],
+}
+
+/// At most this many samples are taken on each side of a pixel when blurring a backdrop,
+/// like `MAX_BLUR_SAMPLES` in `egui.wgsl`.
+const MAX_BLUR_SAMPLES: f32 = 32.0;
+
+/// How to blur a [`Primitive::Backdrop`] of the current frame, see [`Renderer::render_to_target`].
+struct BackdropBlur {
+    /// What's behind the backdrop as far as the blur reaches, in pixels.
+    region: ScissorRect,
+
+    /// Dynamic offset of the [`BlurUniform`] of the horizontal pass.
+    /// That of the vertical pass follows [`Renderer::blur_uniform_stride`] bytes later.
+    uniform_offset: u32,
+}
+
+/// Where what's behind a [`Primitive::Backdrop`] is blurred.
+///
+/// These are kept across frames, and have the size of the screen,
+/// so what's behind a backdrop is copied to the same place in them.
+struct BackdropTextures {
+    size: [u32; 2],
+
+    /// What's behind the backdrop, copied from the render target.
+    copy: wgpu::Texture,
+
+    /// Samples [`Self::copy`] for blurring it horizontally into [`Self::blurred_view`].
+    copy_bind_group: wgpu::BindGroup,
+
+    /// Blurred horizontally.
+    blurred_view: wgpu::TextureView,
+
+    /// Samples [`Self::blurred_view`] for blurring it vertically while painting the backdrop mesh.
+    blurred_bind_group: wgpu::BindGroup,
+}
+
+/// What [`Renderer::render_to_target`] paints into.
+pub struct RenderTarget<'a> {
+    /// The color attachment, e.g. a view of the surface texture, or of a multisampled texture.
+    pub view: &'a wgpu::TextureView,
+
+    /// Where [`Self::view`] is resolved to, if it is multisampled.
+    pub resolve_target: Option<&'a wgpu::TextureView>,
+
+    /// The texture with the final pixels: that of [`Self::resolve_target`] if there is one,
+    /// else that of [`Self::view`].
+    ///
+    /// What's behind each [`Primitive::Backdrop`] is copied from here to be blurred,
+    /// so this needs [`wgpu::TextureUsages::COPY_SRC`], or backdrops are not blurred.
+    pub texture: &'a wgpu::Texture,
+
+    /// The depth attachment, if any.
+    pub depth_view: Option<&'a wgpu::TextureView>,
+
+    /// What to clear [`Self::view`] to first, or `None` to paint on top of what's there.
+    pub clear_color: Option<wgpu::Color>,
+}
+
 struct SlicedBuffer {
     buffer: wgpu::Buffer,
     slices: Vec<Range<usize>>,
@@ -154,6 +221,34 @@ struct SlicedBuffer {
 pub struct Renderer {
     pipeline: wgpu::RenderPipeline,
 
+    /// Used for meshes with textures that are signed distance fields, see [`epaint::textures::TextureOptions::sdf`].
+    sdf_pipeline: wgpu::RenderPipeline,
+
+    /// Paints a [`Primitive::Backdrop`] in the render target, blurring it vertically.
+    backdrop_pipeline: wgpu::RenderPipeline,
+
+    /// Blurs what's behind a [`Primitive::Backdrop`] horizontally into [`BackdropTextures`].
+    blur_pipeline: wgpu::RenderPipeline,
+    blur_bind_group_layout: wgpu::BindGroupLayout,
+
+    /// The [`BlurUniform`]s of the current frame, two per backdrop.
+    blur_uniform_buffer: wgpu::Buffer,
+
+    /// Bytes between the [`BlurUniform`]s in [`Self::blur_uniform_buffer`],
+    /// to align their dynamic offsets.
+    blur_uniform_stride: u32,
+
+    /// The format of [`BackdropTextures`]: that of the output, but not sRGB aware,
+    /// so the blurring happens in gamma space.
+    backdrop_format: wgpu::TextureFormat,
+
+    /// Created when first needed, and when the screen size changes.
+    backdrop_textures: Option<BackdropTextures>,
+
+    /// How to blur each [`Primitive::Backdrop`] of the current frame,
+    /// or `None` if it is outside the screen.
+    backdrops: Vec<Option<BackdropBlur>>,
+
     index_buffer: SlicedBuffer,
     vertex_buffer: SlicedBuffer,
 
@@ -166,6 +261,9 @@ pub struct Renderer {
     /// sampler). The texture may be None if the `TextureId` is just a handle to a user-provided
     /// sampler.
     textures: HashMap<epaint::TextureId, (Option<wgpu::Texture>, wgpu::BindGroup)>,
+
+    /// The textures that are signed distance fields, drawn with the `sdf_pipeline`.
+    sdf_textures: HashSet<epaint::TextureId>,
     next_user_texture_id: u64,
     samplers: HashMap<epaint::textures::TextureOptions, wgpu::Sampler>,
 
@@ -278,11 +376,17 @@ impl Renderer {
             bias: wgpu::DepthBiasState::default(),
         });
 
-        let pipeline = {
+        if output_color_format.is_srgb() {
+            log::warn!("Detected a linear (sRGBA aware) framebuffer {:?}. egui prefers Rgba8Unorm or Bgra8Unorm", output_color_format);
+        }
+
+        let create_pipeline = |label: &str,
+                               layout: &wgpu::PipelineLayout,
+                               fragment_entry_point: &str| {
             crate::profile_scope!("create_render_pipeline");
             device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-                label: Some("egui_pipeline"),
-                layout: Some(&pipeline_layout),
+                label: Some(label),
+                layout: Some(layout),
                 vertex: wgpu::VertexState {
                     entry_point: "vs_main",
                     module: &module,
@@ -304,7 +408,7 @@ impl Renderer {
                     polygon_mode: wgpu::PolygonMode::default(),
                     strip_index_format: None,
                 },
-                depth_stencil,
+                depth_stencil: depth_stencil.clone(),
                 multisample: wgpu::MultisampleState {
                     alpha_to_coverage_enabled: false,
                     count: msaa_samples,
@@ -313,12 +417,7 @@ impl Renderer {
 
                 fragment: Some(wgpu::FragmentState {
                     module: &module,
-                    entry_point: if output_color_format.is_srgb() {
-                        log::warn!("Detected a linear (sRGBA aware) framebuffer {:?}. egui prefers Rgba8Unorm or Bgra8Unorm", output_color_format);
-                        "fs_main_linear_framebuffer"
-                    } else {
-                        "fs_main_gamma_framebuffer" // this is what we prefer
-                    },
+                    entry_point: fragment_entry_point,
                     targets: &[Some(wgpu::ColorTargetState {
                         format: output_color_format,
                         blend: Some(wgpu::BlendState {
@@ -341,6 +440,58 @@ impl Renderer {
         )
         };
 
+        let (pipeline, sdf_pipeline) = if output_color_format.is_srgb() {
+            (
+                create_pipeline(
+                    "egui_pipeline",
+                    &pipeline_layout,
+                    "fs_main_linear_framebuffer",
+                ),
+                create_pipeline(
+                    "egui_sdf_pipeline",
+                    &pipeline_layout,
+                    "fs_main_sdf_linear_framebuffer",
+                ),
+            )
+        } else {
+            // This is what we prefer:
+            (
+                create_pipeline(
+                    "egui_pipeline",
+                    &pipeline_layout,
+                    "fs_main_gamma_framebuffer",
+                ),
+                create_pipeline(
+                    "egui_sdf_pipeline",
+                    &pipeline_layout,
+                    "fs_main_sdf_gamma_framebuffer",
+                ),
+            )
+        };
+
+        let blur_bind_group_layout = create_blur_bind_group_layout(device);
+        let blur_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("egui_blur_pipeline_layout"),
+            bind_group_layouts: &[&uniform_bind_group_layout, &blur_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let backdrop_pipeline = create_pipeline(
+            "egui_backdrop_pipeline",
+            &blur_pipeline_layout,
+            if output_color_format.is_srgb() {
+                "fs_backdrop_linear_framebuffer"
+            } else {
+                "fs_backdrop_gamma_framebuffer"
+            },
+        );
+        let backdrop_format = output_color_format.remove_srgb_suffix();
+        let blur_pipeline =
+            create_blur_pipeline(device, &module, &blur_pipeline_layout, backdrop_format);
+        let blur_uniform_stride = wgpu::util::align_to(
+            std::mem::size_of::<BlurUniform>() as u32,
+            device.limits().min_uniform_buffer_offset_alignment,
+        );
+
         const VERTEX_BUFFER_START_CAPACITY: wgpu::BufferAddress =
             (std::mem::size_of::<Vertex>() * 1024) as _;
         const INDEX_BUFFER_START_CAPACITY: wgpu::BufferAddress =
@@ -348,6 +499,15 @@ impl Renderer {
 
         Self {
             pipeline,
+            sdf_pipeline,
+            backdrop_pipeline,
+            blur_pipeline,
+            blur_bind_group_layout,
+            blur_uniform_buffer: create_blur_uniform_buffer(device, 2 * blur_uniform_stride as u64),
+            blur_uniform_stride,
+            backdrop_format,
+            backdrop_textures: None,
+            backdrops: Vec::new(),
             vertex_buffer: SlicedBuffer {
                 buffer: create_vertex_buffer(device, VERTEX_BUFFER_START_CAPACITY),
                 slices: Vec::with_capacity(64),
@@ -367,6 +527,7 @@ impl Renderer {
             uniform_bind_group,
             texture_bind_group_layout,
             textures: HashMap::default(),
+            sdf_textures: HashSet::default(),
             next_user_texture_id: 0,
             samplers: HashMap::default(),
             callback_resources: CallbackResources::default(),
@@ -374,6 +535,9 @@ impl Renderer {
     }
 
     /// Executes the egui renderer onto an existing wgpu renderpass.
+    ///
+    /// This can't blur what's behind a [`Primitive::Backdrop`], so only the tint of backdrops is painted.
+    /// Use [`Self::render_to_target`] for that.
     pub fn render<'rp>(
         &'rp self,
         render_pass: &mut wgpu::RenderPass<'rp>,
@@ -381,7 +545,159 @@ impl Renderer {
         screen_descriptor: &ScreenDescriptor,
     ) {
         crate::profile_function!();
+        self.render_jobs(render_pass, paint_jobs, 0, 0, false, screen_descriptor);
+    }
+
+    /// Executes the egui renderer into `target`, in as many render passes as needed
+    /// to blur what's behind each [`Primitive::Backdrop`].
+    ///
+    /// Before each backdrop, the render pass ends, what's behind the backdrop is copied
+    /// from [`RenderTarget::texture`] and blurred, and a new render pass continues on top.
+    pub fn render_to_target(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &RenderTarget<'_>,
+        paint_jobs: &[epaint::ClippedPrimitive],
+        screen_descriptor: &ScreenDescriptor,
+    ) {
+        crate::profile_function!();
+
+        let can_blur = self.backdrop_textures.as_ref().is_some_and(|textures| {
+            textures.size == screen_descriptor.size_in_pixels
+                && textures.size == [target.texture.width(), target.texture.height()]
+        }) && target
+            .texture
+            .usage()
+            .contains(wgpu::TextureUsages::COPY_SRC)
+            && target.texture.sample_count() == 1
+            && target.texture.format().remove_srgb_suffix() == self.backdrop_format;
+
+        let mut clear_color = target.clear_color;
+
+        // Where the current render pass starts: the index of the paint job,
+        // its index and vertex buffer slice, its backdrop, and whether it starts with a blurred one.
+        let mut pass_start = (0, 0, 0, false);
+        let (mut slice_index, mut backdrop_index) = (0, 0);
+        for (job_index, job) in paint_jobs.iter().enumerate() {
+            match &job.primitive {
+                Primitive::Mesh(_) => {
+                    slice_index += 1;
+                }
+                Primitive::Backdrop(_) => {
+                    if let (true, Some(Some(blur))) = (can_blur, self.backdrops.get(backdrop_index))
+                    {
+                        {
+                            let mut render_pass =
+                                begin_render_pass(encoder, target, clear_color.take());
+                            let (first_job, first_slice, first_backdrop, blurred_first) =
+                                pass_start;
+                            self.render_jobs(
+                                &mut render_pass,
+                                &paint_jobs[first_job..job_index],
+                                first_slice,
+                                first_backdrop,
+                                blurred_first,
+                                screen_descriptor,
+                            );
+                        }
+                        self.blur_backdrop(encoder, target.texture, blur);
+                        pass_start = (job_index, slice_index, backdrop_index, true);
+                    }
+                    slice_index += 1;
+                    backdrop_index += 1;
+                }
+                Primitive::Callback(_) => {}
+            }
+        }
+
+        let mut render_pass = begin_render_pass(encoder, target, clear_color);
+        let (first_job, first_slice, first_backdrop, blurred_first) = pass_start;
+        self.render_jobs(
+            &mut render_pass,
+            &paint_jobs[first_job..],
+            first_slice,
+            first_backdrop,
+            blurred_first,
+            screen_descriptor,
+        );
+    }
+
+    /// Copy what's behind a backdrop from `target_texture`, and blur it horizontally,
+    /// for [`Self::render_jobs`] to blur it vertically when it paints the backdrop.
+    fn blur_backdrop(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        target_texture: &wgpu::Texture,
+        blur: &BackdropBlur,
+    ) {
+        crate::profile_function!();
+
+        let Some(textures) = &self.backdrop_textures else {
+            return;
+        };
+        let ScissorRect {
+            x,
+            y,
+            width,
+            height,
+        } = blur.region;
+
+        let origin = wgpu::Origin3d { x, y, z: 0 };
+        encoder.copy_texture_to_texture(
+            wgpu::ImageCopyTexture {
+                texture: target_texture,
+                mip_level: 0,
+                origin,
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::ImageCopyTexture {
+                texture: &textures.copy,
+                mip_level: 0,
+                origin,
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+        );
 
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("egui_backdrop_blur"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &textures.blurred_view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        render_pass.set_pipeline(&self.blur_pipeline);
+        render_pass.set_bind_group(0, &self.uniform_bind_group, &[]);
+        render_pass.set_bind_group(1, &textures.copy_bind_group, &[blur.uniform_offset]);
+        render_pass.set_scissor_rect(x, y, width, height);
+        render_pass.draw(0..3, 0..1);
+    }
+
+    /// Paint `paint_jobs`, the first of which uses the index and vertex buffer slice `first_slice`
+    /// (if any) and the first backdrop of which is `first_backdrop`.
+    ///
+    /// If `blurred_first`, the first paint job is a backdrop that [`Self::blur_backdrop`]
+    /// has prepared. Other backdrops are skipped.
+    fn render_jobs<'rp>(
+        &'rp self,
+        render_pass: &mut wgpu::RenderPass<'rp>,
+        paint_jobs: &'rp [epaint::ClippedPrimitive],
+        first_slice: usize,
+        first_backdrop: usize,
+        blurred_first: bool,
+        screen_descriptor: &ScreenDescriptor,
+    ) {
         let pixels_per_point = screen_descriptor.pixels_per_point;
         let size_in_pixels = screen_descriptor.size_in_pixels;
 
@@ -389,13 +705,21 @@ impl Renderer {
         // run.
         let mut needs_reset = true;
 
-        let mut index_buffer_slices = self.index_buffer.slices.iter();
-        let mut vertex_buffer_slices = self.vertex_buffer.slices.iter();
+        // Whether or not the SDF pipeline is the current one.
+        let mut sdf_pipeline_is_set = false;
+
+        let mut index_buffer_slices = self.index_buffer.slices[first_slice..].iter();
+        let mut vertex_buffer_slices = self.vertex_buffer.slices[first_slice..].iter();
+        let mut backdrops = self.backdrops[first_backdrop..].iter();
 
-        for epaint::ClippedPrimitive {
-            clip_rect,
-            primitive,
-        } in paint_jobs
+        for (
+            job_index,
+            epaint::ClippedPrimitive {
+                clip_rect,
+                primitive,
+                ..
+            },
+        ) in paint_jobs.iter().enumerate()
         {
             if needs_reset {
                 render_pass.set_viewport(
@@ -408,6 +732,7 @@ impl Renderer {
                 );
                 render_pass.set_pipeline(&self.pipeline);
                 render_pass.set_bind_group(0, &self.uniform_bind_group, &[]);
+                sdf_pipeline_is_set = false;
                 needs_reset = false;
             }
 
@@ -416,11 +741,14 @@ impl Renderer {
 
                 if rect.width == 0 || rect.height == 0 {
                     // Skip rendering zero-sized clip areas.
-                    if let Primitive::Mesh(_) = primitive {
+                    if let Primitive::Mesh(_) | Primitive::Backdrop(_) = primitive {
                         // If this is a mesh, we need to advance the index and vertex buffer iterators:
                         index_buffer_slices.next().unwrap();
                         vertex_buffer_slices.next().unwrap();
                     }
+                    if let Primitive::Backdrop(_) = primitive {
+                        backdrops.next();
+                    }
                     continue;
                 }
 
@@ -433,6 +761,15 @@ impl Renderer {
                     let vertex_buffer_slice = vertex_buffer_slices.next().unwrap();
 
                     if let Some((_texture, bind_group)) = self.textures.get(&mesh.texture_id) {
+                        let is_sdf = self.sdf_textures.contains(&mesh.texture_id);
+                        if is_sdf != sdf_pipeline_is_set {
+                            render_pass.set_pipeline(if is_sdf {
+                                &self.sdf_pipeline
+                            } else {
+                                &self.pipeline
+                            });
+                            sdf_pipeline_is_set = is_sdf;
+                        }
                         render_pass.set_bind_group(1, bind_group, &[]);
                         render_pass.set_index_buffer(
                             self.index_buffer.buffer.slice(
@@ -451,6 +788,40 @@ impl Renderer {
                         log::warn!("Missing texture: {:?}", mesh.texture_id);
                     }
                 }
+                Primitive::Backdrop(Backdrop { mesh, .. }) => {
+                    let index_buffer_slice = index_buffer_slices.next().unwrap();
+                    let vertex_buffer_slice = vertex_buffer_slices.next().unwrap();
+                    let blur = backdrops.next();
+
+                    if let (true, Some(Some(blur)), Some(textures)) = (
+                        blurred_first && job_index == 0,
+                        blur,
+                        &self.backdrop_textures,
+                    ) {
+                        render_pass.set_pipeline(&self.backdrop_pipeline);
+                        render_pass.set_bind_group(
+                            1,
+                            &textures.blurred_bind_group,
+                            &[blur.uniform_offset + self.blur_uniform_stride],
+                        );
+                        render_pass.set_index_buffer(
+                            self.index_buffer.buffer.slice(
+                                index_buffer_slice.start as u64..index_buffer_slice.end as u64,
+                            ),
+                            wgpu::IndexFormat::Uint32,
+                        );
+                        render_pass.set_vertex_buffer(
+                            0,
+                            self.vertex_buffer.buffer.slice(
+                                vertex_buffer_slice.start as u64..vertex_buffer_slice.end as u64,
+                            ),
+                        );
+                        render_pass.draw_indexed(0..mesh.indices.len() as u32, 0, 0..1);
+
+                        render_pass.set_pipeline(&self.pipeline);
+                        sdf_pipeline_is_set = false;
+                    }
+                }
                 Primitive::Callback(callback) => {
                     let Some(cbfn) = callback.callback.downcast_ref::<Callback>() else {
                         // We already warned in the `prepare` callback
@@ -506,6 +877,12 @@ impl Renderer {
     ) {
         crate::profile_function!();
 
+        if image_delta.options.sdf {
+            self.sdf_textures.insert(id);
+        } else {
+            self.sdf_textures.remove(&id);
+        }
+
         let width = image_delta.image.width() as u32;
         let height = image_delta.image.height() as u32;
 
@@ -616,6 +993,7 @@ impl Renderer {
 
     pub fn free_texture(&mut self, id: &epaint::TextureId) {
         self.textures.remove(id);
+        self.sdf_textures.remove(id);
     }
 
     /// Get the WGPU texture and bind group associated to a texture that has been allocated by egui.
@@ -799,7 +1177,7 @@ impl Renderer {
             crate::profile_scope!("count_vertices_indices");
             paint_jobs.iter().fold((0, 0), |acc, clipped_primitive| {
                 match &clipped_primitive.primitive {
-                    Primitive::Mesh(mesh) => {
+                    Primitive::Mesh(mesh) | Primitive::Backdrop(Backdrop { mesh, .. }) => {
                         (acc.0 + mesh.vertices.len(), acc.1 + mesh.indices.len())
                     }
                     Primitive::Callback(callback) => {
@@ -840,7 +1218,7 @@ impl Renderer {
             let mut index_offset = 0;
             for epaint::ClippedPrimitive { primitive, .. } in paint_jobs {
                 match primitive {
-                    Primitive::Mesh(mesh) => {
+                    Primitive::Mesh(mesh) | Primitive::Backdrop(Backdrop { mesh, .. }) => {
                         let size = mesh.indices.len() * std::mem::size_of::<u32>();
                         let slice = index_offset..(size + index_offset);
                         index_buffer_staging[slice.clone()]
@@ -878,20 +1256,34 @@ impl Renderer {
 
             let mut vertex_offset = 0;
             for epaint::ClippedPrimitive { primitive, .. } in paint_jobs {
-                match primitive {
-                    Primitive::Mesh(mesh) => {
-                        let size = mesh.vertices.len() * std::mem::size_of::<Vertex>();
-                        let slice = vertex_offset..(size + vertex_offset);
-                        vertex_buffer_staging[slice.clone()]
-                            .copy_from_slice(bytemuck::cast_slice(&mesh.vertices));
-                        self.vertex_buffer.slices.push(slice);
-                        vertex_offset += size;
+                let vertices: Cow<'_, [Vertex]> = match primitive {
+                    Primitive::Mesh(mesh) => Cow::Borrowed(&mesh.vertices),
+                    Primitive::Backdrop(Backdrop { mesh, .. }) => {
+                        // The blurred backdrop textures cover the whole screen:
+                        mesh.vertices
+                            .iter()
+                            .map(|vertex| Vertex {
+                                uv: epaint::pos2(
+                                    vertex.pos.x / screen_size_in_points[0],
+                                    vertex.pos.y / screen_size_in_points[1],
+                                ),
+                                ..*vertex
+                            })
+                            .collect()
                     }
-                    Primitive::Callback(_) => {}
-                }
+                    Primitive::Callback(_) => continue,
+                };
+                let size = vertices.len() * std::mem::size_of::<Vertex>();
+                let slice = vertex_offset..(size + vertex_offset);
+                vertex_buffer_staging[slice.clone()]
+                    .copy_from_slice(bytemuck::cast_slice(&vertices));
+                self.vertex_buffer.slices.push(slice);
+                vertex_offset += size;
             }
         }
 
+        self.prepare_backdrops(device, queue, paint_jobs, screen_descriptor);
+
         let mut user_cmd_bufs = Vec::new();
         {
             crate::profile_scope!("prepare callbacks");
@@ -919,6 +1311,284 @@ impl Renderer {
 
         user_cmd_bufs
     }
+
+    /// Work out how to blur each [`Primitive::Backdrop`], for [`Self::render_to_target`],
+    /// and make sure the [`BackdropTextures`] have the size of the screen.
+    fn prepare_backdrops(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        paint_jobs: &[epaint::ClippedPrimitive],
+        screen_descriptor: &ScreenDescriptor,
+    ) {
+        self.backdrops.clear();
+        if !paint_jobs
+            .iter()
+            .any(|job| matches!(job.primitive, Primitive::Backdrop(_)))
+        {
+            return;
+        }
+        crate::profile_function!();
+
+        let pixels_per_point = screen_descriptor.pixels_per_point;
+        let size_in_pixels = screen_descriptor.size_in_pixels;
+        let [width, height] = size_in_pixels.map(|size| size as f32);
+        let stride = self.blur_uniform_stride as usize;
+
+        let mut uniforms = Vec::new();
+        for job in paint_jobs {
+            let Primitive::Backdrop(backdrop) = &job.primitive else {
+                continue;
+            };
+
+            // The blur reaches about three standard deviations into the rect:
+            let region = ScissorRect::new(
+                &backdrop.rect.expand(3.0 * backdrop.blur_radius),
+                pixels_per_point,
+                size_in_pixels,
+            );
+            if region.width == 0 || region.height == 0 {
+                self.backdrops.push(None);
+                continue;
+            }
+
+            // Spread out the samples of wide blurs:
+            let sigma_px = backdrop.blur_radius * pixels_per_point;
+            let sample_spacing = (3.0 * sigma_px / MAX_BLUR_SAMPLES).at_least(1.0);
+            let tex_coord_min = [
+                (region.x as f32 + 0.5) / width,
+                (region.y as f32 + 0.5) / height,
+            ];
+            let tex_coord_max = [
+                ((region.x + region.width) as f32 - 0.5) / width,
+                ((region.y + region.height) as f32 - 0.5) / height,
+            ];
+
+            self.backdrops.push(Some(BackdropBlur {
+                region,
+                uniform_offset: uniforms.len() as u32,
+            }));
+            for step in [
+                [sample_spacing / width, 0.0],
+                [0.0, sample_spacing / height],
+            ] {
+                let uniform = BlurUniform {
+                    step,
+                    sigma: sigma_px / sample_spacing,
+                    _padding: 0.0,
+                    tex_coord_min,
+                    tex_coord_max,
+                };
+                let offset = uniforms.len();
+                uniforms.resize(offset + stride, 0);
+                uniforms[offset..offset + std::mem::size_of::<BlurUniform>()]
+                    .copy_from_slice(bytemuck::bytes_of(&uniform));
+            }
+        }
+        if uniforms.is_empty() {
+            return;
+        }
+
+        if self.blur_uniform_buffer.size() < uniforms.len() as u64 {
+            // The bind groups refer to the old buffer:
+            self.backdrop_textures = None;
+            self.blur_uniform_buffer = create_blur_uniform_buffer(
+                device,
+                (2 * self.blur_uniform_buffer.size()).at_least(uniforms.len() as u64),
+            );
+        }
+        queue.write_buffer(&self.blur_uniform_buffer, 0, &uniforms);
+
+        if self
+            .backdrop_textures
+            .as_ref()
+            .map_or(true, |textures| textures.size != size_in_pixels)
+        {
+            self.backdrop_textures = Some(self.create_backdrop_textures(device, size_in_pixels));
+        }
+    }
+
+    fn create_backdrop_textures(
+        &mut self,
+        device: &wgpu::Device,
+        [width, height]: [u32; 2],
+    ) -> BackdropTextures {
+        crate::profile_function!();
+
+        let create_texture = |label: &str, usage: wgpu::TextureUsages| {
+            device.create_texture(&wgpu::TextureDescriptor {
+                label: Some(label),
+                size: wgpu::Extent3d {
+                    width,
+                    height,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: self.backdrop_format,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING | usage,
+                view_formats: &[],
+            })
+        };
+        let copy = create_texture("egui_backdrop_copy", wgpu::TextureUsages::COPY_DST);
+        let blurred = create_texture(
+            "egui_backdrop_blurred",
+            wgpu::TextureUsages::RENDER_ATTACHMENT,
+        );
+        let copy_view = copy.create_view(&wgpu::TextureViewDescriptor::default());
+        let blurred_view = blurred.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let sampler_options = epaint::textures::TextureOptions::LINEAR;
+        let sampler = self
+            .samplers
+            .entry(sampler_options)
+            .or_insert_with(|| create_sampler(sampler_options, device));
+        let create_bind_group = |label: &str, view: &wgpu::TextureView| {
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some(label),
+                layout: &self.blur_bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(view),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(sampler),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                            buffer: &self.blur_uniform_buffer,
+                            offset: 0,
+                            size: NonZeroU64::new(std::mem::size_of::<BlurUniform>() as _),
+                        }),
+                    },
+                ],
+            })
+        };
+        let copy_bind_group = create_bind_group("egui_backdrop_copy", &copy_view);
+        let blurred_bind_group = create_bind_group("egui_backdrop_blurred", &blurred_view);
+
+        BackdropTextures {
+            size: [width, height],
+            copy,
+            copy_bind_group,
+            blurred_view,
+            blurred_bind_group,
+        }
+    }
+}
+
+/// Begin a render pass into `target`, first clearing it to `clear_color` if it is set.
+fn begin_render_pass<'a>(
+    encoder: &'a mut wgpu::CommandEncoder,
+    target: &RenderTarget<'a>,
+    clear_color: Option<wgpu::Color>,
+) -> wgpu::RenderPass<'a> {
+    encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label: Some("egui_render"),
+        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+            view: target.view,
+            resolve_target: target.resolve_target,
+            ops: wgpu::Operations {
+                load: clear_color.map_or(wgpu::LoadOp::Load, wgpu::LoadOp::Clear),
+                store: wgpu::StoreOp::Store,
+            },
+        })],
+        depth_stencil_attachment: target.depth_view.map(|view| {
+            wgpu::RenderPassDepthStencilAttachment {
+                view,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    // It is very unlikely that the depth buffer is needed after egui finished rendering
+                    // so no need to store it. (this can improve performance on tiling GPUs like mobile chips or Apple Silicon)
+                    store: wgpu::StoreOp::Discard,
+                }),
+                stencil_ops: None,
+            }
+        }),
+        timestamp_writes: None,
+        occlusion_query_set: None,
+    })
+}
+
+fn create_blur_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+    crate::profile_function!();
+    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: Some("egui_blur_bind_group_layout"),
+        entries: &[
+            wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    multisampled: false,
+                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 2,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    has_dynamic_offset: true,
+                    min_binding_size: NonZeroU64::new(std::mem::size_of::<BlurUniform>() as _),
+                    ty: wgpu::BufferBindingType::Uniform,
+                },
+                count: None,
+            },
+        ],
+    })
+}
+
+/// Blurs what's behind a backdrop horizontally, see `fs_blur` in `egui.wgsl`.
+fn create_blur_pipeline(
+    device: &wgpu::Device,
+    module: &wgpu::ShaderModule,
+    layout: &wgpu::PipelineLayout,
+    format: wgpu::TextureFormat,
+) -> wgpu::RenderPipeline {
+    crate::profile_function!();
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("egui_blur_pipeline"),
+        layout: Some(layout),
+        vertex: wgpu::VertexState {
+            entry_point: "vs_fullscreen",
+            module,
+            buffers: &[],
+        },
+        primitive: wgpu::PrimitiveState::default(),
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState::default(),
+        fragment: Some(wgpu::FragmentState {
+            module,
+            entry_point: "fs_blur",
+            targets: &[Some(wgpu::ColorTargetState {
+                format,
+                blend: None,
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+        }),
+        multiview: None,
+    })
+}
+
+fn create_blur_uniform_buffer(device: &wgpu::Device, size: u64) -> wgpu::Buffer {
+    crate::profile_function!();
+    device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("egui_blur_uniform_buffer"),
+        usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        size,
+        mapped_at_creation: false,
+    })
 }
 
 fn create_sampler(
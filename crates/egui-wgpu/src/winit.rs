@@ -149,11 +149,18 @@ impl Painter {
     ) {
         crate::profile_function!();
 
-        let usage = if surface_state.supports_screenshot {
+        let mut usage = if surface_state.supports_screenshot {
             wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_DST
         } else {
             wgpu::TextureUsages::RENDER_ATTACHMENT
         };
+        // For copying what's behind a backdrop, to blur it:
+        let capabilities = surface_state
+            .surface
+            .get_capabilities(&render_state.adapter);
+        if capabilities.usages.contains(wgpu::TextureUsages::COPY_SRC) {
+            usage |= wgpu::TextureUsages::COPY_SRC;
+        }
 
         let width = surface_state.width;
         let height = surface_state.height;
@@ -597,24 +604,20 @@ impl Painter {
 
         {
             let renderer = render_state.renderer.read();
-            let frame_view = if capture {
+            let frame_texture = if capture {
                 Self::update_capture_state(
                     &mut self.screen_capture_state,
                     &output_frame,
                     render_state,
                 );
-                self.screen_capture_state
-                    .as_ref()
-                    .map_or_else(
-                        || &output_frame.texture,
-                        |capture_state| &capture_state.texture,
-                    )
-                    .create_view(&wgpu::TextureViewDescriptor::default())
+                self.screen_capture_state.as_ref().map_or_else(
+                    || &output_frame.texture,
+                    |capture_state| &capture_state.texture,
+                )
             } else {
-                output_frame
-                    .texture
-                    .create_view(&wgpu::TextureViewDescriptor::default())
+                &output_frame.texture
             };
+            let frame_view = frame_texture.create_view(&wgpu::TextureViewDescriptor::default());
 
             let (view, resolve_target) = (self.msaa_samples > 1)
                 .then_some(self.msaa_texture_view.get(&viewport_id))
@@ -623,38 +626,24 @@ impl Painter {
                     (texture_view, Some(&frame_view))
                 });
 
-            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("egui_render"),
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view,
-                    resolve_target,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color {
-                            r: clear_color[0] as f64,
-                            g: clear_color[1] as f64,
-                            b: clear_color[2] as f64,
-                            a: clear_color[3] as f64,
-                        }),
-                        store: wgpu::StoreOp::Store,
-                    },
-                })],
-                depth_stencil_attachment: self.depth_texture_view.get(&viewport_id).map(|view| {
-                    wgpu::RenderPassDepthStencilAttachment {
-                        view,
-                        depth_ops: Some(wgpu::Operations {
-                            load: wgpu::LoadOp::Clear(1.0),
-                            // It is very unlikely that the depth buffer is needed after egui finished rendering
-                            // so no need to store it. (this can improve performance on tiling GPUs like mobile chips or Apple Silicon)
-                            store: wgpu::StoreOp::Discard,
-                        }),
-                        stencil_ops: None,
-                    }
+            let target = renderer::RenderTarget {
+                view,
+                resolve_target,
+                texture: frame_texture,
+                depth_view: self.depth_texture_view.get(&viewport_id),
+                clear_color: Some(wgpu::Color {
+                    r: clear_color[0] as f64,
+                    g: clear_color[1] as f64,
+                    b: clear_color[2] as f64,
+                    a: clear_color[3] as f64,
                 }),
-                timestamp_writes: None,
-                occlusion_query_set: None,
-            });
-
-            renderer.render(&mut render_pass, clipped_primitives, &screen_descriptor);
+            };
+            renderer.render_to_target(
+                &mut encoder,
+                &target,
+                clipped_primitives,
+                &screen_descriptor,
+            );
         }
 
         {
@@ -22,6 +22,9 @@ pub struct Clipboard {
 
     /// Fallback manual clipboard.
     clipboard: String,
+
+    /// Fallback manual primary selection.
+    primary: String,
 }
 
 impl Clipboard {
@@ -44,6 +47,7 @@ impl Clipboard {
             smithay: init_smithay_clipboard(_raw_display_handle),
 
             clipboard: Default::default(),
+            primary: Default::default(),
         }
     }
 
@@ -82,6 +86,29 @@ impl Clipboard {
         Some(self.clipboard.clone())
     }
 
+    /// The image on the clipboard, if any.
+    ///
+    /// Always `None` without the `clipboard-images` feature.
+    #[allow(clippy::unused_self)]
+    pub fn get_image(&mut self) -> Option<egui::ColorImage> {
+        #[cfg(all(feature = "clipboard-images", not(target_os = "android")))]
+        if let Some(clipboard) = &mut self.arboard {
+            return match clipboard.get_image() {
+                Ok(image) => Some(egui::ColorImage::from_rgba_unmultiplied(
+                    [image.width, image.height],
+                    &image.bytes,
+                )),
+                Err(arboard::Error::ContentNotAvailable) => None,
+                Err(err) => {
+                    log::error!("arboard image paste error: {err}");
+                    None
+                }
+            };
+        }
+
+        None
+    }
+
     pub fn set(&mut self, text: String) {
         #[cfg(all(
             any(
@@ -108,6 +135,147 @@ impl Clipboard {
 
         self.clipboard = text;
     }
+
+    /// Put both plain text and HTML on the clipboard,
+    /// for apps that paste rich text to use the HTML.
+    ///
+    /// Only the plain text is kept where HTML isn't supported.
+    pub fn set_html(&mut self, text: String, html: String) {
+        #[cfg(all(
+            any(
+                target_os = "linux",
+                target_os = "dragonfly",
+                target_os = "freebsd",
+                target_os = "netbsd",
+                target_os = "openbsd"
+            ),
+            feature = "smithay-clipboard"
+        ))]
+        if self.smithay.is_some() {
+            // `smithay-clipboard` only supports plain text:
+            self.set(text);
+            return;
+        }
+
+        #[cfg(all(feature = "arboard", not(target_os = "android")))]
+        if let Some(clipboard) = &mut self.arboard {
+            if let Err(err) = clipboard.set_html(html, Some(text)) {
+                log::error!("arboard copy/cut error: {err}");
+            }
+            return;
+        }
+
+        let _ = html;
+        self.set(text);
+    }
+
+    /// The contents of the primary selection, i.e. the most recently selected text.
+    ///
+    /// Always `None` on platforms without a primary selection (anything but Linux and the BSDs).
+    pub fn get_primary(&mut self) -> Option<String> {
+        #[cfg(all(
+            any(
+                target_os = "linux",
+                target_os = "dragonfly",
+                target_os = "freebsd",
+                target_os = "netbsd",
+                target_os = "openbsd"
+            ),
+            feature = "smithay-clipboard"
+        ))]
+        if let Some(clipboard) = &mut self.smithay {
+            return match clipboard.load_primary() {
+                Ok(text) => Some(text),
+                Err(err) => {
+                    log::error!("smithay primary paste error: {err}");
+                    None
+                }
+            };
+        }
+
+        #[cfg(all(
+            any(
+                target_os = "linux",
+                target_os = "dragonfly",
+                target_os = "freebsd",
+                target_os = "netbsd",
+                target_os = "openbsd"
+            ),
+            feature = "arboard"
+        ))]
+        if let Some(clipboard) = &mut self.arboard {
+            use arboard::{GetExtLinux as _, LinuxClipboardKind};
+
+            return match clipboard
+                .get()
+                .clipboard(LinuxClipboardKind::Primary)
+                .text()
+            {
+                Ok(text) => Some(text),
+                Err(err) => {
+                    log::error!("arboard primary paste error: {err}");
+                    None
+                }
+            };
+        }
+
+        if cfg!(any(
+            target_os = "linux",
+            target_os = "dragonfly",
+            target_os = "freebsd",
+            target_os = "netbsd",
+            target_os = "openbsd"
+        )) {
+            Some(self.primary.clone())
+        } else {
+            None
+        }
+    }
+
+    /// Put this text in the primary selection.
+    ///
+    /// Does nothing on platforms without a primary selection.
+    pub fn set_primary(&mut self, text: String) {
+        #[cfg(all(
+            any(
+                target_os = "linux",
+                target_os = "dragonfly",
+                target_os = "freebsd",
+                target_os = "netbsd",
+                target_os = "openbsd"
+            ),
+            feature = "smithay-clipboard"
+        ))]
+        if let Some(clipboard) = &mut self.smithay {
+            clipboard.store_primary(text);
+            return;
+        }
+
+        #[cfg(all(
+            any(
+                target_os = "linux",
+                target_os = "dragonfly",
+                target_os = "freebsd",
+                target_os = "netbsd",
+                target_os = "openbsd"
+            ),
+            feature = "arboard"
+        ))]
+        if let Some(clipboard) = &mut self.arboard {
+            use arboard::{LinuxClipboardKind, SetExtLinux as _};
+
+            if let Err(err) = clipboard
+                .set()
+                .clipboard(LinuxClipboardKind::Primary)
+                .text(text)
+            {
+                log::error!("arboard primary copy error: {err}");
+            }
+            return;
+        }
+
+        self.primary = text;
+    }
 }
 
 #[cfg(all(feature = "arboard", not(target_os = "android")))]
@@ -102,6 +102,12 @@ pub struct State {
 
     allow_ime: bool,
     ime_rect_px: Option<egui::Rect>,
+
+    /// Does the focused widget want pasted images? See [`egui::PlatformOutput::accepts_pasted_images`].
+    accepts_pasted_images: bool,
+
+    /// Is there a mutable text under the cursor? See [`egui::PlatformOutput::mutable_text_under_cursor`].
+    mutable_text_under_cursor: bool,
 }
 
 impl State {
@@ -143,6 +149,9 @@ impl State {
 
             allow_ime: false,
             ime_rect_px: None,
+
+            accepts_pasted_images: false,
+            mutable_text_under_cursor: false,
         };
 
         slf.egui_input
@@ -343,14 +352,19 @@ impl State {
                 // between Commits.
                 match ime {
                     winit::event::Ime::Enabled => {}
-                    winit::event::Ime::Preedit(_, None) => {
-                        self.ime_event_enable();
-                    }
-                    winit::event::Ime::Preedit(text, Some(_cursor)) => {
+                    winit::event::Ime::Preedit(text, cursor) => {
                         self.ime_event_enable();
+                        // winit gives the cursor in bytes, egui wants it in chars:
+                        let char_index = |byte_index: usize| {
+                            text.get(..byte_index).map_or(0, |s| s.chars().count())
+                        };
+                        let cursor = cursor.map(|(start, end)| char_index(start)..char_index(end));
                         self.egui_input
                             .events
-                            .push(egui::Event::Ime(egui::ImeEvent::Preedit(text.clone())));
+                            .push(egui::Event::Ime(egui::ImeEvent::Preedit {
+                                text: text.clone(),
+                                cursor,
+                            }));
                     }
                     winit::event::Ime::Commit(text) => {
                         self.egui_input
@@ -542,6 +556,22 @@ impl State {
                     modifiers: self.egui_input.modifiers,
                 });
 
+                if pressed
+                    && button == egui::PointerButton::Middle
+                    && self.mutable_text_under_cursor
+                {
+                    // Middle-click on a `TextEdit` pastes the primary selection on Linux.
+                    // Only read it then, since reading it means a round-trip to the selection owner.
+                    if let Some(contents) = self.clipboard.get_primary() {
+                        let contents = contents.replace("\r\n", "\n");
+                        if !contents.is_empty() {
+                            self.egui_input
+                                .events
+                                .push(egui::Event::PasteFromPrimary(contents));
+                        }
+                    }
+                }
+
                 if self.simulate_touch_screen {
                     if pressed {
                         self.any_pointer_button_down = true;
@@ -753,7 +783,14 @@ impl State {
                     self.egui_input.events.push(egui::Event::Copy);
                     return;
                 } else if is_paste_command(self.egui_input.modifiers, active_key) {
-                    if let Some(contents) = self.clipboard.get() {
+                    let image = if self.accepts_pasted_images {
+                        self.clipboard.get_image()
+                    } else {
+                        None
+                    };
+                    if let Some(image) = image {
+                        self.egui_input.events.push(egui::Event::PastedImage(image));
+                    } else if let Some(contents) = self.clipboard.get() {
                         let contents = contents.replace("\r\n", "\n");
                         if !contents.is_empty() {
                             self.egui_input.events.push(egui::Event::Paste(contents));
@@ -811,8 +848,11 @@ impl State {
             cursor_icon,
             open_url,
             copied_text,
-            events: _,                    // handled elsewhere
-            mutable_text_under_cursor: _, // only used in eframe web
+            copied_text_primary,
+            copied_rich_text,
+            events: _, // handled elsewhere
+            mutable_text_under_cursor,
+            accepts_pasted_images,
             ime,
             #[cfg(feature = "accesskit")]
             accesskit_update,
@@ -824,10 +864,19 @@ impl State {
             open_url_in_browser(&open_url.url);
         }
 
-        if !copied_text.is_empty() {
+        if let Some((text, html)) = copied_rich_text {
+            self.clipboard.set_html(text, html);
+        } else if !copied_text.is_empty() {
             self.clipboard.set(copied_text);
         }
 
+        if !copied_text_primary.is_empty() {
+            self.clipboard.set_primary(copied_text_primary);
+        }
+
+        self.accepts_pasted_images = accepts_pasted_images;
+        self.mutable_text_under_cursor = mutable_text_under_cursor;
+
         let allow_ime = ime.is_some();
         if self.allow_ime != allow_ime {
             self.allow_ime = allow_ime;
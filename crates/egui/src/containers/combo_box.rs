@@ -182,13 +182,19 @@ impl ComboBox {
         ui: &mut Ui,
         menu_contents: impl FnOnce(&mut Ui) -> R,
     ) -> InnerResponse<Option<R>> {
-        self.show_ui_dyn(ui, Box::new(menu_contents))
+        self.show_popup_dyn(
+            ui,
+            Box::new(|ui: &mut Ui, max_height: f32| {
+                menu_scroll_area(ui, max_height, menu_contents)
+            }),
+        )
     }
 
-    fn show_ui_dyn<'c, R>(
+    /// `popup_contents` is given the ui of the popup and the maximum height of the menu.
+    fn show_popup_dyn<'c, R>(
         self,
         ui: &mut Ui,
-        menu_contents: Box<dyn FnOnce(&mut Ui) -> R + 'c>,
+        popup_contents: Box<dyn FnOnce(&mut Ui, f32) -> R + 'c>,
     ) -> InnerResponse<Option<R>> {
         let Self {
             id_source,
@@ -207,7 +213,7 @@ impl ComboBox {
                 ui,
                 button_id,
                 selected_text,
-                menu_contents,
+                popup_contents,
                 icon,
                 wrap_mode,
                 (width, height),
@@ -271,6 +277,144 @@ impl ComboBox {
         response
     }
 
+    /// Like [`Self::show_index`], but with a box at the top of the menu for filtering the items by typing.
+    ///
+    /// An item matches if the typed characters appear in its text in the same order, ignoring case,
+    /// so `"ny"` matches `"New York"`.
+    /// The keyboard focus stays in the filter box, where the up and down arrows move the highlighted item,
+    /// and enter selects it.
+    ///
+    /// ```
+    /// # egui::__run_test_ui(|ui| {
+    /// let cities = ["Berlin", "London", "New York", "Paris", "Stockholm", "Tokyo"];
+    /// let mut selected = 2;
+    /// egui::ComboBox::from_label("City").show_index_filtered(
+    ///     ui,
+    ///     &mut selected,
+    ///     cities.len(),
+    ///     |i| cities[i]
+    /// );
+    /// # });
+    /// ```
+    pub fn show_index_filtered<Text: Into<WidgetText>>(
+        self,
+        ui: &mut Ui,
+        selected: &mut usize,
+        len: usize,
+        get: impl Fn(usize) -> Text,
+    ) -> Response {
+        let slf = self.selected_text(get(*selected));
+
+        let mut changed = false;
+        let mut reopen_popup = None;
+
+        let mut response = slf
+            .show_popup_dyn(
+                ui,
+                Box::new(|ui: &mut Ui, max_height: f32| {
+                    let popup_id = ui.layer_id().id;
+                    let filter_id = popup_id.with("filter");
+                    let frame_nr = ui.ctx().frame_nr();
+
+                    // Start over each time the popup is opened:
+                    let mut state = ui
+                        .data(|d| d.get_temp::<FilterState>(filter_id))
+                        .filter(|state| frame_nr <= state.frame_nr + 1)
+                        .unwrap_or_else(|| FilterState {
+                            highlighted: Some(*selected),
+                            ..Default::default()
+                        });
+                    state.frame_nr = frame_nr;
+
+                    // Take the keys before the filter box uses them to move its cursor:
+                    let (up, down, enter) = if ui.memory(|m| m.has_focus(filter_id)) {
+                        ui.input_mut(|i| {
+                            (
+                                i.consume_key(Modifiers::NONE, Key::ArrowUp),
+                                i.consume_key(Modifiers::NONE, Key::ArrowDown),
+                                i.consume_key(Modifiers::NONE, Key::Enter),
+                            )
+                        })
+                    } else {
+                        (false, false, false)
+                    };
+
+                    let filter_response = ui.add(
+                        TextEdit::singleline(&mut state.filter)
+                            .id(filter_id)
+                            .hint_text("Filter")
+                            .desired_width(f32::INFINITY),
+                    );
+                    if !filter_response.has_focus() {
+                        filter_response.request_focus();
+                    }
+                    if filter_response.clicked() {
+                        // Clicking anywhere but the combo box closes the popup, but not here.
+                        reopen_popup = Some(popup_id);
+                    }
+
+                    let matches: Vec<(usize, WidgetText)> = (0..len)
+                        .map(|i| (i, get(i).into()))
+                        .filter(|(_, text)| matches_filter(text.text(), &state.filter))
+                        .collect();
+
+                    let mut position = matches
+                        .iter()
+                        .position(|(i, _)| Some(*i) == state.highlighted)
+                        .unwrap_or(0);
+                    if up {
+                        position = position.saturating_sub(1);
+                    }
+                    if down {
+                        position = (position + 1).min(matches.len().saturating_sub(1));
+                    }
+                    let highlighted = matches.get(position).map(|(i, _)| *i);
+                    let scroll_to_highlighted = highlighted != state.highlighted || up || down;
+                    if scroll_to_highlighted {
+                        // The highlight shows up next frame.
+                        ui.ctx().request_repaint();
+                    }
+                    state.highlighted = highlighted;
+
+                    if let Some(highlighted) = highlighted.filter(|_| enter) {
+                        *selected = highlighted;
+                        changed = true;
+                        ui.memory_mut(|m| m.close_popup());
+                    }
+
+                    ui.data_mut(|d| d.insert_temp(filter_id, state));
+
+                    menu_scroll_area(ui, max_height, |ui| {
+                        if matches.is_empty() {
+                            ui.weak("No matches");
+                        }
+                        for (i, text) in matches {
+                            let mut response = ui.selectable_label(i == *selected, text);
+                            if Some(i) == highlighted {
+                                response = response.highlight();
+                                if scroll_to_highlighted {
+                                    response.scroll_to_me(None);
+                                }
+                            }
+                            if response.clicked() {
+                                *selected = i;
+                                changed = true;
+                            }
+                        }
+                    });
+                }),
+            )
+            .response;
+
+        if let Some(popup_id) = reopen_popup {
+            ui.memory_mut(|m| m.open_popup(popup_id));
+        }
+        if changed {
+            response.mark_changed();
+        }
+        response
+    }
+
     /// Check if the [`ComboBox`] with the given id has its popup menu currently opened.
     pub fn is_open(ctx: &Context, id: Id) -> bool {
         ctx.memory(|m| m.is_popup_open(Self::widget_to_popup_id(id)))
@@ -287,7 +431,7 @@ fn combo_box_dyn<'c, R>(
     ui: &mut Ui,
     button_id: Id,
     selected_text: WidgetText,
-    menu_contents: Box<dyn FnOnce(&mut Ui) -> R + 'c>,
+    popup_contents: Box<dyn FnOnce(&mut Ui, f32) -> R + 'c>,
     icon: Option<IconPainter>,
     wrap_mode: Option<TextWrapMode>,
     (width, height): (Option<f32>, Option<f32>),
@@ -380,20 +524,7 @@ fn combo_box_dyn<'c, R>(
         popup_id,
         &button_response,
         above_or_below,
-        |ui| {
-            ScrollArea::vertical()
-                .max_height(height)
-                .show(ui, |ui| {
-                    // Often the button is very narrow, which means this popup
-                    // is also very narrow. Having wrapping on would therefore
-                    // result in labels that wrap very early.
-                    // Instead, we turn it off by default so that the labels
-                    // expand the width of the menu.
-                    ui.style_mut().wrap_mode = Some(TextWrapMode::Extend);
-                    menu_contents(ui)
-                })
-                .inner
-        },
+        |ui| popup_contents(ui, height),
     );
 
     InnerResponse {
@@ -402,6 +533,46 @@ fn combo_box_dyn<'c, R>(
     }
 }
 
+fn menu_scroll_area<R>(
+    ui: &mut Ui,
+    max_height: f32,
+    menu_contents: impl FnOnce(&mut Ui) -> R,
+) -> R {
+    ScrollArea::vertical()
+        .max_height(max_height)
+        .show(ui, |ui| {
+            // Often the button is very narrow, which means this popup
+            // is also very narrow. Having wrapping on would therefore
+            // result in labels that wrap very early.
+            // Instead, we turn it off by default so that the labels
+            // expand the width of the menu.
+            ui.style_mut().wrap_mode = Some(TextWrapMode::Extend);
+            menu_contents(ui)
+        })
+        .inner
+}
+
+/// The state of the filter box of [`ComboBox::show_index_filtered`].
+#[derive(Clone, Default)]
+struct FilterState {
+    filter: String,
+
+    /// The item that enter selects.
+    highlighted: Option<usize>,
+
+    /// The last frame the popup was shown.
+    frame_nr: u64,
+}
+
+/// Do the chars of `filter` appear in `text` in the same order, ignoring case?
+fn matches_filter(text: &str, filter: &str) -> bool {
+    let mut text = text.chars().flat_map(char::to_lowercase);
+    filter
+        .chars()
+        .flat_map(char::to_lowercase)
+        .all(|f| text.any(|c| c == f))
+}
+
 fn button_frame(
     ui: &mut Ui,
     id: Id,
@@ -479,3 +650,17 @@ fn paint_default_icon(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches_filter() {
+        assert!(matches_filter("New York", ""));
+        assert!(matches_filter("New York", "ny"));
+        assert!(matches_filter("New York", "NEW YORK"));
+        assert!(!matches_filter("New York", "yn"));
+        assert!(!matches_filter("New York", "newyorks"));
+    }
+}
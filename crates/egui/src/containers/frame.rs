@@ -68,6 +68,10 @@ pub struct Frame {
     pub fill: Color32,
 
     pub stroke: Stroke,
+
+    /// If larger than zero, what's behind the frame is blurred this much before [`Self::fill`]
+    /// is painted on top, see [`Self::fill_backdrop`].
+    pub backdrop_blur: f32,
 }
 
 impl Frame {
@@ -164,6 +168,29 @@ impl Frame {
         self
     }
 
+    /// Blur what's behind the frame, and tint it with this fill, for a frosted-glass look.
+    ///
+    /// `blur_radius` is the standard deviation of the blur, in points.
+    /// The tint becomes the [`Self::fill`], and should be translucent.
+    ///
+    /// Backends that can't blur paint a mostly opaque fill instead, see [`epaint::BackdropShape`].
+    ///
+    /// ```
+    /// # egui::__run_test_ui(|ui| {
+    /// egui::Frame::popup(ui.style())
+    ///     .fill_backdrop(12.0, egui::Color32::from_black_alpha(96))
+    ///     .show(ui, |ui| {
+    ///         ui.label("Frosted glass");
+    ///     });
+    /// # });
+    /// ```
+    #[inline]
+    pub fn fill_backdrop(mut self, blur_radius: f32, tint: Color32) -> Self {
+        self.backdrop_blur = blur_radius;
+        self.fill = tint;
+        self
+    }
+
     #[inline]
     pub fn stroke(mut self, stroke: impl Into<Stroke>) -> Self {
         self.stroke = stroke.into();
@@ -241,7 +268,8 @@ impl Frame {
     ///
     /// See docs for [`Frame`] for an example.
     pub fn begin(self, ui: &mut Ui) -> Prepared {
-        let where_to_put_background = ui.painter().add(Shape::Noop);
+        // Behind everything else in the layer, even shapes with a negative z (see `Painter::with_z`):
+        let where_to_put_background = ui.painter().with_z(i32::MIN).add(Shape::Noop);
         let outer_rect_bounds = ui.available_rect_before_wrap();
 
         let mut inner_rect = outer_rect_bounds - self.outer_margin - self.inner_margin;
@@ -289,9 +317,17 @@ impl Frame {
             shadow,
             fill,
             stroke,
+            backdrop_blur,
         } = *self;
 
-        let frame_shape = Shape::Rect(epaint::RectShape::new(outer_rect, rounding, fill, stroke));
+        let frame_shape = if 0.0 < backdrop_blur {
+            Shape::Vec(vec![
+                Shape::backdrop(outer_rect, rounding, backdrop_blur, fill),
+                Shape::rect_stroke(outer_rect, rounding, stroke),
+            ])
+        } else {
+            Shape::Rect(epaint::RectShape::new(outer_rect, rounding, fill, stroke))
+        };
 
         if shadow == Default::default() {
             frame_shape
@@ -322,8 +322,11 @@ impl SidePanel {
             // In the meantime: nudge the line so its inside the panel, so it won't be covered by neighboring panel
             // (hence the shrink).
             let resize_x = side.opposite().side_x(rect.shrink(1.0));
-            let resize_x = ui.painter().round_to_pixel(resize_x);
-            ui.painter().vline(resize_x, rect.y_range(), stroke);
+            ui.painter().vline(
+                resize_x,
+                rect.y_range(),
+                epaint::PathStroke::from(stroke).crisp(),
+            );
         }
 
         inner_response
@@ -789,8 +792,11 @@ impl TopBottomPanel {
             // In the meantime: nudge the line so its inside the panel, so it won't be covered by neighboring panel
             // (hence the shrink).
             let resize_y = side.opposite().side_y(rect.shrink(1.0));
-            let resize_y = ui.painter().round_to_pixel(resize_y);
-            ui.painter().hline(rect.x_range(), resize_y, stroke);
+            ui.painter().hline(
+                rect.x_range(),
+                resize_y,
+                epaint::PathStroke::from(stroke).crisp(),
+            );
         }
 
         inner_response
@@ -35,6 +35,7 @@ pub struct Window<'open> {
     open: Option<&'open mut bool>,
     area: Area,
     frame: Option<Frame>,
+    backdrop: Option<(f32, Color32)>,
     resize: Resize,
     scroll: ScrollArea,
     collapsible: bool,
@@ -55,6 +56,7 @@ impl<'open> Window<'open> {
             open: None,
             area,
             frame: None,
+            backdrop: None,
             resize: Resize::default()
                 .with_stroke(false)
                 .min_size([96.0, 32.0])
@@ -156,6 +158,15 @@ impl<'open> Window<'open> {
         self
     }
 
+    /// Blur what's behind the window, and tint it with a translucent fill, for a frosted-glass look.
+    ///
+    /// See [`Frame::fill_backdrop`].
+    #[inline]
+    pub fn fill_backdrop(mut self, blur_radius: f32, tint: Color32) -> Self {
+        self.backdrop = Some((blur_radius, tint));
+        self
+    }
+
     /// Set minimum width of the window.
     #[inline]
     pub fn min_width(mut self, min_width: f32) -> Self {
@@ -419,6 +430,7 @@ impl<'open> Window<'open> {
             open,
             area,
             frame,
+            backdrop,
             resize,
             scroll,
             collapsible,
@@ -430,6 +442,9 @@ impl<'open> Window<'open> {
         let header_color =
             frame.map_or_else(|| ctx.style().visuals.widgets.open.weak_bg_fill, |f| f.fill);
         let mut window_frame = frame.unwrap_or_else(|| Frame::window(&ctx.style()));
+        if let Some((blur_radius, tint)) = backdrop {
+            window_frame = window_frame.fill_backdrop(blur_radius, tint);
+        }
         // Keep the original inner margin for later use
         let window_margin = window_frame.inner_margin;
         let border_padding = window_frame.stroke.width / 2.0;
@@ -1140,7 +1155,8 @@ impl TitleBar {
             // Workaround: To prevent border infringement,
             // the 0.1 value should ideally be calculated using TessellationOptions::feathering_size_in_pixels
             let x_range = outer_rect.x_range().shrink(0.1);
-            ui.painter().hline(x_range, y, stroke);
+            ui.painter()
+                .hline(x_range, y, epaint::PathStroke::from(stroke).crisp());
         }
 
         // Don't cover the close- and collapse buttons:
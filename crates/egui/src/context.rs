@@ -367,6 +367,10 @@ struct ContextImpl {
     /// See <https://github.com/emilk/egui/issues/3664>.
     tex_manager: WrappedTextureManager,
 
+    /// The texture of the SDF glyphs (see [`epaint::text::FontTweak::sdf`]),
+    /// allocated the first time any are used.
+    sdf_font_texture_id: Option<TextureId>,
+
     /// Set during the frame, becomes active at the start of the next frame.
     new_zoom_factor: Option<f32>,
 
@@ -537,6 +541,10 @@ impl ContextImpl {
 
         let mut is_new = false;
 
+        // Share the font atlas with the other `pixels_per_point` (if any),
+        // so that we can reuse the glyphs already rasterized for the same physical size:
+        let existing_fonts = self.fonts.values().next().cloned();
+
         let fonts = self
             .fonts
             .entry(pixels_per_point.into())
@@ -546,17 +554,22 @@ impl ContextImpl {
 
                 is_new = true;
                 crate::profile_scope!("Fonts::new");
-                Fonts::new(
-                    pixels_per_point,
-                    max_texture_side,
-                    self.font_definitions.clone(),
-                )
+                if let Some(existing_fonts) = existing_fonts {
+                    existing_fonts.new_sharing_atlas(pixels_per_point)
+                } else {
+                    Fonts::new(
+                        pixels_per_point,
+                        max_texture_side,
+                        self.font_definitions.clone(),
+                    )
+                }
             });
 
         {
             crate::profile_scope!("Fonts::begin_frame");
             fonts.begin_frame(pixels_per_point, max_texture_side);
         }
+        fonts.set_text_gamma(self.memory.options.text_gamma);
 
         if is_new && self.memory.options.preload_font_glyphs {
             crate::profile_scope!("preload_font_glyphs");
@@ -1114,6 +1127,7 @@ impl Context {
             is_pointer_button_down_on: false,
             interact_pointer_pos: None,
             changed: false,
+            clicked_link: None,
         };
 
         self.write(|ctx| {
@@ -1304,7 +1318,20 @@ impl Context {
     /// ctx.output_mut(|o| o.copied_text = "Copy this".to_owned());
     /// ```
     pub fn copy_text(&self, text: String) {
-        self.output_mut(|o| o.copied_text = text);
+        self.output_mut(|o| {
+            o.copied_text = text;
+            o.copied_rich_text = None;
+        });
+    }
+
+    /// Copy the given text to the system clipboard, as both plain text and HTML.
+    ///
+    /// See [`crate::PlatformOutput::copied_rich_text`].
+    pub fn copy_rich_text(&self, text: String, html: String) {
+        self.output_mut(|o| {
+            o.copied_text = text.clone();
+            o.copied_rich_text = Some((text, html));
+        });
     }
 
     /// Format the given shortcut in a human-readable way (e.g. `Ctrl+Shift+X`).
@@ -1947,6 +1974,17 @@ impl ContextImpl {
                 tex_mngr.set(TextureId::default(), font_image_delta);
             }
 
+            if let Some(sdf_font_image_delta) = fonts.sdf_font_image_delta() {
+                let sdf_font_texture_id = *self.sdf_font_texture_id.get_or_insert_with(|| {
+                    tex_mngr.alloc(
+                        "egui_sdf_font_texture".into(),
+                        epaint::ColorImage::new([0, 0], Color32::TRANSPARENT).into(),
+                        sdf_font_image_delta.options,
+                    )
+                });
+                tex_mngr.set(sdf_font_texture_id, sdf_font_image_delta);
+            }
+
             if 1 < self.fonts.len() {
                 // We have multiple different `pixels_per_point`,
                 // e.g. because we have many viewports spread across
@@ -1963,6 +2001,10 @@ impl ContextImpl {
                 crate::profile_scope!("full_font_atlas_update");
                 let full_delta = ImageDelta::full(fonts.image(), TextureAtlas::texture_options());
                 tex_mngr.set(TextureId::default(), full_delta);
+
+                if let Some(sdf_font_texture_id) = self.sdf_font_texture_id {
+                    tex_mngr.set(sdf_font_texture_id, fonts.sdf_font_image_full_delta());
+                }
             }
         }
 
@@ -2158,12 +2200,15 @@ impl Context {
 
         self.write(|ctx| {
             let tessellation_options = ctx.memory.options.tessellation_options;
-            let texture_atlas = ctx
+            let fonts = ctx
                 .fonts
                 .get(&pixels_per_point.into())
                 .expect("tessellate called with a different pixels_per_point than the font atlas was created with. \
-                         You should use egui::FullOutput::pixels_per_point when tessellating.")
-                .texture_atlas();
+                         You should use egui::FullOutput::pixels_per_point when tessellating.");
+            let texture_atlas = fonts.texture_atlas();
+            let sdf_font_texture = ctx
+                .sdf_font_texture_id
+                .map(|texture_id| (texture_id, fonts.sdf_font_image_size()));
             let (font_tex_size, prepared_discs) = {
                 let atlas = texture_atlas.lock();
                 (atlas.size(), atlas.prepared_discs())
@@ -2172,19 +2217,54 @@ impl Context {
             let paint_stats = PaintStats::from_shapes(&shapes);
             let clipped_primitives = {
                 crate::profile_scope!("tessellator::tessellate_shapes");
-                tessellator::Tessellator::new(
+                let mut tessellator = tessellator::Tessellator::new(
                     pixels_per_point,
                     tessellation_options,
                     font_tex_size,
                     prepared_discs,
-                )
-                .tessellate_shapes(shapes)
+                );
+                if let Some((texture_id, size)) = sdf_font_texture {
+                    tessellator = tessellator.with_sdf_font_texture(texture_id, size);
+                }
+                tessellator.tessellate_shapes(shapes)
             };
             ctx.paint_stats = paint_stats.with_clipped_primitives(&clipped_primitives);
             clipped_primitives
         })
     }
 
+    /// Tessellate a single [`Shape`] into a [`Mesh`] for the current `pixels_per_point`,
+    /// e.g. to paint it many times with an [`epaint::InstancedMeshShape`].
+    ///
+    /// See also [`Painter::cached_mesh`].
+    pub fn tessellate_shape(&self, shape: Shape) -> Mesh {
+        crate::profile_function!();
+
+        self.write(|ctx| {
+            let pixels_per_point = ctx.pixels_per_point();
+            let tessellation_options = ctx.memory.options.tessellation_options;
+            let fonts = ctx
+                .fonts
+                .get(&pixels_per_point.into())
+                .expect("No fonts available until first call to Context::run()");
+            let (font_tex_size, prepared_discs) = {
+                let atlas = fonts.texture_atlas();
+                let atlas = atlas.lock();
+                (atlas.size(), atlas.prepared_discs())
+            };
+
+            let mut mesh = Mesh::default();
+            tessellator::Tessellator::new(
+                pixels_per_point,
+                tessellation_options,
+                font_tex_size,
+                prepared_discs,
+            )
+            .tessellate_shape(shape, &mut mesh);
+            mesh
+        })
+    }
+
     // ---------------------------------------------------------------------
 
     /// Position and size of the egui area.
@@ -2580,8 +2660,14 @@ impl Context {
         CollapsingHeader::new("🔠 Font texture")
             .default_open(false)
             .show(ui, |ui| {
-                let font_image_size = self.fonts(|f| f.font_image_size());
-                crate::introspection::font_texture_ui(ui, font_image_size);
+                let (font_image_size, occupancy, glyphs) = self.fonts(|f| {
+                    (
+                        f.font_image_size(),
+                        f.font_atlas_occupancy(),
+                        f.atlas_glyphs(),
+                    )
+                });
+                crate::introspection::font_texture_ui(ui, font_image_size, occupancy, &glyphs);
             });
 
         CollapsingHeader::new("Label text selection state")
@@ -2908,23 +2994,36 @@ impl Context {
 
     /// Release all memory and textures related to the given image URI.
     ///
+    /// This includes the frames of an animated image (see [`load::AnimatedImage`]).
+    ///
     /// If you attempt to load the image again, it will be reloaded from scratch.
     pub fn forget_image(&self, uri: &str) {
         use load::BytesLoader as _;
 
         crate::profile_function!();
 
+        // The frames of an animated image are loaded as images and textures of their own:
+        let mut image_uris = vec![uri.to_owned()];
+        if let Some(animation) = load::AnimatedImage::remove(self, uri) {
+            image_uris.extend(
+                (0..animation.frame_delays.len())
+                    .map(|frame_index| load::AnimatedImage::frame_uri(uri, frame_index)),
+            );
+        }
+
         let loaders = self.loaders();
 
         loaders.include.forget(uri);
         for loader in loaders.bytes.lock().iter() {
             loader.forget(uri);
         }
-        for loader in loaders.image.lock().iter() {
-            loader.forget(uri);
-        }
-        for loader in loaders.texture.lock().iter() {
-            loader.forget(uri);
+        for image_uri in &image_uris {
+            for loader in loaders.image.lock().iter() {
+                loader.forget(image_uri);
+            }
+            for loader in loaders.texture.lock().iter() {
+                loader.forget(image_uri);
+            }
         }
     }
 
@@ -354,6 +354,19 @@ pub enum Event {
     /// The integration detected a "paste" event (e.g. Cmd+V).
     Paste(String),
 
+    /// The contents of the primary selection, sent by the integration
+    /// together with a [`PointerButton::Middle`] press
+    /// over a mutable [`crate::TextEdit`], see [`crate::PlatformOutput::mutable_text_under_cursor`].
+    ///
+    /// Only sent on Linux and other platforms with a primary selection.
+    PasteFromPrimary(String),
+
+    /// The integration detected a "paste" event (e.g. Cmd+V) while the clipboard contained an image.
+    ///
+    /// Only sent when the focused widget asked for it with [`crate::PlatformOutput::accepts_pasted_images`],
+    /// instead of [`Self::Paste`].
+    PastedImage(ColorImage),
+
     /// Text input, e.g. via keyboard.
     ///
     /// When the user presses enter/return, do not send a [`Text`](Event::Text) (just [`Key::Enter`]).
@@ -504,7 +517,18 @@ pub enum ImeEvent {
     Enabled,
 
     /// A new IME candidate is being suggested.
-    Preedit(String),
+    ///
+    /// It is shown at the cursor, but not added to the text until it is committed.
+    /// An empty `text` means the candidate was cleared.
+    Preedit {
+        /// The text being composed.
+        text: String,
+
+        /// Where the IME cursor is within `text`, as a char range.
+        ///
+        /// `None` means the cursor should be hidden.
+        cursor: Option<std::ops::Range<usize>>,
+    },
 
     /// IME composition ended with this final result.
     Commit(String),
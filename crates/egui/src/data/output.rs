@@ -106,13 +106,33 @@ pub struct PlatformOutput {
     /// ```
     pub copied_text: String,
 
+    /// If set, put this text in the primary selection. Ignore if empty.
+    ///
+    /// Set whenever the user selects text on platforms with a primary selection (Linux),
+    /// so that it can later be pasted with a middle-click, see [`crate::Event::PasteFromPrimary`].
+    pub copied_text_primary: String,
+
+    /// If set, put this on the system clipboard as both plain text (the first)
+    /// and HTML (the second), e.g. to keep the colors of copied code.
+    ///
+    /// Only set with [`crate::style::Interaction::copy_rich_text`],
+    /// in which case [`Self::copied_text`] is set to the same plain text,
+    /// for integrations that only support plain text.
+    pub copied_rich_text: Option<(String, String)>,
+
     /// Events that may be useful to e.g. a screen reader.
     pub events: Vec<OutputEvent>,
 
     /// Is there a mutable [`TextEdit`](crate::TextEdit) under the cursor?
-    /// Use by `eframe` web to show/hide mobile keyboard and IME agent.
+    /// Use by `eframe` web to show/hide mobile keyboard and IME agent,
+    /// and by `egui-winit` to only read the primary selection for a middle-click on a [`TextEdit`](crate::TextEdit).
     pub mutable_text_under_cursor: bool,
 
+    /// Does the focused widget want pasted images, as [`crate::Event::PastedImage`]?
+    ///
+    /// Set by a [`TextEdit`](crate::TextEdit) with [`crate::TextEdit::accept_pasted_images`].
+    pub accepts_pasted_images: bool,
+
     /// This is set if, and only if, the user is currently editing text.
     ///
     /// Useful for IME.
@@ -150,8 +170,11 @@ impl PlatformOutput {
             cursor_icon,
             open_url,
             copied_text,
+            copied_text_primary,
+            copied_rich_text,
             mut events,
             mutable_text_under_cursor,
+            accepts_pasted_images,
             ime,
             #[cfg(feature = "accesskit")]
             accesskit_update,
@@ -164,8 +187,15 @@ impl PlatformOutput {
         if !copied_text.is_empty() {
             self.copied_text = copied_text;
         }
+        if !copied_text_primary.is_empty() {
+            self.copied_text_primary = copied_text_primary;
+        }
+        if copied_rich_text.is_some() {
+            self.copied_rich_text = copied_rich_text;
+        }
         self.events.append(&mut events);
         self.mutable_text_under_cursor = mutable_text_under_cursor;
+        self.accepts_pasted_images = accepts_pasted_images;
         self.ime = ime.or(self.ime);
 
         #[cfg(feature = "accesskit")]
@@ -833,17 +833,7 @@ impl PointerState {
                         let clicked = self.could_any_button_be_click();
 
                         let click = if clicked {
-                            let double_click =
-                                (time - self.last_click_time) < MAX_DOUBLE_CLICK_DELAY;
-                            let triple_click =
-                                (time - self.last_last_click_time) < (MAX_DOUBLE_CLICK_DELAY * 2.0);
-                            let count = if triple_click {
-                                3
-                            } else if double_click {
-                                2
-                            } else {
-                                1
-                            };
+                            let count = self.click_count_at(time);
 
                             self.last_last_click_time = self.last_click_time;
                             self.last_click_time = time;
@@ -945,6 +935,31 @@ impl PointerState {
         self.press_start_time
     }
 
+    /// How many clicks the button that is held down would make if it was released now:
+    /// 2 for the second press of a double-click, 3 for the third press of a triple-click,
+    /// and otherwise 1.
+    ///
+    /// Unlike [`Self::button_double_clicked`], this is known already when the button is pressed,
+    /// e.g. for selecting text word-by-word when dragging after a double-click.
+    /// `None` if no mouse button is down.
+    pub fn press_click_count(&self) -> Option<u32> {
+        self.press_start_time
+            .map(|press_start_time| self.click_count_at(press_start_time))
+    }
+
+    /// 1, 2 (double-click) or 3 (triple-click), for a click at the given time.
+    fn click_count_at(&self, time: f64) -> u32 {
+        let double_click = (time - self.last_click_time) < MAX_DOUBLE_CLICK_DELAY;
+        let triple_click = (time - self.last_last_click_time) < (MAX_DOUBLE_CLICK_DELAY * 2.0);
+        if triple_click {
+            3
+        } else if double_click {
+            2
+        } else {
+            1
+        }
+    }
+
     /// Latest reported pointer position.
     /// When tapping a touch screen, this will be `None`.
     #[inline(always)]
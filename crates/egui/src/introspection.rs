@@ -23,7 +23,12 @@ pub fn font_id_ui(ui: &mut Ui, font_id: &mut FontId) {
 }
 
 // Show font texture in demo Ui
-pub(crate) fn font_texture_ui(ui: &mut Ui, [width, height]: [usize; 2]) -> Response {
+pub(crate) fn font_texture_ui(
+    ui: &mut Ui,
+    [width, height]: [usize; 2],
+    occupancy: epaint::AtlasOccupancy,
+    glyphs: &[epaint::text::AtlasGlyph],
+) -> Response {
     ui.vertical(|ui| {
         let color = if ui.visuals().dark_mode {
             Color32::WHITE
@@ -32,6 +37,12 @@ pub(crate) fn font_texture_ui(ui: &mut Ui, [width, height]: [usize; 2]) -> Respo
         };
 
         ui.label(format!("Texture size: {width} x {height} (hover to zoom)"));
+        ui.label(format!(
+            "{} rows, {} glyphs, {:.1}% of the texels in use",
+            occupancy.num_rows,
+            glyphs.len(),
+            100.0 * occupancy.used_texels as f32 / (width * height).at_least(1) as f32
+        ));
         if width <= 1 || height <= 1 {
             return;
         }
@@ -46,6 +57,24 @@ pub(crate) fn font_texture_ui(ui: &mut Ui, [width, height]: [usize; 2]) -> Respo
 
         let (tex_w, tex_h) = (width as f32, height as f32);
 
+        // Outline the glyphs:
+        let glyph_rect = |glyph: &epaint::text::AtlasGlyph,
+                          texels_to_screen: emath::RectTransform| {
+            let [min_x, min_y] = glyph.min.map(f32::from);
+            let [max_x, max_y] = glyph.max.map(f32::from);
+            texels_to_screen
+                .transform_rect(Rect::from_min_max(pos2(min_x, min_y), pos2(max_x, max_y)))
+        };
+        let texels_to_screen = emath::RectTransform::from_to(
+            Rect::from_min_size(Pos2::ZERO, vec2(tex_w, tex_h)),
+            rect,
+        );
+        let glyph_stroke = Stroke::new(0.5, ui.visuals().selection.bg_fill.gamma_multiply(0.5));
+        for glyph in glyphs {
+            ui.painter()
+                .rect_stroke(glyph_rect(glyph, texels_to_screen), 0.0, glyph_stroke);
+        }
+
         response
             .on_hover_cursor(CursorIcon::ZoomIn)
             .on_hover_ui_at_pointer(|ui| {
@@ -54,6 +83,14 @@ pub(crate) fn font_texture_ui(ui: &mut Ui, [width, height]: [usize; 2]) -> Respo
                     let u = remap_clamp(pos.x, rect.x_range(), 0.0..=tex_w);
                     let v = remap_clamp(pos.y, rect.y_range(), 0.0..=tex_h);
 
+                    // Which glyph is under the pointer?
+                    let owner = glyphs.iter().find(|glyph| {
+                        f32::from(glyph.min[0]) <= u
+                            && u < f32::from(glyph.max[0])
+                            && f32::from(glyph.min[1]) <= v
+                            && v < f32::from(glyph.max[1])
+                    });
+
                     let texel_radius = 32.0;
                     let u = u.at_least(texel_radius).at_most(tex_w - texel_radius);
                     let v = v.at_least(texel_radius).at_most(tex_h - texel_radius);
@@ -65,12 +102,90 @@ pub(crate) fn font_texture_ui(ui: &mut Ui, [width, height]: [usize; 2]) -> Respo
                     let mut mesh = Mesh::default();
                     mesh.add_rect_with_uv(zoom_rect, uv_rect, color);
                     ui.painter().add(Shape::mesh(mesh));
+
+                    if let Some(owner) = owner {
+                        let texels_to_zoom = emath::RectTransform::from_to(
+                            Rect::from_min_max(
+                                pos2(u - texel_radius, v - texel_radius),
+                                pos2(u + texel_radius, v + texel_radius),
+                            ),
+                            zoom_rect,
+                        );
+                        ui.painter().with_clip_rect(zoom_rect).rect_stroke(
+                            glyph_rect(owner, texels_to_zoom),
+                            0.0,
+                            ui.visuals().selection.stroke,
+                        );
+
+                        let chr = owner
+                            .chr
+                            .map_or_else(|| "(shaped glyph)".to_owned(), |chr| format!("{chr:?}"));
+                        ui.label(format!(
+                            "{chr} from {} at {} px",
+                            owner.font_name, owner.scale_in_pixels
+                        ));
+                    }
                 }
             });
     })
     .response
 }
 
+impl Widget for &mut epaint::text::FontTweak {
+    fn ui(self, ui: &mut Ui) -> Response {
+        let original: epaint::text::FontTweak = *self;
+
+        let mut response = Grid::new("font_tweak")
+            .num_columns(2)
+            .show(ui, |ui| {
+                let epaint::text::FontTweak {
+                    scale,
+                    y_offset_factor,
+                    y_offset,
+                    baseline_offset_factor,
+                    subpixel_positioning,
+                    sdf,
+                } = self;
+
+                ui.label("Scale");
+                let speed = *scale * 0.01;
+                ui.add(DragValue::new(scale).speed(speed).clamp_range(0.01..=10.0));
+                ui.end_row();
+
+                ui.label("y_offset_factor");
+                ui.add(DragValue::new(y_offset_factor).speed(0.0025));
+                ui.end_row();
+
+                ui.label("y_offset");
+                ui.add(DragValue::new(y_offset).speed(0.02));
+                ui.end_row();
+
+                ui.label("baseline_offset_factor");
+                ui.add(DragValue::new(baseline_offset_factor).speed(0.0025));
+                ui.end_row();
+
+                ui.label("Subpixel positioning");
+                ui.checkbox(subpixel_positioning, "");
+                ui.end_row();
+
+                ui.label("SDF");
+                ui.checkbox(sdf, "");
+                ui.end_row();
+
+                if ui.button("Reset").clicked() {
+                    *self = Default::default();
+                }
+            })
+            .response;
+
+        if *self != original {
+            response.mark_changed();
+        }
+
+        response
+    }
+}
+
 impl Widget for &epaint::stats::PaintStats {
     fn ui(self, ui: &mut Ui) -> Response {
         ui.vertical(|ui| {
@@ -148,6 +263,7 @@ impl Widget for &mut epaint::TessellationOptions {
                 epsilon: _,
                 parallel_tessellation,
                 validate_meshes,
+                backdrop_blur,
             } = self;
 
             ui.horizontal(|ui| {
@@ -176,6 +292,8 @@ impl Widget for &mut epaint::TessellationOptions {
 
             ui.checkbox(validate_meshes, "Validate meshes").on_hover_text("Check that incoming meshes are valid, i.e. that all indices are in range, etc.");
 
+            ui.checkbox(backdrop_blur, "Blur backdrops").on_hover_text("Let the backend blur what's behind frosted-glass panels. Only works if the backend supports it.");
+
             ui.collapsing("Debug", |ui| {
                 ui.checkbox(
                     coarse_tessellation_culling,
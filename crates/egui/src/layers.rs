@@ -114,30 +114,109 @@ impl LayerId {
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct ShapeIdx(pub usize);
 
+/// A [`Shape`] added with [`Painter::add_handle`], that can be replaced with [`Painter::set_shape`]
+/// later in the same frame.
+///
+/// Unlike a bare [`ShapeIdx`], it remembers which layer the shape was added to,
+/// so the replacement always ends up in the same place.
+///
+/// This is how you paint a frame behind some contents when you don't know
+/// how large the frame needs to be until the contents have been added:
+///
+/// ```
+/// # egui::__run_test_ui(|ui| {
+/// let background = ui.painter().add_handle(egui::Shape::Noop);
+/// let response = ui.label("Framed");
+/// ui.painter().set_shape(
+///     background,
+///     egui::Shape::rect_filled(response.rect.expand(4.0), 2.0, egui::Color32::DARK_BLUE),
+/// );
+/// # });
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ShapeHandle {
+    layer_id: LayerId,
+    idx: ShapeIdx,
+}
+
+impl ShapeHandle {
+    #[inline]
+    pub(crate) fn new(layer_id: LayerId, idx: ShapeIdx) -> Self {
+        Self { layer_id, idx }
+    }
+
+    /// The layer the shape was added to.
+    #[inline]
+    pub fn layer_id(&self) -> LayerId {
+        self.layer_id
+    }
+
+    /// The index of the shape in the [`PaintList`] of [`Self::layer_id`].
+    #[inline]
+    pub fn idx(&self) -> ShapeIdx {
+        self.idx
+    }
+}
+
+impl From<ShapeHandle> for ShapeIdx {
+    #[inline]
+    fn from(handle: ShapeHandle) -> Self {
+        handle.idx
+    }
+}
+
 /// A list of [`Shape`]s paired with a clip rectangle.
+///
+/// Each shape also has a z key (see [`Painter::with_z`]).
+/// The shapes are painted in order of their z key, lowest first,
+/// and in the order they were added for equal keys.
 #[derive(Clone, Default)]
-pub struct PaintList(Vec<ClippedShape>);
+pub struct PaintList {
+    shapes: Vec<ClippedShape>,
+
+    /// The z key of each shape in `shapes`.
+    z: Vec<i32>,
+}
 
 impl PaintList {
     #[inline(always)]
     pub fn is_empty(&self) -> bool {
-        self.0.is_empty()
+        self.shapes.is_empty()
     }
 
     /// Returns the index of the new [`Shape`] that can be used with `PaintList::set`.
     #[inline(always)]
-    pub fn add(&mut self, clip_rect: Rect, shape: Shape) -> ShapeIdx {
-        let idx = ShapeIdx(self.0.len());
-        self.0.push(ClippedShape { clip_rect, shape });
+    pub fn add(
+        &mut self,
+        clip_rect: Rect,
+        clip_rounding: Rounding,
+        z: i32,
+        shape: Shape,
+    ) -> ShapeIdx {
+        let idx = ShapeIdx(self.shapes.len());
+        self.shapes.push(ClippedShape {
+            clip_rect,
+            clip_rounding,
+            shape,
+        });
+        self.z.push(z);
         idx
     }
 
-    pub fn extend<I: IntoIterator<Item = Shape>>(&mut self, clip_rect: Rect, shapes: I) {
-        self.0.extend(
-            shapes
-                .into_iter()
-                .map(|shape| ClippedShape { clip_rect, shape }),
-        );
+    pub fn extend<I: IntoIterator<Item = Shape>>(
+        &mut self,
+        clip_rect: Rect,
+        clip_rounding: Rounding,
+        z: i32,
+        shapes: I,
+    ) {
+        self.shapes
+            .extend(shapes.into_iter().map(|shape| ClippedShape {
+                clip_rect,
+                clip_rounding,
+                shape,
+            }));
+        self.z.resize(self.shapes.len(), z);
     }
 
     /// Modify an existing [`Shape`].
@@ -145,30 +224,55 @@ impl PaintList {
     /// Sometimes you want to paint a frame behind some contents, but don't know how large the frame needs to be
     /// until the contents have been added, and therefor also painted to the [`PaintList`].
     ///
-    /// The solution is to allocate a [`Shape`] using `let idx = paint_list.add(cr, Shape::Noop);`
-    /// and then later setting it using `paint_list.set(idx, cr, frame);`.
+    /// The solution is to allocate a [`Shape`] using `let idx = paint_list.add(cr, rounding, z, Shape::Noop);`
+    /// and then later setting it using `paint_list.set(idx, cr, rounding, frame);`.
+    /// The shape keeps the z key it was added with.
     #[inline(always)]
-    pub fn set(&mut self, idx: ShapeIdx, clip_rect: Rect, shape: Shape) {
-        self.0[idx.0] = ClippedShape { clip_rect, shape };
+    pub fn set(&mut self, idx: ShapeIdx, clip_rect: Rect, clip_rounding: Rounding, shape: Shape) {
+        self.shapes[idx.0] = ClippedShape {
+            clip_rect,
+            clip_rounding,
+            shape,
+        };
     }
 
     /// Set the given shape to be empty (a `Shape::Noop`).
     #[inline(always)]
     pub fn reset_shape(&mut self, idx: ShapeIdx) {
-        self.0[idx.0].shape = Shape::Noop;
+        self.shapes[idx.0].shape = Shape::Noop;
     }
 
     /// Transform each [`Shape`] and clip rectangle by this much, in-place
     pub fn transform(&mut self, transform: TSTransform) {
-        for ClippedShape { clip_rect, shape } in &mut self.0 {
+        for ClippedShape {
+            clip_rect,
+            clip_rounding,
+            shape,
+        } in &mut self.shapes
+        {
             *clip_rect = transform.mul_rect(*clip_rect);
+            *clip_rounding *= transform.scaling;
             shape.transform(transform);
         }
     }
 
     /// Read-only access to all held shapes.
+    ///
+    /// They are in the order they were added, not yet sorted by their z key.
     pub fn all_entries(&self) -> impl ExactSizeIterator<Item = &ClippedShape> {
-        self.0.iter()
+        self.shapes.iter()
+    }
+
+    /// Move all shapes out of the list, in the order they should be painted.
+    fn drain_sorted(&mut self) -> Vec<ClippedShape> {
+        let z = std::mem::take(&mut self.z);
+        if z.iter().all(|&z| z == 0) {
+            return std::mem::take(&mut self.shapes); // the common case: nothing to sort
+        }
+        let mut shapes: Vec<(i32, ClippedShape)> =
+            z.into_iter().zip(self.shapes.drain(..)).collect();
+        shapes.sort_by_key(|(z, _)| *z); // stable, so equal keys keep their order
+        shapes.into_iter().map(|(_, shape)| shape).collect()
     }
 }
 
@@ -215,13 +319,15 @@ impl GraphicLayers {
             for layer_id in area_order {
                 if layer_id.order == order {
                     if let Some(list) = order_map.get_mut(&layer_id.id) {
+                        let mut shapes = list.drain_sorted();
                         if let Some(transform) = transforms.get(layer_id) {
-                            for clipped_shape in &mut list.0 {
+                            for clipped_shape in &mut shapes {
                                 clipped_shape.clip_rect = *transform * clipped_shape.clip_rect;
+                                clipped_shape.clip_rounding *= transform.scaling;
                                 clipped_shape.shape.transform(*transform);
                             }
                         }
-                        all_shapes.append(&mut list.0);
+                        all_shapes.append(&mut shapes);
                     }
                 }
             }
@@ -229,18 +335,79 @@ impl GraphicLayers {
             // Also draw areas that are missing in `area_order`:
             for (id, list) in order_map {
                 let layer_id = LayerId::new(order, *id);
+                let mut shapes = list.drain_sorted();
 
                 if let Some(transform) = transforms.get(&layer_id) {
-                    for clipped_shape in &mut list.0 {
+                    for clipped_shape in &mut shapes {
                         clipped_shape.clip_rect = *transform * clipped_shape.clip_rect;
+                        clipped_shape.clip_rounding *= transform.scaling;
                         clipped_shape.shape.transform(*transform);
                     }
                 }
 
-                all_shapes.append(&mut list.0);
+                all_shapes.append(&mut shapes);
             }
         }
 
         all_shapes
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paint_list_z_order() {
+        let layer_id = LayerId::background();
+        let shape = |x: f32| Shape::circle_filled(pos2(x, 0.0), 1.0, Color32::WHITE);
+
+        let mut layers = GraphicLayers::default();
+        let list = layers.entry(layer_id);
+        list.add(Rect::EVERYTHING, Rounding::ZERO, 0, shape(0.0));
+        let placeholder = list.add(Rect::EVERYTHING, Rounding::ZERO, 1, Shape::Noop);
+        list.extend(
+            Rect::EVERYTHING,
+            Rounding::ZERO,
+            -1,
+            [shape(2.0), shape(3.0)],
+        );
+        list.add(Rect::EVERYTHING, Rounding::ZERO, 0, shape(4.0));
+
+        // Replacing a shape keeps its z:
+        list.set(placeholder, Rect::EVERYTHING, Rounding::ZERO, shape(1.0));
+
+        let xs: Vec<f32> = layers
+            .drain(&[layer_id], &Default::default())
+            .iter()
+            .map(|clipped| clipped.shape.visual_bounding_rect().center().x)
+            .collect();
+        assert_eq!(xs, [2.0, 3.0, 0.0, 4.0, 1.0]);
+        assert!(layers.get(layer_id).is_some_and(|list| list.is_empty()));
+    }
+
+    #[test]
+    fn negative_z_is_in_front_of_frame_background() {
+        let ctx = crate::Context::default();
+        let output = ctx.run(Default::default(), |ctx| {
+            crate::CentralPanel::default().show(ctx, |ui| {
+                let response = ui.label("Glowing");
+                ui.painter()
+                    .with_z(-1)
+                    .rect_filled(response.rect, 0.0, Color32::YELLOW);
+            });
+        });
+
+        let order: Vec<&str> = output
+            .shapes
+            .iter()
+            .filter_map(|clipped| match &clipped.shape {
+                Shape::Rect(rect) if rect.fill == Color32::YELLOW.into() => Some("glow"),
+                Shape::Rect(_) => Some("frame"),
+                Shape::Text(_) => Some("label"),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(order, ["frame", "glow", "label"]);
+    }
+}
@@ -427,17 +427,20 @@ pub use emath::{
 };
 pub use epaint::{
     mutex,
-    text::{FontData, FontDefinitions, FontFamily, FontId, FontTweak},
+    text::{FauxStyle, FontData, FontDefinitions, FontFamily, FontId, FontTweak},
     textures::{TextureFilter, TextureOptions, TextureWrapMode, TexturesDelta},
-    ClippedPrimitive, ColorImage, FontImage, ImageData, Margin, Mesh, PaintCallback,
-    PaintCallbackInfo, Rounding, Shadow, Shape, Stroke, TextureHandle, TextureId,
+    BackdropShape, ClippedPrimitive, ColorImage, CurvePathShape, Fill, FontImage, ImageData,
+    InstancedMeshShape, Margin, Mesh, NinePatch, PaintCallback, PaintCallbackInfo, PathBuilder,
+    Rounding, Shadow, Shape, Stroke, TextureHandle, TextureId,
 };
 
 pub mod text {
-    pub use crate::text_selection::{CCursorRange, CursorRange};
+    pub use crate::text_selection::{
+        text_cursor_state::find_matching_bracket, CCursorRange, CursorRange,
+    };
     pub use epaint::text::{
-        cursor::CCursor, FontData, FontDefinitions, FontFamily, Fonts, Galley, LayoutJob,
-        LayoutSection, TextFormat, TextWrapping, TAB_SIZE,
+        cursor::CCursor, four_cc, FontData, FontDefinitions, FontFamily, Fonts, FourCC, Galley,
+        Hyphenator, LayoutJob, LayoutSection, TextFormat, TextWrapping, TAB_SIZE,
     };
 }
 
@@ -60,6 +60,7 @@ use std::{
     fmt::{Debug, Display},
     ops::Deref,
     sync::Arc,
+    time::Duration,
 };
 
 use ahash::HashMap;
@@ -67,7 +68,7 @@ use ahash::HashMap;
 use emath::{Float, OrderedFloat};
 use epaint::{mutex::Mutex, textures::TextureOptions, ColorImage, TextureHandle, TextureId, Vec2};
 
-use crate::Context;
+use crate::{Context, Id};
 
 pub use self::{bytes_loader::DefaultBytesLoader, texture_loader::DefaultTextureLoader};
 
@@ -376,6 +377,98 @@ pub trait ImageLoader {
     fn byte_size(&self) -> usize;
 }
 
+/// The timing of an animated image, e.g. a GIF or APNG.
+///
+/// An [`ImageLoader`] that decodes an animated image returns its first frame for the `uri` of the image,
+/// and each frame for its [`Self::frame_uri`].
+/// It also [`Self::insert`]s the timing, which [`crate::Image`] uses to show the right frame,
+/// repainting only when the next frame is due.
+///
+/// ```
+/// # use std::time::Duration;
+/// use egui::load::AnimatedImage;
+///
+/// let animation = AnimatedImage::new(vec![Duration::from_millis(100); 3]);
+/// let (frame_index, until_next_frame) = animation.frame_at(1.35); // loops every 0.3 s
+/// assert_eq!(frame_index, 1);
+/// assert!((until_next_frame.as_secs_f64() - 0.05).abs() < 1e-6);
+///
+/// let frame_uri = AnimatedImage::frame_uri("file://cat.gif", 2);
+/// assert_eq!(frame_uri, "file://cat.gif#frame=2");
+/// assert_eq!(AnimatedImage::parse_frame_uri(&frame_uri), Some(("file://cat.gif", 2)));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AnimatedImage {
+    /// How long each frame is shown.
+    pub frame_delays: Arc<[Duration]>,
+}
+
+impl AnimatedImage {
+    pub fn new(frame_delays: impl Into<Arc<[Duration]>>) -> Self {
+        Self {
+            frame_delays: frame_delays.into(),
+        }
+    }
+
+    /// The uri of one frame of the animated image at `uri`.
+    pub fn frame_uri(uri: &str, frame_index: usize) -> String {
+        format!("{uri}#frame={frame_index}")
+    }
+
+    /// The uri of the animated image and the frame index, if this is a [`Self::frame_uri`].
+    pub fn parse_frame_uri(uri: &str) -> Option<(&str, usize)> {
+        let (image_uri, frame_index) = uri.rsplit_once("#frame=")?;
+        Some((image_uri, frame_index.parse().ok()?))
+    }
+
+    /// How long it takes to show all frames once.
+    pub fn total_duration(&self) -> Duration {
+        self.frame_delays.iter().sum()
+    }
+
+    /// Which frame to show `time` seconds into the looping animation,
+    /// and how long until the frame after it.
+    pub fn frame_at(&self, time: f64) -> (usize, Duration) {
+        let total = self.total_duration().as_secs_f64();
+        if total <= 0.0 {
+            return (0, Duration::ZERO);
+        }
+
+        let mut time = time.rem_euclid(total);
+        for (frame_index, delay) in self.frame_delays.iter().enumerate() {
+            let delay = delay.as_secs_f64();
+            if time < delay {
+                return (frame_index, Duration::from_secs_f64(delay - time));
+            }
+            time -= delay;
+        }
+        (0, self.frame_delays[0]) // Rounding errors
+    }
+
+    /// Remember the timing of the animated image at `uri`, for [`crate::Image`] to animate it.
+    pub fn insert(self, ctx: &Context, uri: &str) {
+        ctx.data_mut(|data| data.insert_temp(Self::id(uri), self));
+    }
+
+    /// The timing of the animated image at `uri`, if its [`ImageLoader`] has inserted it.
+    pub fn get(ctx: &Context, uri: &str) -> Option<Self> {
+        ctx.data(|data| data.get_temp(Self::id(uri)))
+    }
+
+    /// Forget the timing of the animated image at `uri`, returning it if there was any.
+    pub(crate) fn remove(ctx: &Context, uri: &str) -> Option<Self> {
+        ctx.data_mut(|data| {
+            let animation = data.get_temp(Self::id(uri));
+            data.remove::<Self>(Self::id(uri));
+            animation
+        })
+    }
+
+    fn id(uri: &str) -> Id {
+        Id::new(("egui::load::AnimatedImage", uri))
+    }
+}
+
 /// A texture with a known size.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct SizedTexture {
@@ -1,8 +1,18 @@
 use super::*;
 
+/// Reload an image (e.g. re-rasterize an svg) when the size it is shown at
+/// changes by more than this fraction.
+const RELOAD_THRESHOLD: f32 = 0.1;
+
+/// Textures are kept for this many sizes of each image,
+/// e.g. for an svg icon that is shown both small and large.
+const MAX_SIZES_PER_IMAGE: usize = 4;
+
 #[derive(Default)]
 pub struct DefaultTextureLoader {
-    cache: Mutex<HashMap<(String, TextureOptions), TextureHandle>>,
+    /// The textures of each image and the size hints they were loaded with,
+    /// least recently used first.
+    cache: Mutex<HashMap<(String, TextureOptions), Vec<(SizeHint, TextureHandle)>>>,
 }
 
 impl TextureLoader for DefaultTextureLoader {
@@ -18,7 +28,31 @@ impl TextureLoader for DefaultTextureLoader {
         size_hint: SizeHint,
     ) -> TextureLoadResult {
         let mut cache = self.cache.lock();
-        if let Some(handle) = cache.get(&(uri.into(), texture_options)) {
+        if let Some(textures) = cache.get_mut(&(uri.into(), texture_options)) {
+            let index = textures
+                .iter()
+                .position(|(hint, _)| !is_significant_change(*hint, size_hint));
+            // With `reduce_texture_memory` the bytes are forgotten, so we can't reload.
+            let reduce_texture_memory = ctx.options(|o| o.reduce_texture_memory);
+            if let Some(index) = index.or(reduce_texture_memory.then_some(textures.len() - 1)) {
+                let texture = textures.remove(index);
+                textures.push(texture);
+            } else if let Ok(ImagePoll::Ready { image }) = ctx.try_load_image(uri, size_hint) {
+                // Images that don't depend on the size hint (like most bitmaps) share one texture:
+                let handle = match textures
+                    .iter()
+                    .find(|(_, handle)| handle.size() == image.size)
+                {
+                    Some((_, handle)) => handle.clone(),
+                    None => ctx.load_texture(uri, image, texture_options),
+                };
+                textures.push((size_hint, handle));
+                if MAX_SIZES_PER_IMAGE < textures.len() {
+                    drop(textures.remove(0)); // frees the texture, unless it is shared
+                }
+            }
+            // While a new size is pending (or if it failed), the most recent texture is shown.
+            let (_, handle) = &textures[textures.len() - 1];
             let texture = SizedTexture::from_handle(handle);
             Ok(TexturePoll::Ready { texture })
         } else {
@@ -27,7 +61,7 @@ impl TextureLoader for DefaultTextureLoader {
                 ImagePoll::Ready { image } => {
                     let handle = ctx.load_texture(uri, image, texture_options);
                     let texture = SizedTexture::from_handle(&handle);
-                    cache.insert((uri.into(), texture_options), handle);
+                    cache.insert((uri.into(), texture_options), vec![(size_hint, handle)]);
                     let reduce_texture_memory = ctx.options(|o| o.reduce_texture_memory);
                     if reduce_texture_memory {
                         let loaders = ctx.loaders();
@@ -62,10 +96,48 @@ impl TextureLoader for DefaultTextureLoader {
     fn end_frame(&self, _: usize) {}
 
     fn byte_size(&self) -> usize {
+        let mut ids = ahash::HashSet::default();
         self.cache
             .lock()
             .values()
-            .map(|texture| texture.byte_size())
+            .flatten()
+            .filter(|(_, texture)| ids.insert(texture.id()))
+            .map(|(_, texture)| texture.byte_size())
             .sum()
     }
 }
+
+/// Does an image loaded with the `old` hint need to be reloaded to look good at `new`?
+fn is_significant_change(old: SizeHint, new: SizeHint) -> bool {
+    let differs = |old: f32, new: f32| RELOAD_THRESHOLD * old.max(new) < (new - old).abs();
+    match (old, new) {
+        (SizeHint::Scale(old), SizeHint::Scale(new)) => differs(old.into_inner(), new.into_inner()),
+        (SizeHint::Width(old), SizeHint::Width(new))
+        | (SizeHint::Height(old), SizeHint::Height(new)) => differs(old as f32, new as f32),
+        (SizeHint::Size(old_w, old_h), SizeHint::Size(new_w, new_h)) => {
+            differs(old_w as f32, new_w as f32) || differs(old_h as f32, new_h as f32)
+        }
+        (old, new) => old != new,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_significant_change() {
+        let change = is_significant_change;
+        assert!(!change(SizeHint::Size(100, 50), SizeHint::Size(105, 52)));
+        assert!(change(SizeHint::Size(100, 50), SizeHint::Size(200, 100)));
+        assert!(change(SizeHint::Width(24), SizeHint::Height(24)));
+        assert!(!change(
+            SizeHint::Scale(1.0.ord()),
+            SizeHint::Scale(1.05.ord())
+        ));
+        assert!(change(
+            SizeHint::Scale(1.0.ord()),
+            SizeHint::Scale(2.0.ord())
+        ));
+    }
+}
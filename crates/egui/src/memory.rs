@@ -223,6 +223,13 @@ pub struct Options {
     /// if you are changing [`Style::text_styles`], of have a lot of text styles.
     pub preload_font_glyphs: bool,
 
+    /// The coverage gamma of text, see [`epaint::Fonts::set_text_gamma`].
+    ///
+    /// Lower values make text look bolder, which can help with light text on a dark background.
+    ///
+    /// Default: [`epaint::FontImage::DEFAULT_GAMMA`].
+    pub text_gamma: f32,
+
     /// Check reusing of [`Id`]s, and show a visual warning on screen when one is found.
     ///
     /// By default this is `true` in debug builds.
@@ -268,6 +275,7 @@ impl Default for Options {
             repaint_on_widget_change: false,
             screen_reader: false,
             preload_font_glyphs: true,
+            text_gamma: epaint::FontImage::DEFAULT_GAMMA,
             warn_on_id_clash: cfg!(debug_assertions),
 
             // Input:
@@ -289,6 +297,7 @@ impl Options {
             repaint_on_widget_change,
             screen_reader: _, // needs to come from the integration
             preload_font_glyphs: _,
+            text_gamma,
             warn_on_id_clash,
 
             line_scroll_speed,
@@ -327,8 +336,17 @@ impl Options {
             .default_open(false)
             .show(ui, |ui| {
                 tessellation_options.ui(ui);
+                ui.add(crate::Slider::new(text_gamma, 0.2..=1.5).text("Text gamma"))
+                    .on_hover_text("Lower values make text bolder, higher values make it thinner");
                 ui.vertical_centered(|ui| {
-                    crate::reset_button(ui, tessellation_options, "Reset paint settings");
+                    let mut paint_settings = (*tessellation_options, *text_gamma);
+                    crate::reset_button_with(
+                        ui,
+                        &mut paint_settings,
+                        "Reset paint settings",
+                        (Default::default(), epaint::FontImage::DEFAULT_GAMMA),
+                    );
+                    (*tessellation_options, *text_gamma) = paint_settings;
                 });
             });
 
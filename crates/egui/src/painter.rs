@@ -1,13 +1,16 @@
 use std::sync::Arc;
 
 use crate::{
-    emath::{Align2, Pos2, Rangef, Rect, Vec2},
-    layers::{LayerId, PaintList, ShapeIdx},
+    emath::{Align2, Pos2, Rangef, Rect, Rot2, Vec2},
+    layers::{LayerId, PaintList, ShapeHandle, ShapeIdx},
+    util::cache::CacheTrait,
     Color32, Context, FontId,
 };
 use epaint::{
     text::{Fonts, Galley, LayoutJob},
-    CircleShape, ClippedShape, PathStroke, RectShape, Rounding, Shape, Stroke,
+    CircleSegmentShape, CircleShape, ClippedShape, CurvePathShape, Fill, InstancedMeshShape,
+    NinePatch, PathBuilder, PathStroke, RectShape, Rounding, Shape, Stroke, TessellationOptions,
+    TextShape,
 };
 
 /// Helper to paint shapes and text to a specific region on a specific layer.
@@ -25,6 +28,12 @@ pub struct Painter {
     /// This means nothing outside of this rectangle will be visible on screen.
     clip_rect: Rect,
 
+    /// How rounded the corners of [`Self::clip_rect`] are.
+    clip_rounding: Rounding,
+
+    /// Shapes are painted in order of this within their layer, see [`Self::with_z`].
+    z: i32,
+
     /// If set, all shapes will have their colors modified to be closer to this.
     /// This is used to implement grayed out interfaces.
     fade_to_color: Option<Color32>,
@@ -42,6 +51,8 @@ impl Painter {
             ctx,
             layer_id,
             clip_rect,
+            clip_rounding: Rounding::ZERO,
+            z: 0,
             fade_to_color: None,
             opacity_factor: 1.0,
         }
@@ -54,6 +65,8 @@ impl Painter {
             ctx: self.ctx,
             layer_id,
             clip_rect: self.clip_rect,
+            clip_rounding: self.clip_rounding,
+            z: self.z,
             fade_to_color: None,
             opacity_factor: 1.0,
         }
@@ -63,16 +76,69 @@ impl Painter {
     ///
     /// The clip-rect of the returned [`Painter`] will be the intersection
     /// of the given rectangle and the `clip_rect()` of the parent [`Painter`].
+    /// Corners it shares with the parent clip rect keep their [`Self::clip_rounding`].
     pub fn with_clip_rect(&self, rect: Rect) -> Self {
+        let clip_rect = rect.intersect(self.clip_rect);
         Self {
             ctx: self.ctx.clone(),
             layer_id: self.layer_id,
-            clip_rect: rect.intersect(self.clip_rect),
+            clip_rect,
+            clip_rounding: shared_corner_rounding(self.clip_rect, self.clip_rounding, clip_rect),
+            z: self.z,
             fade_to_color: self.fade_to_color,
             opacity_factor: self.opacity_factor,
         }
     }
 
+    /// Create a painter that also clips away the rounded corners of its `clip_rect()`.
+    ///
+    /// ```
+    /// # egui::__run_test_ui(|ui| {
+    /// let rect = ui.max_rect();
+    /// let painter = ui.painter_at(rect).with_clip_rounding(16.0);
+    /// // The corners of this are cut off:
+    /// painter.rect_filled(rect, 0.0, egui::Color32::RED);
+    /// # });
+    /// ```
+    #[inline]
+    pub fn with_clip_rounding(&self, clip_rounding: impl Into<Rounding>) -> Self {
+        let mut painter = self.clone();
+        painter.clip_rounding = clip_rounding.into();
+        painter
+    }
+
+    /// Create a painter whose shapes are painted in front of, or behind, the other shapes of the same layer.
+    ///
+    /// Within a layer, shapes are painted in order of their z, lowest first,
+    /// and shapes with the same z in the order they were added.
+    /// The default is zero.
+    ///
+    /// The z is not relative to the [`Ui`](crate::Ui) you paint in, but to the whole layer.
+    /// The background of a [`Frame`](crate::Frame), and so of panels and windows,
+    /// is painted at [`i32::MIN`], so shapes with a negative z still end up in front of it.
+    ///
+    /// This is finer-grained than using a separate [`LayerId`],
+    /// and useful for painting something behind shapes that have already been added:
+    ///
+    /// ```
+    /// # egui::__run_test_ui(|ui| {
+    /// let response = ui.label("Glowing");
+    /// // Painted behind the label (but in front of the panel background),
+    /// // even though it is added after it:
+    /// ui.painter().with_z(-1).rect_filled(
+    ///     response.rect.expand(4.0),
+    ///     4.0,
+    ///     egui::Color32::YELLOW.gamma_multiply(0.3),
+    /// );
+    /// # });
+    /// ```
+    #[inline]
+    pub fn with_z(&self, z: i32) -> Self {
+        let mut painter = self.clone();
+        painter.z = z;
+        painter
+    }
+
     /// Redirect where you are painting.
     pub fn set_layer_id(&mut self, layer_id: LayerId) {
         self.layer_id = layer_id;
@@ -153,11 +219,36 @@ impl Painter {
 
     /// Everything painted in this [`Painter`] will be clipped against this.
     /// This means nothing outside of this rectangle will be visible on screen.
+    ///
+    /// Corners the new clip rect shares with the old one keep their [`Self::clip_rounding`].
     #[inline]
     pub fn set_clip_rect(&mut self, clip_rect: Rect) {
+        self.clip_rounding = shared_corner_rounding(self.clip_rect, self.clip_rounding, clip_rect);
         self.clip_rect = clip_rect;
     }
 
+    /// How rounded the corners of [`Self::clip_rect`] are.
+    ///
+    /// Anything painted outside the rounded corners is cut away.
+    #[inline]
+    pub fn clip_rounding(&self) -> Rounding {
+        self.clip_rounding
+    }
+
+    /// How rounded the corners of [`Self::clip_rect`] are.
+    ///
+    /// Anything painted outside the rounded corners is cut away.
+    #[inline]
+    pub fn set_clip_rounding(&mut self, clip_rounding: impl Into<Rounding>) {
+        self.clip_rounding = clip_rounding.into();
+    }
+
+    /// Shapes are painted in order of this within their layer, see [`Self::with_z`].
+    #[inline]
+    pub fn z(&self) -> i32 {
+        self.z
+    }
+
     /// Useful for pixel-perfect rendering.
     #[inline]
     pub fn round_to_pixel(&self, point: f32) -> f32 {
@@ -203,13 +294,21 @@ impl Painter {
     /// Can be used for free painting.
     /// NOTE: all coordinates are screen coordinates!
     pub fn add(&self, shape: impl Into<Shape>) -> ShapeIdx {
-        if self.fade_to_color == Some(Color32::TRANSPARENT) || self.opacity_factor == 0.0 {
-            self.paint_list(|l| l.add(self.clip_rect, Shape::Noop))
-        } else {
-            let mut shape = shape.into();
-            self.transform_shape(&mut shape);
-            self.paint_list(|l| l.add(self.clip_rect, shape))
-        }
+        self.add_handle(shape).idx()
+    }
+
+    /// Like [`Self::add`], but returns a [`ShapeHandle`] to replace the shape later with [`Self::set_shape`].
+    pub fn add_handle(&self, shape: impl Into<Shape>) -> ShapeHandle {
+        let shape =
+            if self.fade_to_color == Some(Color32::TRANSPARENT) || self.opacity_factor == 0.0 {
+                Shape::Noop
+            } else {
+                let mut shape = shape.into();
+                self.transform_shape(&mut shape);
+                shape
+            };
+        let idx = self.paint_list(|l| l.add(self.clip_rect, self.clip_rounding, self.z, shape));
+        ShapeHandle::new(self.layer_id, idx)
     }
 
     /// Add many shapes at once.
@@ -224,20 +323,39 @@ impl Painter {
                 self.transform_shape(&mut shape);
                 shape
             });
-            self.paint_list(|l| l.extend(self.clip_rect, shapes));
+            self.paint_list(|l| l.extend(self.clip_rect, self.clip_rounding, self.z, shapes));
         } else {
-            self.paint_list(|l| l.extend(self.clip_rect, shapes));
+            self.paint_list(|l| l.extend(self.clip_rect, self.clip_rounding, self.z, shapes));
         }
     }
 
-    /// Modify an existing [`Shape`].
+    /// Modify an existing [`Shape`] in the layer of this painter.
+    ///
+    /// Prefer [`Self::set_shape`], which can't end up in the wrong layer.
     pub fn set(&self, idx: ShapeIdx, shape: impl Into<Shape>) {
         if self.fade_to_color == Some(Color32::TRANSPARENT) {
             return;
         }
         let mut shape = shape.into();
         self.transform_shape(&mut shape);
-        self.paint_list(|l| l.set(idx, self.clip_rect, shape));
+        self.paint_list(|l| l.set(idx, self.clip_rect, self.clip_rounding, shape));
+    }
+
+    /// Replace a [`Shape`] added earlier this frame, e.g. a [`Shape::Noop`] placeholder.
+    ///
+    /// The new shape is clipped to the clip rect of this painter,
+    /// but stays in the layer and at the z it was added with.
+    /// See [`ShapeHandle`] for an example.
+    pub fn set_shape(&self, handle: ShapeHandle, shape: impl Into<Shape>) {
+        if self.fade_to_color == Some(Color32::TRANSPARENT) {
+            return;
+        }
+        let mut shape = shape.into();
+        self.transform_shape(&mut shape);
+        self.ctx.graphics_mut(|g| {
+            g.entry(handle.layer_id())
+                .set(handle.idx(), self.clip_rect, self.clip_rounding, shape);
+        });
     }
 
     /// Access all shapes added this frame.
@@ -320,6 +438,19 @@ impl Painter {
         self.add(Shape::vline(x, y, stroke.into()))
     }
 
+    /// Paints a path of lines and Bézier curves, built with a [`PathBuilder`].
+    ///
+    /// The curves are flattened when tessellated, to suit the `pixels_per_point` at that time.
+    /// Self-intersecting paths are filled with the even-odd rule.
+    pub fn path(
+        &self,
+        path: PathBuilder,
+        fill: impl Into<Fill>,
+        stroke: impl Into<PathStroke>,
+    ) -> ShapeIdx {
+        self.add(CurvePathShape::new(path, fill, stroke))
+    }
+
     pub fn circle(
         &self,
         center: Pos2,
@@ -332,6 +463,7 @@ impl Painter {
             radius,
             fill: fill_color.into(),
             stroke: stroke.into(),
+            stroke_pattern: Default::default(),
         })
     }
 
@@ -346,6 +478,7 @@ impl Painter {
             radius,
             fill: fill_color.into(),
             stroke: Default::default(),
+            stroke_pattern: Default::default(),
         })
     }
 
@@ -355,9 +488,53 @@ impl Painter {
             radius,
             fill: Default::default(),
             stroke: stroke.into(),
+            stroke_pattern: Default::default(),
         })
     }
 
+    /// An arc of a circle, from `start_angle` to `end_angle` in radians.
+    ///
+    /// An angle of zero points right, and increasing angles go clockwise.
+    pub fn arc_stroke(
+        &self,
+        center: Pos2,
+        radius: f32,
+        start_angle: f32,
+        end_angle: f32,
+        stroke: impl Into<Stroke>,
+    ) -> ShapeIdx {
+        self.add(CircleSegmentShape::arc(
+            center,
+            radius,
+            start_angle,
+            end_angle,
+            stroke,
+        ))
+    }
+
+    /// A pie slice of a circle, from `start_angle` to `end_angle` in radians.
+    ///
+    /// An angle of zero points right, and increasing angles go clockwise.
+    /// For a ring segment (e.g. in a donut chart), use [`CircleSegmentShape::ring`].
+    pub fn pie(
+        &self,
+        center: Pos2,
+        radius: f32,
+        start_angle: f32,
+        end_angle: f32,
+        fill_color: impl Into<Color32>,
+        stroke: impl Into<Stroke>,
+    ) -> ShapeIdx {
+        self.add(CircleSegmentShape::pie(
+            center,
+            radius,
+            start_angle,
+            end_angle,
+            fill_color,
+            stroke,
+        ))
+    }
+
     pub fn rect(
         &self,
         rect: Rect,
@@ -377,6 +554,16 @@ impl Painter {
         self.add(RectShape::filled(rect, rounding, fill_color))
     }
 
+    /// A rectangle filled with a gradient, e.g. [`Fill::linear`] or [`Fill::radial`].
+    pub fn rect_filled_gradient(
+        &self,
+        rect: Rect,
+        rounding: impl Into<Rounding>,
+        fill: impl Into<Fill>,
+    ) -> ShapeIdx {
+        self.add(RectShape::filled(rect, rounding, Color32::WHITE).with_fill(fill))
+    }
+
     pub fn rect_stroke(
         &self,
         rect: Rect,
@@ -426,6 +613,131 @@ impl Painter {
     ) -> ShapeIdx {
         self.add(Shape::image(texture_id, rect, uv, tint))
     }
+
+    /// Paint a texture sliced into nine patches to cover `rect`, e.g. a skinned frame or button.
+    ///
+    /// The corners keep their size, while the edges and the center are stretched
+    /// (or tiled, see [`NinePatch::tiled`]) to fill the rest.
+    ///
+    /// `tint` is a color multiplier. Use [`Color32::WHITE`] if you don't want to tint the image.
+    ///
+    /// ```
+    /// # egui::__run_test_ui(|ui| {
+    /// # let texture = ui.ctx().load_texture("skin", egui::ColorImage::example(), Default::default());
+    /// # let rect = ui.max_rect();
+    /// // Corners of a quarter of the texture, at its own size:
+    /// let nine_patch = egui::NinePatch::new(texture.size_vec2(), egui::Margin::same(0.25));
+    /// ui.painter()
+    ///     .image_nine_patch(texture.id(), rect, nine_patch, egui::Color32::WHITE);
+    /// # });
+    /// ```
+    pub fn image_nine_patch(
+        &self,
+        texture_id: epaint::TextureId,
+        rect: Rect,
+        nine_patch: NinePatch,
+        tint: Color32,
+    ) -> ShapeIdx {
+        self.add(Shape::image_nine_patch(texture_id, rect, nine_patch, tint))
+    }
+
+    /// Paint a shared [`crate::Mesh`], moved by `offset` and with its colors multiplied by `tint`.
+    ///
+    /// This is much cheaper than painting the same shape many times,
+    /// since the mesh is only tessellated once, e.g. with [`Self::cached_mesh`].
+    pub fn add_mesh_at(
+        &self,
+        instance: &InstancedMeshShape,
+        offset: Vec2,
+        tint: Color32,
+    ) -> ShapeIdx {
+        self.add(instance.clone().with_offset(offset).with_tint(tint))
+    }
+
+    /// The [`Shape`] tessellated into a [`crate::Mesh`], reused for as long as it is asked for every frame.
+    ///
+    /// `key` must identify everything the shape depends on, e.g. its size, rounding and stroke width.
+    /// The shape is only created and tessellated when there is no mesh for the key yet,
+    /// or when `pixels_per_point` or the [`crate::Context::tessellation_options`] change.
+    ///
+    /// ```
+    /// # egui::__run_test_ui(|ui| {
+    /// # let nodes = [egui::pos2(10.0, 10.0), egui::pos2(10.0, 60.0)];
+    /// let size = egui::vec2(80.0, 40.0);
+    /// let node_mesh = ui.painter().cached_mesh(("node", size.x.to_bits(), size.y.to_bits()), || {
+    ///     let rect = egui::Rect::from_min_size(egui::Pos2::ZERO, size);
+    ///     egui::Shape::rect_filled(rect, 4.0, egui::Color32::WHITE)
+    /// });
+    /// for node in nodes {
+    ///     ui.painter()
+    ///         .add_mesh_at(&node_mesh, node.to_vec2(), egui::Color32::LIGHT_BLUE);
+    /// }
+    /// # });
+    /// ```
+    pub fn cached_mesh(
+        &self,
+        key: impl std::hash::Hash,
+        shape: impl FnOnce() -> Shape,
+    ) -> InstancedMeshShape {
+        let key = epaint::util::hash((key, self.ctx.pixels_per_point().to_bits()));
+        let options = self.ctx.tessellation_options(|options| *options);
+        if let Some(instance) = self
+            .ctx
+            .memory_mut(|mem| mem.caches.cache::<MeshCache>().get(key, &options))
+        {
+            return instance;
+        }
+
+        let mesh = Arc::new(self.ctx.tessellate_shape(shape()));
+        let instance = InstancedMeshShape::new(mesh, Vec2::ZERO);
+        self.ctx.memory_mut(|mem| {
+            mem.caches
+                .cache::<MeshCache>()
+                .insert(key, options, instance.clone());
+        });
+        instance
+    }
+}
+
+/// The meshes of [`Painter::cached_mesh`], and the [`TessellationOptions`] they were tessellated with.
+/// A mesh that isn't used for a frame is evicted.
+#[derive(Default)]
+struct MeshCache {
+    generation: u32,
+    meshes: nohash_hasher::IntMap<u64, (u32, TessellationOptions, InstancedMeshShape)>,
+}
+
+impl MeshCache {
+    fn get(&mut self, key: u64, options: &TessellationOptions) -> Option<InstancedMeshShape> {
+        let (generation, mesh_options, instance) = self.meshes.get_mut(&key)?;
+        if mesh_options != options {
+            return None;
+        }
+        *generation = self.generation;
+        Some(instance.clone())
+    }
+
+    fn insert(&mut self, key: u64, options: TessellationOptions, instance: InstancedMeshShape) {
+        self.meshes
+            .insert(key, (self.generation, options, instance));
+    }
+}
+
+impl CacheTrait for MeshCache {
+    fn update(&mut self) {
+        let current_generation = self.generation;
+        self.meshes
+            .retain(|_key, (generation, _, _)| *generation == current_generation);
+        self.generation = self.generation.wrapping_add(1);
+    }
+
+    fn len(&self) -> usize {
+        self.meshes.len()
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
 }
 
 /// ## Text
@@ -453,6 +765,34 @@ impl Painter {
         rect
     }
 
+    /// Lay out and paint some text, rotated by `angle` radians clockwise, e.g. for the label of a y axis.
+    ///
+    /// The `anchor` point of the text is put at `pos`, and the text is rotated around it.
+    /// To center the text at the given position, use `Align2::CENTER_CENTER`.
+    ///
+    /// Returns the bounding rectangle of the rotated text.
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn text_rotated(
+        &self,
+        pos: Pos2,
+        anchor: Align2,
+        text: impl ToString,
+        font_id: FontId,
+        text_color: Color32,
+        angle: f32,
+    ) -> Rect {
+        let galley = self.layout_no_wrap(text.to_string(), font_id, text_color);
+        let rot = Rot2::from_angle(angle);
+
+        // Where the text is relative to `pos` before rotating it:
+        let unrotated_rect = anchor.anchor_size(Pos2::ZERO, galley.size());
+        let galley_pos = pos + rot * unrotated_rect.min.to_vec2();
+        if !galley.is_empty() {
+            self.add(TextShape::new(galley_pos, galley, text_color).with_angle(angle));
+        }
+        unrotated_rect.rotate_bb(rot).translate(pos.to_vec2())
+    }
+
     /// Will wrap text at the given width and line break at `\n`.
     ///
     /// Paint the results with [`Self::galley`].
@@ -550,3 +890,15 @@ fn multiply_opacity(shape: &mut Shape, opacity: f32) {
         }
     });
 }
+
+/// The rounding of the corners of `new` that coincide with the corners of `old`,
+/// so that a sub-region of a rounded clip rect only stays rounded where it touches the rounded corners.
+fn shared_corner_rounding(old: Rect, rounding: Rounding, new: Rect) -> Rounding {
+    let keep = |a: Pos2, b: Pos2, radius: f32| if a == b { radius } else { 0.0 };
+    Rounding {
+        nw: keep(old.left_top(), new.left_top(), rounding.nw),
+        ne: keep(old.right_top(), new.right_top(), rounding.ne),
+        sw: keep(old.left_bottom(), new.left_bottom(), rounding.sw),
+        se: keep(old.right_bottom(), new.right_bottom(), rounding.se),
+    }
+}
@@ -119,6 +119,10 @@ pub struct Response {
     /// Always `false` for something like a [`Button`](crate::Button).
     #[doc(hidden)]
     pub changed: bool,
+
+    /// Which link in the text of a [`Label`](crate::Label) or [`Button`](crate::Button) was clicked this frame, if any.
+    #[doc(hidden)]
+    pub clicked_link: Option<usize>,
 }
 
 impl Response {
@@ -139,6 +143,14 @@ impl Response {
         self.fake_primary_click || self.clicked_by(PointerButton::Primary)
     }
 
+    /// Which link in the text of a [`crate::Label`] or [`crate::Button`] was clicked this frame, if any.
+    ///
+    /// This is the [`crate::text::TextFormat::link`] of the clicked section.
+    #[inline]
+    pub fn clicked_link(&self) -> Option<usize> {
+        self.clicked_link
+    }
+
     /// Returns true if this widget was clicked this frame by the given mouse button.
     ///
     /// This will NOT return true if the widget was "clicked" via
@@ -991,6 +1003,7 @@ impl Response {
                 || other.is_pointer_button_down_on,
             interact_pointer_pos: self.interact_pointer_pos.or(other.interact_pointer_pos),
             changed: self.changed || other.changed,
+            clicked_link: self.clicked_link.or(other.clicked_link),
         }
     }
 }
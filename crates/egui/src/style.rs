@@ -7,8 +7,8 @@ use std::collections::BTreeMap;
 use epaint::{Rounding, Shadow, Stroke};
 
 use crate::{
-    ecolor::*, emath::*, ComboBox, CursorIcon, FontFamily, FontId, Grid, Margin, Response,
-    RichText, WidgetText,
+    ecolor::*, emath::*, ComboBox, CursorIcon, FontFamily, FontId, Grid, Margin, Modifiers,
+    Response, RichText, WidgetText,
 };
 
 // ----------------------------------------------------------------------------
@@ -673,6 +673,41 @@ pub struct Interaction {
     /// The default is `true`, but text seelction can be slightly glitchy,
     /// so you may want to disable it.
     pub multi_widget_text_select: bool,
+
+    /// Is `_` part of a word when double-clicking text and moving or deleting by words?
+    ///
+    /// The default is `true`, so that identifiers like `snake_case` are one word.
+    pub underscore_is_word_char: bool,
+
+    /// Hold these modifiers (and optionally shift) with the left/right arrow keys
+    /// to move the text cursor by the parts of words, like `Camel` and `Case` in `CamelCase`.
+    ///
+    /// The default is ctrl+alt.
+    pub subword_modifiers: Modifiers,
+
+    /// Should double-clicking text select only a part of a word, like `Camel` in `CamelCase`?
+    ///
+    /// The default is `false`, i.e. to select the whole word.
+    pub double_click_selects_subword: bool,
+
+    /// When drag-selecting text with the pointer outside the visible part of a [`crate::TextEdit`],
+    /// scroll this many points per second for each point the pointer is outside.
+    pub text_drag_scroll_speed: f32,
+
+    /// The fastest that drag-selecting text scrolls, in points per second.
+    pub text_drag_scroll_max_speed: f32,
+
+    /// The keys of all [`crate::TextEdit`]s that don't have their own
+    /// [`crate::TextEdit::keymap`].
+    ///
+    /// The default is `None`, i.e. [`crate::text_edit::TextEditKeymap::default_for`] the current OS and style.
+    pub text_edit_keymap: Option<crate::text_edit::TextEditKeymap>,
+
+    /// Copy the colors, italics etc of selected text as HTML too,
+    /// so that they are kept when pasting into other apps.
+    ///
+    /// The default is `false`, i.e. to only copy plain text.
+    pub copy_rich_text: bool,
 }
 
 /// Look and feel of the text cursor.
@@ -683,6 +718,18 @@ pub struct TextCursorStyle {
     /// The color and width of the text cursor
     pub stroke: Stroke,
 
+    /// The color and width of the text cursor in a read-only [`crate::TextEdit`],
+    /// see [`crate::TextEdit::read_only`].
+    ///
+    /// Usually thinner and greyer than [`Self::stroke`], so that it doesn't look editable.
+    pub read_only_stroke: Stroke,
+
+    /// The color and width of a line at the other end of a selection,
+    /// where the selection started.
+    ///
+    /// The default is [`Stroke::NONE`], i.e. to only show the selection itself.
+    pub secondary_stroke: Stroke,
+
     /// Show where the text cursor would be if you clicked?
     pub preview: bool,
 
@@ -700,6 +747,8 @@ impl Default for TextCursorStyle {
     fn default() -> Self {
         Self {
             stroke: Stroke::new(2.0, Color32::from_rgb(192, 222, 255)), // Dark mode
+            read_only_stroke: Stroke::new(1.0, Color32::from_gray(140)),
+            secondary_stroke: Stroke::NONE,
             preview: false,
             blink: true,
             on_duration: 0.5,
@@ -879,6 +928,12 @@ impl Visuals {
 pub struct Selection {
     pub bg_fill: Color32,
     pub stroke: Stroke,
+
+    /// Rounding of the outer corners of selected text.
+    ///
+    /// The rows of a selection over several rows are merged into one region,
+    /// and only its outer corners are rounded.
+    pub rounding: Rounding,
 }
 
 /// Shape of the handle for sliders and similar widgets.
@@ -1113,6 +1168,13 @@ impl Default for Interaction {
             tooltip_grace_time: 0.2,
             selectable_labels: true,
             multi_widget_text_select: true,
+            underscore_is_word_char: true,
+            subword_modifiers: Modifiers::CTRL | Modifiers::ALT,
+            double_click_selects_subword: false,
+            text_drag_scroll_speed: 20.0,
+            text_drag_scroll_max_speed: 1500.0,
+            text_edit_keymap: None,
+            copy_rich_text: false,
         }
     }
 }
@@ -1209,6 +1271,7 @@ impl Visuals {
 
             text_cursor: TextCursorStyle {
                 stroke: Stroke::new(2.0, Color32::from_rgb(0, 83, 125)),
+                read_only_stroke: Stroke::new(1.0, Color32::from_gray(110)),
                 ..Default::default()
             },
 
@@ -1228,6 +1291,7 @@ impl Selection {
         Self {
             bg_fill: Color32::from_rgb(0, 92, 128),
             stroke: Stroke::new(1.0, Color32::from_rgb(192, 222, 255)),
+            rounding: Rounding::same(2.0),
         }
     }
 
@@ -1235,6 +1299,7 @@ impl Selection {
         Self {
             bg_fill: Color32::from_rgb(144, 209, 255),
             stroke: Stroke::new(1.0, Color32::from_rgb(0, 83, 125)),
+            rounding: Rounding::same(2.0),
         }
     }
 }
@@ -1601,6 +1666,13 @@ impl Interaction {
             tooltip_grace_time,
             selectable_labels,
             multi_widget_text_select,
+            underscore_is_word_char,
+            subword_modifiers: _,
+            double_click_selects_subword,
+            text_drag_scroll_speed,
+            text_drag_scroll_max_speed,
+            text_edit_keymap: _,
+            copy_rich_text,
         } = self;
 
         ui.spacing_mut().item_spacing = vec2(12.0, 8.0);
@@ -1643,6 +1715,25 @@ impl Interaction {
                         .suffix(" s"),
                 );
                 ui.end_row();
+
+                ui.label("Text drag scroll speed").on_hover_text(
+                    "When drag-selecting text outside a TextEdit, scroll this fast per point the pointer is outside",
+                );
+                ui.add(
+                    DragValue::new(text_drag_scroll_speed)
+                        .clamp_range(0.0..=100.0)
+                        .suffix(" /s"),
+                );
+                ui.end_row();
+
+                ui.label("Text drag scroll max speed")
+                    .on_hover_text("The fastest that drag-selecting text scrolls");
+                ui.add(
+                    DragValue::new(text_drag_scroll_max_speed)
+                        .clamp_range(0.0..=10_000.0)
+                        .suffix(" pt/s"),
+                );
+                ui.end_row();
             });
 
         ui.checkbox(
@@ -1657,6 +1748,13 @@ impl Interaction {
             }
         });
 
+        ui.checkbox(underscore_is_word_char, "Treat _ as part of words");
+        ui.checkbox(copy_rich_text, "Copy text with its colors etc");
+        ui.checkbox(
+            double_click_selects_subword,
+            "Double-click selects part of CamelCase words",
+        );
+
         ui.vertical_centered(|ui| reset_button(ui, self, "Reset interaction settings"));
     }
 }
@@ -1700,7 +1798,11 @@ impl Widgets {
 
 impl Selection {
     pub fn ui(&mut self, ui: &mut crate::Ui) {
-        let Self { bg_fill, stroke } = self;
+        let Self {
+            bg_fill,
+            stroke,
+            rounding,
+        } = self;
         ui.label("Selectable labels");
 
         Grid::new("selectiom").num_columns(2).show(ui, |ui| {
@@ -1711,6 +1813,10 @@ impl Selection {
             ui.label("Stroke");
             ui.add(stroke);
             ui.end_row();
+
+            ui.label("Rounding");
+            ui.add(rounding);
+            ui.end_row();
         });
     }
 }
@@ -1975,6 +2081,8 @@ impl TextCursorStyle {
     fn ui(&mut self, ui: &mut Ui) {
         let Self {
             stroke,
+            read_only_stroke,
+            secondary_stroke,
             preview,
             blink,
             on_duration,
@@ -1986,6 +2094,16 @@ impl TextCursorStyle {
             ui.add(stroke);
         });
 
+        ui.horizontal(|ui| {
+            ui.label("Read-only stroke");
+            ui.add(read_only_stroke);
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Selection start stroke");
+            ui.add(secondary_stroke);
+        });
+
         ui.checkbox(preview, "Preview text cursor on hover");
 
         ui.checkbox(blink, "Blink");
@@ -2329,6 +2447,7 @@ impl Widget for &mut crate::Frame {
             shadow,
             fill,
             stroke,
+            backdrop_blur,
         } = self;
 
         crate::Grid::new("frame")
@@ -2359,6 +2478,15 @@ impl Widget for &mut crate::Frame {
                 ui.label("Stroke");
                 ui.add(stroke);
                 ui.end_row();
+
+                ui.label("Backdrop blur");
+                ui.add(
+                    DragValue::new(backdrop_blur)
+                        .clamp_range(0.0..=32.0)
+                        .suffix(" pt"),
+                )
+                .on_hover_text("Blur what's behind the frame. Use a translucent fill.");
+                ui.end_row();
             })
             .response
     }
@@ -66,7 +66,7 @@ pub fn update_accesskit_for_text_widget(
                 let mut was_at_word_end = false;
                 let mut last_word_start = 0usize;
 
-                for glyph in &row.glyphs {
+                for glyph in row.glyphs.iter() {
                     let is_word_char = is_word_char(glyph.chr);
                     if is_word_char && was_at_word_end {
                         word_lengths.push((character_lengths.len() - last_word_start) as _);
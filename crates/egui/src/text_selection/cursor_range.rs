@@ -1,8 +1,8 @@
 use epaint::{text::cursor::*, Galley};
 
-use crate::{os::OperatingSystem, Event, Id, Key, Modifiers};
+use crate::{os::OperatingSystem, style::Interaction, Event, Id, Key, Modifiers};
 
-use super::text_cursor_state::{ccursor_next_word, ccursor_previous_word, slice_char_range};
+use super::text_cursor_state::{slice_char_range, SelectionBoundary};
 
 /// A selected text range (could be a range of length zero).
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
@@ -114,6 +114,12 @@ impl CursorRange {
 
     /// Check for key presses that are moving the cursor.
     ///
+    /// The [`Interaction`] style decides how the cursor moves by words.
+    ///
+    /// `preferred_x` is where along the row the cursor should go when moving up and down,
+    /// and is set by the first such move, and cleared by any other.
+    /// See [`super::TextCursorState::preferred_x`].
+    ///
     /// Returns `true` if we did mutate `self`.
     pub fn on_key_press(
         &mut self,
@@ -121,10 +127,13 @@ impl CursorRange {
         galley: &Galley,
         modifiers: &Modifiers,
         key: Key,
+        interaction: &Interaction,
+        preferred_x: &mut Option<f32>,
     ) -> bool {
         match key {
             Key::A if modifiers.command => {
                 *self = Self::select_all(galley);
+                *preferred_x = None;
                 true
             }
 
@@ -134,6 +143,7 @@ impl CursorRange {
                 } else {
                     *self = Self::one(self.sorted_cursors()[1]);
                 }
+                *preferred_x = None;
                 true
             }
 
@@ -143,7 +153,15 @@ impl CursorRange {
             | Key::ArrowDown
             | Key::Home
             | Key::End => {
-                move_single_cursor(os, &mut self.primary, galley, key, modifiers);
+                move_single_cursor(
+                    os,
+                    &mut self.primary,
+                    galley,
+                    key,
+                    modifiers,
+                    interaction,
+                    preferred_x,
+                );
                 if !modifiers.shift {
                     self.secondary = self.primary;
                 }
@@ -153,7 +171,15 @@ impl CursorRange {
             Key::P | Key::N | Key::B | Key::F | Key::A | Key::E
                 if os == OperatingSystem::Mac && modifiers.ctrl && !modifiers.shift =>
             {
-                move_single_cursor(os, &mut self.primary, galley, key, modifiers);
+                move_single_cursor(
+                    os,
+                    &mut self.primary,
+                    galley,
+                    key,
+                    modifiers,
+                    interaction,
+                    preferred_x,
+                );
                 self.secondary = self.primary;
                 true
             }
@@ -164,6 +190,9 @@ impl CursorRange {
 
     /// Check for events that modify the cursor range.
     ///
+    /// The [`Interaction`] style decides how the cursor moves by words,
+    /// and `preferred_x` is as in [`Self::on_key_press`].
+    ///
     /// Returns `true` if such an event was found and handled.
     pub fn on_event(
         &mut self,
@@ -171,6 +200,8 @@ impl CursorRange {
         event: &Event,
         galley: &Galley,
         _widget_id: Id,
+        interaction: &Interaction,
+        preferred_x: &mut Option<f32>,
     ) -> bool {
         match event {
             Event::Key {
@@ -178,7 +209,7 @@ impl CursorRange {
                 key,
                 pressed: true,
                 ..
-            } => self.on_key_press(os, galley, modifiers, *key),
+            } => self.on_key_press(os, galley, modifiers, *key, interaction, preferred_x),
 
             #[cfg(feature = "accesskit")]
             Event::AccessKitActionRequest(accesskit::ActionRequest {
@@ -196,6 +227,7 @@ impl CursorRange {
                             primary: galley.from_ccursor(primary),
                             secondary: galley.from_ccursor(secondary),
                         };
+                        *preferred_x = None;
                         return true;
                     }
                 }
@@ -241,6 +273,12 @@ impl CCursorRange {
         }
     }
 
+    /// True if the selected range contains no characters.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.primary.index == self.secondary.index
+    }
+
     #[inline]
     pub fn is_sorted(&self) -> bool {
         let p = self.primary;
@@ -305,36 +343,84 @@ fn move_single_cursor(
     galley: &Galley,
     key: Key,
     modifiers: &Modifiers,
+    interaction: &Interaction,
+    preferred_x: &mut Option<f32>,
 ) {
-    if os == OperatingSystem::Mac && modifiers.ctrl && !modifiers.shift {
+    let by_subword = Modifiers {
+        shift: false,
+        ..*modifiers
+    }
+    .matches_exact(interaction.subword_modifiers);
+    let word_boundary = if by_subword {
+        SelectionBoundary::Subword
+    } else {
+        SelectionBoundary::Word
+    };
+
+    // Moving up/down keeps the cursor at the same place along the rows,
+    // even after passing rows that are too short:
+    let preferred_offset = preferred_x.take();
+    let mut move_vertically = |cursor: &mut Cursor, up: bool| {
+        let offset = preferred_offset.unwrap_or_else(|| galley.cursor_offset_along_row(cursor));
+        *cursor = if up {
+            galley.cursor_up_one_row_at(cursor, offset)
+        } else {
+            galley.cursor_down_one_row_at(cursor, offset)
+        };
+        *preferred_x = Some(offset);
+    };
+
+    if os == OperatingSystem::Mac && modifiers.ctrl && !modifiers.shift && !by_subword {
         match key {
             Key::A => *cursor = galley.cursor_begin_of_row(cursor),
             Key::E => *cursor = galley.cursor_end_of_row(cursor),
-            Key::P => *cursor = galley.cursor_up_one_row(cursor),
-            Key::N => *cursor = galley.cursor_down_one_row(cursor),
+            Key::P => move_vertically(cursor, true),
+            Key::N => move_vertically(cursor, false),
             Key::B => *cursor = galley.cursor_left_one_character(cursor),
             Key::F => *cursor = galley.cursor_right_one_character(cursor),
             _ => (),
         }
         return;
     }
+
+    // In vertical text the characters go downwards, and the rows (columns) go leftwards:
+    let key = if galley.is_vertical() {
+        match key {
+            Key::ArrowUp => Key::ArrowLeft,
+            Key::ArrowDown => Key::ArrowRight,
+            Key::ArrowLeft => Key::ArrowDown,
+            Key::ArrowRight => Key::ArrowUp,
+            key => key,
+        }
+    } else {
+        key
+    };
+
     match key {
         Key::ArrowLeft => {
-            if modifiers.alt || modifiers.ctrl {
+            if by_subword || modifiers.alt || modifiers.ctrl {
                 // alt on mac, ctrl on windows
-                *cursor = galley.from_ccursor(ccursor_previous_word(galley, cursor.ccursor));
+                *cursor = galley.from_ccursor(word_boundary.ccursor_previous_bounded(
+                    galley,
+                    cursor.ccursor,
+                    interaction.underscore_is_word_char,
+                ));
             } else if modifiers.mac_cmd {
-                *cursor = galley.cursor_begin_of_row(cursor);
+                *cursor = galley.cursor_left_end_of_row(cursor);
             } else {
                 *cursor = galley.cursor_left_one_character(cursor);
             }
         }
         Key::ArrowRight => {
-            if modifiers.alt || modifiers.ctrl {
+            if by_subword || modifiers.alt || modifiers.ctrl {
                 // alt on mac, ctrl on windows
-                *cursor = galley.from_ccursor(ccursor_next_word(galley, cursor.ccursor));
+                *cursor = galley.from_ccursor(word_boundary.ccursor_next_bounded(
+                    galley,
+                    cursor.ccursor,
+                    interaction.underscore_is_word_char,
+                ));
             } else if modifiers.mac_cmd {
-                *cursor = galley.cursor_end_of_row(cursor);
+                *cursor = galley.cursor_right_end_of_row(cursor);
             } else {
                 *cursor = galley.cursor_right_one_character(cursor);
             }
@@ -344,7 +430,7 @@ fn move_single_cursor(
                 // mac and windows behavior
                 *cursor = galley.begin();
             } else {
-                *cursor = galley.cursor_up_one_row(cursor);
+                move_vertically(cursor, true);
             }
         }
         Key::ArrowDown => {
@@ -352,7 +438,7 @@ fn move_single_cursor(
                 // mac and windows behavior
                 *cursor = galley.end();
             } else {
-                *cursor = galley.cursor_down_one_row(cursor);
+                move_vertically(cursor, false);
             }
         }
 
@@ -361,7 +447,9 @@ fn move_single_cursor(
                 // windows behavior
                 *cursor = galley.begin();
             } else {
-                *cursor = galley.cursor_begin_of_row(cursor);
+                // The first non-whitespace character of the row, and then the start of it.
+                // In right-to-left text this is the visual end of the row instead:
+                *cursor = galley.cursor_smart_begin_of_row(cursor);
             }
         }
         Key::End => {
@@ -369,7 +457,7 @@ fn move_single_cursor(
                 // windows behavior
                 *cursor = galley.end();
             } else {
-                *cursor = galley.cursor_end_of_row(cursor);
+                *cursor = galley.cursor_right_end_of_row(cursor);
             }
         }
 
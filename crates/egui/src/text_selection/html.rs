@@ -0,0 +1,143 @@
+//! Selected text as HTML, for [`crate::style::Interaction::copy_rich_text`].
+
+use std::{fmt::Write as _, ops::Range};
+
+use epaint::text::{FontFamily, Galley, TextFormat};
+
+use crate::Color32;
+
+use super::text_cursor_state::byte_index_from_char_index;
+
+/// The text of `galley` in the given char range as HTML,
+/// with a `<span>` for the color, weight etc of each section.
+pub fn galley_html(galley: &Galley, char_range: Range<usize>) -> String {
+    let text = galley.text();
+    let selected = byte_index_from_char_index(text, char_range.start)
+        ..byte_index_from_char_index(text, char_range.end);
+
+    let mut html = String::new();
+    for section in &galley.job.sections {
+        let start = section.byte_range.start.max(selected.start);
+        let end = section.byte_range.end.min(selected.end);
+        if start < end {
+            let _ = write!(html, r#"<span style="{}">"#, css_style(&section.format));
+            push_escaped(&mut html, &text[start..end]);
+            html.push_str("</span>");
+        }
+    }
+    html
+}
+
+/// Several pieces of HTML, one per line, in a block that keeps the whitespace.
+pub fn html_block(pieces: impl IntoIterator<Item = String>) -> String {
+    let pieces: Vec<String> = pieces.into_iter().collect();
+    format!(
+        r#"<div style="white-space: pre-wrap">{}</div>"#,
+        pieces.join("<br>")
+    )
+}
+
+fn css_style(format: &TextFormat) -> String {
+    let mut style = format!("color: {};", css_color(format.color));
+    if format.background != Color32::TRANSPARENT {
+        let _ = write!(
+            style,
+            " background-color: {};",
+            css_color(format.background)
+        );
+    }
+    if format.font_id.family == FontFamily::Monospace {
+        style.push_str(" font-family: monospace;");
+    }
+    if format.strong {
+        style.push_str(" font-weight: bold;");
+    }
+    if format.italics {
+        style.push_str(" font-style: italic;");
+    }
+    match (
+        !format.underline.is_empty(),
+        !format.strikethrough.is_empty(),
+    ) {
+        (true, true) => style.push_str(" text-decoration: underline line-through;"),
+        (true, false) => style.push_str(" text-decoration: underline;"),
+        (false, true) => style.push_str(" text-decoration: line-through;"),
+        (false, false) => {}
+    }
+    style
+}
+
+fn css_color(color: Color32) -> String {
+    let [r, g, b, a] = color.to_srgba_unmultiplied();
+    if a == 255 {
+        format!("#{r:02x}{g:02x}{b:02x}")
+    } else {
+        format!("rgba({r}, {g}, {b}, {:.3})", f32::from(a) / 255.0)
+    }
+}
+
+fn push_escaped(html: &mut String, text: &str) {
+    for c in text.chars() {
+        match c {
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '"' => html.push_str("&quot;"),
+            '\n' => html.push_str("<br>"),
+            c => html.push(c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use epaint::text::{FontDefinitions, Fonts, LayoutJob};
+
+    use super::*;
+    use crate::FontId;
+
+    #[test]
+    fn test_galley_html() {
+        let fonts = Fonts::new(1.0, 1024, FontDefinitions::default());
+        let mut job = LayoutJob::default();
+        job.append(
+            "fn ",
+            0.0,
+            TextFormat {
+                color: Color32::RED,
+                strong: true,
+                ..Default::default()
+            },
+        );
+        job.append(
+            "héllo<T>",
+            0.0,
+            TextFormat {
+                font_id: FontId::monospace(12.0),
+                color: Color32::from_rgb(0, 128, 255),
+                italics: true,
+                ..Default::default()
+            },
+        );
+        let galley = fonts.layout_job(job);
+
+        assert_eq!(
+            galley_html(&galley, 0..3),
+            r#"<span style="color: #ff0000; font-weight: bold;">fn </span>"#
+        );
+
+        // Char offsets within a section, past a multi-byte char:
+        assert_eq!(
+            galley_html(&galley, 1..8),
+            concat!(
+                r#"<span style="color: #ff0000; font-weight: bold;">n </span>"#,
+                r#"<span style="color: #0080ff; font-family: monospace; font-style: italic;">héllo</span>"#,
+            )
+        );
+        assert_eq!(
+            galley_html(&galley, 7..10),
+            r#"<span style="color: #0080ff; font-family: monospace; font-style: italic;">o&lt;T</span>"#
+        );
+        assert_eq!(galley_html(&galley, 4..4), "");
+    }
+}
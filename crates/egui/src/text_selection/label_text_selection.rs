@@ -1,10 +1,11 @@
 use crate::{
-    layers::ShapeIdx, text::CCursor, text_selection::CCursorRange, Context, CursorIcon, Event,
-    Galley, Id, LayerId, Pos2, Rect, Response, Ui,
+    layers::ShapeIdx, os::OperatingSystem, text::CCursor, text_selection::CCursorRange, Context,
+    CursorIcon, Event, Galley, Id, LayerId, Pos2, Rect, Response, Ui,
 };
 
 use super::{
-    text_cursor_state::cursor_rect, visuals::paint_text_selection, CursorRange, TextCursorState,
+    text_cursor_state::cursor_rect, visuals::paint_text_selection, CursorRange, SelectionBoundary,
+    TextCursorState,
 };
 
 /// Turn on to help debug this
@@ -94,6 +95,14 @@ struct CurrentSelection {
     /// When selecting with a mouse, this is where the mouse was first pressed.
     /// This part of the cursor does not move when shift is down.
     pub secondary: WidgetTextCursor,
+
+    /// After a double- or triple-click: what the selection snaps to when it is extended,
+    /// and the word or line that was clicked, which is in the widget of [`Self::secondary`].
+    pub bounded: Option<(SelectionBoundary, CCursorRange)>,
+
+    /// Where along the row [`Self::primary`] goes when moving it up and down with the keyboard,
+    /// see [`TextCursorState::preferred_x`].
+    pub preferred_x: Option<f32>,
 }
 
 /// Handles text selection in labels (NOT in [`crate::TextEdit`])s.
@@ -121,8 +130,18 @@ pub struct LabelSelectionState {
 
     /// Accumulated text to copy.
     text_to_copy: String,
+
+    /// Accumulated text to copy as HTML, with [`crate::style::Interaction::copy_rich_text`].
+    html_to_copy: String,
     last_copied_galley_rect: Option<Rect>,
 
+    /// Accumulated selected text, for the primary selection (Linux).
+    primary_text: String,
+    last_primary_galley_rect: Option<Rect>,
+
+    /// Did the selection in any label change this frame?
+    selection_changed: bool,
+
     /// Painted selections this frame.
     painted_shape_idx: Vec<ShapeIdx>,
 }
@@ -138,7 +157,11 @@ impl Default for LabelSelectionState {
             has_reached_primary: Default::default(),
             has_reached_secondary: Default::default(),
             text_to_copy: Default::default(),
+            html_to_copy: Default::default(),
             last_copied_galley_rect: Default::default(),
+            primary_text: Default::default(),
+            last_primary_galley_rect: Default::default(),
+            selection_changed: Default::default(),
             painted_shape_idx: Default::default(),
         }
     }
@@ -179,7 +202,11 @@ impl LabelSelectionState {
         state.has_reached_primary = false;
         state.has_reached_secondary = false;
         state.text_to_copy.clear();
+        state.html_to_copy.clear();
         state.last_copied_galley_rect = None;
+        state.primary_text.clear();
+        state.last_primary_galley_rect = None;
+        state.selection_changed = false;
         state.painted_shape_idx.clear();
 
         state.store(ctx);
@@ -188,6 +215,10 @@ impl LabelSelectionState {
     fn end_frame(ctx: &Context) {
         let mut state = Self::load(ctx);
 
+        if ctx.input(|i| i.pointer.any_released()) {
+            state.is_dragging = false;
+        }
+
         if state.is_dragging {
             ctx.set_cursor_icon(CursorIcon::Text);
         }
@@ -219,13 +250,19 @@ impl LabelSelectionState {
             state.selection = None;
         }
 
-        if ctx.input(|i| i.pointer.any_released()) {
-            state.is_dragging = false;
-        }
-
         let text_to_copy = std::mem::take(&mut state.text_to_copy);
+        let html_to_copy = std::mem::take(&mut state.html_to_copy);
         if !text_to_copy.is_empty() {
-            ctx.copy_text(text_to_copy);
+            if html_to_copy.is_empty() {
+                ctx.copy_text(text_to_copy);
+            } else {
+                ctx.copy_rich_text(text_to_copy, super::html::html_block([html_to_copy]));
+            }
+        }
+
+        let primary_text = std::mem::take(&mut state.primary_text);
+        if state.selection_changed && !primary_text.is_empty() {
+            ctx.output_mut(|o| o.copied_text_primary = primary_text);
         }
 
         state.store(ctx);
@@ -239,51 +276,51 @@ impl LabelSelectionState {
         self.selection = None;
     }
 
-    fn copy_text(&mut self, galley_pos: Pos2, galley: &Galley, cursor_range: &CursorRange) {
-        let new_galley_rect = Rect::from_min_size(galley_pos, galley.size());
+    fn copy_text(
+        &mut self,
+        galley_pos: Pos2,
+        galley: &Galley,
+        cursor_range: &CursorRange,
+        rich_text: bool,
+    ) {
         let new_text = selected_text(galley, cursor_range);
-        if new_text.is_empty() {
-            return;
-        }
-
-        if self.text_to_copy.is_empty() {
-            self.text_to_copy = new_text;
-            self.last_copied_galley_rect = Some(new_galley_rect);
-            return;
+        let new_html = (rich_text && !new_text.is_empty())
+            .then(|| super::html::galley_html(galley, selected_char_range(galley, cursor_range)));
+        let len_before = self.text_to_copy.len();
+        let new_len = new_text.len();
+        append_text(
+            &mut self.text_to_copy,
+            &mut self.last_copied_galley_rect,
+            galley_pos,
+            galley,
+            new_text,
+        );
+        if let Some(new_html) = new_html {
+            // Use the same separator as the plain text:
+            let separator = &self.text_to_copy[len_before..self.text_to_copy.len() - new_len];
+            self.html_to_copy.push_str(&separator.replace('\n', "<br>"));
+            self.html_to_copy.push_str(&new_html);
         }
+    }
 
-        let Some(last_copied_galley_rect) = self.last_copied_galley_rect else {
-            self.text_to_copy = new_text;
-            self.last_copied_galley_rect = Some(new_galley_rect);
+    /// Collect the selected text of all labels, for the primary selection.
+    fn select_primary_text(
+        &mut self,
+        galley_pos: Pos2,
+        galley: &Galley,
+        cursor_range: &CursorRange,
+    ) {
+        if cursor_range.is_empty() {
             return;
-        };
-
-        // We need to append or prepend the new text to the already copied text.
-        // We need to do so intelligently.
-
-        if last_copied_galley_rect.bottom() <= new_galley_rect.top() {
-            self.text_to_copy.push('\n');
-            let vertical_distance = new_galley_rect.top() - last_copied_galley_rect.bottom();
-            if estimate_row_height(galley) * 0.5 < vertical_distance {
-                self.text_to_copy.push('\n');
-            }
-        } else {
-            let existing_ends_with_space =
-                self.text_to_copy.chars().last().map(|c| c.is_whitespace());
-
-            let new_text_starts_with_space_or_punctuation = new_text
-                .chars()
-                .next()
-                .map_or(false, |c| c.is_whitespace() || c.is_ascii_punctuation());
-
-            if existing_ends_with_space == Some(false) && !new_text_starts_with_space_or_punctuation
-            {
-                self.text_to_copy.push(' ');
-            }
         }
-
-        self.text_to_copy.push_str(&new_text);
-        self.last_copied_galley_rect = Some(new_galley_rect);
+        let new_text = cursor_range.slice_str(galley).to_owned();
+        append_text(
+            &mut self.primary_text,
+            &mut self.last_primary_galley_rect,
+            galley_pos,
+            galley,
+            new_text,
+        );
     }
 
     /// Handle text selection state for a label or similar widget.
@@ -371,6 +408,7 @@ impl LabelSelectionState {
                 if let Some(new_primary) = new_primary {
                     selection.primary =
                         WidgetTextCursor::new(response.id, new_primary, galley_pos, galley);
+                    selection.preferred_x = None;
 
                     // We don't want the latency of `drag_started`.
                     let drag_started = ui.input(|i| i.pointer.any_pressed());
@@ -381,11 +419,44 @@ impl LabelSelectionState {
                             } else {
                                 // A new selection in the same layer.
                                 selection.secondary = selection.primary;
+                                selection.bounded = None;
                             }
                         } else {
                             // A new selection in a new layer.
                             selection.layer_id = response.layer_id;
                             selection.secondary = selection.primary;
+                            selection.bounded = None;
+                        }
+                    }
+
+                    if selection.bounded.is_some()
+                        && response.contains_pointer()
+                        && selection.secondary.widget_id == response.id
+                    {
+                        // Extend the double- or triple-clicked word or line by whole words or lines:
+                        let mut cursor_state = TextCursorState::from(CCursorRange {
+                            primary: selection.secondary.ccursor,
+                            secondary: selection.secondary.ccursor,
+                        });
+                        cursor_state.set_bounded_selection(selection.bounded);
+                        cursor_state.extend_selection_to(
+                            galley,
+                            new_primary,
+                            ui.style().interaction.underscore_is_word_char,
+                        );
+                        if let Some(range) = cursor_state.char_range() {
+                            selection.primary = WidgetTextCursor::new(
+                                response.id,
+                                range.primary,
+                                galley_pos,
+                                galley,
+                            );
+                            selection.secondary = WidgetTextCursor::new(
+                                response.id,
+                                range.secondary,
+                                galley_pos,
+                                galley,
+                            );
                         }
                     }
                 }
@@ -417,7 +488,9 @@ impl LabelSelectionState {
         match (primary, secondary) {
             (Some(primary), Some(secondary)) => {
                 // This is the only selected label.
-                TextCursorState::from(CCursorRange { primary, secondary })
+                let mut cursor_state = TextCursorState::from(CCursorRange { primary, secondary });
+                cursor_state.set_bounded_selection(selection.bounded);
+                cursor_state
             }
 
             (Some(primary), None) => {
@@ -482,6 +555,9 @@ impl LabelSelectionState {
 
         let mut cursor_state = self.cursor_for(ui, response, galley_pos, galley);
 
+        // Set if the pointer interacted with this label:
+        let mut bounded = None;
+
         let old_range = cursor_state.range(galley);
 
         if let Some(pointer_pos) = ui.ctx().pointer_interact_pos() {
@@ -491,7 +567,15 @@ impl LabelSelectionState {
                 // This is where we handle start-of-drag and double-click-to-select.
                 // Actual drag-to-select happens elsewhere.
                 let dragged = false;
-                cursor_state.pointer_interaction(ui, response, cursor_at_pointer, galley, dragged);
+                if cursor_state.pointer_interaction(
+                    ui,
+                    response,
+                    cursor_at_pointer,
+                    galley,
+                    dragged,
+                ) {
+                    bounded = Some(cursor_state.bounded_selection());
+                }
             }
         }
 
@@ -499,14 +583,25 @@ impl LabelSelectionState {
             let galley_rect = Rect::from_min_size(galley_pos, galley.size());
             self.selection_bbox_this_frame = self.selection_bbox_this_frame.union(galley_rect);
 
-            if let Some(selection) = &self.selection {
+            if let Some(selection) = &mut self.selection {
                 if selection.primary.widget_id == response.id {
-                    process_selection_key_events(ui.ctx(), galley, response.id, &mut cursor_range);
+                    process_selection_key_events(
+                        ui.ctx(),
+                        galley,
+                        response.id,
+                        &mut cursor_range,
+                        &mut selection.preferred_x,
+                    );
                 }
             }
 
             if got_copy_event(ui.ctx()) {
-                self.copy_text(galley_pos, galley, &cursor_range);
+                let rich_text = ui.style().interaction.copy_rich_text;
+                self.copy_text(galley_pos, galley, &cursor_range, rich_text);
+            }
+
+            if ui.ctx().os() == OperatingSystem::Nix {
+                self.select_primary_text(galley_pos, galley, &cursor_range);
             }
 
             cursor_state.set_range(Some(cursor_range));
@@ -515,6 +610,7 @@ impl LabelSelectionState {
         // Look for changes due to keyboard and/or mouse interaction:
         let new_range = cursor_state.range(galley);
         let selection_changed = old_range != new_range;
+        self.selection_changed |= selection_changed;
 
         if let (true, Some(range)) = (selection_changed, new_range) {
             // --------------
@@ -547,12 +643,20 @@ impl LabelSelectionState {
                         galley_pos,
                         galley,
                     ),
+                    bounded: None,
+                    preferred_x: None,
                 });
                 self.has_reached_primary = true;
                 self.has_reached_secondary = true;
             }
         }
 
+        if let (Some(bounded), Some(selection)) = (bounded, &mut self.selection) {
+            // Remember a double- or triple-clicked word or line for extending it by more of the same:
+            selection.bounded = bounded;
+            selection.preferred_x = None;
+        }
+
         // Scroll containing ScrollArea on cursor change:
         if let Some(range) = new_range {
             let old_primary = old_selection.map(|s| s.primary);
@@ -585,6 +689,57 @@ impl LabelSelectionState {
     }
 }
 
+/// Append (or prepend) `new_text` from another galley to the already collected `text_to_copy`.
+fn append_text(
+    text_to_copy: &mut String,
+    last_galley_rect: &mut Option<Rect>,
+    galley_pos: Pos2,
+    galley: &Galley,
+    new_text: String,
+) {
+    let new_galley_rect = Rect::from_min_size(galley_pos, galley.size());
+    if new_text.is_empty() {
+        return;
+    }
+
+    if text_to_copy.is_empty() {
+        *text_to_copy = new_text;
+        *last_galley_rect = Some(new_galley_rect);
+        return;
+    }
+
+    let Some(last_copied_galley_rect) = *last_galley_rect else {
+        *text_to_copy = new_text;
+        *last_galley_rect = Some(new_galley_rect);
+        return;
+    };
+
+    // We need to append or prepend the new text to the already copied text.
+    // We need to do so intelligently.
+
+    if last_copied_galley_rect.bottom() <= new_galley_rect.top() {
+        text_to_copy.push('\n');
+        let vertical_distance = new_galley_rect.top() - last_copied_galley_rect.bottom();
+        if estimate_row_height(galley) * 0.5 < vertical_distance {
+            text_to_copy.push('\n');
+        }
+    } else {
+        let existing_ends_with_space = text_to_copy.chars().last().map(|c| c.is_whitespace());
+
+        let new_text_starts_with_space_or_punctuation = new_text
+            .chars()
+            .next()
+            .map_or(false, |c| c.is_whitespace() || c.is_ascii_punctuation());
+
+        if existing_ends_with_space == Some(false) && !new_text_starts_with_space_or_punctuation {
+            text_to_copy.push(' ');
+        }
+    }
+
+    text_to_copy.push_str(&new_text);
+    *last_galley_rect = Some(new_galley_rect);
+}
+
 fn got_copy_event(ctx: &Context) -> bool {
     ctx.input(|i| {
         i.events
@@ -594,13 +749,17 @@ fn got_copy_event(ctx: &Context) -> bool {
 }
 
 /// Returns true if the cursor changed
+///
+/// `preferred_x` is remembered between frames, see [`TextCursorState::preferred_x`].
 fn process_selection_key_events(
     ctx: &Context,
     galley: &Galley,
     widget_id: Id,
     cursor_range: &mut CursorRange,
+    preferred_x: &mut Option<f32>,
 ) -> bool {
     let os = ctx.os();
+    let style = ctx.style();
 
     let mut changed = false;
 
@@ -608,7 +767,14 @@ fn process_selection_key_events(
         // NOTE: we have a lock on ui/ctx here,
         // so be careful to not call into `ui` or `ctx` again.
         for event in &i.events {
-            changed |= cursor_range.on_event(os, event, galley, widget_id);
+            changed |= cursor_range.on_event(
+                os,
+                event,
+                galley,
+                widget_id,
+                &style.interaction,
+                preferred_x,
+            );
         }
     });
 
@@ -616,19 +782,31 @@ fn process_selection_key_events(
 }
 
 fn selected_text(galley: &Galley, cursor_range: &CursorRange) -> String {
-    // This logic means we can select everything in an ellided label (including the `…`)
-    // and still copy the entire un-ellided text!
-    let everything_is_selected = cursor_range.contains(&CursorRange::select_all(galley));
-
-    let copy_everything = cursor_range.is_empty() || everything_is_selected;
-
-    if copy_everything {
+    if copy_everything(galley, cursor_range) {
         galley.text().to_owned()
     } else {
         cursor_range.slice_str(galley).to_owned()
     }
 }
 
+/// The char range that [`selected_text`] copies.
+fn selected_char_range(galley: &Galley, cursor_range: &CursorRange) -> std::ops::Range<usize> {
+    if copy_everything(galley, cursor_range) {
+        0..galley.text().chars().count()
+    } else {
+        let [min, max] = cursor_range.sorted_cursors();
+        min.ccursor.index..max.ccursor.index
+    }
+}
+
+fn copy_everything(galley: &Galley, cursor_range: &CursorRange) -> bool {
+    // This logic means we can select everything in an ellided label (including the `…`)
+    // and still copy the entire un-ellided text!
+    let everything_is_selected = cursor_range.contains(&CursorRange::select_all(galley));
+
+    cursor_range.is_empty() || everything_is_selected
+}
+
 fn estimate_row_height(galley: &Galley) -> f32 {
     if let Some(row) = galley.rows.first() {
         row.rect.height()
@@ -636,3 +814,204 @@ fn estimate_row_height(galley: &Galley) -> f32 {
         galley.size().y
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn test_double_click_then_extend_by_words() {
+        let ctx = Context::default();
+        let text = "one two three four";
+
+        let time = std::cell::Cell::new(0.0);
+        let run = |events: Vec<Event>, modifiers: Modifiers| {
+            time.set(time.get() + 0.05);
+            let input = RawInput {
+                screen_rect: Some(Rect::from_min_size(Pos2::ZERO, vec2(800.0, 600.0))),
+                events,
+                modifiers,
+                time: Some(time.get()),
+                ..Default::default()
+            };
+            let mut label_pos = Pos2::ZERO;
+            let output = ctx.run(input, |ctx| {
+                CentralPanel::default().show(ctx, |ui| {
+                    label_pos = ui.label(text).rect.min;
+                });
+            });
+            (label_pos, output.platform_output.copied_text)
+        };
+
+        let (label_pos, _) = run(vec![], Modifiers::NONE);
+        let font_id = TextStyle::Body.resolve(&ctx.style());
+        let galley =
+            ctx.fonts(|fonts| fonts.layout_no_wrap(text.to_owned(), font_id, Color32::WHITE));
+        let pos_of = |index: usize| {
+            let cursor = galley.from_ccursor(text::CCursor::new(index));
+            label_pos + galley.pos_from_cursor(&cursor).center().to_vec2()
+        };
+        let button = |pos: Pos2, pressed: bool, modifiers: Modifiers| Event::PointerButton {
+            pos,
+            button: PointerButton::Primary,
+            pressed,
+            modifiers,
+        };
+
+        // Double-click "two":
+        let two = pos_of(5);
+        run(vec![Event::PointerMoved(two)], Modifiers::NONE);
+        for _ in 0..2 {
+            run(vec![button(two, true, Modifiers::NONE)], Modifiers::NONE);
+            run(vec![button(two, false, Modifiers::NONE)], Modifiers::NONE);
+        }
+        let (_, copied_text) = run(vec![Event::Copy], Modifiers::NONE);
+        assert_eq!(copied_text, "two");
+
+        // Shift-click into the middle of "three", which selects all of it
+        // (late enough not to be a triple-click):
+        time.set(time.get() + 1.0);
+        let three = pos_of(10);
+        run(
+            vec![
+                Event::PointerMoved(three),
+                button(three, true, Modifiers::SHIFT),
+            ],
+            Modifiers::SHIFT,
+        );
+        run(
+            vec![button(three, false, Modifiers::SHIFT)],
+            Modifiers::SHIFT,
+        );
+        let (_, copied_text) = run(vec![Event::Copy], Modifiers::NONE);
+        assert_eq!(copied_text, "two three");
+
+        // A new drag selects by characters again
+        // (late enough for the press not to be part of a double-click):
+        time.set(time.get() + 1.0);
+        let one = pos_of(1);
+        run(
+            vec![Event::PointerMoved(one), button(one, true, Modifiers::NONE)],
+            Modifiers::NONE,
+        );
+        run(vec![Event::PointerMoved(two)], Modifiers::NONE);
+        run(vec![button(two, false, Modifiers::NONE)], Modifiers::NONE);
+        let (_, copied_text) = run(vec![Event::Copy], Modifiers::NONE);
+        assert_eq!(copied_text, "ne t");
+
+        // Click "two", then press it again and drag into the middle of "three",
+        // which extends the selection by whole words while dragging:
+        time.set(time.get() + 1.0);
+        run(
+            vec![Event::PointerMoved(two), button(two, true, Modifiers::NONE)],
+            Modifiers::NONE,
+        );
+        run(vec![button(two, false, Modifiers::NONE)], Modifiers::NONE);
+        run(vec![button(two, true, Modifiers::NONE)], Modifiers::NONE);
+        for pos in [two + vec2(10.0, 0.0), three] {
+            run(vec![Event::PointerMoved(pos)], Modifiers::NONE);
+            let (_, copied_text) = run(vec![Event::Copy], Modifiers::NONE);
+            assert!(copied_text.starts_with("two"), "{copied_text:?}");
+        }
+        run(vec![button(three, false, Modifiers::NONE)], Modifiers::NONE);
+        let (_, copied_text) = run(vec![Event::Copy], Modifiers::NONE);
+        assert_eq!(copied_text, "two three");
+    }
+
+    #[test]
+    fn test_select_across_labels() {
+        let ctx = Context::default();
+        let lines = ["First message", "Second message", "Third message"];
+
+        let time = std::cell::Cell::new(0.0);
+        let run = |events: Vec<Event>| {
+            time.set(time.get() + 0.05);
+            let input = RawInput {
+                screen_rect: Some(Rect::from_min_size(Pos2::ZERO, vec2(800.0, 600.0))),
+                events,
+                time: Some(time.get()),
+                ..Default::default()
+            };
+            let mut rects = vec![];
+            let output = ctx.run(input, |ctx| {
+                CentralPanel::default().show(ctx, |ui| {
+                    rects = lines.iter().map(|line| ui.label(*line).rect).collect();
+                });
+            });
+            (rects, output.platform_output.copied_text)
+        };
+        let button = |pos: Pos2, pressed: bool| Event::PointerButton {
+            pos,
+            button: PointerButton::Primary,
+            pressed,
+            modifiers: Modifiers::NONE,
+        };
+
+        // Sweep from the start of the first label to the end of the last one:
+        let (rects, _) = run(vec![]);
+        let start = rects[0].left_center() + vec2(1.0, 0.0);
+        let end = rects[2].right_center() - vec2(1.0, 0.0);
+        run(vec![Event::PointerMoved(start), button(start, true)]);
+        run(vec![Event::PointerMoved(rects[1].center())]);
+        run(vec![Event::PointerMoved(end)]);
+        run(vec![button(end, false)]);
+
+        let (_, copied_text) = run(vec![Event::Copy]);
+        assert_eq!(copied_text, "First message\nSecond message\nThird message");
+    }
+
+    #[test]
+    fn test_moving_down_remembers_the_column() {
+        let ctx = Context::default();
+        let text = "long line one\nab\nlong line three";
+
+        let time = std::cell::Cell::new(0.0);
+        let run = |events: Vec<Event>| {
+            time.set(time.get() + 0.05);
+            let input = RawInput {
+                screen_rect: Some(Rect::from_min_size(Pos2::ZERO, vec2(800.0, 600.0))),
+                events,
+                time: Some(time.get()),
+                ..Default::default()
+            };
+            let mut label_pos = Pos2::ZERO;
+            let output = ctx.run(input, |ctx| {
+                CentralPanel::default().show(ctx, |ui| {
+                    label_pos = ui.label(text).rect.min;
+                });
+            });
+            (label_pos, output.platform_output.copied_text)
+        };
+        let key = |key: Key, modifiers: Modifiers| Event::Key {
+            key,
+            physical_key: None,
+            pressed: true,
+            repeat: false,
+            modifiers,
+        };
+
+        // Click before "one":
+        let (label_pos, _) = run(vec![]);
+        let font_id = TextStyle::Body.resolve(&ctx.style());
+        let galley =
+            ctx.fonts(|fonts| fonts.layout_no_wrap(text.to_owned(), font_id, Color32::WHITE));
+        let cursor = galley.from_ccursor(text::CCursor::new(10));
+        let pos = label_pos + galley.pos_from_cursor(&cursor).center().to_vec2();
+        run(vec![Event::PointerMoved(pos)]);
+        for pressed in [true, false] {
+            run(vec![Event::PointerButton {
+                pos,
+                button: PointerButton::Primary,
+                pressed,
+                modifiers: Modifiers::NONE,
+            }]);
+        }
+
+        // Move down past the short row, one frame at a time, then select to the end of the row:
+        run(vec![key(Key::ArrowDown, Modifiers::NONE)]);
+        run(vec![key(Key::ArrowDown, Modifiers::NONE)]);
+        run(vec![key(Key::End, Modifiers::SHIFT)]);
+        let (_, copied_text) = run(vec![Event::Copy]);
+        assert_eq!(copied_text, "three");
+    }
+}
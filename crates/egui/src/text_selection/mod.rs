@@ -4,10 +4,11 @@
 pub mod accesskit_text;
 
 mod cursor_range;
+pub mod html;
 mod label_text_selection;
 pub mod text_cursor_state;
 pub mod visuals;
 
 pub use cursor_range::{CCursorRange, CursorRange, PCursorRange};
 pub use label_text_selection::LabelSelectionState;
-pub use text_cursor_state::TextCursorState;
+pub use text_cursor_state::{BlockSelection, SelectionBoundary, TextCursorState};
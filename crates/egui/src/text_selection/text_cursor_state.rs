@@ -1,6 +1,9 @@
 //! Text cursor changes/interaction, without modifying the text.
 
+use std::ops::Range;
+
 use epaint::text::{cursor::*, Galley};
+use unicode_segmentation::UnicodeSegmentation as _;
 
 use crate::*;
 
@@ -18,6 +21,180 @@ pub struct TextCursorState {
     /// This is what is easiest to work with when editing text,
     /// so users are more likely to read/write this.
     ccursor_range: Option<CCursorRange>,
+
+    /// What the selection snaps to when it is extended with the pointer.
+    ///
+    /// Set by double-clicking (words) and triple-clicking (lines).
+    #[cfg_attr(feature = "serde", serde(skip))]
+    selection_boundary: SelectionBoundary,
+
+    /// The word or line that was double- or triple-clicked.
+    ///
+    /// It stays selected while the selection is extended with the pointer.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    initial_cursor_range: Option<CCursorRange>,
+
+    /// Where along the rows the cursor goes when moving up and down.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    preferred_x: Option<f32>,
+
+    /// A rectangular selection, made by alt-dragging.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    block_selection: Option<BlockSelection>,
+}
+
+/// A rectangular selection of the same part of several rows, made by alt-dragging.
+///
+/// This is for editing tabular text: typing replaces the selected part of every row.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BlockSelection {
+    /// The first selected row of the [`Galley`].
+    pub first_row: usize,
+
+    /// The last selected row of the [`Galley`] (inclusive).
+    pub last_row: usize,
+
+    /// The selected part of each row, in galley coordinates.
+    pub x_range: Rangef,
+}
+
+impl BlockSelection {
+    /// The block with a cursor in each of two opposite corners,
+    /// e.g. where a drag started and where the pointer is now.
+    pub fn between(galley: &Galley, a: &Cursor, b: &Cursor) -> Self {
+        let [a_x, b_x] = [a, b].map(|cursor| galley.pos_from_cursor(cursor).center().x);
+        let [min_row, max_row] = [
+            a.rcursor.row.min(b.rcursor.row),
+            a.rcursor.row.max(b.rcursor.row),
+        ];
+        Self {
+            first_row: min_row,
+            last_row: max_row,
+            x_range: Rangef::new(a_x.min(b_x), a_x.max(b_x)),
+        }
+    }
+
+    /// The selected rows of the [`Galley`].
+    pub fn rows(&self) -> Range<usize> {
+        self.first_row..self.last_row + 1
+    }
+
+    /// The selected characters, as one range per row, from the first row to the last.
+    ///
+    /// Rows that end before the block get an empty range at their end.
+    pub fn char_ranges(&self, galley: &Galley) -> Vec<CCursorRange> {
+        self.rows()
+            .filter_map(|row| {
+                let row_ref = galley.rows.get(row)?;
+                let [min, max] = [self.x_range.min, self.x_range.max].map(|x| {
+                    galley
+                        .from_rcursor(RCursor {
+                            row,
+                            column: row_ref.char_at(x),
+                        })
+                        .ccursor
+                });
+                Some(CCursorRange::two(min, max))
+            })
+            .collect()
+    }
+
+    /// The selected text, with a line for each row, even the rows that end before the block.
+    pub fn selected_text(&self, galley: &Galley) -> String {
+        self.char_ranges(galley)
+            .iter()
+            .map(|ccursor_range| {
+                let [min, max] = ccursor_range.sorted();
+                slice_char_range(galley.text(), min.index..max.index)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// What a selection snaps to when it is made or extended with the pointer.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SelectionBoundary {
+    /// Any character, e.g. after a single click.
+    #[default]
+    Character,
+
+    /// Whole words, e.g. after a double-click.
+    Word,
+
+    /// Parts of words, like `Camel` and `Case` in `CamelCase`, or `snake` and `case` in `snake_case`.
+    ///
+    /// See [`crate::style::Interaction::double_click_selects_subword`].
+    Subword,
+
+    /// Whole lines, including the newline that ends them, e.g. after a triple-click.
+    Line,
+}
+
+impl SelectionBoundary {
+    /// The range of the character, word or line at the given cursor.
+    ///
+    /// See [`crate::style::Interaction::underscore_is_word_char`].
+    pub fn select_bounded_at(
+        self,
+        text: &str,
+        ccursor: CCursor,
+        underscore_is_word_char: bool,
+    ) -> CCursorRange {
+        match self {
+            Self::Character => CCursorRange::one(ccursor),
+            Self::Word => select_word_at(text, ccursor, underscore_is_word_char),
+            Self::Subword => select_subword_at(text, ccursor),
+            Self::Line => select_line_at(text, ccursor),
+        }
+    }
+
+    /// Where to move the cursor to the right by one character, word or line,
+    /// e.g. with ctrl+right: the end of the next word after the cursor.
+    ///
+    /// Double-clicking the word selects up to the same place.
+    pub fn ccursor_next_bounded(
+        self,
+        text: &str,
+        ccursor: CCursor,
+        underscore_is_word_char: bool,
+    ) -> CCursor {
+        match self {
+            Self::Character => CCursor::new((ccursor.index + 1).min(text.chars().count())),
+            Self::Word => {
+                next_segment_end(text, &word_segments(text, underscore_is_word_char), ccursor)
+            }
+            Self::Subword => next_segment_end(text, &subword_segments(text), ccursor),
+            Self::Line => select_line_at(text, ccursor).primary,
+        }
+    }
+
+    /// Where to move the cursor to the left by one character, word or line,
+    /// e.g. with ctrl+left: the start of the previous word before the cursor.
+    ///
+    /// Double-clicking the word selects from the same place.
+    pub fn ccursor_previous_bounded(
+        self,
+        text: &str,
+        ccursor: CCursor,
+        underscore_is_word_char: bool,
+    ) -> CCursor {
+        let index = match self {
+            Self::Character => ccursor.index.saturating_sub(1),
+            Self::Word => {
+                previous_segment_start(&word_segments(text, underscore_is_word_char), ccursor)
+            }
+            Self::Subword => previous_segment_start(&subword_segments(text), ccursor),
+            Self::Line => {
+                let before = CCursor::new(ccursor.index.saturating_sub(1));
+                select_line_at(text, before).secondary.index
+            }
+        };
+        CCursor {
+            index,
+            prefer_next_row: true,
+        }
+    }
 }
 
 impl From<CursorRange> for TextCursorState {
@@ -28,6 +205,7 @@ impl From<CursorRange> for TextCursorState {
                 primary: cursor_range.primary.ccursor,
                 secondary: cursor_range.secondary.ccursor,
             }),
+            ..Default::default()
         }
     }
 }
@@ -37,6 +215,7 @@ impl From<CCursorRange> for TextCursorState {
         Self {
             cursor_range: None,
             ccursor_range: Some(ccursor_range),
+            ..Default::default()
         }
     }
 }
@@ -81,11 +260,175 @@ impl TextCursorState {
     pub fn set_char_range(&mut self, ccursor_range: Option<CCursorRange>) {
         self.cursor_range = None;
         self.ccursor_range = ccursor_range;
+        self.selection_boundary = SelectionBoundary::Character;
+        self.initial_cursor_range = None;
+        self.preferred_x = None;
+        self.block_selection = None;
     }
 
     pub fn set_range(&mut self, cursor_range: Option<CursorRange>) {
         self.cursor_range = cursor_range;
         self.ccursor_range = None;
+        self.selection_boundary = SelectionBoundary::Character;
+        self.initial_cursor_range = None;
+        self.preferred_x = None;
+        self.block_selection = None;
+    }
+
+    /// The rectangular selection made by alt-dragging, if any.
+    ///
+    /// While there is one, [`Self::range`] goes from the corner where the drag started
+    /// to the one at the pointer.
+    /// Setting the range with [`Self::set_char_range`] or [`Self::set_range`] removes it.
+    pub fn block_selection(&self) -> Option<&BlockSelection> {
+        self.block_selection.as_ref()
+    }
+
+    /// See [`Self::block_selection`].
+    pub fn set_block_selection(&mut self, block_selection: Option<BlockSelection>) {
+        self.block_selection = block_selection;
+    }
+
+    /// Where along the rows (see [`Galley::cursor_offset_along_row`])
+    /// the cursor goes when moving up and down, e.g. with the arrow keys.
+    ///
+    /// This is remembered while moving up and down, so that the cursor gets back
+    /// to the same column after passing shorter rows.
+    /// Setting the range (e.g. by clicking or moving sideways) clears it.
+    pub fn preferred_x(&self) -> Option<f32> {
+        self.preferred_x
+    }
+
+    /// See [`Self::preferred_x`].
+    pub fn set_preferred_x(&mut self, preferred_x: Option<f32>) {
+        self.preferred_x = preferred_x;
+    }
+
+    /// Select the word at the cursor, like double-clicking it does.
+    ///
+    /// The word stays selected if the selection is then extended with the pointer.
+    ///
+    /// See [`crate::style::Interaction::underscore_is_word_char`].
+    pub fn select_word_at(
+        &mut self,
+        galley: &Galley,
+        ccursor: CCursor,
+        underscore_is_word_char: bool,
+    ) {
+        self.select_bounded_at(
+            galley,
+            SelectionBoundary::Word,
+            ccursor,
+            underscore_is_word_char,
+        );
+    }
+
+    /// Select the line at the cursor, including its newline, like triple-clicking it does.
+    ///
+    /// The line stays selected if the selection is then extended with the pointer.
+    pub fn select_line_at(&mut self, galley: &Galley, ccursor: CCursor) {
+        self.select_bounded_at(galley, SelectionBoundary::Line, ccursor, true);
+    }
+
+    /// Select all the text.
+    pub fn select_all(&mut self, galley: &Galley) {
+        self.set_range(Some(CursorRange::select_all(galley)));
+        self.initial_cursor_range = self.char_range();
+    }
+
+    /// Select the word or line at the cursor, and keep it selected
+    /// while the selection is extended with the pointer.
+    fn select_bounded_at(
+        &mut self,
+        galley: &Galley,
+        boundary: SelectionBoundary,
+        ccursor: CCursor,
+        underscore_is_word_char: bool,
+    ) {
+        let ccursor_range =
+            boundary.select_bounded_at(galley.text(), ccursor, underscore_is_word_char);
+        self.set_range(Some(CursorRange {
+            primary: galley.from_ccursor(ccursor_range.primary),
+            secondary: galley.from_ccursor(ccursor_range.secondary),
+        }));
+        self.selection_boundary = boundary;
+        self.initial_cursor_range = Some(ccursor_range);
+    }
+
+    /// The word or line that was double- or triple-clicked, and what the selection
+    /// snaps to when it is extended from it with the pointer.
+    pub(crate) fn bounded_selection(&self) -> Option<(SelectionBoundary, CCursorRange)> {
+        self.initial_cursor_range
+            .map(|initial_cursor_range| (self.selection_boundary, initial_cursor_range))
+    }
+
+    /// See [`Self::bounded_selection`].
+    pub(crate) fn set_bounded_selection(
+        &mut self,
+        bounded_selection: Option<(SelectionBoundary, CCursorRange)>,
+    ) {
+        let (selection_boundary, initial_cursor_range) = bounded_selection.unzip();
+        self.selection_boundary = selection_boundary.unwrap_or_default();
+        self.initial_cursor_range = initial_cursor_range;
+    }
+
+    /// Select the block between where the drag started and the pointer.
+    fn extend_block_selection_to(&mut self, galley: &Galley, cursor_at_pointer: Cursor) {
+        let anchor = self
+            .range(galley)
+            .map_or(cursor_at_pointer, |cursor_range| cursor_range.secondary);
+        self.set_range(Some(CursorRange {
+            primary: cursor_at_pointer,
+            secondary: anchor,
+        }));
+        self.block_selection = Some(BlockSelection::between(galley, &anchor, &cursor_at_pointer));
+    }
+
+    /// Move the primary end of the selection to the pointer,
+    /// snapping to whole words or lines after a double- or triple-click.
+    pub(crate) fn extend_selection_to(
+        &mut self,
+        galley: &Galley,
+        cursor_at_pointer: Cursor,
+        underscore_is_word_char: bool,
+    ) {
+        let Some(mut cursor_range) = self.range(galley) else {
+            self.set_range(Some(CursorRange::one(cursor_at_pointer)));
+            return;
+        };
+
+        let Some(initial_cursor_range) = self.initial_cursor_range else {
+            cursor_range.primary = cursor_at_pointer;
+            self.set_range(Some(cursor_range));
+            return;
+        };
+
+        // Keep the initial word/line selected, and snap the end at the pointer.
+        // Whichever way we drag, the primary end is at the pointer and the secondary end
+        // on the far side of the initial word/line, so that e.g. shift+arrows afterwards
+        // move the end at the pointer:
+        let boundary = self.selection_boundary;
+        let [initial_min, initial_max] = initial_cursor_range.sorted();
+        let [pointer_min, pointer_max] = boundary
+            .select_bounded_at(
+                galley.text(),
+                cursor_at_pointer.ccursor,
+                underscore_is_word_char,
+            )
+            .sorted();
+        let [secondary, primary] = if pointer_min.index < initial_min.index {
+            [initial_max, pointer_min]
+        } else if initial_max.index < pointer_max.index {
+            [initial_min, pointer_max]
+        } else {
+            [initial_min, initial_max]
+        };
+        self.set_range(Some(CursorRange {
+            primary: galley.from_ccursor(primary),
+            secondary: galley.from_ccursor(secondary),
+        }));
+        self.selection_boundary = boundary;
+        self.initial_cursor_range = Some(initial_cursor_range);
     }
 }
 
@@ -101,43 +444,66 @@ impl TextCursorState {
         galley: &Galley,
         is_being_dragged: bool,
     ) -> bool {
-        let text = galley.text();
+        let underscore_is_word_char = ui.style().interaction.underscore_is_word_char;
 
-        if response.double_clicked() {
-            // Select word:
-            let ccursor_range = select_word_at(text, cursor_at_pointer.ccursor);
-            self.set_range(Some(CursorRange {
-                primary: galley.from_ccursor(ccursor_range.primary),
-                secondary: galley.from_ccursor(ccursor_range.secondary),
-            }));
-            true
-        } else if response.triple_clicked() {
+        let word_boundary = if ui.style().interaction.double_click_selects_subword {
+            SelectionBoundary::Subword
+        } else {
+            SelectionBoundary::Word
+        };
+
+        if response.triple_clicked() {
             // Select line:
-            let ccursor_range = select_line_at(text, cursor_at_pointer.ccursor);
-            self.set_range(Some(CursorRange {
-                primary: galley.from_ccursor(ccursor_range.primary),
-                secondary: galley.from_ccursor(ccursor_range.secondary),
-            }));
+            self.select_bounded_at(
+                galley,
+                SelectionBoundary::Line,
+                cursor_at_pointer.ccursor,
+                underscore_is_word_char,
+            );
+            true
+        } else if response.double_clicked() {
+            // Select word:
+            self.select_bounded_at(
+                galley,
+                word_boundary,
+                cursor_at_pointer.ccursor,
+                underscore_is_word_char,
+            );
             true
         } else if response.sense.drag {
             if response.hovered() && ui.input(|i| i.pointer.any_pressed()) {
                 // The start of a drag (or a click).
                 if ui.input(|i| i.modifiers.shift) {
-                    if let Some(mut cursor_range) = self.range(galley) {
-                        cursor_range.primary = cursor_at_pointer;
-                        self.set_range(Some(cursor_range));
-                    } else {
+                    // Extend the selection, by whole words/lines after a double/triple-click:
+                    self.extend_selection_to(galley, cursor_at_pointer, underscore_is_word_char);
+                } else {
+                    // The second or third press of a double- or triple-click selects the
+                    // word/line already, so that dragging extends it by whole words/lines:
+                    let boundary = match ui.input(|i| i.pointer.press_click_count()) {
+                        Some(2) => word_boundary,
+                        Some(3) => SelectionBoundary::Line,
+                        _ => SelectionBoundary::Character,
+                    };
+                    if boundary == SelectionBoundary::Character {
                         self.set_range(Some(CursorRange::one(cursor_at_pointer)));
+                    } else {
+                        // Sets the range first, and then the boundary:
+                        self.select_bounded_at(
+                            galley,
+                            boundary,
+                            cursor_at_pointer.ccursor,
+                            underscore_is_word_char,
+                        );
                     }
-                } else {
-                    self.set_range(Some(CursorRange::one(cursor_at_pointer)));
                 }
                 true
             } else if is_being_dragged {
                 // Drag to select text:
-                if let Some(mut cursor_range) = self.range(galley) {
-                    cursor_range.primary = cursor_at_pointer;
-                    self.set_range(Some(cursor_range));
+                if ui.input(|i| i.modifiers.alt) {
+                    // The same columns of each row:
+                    self.extend_block_selection_to(galley, cursor_at_pointer);
+                } else if self.range(galley).is_some() {
+                    self.extend_selection_to(galley, cursor_at_pointer, underscore_is_word_char);
                 }
                 true
             } else {
@@ -149,150 +515,273 @@ impl TextCursorState {
     }
 }
 
-fn select_word_at(text: &str, ccursor: CCursor) -> CCursorRange {
-    if ccursor.index == 0 {
-        CCursorRange::two(ccursor, ccursor_next_word(text, ccursor))
-    } else {
-        let it = text.chars();
-        let mut it = it.skip(ccursor.index - 1);
-        if let Some(char_before_cursor) = it.next() {
-            if let Some(char_after_cursor) = it.next() {
-                if is_word_char(char_before_cursor) && is_word_char(char_after_cursor) {
-                    let min = ccursor_previous_word(text, ccursor + 1);
-                    let max = ccursor_next_word(text, min);
-                    CCursorRange::two(min, max)
-                } else if is_word_char(char_before_cursor) {
-                    let min = ccursor_previous_word(text, ccursor);
-                    let max = ccursor_next_word(text, min);
-                    CCursorRange::two(min, max)
-                } else if is_word_char(char_after_cursor) {
-                    let max = ccursor_next_word(text, ccursor);
-                    CCursorRange::two(ccursor, max)
-                } else {
-                    let min = ccursor_previous_word(text, ccursor);
-                    let max = ccursor_next_word(text, ccursor);
-                    CCursorRange::two(min, max)
-                }
-            } else {
-                let min = ccursor_previous_word(text, ccursor);
-                CCursorRange::two(min, ccursor)
-            }
+/// How far to scroll this frame while drag-selecting text with the pointer at `pointer_pos`.
+///
+/// The speed is proportional to how far the pointer is outside `visible_rect`,
+/// up to [`crate::style::Interaction::text_drag_scroll_max_speed`].
+/// Only scrolls towards parts of `content_rect` that are hidden.
+/// A positive delta means the view should move right/down.
+pub fn drag_scroll_delta(
+    ui: &Ui,
+    content_rect: Rect,
+    visible_rect: Rect,
+    pointer_pos: Pos2,
+) -> Vec2 {
+    let interaction = &ui.style().interaction;
+    let max_speed = interaction.text_drag_scroll_max_speed;
+    let dt = ui.input(|i| i.stable_dt).at_most(0.1);
+
+    let mut delta = Vec2::ZERO;
+    for d in 0..2 {
+        let overshoot = if pointer_pos[d] < visible_rect.min[d]
+            && content_rect.min[d] < visible_rect.min[d]
+        {
+            pointer_pos[d] - visible_rect.min[d]
+        } else if visible_rect.max[d] < pointer_pos[d] && visible_rect.max[d] < content_rect.max[d]
+        {
+            pointer_pos[d] - visible_rect.max[d]
         } else {
-            let max = ccursor_next_word(text, ccursor);
-            CCursorRange::two(ccursor, max)
-        }
+            0.0
+        };
+        let speed = (overshoot * interaction.text_drag_scroll_speed).clamp(-max_speed, max_speed);
+        delta[d] = speed * dt;
     }
+    delta
 }
 
-fn select_line_at(text: &str, ccursor: CCursor) -> CCursorRange {
-    if ccursor.index == 0 {
-        CCursorRange::two(ccursor, ccursor_next_line(text, ccursor))
-    } else {
-        let it = text.chars();
-        let mut it = it.skip(ccursor.index - 1);
-        if let Some(char_before_cursor) = it.next() {
-            if let Some(char_after_cursor) = it.next() {
-                if (!is_linebreak(char_before_cursor)) && (!is_linebreak(char_after_cursor)) {
-                    let min = ccursor_previous_line(text, ccursor + 1);
-                    let max = ccursor_next_line(text, min);
-                    CCursorRange::two(min, max)
-                } else if !is_linebreak(char_before_cursor) {
-                    let min = ccursor_previous_line(text, ccursor);
-                    let max = ccursor_next_line(text, min);
-                    CCursorRange::two(min, max)
-                } else if !is_linebreak(char_after_cursor) {
-                    let max = ccursor_next_line(text, ccursor);
-                    CCursorRange::two(ccursor, max)
-                } else {
-                    let min = ccursor_previous_line(text, ccursor);
-                    let max = ccursor_next_line(text, ccursor);
-                    CCursorRange::two(min, max)
-                }
-            } else {
-                let min = ccursor_previous_line(text, ccursor);
-                CCursorRange::two(min, ccursor)
+/// Merge the cursor ranges that overlap, so that each character is selected at most once.
+///
+/// Ranges that overlap the primary one are merged into it.
+/// Empty ranges (plain cursors) are also merged with ranges they touch.
+pub fn merge_overlapping_ranges(primary: &mut CCursorRange, extra: &mut Vec<CCursorRange>) {
+    fn overlaps(a: &CCursorRange, b: &CCursorRange) -> bool {
+        let [a_min, a_max] = a.sorted();
+        let [b_min, b_max] = b.sorted();
+        if a_min.index == a_max.index || b_min.index == b_max.index {
+            a_min.index <= b_max.index && b_min.index <= a_max.index
+        } else {
+            a_min.index < b_max.index && b_min.index < a_max.index
+        }
+    }
+
+    /// Grow `a` to also cover `b`, keeping the direction of `a`.
+    fn merge(a: &mut CCursorRange, b: &CCursorRange) {
+        let [a_min, a_max] = a.sorted();
+        let [b_min, b_max] = b.sorted();
+        let min = if b_min.index < a_min.index {
+            b_min
+        } else {
+            a_min
+        };
+        let max = if a_max.index < b_max.index {
+            b_max
+        } else {
+            a_max
+        };
+        *a = if a.primary.index < a.secondary.index {
+            CCursorRange {
+                primary: min,
+                secondary: max,
             }
         } else {
-            let max = ccursor_next_line(text, ccursor);
-            CCursorRange::two(ccursor, max)
+            CCursorRange::two(min, max)
+        };
+    }
+
+    extra.sort_by_key(|range| range.sorted().map(|ccursor| ccursor.index));
+
+    let mut merged: Vec<CCursorRange> = Vec::with_capacity(extra.len());
+    for range in extra.drain(..) {
+        if overlaps(primary, &range) {
+            merge(primary, &range);
+        } else if let Some(last) = merged.last_mut().filter(|last| overlaps(last, &range)) {
+            merge(last, &range);
+        } else {
+            merged.push(range);
         }
     }
+
+    // The primary range may have grown to overlap ranges that came before it:
+    merged.retain(|range| {
+        if overlaps(primary, range) {
+            merge(primary, range);
+            false
+        } else {
+            true
+        }
+    });
+
+    *extra = merged;
 }
 
-pub fn ccursor_next_word(text: &str, ccursor: CCursor) -> CCursor {
-    CCursor {
-        index: next_word_boundary_char_index(text.chars(), ccursor.index),
-        prefer_next_row: false,
-    }
+/// The word at the cursor, or else the whitespace or punctuation at the cursor.
+///
+/// See [`crate::style::Interaction::underscore_is_word_char`].
+pub fn select_word_at(text: &str, ccursor: CCursor, underscore_is_word_char: bool) -> CCursorRange {
+    select_segment_at(&word_segments(text, underscore_is_word_char), ccursor)
 }
 
-fn ccursor_next_line(text: &str, ccursor: CCursor) -> CCursor {
-    CCursor {
-        index: next_line_boundary_char_index(text.chars(), ccursor.index),
-        prefer_next_row: false,
-    }
+/// The part of a word at the cursor, like `Camel` in `CamelCase`,
+/// or else the whitespace or punctuation at the cursor.
+///
+/// See [`SelectionBoundary::Subword`].
+pub fn select_subword_at(text: &str, ccursor: CCursor) -> CCursorRange {
+    select_segment_at(&subword_segments(text), ccursor)
 }
 
-pub fn ccursor_previous_word(text: &str, ccursor: CCursor) -> CCursor {
-    let num_chars = text.chars().count();
-    CCursor {
-        index: num_chars
-            - next_word_boundary_char_index(text.chars().rev(), num_chars - ccursor.index),
-        prefer_next_row: true,
-    }
+fn select_segment_at(segments: &[(Range<usize>, bool)], ccursor: CCursor) -> CCursorRange {
+    let after = segments
+        .iter()
+        .find(|(range, _)| range.contains(&ccursor.index));
+    let before = segments
+        .iter()
+        .find(|(range, _)| range.end == ccursor.index);
+
+    // Prefer a word over whatever is on the other side of the cursor:
+    let segment = match (before, after) {
+        (_, Some(after)) if after.1 => Some(after),
+        (Some(before), _) if before.1 => Some(before),
+        _ => after.or(before),
+    };
+
+    segment.map_or(CCursorRange::one(ccursor), |(range, _)| {
+        CCursorRange::two(CCursor::new(range.start), CCursor::new(range.end))
+    })
+}
+
+/// The line at the cursor, including the newline that ends it (if any).
+pub fn select_line_at(text: &str, ccursor: CCursor) -> CCursorRange {
+    let min = text
+        .chars()
+        .take(ccursor.index)
+        .enumerate()
+        .filter(|&(_, c)| c == '\n')
+        .last()
+        .map_or(0, |(index, _)| index + 1);
+    let max = text
+        .chars()
+        .enumerate()
+        .skip(ccursor.index)
+        .find(|&(_, c)| c == '\n')
+        .map_or_else(|| text.chars().count(), |(index, _)| index + 1);
+    CCursorRange::two(CCursor::new(min), CCursor::new(max))
+}
+
+/// The end of the next word after the cursor.
+///
+/// See [`crate::style::Interaction::underscore_is_word_char`].
+pub fn ccursor_next_word(text: &str, ccursor: CCursor, underscore_is_word_char: bool) -> CCursor {
+    SelectionBoundary::Word.ccursor_next_bounded(text, ccursor, underscore_is_word_char)
 }
 
-fn ccursor_previous_line(text: &str, ccursor: CCursor) -> CCursor {
-    let num_chars = text.chars().count();
+/// The start of the previous word before the cursor.
+///
+/// See [`crate::style::Interaction::underscore_is_word_char`].
+pub fn ccursor_previous_word(
+    text: &str,
+    ccursor: CCursor,
+    underscore_is_word_char: bool,
+) -> CCursor {
+    SelectionBoundary::Word.ccursor_previous_bounded(text, ccursor, underscore_is_word_char)
+}
+
+fn next_segment_end(text: &str, segments: &[(Range<usize>, bool)], ccursor: CCursor) -> CCursor {
+    let index = segments
+        .iter()
+        .find(|(range, is_word)| *is_word && ccursor.index < range.end)
+        .map_or_else(|| text.chars().count(), |(range, _)| range.end);
     CCursor {
-        index: num_chars
-            - next_line_boundary_char_index(text.chars().rev(), num_chars - ccursor.index),
-        prefer_next_row: true,
+        index,
+        prefer_next_row: false,
     }
 }
 
-fn next_word_boundary_char_index(it: impl Iterator<Item = char>, mut index: usize) -> usize {
-    let mut it = it.skip(index);
-    if let Some(_first) = it.next() {
-        index += 1;
+fn previous_segment_start(segments: &[(Range<usize>, bool)], ccursor: CCursor) -> usize {
+    segments
+        .iter()
+        .rev()
+        .find(|(range, is_word)| *is_word && range.start < ccursor.index)
+        .map_or(0, |(range, _)| range.start)
+}
+
+/// Split the text into words and the whitespace and punctuation between them,
+/// using Unicode word segmentation.
+///
+/// Returns the char ranges of all segments, and whether or not each is a word.
+/// A word with letters that aren't ASCII, like "café", is one word,
+/// and text without spaces between words (e.g. Chinese) is split into short segments.
+fn word_segments(text: &str, underscore_is_word_char: bool) -> Vec<(Range<usize>, bool)> {
+    let mut segments = vec![];
+    let mut index = 0;
 
-        if let Some(second) = it.next() {
-            index += 1;
-            for next in it {
-                if is_word_char(next) != is_word_char(second) {
-                    break;
+    for segment in text.split_word_bounds() {
+        if underscore_is_word_char || !segment.contains('_') {
+            let start = index;
+            index += segment.chars().count();
+            let is_word = segment
+                .chars()
+                .any(|c| c.is_alphanumeric() || (underscore_is_word_char && c == '_'));
+            segments.push((start..index, is_word));
+        } else {
+            // Split e.g. `snake_case` into `snake`, `_` and `case`:
+            let mut start = index;
+            let mut is_underscore = segment.starts_with('_');
+            for c in segment.chars() {
+                if (c == '_') != is_underscore {
+                    segments.push((start..index, !is_underscore));
+                    start = index;
+                    is_underscore = c == '_';
                 }
                 index += 1;
             }
+            segments.push((start..index, !is_underscore));
         }
     }
-    index
+
+    segments
 }
 
-fn next_line_boundary_char_index(it: impl Iterator<Item = char>, mut index: usize) -> usize {
-    let mut it = it.skip(index);
-    if let Some(_first) = it.next() {
-        index += 1;
+/// Like [`word_segments`], but also split words into their parts,
+/// e.g. `snake_case` into `snake`, `_` and `case`, `CamelCase` into `Camel` and `Case`,
+/// and `utf8` into `utf` and `8`.
+fn subword_segments(text: &str) -> Vec<(Range<usize>, bool)> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut segments = vec![];
 
-        if let Some(second) = it.next() {
-            index += 1;
-            for next in it {
-                if is_linebreak(next) != is_linebreak(second) {
-                    break;
-                }
-                index += 1;
+    for (range, is_word) in word_segments(text, false) {
+        if !is_word {
+            segments.push((range, false));
+            continue;
+        }
+
+        let mut start = range.start;
+        for i in range.start + 1..range.end {
+            if is_subword_boundary(chars[i - 1], chars[i], chars.get(i + 1).copied()) {
+                segments.push((start..i, true));
+                start = i;
             }
         }
+        segments.push((start..range.end, true));
     }
-    index
+
+    segments
 }
 
-pub fn is_word_char(c: char) -> bool {
-    c.is_ascii_alphanumeric() || c == '_'
+/// Is there a sub-word boundary between `a` and `b`, which is followed by `after_b`?
+///
+/// True for `camel|Case`, `XML|Http` and `utf|8`.
+fn is_subword_boundary(a: char, b: char, after_b: Option<char>) -> bool {
+    let camel_hump = a.is_lowercase() && b.is_uppercase();
+    let end_of_acronym =
+        a.is_uppercase() && b.is_uppercase() && after_b.is_some_and(char::is_lowercase);
+    let letters_and_digits =
+        a.is_alphanumeric() && b.is_alphanumeric() && a.is_numeric() != b.is_numeric();
+    camel_hump || end_of_acronym || letters_and_digits
 }
 
-fn is_linebreak(c: char) -> bool {
-    c == '\r' || c == '\n'
+/// Is this character part of a word?
+pub fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
 }
 
 /// Accepts and returns character offset (NOT byte offset!).
@@ -316,6 +805,56 @@ pub fn find_line_start(text: &str, current_index: CCursor) -> CCursor {
     }
 }
 
+/// Find the bracket matching the one at the cursor: the char after the cursor, or else the one before it.
+///
+/// Knows about `()`, `[]` and `{}`, and skips nested pairs of the same kind.
+/// Returns the char offset of the matching bracket, or `None` if there is no bracket at the cursor
+/// or it is unmatched.
+///
+/// Use this in a [`crate::TextEdit::layouter_with_context`] to e.g. highlight the matching bracket.
+pub fn find_matching_bracket(text: &str, ccursor: CCursor) -> Option<CCursor> {
+    const BRACKETS: [(char, char); 3] = [('(', ')'), ('[', ']'), ('{', '}')];
+
+    let chars: Vec<char> = text.chars().collect();
+    for index in [Some(ccursor.index), ccursor.index.checked_sub(1)]
+        .into_iter()
+        .flatten()
+    {
+        let Some(&c) = chars.get(index) else {
+            continue;
+        };
+        if let Some(&(open, close)) = BRACKETS.iter().find(|(open, _)| *open == c) {
+            let mut depth = 0;
+            for (i, &c) in chars.iter().enumerate().skip(index) {
+                if c == open {
+                    depth += 1;
+                } else if c == close {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Some(CCursor::new(i));
+                    }
+                }
+            }
+            return None;
+        }
+        if let Some(&(open, close)) = BRACKETS.iter().find(|(_, close)| *close == c) {
+            let mut depth = 0;
+            for (i, &c) in chars.iter().enumerate().take(index + 1).rev() {
+                if c == close {
+                    depth += 1;
+                } else if c == open {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Some(CCursor::new(i));
+                    }
+                }
+            }
+            return None;
+        }
+    }
+    None
+}
+
 pub fn byte_index_from_char_index(s: &str, char_index: usize) -> usize {
     for (ci, (bi, _)) in s.char_indices().enumerate() {
         if ci == char_index {
@@ -337,9 +876,443 @@ pub fn cursor_rect(galley_pos: Pos2, galley: &Galley, cursor: &Cursor, row_heigh
     let mut cursor_pos = galley
         .pos_from_cursor(cursor)
         .translate(galley_pos.to_vec2());
-    cursor_pos.max.y = cursor_pos.max.y.at_least(cursor_pos.min.y + row_height);
+    if !galley.is_vertical() {
+        cursor_pos.max.y = cursor_pos.max.y.at_least(cursor_pos.min.y + row_height);
+    }
     // Handle completely empty galleys
     cursor_pos = cursor_pos.expand(1.5);
     // slightly above/below row
     cursor_pos
 }
+
+#[cfg(test)]
+mod tests {
+    use epaint::text::{FontDefinitions, Fonts};
+
+    use super::*;
+
+    /// Double-click the word at `clicked`, then drag to `dragged`.
+    fn word_drag(galley: &Galley, clicked: usize, dragged: usize) -> CursorRange {
+        let mut state = TextCursorState::default();
+        state.select_bounded_at(galley, SelectionBoundary::Word, CCursor::new(clicked), true);
+        state.extend_selection_to(galley, galley.from_ccursor(CCursor::new(dragged)), true);
+        state.range(galley).unwrap()
+    }
+
+    #[test]
+    fn test_word_drag() {
+        let fonts = Fonts::new(1.0, 1024, FontDefinitions::default());
+        let galley = fonts.layout_no_wrap(
+            "one two three four".to_owned(),
+            FontId::default(),
+            Color32::WHITE,
+        );
+        let shift_right = |mut cursor_range: CursorRange| {
+            let shift = Modifiers::SHIFT;
+            cursor_range.on_key_press(
+                crate::os::OperatingSystem::Windows,
+                &galley,
+                &shift,
+                Key::ArrowRight,
+                &crate::style::Interaction::default(),
+                &mut None,
+            );
+            cursor_range.as_ccursor_range()
+        };
+
+        // Drag to the right, from "two" into "three":
+        let cursor_range = word_drag(&galley, 5, 10);
+        assert_eq!(cursor_range.secondary.ccursor.index, 4);
+        assert_eq!(cursor_range.primary.ccursor.index, 13);
+        let ccursor_range = shift_right(cursor_range);
+        assert_eq!(ccursor_range.secondary.index, 4);
+        assert_eq!(ccursor_range.primary.index, 14);
+
+        // Drag to the left, from "three" into "one":
+        let cursor_range = word_drag(&galley, 10, 1);
+        assert_eq!(cursor_range.secondary.ccursor.index, 13);
+        assert_eq!(cursor_range.primary.ccursor.index, 0);
+        let ccursor_range = shift_right(cursor_range);
+        assert_eq!(ccursor_range.secondary.index, 13);
+        assert_eq!(ccursor_range.primary.index, 1);
+
+        // Drag back into the initial word:
+        let cursor_range = word_drag(&galley, 10, 9);
+        assert_eq!(cursor_range.secondary.ccursor.index, 8);
+        assert_eq!(cursor_range.primary.ccursor.index, 13);
+    }
+
+    fn select_line(text: &str, index: usize) -> &str {
+        let [min, max] = SelectionBoundary::Line
+            .select_bounded_at(text, CCursor::new(index), true)
+            .sorted();
+        slice_char_range(text, min.index..max.index)
+    }
+
+    fn select_word(text: &str, index: usize, underscore_is_word_char: bool) -> &str {
+        let [min, max] = SelectionBoundary::Word
+            .select_bounded_at(text, CCursor::new(index), underscore_is_word_char)
+            .sorted();
+        slice_char_range(text, min.index..max.index)
+    }
+
+    #[test]
+    fn test_select_word() {
+        let text = "Un café, s'il vous plaît!";
+        assert_eq!(select_word(text, 0, true), "Un");
+        assert_eq!(select_word(text, 2, true), "Un"); // right after the word
+        assert_eq!(select_word(text, 5, true), "café");
+        assert_eq!(select_word(text, 7, true), "café");
+        assert_eq!(select_word(text, 10, true), "s'il");
+        assert_eq!(select_word(text, 21, true), "plaît");
+        assert_eq!(select_word("a  ,  b", 3, true), ",");
+        assert_eq!(select_word("a    b", 3, true), "    ");
+
+        // Don't select a whole line of text without spaces:
+        let text = "我喜欢吃苹果。";
+        let word = select_word(text, 1, true);
+        assert!(!word.is_empty() && word.len() < text.len(), "{word:?}");
+
+        let text = "let snake_case = 42;";
+        assert_eq!(select_word(text, 6, true), "snake_case");
+        assert_eq!(select_word(text, 6, false), "snake");
+        assert_eq!(select_word(text, 12, false), "case");
+        assert_eq!(select_word(text, 17, false), "42");
+    }
+
+    #[test]
+    fn test_next_and_previous_word() {
+        let text = "Ça va, très_bien?";
+        let next = |index| ccursor_next_word(text, CCursor::new(index), true).index;
+        let previous = |index| ccursor_previous_word(text, CCursor::new(index), true).index;
+        assert_eq!(next(0), 2);
+        assert_eq!(next(2), 5);
+        assert_eq!(next(5), 16);
+        assert_eq!(next(16), 17);
+        assert_eq!(previous(17), 7);
+        assert_eq!(previous(7), 3);
+        assert_eq!(previous(3), 0);
+
+        let next = |index| ccursor_next_word(text, CCursor::new(index), false).index;
+        assert_eq!(next(5), 11);
+        assert_eq!(next(11), 16);
+    }
+
+    #[test]
+    fn test_subwords() {
+        let text = "let parseXMLHttpRequest = snake_case_v2;";
+        let select_subword = |index| {
+            let [min, max] = SelectionBoundary::Subword
+                .select_bounded_at(text, CCursor::new(index), true)
+                .sorted();
+            slice_char_range(text, min.index..max.index)
+        };
+        assert_eq!(select_subword(4), "parse");
+        assert_eq!(select_subword(10), "XML");
+        assert_eq!(select_subword(13), "Http");
+        assert_eq!(select_subword(20), "Request");
+        assert_eq!(select_subword(27), "snake");
+        assert_eq!(select_subword(33), "case");
+        assert_eq!(select_subword(37), "v");
+        assert_eq!(select_subword(38), "2");
+
+        let next = |index| {
+            SelectionBoundary::Subword
+                .ccursor_next_bounded(text, CCursor::new(index), true)
+                .index
+        };
+        assert_eq!(next(4), 9);
+        assert_eq!(next(9), 12);
+        assert_eq!(next(12), 16);
+        assert_eq!(next(23), 31);
+        assert_eq!(next(31), 36);
+        assert_eq!(next(36), 38);
+
+        let previous = |index| {
+            SelectionBoundary::Subword
+                .ccursor_previous_bounded(text, CCursor::new(index), true)
+                .index
+        };
+        assert_eq!(previous(23), 16);
+        assert_eq!(previous(16), 12);
+        assert_eq!(previous(36), 32);
+    }
+
+    #[test]
+    fn test_subword_keys() {
+        let fonts = Fonts::new(1.0, 1024, FontDefinitions::default());
+        let galley =
+            fonts.layout_no_wrap("parseXMLHttp".to_owned(), FontId::default(), Color32::WHITE);
+        let interaction = crate::style::Interaction::default();
+
+        for os in [
+            crate::os::OperatingSystem::Mac,
+            crate::os::OperatingSystem::Windows,
+        ] {
+            let mut cursor_range = CursorRange::one(galley.begin());
+            let modifiers = Modifiers::CTRL | Modifiers::ALT | Modifiers::SHIFT;
+            cursor_range.on_key_press(
+                os,
+                &galley,
+                &modifiers,
+                Key::ArrowRight,
+                &interaction,
+                &mut None,
+            );
+            cursor_range.on_key_press(
+                os,
+                &galley,
+                &modifiers,
+                Key::ArrowRight,
+                &interaction,
+                &mut None,
+            );
+            assert_eq!(cursor_range.primary.ccursor.index, 8, "{os:?}");
+            assert_eq!(cursor_range.secondary.ccursor.index, 0, "{os:?}");
+        }
+    }
+
+    #[test]
+    fn test_home_and_end() {
+        let fonts = Fonts::new(1.0, 1024, FontDefinitions::default());
+        let galley = fonts.layout_no_wrap(
+            "first\n    indented line\nlast".to_owned(),
+            FontId::default(),
+            Color32::WHITE,
+        );
+        let interaction = crate::style::Interaction::default();
+        let press = |cursor_range: &mut CursorRange, modifiers: Modifiers, key: Key| {
+            cursor_range.on_key_press(
+                crate::os::OperatingSystem::Windows,
+                &galley,
+                &modifiers,
+                key,
+                &interaction,
+                &mut None,
+            );
+            [
+                cursor_range.secondary.ccursor.index,
+                cursor_range.primary.ccursor.index,
+            ]
+        };
+
+        // Home toggles between the indentation and the start of the row:
+        let mut cursor_range = CursorRange::one(galley.from_ccursor(CCursor::new(15)));
+        assert_eq!(
+            press(&mut cursor_range, Modifiers::NONE, Key::Home),
+            [10, 10]
+        );
+        assert_eq!(press(&mut cursor_range, Modifiers::NONE, Key::Home), [6, 6]);
+        assert_eq!(
+            press(&mut cursor_range, Modifiers::NONE, Key::Home),
+            [10, 10]
+        );
+        assert_eq!(
+            press(&mut cursor_range, Modifiers::NONE, Key::End),
+            [23, 23]
+        );
+
+        // …and extends the selection with shift:
+        assert_eq!(
+            press(&mut cursor_range, Modifiers::SHIFT, Key::Home),
+            [23, 10]
+        );
+        assert_eq!(
+            press(&mut cursor_range, Modifiers::SHIFT, Key::Home),
+            [23, 6]
+        );
+
+        // Ctrl+Home/End go to the start and end of the text:
+        let ctrl_shift = Modifiers::CTRL | Modifiers::SHIFT;
+        assert_eq!(press(&mut cursor_range, ctrl_shift, Key::End), [23, 28]);
+        assert_eq!(press(&mut cursor_range, ctrl_shift, Key::Home), [23, 0]);
+        assert_eq!(
+            press(&mut cursor_range, Modifiers::CTRL, Key::End),
+            [28, 28]
+        );
+    }
+
+    #[test]
+    fn test_merge_overlapping_ranges() {
+        let range = |secondary, primary| CCursorRange {
+            primary: CCursor::new(primary),
+            secondary: CCursor::new(secondary),
+        };
+
+        // Backwards selection overlapping two others, which are merged into it:
+        let mut primary = range(6, 4);
+        let mut extra = vec![range(10, 10), range(5, 8), range(0, 2), range(2, 5)];
+        merge_overlapping_ranges(&mut primary, &mut extra);
+        assert_eq!(primary, range(8, 2));
+        assert_eq!(extra, [range(0, 2), range(10, 10)]);
+
+        // A cursor at the edge of a selection is merged into it,
+        // but selections that only touch are kept apart:
+        let mut primary = range(3, 3);
+        let mut extra = vec![range(0, 3), range(3, 6)];
+        merge_overlapping_ranges(&mut primary, &mut extra);
+        assert_eq!(primary, range(0, 3));
+        assert_eq!(extra, [range(3, 6)]);
+    }
+
+    #[test]
+    fn test_select_programmatically() {
+        let fonts = Fonts::new(1.0, 1024, FontDefinitions::default());
+        let galley = fonts.layout_no_wrap(
+            "one two\nthree four\nfive".to_owned(),
+            FontId::default(),
+            Color32::WHITE,
+        );
+        let char_range = |state: &TextCursorState| {
+            let [min, max] = state.char_range().unwrap().sorted();
+            min.index..max.index
+        };
+
+        let mut state = TextCursorState::default();
+        state.select_word_at(&galley, CCursor::new(5), true);
+        assert_eq!(char_range(&state), 4..7);
+        state.extend_selection_to(&galley, galley.from_ccursor(CCursor::new(10)), true);
+        assert_eq!(char_range(&state), 4..13);
+
+        state.select_line_at(&galley, CCursor::new(10));
+        assert_eq!(char_range(&state), 8..19);
+        state.extend_selection_to(&galley, galley.from_ccursor(CCursor::new(2)), true);
+        assert_eq!(char_range(&state), 0..19);
+
+        state.select_all(&galley);
+        assert_eq!(char_range(&state), 0..23);
+        state.extend_selection_to(&galley, galley.from_ccursor(CCursor::new(2)), true);
+        assert_eq!(char_range(&state), 0..23);
+    }
+
+    #[test]
+    fn test_keyboard_and_double_click_select_the_same_words() {
+        let text = "Hello, wörld! foo_bar  (baz) 42 café... «quoted» l'été";
+        let fonts = Fonts::new(1.0, 1024, FontDefinitions::default());
+        let galley = fonts.layout_no_wrap(text.to_owned(), FontId::default(), Color32::WHITE);
+        let interaction = crate::style::Interaction::default();
+        let ctrl_shift = Modifiers::CTRL | Modifiers::SHIFT;
+        let os = crate::os::OperatingSystem::Windows;
+
+        let words = word_segments(text, true)
+            .into_iter()
+            .filter(|(_, is_word)| *is_word);
+        for (range, _) in words {
+            let double_clicked = select_word_at(text, CCursor::new(range.start + 1), true).sorted();
+            let double_clicked = [double_clicked[0].index, double_clicked[1].index];
+            assert_eq!(double_clicked, [range.start, range.end]);
+
+            // Select the word with ctrl+shift+right from its start:
+            let mut cursor_range = CursorRange::one(galley.from_ccursor(CCursor::new(range.start)));
+            cursor_range.on_key_press(
+                os,
+                &galley,
+                &ctrl_shift,
+                Key::ArrowRight,
+                &interaction,
+                &mut None,
+            );
+            let [min, max] = cursor_range.sorted_cursors();
+            assert_eq!([min.ccursor.index, max.ccursor.index], double_clicked);
+
+            // …and with ctrl+shift+left from its end:
+            let mut cursor_range = CursorRange::one(galley.from_ccursor(CCursor::new(range.end)));
+            cursor_range.on_key_press(
+                os,
+                &galley,
+                &ctrl_shift,
+                Key::ArrowLeft,
+                &interaction,
+                &mut None,
+            );
+            let [min, max] = cursor_range.sorted_cursors();
+            assert_eq!([min.ccursor.index, max.ccursor.index], double_clicked);
+        }
+    }
+
+    #[test]
+    fn test_line_and_character_bounds() {
+        let text = "ab\ncd\n";
+        let next = |boundary: SelectionBoundary, index| {
+            boundary
+                .ccursor_next_bounded(text, CCursor::new(index), true)
+                .index
+        };
+        let previous = |boundary: SelectionBoundary, index| {
+            boundary
+                .ccursor_previous_bounded(text, CCursor::new(index), true)
+                .index
+        };
+        assert_eq!(next(SelectionBoundary::Character, 0), 1);
+        assert_eq!(next(SelectionBoundary::Character, 6), 6);
+        assert_eq!(previous(SelectionBoundary::Character, 0), 0);
+        assert_eq!(next(SelectionBoundary::Line, 1), 3);
+        assert_eq!(next(SelectionBoundary::Line, 3), 6);
+        assert_eq!(previous(SelectionBoundary::Line, 5), 3);
+        assert_eq!(previous(SelectionBoundary::Line, 3), 0);
+    }
+
+    #[test]
+    fn test_select_line() {
+        let text = "first\nsecond\n\nlast";
+        assert_eq!(select_line(text, 0), "first\n");
+        assert_eq!(select_line(text, 5), "first\n");
+        assert_eq!(select_line(text, 6), "second\n");
+        assert_eq!(select_line(text, 9), "second\n");
+
+        // An empty line is just its newline:
+        assert_eq!(select_line(text, 13), "\n");
+
+        // The last line has no newline:
+        assert_eq!(select_line(text, 14), "last");
+        assert_eq!(select_line(text, 18), "last");
+        assert_eq!(select_line("ends with newline\n", 18), "");
+
+        // The whole line break is included:
+        let text = "first\r\nsecond\r\n";
+        assert_eq!(select_line(text, 2), "first\r\n");
+        assert_eq!(select_line(text, 7), "second\r\n");
+
+        // Multi-byte characters:
+        assert_eq!(select_line("café\nthé", 7), "thé");
+    }
+
+    #[test]
+    fn test_find_matching_bracket() {
+        let matching = |text: &str, index: usize| {
+            find_matching_bracket(text, CCursor::new(index)).map(|ccursor| ccursor.index)
+        };
+
+        let text = "f(a[0], (b)) {}";
+        assert_eq!(matching(text, 1), Some(11), "the bracket after the cursor");
+        assert_eq!(matching(text, 12), Some(1), "the bracket before the cursor");
+        assert_eq!(matching(text, 8), Some(10));
+        assert_eq!(matching(text, 10), Some(8));
+        assert_eq!(matching(text, 3), Some(5));
+        assert_eq!(matching(text, 14), Some(13));
+        assert_eq!(matching(text, 0), None, "no bracket at the cursor");
+        assert_eq!(matching("(()", 0), None, "unmatched");
+    }
+
+    #[test]
+    fn test_double_click_right_half_of_last_letter() {
+        let fonts = Fonts::new(1.0, 1024, FontDefinitions::default());
+        let galley = fonts.layout_no_wrap("one two".to_owned(), FontId::default(), Color32::WHITE);
+
+        // The cursor goes after the "e", but the word is still the one that was clicked:
+        let e = galley.rows[0].glyphs[2];
+        let pos = vec2(e.pos.x + 0.75 * e.size.x, galley.rows[0].rect.center().y);
+        let cursor_at_pointer = galley.cursor_from_pos(pos);
+        assert_eq!(cursor_at_pointer.ccursor.index, 3);
+
+        let mut state = TextCursorState::default();
+        state.select_bounded_at(
+            &galley,
+            SelectionBoundary::Word,
+            cursor_at_pointer.ccursor,
+            true,
+        );
+        let [min, max] = state.char_range().unwrap().sorted();
+        assert_eq!((min.index, max.index), (0, 3));
+    }
+}
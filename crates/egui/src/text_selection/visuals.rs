@@ -2,7 +2,7 @@ use crate::*;
 
 use self::layers::ShapeIdx;
 
-use super::CursorRange;
+use super::{BlockSelection, CursorRange};
 
 pub fn paint_text_selection(
     painter: &Painter,
@@ -19,47 +19,123 @@ pub fn paint_text_selection(
     // We paint the cursor selection on top of the text, so make it transparent:
     let color = visuals.selection.bg_fill.linear_multiply(0.5);
     let [min, max] = cursor_range.sorted_cursors();
-    let min = min.rcursor;
-    let max = max.rcursor;
 
-    for ri in min.row..=max.row {
-        let row = &galley.rows[ri];
-        let left = if ri == min.row {
-            row.x_offset(min.column)
-        } else {
-            row.rect.left()
-        };
-        let right = if ri == max.row {
-            row.x_offset(max.column)
-        } else {
-            let newline_size = if row.ends_with_newline {
-                row.height() / 2.0 // visualize that we select the newline
-            } else {
-                0.0
-            };
-            row.rect.right() + newline_size
-        };
-        let rect = Rect::from_min_max(
-            galley_pos + vec2(left, row.min_y()),
-            galley_pos + vec2(right, row.max_y()),
-        );
-        let shape_idx = painter.rect_filled(rect, 0.0, color);
+    let rects: Vec<Rect> = galley
+        .rects_for_char_range(min.ccursor.index..max.ccursor.index)
+        .into_iter()
+        .map(|rect| rect.translate(galley_pos.to_vec2()))
+        .collect();
+    for (rect, rounding) in merged_selection_rects(&rects, visuals.selection.rounding) {
+        let shape_idx = painter.rect_filled(rect, rounding, color);
         if let Some(out_shaped_idx) = &mut out_shaped_idx {
             out_shaped_idx.push(shape_idx);
         }
     }
 }
 
+/// Extend the rectangle of each selected row down to the next row,
+/// so that they form one region, and round only the outer corners of that region.
+pub(crate) fn merged_selection_rects(rects: &[Rect], rounding: Rounding) -> Vec<(Rect, Rounding)> {
+    // Is `below` the next row down from `above`, and do they overlap horizontally?
+    let is_row_below = |above: &Rect, below: &Rect| {
+        above.min.y < below.min.y && above.min.x < below.max.x && below.min.x < above.max.x
+    };
+
+    (0..rects.len())
+        .map(|i| {
+            let mut rect = rects[i];
+            let prev = i
+                .checked_sub(1)
+                .map(|i| rects[i])
+                .filter(|prev| is_row_below(prev, &rect));
+            let next = rects
+                .get(i + 1)
+                .copied()
+                .filter(|next| is_row_below(&rect, next));
+
+            let mut corners = rounding;
+            if let Some(prev) = prev {
+                if prev.min.x <= rect.min.x {
+                    corners.nw = 0.0;
+                }
+                if rect.max.x <= prev.max.x {
+                    corners.ne = 0.0;
+                }
+            }
+            if let Some(next) = next {
+                if next.min.x <= rect.min.x {
+                    corners.sw = 0.0;
+                }
+                if rect.max.x <= next.max.x {
+                    corners.se = 0.0;
+                }
+                rect.max.y = rect.max.y.max(next.min.y);
+            }
+            (rect, corners)
+        })
+        .collect()
+}
+
+/// Paint a background for the characters in the given range of a galley,
+/// e.g. to highlight search results.
+///
+/// Each row the range covers gets its own rectangle.
+/// Paint this before the galley to have it behind the text.
+pub fn paint_text_highlight(
+    painter: &Painter,
+    galley_pos: Pos2,
+    galley: &Galley,
+    char_range: std::ops::Range<usize>,
+    color: Color32,
+) -> Vec<ShapeIdx> {
+    galley
+        .rects_for_char_range(char_range)
+        .into_iter()
+        .map(|rect| painter.rect_filled(rect.translate(galley_pos.to_vec2()), 0.0, color))
+        .collect()
+}
+
+/// Underline the characters in the given range of a galley,
+/// e.g. the text that is being composed with an IME.
+pub fn paint_text_underline(
+    painter: &Painter,
+    galley_pos: Pos2,
+    galley: &Galley,
+    char_range: std::ops::Range<usize>,
+    stroke: Stroke,
+) {
+    for rect in galley.rects_for_char_range(char_range) {
+        let rect = rect.translate(galley_pos.to_vec2());
+        painter.hline(rect.x_range(), rect.bottom(), stroke);
+    }
+}
+
+/// Paint the selected part of each row of a [`BlockSelection`].
+pub fn paint_block_selection(
+    painter: &Painter,
+    visuals: &Visuals,
+    galley_pos: Pos2,
+    galley: &Galley,
+    block_selection: &BlockSelection,
+) {
+    // We paint the cursor selection on top of the text, so make it transparent:
+    let color = visuals.selection.bg_fill.linear_multiply(0.5);
+
+    for row in &galley.rows[block_selection.first_row.min(galley.rows.len())
+        ..block_selection.rows().end.min(galley.rows.len())]
+    {
+        let rect = Rect::from_x_y_ranges(block_selection.x_range, row.rect.y_range());
+        painter.rect_filled(rect.translate(galley_pos.to_vec2()), 0.0, color);
+    }
+}
+
 /// Paint one end of the selection, e.g. the primary cursor.
 ///
 /// This will never blink.
 pub fn paint_cursor_end(painter: &Painter, visuals: &Visuals, cursor_rect: Rect) {
     let stroke = visuals.text_cursor.stroke;
 
-    let top = cursor_rect.center_top();
-    let bottom = cursor_rect.center_bottom();
-
-    painter.line_segment([top, bottom], (stroke.width, stroke.color));
+    let [top, bottom] = paint_cursor_line(painter, stroke, cursor_rect);
 
     if false {
         // Roof/floor:
@@ -76,14 +152,70 @@ pub fn paint_cursor_end(painter: &Painter, visuals: &Visuals, cursor_rect: Rect)
     }
 }
 
-/// Paint one end of the selection, e.g. the primary cursor, with blinking (if enabled).
+/// Paint the cursor of a read-only [`TextEdit`], using [`crate::style::TextCursorStyle::read_only_stroke`].
+///
+/// This will never blink.
+pub fn paint_read_only_cursor(painter: &Painter, visuals: &Visuals, cursor_rect: Rect) {
+    paint_cursor_line(painter, visuals.text_cursor.read_only_stroke, cursor_rect);
+}
+
+/// Paint the other end of a selection, where it started,
+/// using [`crate::style::TextCursorStyle::secondary_stroke`].
+///
+/// This will never blink.
+pub fn paint_secondary_cursor(painter: &Painter, visuals: &Visuals, cursor_rect: Rect) {
+    let stroke = visuals.text_cursor.secondary_stroke;
+    if !stroke.is_empty() {
+        paint_cursor_line(painter, stroke, cursor_rect);
+    }
+}
+
+/// Returns the two ends of the painted line.
+fn paint_cursor_line(painter: &Painter, stroke: Stroke, cursor_rect: Rect) -> [Pos2; 2] {
+    let points = if cursor_rect.width() > cursor_rect.height() {
+        // Vertical text, where the cursor lies across the column:
+        [cursor_rect.left_center(), cursor_rect.right_center()]
+    } else {
+        [cursor_rect.center_top(), cursor_rect.center_bottom()]
+    };
+
+    painter.line_segment(points, (stroke.width, stroke.color));
+    points
+}
+
+/// Paint one end of the selection, e.g. the primary cursor, with blinking (if `blink` is set).
+///
+/// Usually `blink` is [`crate::style::TextCursorStyle::blink`].
 pub fn paint_text_cursor(
     ui: &Ui,
     painter: &Painter,
     primary_cursor_rect: Rect,
     time_since_last_edit: f64,
+    blink: bool,
 ) {
-    if ui.visuals().text_cursor.blink {
+    paint_blinking(ui, time_since_last_edit, blink, || {
+        paint_cursor_end(painter, ui.visuals(), primary_cursor_rect);
+    });
+}
+
+/// Paint the cursor of overwrite mode as a block covering the character that typing replaces,
+/// with blinking (if `blink` is set).
+pub fn paint_block_cursor(
+    ui: &Ui,
+    painter: &Painter,
+    block_rect: Rect,
+    time_since_last_edit: f64,
+    blink: bool,
+) {
+    paint_blinking(ui, time_since_last_edit, blink, || {
+        // We paint the cursor on top of the text, so make it transparent:
+        let color = ui.visuals().text_cursor.stroke.color.linear_multiply(0.5);
+        painter.rect_filled(block_rect, 0.0, color);
+    });
+}
+
+fn paint_blinking(ui: &Ui, time_since_last_edit: f64, blink: bool, paint: impl FnOnce()) {
+    if blink {
         let on_duration = ui.visuals().text_cursor.on_duration;
         let off_duration = ui.visuals().text_cursor.off_duration;
         let total_duration = on_duration + off_duration;
@@ -92,7 +224,7 @@ pub fn paint_text_cursor(
 
         let wake_in = if time_in_cycle < on_duration {
             // Cursor is visible
-            paint_cursor_end(painter, ui.visuals(), primary_cursor_rect);
+            paint();
             on_duration - time_in_cycle
         } else {
             // Cursor is not visible
@@ -102,6 +234,79 @@ pub fn paint_text_cursor(
         ui.ctx()
             .request_repaint_after(std::time::Duration::from_secs_f32(wake_in));
     } else {
-        paint_cursor_end(painter, ui.visuals(), primary_cursor_rect);
+        paint();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use epaint::text::{FontDefinitions, Fonts, LayoutJob, TextFormat};
+
+    use super::*;
+
+    #[test]
+    fn test_merged_selection_rects() {
+        let r = 3.0;
+        let rounding = Rounding::same(r);
+
+        // A selection from the middle of a row to the middle of the row two rows down,
+        // with gaps between the rows:
+        let rects = [
+            Rect::from_min_max(pos2(50.0, 0.0), pos2(200.0, 10.0)),
+            Rect::from_min_max(pos2(0.0, 12.0), pos2(200.0, 22.0)),
+            Rect::from_min_max(pos2(0.0, 24.0), pos2(80.0, 34.0)),
+        ];
+        assert_eq!(
+            merged_selection_rects(&rects, rounding),
+            vec![
+                (
+                    Rect::from_min_max(pos2(50.0, 0.0), pos2(200.0, 12.0)),
+                    Rounding {
+                        nw: r,
+                        ne: r,
+                        sw: 0.0,
+                        se: 0.0
+                    }
+                ),
+                (
+                    Rect::from_min_max(pos2(0.0, 12.0), pos2(200.0, 24.0)),
+                    Rounding {
+                        nw: r,
+                        ne: 0.0,
+                        sw: 0.0,
+                        se: r
+                    }
+                ),
+                (
+                    Rect::from_min_max(pos2(0.0, 24.0), pos2(80.0, 34.0)),
+                    Rounding {
+                        nw: 0.0,
+                        ne: 0.0,
+                        sw: r,
+                        se: r
+                    }
+                ),
+            ]
+        );
+
+        // The same for a wrapped galley:
+        let fonts = Fonts::new(1.0, 1024, FontDefinitions::default());
+        let font_id = TextFormat::default().font_id;
+        let word_width = fonts
+            .layout_no_wrap("three ".to_owned(), font_id, Color32::WHITE)
+            .size()
+            .x;
+        let mut job = LayoutJob::default();
+        job.append("one two three", 0.0, TextFormat::default());
+        job.wrap.max_width = word_width + 1.0; // one word per row
+        let galley = fonts.layout_job(job);
+        let rects = galley.rects_for_char_range(1..12);
+        assert_eq!(rects.len(), 3, "{rects:?}");
+        let merged = merged_selection_rects(&rects, rounding);
+        for pair in merged.windows(2) {
+            assert_eq!(pair[0].0.bottom(), pair[1].0.top(), "no gaps between rows");
+        }
+        assert_eq!(merged[0].1.nw, r);
+        assert_eq!(merged[2].1.se, r);
     }
 }
@@ -452,6 +452,20 @@ impl Ui {
         self.painter.set_clip_rect(clip_rect);
     }
 
+    /// How rounded the corners of [`Self::clip_rect`] are.
+    #[inline]
+    pub fn clip_rounding(&self) -> Rounding {
+        self.painter.clip_rounding()
+    }
+
+    /// Round the corners of [`Self::clip_rect`], e.g. to keep the contents inside a rounded frame.
+    ///
+    /// Anything painted outside the rounded corners is cut away.
+    /// Child uis whose clip rect shares a corner with this one keep that corner rounded.
+    pub fn set_clip_rounding(&mut self, clip_rounding: impl Into<Rounding>) {
+        self.painter.set_clip_rounding(clip_rounding);
+    }
+
     /// Can be used for culling: if `false`, then no part of `rect` will be visible on screen.
     pub fn is_rect_visible(&self, rect: Rect) -> bool {
         self.is_visible() && rect.intersects(self.clip_rect())
@@ -171,6 +171,15 @@ where
         self.flux = None;
     }
 
+    /// Add an undo point for the state we are just about to change,
+    /// no matter how long it has been stable.
+    ///
+    /// The redos are cleared, as they no longer apply after the change.
+    pub fn add_undo_before_change(&mut self, current_state: &State) {
+        self.redos.clear();
+        self.add_undo(current_state);
+    }
+
     /// Call this as often as you want (e.g. every frame)
     /// and [`Undoer`] will determine if a new undo point should be created.
     ///
@@ -26,9 +26,12 @@ pub struct RichText {
     size: Option<f32>,
     extra_letter_spacing: f32,
     line_height: Option<f32>,
+    font_features: Vec<(crate::text::FourCC, u32)>,
     family: Option<FontFamily>,
     text_style: Option<TextStyle>,
     background_color: Color32,
+    outline: Option<(f32, Color32)>,
+    shadow: Option<crate::Shadow>,
     text_color: Option<Color32>,
     code: bool,
     strong: bool,
@@ -127,6 +130,23 @@ impl RichText {
         self
     }
 
+    /// `OpenType` features to turn on (`1`) or off (`0`), e.g. `&[("tnum", 1)]` for tabular numbers.
+    ///
+    /// See [`crate::text::TextFormat::font_features`].
+    ///
+    /// ```
+    /// # use egui::RichText;
+    /// RichText::new("1234").font_features(&[("tnum", 1)]);
+    /// ```
+    #[inline]
+    pub fn font_features(mut self, font_features: &[(&str, u32)]) -> Self {
+        self.font_features = font_features
+            .iter()
+            .map(|&(tag, value)| (crate::text::four_cc(tag), value))
+            .collect();
+        self
+    }
+
     /// Select the font family.
     ///
     /// This overrides the value from [`Self::text_style`].
@@ -182,6 +202,9 @@ impl RichText {
     }
 
     /// Extra strong text (stronger color).
+    ///
+    /// If the font family has a bold variant in [`crate::FontDefinitions::strong_families`]
+    /// (e.g. from [`crate::FontDefinitions::add_faux_bold_strong`]), that is used too.
     #[inline]
     pub fn strong(mut self) -> Self {
         self.strong = true;
@@ -246,6 +269,24 @@ impl RichText {
         self
     }
 
+    /// Paint an outline around the text, e.g. to make it readable over any background.
+    ///
+    /// See [`crate::text::TextFormat::outline`].
+    #[inline]
+    pub fn outline(mut self, width: f32, color: impl Into<Color32>) -> Self {
+        self.outline = Some((width, color.into()));
+        self
+    }
+
+    /// Paint a drop shadow behind the text.
+    ///
+    /// See [`crate::text::TextFormat::shadow`].
+    #[inline]
+    pub fn shadow(mut self, shadow: crate::Shadow) -> Self {
+        self.shadow = Some(shadow);
+        self
+    }
+
     /// Override text color.
     ///
     /// If not set, [`Color32::PLACEHOLDER`] will be used,
@@ -336,13 +377,16 @@ impl RichText {
             size,
             extra_letter_spacing,
             line_height,
+            font_features,
             family,
             text_style,
             background_color,
+            outline,
+            shadow,
             text_color: _, // already used by `get_text_color`
             code,
-            strong: _, // already used by `get_text_color`
-            weak: _,   // already used by `get_text_color`
+            strong,
+            weak: _, // already used by `get_text_color`
             strikethrough,
             underline,
             italics,
@@ -395,12 +439,17 @@ impl RichText {
                 font_id,
                 extra_letter_spacing,
                 line_height,
+                font_features,
                 color: text_color,
                 background: background_color,
+                outline,
+                shadow,
                 italics,
+                strong,
                 underline,
                 strikethrough,
                 valign,
+                ..Default::default()
             },
         )
     }
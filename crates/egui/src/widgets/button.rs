@@ -18,6 +18,9 @@ use crate::*;
 /// }
 /// # });
 /// ```
+///
+/// Like in a [`Label`], sections of the text with a [`crate::text::TextFormat::link`] are links.
+/// Clicking one also clicks the button, so check [`Response::clicked_link`] first.
 #[must_use = "You should put this widget in an ui with `ui.add(widget);`"]
 pub struct Button<'a> {
     image: Option<Image<'a>>,
@@ -34,6 +37,7 @@ pub struct Button<'a> {
     min_size: Vec2,
     rounding: Option<Rounding>,
     selected: bool,
+    link_urls: Vec<(usize, String)>,
 }
 
 impl<'a> Button<'a> {
@@ -67,6 +71,7 @@ impl<'a> Button<'a> {
             min_size: Vec2::ZERO,
             rounding: None,
             selected: false,
+            link_urls: Vec::new(),
         }
     }
 
@@ -170,6 +175,14 @@ impl<'a> Button<'a> {
         self.selected = selected;
         self
     }
+
+    /// Open `url` when the link with the given [`crate::text::TextFormat::link`] tag is clicked,
+    /// see [`Label::link_url`].
+    #[inline]
+    pub fn link_url(mut self, link: usize, url: impl Into<String>) -> Self {
+        self.link_urls.push((link, url.into()));
+        self
+    }
 }
 
 impl Widget for Button<'_> {
@@ -187,6 +200,7 @@ impl Widget for Button<'_> {
             min_size,
             rounding,
             selected,
+            link_urls,
         } = self;
 
         let frame = frame.unwrap_or_else(|| ui.visuals().button_frame);
@@ -239,6 +253,9 @@ impl Widget for Button<'_> {
 
         let galley =
             text.map(|text| text.into_galley(ui, wrap_mode, text_wrap_width, TextStyle::Button));
+        let sense = galley.as_ref().map_or(sense, |galley| {
+            widgets::label::link_sense(&galley.job, sense)
+        });
 
         let mut desired_size = Vec2::ZERO;
         if image.is_some() {
@@ -263,6 +280,7 @@ impl Widget for Button<'_> {
         desired_size = desired_size.at_least(min_size);
 
         let (rect, mut response) = ui.allocate_at_least(desired_size, sense);
+        let mut hovered_link = None;
         response.widget_info(|| {
             if let Some(galley) = &galley {
                 WidgetInfo::labeled(WidgetType::Button, galley.text())
@@ -327,7 +345,7 @@ impl Widget for Button<'_> {
                 cursor_x += ui.spacing().icon_spacing;
             }
 
-            if let Some(galley) = galley {
+            if let Some(mut galley) = galley {
                 let text_pos = if image.is_some() || shortcut_galley.is_some() {
                     pos2(cursor_x, rect.center().y - 0.5 * galley.size().y)
                 } else {
@@ -336,6 +354,13 @@ impl Widget for Button<'_> {
                         .align_size_within_rect(galley.size(), rect.shrink2(button_padding))
                         .min
                 };
+                hovered_link = widgets::label::handle_links(
+                    ui,
+                    &mut response,
+                    text_pos,
+                    &mut galley,
+                    &link_urls,
+                );
                 ui.painter().galley(text_pos, galley, visuals.text_color());
             }
 
@@ -353,7 +378,7 @@ impl Widget for Button<'_> {
         }
 
         if let Some(cursor) = ui.visuals().interact_cursor {
-            if response.hovered {
+            if response.hovered && hovered_link.is_none() {
                 ui.ctx().set_cursor_icon(cursor);
             }
         }
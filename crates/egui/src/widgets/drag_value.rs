@@ -9,6 +9,12 @@ use crate::*;
 type NumFormatter<'a> = Box<dyn 'a + Fn(f64, RangeInclusive<usize>) -> String>;
 type NumParser<'a> = Box<dyn 'a + Fn(&str) -> Option<f64>>;
 
+/// Where [`DragValue::input_rejected`] is stored, relative to the id of the widget.
+const REJECTED_INPUT_ID: &str = "rejected_input";
+
+/// Where the value from before a keyboard edit is kept, relative to the id of the widget.
+const EDIT_START_ID: &str = "edit_start";
+
 // ----------------------------------------------------------------------------
 
 /// Combined into one function (rather than two) to make it easier
@@ -42,6 +48,8 @@ pub struct DragValue<'a> {
     max_decimals: Option<usize>,
     custom_formatter: Option<NumFormatter<'a>>,
     custom_parser: Option<NumParser<'a>>,
+    expressions: bool,
+    units: Vec<(String, f64)>,
     update_while_editing: bool,
 }
 
@@ -74,6 +82,8 @@ impl<'a> DragValue<'a> {
             max_decimals: None,
             custom_formatter: None,
             custom_parser: None,
+            expressions: false,
+            units: Vec::new(),
             update_while_editing: true,
         }
     }
@@ -232,6 +242,54 @@ impl<'a> DragValue<'a> {
         self
     }
 
+    /// Evaluate what the user types as an expression, like `2*pi`, `1/3` or `(1 + 2) * 3`.
+    ///
+    /// Supports `+ - * / ^`, parentheses, the constants `pi`, `tau` and `e`,
+    /// and [units](Self::unit) after numbers, like `1m + 20cm`.
+    ///
+    /// Not used with a [`Self::custom_parser`].
+    ///
+    /// Default: `false`.
+    #[inline]
+    pub fn expressions(mut self, expressions: bool) -> Self {
+        self.expressions = expressions;
+        self
+    }
+
+    /// Let the user type the value in another unit.
+    ///
+    /// `factor` converts from `unit` to the unit of the value.
+    /// The [`Self::suffix`] is always understood as a unit (with a factor of 1),
+    /// and a typed [`Self::prefix`] is ignored.
+    ///
+    /// Not used with a [`Self::custom_parser`].
+    ///
+    /// ```
+    /// # egui::__run_test_ui(|ui| {
+    /// # let mut meters: f32 = 0.0;
+    /// // Typing "15mm" sets `meters` to 0.015:
+    /// ui.add(egui::DragValue::new(&mut meters)
+    ///     .suffix(" m")
+    ///     .unit("cm", 0.01)
+    ///     .unit("mm", 0.001));
+    /// # });
+    /// ```
+    #[inline]
+    pub fn unit(mut self, unit: impl ToString, factor: f64) -> Self {
+        self.units.push((unit.to_string(), factor));
+        self
+    }
+
+    /// Was text that couldn't be parsed just entered into the [`DragValue`] with this response?
+    ///
+    /// The value is then left unchanged.
+    pub fn input_rejected(response: &Response) -> bool {
+        response
+            .ctx
+            .data(|d| d.get_temp::<bool>(response.id.with(REJECTED_INPUT_ID)))
+            .unwrap_or(false)
+    }
+
     /// Set `custom_formatter` and `custom_parser` to display and parse numbers as binary integers. Floating point
     /// numbers are *not* supported.
     ///
@@ -368,9 +426,16 @@ impl<'a> Widget for DragValue<'a> {
             max_decimals,
             custom_formatter,
             custom_parser,
+            expressions,
+            units,
             update_while_editing,
         } = self;
 
+        let parse = |text: &str| match &custom_parser {
+            Some(parser) => parser(text),
+            None => parse_value(text, &prefix, &suffix, expressions, &units),
+        };
+
         let shift = ui.input(|i| i.modifiers.shift_only());
         // The widget has the same ID whether it's in edit or button mode.
         let id = ui.next_auto_id();
@@ -386,8 +451,20 @@ impl<'a> Widget for DragValue<'a> {
         });
 
         if ui.memory_mut(|mem| mem.gained_focus(id)) {
-            ui.data_mut(|data| data.remove::<String>(id));
+            ui.data_mut(|data| {
+                data.remove::<String>(id);
+                data.remove::<f64>(id.with(EDIT_START_ID));
+            });
         }
+        if is_kb_editing {
+            // Remember the value from when the edit started, so that we can go back to it
+            // if the edit is rejected, even after `update_while_editing` has applied parts of it:
+            let value = get(&mut get_set_value);
+            ui.data_mut(|data| {
+                data.get_temp_mut_or_insert_with(id.with(EDIT_START_ID), || value);
+            });
+        }
+        ui.data_mut(|data| data.remove::<bool>(id.with(REJECTED_INPUT_ID)));
 
         let old_value = get(&mut get_set_value);
         let mut value = old_value;
@@ -465,16 +542,19 @@ impl<'a> Widget for DragValue<'a> {
 
         if ui.memory(|mem| mem.lost_focus(id)) {
             let value_text = ui.data_mut(|data| data.remove_temp::<String>(id));
+            let edit_start = ui.data_mut(|data| data.remove_temp::<f64>(id.with(EDIT_START_ID)));
             if let Some(value_text) = value_text {
                 // We were editing the value as text last frame, but lost focus.
                 // Make sure we applied the last text value:
-                let parsed_value = match &custom_parser {
-                    Some(parser) => parser(&value_text),
-                    None => value_text.parse().ok(),
-                };
-                if let Some(parsed_value) = parsed_value {
+                if let Some(parsed_value) = parse(&value_text) {
                     let parsed_value = clamp_to_range(parsed_value, clamp_range.clone());
                     set(&mut get_set_value, parsed_value);
+                } else {
+                    // Go back to the value from before the edit:
+                    if let Some(edit_start) = edit_start {
+                        set(&mut get_set_value, edit_start);
+                    }
+                    ui.data_mut(|data| data.insert_temp(id.with(REJECTED_INPUT_ID), true));
                 }
             }
         }
@@ -504,16 +584,32 @@ impl<'a> Widget for DragValue<'a> {
                 // Update only when the edit has lost focus.
                 response.lost_focus()
             };
+            let parsed_value = parse(&value_text);
             if update {
-                let parsed_value = match &custom_parser {
-                    Some(parser) => parser(&value_text),
-                    None => value_text.parse().ok(),
-                };
                 if let Some(parsed_value) = parsed_value {
                     let parsed_value = clamp_to_range(parsed_value, clamp_range.clone());
                     set(&mut get_set_value, parsed_value);
                 }
             }
+            if response.lost_focus() {
+                let edit_start =
+                    ui.data_mut(|data| data.remove_temp::<f64>(id.with(EDIT_START_ID)));
+                if parsed_value.is_none() {
+                    // Go back to the value from before the edit, which `update_while_editing`
+                    // may have replaced with a valid prefix of the rejected text:
+                    if let Some(edit_start) = edit_start {
+                        set(&mut get_set_value, edit_start);
+                    }
+                    ui.data_mut(|data| data.insert_temp(id.with(REJECTED_INPUT_ID), true));
+                }
+            }
+            if parsed_value.is_none() && ui.is_rect_visible(response.rect) {
+                ui.painter().rect_stroke(
+                    response.rect,
+                    ui.visuals().widgets.active.rounding,
+                    Stroke::new(1.0, ui.visuals().error_fg_color),
+                );
+            }
             ui.data_mut(|data| data.insert_temp(id, value_text));
             response
         } else {
@@ -647,6 +743,209 @@ impl<'a> Widget for DragValue<'a> {
     }
 }
 
+/// The default parser: a number (or [expression](DragValue::expressions)) with an optional unit.
+fn parse_value(
+    text: &str,
+    prefix: &str,
+    suffix: &str,
+    expressions: bool,
+    units: &[(String, f64)],
+) -> Option<f64> {
+    let text = text.trim();
+    let text = text.strip_prefix(prefix.trim()).unwrap_or(text).trim();
+
+    let suffix = suffix.trim();
+    let units: Vec<(&str, f64)> = std::iter::once((suffix, 1.0))
+        .filter(|(suffix, _)| !suffix.is_empty())
+        .chain(units.iter().map(|(unit, factor)| (unit.as_str(), *factor)))
+        .collect();
+
+    if expressions {
+        Expression::parse(text, &units).filter(|value| value.is_finite())
+    } else {
+        text.parse().ok().or_else(|| {
+            units.iter().find_map(|&(unit, factor)| {
+                let number: f64 = text.strip_suffix(unit)?.trim_end().parse().ok()?;
+                Some(factor * number)
+            })
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Token<'s> {
+    Number(f64),
+    Operator(char),
+    Word(&'s str),
+}
+
+/// Evaluates [`DragValue::expressions`] by recursive descent.
+struct Expression<'s, 'u> {
+    tokens: Vec<Token<'s>>,
+    next: usize,
+    units: &'u [(&'u str, f64)],
+}
+
+impl<'s, 'u> Expression<'s, 'u> {
+    const OPERATORS: &'static str = "+-*/^()";
+
+    fn parse(text: &'s str, units: &'u [(&'u str, f64)]) -> Option<f64> {
+        let mut parser = Self {
+            tokens: Self::tokenize(text)?,
+            next: 0,
+            units,
+        };
+        let value = parser.sum()?;
+        (parser.next == parser.tokens.len()).then_some(value)
+    }
+
+    fn tokenize(text: &'s str) -> Option<Vec<Token<'s>>> {
+        let mut tokens = vec![];
+        let mut rest = text.trim_start();
+        while let Some(c) = rest.chars().next() {
+            let len = if c.is_ascii_digit() || c == '.' {
+                let len = number_len(rest);
+                tokens.push(Token::Number(rest[..len].parse().ok()?));
+                len
+            } else if Self::OPERATORS.contains(c) {
+                tokens.push(Token::Operator(c));
+                c.len_utf8()
+            } else {
+                let len = rest
+                    .find(|c: char| {
+                        c.is_whitespace() || c.is_ascii_digit() || Self::OPERATORS.contains(c)
+                    })
+                    .unwrap_or(rest.len());
+                tokens.push(Token::Word(&rest[..len]));
+                len
+            };
+            rest = rest[len..].trim_start();
+        }
+        Some(tokens)
+    }
+
+    fn peek(&self) -> Option<Token<'s>> {
+        self.tokens.get(self.next).copied()
+    }
+
+    fn eat(&mut self, operator: char) -> bool {
+        let found = self.peek() == Some(Token::Operator(operator));
+        self.next += found as usize;
+        found
+    }
+
+    /// `a + b - c`
+    fn sum(&mut self) -> Option<f64> {
+        let mut value = self.product()?;
+        loop {
+            if self.eat('+') {
+                value += self.product()?;
+            } else if self.eat('-') {
+                value -= self.product()?;
+            } else {
+                return Some(value);
+            }
+        }
+    }
+
+    /// `a * b / c`
+    fn product(&mut self) -> Option<f64> {
+        let mut value = self.signed()?;
+        loop {
+            if self.eat('*') {
+                value *= self.signed()?;
+            } else if self.eat('/') {
+                value /= self.signed()?;
+            } else {
+                return Some(value);
+            }
+        }
+    }
+
+    /// `-a`
+    fn signed(&mut self) -> Option<f64> {
+        if self.eat('-') {
+            Some(-self.signed()?)
+        } else if self.eat('+') {
+            self.signed()
+        } else {
+            self.power()
+        }
+    }
+
+    /// `a ^ b`, where `b` may be signed, e.g. `10^-3`.
+    fn power(&mut self) -> Option<f64> {
+        let base = self.atom()?;
+        if self.eat('^') {
+            Some(base.powf(self.signed()?))
+        } else {
+            Some(base)
+        }
+    }
+
+    /// A number or parenthesis, optionally followed by a unit (or a constant, as in `2pi`), or a constant.
+    fn atom(&mut self) -> Option<f64> {
+        let value = match self.peek()? {
+            Token::Number(number) => {
+                self.next += 1;
+                number
+            }
+            Token::Operator('(') => {
+                self.next += 1;
+                let value = self.sum()?;
+                self.eat(')').then_some(value)?
+            }
+            Token::Word(word) => {
+                self.next += 1;
+                return constant(word);
+            }
+            Token::Operator(_) => return None,
+        };
+        if let Some(Token::Word(word)) = self.peek() {
+            self.next += 1;
+            let factor = self
+                .units
+                .iter()
+                .find(|(unit, _)| *unit == word)
+                .map(|&(_, factor)| factor)
+                .or_else(|| constant(word))?;
+            Some(factor * value)
+        } else {
+            Some(value)
+        }
+    }
+}
+
+/// The length of the number at the start of `text`, like `12`, `.5` or `1.5e-3`.
+fn number_len(text: &str) -> usize {
+    let bytes = text.as_bytes();
+    let digits = |start: usize| {
+        start
+            + bytes[start..]
+                .iter()
+                .take_while(|b| b.is_ascii_digit() || **b == b'.')
+                .count()
+    };
+    let len = digits(0);
+    // An exponent, but not the constant `e`:
+    if matches!(bytes.get(len), Some(b'e' | b'E')) {
+        let sign = matches!(bytes.get(len + 1), Some(b'+' | b'-')) as usize;
+        if bytes.get(len + 1 + sign).is_some_and(u8::is_ascii_digit) {
+            return digits(len + 1 + sign);
+        }
+    }
+    len
+}
+
+fn constant(word: &str) -> Option<f64> {
+    match word.to_lowercase().as_str() {
+        "pi" | "π" => Some(std::f64::consts::PI),
+        "tau" | "τ" => Some(std::f64::consts::TAU),
+        "e" => Some(std::f64::consts::E),
+        _ => None,
+    }
+}
+
 fn clamp_to_range(x: f64, range: RangeInclusive<f64>) -> f64 {
     let (mut min, mut max) = (*range.start(), *range.end());
 
@@ -665,7 +964,8 @@ fn clamp_to_range(x: f64, range: RangeInclusive<f64>) -> f64 {
 
 #[cfg(test)]
 mod tests {
-    use super::clamp_to_range;
+    use super::{clamp_to_range, parse_value};
+    use crate::*;
 
     macro_rules! total_assert_eq {
         ($a:expr, $b:expr) => {
@@ -691,4 +991,88 @@ mod tests {
         total_assert_eq!(5.0_f64, clamp_to_range(15.0, 5.0..=1.0));
         total_assert_eq!(1.0_f64, clamp_to_range(-5.0, 5.0..=1.0));
     }
+
+    #[test]
+    fn test_parse_value() {
+        let units = [("cm".to_owned(), 0.01), ("mm".to_owned(), 0.001)];
+        let parse =
+            |text: &str, expressions: bool| parse_value(text, "x: ", " m", expressions, &units);
+
+        assert_eq!(parse("1.5", false), Some(1.5));
+        assert_eq!(parse("x: 2 m", false), Some(2.0));
+        assert_eq!(parse("15mm", false), Some(0.015));
+        assert_eq!(parse("2*3", false), None);
+
+        assert_eq!(parse("2 * (3 + 4)", true), Some(14.0));
+        assert_eq!(parse("-2^2", true), Some(-4.0));
+        assert_eq!(parse("1.5e3 / 2", true), Some(750.0));
+        assert_eq!(parse("2pi", true), Some(std::f64::consts::TAU));
+        assert_eq!(parse("1m + 20cm", true), Some(1.2));
+        assert_eq!(parse("1/3", true), Some(1.0 / 3.0));
+        assert_eq!(parse("2 *", true), None);
+        assert_eq!(parse("(1", true), None);
+        assert_eq!(parse("1/0", true), None);
+        assert_eq!(parse("3 apples", true), None);
+    }
+
+    #[test]
+    fn test_rejected_edit_restores_value() {
+        let ctx = Context::default();
+        let mut value = 5.0;
+
+        let run = |value: &mut f64, events: Vec<Event>, modifiers: Modifiers| {
+            let input = RawInput {
+                screen_rect: Some(Rect::from_min_size(Pos2::ZERO, vec2(800.0, 600.0))),
+                events,
+                modifiers,
+                ..Default::default()
+            };
+            let mut response = None;
+            let _ = ctx.run(input, |ctx| {
+                CentralPanel::default().show(ctx, |ui| {
+                    response = Some(ui.add(DragValue::new(value).update_while_editing(true)));
+                });
+            });
+            response.unwrap()
+        };
+        let key = |key: Key, modifiers: Modifiers| Event::Key {
+            key,
+            physical_key: None,
+            pressed: true,
+            repeat: false,
+            modifiers,
+        };
+
+        let response = run(&mut value, vec![], Modifiers::NONE);
+        ctx.memory_mut(|mem| mem.request_focus(response.id));
+        run(&mut value, vec![], Modifiers::NONE);
+        run(
+            &mut value,
+            vec![key(Key::A, Modifiers::COMMAND)],
+            Modifiers::COMMAND,
+        );
+
+        // A valid prefix is applied while editing…
+        run(
+            &mut value,
+            vec![Event::Text("2".to_owned())],
+            Modifiers::NONE,
+        );
+        assert_eq!(value, 2.0);
+        run(
+            &mut value,
+            vec![Event::Text("x".to_owned())],
+            Modifiers::NONE,
+        );
+        assert_eq!(value, 2.0);
+
+        // …but rejecting the whole edit goes back to the value from before it:
+        let response = run(
+            &mut value,
+            vec![key(Key::Enter, Modifiers::NONE)],
+            Modifiers::NONE,
+        );
+        assert_eq!(value, 5.0);
+        assert!(DragValue::input_rejected(&response));
+    }
 }
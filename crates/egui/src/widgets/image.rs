@@ -4,7 +4,7 @@ use emath::{Float as _, Rot2};
 use epaint::RectShape;
 
 use crate::{
-    load::{Bytes, SizeHint, SizedTexture, TextureLoadResult, TexturePoll},
+    load::{AnimatedImage, Bytes, SizeHint, SizedTexture, TextureLoadResult, TexturePoll},
     *,
 };
 
@@ -299,7 +299,7 @@ impl<'a> Image<'a> {
     /// # Errors
     /// May fail if they underlying [`Context::try_load_texture`] call fails.
     pub fn load_for_size(&self, ctx: &Context, available_size: Vec2) -> TextureLoadResult {
-        let size_hint = self.size.hint(available_size);
+        let size_hint = self.size.hint(available_size, ctx.pixels_per_point());
         self.source
             .clone()
             .load(ctx, self.texture_options, size_hint)
@@ -403,17 +403,18 @@ impl ImageFit {
 }
 
 impl ImageSize {
-    /// Size hint for e.g. rasterizing an svg.
-    pub fn hint(&self, available_size: Vec2) -> SizeHint {
+    /// Size hint for e.g. rasterizing an svg, in physical pixels.
+    ///
+    /// With [`ImageFit::Original`] the image is shown one texel per point,
+    /// so the hint is only scaled by `scale`, not by `pixels_per_point`.
+    pub fn hint(&self, available_size: Vec2, pixels_per_point: f32) -> SizeHint {
         let size = match self.fit {
             ImageFit::Original { scale } => return SizeHint::Scale(scale.ord()),
             ImageFit::Fraction(fract) => available_size * fract,
             ImageFit::Exact(size) => size,
         };
 
-        let size = size.min(self.max_size);
-
-        // TODO(emilk): take pixels_per_point into account here!
+        let size = pixels_per_point * size.min(self.max_size);
 
         // `inf` on an axis means "any value"
         match (size.x.is_finite(), size.y.is_finite()) {
@@ -539,6 +540,9 @@ impl<'a> ImageSource<'a> {
         }
     }
 
+    /// If this is an [`AnimatedImage`], the current frame is loaded,
+    /// and a repaint is requested for when the next one is due.
+    ///
     /// # Errors
     /// Failure to load the texture.
     pub fn load(
@@ -549,10 +553,10 @@ impl<'a> ImageSource<'a> {
     ) -> TextureLoadResult {
         match self {
             Self::Texture(texture) => Ok(TexturePoll::Ready { texture }),
-            Self::Uri(uri) => ctx.try_load_texture(uri.as_ref(), texture_options, size_hint),
+            Self::Uri(uri) => load_texture_or_frame(ctx, uri.as_ref(), texture_options, size_hint),
             Self::Bytes { uri, bytes } => {
                 ctx.include_bytes(uri.clone(), bytes);
-                ctx.try_load_texture(uri.as_ref(), texture_options, size_hint)
+                load_texture_or_frame(ctx, uri.as_ref(), texture_options, size_hint)
             }
         }
     }
@@ -568,6 +572,32 @@ impl<'a> ImageSource<'a> {
     }
 }
 
+/// Load the texture at `uri`, or its current frame if it is an [`AnimatedImage`].
+fn load_texture_or_frame(
+    ctx: &Context,
+    uri: &str,
+    texture_options: TextureOptions,
+    size_hint: SizeHint,
+) -> TextureLoadResult {
+    // Loading the whole image is what makes the loader tell us it is animated:
+    let result = ctx.try_load_texture(uri, texture_options, size_hint);
+
+    if let Some(animation) = AnimatedImage::get(ctx, uri) {
+        if 1 < animation.frame_delays.len() && !animation.total_duration().is_zero() {
+            let (frame_index, until_next_frame) = animation.frame_at(ctx.input(|i| i.time));
+            ctx.request_repaint_after(until_next_frame);
+
+            let frame_uri = AnimatedImage::frame_uri(uri, frame_index);
+            let frame = ctx.try_load_texture(&frame_uri, texture_options, size_hint);
+            if let Ok(TexturePoll::Ready { .. }) = frame {
+                return frame;
+            }
+        }
+    }
+
+    result
+}
+
 pub fn paint_texture_load_result(
     ui: &Ui,
     tlr: &TextureLoadResult,
@@ -652,6 +682,28 @@ impl<'a> From<Cow<'a, str>> for ImageSource<'a> {
     }
 }
 
+impl From<&'static [u8]> for ImageSource<'static> {
+    /// Static bytes, e.g. from `include_bytes!`.
+    ///
+    /// The uri is made up from the address of the bytes,
+    /// so the image format has to be recognized from the bytes themselves.
+    /// Prefer [`include_image`] if you can, which also gives the uri a file extension.
+    fn from(bytes: &'static [u8]) -> Self {
+        Self::Bytes {
+            uri: format!("bytes://static-{:p}-{}", bytes.as_ptr(), bytes.len()).into(),
+            bytes: bytes.into(),
+        }
+    }
+}
+
+impl<const N: usize> From<&'static [u8; N]> for ImageSource<'static> {
+    /// Static bytes, e.g. from `include_bytes!`.
+    #[inline]
+    fn from(bytes: &'static [u8; N]) -> Self {
+        Self::from(bytes.as_slice())
+    }
+}
+
 impl<T: Into<Bytes>> From<(&'static str, T)> for ImageSource<'static> {
     #[inline]
     fn from((uri, bytes): (&'static str, T)) -> Self {
@@ -760,8 +812,9 @@ pub fn paint_texture_at(
             painter.add(RectShape {
                 rect,
                 rounding: options.rounding,
-                fill: options.tint,
+                fill: options.tint.into(),
                 stroke: Stroke::NONE,
+                stroke_pattern: Default::default(),
                 blur_width: 0.0,
                 fill_texture_id: texture.id,
                 uv: options.uv,
@@ -20,12 +20,35 @@ use self::text_selection::LabelSelectionState;
 ///
 /// For full control of the text you can use [`crate::text::LayoutJob`]
 /// as argument to [`Self::new`].
+///
+/// Sections of the text with a [`crate::text::TextFormat::link`] are links:
+/// they are underlined when hovered, and [`Response::clicked_link`] tells which one was clicked.
+///
+/// ```
+/// # egui::__run_test_ui(|ui| {
+/// use egui::text::{LayoutJob, TextFormat};
+///
+/// let color = ui.visuals().hyperlink_color;
+/// let mut job = LayoutJob::default();
+/// job.append("Read the ", 0.0, TextFormat::default());
+/// job.append("docs", 0.0, TextFormat { color, link: Some(0), ..Default::default() });
+/// job.append(" or the ", 0.0, TextFormat::default());
+/// job.append("source", 0.0, TextFormat { color, link: Some(1), ..Default::default() });
+///
+/// let label = egui::Label::new(job).link_url(0, "https://docs.rs/egui");
+/// if ui.add(label).clicked_link() == Some(1) {
+///     /* … */
+/// }
+/// # });
+/// ```
 #[must_use = "You should put this widget in an ui with `ui.add(widget);`"]
 pub struct Label {
     text: WidgetText,
     wrap_mode: Option<TextWrapMode>,
     sense: Option<Sense>,
     selectable: Option<bool>,
+    estimated: bool,
+    link_urls: Vec<(usize, String)>,
 }
 
 impl Label {
@@ -35,6 +58,8 @@ impl Label {
             wrap_mode: None,
             sense: None,
             selectable: None,
+            estimated: false,
+            link_urls: Vec::new(),
         }
     }
 
@@ -96,9 +121,56 @@ impl Label {
         self.sense = Some(sense);
         self
     }
+
+    /// Only lay out the text if the label is visible.
+    ///
+    /// A label outside of the clip rect (e.g. scrolled out of view in a [`ScrollArea`])
+    /// instead allocates the size it had when it was last visible, or else
+    /// an estimate from [`crate::text::Fonts::estimate_layout_size`].
+    /// The measured sizes are stored in [`Memory`], so the total size of e.g.
+    /// a long list of labels converges as they are scrolled into view.
+    ///
+    /// This saves a lot of work for a [`ScrollArea`] with thousands of labels.
+    /// It has no effect in a horizontal wrapping layout, or for a pre-laid out [`Galley`].
+    ///
+    /// ```
+    /// # egui::__run_test_ui(|ui| {
+    /// egui::ScrollArea::vertical().show(ui, |ui| {
+    ///     for i in 0..10_000 {
+    ///         ui.add(egui::Label::new(format!("Log line {i}")).estimated());
+    ///     }
+    /// });
+    /// # });
+    /// ```
+    #[inline]
+    pub fn estimated(mut self) -> Self {
+        self.estimated = true;
+        self
+    }
+
+    /// Open `url` when the link with the given [`crate::text::TextFormat::link`] tag is clicked,
+    /// just like a [`Hyperlink`] does.
+    #[inline]
+    pub fn link_url(mut self, link: usize, url: impl Into<String>) -> Self {
+        self.link_urls.push((link, url.into()));
+        self
+    }
 }
 
 impl Label {
+    /// The size of the label if it were laid out now, without laying it out.
+    fn estimate_size(&self, ui: &Ui) -> Vec2 {
+        let valign = ui.layout().vertical_align();
+        let mut layout_job =
+            self.text
+                .clone()
+                .into_layout_job(ui.style(), FontSelection::Default, valign);
+        let wrap_mode = self.wrap_mode.unwrap_or_else(|| ui.wrap_mode());
+        layout_job.wrap =
+            text::TextWrapping::from_wrap_mode_and_width(wrap_mode, ui.available_width());
+        ui.fonts(|fonts| fonts.estimate_layout_size(&layout_job))
+    }
+
     /// Do layout and position the galley in the ui, without painting it or adding widget info.
     pub fn layout_in_ui(self, ui: &mut Ui) -> (Pos2, Arc<Galley>, Response) {
         let selectable = self
@@ -133,6 +205,7 @@ impl Label {
 
         if let WidgetText::Galley(galley) = self.text {
             // If the user said "use this specific galley", then just use it:
+            let sense = link_sense(&galley.job, sense);
             let (rect, response) = ui.allocate_exact_size(galley.size(), sense);
             let pos = match galley.job.halign {
                 Align::LEFT => rect.left_top(),
@@ -146,6 +219,7 @@ impl Label {
         let mut layout_job = self
             .text
             .into_layout_job(ui.style(), FontSelection::Default, valign);
+        let sense = link_sense(&layout_job, sense);
 
         let available_width = ui.available_width();
 
@@ -207,7 +281,7 @@ impl Label {
 }
 
 impl Widget for Label {
-    fn ui(self, ui: &mut Ui) -> Response {
+    fn ui(mut self, ui: &mut Ui) -> Response {
         // Interactive = the uses asked to sense interaction.
         // We DON'T want to have the color respond just because the text is selectable;
         // the cursor is enough to communicate that.
@@ -215,9 +289,33 @@ impl Widget for Label {
 
         let selectable = self.selectable;
 
-        let (galley_pos, galley, mut response) = self.layout_in_ui(ui);
+        let estimated_id = (self.estimated
+            && !ui.layout().main_wrap()
+            && !matches!(self.text, WidgetText::Galley(_)))
+        .then(|| ui.next_auto_id());
+
+        if let Some(id) = estimated_id {
+            let size = ui
+                .data(|data| data.get_temp::<Vec2>(id))
+                .unwrap_or_else(|| self.estimate_size(ui));
+            if !ui.is_rect_visible(Rect::from_min_size(ui.cursor().min, size)) {
+                let (_, response) = ui.allocate_exact_size(size, Sense::hover());
+                response.widget_info(|| WidgetInfo::labeled(WidgetType::Label, self.text.text()));
+                return response;
+            }
+        }
+
+        let link_urls = std::mem::take(&mut self.link_urls);
+        let (galley_pos, mut galley, mut response) = self.layout_in_ui(ui);
         response.widget_info(|| WidgetInfo::labeled(WidgetType::Label, galley.text()));
 
+        if let Some(id) = estimated_id {
+            let size = response.rect.size();
+            ui.data_mut(|data| data.insert_temp(id, size));
+        }
+
+        let hovered_link = handle_links(ui, &mut response, galley_pos, &mut galley, &link_urls);
+
         if ui.is_rect_visible(response.rect) {
             if galley.elided {
                 // Show the full (non-elided) text on hover:
@@ -244,9 +342,188 @@ impl Widget for Label {
             let selectable = selectable.unwrap_or_else(|| ui.style().interaction.selectable_labels);
             if selectable {
                 LabelSelectionState::label_text_selection(ui, &response, galley_pos, &galley);
+                if hovered_link.is_some() {
+                    // The selection shows the text cursor, but links are still for clicking:
+                    ui.ctx().set_cursor_icon(CursorIcon::PointingHand);
+                }
             }
         }
 
         response
     }
 }
+
+/// Point at, follow and underline the hovered link in the text of a [`Label`] or [`Button`],
+/// see [`text::TextFormat::link`].
+///
+/// Returns the hovered link, if any.
+pub(crate) fn handle_links(
+    ui: &Ui,
+    response: &mut Response,
+    galley_pos: Pos2,
+    galley: &mut Arc<Galley>,
+    link_urls: &[(usize, String)],
+) -> Option<usize> {
+    let link = response
+        .hover_pos()
+        .and_then(|pos| link_at(galley, pos - galley_pos.to_vec2()))?;
+    ui.ctx().set_cursor_icon(CursorIcon::PointingHand);
+
+    if response.clicked() || response.middle_clicked() {
+        if response.clicked() {
+            response.clicked_link = Some(link);
+        }
+        if let Some((_, url)) = link_urls.iter().find(|(l, _)| *l == link) {
+            let modifiers = ui.ctx().input(|i| i.modifiers);
+            ui.ctx().open_url(crate::OpenUrl {
+                url: url.clone(),
+                new_tab: response.middle_clicked() || modifiers.any(),
+            });
+        }
+    }
+
+    // Underline the hovered link:
+    let mut job = (*galley.job).clone();
+    for section in &mut job.sections {
+        if section.format.link == Some(link) {
+            section.format.underline = Stroke::new(1.0, section.format.color);
+        }
+    }
+    *galley = ui.fonts(|fonts| fonts.layout_job(job));
+
+    Some(link)
+}
+
+/// Links need to sense clicks.
+pub(crate) fn link_sense(job: &text::LayoutJob, sense: Sense) -> Sense {
+    if job
+        .sections
+        .iter()
+        .any(|section| section.format.link.is_some())
+    {
+        sense.union(Sense::click())
+    } else {
+        sense
+    }
+}
+
+/// The [`text::TextFormat::link`] of the glyph at `pos`, relative to the galley.
+fn link_at(galley: &Galley, pos: Pos2) -> Option<usize> {
+    let row = galley.rows.iter().find(|row| row.rect.contains(pos))?;
+    let glyph = row
+        .glyphs
+        .iter()
+        .find(|glyph| glyph.pos.x <= pos.x && pos.x < glyph.max_x())?;
+    let section = galley.job.sections.get(glyph.section_index as usize)?;
+    section.format.link
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimated_labels() {
+        let ctx = Context::default();
+        let run = |estimated: bool| {
+            let input = RawInput {
+                screen_rect: Some(Rect::from_min_size(Pos2::ZERO, vec2(800.0, 600.0))),
+                ..Default::default()
+            };
+            let mut content_size = Vec2::ZERO;
+            let _ = ctx.run(input, |ctx| {
+                CentralPanel::default().show(ctx, |ui| {
+                    content_size = ScrollArea::vertical()
+                        .show(ui, |ui| {
+                            for i in 0..1000 {
+                                let label = Label::new(format!("Log line {i}"));
+                                ui.add(if estimated { label.estimated() } else { label });
+                            }
+                        })
+                        .content_size;
+                });
+            });
+            (
+                content_size,
+                ctx.fonts(|fonts| fonts.num_galleys_in_cache()),
+            )
+        };
+
+        let (size, num_galleys) = run(false);
+        assert!(1000 <= num_galleys);
+
+        // Only the visible labels are laid out
+        // (the galleys from the first frame are kept in the cache for another frame):
+        run(true);
+        let (estimated_size, num_galleys) = run(true);
+        assert!(num_galleys < 100, "{num_galleys}");
+        assert!(
+            (estimated_size.y / size.y - 1.0).abs() < 0.01,
+            "{estimated_size:?} vs {size:?}"
+        );
+    }
+
+    #[test]
+    fn test_links() {
+        let ctx = Context::default();
+        let mut job = text::LayoutJob::default();
+        job.append("plain ", 0.0, text::TextFormat::default());
+        job.append(
+            "link",
+            0.0,
+            text::TextFormat {
+                link: Some(7),
+                ..Default::default()
+            },
+        );
+
+        // Links work the same in a label and a button:
+        for in_button in [false, true] {
+            let run = |events: Vec<Event>| {
+                let input = RawInput {
+                    screen_rect: Some(Rect::from_min_size(Pos2::ZERO, vec2(800.0, 600.0))),
+                    events,
+                    ..Default::default()
+                };
+                let mut response = None;
+                let output = ctx.run(input, |ctx| {
+                    CentralPanel::default().show(ctx, |ui| {
+                        response = Some(if in_button {
+                            ui.add(Button::new(job.clone()).link_url(7, "https://example.com"))
+                        } else {
+                            ui.add(Label::new(job.clone()).link_url(7, "https://example.com"))
+                        });
+                    });
+                });
+                (response.unwrap(), output.platform_output)
+            };
+            let button = |pos: Pos2, pressed: bool| Event::PointerButton {
+                pos,
+                button: PointerButton::Primary,
+                pressed,
+                modifiers: Modifiers::NONE,
+            };
+            let click = |pos: Pos2| {
+                run(vec![Event::PointerMoved(pos), button(pos, true)]);
+                run(vec![button(pos, false)])
+            };
+
+            let padding = if in_button {
+                ctx.style().spacing.button_padding.x
+            } else {
+                0.0
+            };
+            let rect = run(vec![]).0.rect.shrink2(vec2(padding + 2.0, 0.0));
+            let (response, output) = click(rect.left_center());
+            assert!(response.clicked());
+            assert_eq!(response.clicked_link(), None);
+            assert!(output.open_url.is_none());
+            assert_ne!(output.cursor_icon, CursorIcon::PointingHand);
+
+            let (response, output) = click(rect.right_center());
+            assert_eq!(response.clicked_link(), Some(7));
+            assert_eq!(output.open_url.unwrap().url, "https://example.com");
+            assert_eq!(output.cursor_icon, CursorIcon::PointingHand);
+        }
+    }
+}
@@ -81,6 +81,7 @@ impl Widget for RadioButton {
                 radius: big_icon_rect.width() / 2.0 + visuals.expansion,
                 fill: visuals.bg_fill,
                 stroke: visuals.bg_stroke,
+                stroke_pattern: Default::default(),
             });
 
             if checked {
@@ -90,6 +91,7 @@ impl Widget for RadioButton {
                     fill: visuals.fg_stroke.color, // Intentional to use stroke and not fill
                     // fill: ui.visuals().selection.stroke.color, // too much color
                     stroke: Default::default(),
+                    stroke_pattern: Default::default(),
                 });
             }
 
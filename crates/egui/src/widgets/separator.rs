@@ -111,17 +111,19 @@ impl Widget for Separator {
         let (rect, response) = ui.allocate_at_least(size, Sense::hover());
 
         if ui.is_rect_visible(response.rect) {
-            let stroke = ui.visuals().widgets.noninteractive.bg_stroke;
+            // Snapped to the pixel grid by the tessellator, so it stays sharp at any `pixels_per_point`:
+            let stroke =
+                epaint::PathStroke::from(ui.visuals().widgets.noninteractive.bg_stroke).crisp();
             let painter = ui.painter();
             if is_horizontal_line {
                 painter.hline(
                     (rect.left() - grow)..=(rect.right() + grow),
-                    painter.round_to_pixel(rect.center().y),
+                    rect.center().y,
                     stroke,
                 );
             } else {
                 painter.vline(
-                    painter.round_to_pixel(rect.center().x),
+                    rect.center().x,
                     (rect.top() - grow)..=(rect.bottom() + grow),
                     stroke,
                 );
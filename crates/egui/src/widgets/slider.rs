@@ -732,6 +732,7 @@ impl<'a> Slider<'a> {
                         radius: radius + visuals.expansion,
                         fill: visuals.bg_fill,
                         stroke: visuals.fg_stroke,
+                        stroke_pattern: Default::default(),
                     });
                 }
                 style::HandleShape::Rect { aspect_ratio } => {
@@ -1,17 +1,34 @@
-use std::sync::Arc;
+use std::{ops::Range, sync::Arc, time::Duration};
 
-use epaint::text::{cursor::*, Galley, LayoutJob};
+use epaint::text::{cursor::*, Galley, LayoutJob, TAB_SIZE};
 
 use crate::{
     os::OperatingSystem,
     output::OutputEvent,
+    style::Interaction,
     text_selection::{
-        text_cursor_state::cursor_rect, visuals::paint_text_selection, CCursorRange, CursorRange,
+        text_cursor_state::{
+            cursor_rect, find_line_start, is_word_char, merge_overlapping_ranges, select_word_at,
+            slice_char_range,
+        },
+        visuals::{
+            paint_block_cursor, paint_block_selection, paint_read_only_cursor,
+            paint_secondary_cursor, paint_text_cursor, paint_text_highlight, paint_text_selection,
+            paint_text_underline,
+        },
+        BlockSelection, CCursorRange, CursorRange,
     },
     *,
 };
 
-use super::{TextEditOutput, TextEditState};
+use super::{
+    completion::word_at,
+    state::{CompletionPopup, EditKind, ImePreedit, TextDrag, UndoGroup},
+    CharFilter, CompletionItem, DraggedText, LayouterContext, TextEditCommand, TextEditKeymap,
+    TextEditOutput, TextEditState, UndoMergePolicy,
+};
+
+type CompletionProvider<'a> = Box<dyn 'a + Fn(&str, CCursor) -> Vec<CompletionItem>>;
 
 /// A text region that the user can edit the contents of.
 ///
@@ -65,12 +82,14 @@ pub struct TextEdit<'t> {
     id_source: Option<Id>,
     font_selection: FontSelection,
     text_color: Option<Color32>,
-    layouter: Option<&'t mut dyn FnMut(&Ui, &str, f32) -> Arc<Galley>>,
+    #[allow(clippy::type_complexity)]
+    layouter: Option<Box<dyn FnMut(&Ui, &LayouterContext<'_>) -> Arc<Galley> + 't>>,
     password: bool,
     frame: bool,
     margin: Margin,
     multiline: bool,
     interactive: bool,
+    read_only: bool,
     desired_width: Option<f32>,
     desired_height_rows: usize,
     event_filter: EventFilter,
@@ -80,6 +99,26 @@ pub struct TextEdit<'t> {
     clip_text: bool,
     char_limit: usize,
     return_key: Option<KeyboardShortcut>,
+    kill_keys: bool,
+    keymap: Option<TextEditKeymap>,
+    auto_close_pairs: Vec<(char, char)>,
+    auto_indent: bool,
+    indent_string: String,
+    undo_merge: UndoMergePolicy,
+    highlight_ranges: Vec<(Range<usize>, Color32)>,
+    cursor_blink: Option<bool>,
+    overwrite_mode: bool,
+    char_filter: Option<CharFilter<'t>>,
+    password_char: char,
+    reveal_last_char_for: Option<Duration>,
+    password_reveal_button: bool,
+    allow_password_copy: bool,
+    show_line_numbers: bool,
+    highlight_current_line: bool,
+    show_whitespace: bool,
+    completion_provider: Option<CompletionProvider<'t>>,
+    accept_pasted_images: bool,
+    on_paste_image: Option<Box<dyn FnMut(ColorImage) -> Option<String> + 't>>,
 }
 
 impl<'t> WidgetWithState for TextEdit<'t> {
@@ -123,6 +162,7 @@ impl<'t> TextEdit<'t> {
             margin: Margin::symmetric(4.0, 2.0),
             multiline: true,
             interactive: true,
+            read_only: false,
             desired_width: None,
             desired_height_rows: 4,
             event_filter: EventFilter {
@@ -138,6 +178,26 @@ impl<'t> TextEdit<'t> {
             clip_text: false,
             char_limit: usize::MAX,
             return_key: Some(KeyboardShortcut::new(Modifiers::NONE, Key::Enter)),
+            kill_keys: true,
+            keymap: None,
+            auto_close_pairs: Vec::new(),
+            auto_indent: false,
+            indent_string: "\t".to_owned(),
+            undo_merge: UndoMergePolicy::default(),
+            highlight_ranges: Vec::new(),
+            cursor_blink: None,
+            overwrite_mode: false,
+            char_filter: None,
+            password_char: epaint::text::PASSWORD_REPLACEMENT_CHAR,
+            reveal_last_char_for: None,
+            password_reveal_button: false,
+            allow_password_copy: false,
+            show_line_numbers: false,
+            highlight_current_line: false,
+            show_whitespace: false,
+            completion_provider: None,
+            accept_pasted_images: false,
+            on_paste_image: None,
         }
     }
 
@@ -199,12 +259,76 @@ impl<'t> TextEdit<'t> {
     }
 
     /// If true, hide the letters from view and prevent copying from the field.
+    ///
+    /// See also [`Self::password_char`], [`Self::reveal_last_char_for`],
+    /// [`Self::password_reveal_button`] and [`Self::allow_password_copy`].
     #[inline]
     pub fn password(mut self, password: bool) -> Self {
         self.password = password;
         self
     }
 
+    /// The character shown in place of each character of a [`Self::password`] (default: `•`).
+    ///
+    /// This only affects the default layouter, not one set with [`Self::layouter`].
+    #[inline]
+    pub fn password_char(mut self, password_char: char) -> Self {
+        self.password_char = password_char;
+        self
+    }
+
+    /// Show the last typed character of a [`Self::password`] for a while before hiding it,
+    /// like on mobile phones.
+    ///
+    /// The character is hidden after `duration`, or as soon as the user presses another key.
+    #[inline]
+    pub fn reveal_last_char_for(mut self, duration: Duration) -> Self {
+        self.reveal_last_char_for = Some(duration);
+        self
+    }
+
+    /// Show a button inside a [`Self::password`] field that lets the user show and hide the password
+    /// (default: `false`).
+    #[inline]
+    pub fn password_reveal_button(mut self, password_reveal_button: bool) -> Self {
+        self.password_reveal_button = password_reveal_button;
+        self
+    }
+
+    /// Allow the user to copy and cut the text of a [`Self::password`] (default: `false`).
+    #[inline]
+    pub fn allow_password_copy(mut self, allow_password_copy: bool) -> Self {
+        self.allow_password_copy = allow_password_copy;
+        self
+    }
+
+    /// Show the number of each line in a gutter before the text (default: `false`).
+    ///
+    /// A line that is wrapped over several rows only gets a number on its first row.
+    /// This only works for multiline [`TextEdit`].
+    #[inline]
+    pub fn show_line_numbers(mut self, show_line_numbers: bool) -> Self {
+        self.show_line_numbers = show_line_numbers;
+        self
+    }
+
+    /// Paint a subtle background behind the row with the cursor (default: `false`).
+    #[inline]
+    pub fn highlight_current_line(mut self, highlight_current_line: bool) -> Self {
+        self.highlight_current_line = highlight_current_line;
+        self
+    }
+
+    /// Show spaces, tabs and line ends with dimmed symbols, e.g. for a diff viewer (default: `false`).
+    ///
+    /// This only affects the default layouter, not one set with [`Self::layouter`],
+    /// which can use [`LayoutJob::show_whitespace`] instead.
+    #[inline]
+    pub fn show_whitespace(mut self, show_whitespace: bool) -> Self {
+        self.show_whitespace = show_whitespace;
+        self
+    }
+
     /// Pick a [`FontId`] or [`TextStyle`].
     #[inline]
     pub fn font(mut self, font_selection: impl Into<FontSelection>) -> Self {
@@ -247,13 +371,47 @@ impl<'t> TextEdit<'t> {
     /// ui.add(egui::TextEdit::multiline(&mut my_code).layouter(&mut layouter));
     /// # });
     /// ```
+    ///
+    /// See also [`Self::layouter_with_context`].
     #[inline]
     pub fn layouter(mut self, layouter: &'t mut dyn FnMut(&Ui, &str, f32) -> Arc<Galley>) -> Self {
-        self.layouter = Some(layouter);
+        self.layouter = Some(Box::new(move |ui: &Ui, context: &LayouterContext<'_>| {
+            layouter(ui, context.text, context.wrap_width)
+        }));
 
         self
     }
 
+    /// Like [`Self::layouter`], but the layouter also gets to know the cursor position
+    /// and if the [`TextEdit`] has focus, see [`LayouterContext`].
+    ///
+    /// The layouter is run again when the cursor moves, even if the text didn't change.
+    ///
+    /// ```
+    /// # egui::__run_test_ui(|ui| {
+    /// # let mut my_code = String::new();
+    /// # fn my_memoized_highlighter(s: &str, cursor: Option<usize>) -> egui::text::LayoutJob { Default::default() }
+    /// use egui::text_edit::LayouterContext;
+    ///
+    /// let layouter = |ui: &egui::Ui, context: &LayouterContext<'_>| {
+    ///     // Emphasize the bracket matching the one at the cursor:
+    ///     let cursor = context.cursor.filter(|_| context.has_focus);
+    ///     let mut layout_job = my_memoized_highlighter(context.text, cursor.map(|c| c.index));
+    ///     layout_job.wrap.max_width = context.wrap_width;
+    ///     ui.fonts(|f| f.layout_job(layout_job))
+    /// };
+    /// ui.add(egui::TextEdit::multiline(&mut my_code).layouter_with_context(layouter));
+    /// # });
+    /// ```
+    #[inline]
+    pub fn layouter_with_context(
+        mut self,
+        layouter: impl FnMut(&Ui, &LayouterContext<'_>) -> Arc<Galley> + 't,
+    ) -> Self {
+        self.layouter = Some(Box::new(layouter));
+        self
+    }
+
     /// Default is `true`. If set to `false` then you cannot interact with the text (neither edit or select it).
     ///
     /// Consider using [`Ui::add_enabled`] instead to also give the [`TextEdit`] a greyed out look.
@@ -263,6 +421,19 @@ impl<'t> TextEdit<'t> {
         self
     }
 
+    /// Default is `false`. If set to `true` the text can be selected, navigated and copied,
+    /// but not changed.
+    ///
+    /// Unlike [`Self::interactive`], all keyboard navigation and mouse selection still works,
+    /// but typing, deleting, cutting, pasting and dropping text is ignored,
+    /// so [`Response::changed`] is never set.
+    /// The cursor is painted with [`crate::style::TextCursorStyle::read_only_stroke`], without blinking.
+    #[inline]
+    pub fn read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
     /// Default is `true`. If set to `false` there will be no frame showing that this is editable text!
     #[inline]
     pub fn frame(mut self, frame: bool) -> Self {
@@ -299,6 +470,8 @@ impl<'t> TextEdit<'t> {
     ///
     /// When `true`, the widget will keep the focus and pressing TAB
     /// will insert the `'\t'` character.
+    /// When the selection spans several lines, TAB indents them
+    /// and Shift+TAB outdents them instead.
     #[inline]
     pub fn lock_focus(mut self, tab_will_indent: bool) -> Self {
         self.event_filter.tab = tab_will_indent;
@@ -328,15 +501,28 @@ impl<'t> TextEdit<'t> {
         self
     }
 
-    /// Sets the limit for the amount of characters can be entered
+    /// Sets the limit for the amount of characters (not bytes) that can be entered.
     ///
-    /// This only works for singleline [`TextEdit`]
+    /// Characters typed or pasted beyond the limit are dropped,
+    /// see [`TextEditOutput::input_rejected`].
     #[inline]
     pub fn char_limit(mut self, limit: usize) -> Self {
         self.char_limit = limit;
         self
     }
 
+    /// Only accept some characters when typing or pasting, e.g. [`CharFilter::integer`].
+    ///
+    /// Other characters are dropped, so pasting `"12a3"` into a [`CharFilter::integer`] field inserts `"123"`.
+    /// Use [`TextEditOutput::input_rejected`] to e.g. flash the field when this happens.
+    ///
+    /// This only filters what the user enters, not the text you give the [`TextEdit`].
+    #[inline]
+    pub fn char_filter(mut self, char_filter: impl Into<CharFilter<'t>>) -> Self {
+        self.char_filter = Some(char_filter.into());
+        self
+    }
+
     /// Set the horizontal align of the inner text.
     #[inline]
     pub fn horizontal_align(mut self, align: Align) -> Self {
@@ -369,6 +555,158 @@ impl<'t> TextEdit<'t> {
         self.return_key = return_key.into();
         self
     }
+
+    /// Enable the readline/Emacs style kill and yank keys (default: `true`):
+    ///
+    /// * `Ctrl+K`: kill to the end of the line
+    /// * `Ctrl+U`: kill to the start of the line
+    /// * `Ctrl+W`: kill the previous word
+    /// * `Ctrl+Y`: yank (insert) the last killed text. On Windows `Ctrl+Y` is redo instead.
+    ///
+    /// If there is a selection, the kill keys kill the selection instead.
+    /// Each kill is its own undo step.
+    ///
+    /// Disable this if you use these shortcuts for something else.
+    #[inline]
+    pub fn kill_keys(mut self, kill_keys: bool) -> Self {
+        self.kill_keys = kill_keys;
+        self
+    }
+
+    /// Which keys move the cursor, delete words, undo, etc.
+    ///
+    /// The default is [`crate::style::Interaction::text_edit_keymap`],
+    /// or else [`TextEditKeymap::default_for`] the current OS and style.
+    #[inline]
+    pub fn keymap(mut self, keymap: TextEditKeymap) -> Self {
+        self.keymap = Some(keymap);
+        self
+    }
+
+    /// Typing the first char of one of these pairs also inserts the second one after the cursor,
+    /// e.g. `&[('(', ')'), ('[', ']'), ('{', '}'), ('"', '"')]` for a small code editor.
+    ///
+    /// * Typing the closing char right before one that was inserted like this just steps over it.
+    /// * Backspace between an auto-inserted pair deletes both chars.
+    ///
+    /// Pairs are only closed when the cursor is before whitespace, a closing char or the end of the text,
+    /// and pairs of the same char (like quotes) only after a non-word char.
+    /// Each auto-closing edit is undone together with the typed char.
+    ///
+    /// See also [`crate::text::find_matching_bracket`].
+    #[inline]
+    pub fn auto_close_pairs(mut self, auto_close_pairs: &[(char, char)]) -> Self {
+        self.auto_close_pairs = auto_close_pairs.to_vec();
+        self
+    }
+
+    /// Start each new line with the same leading whitespace as the line before it (default: `false`).
+    ///
+    /// After a line ending in `{` or `:` the new line is indented one more [`Self::indent_string`].
+    /// `Shift+Enter` inserts a newline without indentation.
+    ///
+    /// Backspace in the indentation at the start of a line deletes a whole [`Self::indent_string`].
+    ///
+    /// Only has an effect on multiline [`TextEdit`]s.
+    #[inline]
+    pub fn auto_indent(mut self, auto_indent: bool) -> Self {
+        self.auto_indent = auto_indent;
+        self
+    }
+
+    /// One level of indentation for [`Self::auto_indent`], e.g. `"    "` or `"\t"` (the default).
+    #[inline]
+    pub fn indent_string(mut self, indent_string: impl Into<String>) -> Self {
+        self.indent_string = indent_string.into();
+        self
+    }
+
+    /// How edits are grouped into undo steps.
+    ///
+    /// By default consecutive typing is undone a word at a time, see [`UndoMergePolicy::Words`].
+    #[inline]
+    pub fn undo_merge(mut self, undo_merge: UndoMergePolicy) -> Self {
+        self.undo_merge = undo_merge;
+        self
+    }
+
+    /// Paint these ranges of characters with a background color, e.g. to highlight search results.
+    ///
+    /// The ranges are character (not byte) offsets into the text.
+    /// They are painted behind the text and the selection, and are not adjusted when the text is edited,
+    /// so recompute them each frame.
+    ///
+    /// Use [`TextEditOutput::scroll_to_char_range`] to scroll to one of them.
+    #[inline]
+    pub fn highlight_ranges(mut self, highlight_ranges: Vec<(Range<usize>, Color32)>) -> Self {
+        self.highlight_ranges = highlight_ranges;
+        self
+    }
+
+    /// Should the text cursor blink?
+    ///
+    /// By default this follows [`crate::style::TextCursorStyle::blink`] in [`Visuals::text_cursor`],
+    /// which also sets how long the cursor is on and off.
+    #[inline]
+    pub fn cursor_blink(mut self, cursor_blink: bool) -> Self {
+        self.cursor_blink = Some(cursor_blink);
+        self
+    }
+
+    /// Start out in overwrite mode, where typing replaces the characters after the cursor
+    /// (default: `false`).
+    ///
+    /// The user toggles overwrite mode with the Insert key.
+    /// This only sets the mode of a new [`TextEdit`];
+    /// use [`TextEditState::set_overwrite_mode`] to change it later.
+    #[inline]
+    pub fn overwrite_mode(mut self, overwrite_mode: bool) -> Self {
+        self.overwrite_mode = overwrite_mode;
+        self
+    }
+
+    /// Show a popup with completions below the cursor while the user types.
+    ///
+    /// `completion_provider` is called with the text and the cursor after each typed character,
+    /// and the popup stays open while it returns any [`CompletionItem`]s.
+    ///
+    /// The keyboard focus stays in the [`TextEdit`]: Up and Down select an item,
+    /// Tab or Enter replaces the word at the cursor with it, and Escape closes the popup.
+    /// Items can also be clicked.
+    #[inline]
+    pub fn completion_provider(
+        mut self,
+        completion_provider: impl Fn(&str, CCursor) -> Vec<CompletionItem> + 't,
+    ) -> Self {
+        self.completion_provider = Some(Box::new(completion_provider));
+        self
+    }
+
+    /// Accept images pasted from the clipboard, as [`Event::PastedImage`] (default: `false`).
+    ///
+    /// While focused, the [`TextEdit`] then asks the integration for pasted images
+    /// with [`crate::PlatformOutput::accepts_pasted_images`],
+    /// and hands them to [`Self::on_paste_image`].
+    /// Otherwise pasted images are ignored, so that other widgets can use them.
+    #[inline]
+    pub fn accept_pasted_images(mut self, accept_pasted_images: bool) -> Self {
+        self.accept_pasted_images = accept_pasted_images;
+        self
+    }
+
+    /// Called with each pasted image when [`Self::accept_pasted_images`] is on.
+    ///
+    /// The returned text, e.g. a markdown link to where the app saved the image,
+    /// is inserted at the cursor just like pasted text, and can be undone.
+    /// Return `None` to ignore the image.
+    #[inline]
+    pub fn on_paste_image(
+        mut self,
+        on_paste_image: impl FnMut(ColorImage) -> Option<String> + 't,
+    ) -> Self {
+        self.on_paste_image = Some(Box::new(on_paste_image));
+        self
+    }
 }
 
 // ----------------------------------------------------------------------------
@@ -395,11 +733,35 @@ impl<'t> TextEdit<'t> {
     /// }
     /// # });
     /// ```
-    pub fn show(self, ui: &mut Ui) -> TextEditOutput {
-        let is_mutable = self.text.is_mutable();
+    pub fn show(mut self, ui: &mut Ui) -> TextEditOutput {
+        let is_mutable = self.text.is_mutable() && !self.read_only;
         let frame = self.frame;
         let where_to_put_background = ui.painter().add(Shape::Noop);
 
+        // Make room for the reveal button at the end of the field:
+        let reveal_button_width = if self.password && self.password_reveal_button {
+            ui.spacing().interact_size.y
+        } else {
+            0.0
+        };
+        self.margin.right += reveal_button_width;
+
+        // Make room for the line numbers before the text:
+        let line_number_gutter = if self.multiline && self.show_line_numbers {
+            // Resolve the font once, for both the text and the line numbers:
+            let font_id = std::mem::take(&mut self.font_selection).resolve(ui.style());
+            self.font_selection = FontSelection::FontId(font_id.clone());
+            let num_digits = self.text.as_str().split('\n').count().to_string().len();
+            let digit_width = ui.fonts(|f| f.glyph_width(&font_id, '0'));
+            let width = num_digits as f32 * digit_width + ui.spacing().item_spacing.x;
+            Some((font_id, width))
+        } else {
+            None
+        };
+        if let Some((_, width)) = &line_number_gutter {
+            self.margin.left += width;
+        }
+
         let margin = self.margin;
         let mut output = self.show_content(ui);
 
@@ -409,6 +771,22 @@ impl<'t> TextEdit<'t> {
         let inner_rect = outer_rect - margin;
         output.response.rect = inner_rect;
 
+        if reveal_button_width > 0.0 {
+            let button_rect = Rect::from_x_y_ranges(
+                inner_rect.right()..=inner_rect.right() + reveal_button_width,
+                inner_rect.y_range(),
+            );
+            password_reveal_button(ui, button_rect, &mut output);
+        }
+
+        if let Some((font_id, width)) = line_number_gutter {
+            let gutter_rect = Rect::from_x_y_ranges(
+                inner_rect.left() - width..=inner_rect.left(),
+                output.text_clip_rect.y_range(),
+            );
+            paint_line_numbers(ui, gutter_rect, &output, &font_id);
+        }
+
         if frame {
             let visuals = ui.style().interact(&output.response);
             let frame_rect = outer_rect.expand(visuals.expansion);
@@ -458,6 +836,7 @@ impl<'t> TextEdit<'t> {
             margin,
             multiline,
             interactive,
+            read_only,
             desired_width,
             desired_height_rows,
             event_filter,
@@ -467,6 +846,26 @@ impl<'t> TextEdit<'t> {
             clip_text,
             char_limit,
             return_key,
+            kill_keys,
+            keymap,
+            auto_close_pairs,
+            auto_indent,
+            indent_string,
+            undo_merge,
+            highlight_ranges,
+            cursor_blink,
+            overwrite_mode,
+            char_filter,
+            password_char,
+            reveal_last_char_for,
+            password_reveal_button: _,
+            allow_password_copy,
+            show_line_numbers: _,
+            highlight_current_line,
+            show_whitespace,
+            completion_provider,
+            accept_pasted_images,
+            mut on_paste_image,
         } = self;
 
         let text_color = text_color
@@ -475,6 +874,7 @@ impl<'t> TextEdit<'t> {
             .unwrap_or_else(|| ui.visuals().widgets.inactive.text_color());
 
         let prev_text = text.as_str().to_owned();
+        let is_editable = text.is_mutable() && !read_only;
 
         let font_id = font_selection.resolve(ui.style());
         let row_height = ui.fonts(|f| f.row_height(&font_id));
@@ -487,18 +887,61 @@ impl<'t> TextEdit<'t> {
             desired_width.min(available_width)
         };
 
+        // Until we have loaded the state we don't know if any of the password should be revealed:
+        let hidden_password = password.then_some(PasswordMask {
+            replacement: password_char,
+            revealed_char: None,
+        });
+        let current_password_mask = std::cell::Cell::new(hidden_password);
+
         let font_id_clone = font_id.clone();
-        let mut default_layouter = move |ui: &Ui, text: &str, wrap_width: f32| {
-            let text = mask_if_password(password, text);
-            let layout_job = if multiline {
+        let current_password_mask_ref = &current_password_mask;
+        let default_layouter = move |ui: &Ui, text: &str, wrap_width: f32| {
+            let text = mask_if_password(current_password_mask_ref.get(), text);
+            let mut layout_job = if multiline {
                 LayoutJob::simple(text, font_id_clone.clone(), text_color, wrap_width)
             } else {
                 LayoutJob::simple_singleline(text, font_id_clone.clone(), text_color)
             };
+            if show_whitespace {
+                layout_job.show_whitespace = Some(ui.visuals().weak_text_color());
+            }
             ui.fonts(|f| f.layout_job(layout_job))
         };
 
-        let layouter = layouter.unwrap_or(&mut default_layouter);
+        // What a custom layouter was last run with, so we can run it again when it changes:
+        let has_custom_layouter = layouter.is_some();
+        let layouter_cursor = std::cell::Cell::new((None, false));
+        if has_custom_layouter {
+            // The same id as below, before we have allocated space for the text:
+            let id = id.unwrap_or_else(|| {
+                id_source.map_or_else(
+                    || ui.next_auto_id(),
+                    |id_source| ui.make_persistent_id(id_source),
+                )
+            });
+            let cursor = TextEditState::load(ui.ctx(), id)
+                .and_then(|state| state.cursor.char_range())
+                .map(|range| range.primary);
+            layouter_cursor.set((cursor, ui.memory(|mem| mem.has_focus(id))));
+        }
+        let mut custom_layouter = layouter;
+        let mut layouter = |ui: &Ui, text: &str, wrap_width: f32| {
+            if let Some(custom_layouter) = &mut custom_layouter {
+                let (cursor, has_focus) = layouter_cursor.get();
+                let context = LayouterContext {
+                    text,
+                    wrap_width,
+                    cursor,
+                    has_focus,
+                    style: ui.style(),
+                };
+                custom_layouter(ui, &context)
+            } else {
+                default_layouter(ui, text, wrap_width)
+            }
+        };
+        let layouter: &mut dyn FnMut(&Ui, &str, f32) -> Arc<Galley> = &mut layouter;
 
         let mut galley = layouter(ui, text.as_str(), wrap_width);
 
@@ -520,7 +963,23 @@ impl<'t> TextEdit<'t> {
                 auto_id // Since we are only storing the cursor a persistent Id is not super important
             }
         });
-        let mut state = TextEditState::load(ui.ctx(), id).unwrap_or_default();
+        let mut state = TextEditState::load(ui.ctx(), id).unwrap_or_else(|| TextEditState {
+            overwrite_mode,
+            ..Default::default()
+        });
+
+        let mask = password_mask(ui, &state, password, password_char, reveal_last_char_for);
+        if mask != current_password_mask.get() {
+            current_password_mask.set(mask);
+            galley = layouter(ui, text.as_str(), wrap_width);
+        }
+
+        let cursor = state.cursor.char_range().map(|range| range.primary);
+        let has_focus = ui.memory(|mem| mem.has_focus(id));
+        if has_custom_layouter && layouter_cursor.get() != (cursor, has_focus) {
+            layouter_cursor.set((cursor, has_focus));
+            galley = layouter(ui, text.as_str(), wrap_width);
+        }
 
         // On touch screens (e.g. mobile in `eframe` web), should
         // dragging select text, or scroll the enclosing [`ScrollArea`] (if any)?
@@ -542,9 +1001,39 @@ impl<'t> TextEdit<'t> {
         let text_clip_rect = rect;
         let painter = ui.painter_at(text_clip_rect.expand(1.0)); // expand to avoid clipping cursor
 
+        // Dragging to select text (rather than dragging the selected text somewhere):
+        let is_drag_selecting =
+            interactive && ui.ctx().is_being_dragged(response.id) && state.text_drag.is_none();
+
         if interactive {
-            if let Some(pointer_pos) = ui.ctx().pointer_interact_pos() {
-                if response.hovered() && text.is_mutable() {
+            if let Some(mut pointer_pos) = ui.ctx().pointer_interact_pos() {
+                if is_drag_selecting {
+                    // Scroll gradually towards the pointer when it is outside,
+                    // and select up to the edge of what is visible:
+                    let visible_rect = text_clip_rect.intersect(ui.clip_rect());
+                    let content_rect = Rect::from_min_size(
+                        rect.min - vec2(state.singleline_offset, 0.0),
+                        galley.size(),
+                    );
+                    let delta = text_selection::text_cursor_state::drag_scroll_delta(
+                        ui,
+                        content_rect,
+                        visible_rect,
+                        pointer_pos,
+                    );
+                    if delta != Vec2::ZERO {
+                        if clip_text {
+                            state.singleline_offset += delta.x;
+                            ui.scroll_with_delta(vec2(0.0, -delta.y));
+                        } else {
+                            ui.scroll_with_delta(-delta);
+                        }
+                        ui.ctx().request_repaint();
+                    }
+                    pointer_pos = visible_rect.clamp(pointer_pos);
+                }
+
+                if response.hovered() && is_editable {
                     ui.output_mut(|o| o.mutable_text_under_cursor = true);
                 }
 
@@ -564,14 +1053,42 @@ impl<'t> TextEdit<'t> {
                     text_selection::visuals::paint_cursor_end(&painter, ui.visuals(), cursor_rect);
                 }
 
+                if is_editable && response.dnd_hover_payload::<DraggedText>().is_some() {
+                    // Where the dragged text would be dropped:
+                    let cursor_rect = cursor_rect(
+                        rect.min - singleline_offset,
+                        &galley,
+                        &cursor_at_pointer,
+                        row_height,
+                    );
+                    text_selection::visuals::paint_cursor_end(&painter, ui.visuals(), cursor_rect);
+                }
+
                 let is_being_dragged = ui.ctx().is_being_dragged(response.id);
-                let did_interact = state.cursor.pointer_interaction(
+                let allow_text_drag = !password || allow_password_copy;
+                let did_drag_text = drag_selected_text(
                     ui,
+                    &mut state,
                     &response,
-                    cursor_at_pointer,
-                    &galley,
-                    is_being_dragged,
+                    text.as_str(),
+                    &cursor_at_pointer,
+                    allow_text_drag,
                 );
+                let mut did_interact = did_drag_text
+                    || add_cursor_at_pointer(ui, &mut state, &response, &cursor_at_pointer);
+                if !did_interact
+                    && state.cursor.pointer_interaction(
+                        ui,
+                        &response,
+                        cursor_at_pointer,
+                        &galley,
+                        is_being_dragged,
+                    )
+                {
+                    // Clicking or dragging replaces all the cursors with one:
+                    state.extra_ccursor_ranges.clear();
+                    did_interact = true;
+                }
 
                 if did_interact {
                     ui.memory_mut(|mem| mem.request_focus(response.id));
@@ -584,9 +1101,80 @@ impl<'t> TextEdit<'t> {
         }
 
         let mut cursor_range = None;
+        let mut input_rejected = false;
+
+        if interactive && is_editable {
+            let mut changed = false;
+            if let Some(dragged) = response.dnd_release_payload::<DraggedText>() {
+                // Text dragged from this or another `TextEdit` is dropped at the pointer:
+                let singleline_offset = vec2(state.singleline_offset, 0.0);
+                let drop_ccursor = ui.ctx().pointer_interact_pos().map(|pointer_pos| {
+                    galley
+                        .cursor_from_pos(pointer_pos - rect.min + singleline_offset)
+                        .ccursor
+                });
+                if let Some(drop_ccursor) = drop_ccursor {
+                    let copy = ui.input(|i| i.modifiers.command);
+                    if let Some(dropped) = drop_text(
+                        &mut state,
+                        text,
+                        id,
+                        &dragged,
+                        drop_ccursor,
+                        copy,
+                        char_limit,
+                        char_filter.as_ref(),
+                        &mut input_rejected,
+                    ) {
+                        state.cursor.set_char_range(Some(dropped));
+                        state.extra_ccursor_ranges.clear();
+                        ui.memory_mut(|mem| mem.request_focus(id));
+                        changed = true;
+                    }
+                }
+            }
+            changed |= delete_moved_text(ui, &mut state, text, id);
+            if ui.ctx().os() == OperatingSystem::Nix && response.hovered() {
+                let singleline_offset = vec2(state.singleline_offset, 0.0);
+                let cursor_at_pointer = ui.ctx().pointer_interact_pos().map(|pointer_pos| {
+                    galley
+                        .cursor_from_pos(pointer_pos - rect.min + singleline_offset)
+                        .ccursor
+                });
+                if let Some(cursor_at_pointer) = cursor_at_pointer {
+                    if let Some(ccursor) = paste_from_primary(
+                        ui,
+                        &mut state,
+                        text,
+                        cursor_at_pointer,
+                        char_limit,
+                        char_filter.as_ref(),
+                        &mut input_rejected,
+                    ) {
+                        state
+                            .cursor
+                            .set_char_range(Some(CCursorRange::one(ccursor)));
+                        state.extra_ccursor_ranges.clear();
+                        ui.memory_mut(|mem| mem.request_focus(id));
+                        changed = true;
+                    }
+                }
+            }
+            if changed {
+                galley = layouter(ui, text.as_str(), wrap_width);
+                response.mark_changed();
+            }
+        }
+
         let prev_cursor_range = state.cursor.range(&galley);
         if interactive && ui.memory(|mem| mem.has_focus(id)) {
-            ui.memory_mut(|mem| mem.set_focus_lock_filter(id, event_filter));
+            let accept_pasted_images = accept_pasted_images && is_editable;
+            if accept_pasted_images {
+                ui.output_mut(|o| o.accepts_pasted_images = true);
+            }
+
+            let lock_filter = focus_lock_filter(event_filter, &state);
+            ui.memory_mut(|mem| mem.set_focus_lock_filter(id, lock_filter));
 
             let default_cursor_range = if cursor_at_end {
                 CursorRange::one(galley.end())
@@ -594,7 +1182,7 @@ impl<'t> TextEdit<'t> {
                 CursorRange::default()
             };
 
-            let (changed, new_cursor_range) = events(
+            let (changed, rejected, new_cursor_range) = events(
                 ui,
                 &mut state,
                 text,
@@ -604,16 +1192,66 @@ impl<'t> TextEdit<'t> {
                 wrap_width,
                 multiline,
                 password,
+                allow_password_copy,
+                read_only,
                 default_cursor_range,
                 char_limit,
-                event_filter,
+                char_filter.as_ref(),
+                lock_filter,
                 return_key,
+                kill_keys,
+                keymap.as_ref(),
+                &auto_close_pairs,
+                auto_indent,
+                &indent_string,
+                undo_merge,
+                on_paste_image
+                    .as_deref_mut()
+                    .filter(|_| accept_pasted_images),
             );
 
             if changed {
                 response.mark_changed();
             }
+            input_rejected = rejected;
             cursor_range = Some(new_cursor_range);
+
+            if let Some(completion_provider) = completion_provider.as_ref().filter(|_| !read_only) {
+                update_completion(
+                    ui,
+                    &mut state,
+                    text.as_str(),
+                    changed,
+                    prev_cursor_range,
+                    &new_cursor_range,
+                    completion_provider,
+                );
+                // The popup may have opened or closed:
+                let lock_filter = focus_lock_filter(event_filter, &state);
+                ui.memory_mut(|mem| mem.set_focus_lock_filter(id, lock_filter));
+            }
+
+            // E.g. reveal the char that was just typed:
+            let mask = password_mask(ui, &state, password, password_char, reveal_last_char_for);
+            if mask != current_password_mask.get() {
+                current_password_mask.set(mask);
+                galley = layouter(ui, text.as_str(), wrap_width);
+                cursor_range = Some(cursor_range_from_ccursors(
+                    &galley,
+                    &new_cursor_range.as_ccursor_range(),
+                ));
+            }
+        }
+
+        // E.g. highlight the bracket matching the one at the moved cursor:
+        let cursor = state.cursor.char_range().map(|range| range.primary);
+        let has_focus = ui.memory(|mem| mem.has_focus(id));
+        if has_custom_layouter && layouter_cursor.get() != (cursor, has_focus) {
+            layouter_cursor.set((cursor, has_focus));
+            galley = layouter(ui, text.as_str(), wrap_width);
+            cursor_range = cursor_range.map(|cursor_range| {
+                cursor_range_from_ccursors(&galley, &cursor_range.as_ccursor_range())
+            });
         }
 
         let mut galley_pos = align
@@ -624,19 +1262,35 @@ impl<'t> TextEdit<'t> {
 
         // Visual clipping for singleline text editor with text larger than width
         if clip_text && align_offset == 0.0 {
+            // Follow the cursor when it moves, and go back to the start when we are done editing.
+            // Otherwise stay where we are, e.g. after `TextEditOutput::scroll_to_char_range`.
+            let cursor_moved = response.changed
+                || response.gained_focus()
+                || state.scroll_to_cursor
+                || cursor_range.map(|cursor_range| cursor_range.as_ccursor_range())
+                    != prev_cursor_range.map(|cursor_range| cursor_range.as_ccursor_range());
             let cursor_pos = match (cursor_range, ui.memory(|mem| mem.has_focus(id))) {
-                (Some(cursor_range), true) => galley.pos_from_cursor(&cursor_range.primary).min.x,
-                _ => 0.0,
+                (Some(cursor_range), true) if cursor_moved => {
+                    Some(galley.pos_from_cursor(&cursor_range.primary).min.x)
+                }
+                (_, false) if state.scroll_to_cursor => state
+                    .cursor
+                    .range(&galley)
+                    .map(|cursor_range| galley.pos_from_cursor(&cursor_range.primary).min.x),
+                (_, false) if response.lost_focus() => Some(0.0),
+                _ => None,
             };
 
             let mut offset_x = state.singleline_offset;
             let visible_range = offset_x..=offset_x + desired_inner_size.x;
 
-            if !visible_range.contains(&cursor_pos) {
-                if cursor_pos < *visible_range.start() {
-                    offset_x = cursor_pos;
-                } else {
-                    offset_x = cursor_pos - desired_inner_size.x;
+            if let Some(cursor_pos) = cursor_pos {
+                if !visible_range.contains(&cursor_pos) {
+                    if cursor_pos < *visible_range.start() {
+                        offset_x = cursor_pos;
+                    } else {
+                        offset_x = cursor_pos - desired_inner_size.x;
+                    }
                 }
             }
 
@@ -650,6 +1304,17 @@ impl<'t> TextEdit<'t> {
             state.singleline_offset = align_offset;
         }
 
+        if state.scroll_to_cursor {
+            state.scroll_to_cursor = false;
+            if let Some(cursor_range) = state.cursor.range(&galley) {
+                let primary_cursor_rect =
+                    cursor_rect(galley_pos, &galley, &cursor_range.primary, row_height);
+                let align = state.scroll_to_cursor_align.take();
+                let margin = if align.is_some() { 0.0 } else { row_height };
+                ui.scroll_to_rect(primary_cursor_rect.expand2(vec2(0.0, margin)), align);
+            }
+        }
+
         let selection_changed = if let (Some(cursor_range), Some(prev_cursor_range)) =
             (cursor_range, prev_cursor_range)
         {
@@ -658,10 +1323,91 @@ impl<'t> TextEdit<'t> {
             false
         };
 
+        if selection_changed
+            && ui.ctx().os() == OperatingSystem::Nix
+            && (!password || allow_password_copy)
+        {
+            // Selecting text puts it in the primary selection, to be pasted with a middle-click:
+            if let Some(cursor_range) = cursor_range.filter(|cursor_range| !cursor_range.is_empty())
+            {
+                let selected = cursor_range.slice_str(text.as_str()).to_owned();
+                ui.output_mut(|o| o.copied_text_primary = selected);
+            }
+        }
+
+        let has_focus = ui.memory(|mem| mem.has_focus(id));
+        if !has_focus {
+            state.ime_preedit = None;
+
+            // Clicking an item of the completion popup takes the focus until it is picked:
+            let completion_popup_layer = LayerId::new(Order::Foreground, completion_popup_id(id));
+            let is_pointer_on_popup = ui
+                .ctx()
+                .pointer_interact_pos()
+                .is_some_and(|pos| ui.ctx().layer_id_at(pos) == Some(completion_popup_layer));
+            if !is_pointer_on_popup {
+                state.completion = None;
+            }
+        }
+        let ime_preedit = state
+            .ime_preedit
+            .as_ref()
+            .zip(state.cursor.range(&galley))
+            .map(|(preedit, cursor_range)| {
+                layout_ime_preedit(
+                    ui,
+                    text.as_str(),
+                    preedit,
+                    &cursor_range,
+                    layouter,
+                    wrap_width,
+                    galley_pos,
+                    row_height,
+                )
+            });
+
         if ui.is_rect_visible(rect) {
-            painter.galley(galley_pos, galley.clone(), text_color);
+            let shown_galley = ime_preedit
+                .as_ref()
+                .map_or(&galley, |ime_preedit| &ime_preedit.galley);
+
+            if highlight_current_line && has_focus {
+                if let Some(cursor_range) = state.cursor.range(&galley) {
+                    if let Some(row) = galley.rows.get(cursor_range.primary.rcursor.row) {
+                        let row_rect = row.rect.translate(galley_pos.to_vec2());
+                        painter.rect_filled(
+                            Rect::from_x_y_ranges(rect.x_range(), row_rect.y_range()),
+                            0.0,
+                            ui.visuals().faint_bg_color,
+                        );
+                    }
+                }
+            }
+
+            for (char_range, color) in highlight_ranges {
+                let char_range = match &ime_preedit {
+                    Some(ime_preedit) => {
+                        ime_preedit.char_index(char_range.start)
+                            ..ime_preedit.char_index(char_range.end)
+                    }
+                    None => char_range,
+                };
+                paint_text_highlight(&painter, galley_pos, shown_galley, char_range, color);
+            }
+
+            painter.galley(galley_pos, shown_galley.clone(), text_color);
+
+            if let Some(ime_preedit) = &ime_preedit {
+                paint_text_underline(
+                    &painter,
+                    galley_pos,
+                    &ime_preedit.galley,
+                    ime_preedit.char_range.clone(),
+                    Stroke::new(1.0, text_color),
+                );
+            }
 
-            if text.as_str().is_empty() && !hint_text.is_empty() {
+            if text.as_str().is_empty() && !hint_text.is_empty() && ime_preedit.is_none() {
                 let hint_text_color = ui.visuals().weak_text_color();
                 let hint_text_font_id = hint_text_font.unwrap_or(font_id.into());
                 let galley = if multiline {
@@ -682,30 +1428,72 @@ impl<'t> TextEdit<'t> {
                 painter.galley(rect.min, galley, hint_text_color);
             }
 
-            if ui.memory(|mem| mem.has_focus(id)) {
+            if has_focus {
                 if let Some(cursor_range) = state.cursor.range(&galley) {
                     // We paint the cursor on top of the text, in case
                     // the text galley has backgrounds (as e.g. `code` snippets in markup do).
-                    paint_text_selection(
-                        &painter,
-                        ui.visuals(),
-                        galley_pos,
-                        &galley,
-                        &cursor_range,
-                        None,
-                    );
+                    let extra_cursor_ranges: Vec<CursorRange> = state
+                        .extra_char_ranges()
+                        .iter()
+                        .map(|ccursor_range| cursor_range_from_ccursors(&galley, ccursor_range))
+                        .collect();
+                    if let Some(block_selection) = state.cursor.block_selection() {
+                        paint_block_selection(
+                            &painter,
+                            ui.visuals(),
+                            galley_pos,
+                            &galley,
+                            block_selection,
+                        );
+                    } else if ime_preedit.is_none() {
+                        for cursor_range in
+                            std::iter::once(&cursor_range).chain(&extra_cursor_ranges)
+                        {
+                            paint_text_selection(
+                                &painter,
+                                ui.visuals(),
+                                galley_pos,
+                                &galley,
+                                cursor_range,
+                                None,
+                            );
+                        }
+                    }
 
-                    let primary_cursor_rect =
-                        cursor_rect(galley_pos, &galley, &cursor_range.primary, row_height);
+                    let primary_cursor_rect = ime_preedit.as_ref().map_or_else(
+                        || cursor_rect(galley_pos, &galley, &cursor_range.primary, row_height),
+                        |ime_preedit| ime_preedit.cursor_rect,
+                    );
 
                     let is_fully_visible = ui.clip_rect().contains_rect(rect); // TODO(emilk): remove this HACK workaround for https://github.com/emilk/egui/issues/1531
-                    if (response.changed || selection_changed) && !is_fully_visible {
+                    if (response.changed || selection_changed)
+                        && !is_fully_visible
+                        && !is_drag_selecting
+                    {
                         // Scroll to keep primary cursor in view:
                         ui.scroll_to_rect(primary_cursor_rect, None);
                     }
 
-                    if text.is_mutable() && interactive {
+                    if read_only && interactive {
+                        // A thin cursor that doesn't blink, so it doesn't look editable:
+                        if ui.ctx().input(|i| i.focused) {
+                            paint_read_only_cursor(&painter, ui.visuals(), primary_cursor_rect);
+                            for extra_cursor_range in &extra_cursor_ranges {
+                                paint_read_only_cursor(
+                                    &painter,
+                                    ui.visuals(),
+                                    cursor_rect(
+                                        galley_pos,
+                                        &galley,
+                                        &extra_cursor_range.primary,
+                                        row_height,
+                                    ),
+                                );
+                            }
+                        }
+                    } else if text.is_mutable() && interactive {
                         let now = ui.ctx().input(|i| i.time);
+                        let cursor_blink = cursor_blink.unwrap_or(ui.visuals().text_cursor.blink);
                         if response.changed || selection_changed {
                             state.last_edit_time = now;
                         }
@@ -714,38 +1502,112 @@ impl<'t> TextEdit<'t> {
                         // This is for two reasons:
                         // * Don't give the impression that the user can type into a window without focus
                         // * Don't repaint the ui because of a blinking cursor in an app that is not in focus
-                        if ui.ctx().input(|i| i.focused) {
-                            text_selection::visuals::paint_text_cursor(
-                                ui,
-                                &painter,
-                                primary_cursor_rect,
-                                now - state.last_edit_time,
-                            );
+                        let show_cursor = ime_preedit
+                            .as_ref()
+                            .map_or(true, |ime_preedit| ime_preedit.show_cursor);
+                        if ui.ctx().input(|i| i.focused) && show_cursor {
+                            let time_since_last_edit = now - state.last_edit_time;
+                            // In overwrite mode the cursor covers the character that typing replaces:
+                            let overwrite_mode = state.overwrite_mode && ime_preedit.is_none();
+                            let paint_cursor = |cursor_range: &CursorRange, line_rect: Rect| {
+                                if overwrite_mode && cursor_range.is_empty() {
+                                    paint_block_cursor(
+                                        ui,
+                                        &painter,
+                                        block_cursor_rect(
+                                            galley_pos,
+                                            &galley,
+                                            &cursor_range.primary,
+                                            row_height,
+                                        ),
+                                        time_since_last_edit,
+                                        cursor_blink,
+                                    );
+                                } else {
+                                    paint_text_cursor(
+                                        ui,
+                                        &painter,
+                                        line_rect,
+                                        time_since_last_edit,
+                                        cursor_blink,
+                                    );
+                                }
+                            };
+                            paint_cursor(&cursor_range, primary_cursor_rect);
+                            if !cursor_range.is_empty() {
+                                paint_secondary_cursor(
+                                    &painter,
+                                    ui.visuals(),
+                                    cursor_rect(
+                                        galley_pos,
+                                        &galley,
+                                        &cursor_range.secondary,
+                                        row_height,
+                                    ),
+                                );
+                            }
+                            for extra_cursor_range in &extra_cursor_ranges {
+                                paint_cursor(
+                                    extra_cursor_range,
+                                    cursor_rect(
+                                        galley_pos,
+                                        &galley,
+                                        &extra_cursor_range.primary,
+                                        row_height,
+                                    ),
+                                );
+                            }
                         }
-
-                        // Set IME output (in screen coords) when text is editable and visible
-                        let transform = ui
-                            .memory(|m| m.layer_transforms.get(&ui.layer_id()).copied())
-                            .unwrap_or_default();
-
-                        ui.ctx().output_mut(|o| {
-                            o.ime = Some(crate::output::IMEOutput {
-                                rect: transform * rect,
-                                cursor_rect: transform * primary_cursor_rect,
-                            });
-                        });
                     }
                 }
             }
         }
 
-        state.clone().store(ui.ctx(), id);
+        if has_focus && is_editable && interactive {
+            if let Some(cursor_range) = state.cursor.range(&galley) {
+                // Set IME output (in screen coords) every frame,
+                // so that the backend can place the IME candidate window at the cursor:
+                let primary_cursor_rect = ime_preedit.as_ref().map_or_else(
+                    || cursor_rect(galley_pos, &galley, &cursor_range.primary, row_height),
+                    |ime_preedit| ime_preedit.cursor_rect,
+                );
+                let transform = ui
+                    .memory(|m| m.layer_transforms.get(&ui.layer_id()).copied())
+                    .unwrap_or_default();
+
+                ui.ctx().output_mut(|o| {
+                    o.ime = Some(crate::output::IMEOutput {
+                        rect: transform * rect,
+                        cursor_rect: transform * primary_cursor_rect,
+                    });
+                });
+            }
+        }
 
-        if response.changed {
-            response.widget_info(|| {
+        if let Some(cursor_range) = state.cursor.range(&galley) {
+            if let Some(completion) = &mut state.completion {
+                let primary_cursor_rect =
+                    cursor_rect(galley_pos, &galley, &cursor_range.primary, row_height);
+                show_completion_popup(ui, id, primary_cursor_rect, completion);
+            }
+        }
+
+        state.clone().store(ui.ctx(), id);
+
+        let (undo_available, redo_available) = {
+            let undo_state = (
+                state.cursor.char_range().unwrap_or_default(),
+                text.as_str().to_owned(),
+            );
+            let undoer = state.undoer.lock();
+            (undoer.has_undo(&undo_state), undoer.has_redo(&undo_state))
+        };
+
+        if response.changed {
+            response.widget_info(|| {
                 WidgetInfo::text_edit(
-                    mask_if_password(password, prev_text.as_str()),
-                    mask_if_password(password, text.as_str()),
+                    mask_if_password(hidden_password, prev_text.as_str()),
+                    mask_if_password(hidden_password, text.as_str()),
                 )
             });
         } else if selection_changed {
@@ -754,14 +1616,14 @@ impl<'t> TextEdit<'t> {
                 cursor_range.primary.ccursor.index..=cursor_range.secondary.ccursor.index;
             let info = WidgetInfo::text_selection_changed(
                 char_range,
-                mask_if_password(password, text.as_str()),
+                mask_if_password(hidden_password, text.as_str()),
             );
             response.output_event(OutputEvent::TextSelectionChanged(info));
         } else {
             response.widget_info(|| {
                 WidgetInfo::text_edit(
-                    mask_if_password(password, prev_text.as_str()),
-                    mask_if_password(password, text.as_str()),
+                    mask_if_password(hidden_password, prev_text.as_str()),
+                    mask_if_password(hidden_password, text.as_str()),
                 )
             });
         }
@@ -786,6 +1648,19 @@ impl<'t> TextEdit<'t> {
             );
         }
 
+        let cursor_screen_rect = cursor_range.map(|cursor_range| {
+            cursor_rect(galley_pos, &galley, &cursor_range.primary, row_height)
+        });
+        let visible_row_range = {
+            let clip_rect = text_clip_rect.intersect(ui.clip_rect());
+            let rows = &galley.rows;
+            let start =
+                rows.partition_point(|row| galley_pos.y + row.rect.bottom() < clip_rect.top());
+            let end =
+                rows.partition_point(|row| galley_pos.y + row.rect.top() <= clip_rect.bottom());
+            start..end.max(start)
+        };
+
         TextEditOutput {
             response,
             galley,
@@ -793,19 +1668,198 @@ impl<'t> TextEdit<'t> {
             text_clip_rect,
             state,
             cursor_range,
+            cursor_screen_rect,
+            visible_row_range,
+            undo_available,
+            redo_available,
+            input_rejected,
+        }
+    }
+}
+
+/// Paint the number of each paragraph on its first row, right-aligned in `gutter_rect`.
+fn paint_line_numbers(ui: &Ui, gutter_rect: Rect, output: &TextEditOutput, font_id: &FontId) {
+    let painter = ui.painter_at(gutter_rect);
+    let color = ui.visuals().weak_text_color();
+    let x = gutter_rect.right() - ui.spacing().item_spacing.x;
+
+    let mut line_number = 1;
+    let mut starts_line = true;
+    for row in &output.galley.rows {
+        let row_rect = row.rect.translate(output.galley_pos.to_vec2());
+        if painter.clip_rect().bottom() < row_rect.top() {
+            break;
+        }
+        if starts_line {
+            if painter.clip_rect().top() <= row_rect.bottom() {
+                painter.text(
+                    pos2(x, row_rect.top()),
+                    Align2::RIGHT_TOP,
+                    line_number.to_string(),
+                    font_id.clone(),
+                    color,
+                );
+            }
+            line_number += 1;
+        }
+        starts_line = row.ends_with_newline;
+    }
+}
+
+/// The button that shows and hides the text of a password field.
+fn password_reveal_button(ui: &Ui, button_rect: Rect, output: &mut TextEditOutput) {
+    let id = output.response.id;
+    let response = ui.interact(
+        button_rect,
+        id.with("password_reveal_button"),
+        Sense::click(),
+    );
+    if response.clicked() {
+        output.state.password_revealed = !output.state.password_revealed;
+        output.state.clone().store(ui.ctx(), id);
+        ui.ctx().request_repaint();
+    }
+    let revealed = output.state.password_revealed;
+    response.widget_info(|| WidgetInfo::selected(WidgetType::Checkbox, revealed, "Show password"));
+
+    if ui.is_rect_visible(button_rect) {
+        let visuals = ui.style().interact_selectable(&response, revealed);
+        ui.painter().text(
+            button_rect.center(),
+            Align2::CENTER_CENTER,
+            "👁",
+            TextStyle::Button.resolve(ui.style()),
+            visuals.text_color(),
+        );
+    }
+    response.on_hover_text(if revealed {
+        "Hide password"
+    } else {
+        "Show password"
+    });
+}
+
+fn completion_popup_id(id: Id) -> Id {
+    id.with("completion_popup")
+}
+
+/// Ask `completion_provider` for completions after the user typed, and close the popup
+/// when the cursor moves away.
+fn update_completion(
+    ui: &Ui,
+    state: &mut TextEditState,
+    text: &str,
+    changed: bool,
+    prev_cursor_range: Option<CursorRange>,
+    cursor_range: &CursorRange,
+    completion_provider: &dyn Fn(&str, CCursor) -> Vec<CompletionItem>,
+) {
+    if !cursor_range.is_empty() {
+        state.completion = None;
+    } else if changed {
+        // Picking a completion closes the popup, so only typing opens it again:
+        let typed = ui.input(|i| i.events.iter().any(|event| matches!(event, Event::Text(_))));
+        if state.completion.is_some() || typed {
+            let items = completion_provider(text, cursor_range.primary.ccursor);
+            state.completion = (!items.is_empty()).then(|| CompletionPopup::new(items));
         }
+    } else if prev_cursor_range.map(|prev| prev.as_ccursor_range())
+        != Some(cursor_range.as_ccursor_range())
+    {
+        state.completion = None;
     }
 }
 
-fn mask_if_password(is_password: bool, text: &str) -> String {
-    fn mask_password(text: &str) -> String {
-        std::iter::repeat(epaint::text::PASSWORD_REPLACEMENT_CHAR)
-            .take(text.chars().count())
-            .collect::<String>()
+/// Show the completions below the cursor, and remember which one is clicked.
+///
+/// This is an [`Area`] of its own rather than a [`crate::popup`],
+/// so that it doesn't close other popups (e.g. one that the [`TextEdit`] is in).
+fn show_completion_popup(ui: &Ui, id: Id, cursor_rect: Rect, completion: &mut CompletionPopup) {
+    Area::new(completion_popup_id(id))
+        .order(Order::Foreground)
+        .fixed_pos(cursor_rect.left_bottom())
+        .show(ui.ctx(), |ui| {
+            Frame::popup(ui.style()).show(ui, |ui| {
+                ScrollArea::vertical()
+                    .max_height(ui.spacing().combo_height)
+                    .show(ui, |ui| {
+                        for (index, item) in completion.items.iter().enumerate() {
+                            let is_selected = index == completion.selected;
+                            let response = ui
+                                .horizontal(|ui| {
+                                    let response = ui.selectable_label(is_selected, &item.text);
+                                    if !item.detail.is_empty() {
+                                        ui.weak(&item.detail);
+                                    }
+                                    response
+                                })
+                                .inner;
+                            if is_selected && completion.scroll_to_selected {
+                                response.scroll_to_me(None);
+                            }
+                            if response.clicked() {
+                                completion.clicked = Some(index);
+                                ui.memory_mut(|mem| mem.request_focus(id));
+                                ui.ctx().request_repaint();
+                            }
+                        }
+                        completion.scroll_to_selected = false;
+                    });
+            });
+        });
+}
+
+/// How the text of a password field is hidden.
+#[derive(Clone, Copy, PartialEq)]
+struct PasswordMask {
+    /// Shown in place of each character.
+    replacement: char,
+
+    /// Show the character at this index as is, e.g. the one that was just typed.
+    revealed_char: Option<usize>,
+}
+
+/// How to hide the text of this password field, if at all.
+fn password_mask(
+    ui: &Ui,
+    state: &TextEditState,
+    password: bool,
+    password_char: char,
+    reveal_last_char_for: Option<Duration>,
+) -> Option<PasswordMask> {
+    if !password || state.password_revealed {
+        return None;
     }
 
-    if is_password {
-        mask_password(text)
+    let revealed_char = reveal_last_char_for.and_then(|duration| {
+        let (index, typed_time) = state.password_last_typed?;
+        let time_left = duration.as_secs_f64() - (ui.input(|i| i.time) - typed_time);
+        // Hide it again once the time is up:
+        (time_left > 0.0).then(|| {
+            ui.ctx()
+                .request_repaint_after(Duration::from_secs_f64(time_left));
+            index
+        })
+    });
+
+    Some(PasswordMask {
+        replacement: password_char,
+        revealed_char,
+    })
+}
+
+fn mask_if_password(mask: Option<PasswordMask>, text: &str) -> String {
+    if let Some(mask) = mask {
+        text.chars()
+            .enumerate()
+            .map(|(i, c)| {
+                if Some(i) == mask.revealed_char {
+                    c
+                } else {
+                    mask.replacement
+                }
+            })
+            .collect()
     } else {
         text.to_owned()
     }
@@ -813,8 +1867,580 @@ fn mask_if_password(is_password: bool, text: &str) -> String {
 
 // ----------------------------------------------------------------------------
 
-/// Check for (keyboard) events to edit the cursor and/or text.
+/// The text being composed with an IME, laid out in place of the selection.
+struct ImePreeditLayout {
+    /// The whole text, with the preedit in it.
+    galley: Arc<Galley>,
+
+    /// The chars of the preedit in [`Self::galley`].
+    char_range: Range<usize>,
+
+    /// The chars of the selection that the preedit replaces, in the actual text.
+    replaced: Range<usize>,
+
+    /// Where the IME cursor is, or the start of the preedit if it is hidden.
+    cursor_rect: Rect,
+
+    show_cursor: bool,
+}
+
+impl ImePreeditLayout {
+    /// Where the char at `index` of the actual text ended up in [`Self::galley`].
+    fn char_index(&self, index: usize) -> usize {
+        if index <= self.replaced.start {
+            index
+        } else if index < self.replaced.end {
+            self.replaced.start
+        } else {
+            index - self.replaced.len() + self.char_range.len()
+        }
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
+fn layout_ime_preedit(
+    ui: &Ui,
+    text: &str,
+    preedit: &ImePreedit,
+    cursor_range: &CursorRange,
+    layouter: &mut dyn FnMut(&Ui, &str, f32) -> Arc<Galley>,
+    wrap_width: f32,
+    galley_pos: Pos2,
+    row_height: f32,
+) -> ImePreeditLayout {
+    let replaced = cursor_range.as_sorted_char_range();
+    let num_chars = text.chars().count();
+    let shown_text = format!(
+        "{}{}{}",
+        slice_char_range(text, 0..replaced.start),
+        preedit.text,
+        slice_char_range(text, replaced.end..num_chars)
+    );
+    let galley = layouter(ui, &shown_text, wrap_width);
+
+    let char_range = replaced.start..replaced.start + preedit.text.chars().count();
+    let cursor_index = preedit.cursor.as_ref().map_or(0, |cursor| cursor.end);
+    let cursor = galley.from_ccursor(CCursor::new(
+        (char_range.start + cursor_index).min(char_range.end),
+    ));
+    let cursor_rect = cursor_rect(galley_pos, &galley, &cursor, row_height);
+
+    ImePreeditLayout {
+        galley,
+        char_range,
+        replaced,
+        cursor_rect,
+        show_cursor: preedit.cursor.is_some(),
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+/// Insert the characters of `text_to_insert` that `char_filter` accepts and that fit within `char_limit`.
+///
+/// Returns `true` if any characters were rejected.
+fn insert_filtered(
+    text: &mut dyn TextBuffer,
+    ccursor: &mut CCursor,
+    text_to_insert: &str,
+    char_limit: usize,
+    char_filter: Option<&CharFilter<'_>>,
+) -> bool {
+    let filtered = char_filter.map(|char_filter| char_filter.filter(text.as_str(), text_to_insert));
+    let index_before = *ccursor;
+    text.insert_text_at(
+        ccursor,
+        filtered.as_deref().unwrap_or(text_to_insert),
+        char_limit,
+    );
+    ccursor.index - index_before.index < text_to_insert.chars().count()
+}
+
+/// Alt-clicking adds another cursor, which becomes the primary one.
+///
+/// Cursors that overlap the new one are merged with it.
+/// Returns `true` if the pointer was used for that.
+fn add_cursor_at_pointer(
+    ui: &Ui,
+    state: &mut TextEditState,
+    response: &Response,
+    cursor_at_pointer: &Cursor,
+) -> bool {
+    let Some(old_primary) = state.cursor.char_range() else {
+        return false;
+    };
+    let is_alt_press =
+        ui.input(|i| i.pointer.any_pressed() && i.modifiers.alt && !i.modifiers.shift);
+    if !response.hovered() || !is_alt_press {
+        return false;
+    }
+
+    let mut extra_ranges = std::mem::take(&mut state.extra_ccursor_ranges);
+    extra_ranges.push(old_primary);
+    let mut primary = CCursorRange::one(cursor_at_pointer.ccursor);
+    merge_overlapping_ranges(&mut primary, &mut extra_ranges);
+    state.cursor.set_char_range(Some(primary));
+    state.extra_ccursor_ranges = extra_ranges;
+    true
+}
+
+/// Dragging the selection drags the selected text (see [`DraggedText`]),
+/// rather than selecting other text.
+///
+/// Returns `true` if the pointer was used for that.
+fn drag_selected_text(
+    ui: &Ui,
+    state: &mut TextEditState,
+    response: &Response,
+    text: &str,
+    cursor_at_pointer: &Cursor,
+    allow_drag: bool,
+) -> bool {
+    if response.hovered() && ui.input(|i| i.pointer.any_pressed()) {
+        // The start of a drag (or a click):
+        state.text_drag = None;
+        let modifiers = ui.input(|i| i.modifiers);
+        let has_one_cursor =
+            state.extra_char_ranges().is_empty() && state.cursor.block_selection().is_none();
+        let selection = state
+            .cursor
+            .char_range()
+            .filter(|selection| !selection.is_empty() && has_one_cursor);
+        if let Some(selection) = selection {
+            let [min, max] = selection.sorted();
+            let index = cursor_at_pointer.ccursor.index;
+            // Pressing a double-clicked word again is the start of a triple-click, not a drag:
+            let is_single_press = ui.input(|i| i.pointer.press_click_count()).unwrap_or(1) == 1;
+            if allow_drag
+                && !modifiers.shift
+                && !modifiers.alt
+                && is_single_press
+                && min.index <= index
+                && index <= max.index
+            {
+                state.text_drag = Some(TextDrag {
+                    char_range: min.index..max.index,
+                    started: false,
+                    moved: Default::default(),
+                });
+                return true;
+            }
+        }
+        return false;
+    }
+
+    let Some(text_drag) = &mut state.text_drag else {
+        return false;
+    };
+    if text_drag.started {
+        // Keep the selection while it is dragged:
+        ui.ctx().is_being_dragged(response.id)
+    } else if response.drag_started() {
+        text_drag.started = true;
+        DragAndDrop::set_payload(
+            ui.ctx(),
+            DraggedText {
+                text: slice_char_range(text, text_drag.char_range.clone()).to_owned(),
+                source: response.id,
+                char_range: text_drag.char_range.clone(),
+                moved: text_drag.moved.clone(),
+            },
+        );
+        true
+    } else if response.double_clicked() || response.triple_clicked() {
+        state.text_drag = None;
+        false
+    } else if response.clicked() {
+        // Clicking the selection without dragging it just moves the cursor there:
+        state.text_drag = None;
+        state
+            .cursor
+            .set_char_range(Some(CCursorRange::one(cursor_at_pointer.ccursor)));
+        true
+    } else {
+        let is_pressed = ui.input(|i| i.pointer.any_down());
+        if !is_pressed {
+            state.text_drag = None;
+        }
+        is_pressed
+    }
+}
+
+/// Insert `dragged` text at `drop_ccursor`.
+/// Text that is moved within this [`TextEdit`] is also deleted from where it was.
+///
+/// Returns the dropped text as a char range, or `None` if nothing changed.
+#[allow(clippy::too_many_arguments)]
+fn drop_text(
+    state: &mut TextEditState,
+    text: &mut dyn TextBuffer,
+    id: Id,
+    dragged: &DraggedText,
+    drop_ccursor: CCursor,
+    copy: bool,
+    char_limit: usize,
+    char_filter: Option<&CharFilter<'_>>,
+    input_rejected: &mut bool,
+) -> Option<CCursorRange> {
+    let is_source = dragged.source == id;
+    let mut insert_at = drop_ccursor.index;
+    if is_source {
+        state.text_drag = None;
+        // Dropping the selection onto itself does nothing:
+        if dragged.char_range.start <= insert_at && insert_at <= dragged.char_range.end {
+            return None;
+        }
+    }
+
+    // Moving the text is one undo step:
+    state.undoer.lock().add_undo(&(
+        state.cursor.char_range().unwrap_or_default(),
+        text.as_str().to_owned(),
+    ));
+    state.undo_group = None;
+
+    if is_source && !copy {
+        text.delete_char_range(dragged.char_range.clone());
+        if dragged.char_range.end <= insert_at {
+            insert_at -= dragged.char_range.len();
+        }
+    }
+    let mut ccursor = CCursor::new(insert_at);
+    *input_rejected |= insert_filtered(text, &mut ccursor, &dragged.text, char_limit, char_filter);
+    if !is_source && !copy && insert_at < ccursor.index {
+        dragged.mark_moved();
+    }
+    Some(CCursorRange::two(CCursor::new(insert_at), ccursor))
+}
+
+/// Middle-clicking pastes the primary selection at `cursor_at_pointer`,
+/// as sent by the integration in [`Event::PasteFromPrimary`].
+///
+/// Returns the cursor after the pasted text, or `None` if nothing was pasted.
+fn paste_from_primary(
+    ui: &Ui,
+    state: &mut TextEditState,
+    text: &mut dyn TextBuffer,
+    cursor_at_pointer: CCursor,
+    char_limit: usize,
+    char_filter: Option<&CharFilter<'_>>,
+    input_rejected: &mut bool,
+) -> Option<CCursor> {
+    let pasted = ui.input(|i| {
+        if !i.pointer.button_pressed(PointerButton::Middle) {
+            return None;
+        }
+        i.events.iter().find_map(|event| match event {
+            Event::PasteFromPrimary(pasted) if !pasted.is_empty() => Some(pasted.clone()),
+            _ => None,
+        })
+    })?;
+
+    // Pasting is one undo step:
+    state.undoer.lock().add_undo(&(
+        state.cursor.char_range().unwrap_or_default(),
+        text.as_str().to_owned(),
+    ));
+    state.undo_group = None;
+
+    let mut ccursor = cursor_at_pointer;
+    *input_rejected |= insert_filtered(text, &mut ccursor, &pasted, char_limit, char_filter);
+    Some(ccursor)
+}
+
+/// Delete the dragged text once it has been moved to another widget.
+///
+/// Returns `true` if the text changed.
+fn delete_moved_text(
+    ui: &Ui,
+    state: &mut TextEditState,
+    text: &mut dyn TextBuffer,
+    id: Id,
+) -> bool {
+    let Some(text_drag) = state
+        .text_drag
+        .clone()
+        .filter(|text_drag| text_drag.started)
+    else {
+        return false;
+    };
+    if text_drag.is_moved() {
+        state.text_drag = None;
+        state.undoer.lock().add_undo(&(
+            state.cursor.char_range().unwrap_or_default(),
+            text.as_str().to_owned(),
+        ));
+        state.undo_group = None;
+        text.delete_char_range(text_drag.char_range.clone());
+        state
+            .cursor
+            .set_char_range(Some(CCursorRange::one(CCursor::new(
+                text_drag.char_range.start,
+            ))));
+        true
+    } else {
+        // The widget it is dropped on (if any) marks it as moved in the frame it is released,
+        // so we wait for the next frame before giving up:
+        if !ui.ctx().is_being_dragged(id) && !ui.input(|i| i.pointer.any_released()) {
+            state.text_drag = None;
+        }
+        false
+    }
+}
+
+/// Replace the word at `ccursor` with the text of a picked [`CompletionItem`].
+///
+/// Returns the cursor after the inserted text.
+fn insert_completion(
+    text: &mut dyn TextBuffer,
+    ccursor: CCursor,
+    item: &CompletionItem,
+    char_limit: usize,
+    char_filter: Option<&CharFilter<'_>>,
+    input_rejected: &mut bool,
+) -> CCursor {
+    let word = word_at(text.as_str(), ccursor);
+    text.delete_char_range(word.clone());
+    let mut ccursor = CCursor::new(word.start);
+    *input_rejected |= insert_filtered(text, &mut ccursor, &item.text, char_limit, char_filter);
+    ccursor
+}
+
+/// The char to insert after the `typed` char, if it opens one of [`TextEdit::auto_close_pairs`].
+fn auto_closing_char(
+    text: &str,
+    cursor_range: &CursorRange,
+    typed: &str,
+    auto_close_pairs: &[(char, char)],
+) -> Option<char> {
+    let typed = single_char(typed)?;
+    let &(opening, closing) = auto_close_pairs
+        .iter()
+        .find(|(opening, _)| *opening == typed)?;
+    if !cursor_range.is_empty() {
+        return None;
+    }
+
+    let index = cursor_range.primary.ccursor.index;
+    let next = text.chars().nth(index);
+    let before_space_or_closing = next.map_or(true, |next| {
+        next.is_whitespace() || auto_close_pairs.iter().any(|(_, closing)| *closing == next)
+    });
+    let after_word = index
+        .checked_sub(1)
+        .and_then(|prev| text.chars().nth(prev))
+        .is_some_and(is_word_char);
+    let closes = before_space_or_closing && !(opening == closing && after_word);
+    closes.then_some(closing)
+}
+
+/// Does typing the `typed` char step over a closing char inserted by [`TextEdit::auto_close_pairs`]?
+fn steps_over_auto_closed(
+    text: &str,
+    cursor_range: &CursorRange,
+    typed: &str,
+    auto_closed: &[usize],
+) -> bool {
+    let index = cursor_range.primary.ccursor.index;
+    cursor_range.is_empty()
+        && auto_closed.contains(&index)
+        && single_char(typed).is_some_and(|typed| text.chars().nth(index) == Some(typed))
+}
+
+/// See [`TextEditState::auto_closed_at`].
+fn auto_closed_at(text: &str, ccursor_range: &CCursorRange) -> ([usize; 2], usize) {
+    (
+        [ccursor_range.primary.index, ccursor_range.secondary.index],
+        text.chars().count(),
+    )
+}
+
+/// Is the cursor between the chars of a pair inserted by [`TextEdit::auto_close_pairs`]?
+fn is_in_auto_closed_pair(
+    text: &str,
+    cursor_range: &CursorRange,
+    auto_close_pairs: &[(char, char)],
+    auto_closed: &[usize],
+) -> bool {
+    let index = cursor_range.primary.ccursor.index;
+    if !cursor_range.is_empty() || index == 0 || !auto_closed.contains(&index) {
+        return false;
+    }
+    let mut chars = text.chars().skip(index - 1);
+    let pair = (chars.next(), chars.next());
+    auto_close_pairs
+        .iter()
+        .any(|&(opening, closing)| pair == (Some(opening), Some(closing)))
+}
+
+fn single_char(s: &str) -> Option<char> {
+    let mut chars = s.chars();
+    let c = chars.next()?;
+    chars.next().is_none().then_some(c)
+}
+
+/// The leading whitespace of the line of `ccursor`, for the line that a newline at `ccursor` starts,
+/// plus one more `indent_string` if the line before `ccursor` ends in `{` or `:`.
+fn indentation_for_new_line(text: &str, ccursor: CCursor, indent_string: &str) -> String {
+    let line_start = find_line_start(text, ccursor);
+    let line = slice_char_range(text, line_start.index..ccursor.index);
+    let mut indentation: String = line
+        .chars()
+        .take_while(|&c| c == ' ' || c == '\t')
+        .collect();
+    if line.trim_end().ends_with(['{', ':']) {
+        indentation.push_str(indent_string);
+    }
+    indentation
+}
+
+/// The char range of the `indent_string` just before the cursor,
+/// if there is only indentation between it and the start of the line.
+fn indent_before_cursor(
+    text: &str,
+    cursor_range: &CursorRange,
+    indent_string: &str,
+) -> Option<Range<usize>> {
+    if !cursor_range.is_empty() || indent_string.is_empty() {
+        return None;
+    }
+    let ccursor = cursor_range.primary.ccursor;
+    let line_start = find_line_start(text, ccursor);
+    let before_cursor = slice_char_range(text, line_start.index..ccursor.index);
+    let is_indentation = before_cursor.chars().all(|c| c == ' ' || c == '\t');
+    (is_indentation && before_cursor.ends_with(indent_string))
+        .then(|| ccursor.index - indent_string.chars().count()..ccursor.index)
+}
+
+/// Insert `indent_string` at the start of each line of `ccursor_range`,
+/// or with `outdent` remove up to one level of indentation from them.
+///
+/// A line where the selection ends at its very start is left alone.
+/// Returns the range adjusted so that the same text stays selected,
+/// or `None` if indenting all the lines would go over `char_limit`, in which case nothing is changed.
+fn indent_lines(
+    text: &mut dyn TextBuffer,
+    ccursor_range: CCursorRange,
+    indent_string: &str,
+    outdent: bool,
+    char_limit: usize,
+) -> Option<CCursorRange> {
+    let [min, max] = ccursor_range.sorted().map(|ccursor| ccursor.index);
+    let indent_width = if indent_string.chars().all(|c| c == ' ') {
+        indent_string.chars().count()
+    } else {
+        TAB_SIZE
+    };
+
+    // The start of each selected line, and how many chars we insert (or remove) there:
+    let mut line_edits: Vec<(usize, usize)> = vec![];
+    let mut line_start = 0;
+    for line in text.as_str().split('\n') {
+        let line_len = line.chars().count();
+        let is_selected = min <= line_start + line_len && (line_start < max || line_start == min);
+        if is_selected {
+            let num_chars =
+                if !outdent || (line.starts_with(indent_string) && !indent_string.is_empty()) {
+                    indent_string.chars().count()
+                } else if line.starts_with('\t') {
+                    1
+                } else {
+                    line.chars()
+                        .take(indent_width)
+                        .take_while(|&c| c == ' ')
+                        .count()
+                };
+            line_edits.push((line_start, num_chars));
+        }
+        line_start += line_len + 1;
+    }
+
+    let num_inserted: usize = line_edits.iter().map(|&(_, num_chars)| num_chars).sum();
+    if !outdent && char_limit < text.as_str().chars().count().saturating_add(num_inserted) {
+        return None;
+    }
+
+    for &(line_start, num_chars) in line_edits.iter().rev() {
+        if outdent {
+            text.delete_char_range(line_start..line_start + num_chars);
+        } else {
+            text.insert_text(indent_string, line_start);
+        }
+    }
+
+    let adjust = |ccursor: CCursor| {
+        let mut index = ccursor.index;
+        for &(line_start, num_chars) in &line_edits {
+            if outdent {
+                if ccursor.index >= line_start + num_chars {
+                    index -= num_chars;
+                } else if ccursor.index > line_start {
+                    index -= ccursor.index - line_start;
+                }
+            } else if line_start < ccursor.index {
+                index += num_chars;
+            }
+        }
+        CCursor::new(index)
+    };
+    Some(CCursorRange {
+        primary: adjust(ccursor_range.primary),
+        secondary: adjust(ccursor_range.secondary),
+    })
+}
+
+/// In overwrite mode, delete the `num_chars` characters after `ccursor` that typing replaces.
+///
+/// We never delete past the end of the line.
+fn overwrite_chars(text: &mut dyn TextBuffer, ccursor: CCursor, num_chars: usize) {
+    let after_cursor = &text.as_str()[text.byte_index_from_char_index(ccursor.index)..];
+    let num_replaced = after_cursor
+        .chars()
+        .take(num_chars)
+        .take_while(|&c| c != '\n')
+        .count();
+    text.delete_char_range(ccursor.index..ccursor.index + num_replaced);
+}
+
+/// The cursor of overwrite mode: a block covering the character after `cursor`.
+fn block_cursor_rect(galley_pos: Pos2, galley: &Galley, cursor: &Cursor, row_height: f32) -> Rect {
+    let index = cursor.ccursor.index;
+    let char_width = galley
+        .rects_for_char_range(index..index + 1)
+        .first()
+        .map_or(0.0, |rect| rect.width());
+    // At the end of a line there is no character, so we use half a row instead:
+    let width = if char_width > 0.0 {
+        char_width
+    } else {
+        0.5 * row_height
+    };
+    let pos = galley
+        .pos_from_cursor(cursor)
+        .translate(galley_pos.to_vec2());
+    Rect::from_min_size(pos.min, vec2(width, pos.height().at_least(row_height)))
+}
+
+// ----------------------------------------------------------------------------
+
+/// The focus lock filter of a focused [`TextEdit`]:
+/// which keys it handles itself, rather than moving the focus with.
+fn focus_lock_filter(event_filter: EventFilter, state: &TextEditState) -> EventFilter {
+    // With several cursors, escape removes all but the primary one:
+    let has_several_cursors =
+        !state.extra_char_ranges().is_empty() || state.cursor.block_selection().is_some();
+    // The completion popup is used with the keyboard:
+    let completion_open = state.completion.is_some();
+    EventFilter {
+        tab: event_filter.tab || completion_open,
+        vertical_arrows: event_filter.vertical_arrows || completion_open,
+        escape: event_filter.escape || has_several_cursors || completion_open,
+        ..event_filter
+    }
+}
+
+/// Check for (keyboard) events to edit the cursor and/or text.
+#[allow(clippy::too_many_arguments, clippy::fn_params_excessive_bools)]
 fn events(
     ui: &crate::Ui,
     state: &mut TextEditState,
@@ -825,60 +2451,424 @@ fn events(
     wrap_width: f32,
     multiline: bool,
     password: bool,
+    allow_password_copy: bool,
+    read_only: bool,
     default_cursor_range: CursorRange,
     char_limit: usize,
+    char_filter: Option<&CharFilter<'_>>,
     event_filter: EventFilter,
     return_key: Option<KeyboardShortcut>,
-) -> (bool, CursorRange) {
+    kill_keys: bool,
+    keymap: Option<&TextEditKeymap>,
+    auto_close_pairs: &[(char, char)],
+    auto_indent: bool,
+    indent_string: &str,
+    undo_merge: UndoMergePolicy,
+    on_paste_image: Option<&mut (dyn FnMut(ColorImage) -> Option<String> + '_)>,
+) -> (bool, bool, CursorRange) {
     let os = ui.ctx().os();
+    let interaction = &ui.style().interaction;
+    let underscore_is_word_char = interaction.underscore_is_word_char;
 
     let mut cursor_range = state.cursor.range(galley).unwrap_or(default_cursor_range);
+    let mut preferred_x = state.cursor.preferred_x();
+    let mut extra_ranges = state.extra_char_ranges().to_vec();
+    let mut block_selection = state.cursor.block_selection().copied();
 
     // We feed state to the undoer both before and after handling input
     // so that the undoer creates automatic saves even when there are no events for a while.
-    state.undoer.lock().feed_state(
-        ui.input(|i| i.time),
-        &(cursor_range.as_ccursor_range(), text.as_str().to_owned()),
-    );
+    let time = ui.input(|i| i.time);
+    if undo_merge == UndoMergePolicy::Time {
+        state.undoer.lock().feed_state(
+            time,
+            &(cursor_range.as_ccursor_range(), text.as_str().to_owned()),
+        );
+    }
 
-    let copy_if_not_password = |ui: &Ui, text: String| {
-        if !password {
-            ui.ctx().copy_text(text);
+    let copy_if_not_password = |ui: &Ui, text: String, html: Option<String>| {
+        if !password || allow_password_copy {
+            if let Some(html) = html {
+                ui.ctx().copy_rich_text(text, html);
+            } else {
+                ui.ctx().copy_text(text);
+            }
         }
     };
 
+    // Forget the auto-closed chars if the cursor was moved or the text edited since they were typed:
+    if !state.auto_closed.is_empty()
+        && state.auto_closed_at
+            != Some(auto_closed_at(
+                text.as_str(),
+                &cursor_range.as_ccursor_range(),
+            ))
+    {
+        state.auto_closed.clear();
+    }
+
     let mut any_change = false;
+    let mut input_rejected = false;
+
+    // An item of the completion popup was clicked last frame:
+    let clicked_completion = state.completion.as_ref().and_then(|completion| {
+        let index = completion.clicked?;
+        completion.items.get(index).cloned()
+    });
+    if let Some(item) = clicked_completion {
+        state.completion = None;
+        state
+            .undoer
+            .lock()
+            .add_undo(&(cursor_range.as_ccursor_range(), text.as_str().to_owned()));
+        state.undo_group = None;
+        state.auto_closed.clear();
+        extra_ranges.clear();
+        block_selection = None;
+        let ccursor = insert_completion(
+            text,
+            cursor_range.primary.ccursor,
+            &item,
+            char_limit,
+            char_filter,
+            &mut input_rejected,
+        );
+        *galley = layouter(ui, text.as_str(), wrap_width);
+        cursor_range = CursorRange::one(galley.from_ccursor(ccursor));
+        any_change = true;
+    }
 
-    let events = ui.input(|i| i.filtered_events(&event_filter));
+    let mut events = ui.input(|i| i.filtered_events(&event_filter));
+    if let Some(keymap) = keymap.or(interaction.text_edit_keymap.as_ref()) {
+        events = remap_key_presses(ui, keymap, events, return_key);
+    }
+    if let Some(on_paste_image) = on_paste_image {
+        // A pasted image is pasted as the text the app gives for it:
+        events = events
+            .into_iter()
+            .filter_map(|event| match event {
+                Event::PastedImage(image) => on_paste_image(image).map(Event::Paste),
+                event => Some(event),
+            })
+            .collect();
+    }
     for event in &events {
+        // A block selection is copied with a line for each row.
+        // For editing and moving the cursor it becomes a cursor on each row:
+        let mut did_copy = false;
+        if let Some(block) = &block_selection {
+            if matches!(event, Event::Copy | Event::Cut) {
+                copy_if_not_password(ui, block.selected_text(galley), None);
+                did_copy = true;
+            }
+        }
+        if did_copy && matches!(event, Event::Copy) {
+            continue;
+        }
+        let uses_cursors = matches!(
+            event,
+            Event::Cut
+                | Event::Text(_)
+                | Event::Paste(_)
+                | Event::Ime(_)
+                | Event::Key { pressed: true, .. }
+        );
+        // The state before an edit, in case it starts a new undo step:
+        let state_before_edit =
+            uses_cursors.then(|| (cursor_range.as_ccursor_range(), text.as_str().to_owned()));
+        let mut is_undo_or_redo = false;
+        let mut is_completion = false;
+        let mut is_auto_close = false;
+        let ccursor_range_before = cursor_range.as_ccursor_range();
+        if uses_cursors {
+            // The last typed char of a password is only shown until the next keystroke:
+            state.password_last_typed = None;
+        }
+
+        let split_block = if uses_cursors {
+            block_selection.take()
+        } else {
+            None
+        };
+        if let Some(block) = split_block {
+            if matches!(event, Event::Text(_) | Event::Paste(_))
+                && pad_rows_to(text, galley, &block, char_limit)
+            {
+                *galley = layouter(ui, text.as_str(), wrap_width);
+            }
+
+            let mut row_ranges = block.char_ranges(galley);
+            if !row_ranges.is_empty() {
+                let primary_row = cursor_range.primary.rcursor.row;
+                let primary_index = primary_row
+                    .saturating_sub(block.first_row)
+                    .min(row_ranges.len() - 1);
+                cursor_range =
+                    cursor_range_from_ccursors(galley, &row_ranges.remove(primary_index));
+                extra_ranges = row_ranges;
+            }
+        }
+
         let did_mutate_text = match event {
+            // The completion popup is used with the keyboard while the focus stays here:
+            Event::Key {
+                key: key @ (Key::ArrowDown | Key::ArrowUp),
+                pressed: true,
+                modifiers,
+                ..
+            } if modifiers.is_none() && state.completion.is_some() => {
+                if let Some(completion) = &mut state.completion {
+                    completion.select_next(*key == Key::ArrowDown);
+                }
+                None
+            }
+            Event::Key {
+                key: Key::Tab | Key::Enter,
+                pressed: true,
+                modifiers,
+                ..
+            } if modifiers.is_none() && state.completion.is_some() => {
+                let item = state
+                    .completion
+                    .take()
+                    .and_then(|completion| completion.items.into_iter().nth(completion.selected));
+                item.map(|item| {
+                    extra_ranges.clear();
+                    is_completion = true;
+                    CCursorRange::one(insert_completion(
+                        text,
+                        cursor_range.primary.ccursor,
+                        &item,
+                        char_limit,
+                        char_filter,
+                        &mut input_rejected,
+                    ))
+                })
+            }
+            Event::Key {
+                key: Key::Escape,
+                pressed: true,
+                ..
+            } if state.completion.is_some() => {
+                state.completion = None;
+                None
+            }
+
             // First handle events that only changes the selection cursor, not the text:
-            event if cursor_range.on_event(os, event, galley, id) => None,
+            event
+                if cursor_range.on_event(os, event, galley, id, interaction, &mut preferred_x) =>
+            {
+                // Move the other cursors the same way:
+                for extra_range in &mut extra_ranges {
+                    let mut extra_cursor_range = cursor_range_from_ccursors(galley, extra_range);
+                    extra_cursor_range.on_event(os, event, galley, id, interaction, &mut None);
+                    *extra_range = extra_cursor_range.as_ccursor_range();
+                }
+                None
+            }
 
-            Event::Copy => {
+            Event::Key {
+                key: Key::Escape,
+                pressed: true,
+                ..
+            } if !extra_ranges.is_empty() => {
+                extra_ranges.clear();
+                None
+            }
+            Event::Key {
+                key: Key::Insert,
+                pressed: true,
+                modifiers,
+                ..
+            } if modifiers.is_none() && !read_only => {
+                state.overwrite_mode = !state.overwrite_mode;
+                None
+            }
+            Event::Key {
+                key: Key::D,
+                pressed: true,
+                modifiers,
+                ..
+            } if modifiers.matches_logically(Modifiers::COMMAND) => {
                 if cursor_range.is_empty() {
-                    copy_if_not_password(ui, text.as_str().to_owned());
+                    // Select the word at the cursor first:
+                    let word = select_word_at(
+                        text.as_str(),
+                        cursor_range.primary.ccursor,
+                        underscore_is_word_char,
+                    );
+                    cursor_range = cursor_range_from_ccursors(galley, &word);
+                } else if let Some(next) =
+                    next_occurrence(text.as_str(), &cursor_range, &extra_ranges)
+                {
+                    // Add a cursor that selects the next occurrence of the selection:
+                    extra_ranges.push(cursor_range.as_ccursor_range());
+                    cursor_range = cursor_range_from_ccursors(galley, &next);
+                }
+                None
+            }
+
+            Event::Copy => {
+                if cursor_range.is_empty() && extra_ranges.is_empty() {
+                    let html = copied_html(
+                        ui,
+                        galley,
+                        text.as_str(),
+                        std::iter::once(0..text.as_str().chars().count()),
+                    );
+                    copy_if_not_password(ui, text.as_str().to_owned(), html);
                 } else {
-                    copy_if_not_password(ui, cursor_range.slice_str(text.as_str()).to_owned());
+                    let html = copied_html(
+                        ui,
+                        galley,
+                        text.as_str(),
+                        selected_char_ranges(&cursor_range, &extra_ranges),
+                    );
+                    copy_if_not_password(
+                        ui,
+                        selected_text(text.as_str(), &cursor_range, &extra_ranges),
+                        html,
+                    );
                 }
                 None
             }
+
+            // Everything below changes the text:
+            _ if read_only => None,
+
+            // Typing the closing char of an auto-closed pair steps over it:
+            Event::Text(typed)
+                if extra_ranges.is_empty()
+                    && steps_over_auto_closed(
+                        text.as_str(),
+                        &cursor_range,
+                        typed,
+                        &state.auto_closed,
+                    ) =>
+            {
+                is_auto_close = true;
+                let index = cursor_range.primary.ccursor.index;
+                state.auto_closed.retain(|&closed| closed != index);
+                cursor_range =
+                    cursor_range_from_ccursors(galley, &CCursorRange::one(CCursor::new(index + 1)));
+                None
+            }
+            Event::Text(typed)
+                if extra_ranges.is_empty()
+                    && auto_closing_char(text.as_str(), &cursor_range, typed, auto_close_pairs)
+                        .is_some() =>
+            {
+                let closing =
+                    auto_closing_char(text.as_str(), &cursor_range, typed, auto_close_pairs);
+                closing.map(|closing| {
+                    is_auto_close = true;
+                    let at = cursor_range.primary.ccursor.index;
+                    let mut ccursor = cursor_range.primary.ccursor;
+                    input_rejected |=
+                        insert_filtered(text, &mut ccursor, typed, char_limit, char_filter);
+                    let mut after_closing = ccursor;
+                    if at < ccursor.index {
+                        insert_filtered(
+                            text,
+                            &mut after_closing,
+                            &closing.to_string(),
+                            char_limit,
+                            char_filter,
+                        );
+                    }
+                    for closed in &mut state.auto_closed {
+                        if at <= *closed {
+                            *closed += after_closing.index - at;
+                        }
+                    }
+                    if ccursor.index < after_closing.index {
+                        state.auto_closed.push(ccursor.index);
+                    }
+                    CCursorRange::one(ccursor)
+                })
+            }
+            Event::Key {
+                key: Key::Backspace,
+                pressed: true,
+                modifiers,
+                ..
+            } if modifiers.is_none()
+                && extra_ranges.is_empty()
+                && is_in_auto_closed_pair(
+                    text.as_str(),
+                    &cursor_range,
+                    auto_close_pairs,
+                    &state.auto_closed,
+                ) =>
+            {
+                // Delete the whole pair:
+                is_auto_close = true;
+                let index = cursor_range.primary.ccursor.index;
+                text.delete_char_range(index - 1..index + 1);
+                state.auto_closed.retain(|&closed| closed != index);
+                for closed in &mut state.auto_closed {
+                    if index < *closed {
+                        *closed -= 2;
+                    }
+                }
+                Some(CCursorRange::one(CCursor::new(index - 1)))
+            }
+
             Event::Cut => {
-                if cursor_range.is_empty() {
-                    copy_if_not_password(ui, text.take());
+                if cursor_range.is_empty() && extra_ranges.is_empty() && !did_copy {
+                    let html = copied_html(
+                        ui,
+                        galley,
+                        text.as_str(),
+                        std::iter::once(0..text.as_str().chars().count()),
+                    );
+                    copy_if_not_password(ui, text.take(), html);
                     Some(CCursorRange::default())
                 } else {
-                    copy_if_not_password(ui, cursor_range.slice_str(text.as_str()).to_owned());
-                    Some(CCursorRange::one(text.delete_selected(&cursor_range)))
+                    if !did_copy {
+                        let html = copied_html(
+                            ui,
+                            galley,
+                            text.as_str(),
+                            selected_char_ranges(&cursor_range, &extra_ranges),
+                        );
+                        copy_if_not_password(
+                            ui,
+                            selected_text(text.as_str(), &cursor_range, &extra_ranges),
+                            html,
+                        );
+                    }
+                    edit_at_each_cursor(
+                        text,
+                        galley,
+                        &cursor_range,
+                        &mut extra_ranges,
+                        |text, cursor_range| {
+                            Some(CCursorRange::one(text.delete_selected(cursor_range)))
+                        },
+                    )
                 }
             }
             Event::Paste(text_to_insert) => {
                 if !text_to_insert.is_empty() {
-                    let mut ccursor = text.delete_selected(&cursor_range);
-
-                    text.insert_text_at(&mut ccursor, text_to_insert, char_limit);
+                    edit_at_each_cursor(
+                        text,
+                        galley,
+                        &cursor_range,
+                        &mut extra_ranges,
+                        |text, cursor_range| {
+                            let mut ccursor = text.delete_selected(cursor_range);
+
+                            input_rejected |= insert_filtered(
+                                text,
+                                &mut ccursor,
+                                text_to_insert,
+                                char_limit,
+                                char_filter,
+                            );
 
-                    Some(CCursorRange::one(ccursor))
+                            Some(CCursorRange::one(ccursor))
+                        },
+                    )
                 } else {
                     None
                 }
@@ -886,11 +2876,38 @@ fn events(
             Event::Text(text_to_insert) => {
                 // Newlines are handled by `Key::Enter`.
                 if !text_to_insert.is_empty() && text_to_insert != "\n" && text_to_insert != "\r" {
-                    let mut ccursor = text.delete_selected(&cursor_range);
-
-                    text.insert_text_at(&mut ccursor, text_to_insert, char_limit);
+                    let overwrite_mode = state.overwrite_mode;
+                    edit_at_each_cursor(
+                        text,
+                        galley,
+                        &cursor_range,
+                        &mut extra_ranges,
+                        |text, cursor_range| {
+                            let mut ccursor = text.delete_selected(cursor_range);
+                            if overwrite_mode && cursor_range.is_empty() {
+                                let num_chars = char_filter.map_or_else(
+                                    || text_to_insert.chars().count(),
+                                    |char_filter| {
+                                        char_filter
+                                            .filter(text.as_str(), text_to_insert)
+                                            .chars()
+                                            .count()
+                                    },
+                                );
+                                overwrite_chars(text, ccursor, num_chars);
+                            }
+
+                            input_rejected |= insert_filtered(
+                                text,
+                                &mut ccursor,
+                                text_to_insert,
+                                char_limit,
+                                char_filter,
+                            );
 
-                    Some(CCursorRange::one(ccursor))
+                            Some(CCursorRange::one(ccursor))
+                        },
+                    )
                 } else {
                     None
                 }
@@ -900,16 +2917,36 @@ fn events(
                 pressed: true,
                 modifiers,
                 ..
-            } if multiline => {
-                let mut ccursor = text.delete_selected(&cursor_range);
-                if modifiers.shift {
-                    // TODO(emilk): support removing indentation over a selection?
-                    text.decrease_indentation(&mut ccursor);
-                } else {
-                    text.insert_text_at(&mut ccursor, "\t", char_limit);
-                }
-                Some(CCursorRange::one(ccursor))
-            }
+            } if multiline => edit_at_each_cursor(
+                text,
+                galley,
+                &cursor_range,
+                &mut extra_ranges,
+                |text, cursor_range| {
+                    if cursor_range.primary.pcursor.paragraph
+                        != cursor_range.secondary.pcursor.paragraph
+                    {
+                        // Indent or outdent all the selected lines:
+                        let indented = indent_lines(
+                            text,
+                            cursor_range.as_ccursor_range(),
+                            indent_string,
+                            modifiers.shift,
+                            char_limit,
+                        );
+                        input_rejected |= indented.is_none();
+                        return indented;
+                    }
+                    let mut ccursor = text.delete_selected(cursor_range);
+                    if modifiers.shift {
+                        text.decrease_indentation(&mut ccursor);
+                    } else {
+                        input_rejected |=
+                            insert_filtered(text, &mut ccursor, "\t", char_limit, char_filter);
+                    }
+                    Some(CCursorRange::one(ccursor))
+                },
+            ),
             Event::Key {
                 key,
                 pressed: true,
@@ -920,15 +2957,107 @@ fn events(
             }) =>
             {
                 if multiline {
-                    let mut ccursor = text.delete_selected(&cursor_range);
-                    text.insert_text_at(&mut ccursor, "\n", char_limit);
-                    // TODO(emilk): if code editor, auto-indent by same leading tabs, + one if the lines end on an opening bracket
-                    Some(CCursorRange::one(ccursor))
+                    edit_at_each_cursor(
+                        text,
+                        galley,
+                        &cursor_range,
+                        &mut extra_ranges,
+                        |text, cursor_range| {
+                            let mut ccursor = text.delete_selected(cursor_range);
+                            let indentation = if auto_indent && !modifiers.shift {
+                                indentation_for_new_line(text.as_str(), ccursor, indent_string)
+                            } else {
+                                String::new()
+                            };
+                            input_rejected |= insert_filtered(
+                                text,
+                                &mut ccursor,
+                                &format!("\n{indentation}"),
+                                char_limit,
+                                char_filter,
+                            );
+                            Some(CCursorRange::one(ccursor))
+                        },
+                    )
                 } else {
                     ui.memory_mut(|mem| mem.surrender_focus(id)); // End input with enter
                     break;
                 }
             }
+            Event::Key {
+                key: key @ (Key::K | Key::U | Key::W),
+                pressed: true,
+                modifiers,
+                ..
+            } if kill_keys && modifiers.ctrl && !modifiers.alt && !modifiers.shift => {
+                // Each kill is its own undo step:
+                state
+                    .undoer
+                    .lock()
+                    .add_undo(&(cursor_range.as_ccursor_range(), text.as_str().to_owned()));
+
+                let mut killed = vec![];
+                let new_ccursor_range = edit_at_each_cursor(
+                    text,
+                    galley,
+                    &cursor_range,
+                    &mut extra_ranges,
+                    |text, cursor_range| {
+                        let (ccursor, killed_text) =
+                            kill(text, galley, cursor_range, *key, interaction);
+                        killed.push((ccursor.index, killed_text));
+                        Some(CCursorRange::one(ccursor))
+                    },
+                );
+
+                // The cursors were edited from the back, so put the killed pieces in text order:
+                killed.sort_by_key(|(index, _)| *index);
+                let killed: Vec<String> = killed
+                    .into_iter()
+                    .map(|(_, killed_text)| killed_text)
+                    .filter(|killed_text| !killed_text.is_empty())
+                    .collect();
+                if !killed.is_empty() {
+                    state.kill_buffer = killed.join("\n");
+                }
+                new_ccursor_range
+            }
+            Event::Key {
+                key: Key::Y,
+                pressed: true,
+                modifiers,
+                ..
+            } if kill_keys
+                && os != OperatingSystem::Windows
+                && modifiers.ctrl
+                && !modifiers.alt
+                && !modifiers.shift =>
+            {
+                // Yank:
+                if state.kill_buffer.is_empty() {
+                    None
+                } else {
+                    let kill_buffer = &state.kill_buffer;
+                    edit_at_each_cursor(
+                        text,
+                        galley,
+                        &cursor_range,
+                        &mut extra_ranges,
+                        |text, cursor_range| {
+                            let mut ccursor = text.delete_selected(cursor_range);
+                            input_rejected |= insert_filtered(
+                                text,
+                                &mut ccursor,
+                                kill_buffer,
+                                char_limit,
+                                char_filter,
+                            );
+                            Some(CCursorRange::one(ccursor))
+                        },
+                    )
+                }
+            }
+
             Event::Key {
                 key: Key::Z,
                 pressed: true,
@@ -941,6 +3070,8 @@ fn events(
                     .undo(&(cursor_range.as_ccursor_range(), text.as_str().to_owned()))
                 {
                     text.replace_with(undo_txt);
+                    extra_ranges.clear();
+                    is_undo_or_redo = true;
                     Some(*undo_ccursor_range)
                 } else {
                     None
@@ -961,61 +3092,115 @@ fn events(
                     .redo(&(cursor_range.as_ccursor_range(), text.as_str().to_owned()))
                 {
                     text.replace_with(redo_txt);
+                    extra_ranges.clear();
+                    is_undo_or_redo = true;
                     Some(*redo_ccursor_range)
                 } else {
                     None
                 }
             }
 
+            Event::Key {
+                key: Key::Backspace,
+                pressed: true,
+                modifiers,
+                ..
+            } if multiline && modifiers.is_none() && auto_indent => edit_at_each_cursor(
+                text,
+                galley,
+                &cursor_range,
+                &mut extra_ranges,
+                |text, cursor_range| {
+                    if let Some(indent) =
+                        indent_before_cursor(text.as_str(), cursor_range, indent_string)
+                    {
+                        // Delete a whole level of indentation:
+                        text.delete_char_range(indent.clone());
+                        Some(CCursorRange::one(CCursor::new(indent.start)))
+                    } else {
+                        check_for_mutating_key_press(
+                            os,
+                            cursor_range,
+                            text,
+                            galley,
+                            modifiers,
+                            Key::Backspace,
+                            interaction,
+                        )
+                    }
+                },
+            ),
             Event::Key {
                 modifiers,
                 key,
                 pressed: true,
                 ..
-            } => check_for_mutating_key_press(os, &cursor_range, text, galley, modifiers, *key),
+            } => edit_at_each_cursor(
+                text,
+                galley,
+                &cursor_range,
+                &mut extra_ranges,
+                |text, cursor_range| {
+                    check_for_mutating_key_press(
+                        os,
+                        cursor_range,
+                        text,
+                        galley,
+                        modifiers,
+                        *key,
+                        interaction,
+                    )
+                },
+            ),
 
+            // IME is only supported with a single cursor:
             Event::Ime(ime_event) => match ime_event {
                 ImeEvent::Enabled => {
                     state.ime_enabled = true;
-                    state.ime_cursor_range = cursor_range;
                     None
                 }
-                ImeEvent::Preedit(text_mark) => {
-                    if text_mark == "\n" || text_mark == "\r" {
-                        None
-                    } else {
+                ImeEvent::Preedit {
+                    text: text_mark,
+                    cursor,
+                } => {
+                    if text_mark != "\n" && text_mark != "\r" {
+                        // The preedit is shown at the cursor, but not added to `text` until it is committed.
                         // Empty prediction can be produced when user press backspace
-                        // or escape during IME, so we clear current text.
-                        let mut ccursor = text.delete_selected(&cursor_range);
-                        let start_cursor = ccursor;
-                        if !text_mark.is_empty() {
-                            text.insert_text_at(&mut ccursor, text_mark, char_limit);
-                        }
-                        state.ime_cursor_range = cursor_range;
-                        Some(CCursorRange::two(start_cursor, ccursor))
+                        // or escape during IME, so we clear the current preedit.
+                        extra_ranges.clear();
+                        state.ime_preedit = (!text_mark.is_empty()).then(|| ImePreedit {
+                            text: text_mark.clone(),
+                            cursor: cursor.clone(),
+                        });
                     }
+                    None
                 }
                 ImeEvent::Commit(prediction) => {
                     if prediction == "\n" || prediction == "\r" {
                         None
                     } else {
                         state.ime_enabled = false;
+                        state.ime_preedit = None;
+                        extra_ranges.clear();
 
-                        if !prediction.is_empty()
-                            && cursor_range.secondary.ccursor.index
-                                == state.ime_cursor_range.secondary.ccursor.index
-                        {
-                            let mut ccursor = text.delete_selected(&cursor_range);
-                            text.insert_text_at(&mut ccursor, prediction, char_limit);
-                            Some(CCursorRange::one(ccursor))
+                        if prediction.is_empty() {
+                            None
                         } else {
-                            let ccursor = cursor_range.primary.ccursor;
+                            let mut ccursor = text.delete_selected(&cursor_range);
+                            input_rejected |= insert_filtered(
+                                text,
+                                &mut ccursor,
+                                prediction,
+                                char_limit,
+                                char_filter,
+                            );
                             Some(CCursorRange::one(ccursor))
                         }
                     }
                 }
                 ImeEvent::Disabled => {
                     state.ime_enabled = false;
+                    state.ime_preedit = None;
                     None
                 }
             },
@@ -1024,31 +3209,366 @@ fn events(
         };
 
         if let Some(new_ccursor_range) = did_mutate_text {
+            preferred_x = None;
             any_change = true;
 
+            if !is_auto_close && !state.auto_closed.is_empty() {
+                // Typing moves the auto-inserted closing chars after the cursor along,
+                // other edits forget about them:
+                match &state_before_edit {
+                    Some((before, _))
+                        if matches!(event, Event::Text(_))
+                            && before.is_empty()
+                            && extra_ranges.is_empty()
+                            && !state.overwrite_mode =>
+                    {
+                        let at = before.primary.index;
+                        let num_inserted = new_ccursor_range.primary.index.saturating_sub(at);
+                        for closed in &mut state.auto_closed {
+                            if at <= *closed {
+                                *closed += num_inserted;
+                            }
+                        }
+                    }
+                    _ => state.auto_closed.clear(),
+                }
+            }
+
+            if password && matches!(event, Event::Text(_)) {
+                state.password_last_typed =
+                    Some((new_ccursor_range.primary.index.saturating_sub(1), time));
+            }
+
+            if is_undo_or_redo {
+                state.undo_group = None;
+            } else if let Some(state_before_edit) = state_before_edit {
+                // A picked completion is an undo step of its own:
+                let kind = if is_completion || is_auto_close {
+                    EditKind::Other
+                } else {
+                    edit_kind(event)
+                };
+                if undo_merge.starts_new_step(
+                    state.undo_group.as_ref(),
+                    kind,
+                    &state_before_edit.0,
+                    time,
+                ) {
+                    state
+                        .undoer
+                        .lock()
+                        .add_undo_before_change(&state_before_edit);
+                }
+                state.undo_group = Some(UndoGroup {
+                    kind,
+                    ccursor_range: new_ccursor_range,
+                    time,
+                });
+            }
+
             // Layout again to avoid frame delay, and to keep `text` and `galley` in sync.
             *galley = layouter(ui, text.as_str(), wrap_width);
 
             // Set cursor_range using new galley:
-            cursor_range = CursorRange {
-                primary: galley.from_ccursor(new_ccursor_range.primary),
-                secondary: galley.from_ccursor(new_ccursor_range.secondary),
-            };
+            cursor_range = cursor_range_from_ccursors(galley, &new_ccursor_range);
+        } else if !is_auto_close && cursor_range.as_ccursor_range() != ccursor_range_before {
+            // Moving the cursor forgets the auto-closed chars:
+            state.auto_closed.clear();
         }
-    }
 
-    state.cursor.set_range(Some(cursor_range));
+        if !extra_ranges.is_empty() {
+            // Cursors that moved or were edited into each other become one:
+            let ccursor_range = cursor_range.as_ccursor_range();
+            let mut merged = ccursor_range;
+            merge_overlapping_ranges(&mut merged, &mut extra_ranges);
+            if merged != ccursor_range {
+                cursor_range = cursor_range_from_ccursors(galley, &merged);
+            }
+        }
+    }
 
-    state.undoer.lock().feed_state(
-        ui.input(|i| i.time),
-        &(cursor_range.as_ccursor_range(), text.as_str().to_owned()),
-    );
+    // Setting the range forgets what a double- or triple-click selected,
+    // so leave it alone unless we did change it, to keep dragging by words/lines:
+    let ccursor_range = cursor_range.as_ccursor_range();
+    let cursor_moved = state.cursor.char_range().map_or(true, |old| {
+        old.primary.index != ccursor_range.primary.index
+            || old.secondary.index != ccursor_range.secondary.index
+    });
+    if any_change || cursor_moved {
+        state.cursor.set_range(Some(cursor_range));
+    }
+    state.auto_closed_at =
+        (!state.auto_closed.is_empty()).then(|| auto_closed_at(text.as_str(), &ccursor_range));
+    state.cursor.set_preferred_x(preferred_x);
+    state.set_extra_char_ranges(extra_ranges);
+    state.cursor.set_block_selection(block_selection);
+
+    if undo_merge == UndoMergePolicy::Time {
+        state.undoer.lock().feed_state(
+            time,
+            &(cursor_range.as_ccursor_range(), text.as_str().to_owned()),
+        );
+    }
 
-    (any_change, cursor_range)
+    (any_change, input_rejected, cursor_range)
 }
 
 // ----------------------------------------------------------------------------
 
+/// What kind of edit `event` made, for grouping edits into undo steps.
+fn edit_kind(event: &Event) -> EditKind {
+    match event {
+        Event::Text(text) => {
+            if text.chars().all(char::is_whitespace) {
+                EditKind::TypingWhitespace
+            } else {
+                EditKind::Typing
+            }
+        }
+        Event::Key {
+            key: Key::Enter | Key::Tab,
+            ..
+        } => EditKind::TypingWhitespace,
+        Event::Key {
+            key: Key::Backspace | Key::Delete,
+            ..
+        } => EditKind::Deleting,
+        Event::Key {
+            key: Key::H,
+            modifiers,
+            ..
+        } if modifiers.ctrl => EditKind::Deleting,
+        // Committed IME input is an undo step of its own, like a paste:
+        _ => EditKind::Other,
+    }
+}
+
+fn cursor_range_from_ccursors(galley: &Galley, ccursor_range: &CCursorRange) -> CursorRange {
+    CursorRange {
+        primary: galley.from_ccursor(ccursor_range.primary),
+        secondary: galley.from_ccursor(ccursor_range.secondary),
+    }
+}
+
+/// Apply `edit` at the primary cursor and at each of the extra ones,
+/// e.g. to type the same text at all of them.
+///
+/// The edits are made starting with the cursor furthest into the text.
+/// After each edit, the other cursors are moved past the chars it added or removed.
+/// The cursors still to be edited end up before the edit, where `text` (and `galley`)
+/// hasn't changed, even if the edit reached into them, e.g. by deleting a word backwards.
+///
+/// Returns the new primary cursor if we did mutate `text`, and updates `extra_ranges`.
+fn edit_at_each_cursor(
+    text: &mut dyn TextBuffer,
+    galley: &Galley,
+    cursor_range: &CursorRange,
+    extra_ranges: &mut [CCursorRange],
+    mut edit: impl FnMut(&mut dyn TextBuffer, &CursorRange) -> Option<CCursorRange>,
+) -> Option<CCursorRange> {
+    if extra_ranges.is_empty() {
+        return edit(text, cursor_range);
+    }
+
+    // The primary cursor comes first:
+    let mut ranges: Vec<CCursorRange> = std::iter::once(cursor_range.as_ccursor_range())
+        .chain(extra_ranges.iter().copied())
+        .collect();
+    let mut order: Vec<usize> = (0..ranges.len()).collect();
+    order.sort_by_key(|&i| std::cmp::Reverse(ranges[i].sorted().map(|ccursor| ccursor.index)));
+
+    let mut did_mutate = false;
+    for (num_edited, &i) in order.iter().enumerate() {
+        let text_before = text.as_str().to_owned();
+        let [min, _] = ranges[i].sorted();
+        let Some(new_range) = edit(text, &cursor_range_from_ccursors(galley, &ranges[i])) else {
+            continue;
+        };
+        did_mutate = true;
+        ranges[i] = new_range;
+
+        let [start, old_end, new_end] = changed_char_range(&text_before, text.as_str(), min.index);
+        for (n, &j) in order.iter().enumerate() {
+            if j == i {
+                continue;
+            }
+            let is_edited = n < num_edited;
+            let range = &mut ranges[j];
+            for ccursor in [&mut range.primary, &mut range.secondary] {
+                ccursor.index = if ccursor.index < start || (ccursor.index == start && !is_edited) {
+                    ccursor.index
+                } else if old_end <= ccursor.index {
+                    ccursor.index - old_end + new_end
+                } else if is_edited {
+                    new_end
+                } else {
+                    start
+                };
+            }
+        }
+    }
+
+    extra_ranges.copy_from_slice(&ranges[1..]);
+    did_mutate.then_some(ranges[0])
+}
+
+/// The chars that an edit replaced, as `[start, end before the edit, end after the edit]`.
+///
+/// Where a run of equal chars makes this ambiguous, the edit is taken to start
+/// no later than `edit_min`, the start of the cursor range that was edited.
+fn changed_char_range(before: &str, after: &str, edit_min: usize) -> [usize; 3] {
+    let [before_len, after_len] = [before, after].map(|text| text.chars().count());
+    let start = before
+        .chars()
+        .zip(after.chars())
+        .take_while(|(a, b)| a == b)
+        .count()
+        .min(edit_min);
+    let num_same_at_end = before
+        .chars()
+        .rev()
+        .zip(after.chars().rev())
+        .take_while(|(a, b)| a == b)
+        .count()
+        .min(before_len.min(after_len) - start);
+    [
+        start,
+        before_len - num_same_at_end,
+        after_len - num_same_at_end,
+    ]
+}
+
+/// Add spaces to the end of the lines that end before the block selection,
+/// so that text typed into the block lines up on every row.
+///
+/// Rows are only padded as far as `char_limit` allows.
+/// Returns `true` if we did mutate `text`.
+fn pad_rows_to(
+    text: &mut dyn TextBuffer,
+    galley: &Galley,
+    block: &BlockSelection,
+    char_limit: usize,
+) -> bool {
+    // The padding is for monospace text, where a space is as wide as anything else:
+    let Some(space_width) = galley
+        .rows
+        .iter()
+        .flat_map(|row| row.glyphs.iter())
+        .find(|glyph| glyph.chr == ' ')
+        .or_else(|| galley.rows.iter().find_map(|row| row.glyphs.first()))
+        .map(|glyph| glyph.size.x)
+        .filter(|&width| 0.0 < width)
+    else {
+        return false;
+    };
+
+    let mut did_mutate = false;
+    // Last row first, so that the rows still to be padded stay where they are:
+    for row in block.rows().rev() {
+        let Some(row_ref) = galley.rows.get(row) else {
+            continue;
+        };
+        let is_end_of_line = row_ref.ends_with_newline || row + 1 == galley.rows.len();
+        let missing_width = (block.x_range.min - row_ref.rect.right()).at_least(0.0);
+        let num_spaces = (missing_width / space_width).round() as usize;
+        if is_end_of_line && 0 < num_spaces {
+            let mut ccursor = galley
+                .from_rcursor(RCursor {
+                    row,
+                    column: row_ref.char_count_excluding_newline(),
+                })
+                .ccursor;
+            let index_before = ccursor.index;
+            text.insert_text_at(&mut ccursor, &" ".repeat(num_spaces), char_limit);
+            did_mutate |= index_before < ccursor.index;
+        }
+    }
+    did_mutate
+}
+
+/// The selected text of each cursor, in the order they come in the text, one per line.
+fn selected_text(text: &str, cursor_range: &CursorRange, extra_ranges: &[CCursorRange]) -> String {
+    selected_char_ranges(cursor_range, extra_ranges)
+        .into_iter()
+        .map(|range| slice_char_range(text, range))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The non-empty selections, in order, as char ranges.
+fn selected_char_ranges(
+    cursor_range: &CursorRange,
+    extra_ranges: &[CCursorRange],
+) -> Vec<Range<usize>> {
+    let mut ranges: Vec<Range<usize>> = std::iter::once(cursor_range.as_ccursor_range())
+        .chain(extra_ranges.iter().copied())
+        .map(|ccursor_range| {
+            let [min, max] = ccursor_range.sorted();
+            min.index..max.index
+        })
+        .filter(|range| !range.is_empty())
+        .collect();
+    ranges.sort_by_key(|range| range.start);
+    ranges
+}
+
+/// The given char ranges of the galley as HTML, if [`crate::style::Interaction::copy_rich_text`] is on
+/// and the galley shows the text as-is (and not e.g. a password mask).
+fn copied_html(
+    ui: &Ui,
+    galley: &Galley,
+    text: &str,
+    char_ranges: impl IntoIterator<Item = Range<usize>>,
+) -> Option<String> {
+    (ui.style().interaction.copy_rich_text && galley.text() == text).then(|| {
+        text_selection::html::html_block(
+            char_ranges
+                .into_iter()
+                .map(|range| text_selection::html::galley_html(galley, range)),
+        )
+    })
+}
+
+/// The next place where the selected text occurs again, wrapping around to the start of the text,
+/// skipping the ones that are already selected.
+fn next_occurrence(
+    text: &str,
+    cursor_range: &CursorRange,
+    extra_ranges: &[CCursorRange],
+) -> Option<CCursorRange> {
+    let needle = cursor_range.slice_str(text);
+    let needle_char_count = needle.chars().count();
+    let [_, max] = cursor_range.sorted_cursors();
+
+    let mut char_index = 0;
+    let mut byte_index = 0;
+    let starts: Vec<usize> = text
+        .match_indices(needle)
+        .map(|(start, _)| {
+            char_index += text[byte_index..start].chars().count();
+            byte_index = start;
+            char_index
+        })
+        .collect();
+
+    let is_selected = |start: usize| {
+        std::iter::once(cursor_range.as_ccursor_range())
+            .chain(extra_ranges.iter().copied())
+            .any(|ccursor_range| {
+                let [min, max] = ccursor_range.sorted();
+                min.index == start && max.index == start + needle_char_count
+            })
+    };
+
+    let after = starts.iter().filter(|&&start| max.ccursor.index <= start);
+    let before = starts.iter().filter(|&&start| start < max.ccursor.index);
+    let start = *after.chain(before).find(|&&start| !is_selected(start))?;
+    Some(CCursorRange::two(
+        CCursor::new(start),
+        CCursor::new(start + needle_char_count),
+    ))
+}
+
 /// Returns `Some(new_cursor)` if we did mutate `text`.
 fn check_for_mutating_key_press(
     os: OperatingSystem,
@@ -1057,6 +3577,7 @@ fn check_for_mutating_key_press(
     galley: &Galley,
     modifiers: &Modifiers,
     key: Key,
+    interaction: &Interaction,
 ) -> Option<CCursorRange> {
     match key {
         Key::Backspace => {
@@ -1065,7 +3586,7 @@ fn check_for_mutating_key_press(
             } else if let Some(cursor) = cursor_range.single() {
                 if modifiers.alt || modifiers.ctrl {
                     // alt on mac, ctrl on windows
-                    text.delete_previous_word(cursor.ccursor)
+                    text.delete_previous_word(cursor.ccursor, interaction)
                 } else {
                     text.delete_previous_char(cursor.ccursor)
                 }
@@ -1081,7 +3602,7 @@ fn check_for_mutating_key_press(
             } else if let Some(cursor) = cursor_range.single() {
                 if modifiers.alt || modifiers.ctrl {
                     // alt on mac, ctrl on windows
-                    text.delete_next_word(cursor.ccursor)
+                    text.delete_next_word(cursor.ccursor, interaction)
                 } else {
                     text.delete_next_char(cursor.ccursor)
                 }
@@ -1100,25 +3621,105 @@ fn check_for_mutating_key_press(
             Some(CCursorRange::one(ccursor))
         }
 
-        Key::K if modifiers.ctrl => {
-            let ccursor = text.delete_paragraph_after_cursor(galley, cursor_range);
-            Some(CCursorRange::one(ccursor))
-        }
+        _ => None,
+    }
+}
 
-        Key::U if modifiers.ctrl => {
-            let ccursor = text.delete_paragraph_before_cursor(galley, cursor_range);
-            Some(CCursorRange::one(ccursor))
+/// Kill (delete) the text that `key` (`K`, `U` or `W`) kills at the cursor,
+/// or the selection if there is one.
+///
+/// Returns the new cursor and the killed text.
+fn kill(
+    text: &mut dyn TextBuffer,
+    galley: &Galley,
+    cursor_range: &CursorRange,
+    key: Key,
+    interaction: &Interaction,
+) -> (CCursor, String) {
+    let old_text = text.as_str().to_owned();
+    let ccursor = if !cursor_range.is_empty() {
+        text.delete_selected(cursor_range)
+    } else {
+        match key {
+            Key::K => text.delete_paragraph_after_cursor(galley, cursor_range),
+            Key::U => text.delete_paragraph_before_cursor(galley, cursor_range),
+            _ => text.delete_previous_word(cursor_range.primary.ccursor, interaction),
         }
+    };
 
-        Key::W if modifiers.ctrl => {
-            let ccursor = if let Some(cursor) = cursor_range.single() {
-                text.delete_previous_word(cursor.ccursor)
-            } else {
-                text.delete_selected(cursor_range)
-            };
-            Some(CCursorRange::one(ccursor))
+    // Whatever was deleted started at the new cursor:
+    let killed_count = old_text.chars().count() - text.as_str().chars().count();
+    let killed_text = old_text
+        .chars()
+        .skip(ccursor.index)
+        .take(killed_count)
+        .collect();
+    (ccursor, killed_text)
+}
+
+/// Turn the key presses of a custom [`TextEditKeymap`] into the ones that do the same
+/// with the default keys, which is what the event handling of [`TextEdit`] understands.
+fn remap_key_presses(
+    ui: &Ui,
+    keymap: &TextEditKeymap,
+    events: Vec<Event>,
+    return_key: Option<KeyboardShortcut>,
+) -> Vec<Event> {
+    let os = ui.ctx().os();
+    let default_keymap = TextEditKeymap::default_for_ui(ui);
+    let mut remapped = Vec::with_capacity(events.len());
+    for event in events {
+        let Event::Key {
+            key,
+            pressed: true,
+            repeat,
+            modifiers,
+            ..
+        } = event
+        else {
+            remapped.push(event);
+            continue;
+        };
+        let default_command = default_keymap.command_for(key, modifiers);
+        let Some(command) = keymap.command_for(key, modifiers) else {
+            if default_command.is_none() {
+                remapped.push(event);
+            }
+            continue;
+        };
+        if Some(command) == default_command {
+            // Exactly like without a keymap:
+            remapped.push(event);
+            continue;
         }
 
-        _ => None,
+        let key_press = match command {
+            TextEditCommand::Cut => {
+                remapped.push(Event::Cut);
+                None
+            }
+            TextEditCommand::Copy => {
+                remapped.push(Event::Copy);
+                None
+            }
+            TextEditCommand::Paste => {
+                ui.ctx().send_viewport_cmd(ViewportCommand::RequestPaste);
+                None
+            }
+            TextEditCommand::Newline => {
+                return_key.map(|return_key| (return_key.logical_key, return_key.modifiers))
+            }
+            command => command.key_press(os, &ui.style().interaction),
+        };
+        if let Some((key, modifiers)) = key_press {
+            remapped.push(Event::Key {
+                key,
+                physical_key: None,
+                pressed: true,
+                repeat,
+                modifiers,
+            });
+        }
     }
+    remapped
 }
@@ -0,0 +1,89 @@
+/// Which characters can be typed or pasted into a [`TextEdit`](crate::TextEdit),
+/// see [`TextEdit::char_filter`](crate::TextEdit::char_filter).
+///
+/// Characters that are not accepted are dropped when they are inserted,
+/// so the rest of a pasted text still ends up in the [`TextEdit`](crate::TextEdit).
+///
+/// ```
+/// # egui::__run_test_ui(|ui| {
+/// # let mut text = String::new();
+/// use egui::text_edit::CharFilter;
+///
+/// ui.add(egui::TextEdit::singleline(&mut text).char_filter(CharFilter::decimal('.')));
+/// ui.add(egui::TextEdit::singleline(&mut text).char_filter(|c: char| c.is_ascii_uppercase()));
+/// # });
+/// ```
+pub struct CharFilter<'a> {
+    accepts: Box<dyn Fn(char) -> bool + 'a>,
+    at_most_once: Vec<char>,
+}
+
+impl<'a, F: Fn(char) -> bool + 'a> From<F> for CharFilter<'a> {
+    fn from(accepts: F) -> Self {
+        Self::new(accepts)
+    }
+}
+
+impl<'a> CharFilter<'a> {
+    /// Accept the characters for which `accepts` returns `true`.
+    pub fn new(accepts: impl Fn(char) -> bool + 'a) -> Self {
+        Self {
+            accepts: Box::new(accepts),
+            at_most_once: Vec::new(),
+        }
+    }
+
+    /// Only accept `c` if it isn't in the text already, e.g. for a decimal separator.
+    #[inline]
+    pub fn at_most_once(mut self, c: char) -> Self {
+        self.at_most_once.push(c);
+        self
+    }
+
+    /// The digits `0-9` and a minus sign.
+    pub fn integer() -> Self {
+        Self::new(|c| c.is_ascii_digit() || c == '-').at_most_once('-')
+    }
+
+    /// The digits `0-9`, a minus sign, and a single decimal `separator`, e.g. `'.'` or `','`.
+    pub fn decimal(separator: char) -> Self {
+        Self::new(move |c| c.is_ascii_digit() || c == '-' || c == separator)
+            .at_most_once('-')
+            .at_most_once(separator)
+    }
+
+    /// The hexadecimal digits `0-9`, `a-f` and `A-F`.
+    pub fn hexadecimal() -> Self {
+        Self::new(|c| c.is_ascii_hexdigit())
+    }
+
+    /// Does this filter accept `c`, ignoring [`Self::at_most_once`]?
+    pub fn accepts(&self, c: char) -> bool {
+        (self.accepts)(c)
+    }
+
+    /// The characters of `text_to_insert` that can be inserted into `text`.
+    pub(crate) fn filter(&self, text: &str, text_to_insert: &str) -> String {
+        let mut seen: Vec<char> = self
+            .at_most_once
+            .iter()
+            .copied()
+            .filter(|&c| text.contains(c))
+            .collect();
+        text_to_insert
+            .chars()
+            .filter(|&c| {
+                if !self.accepts(c) {
+                    return false;
+                }
+                if self.at_most_once.contains(&c) {
+                    if seen.contains(&c) {
+                        return false;
+                    }
+                    seen.push(c);
+                }
+                true
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,82 @@
+use std::ops::Range;
+
+use epaint::text::cursor::CCursor;
+
+use crate::text_selection::text_cursor_state::is_word_char;
+
+/// A suggestion in the completion popup of a [`TextEdit`](crate::TextEdit),
+/// see [`TextEdit::completion_provider`](crate::TextEdit::completion_provider).
+///
+/// ```
+/// # egui::__run_test_ui(|ui| {
+/// # let mut command = String::new();
+/// use egui::text_edit::CompletionItem;
+///
+/// let commands = ["open", "close", "quit"];
+/// ui.add(
+///     egui::TextEdit::singleline(&mut command).completion_provider(|text, ccursor| {
+///         let typed: String = text.chars().take(ccursor.index).collect();
+///         commands
+///             .iter()
+///             .filter(|command| !typed.is_empty() && command.starts_with(typed.as_str()))
+///             .map(|command| CompletionItem::new(*command).detail("command"))
+///             .collect()
+///     }),
+/// );
+/// # });
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CompletionItem {
+    /// Replaces the word at the cursor when the item is picked.
+    pub text: String,
+
+    /// Shown weakly after [`Self::text`], e.g. what kind of thing the item is.
+    pub detail: String,
+}
+
+impl CompletionItem {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            detail: String::new(),
+        }
+    }
+
+    /// Shown weakly after [`Self::text`].
+    #[inline]
+    pub fn detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = detail.into();
+        self
+    }
+}
+
+impl From<&str> for CompletionItem {
+    fn from(text: &str) -> Self {
+        Self::new(text)
+    }
+}
+
+impl From<String> for CompletionItem {
+    fn from(text: String) -> Self {
+        Self::new(text)
+    }
+}
+
+/// The word that a picked [`CompletionItem`] replaces:
+/// the word characters before and after `ccursor`, as a char range.
+pub(crate) fn word_at(text: &str, ccursor: CCursor) -> Range<usize> {
+    let before = text
+        .chars()
+        .take(ccursor.index)
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+        .take_while(|&c| is_word_char(c))
+        .count();
+    let after = text
+        .chars()
+        .skip(ccursor.index)
+        .take_while(|&c| is_word_char(c))
+        .count();
+    ccursor.index - before..ccursor.index + after
+}
@@ -0,0 +1,375 @@
+use std::sync::Arc;
+
+use crate::{os::OperatingSystem, style::Interaction, Id, Key, KeyboardShortcut, Modifiers, Ui};
+
+/// Where a [`TextEditCommand`] moves the cursor to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum CursorMove {
+    Left,
+    Right,
+    Up,
+    Down,
+
+    /// The start of the previous word.
+    WordLeft,
+
+    /// The end of the next word.
+    WordRight,
+
+    /// The start of the previous part of a word, like `Case` in `CamelCase`.
+    SubwordLeft,
+
+    /// The end of the next part of a word, like `Camel` in `CamelCase`.
+    SubwordRight,
+
+    /// The first non-whitespace character of the row, and then the start of it.
+    LineStart,
+    LineEnd,
+    TextStart,
+    TextEnd,
+}
+
+/// Something a key press does in a [`TextEdit`](crate::TextEdit), see [`TextEditKeymap`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum TextEditCommand {
+    /// Move the cursor, and deselect.
+    Move(CursorMove),
+
+    /// Move the cursor, extending the selection.
+    Select(CursorMove),
+
+    SelectAll,
+
+    /// Delete the selection, or else the character before the cursor.
+    DeleteBackward,
+
+    /// Delete the selection, or else the character after the cursor.
+    DeleteForward,
+
+    DeleteWordBackward,
+    DeleteWordForward,
+    DeleteToLineStart,
+    DeleteToLineEnd,
+
+    Cut,
+    Copy,
+
+    /// Ask the integration to paste from the clipboard.
+    Paste,
+
+    Undo,
+    Redo,
+
+    /// Insert a newline in a multiline [`TextEdit`](crate::TextEdit),
+    /// like pressing [`TextEdit::return_key`](crate::TextEdit::return_key).
+    Newline,
+
+    /// Insert a tab, or indent the selected lines.
+    Indent,
+
+    /// Remove a level of indentation from the current or selected lines.
+    Outdent,
+}
+
+impl TextEditCommand {
+    /// A key press that does this command, for the event handling of [`crate::TextEdit`].
+    ///
+    /// `None` for the commands that are not key presses.
+    pub(crate) fn key_press(
+        self,
+        os: OperatingSystem,
+        interaction: &Interaction,
+    ) -> Option<(Key, Modifiers)> {
+        let move_key_press = |cursor_move: CursorMove| match cursor_move {
+            CursorMove::Left => (Key::ArrowLeft, Modifiers::NONE),
+            CursorMove::Right => (Key::ArrowRight, Modifiers::NONE),
+            CursorMove::Up => (Key::ArrowUp, Modifiers::NONE),
+            CursorMove::Down => (Key::ArrowDown, Modifiers::NONE),
+            CursorMove::WordLeft => (Key::ArrowLeft, Modifiers::ALT),
+            CursorMove::WordRight => (Key::ArrowRight, Modifiers::ALT),
+            CursorMove::SubwordLeft => (Key::ArrowLeft, interaction.subword_modifiers),
+            CursorMove::SubwordRight => (Key::ArrowRight, interaction.subword_modifiers),
+            CursorMove::LineStart => (Key::Home, Modifiers::NONE),
+            CursorMove::LineEnd => (Key::End, Modifiers::NONE),
+            CursorMove::TextStart if os == OperatingSystem::Mac => {
+                (Key::ArrowUp, Modifiers::COMMAND)
+            }
+            CursorMove::TextEnd if os == OperatingSystem::Mac => {
+                (Key::ArrowDown, Modifiers::COMMAND)
+            }
+            CursorMove::TextStart => (Key::Home, Modifiers::CTRL),
+            CursorMove::TextEnd => (Key::End, Modifiers::CTRL),
+        };
+
+        match self {
+            Self::Move(cursor_move) => Some(move_key_press(cursor_move)),
+            Self::Select(cursor_move) => {
+                let (key, modifiers) = move_key_press(cursor_move);
+                Some((key, modifiers | Modifiers::SHIFT))
+            }
+            Self::SelectAll => Some((Key::A, Modifiers::COMMAND)),
+            Self::DeleteBackward => Some((Key::Backspace, Modifiers::NONE)),
+            Self::DeleteForward => Some((Key::Delete, Modifiers::NONE)),
+            Self::DeleteWordBackward => Some((Key::Backspace, Modifiers::ALT)),
+            Self::DeleteWordForward => Some((Key::Delete, Modifiers::ALT)),
+            Self::DeleteToLineStart => Some((Key::Backspace, Modifiers::MAC_CMD)),
+            Self::DeleteToLineEnd => Some((Key::Delete, Modifiers::MAC_CMD)),
+            Self::Undo => Some((Key::Z, Modifiers::COMMAND)),
+            Self::Redo => Some((Key::Y, Modifiers::COMMAND)),
+            Self::Indent => Some((Key::Tab, Modifiers::NONE)),
+            Self::Outdent => Some((Key::Tab, Modifiers::SHIFT)),
+            Self::Cut | Self::Copy | Self::Paste | Self::Newline => None,
+        }
+    }
+}
+
+/// Which keyboard shortcuts do what in a [`TextEdit`](crate::TextEdit).
+///
+/// Set it with [`TextEdit::keymap`](crate::TextEdit::keymap),
+/// or for all text edits with [`Interaction::text_edit_keymap`].
+/// Without one, the keys work as in [`Self::default_for`] the current [`OperatingSystem`] and style.
+///
+/// Key presses that are in neither this nor the default keymap
+/// (like typing text, or `Escape`) work as usual.
+/// Key presses that are only in the default keymap do nothing.
+///
+/// Cut, copy and paste are not in the default keymap, since the integration
+/// turns the platform's shortcuts for them into [`crate::Event::Cut`] etc,
+/// but they can be added to other keys.
+///
+/// ```
+/// # egui::__run_test_ui(|ui| {
+/// # let mut text = String::new();
+/// use egui::text_edit::{CursorMove, TextEditCommand, TextEditKeymap};
+/// use egui::{Key, KeyboardShortcut, Modifiers};
+///
+/// // Emacs-style line start and end:
+/// let keymap = TextEditKeymap::default_for(ui.ctx().os(), &ui.style().interaction)
+///     .bind(
+///         KeyboardShortcut::new(Modifiers::CTRL, Key::A),
+///         TextEditCommand::Move(CursorMove::LineStart),
+///     )
+///     .bind(
+///         KeyboardShortcut::new(Modifiers::CTRL, Key::E),
+///         TextEditCommand::Move(CursorMove::LineEnd),
+///     );
+/// ui.add(egui::TextEdit::singleline(&mut text).keymap(keymap));
+/// # });
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct TextEditKeymap {
+    /// The first one that matches a key press is used.
+    pub bindings: Vec<(KeyboardShortcut, TextEditCommand)>,
+}
+
+impl TextEditKeymap {
+    /// The keys of a [`TextEdit`](crate::TextEdit) on the given [`OperatingSystem`],
+    /// moving by the parts of words with [`Interaction::subword_modifiers`].
+    pub fn default_for(os: OperatingSystem, interaction: &Interaction) -> Self {
+        let is_mac = os == OperatingSystem::Mac;
+        let shortcut = KeyboardShortcut::new;
+
+        let mut moves = vec![
+            (shortcut(Modifiers::NONE, Key::ArrowLeft), CursorMove::Left),
+            (
+                shortcut(Modifiers::NONE, Key::ArrowRight),
+                CursorMove::Right,
+            ),
+            (shortcut(Modifiers::NONE, Key::ArrowUp), CursorMove::Up),
+            (shortcut(Modifiers::NONE, Key::ArrowDown), CursorMove::Down),
+            (shortcut(Modifiers::NONE, Key::Home), CursorMove::LineStart),
+            (shortcut(Modifiers::NONE, Key::End), CursorMove::LineEnd),
+            (
+                shortcut(interaction.subword_modifiers, Key::ArrowLeft),
+                CursorMove::SubwordLeft,
+            ),
+            (
+                shortcut(interaction.subword_modifiers, Key::ArrowRight),
+                CursorMove::SubwordRight,
+            ),
+            (
+                shortcut(Modifiers::ALT, Key::ArrowLeft),
+                CursorMove::WordLeft,
+            ),
+            (
+                shortcut(Modifiers::ALT, Key::ArrowRight),
+                CursorMove::WordRight,
+            ),
+            (
+                shortcut(Modifiers::COMMAND, Key::ArrowUp),
+                CursorMove::TextStart,
+            ),
+            (
+                shortcut(Modifiers::COMMAND, Key::ArrowDown),
+                CursorMove::TextEnd,
+            ),
+        ];
+        if is_mac {
+            moves.extend([
+                (
+                    shortcut(Modifiers::COMMAND, Key::ArrowLeft),
+                    CursorMove::LineStart,
+                ),
+                (
+                    shortcut(Modifiers::COMMAND, Key::ArrowRight),
+                    CursorMove::LineEnd,
+                ),
+            ]);
+        } else {
+            moves.extend([
+                (
+                    shortcut(Modifiers::CTRL, Key::ArrowLeft),
+                    CursorMove::WordLeft,
+                ),
+                (
+                    shortcut(Modifiers::CTRL, Key::ArrowRight),
+                    CursorMove::WordRight,
+                ),
+                (shortcut(Modifiers::CTRL, Key::Home), CursorMove::TextStart),
+                (shortcut(Modifiers::CTRL, Key::End), CursorMove::TextEnd),
+            ]);
+        }
+
+        let mut bindings = vec![];
+        for (shortcut, cursor_move) in moves {
+            bindings.push((shortcut, TextEditCommand::Move(cursor_move)));
+            let selecting =
+                KeyboardShortcut::new(shortcut.modifiers | Modifiers::SHIFT, shortcut.logical_key);
+            bindings.push((selecting, TextEditCommand::Select(cursor_move)));
+        }
+
+        if is_mac {
+            // Emacs-style keys:
+            bindings.extend([
+                (
+                    shortcut(Modifiers::CTRL, Key::A),
+                    TextEditCommand::Move(CursorMove::LineStart),
+                ),
+                (
+                    shortcut(Modifiers::CTRL, Key::E),
+                    TextEditCommand::Move(CursorMove::LineEnd),
+                ),
+                (
+                    shortcut(Modifiers::CTRL, Key::P),
+                    TextEditCommand::Move(CursorMove::Up),
+                ),
+                (
+                    shortcut(Modifiers::CTRL, Key::N),
+                    TextEditCommand::Move(CursorMove::Down),
+                ),
+                (
+                    shortcut(Modifiers::CTRL, Key::B),
+                    TextEditCommand::Move(CursorMove::Left),
+                ),
+                (
+                    shortcut(Modifiers::CTRL, Key::F),
+                    TextEditCommand::Move(CursorMove::Right),
+                ),
+                (
+                    shortcut(Modifiers::COMMAND, Key::Backspace),
+                    TextEditCommand::DeleteToLineStart,
+                ),
+                (
+                    shortcut(Modifiers::COMMAND, Key::Delete),
+                    TextEditCommand::DeleteToLineEnd,
+                ),
+            ]);
+        } else {
+            bindings.extend([
+                (
+                    shortcut(Modifiers::CTRL, Key::Backspace),
+                    TextEditCommand::DeleteWordBackward,
+                ),
+                (
+                    shortcut(Modifiers::CTRL, Key::Delete),
+                    TextEditCommand::DeleteWordForward,
+                ),
+            ]);
+        }
+
+        bindings.extend([
+            (
+                shortcut(Modifiers::COMMAND, Key::A),
+                TextEditCommand::SelectAll,
+            ),
+            (
+                shortcut(Modifiers::NONE, Key::Backspace),
+                TextEditCommand::DeleteBackward,
+            ),
+            (
+                shortcut(Modifiers::NONE, Key::Delete),
+                TextEditCommand::DeleteForward,
+            ),
+            (
+                shortcut(Modifiers::CTRL, Key::H),
+                TextEditCommand::DeleteBackward,
+            ),
+            (
+                shortcut(Modifiers::ALT, Key::Backspace),
+                TextEditCommand::DeleteWordBackward,
+            ),
+            (
+                shortcut(Modifiers::ALT, Key::Delete),
+                TextEditCommand::DeleteWordForward,
+            ),
+            (shortcut(Modifiers::COMMAND, Key::Z), TextEditCommand::Undo),
+            (shortcut(Modifiers::COMMAND, Key::Y), TextEditCommand::Redo),
+            (
+                shortcut(Modifiers::COMMAND | Modifiers::SHIFT, Key::Z),
+                TextEditCommand::Redo,
+            ),
+            (
+                shortcut(Modifiers::NONE, Key::Enter),
+                TextEditCommand::Newline,
+            ),
+            (shortcut(Modifiers::NONE, Key::Tab), TextEditCommand::Indent),
+            (
+                shortcut(Modifiers::SHIFT, Key::Tab),
+                TextEditCommand::Outdent,
+            ),
+        ]);
+
+        Self { bindings }
+    }
+
+    /// [`Self::default_for`] the current OS and style of the [`Ui`],
+    /// kept in the temporary data of the [`crate::Context`] so it is only built once.
+    pub(crate) fn default_for_ui(ui: &Ui) -> Arc<Self> {
+        let os = ui.ctx().os();
+        let interaction = &ui.style().interaction;
+        let id = Id::new(("TextEditKeymap", os, interaction.subword_modifiers));
+        ui.data_mut(|data| {
+            data.get_temp_mut_or_insert_with(id, || Arc::new(Self::default_for(os, interaction)))
+                .clone()
+        })
+    }
+
+    /// Make `shortcut` do `command`, instead of whatever it did before.
+    #[inline]
+    pub fn bind(mut self, shortcut: KeyboardShortcut, command: TextEditCommand) -> Self {
+        self.bindings.retain(|(bound, _)| *bound != shortcut);
+        // First, so that it wins over other bindings that match the same key presses:
+        self.bindings.insert(0, (shortcut, command));
+        self
+    }
+
+    /// Make `shortcut` do nothing.
+    #[inline]
+    pub fn unbind(mut self, shortcut: KeyboardShortcut) -> Self {
+        self.bindings.retain(|(bound, _)| *bound != shortcut);
+        self
+    }
+
+    /// What pressing `key` with `modifiers` does, if anything.
+    pub fn command_for(&self, key: Key, modifiers: Modifiers) -> Option<TextEditCommand> {
+        self.bindings
+            .iter()
+            .find(|(shortcut, _)| {
+                shortcut.logical_key == key && modifiers.matches_exact(shortcut.modifiers)
+            })
+            .map(|(_, command)| *command)
+    }
+}
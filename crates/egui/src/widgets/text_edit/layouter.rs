@@ -0,0 +1,26 @@
+use epaint::text::cursor::CCursor;
+
+use crate::Style;
+
+/// What a custom layouter of a [`TextEdit`](crate::TextEdit) gets to know,
+/// see [`TextEdit::layouter_with_context`](crate::TextEdit::layouter_with_context).
+///
+/// The layouter is run again when any of this changes,
+/// e.g. so that a syntax highlighter can emphasize the bracket matching the one at the cursor.
+#[derive(Clone, Copy)]
+pub struct LayouterContext<'a> {
+    /// The text to lay out.
+    pub text: &'a str,
+
+    /// Wrap the text at this width.
+    pub wrap_width: f32,
+
+    /// The primary text cursor, if the text has ever been focused.
+    pub cursor: Option<CCursor>,
+
+    /// Does the [`TextEdit`](crate::TextEdit) have keyboard focus?
+    pub has_focus: bool,
+
+    /// The style of the enclosing [`Ui`](crate::Ui).
+    pub style: &'a Style,
+}
@@ -1,9 +1,23 @@
 mod builder;
+mod char_filter;
+mod completion;
+mod keymap;
+mod layouter;
 mod output;
 mod state;
 mod text_buffer;
 
+#[cfg(test)]
+mod tests;
+
 pub use {
-    crate::text_selection::TextCursorState, builder::TextEdit, output::TextEditOutput,
-    state::TextEditState, text_buffer::TextBuffer,
+    crate::text_selection::TextCursorState,
+    builder::TextEdit,
+    char_filter::CharFilter,
+    completion::CompletionItem,
+    keymap::{CursorMove, TextEditCommand, TextEditKeymap},
+    layouter::LayouterContext,
+    output::TextEditOutput,
+    state::{DraggedText, TextEditState, UndoMergePolicy},
+    text_buffer::TextBuffer,
 };
@@ -1,8 +1,14 @@
-use std::sync::Arc;
+use std::{ops::Range, sync::Arc};
 
-use crate::text::CursorRange;
+use crate::{
+    text::{CCursor, CursorRange},
+    NumExt as _,
+};
 
 /// The output from a [`TextEdit`](crate::TextEdit).
+///
+/// The screen positions in here are only valid for the frame they were produced in,
+/// e.g. for painting overlays on top of the text.
 pub struct TextEditOutput {
     /// The interaction response.
     pub response: crate::Response,
@@ -21,9 +27,87 @@ pub struct TextEditOutput {
 
     /// Where the text cursor is.
     pub cursor_range: Option<CursorRange>,
+
+    /// Where the primary text cursor of [`Self::cursor_range`] is on the screen.
+    pub cursor_screen_rect: Option<crate::Rect>,
+
+    /// The rows of [`Self::galley`] that are (at least partially) visible,
+    /// i.e. intersect [`Self::text_clip_rect`] and the clip rect of the [`crate::Ui`].
+    pub visible_row_range: Range<usize>,
+
+    /// Is there anything to undo?
+    ///
+    /// Use this to e.g. disable an "Undo" menu button.
+    pub undo_available: bool,
+
+    /// Is there anything to redo?
+    pub redo_available: bool,
+
+    /// Did the user type or paste characters this frame that were dropped,
+    /// because of [`TextEdit::char_filter`](crate::TextEdit::char_filter)
+    /// or [`TextEdit::char_limit`](crate::TextEdit::char_limit)?
+    ///
+    /// Use this to e.g. flash the field.
+    pub input_rejected: bool,
 }
 
 impl TextEditOutput {
+    /// Where the character at `char_index` is on the screen: the top of the text cursor before it.
+    ///
+    /// Returns `None` if `char_index` is past the end of the text.
+    pub fn char_to_screen_pos(&self, char_index: usize) -> Option<crate::Pos2> {
+        let galley = &self.galley;
+        if galley.end().ccursor.index < char_index {
+            return None;
+        }
+        let cursor = galley.from_ccursor(CCursor::new(char_index));
+        Some(galley.pos_from_cursor(&cursor).left_top() + self.galley_pos.to_vec2())
+    }
+
+    /// Scroll so that the given range of characters is visible.
+    ///
+    /// If the text is too wide for the [`TextEdit`](crate::TextEdit) (e.g. a long singleline one)
+    /// the text is scrolled sideways within it.
+    /// The range is also scrolled into view in any enclosing [`ScrollArea`](crate::ScrollArea).
+    ///
+    /// Takes effect the next frame.
+    pub fn scroll_to_char_range(&mut self, ui: &crate::Ui, char_range: Range<usize>) {
+        let galley = &self.galley;
+        let mut rect = galley
+            .rects_for_char_range(char_range.clone())
+            .into_iter()
+            .reduce(crate::Rect::union)
+            .unwrap_or_else(|| {
+                // An empty range, i.e. a cursor:
+                galley.pos_from_cursor(&galley.from_ccursor(CCursor::new(char_range.start)))
+            })
+            .translate(self.galley_pos.to_vec2());
+
+        let clip_rect = self.text_clip_rect;
+        if clip_rect.width() < galley.size().x {
+            let delta_x = if rect.left() < clip_rect.left() {
+                rect.left() - clip_rect.left()
+            } else if clip_rect.right() < rect.right() {
+                // …but keep the start of the range visible:
+                (rect.right() - clip_rect.right()).min(rect.left() - clip_rect.left())
+            } else {
+                0.0
+            };
+            let offset = (self.state.singleline_offset + delta_x)
+                .at_most(galley.size().x - clip_rect.width())
+                .at_least(0.0);
+            rect = rect.translate(crate::vec2(self.state.singleline_offset - offset, 0.0));
+
+            if offset != self.state.singleline_offset {
+                self.state.singleline_offset = offset;
+                self.state.clone().store(ui.ctx(), self.response.id);
+                ui.ctx().request_repaint();
+            }
+        }
+
+        ui.scroll_to_rect(rect, None);
+    }
+
     #[deprecated = "Renamed `self.galley_pos`"]
     pub fn text_draw_pos(&self) -> crate::Pos2 {
         self.galley_pos
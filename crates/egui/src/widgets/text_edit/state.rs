@@ -1,4 +1,7 @@
-use std::sync::Arc;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
 
 use crate::mutex::Mutex;
 
@@ -6,8 +9,188 @@ use crate::*;
 
 use self::text_selection::{CCursorRange, CursorRange, TextCursorState};
 
+use super::CompletionItem;
+
 pub type TextEditUndoer = crate::util::undoer::Undoer<(CCursorRange, String)>;
 
+/// How a [`TextEdit`] groups edits into undo steps.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum UndoMergePolicy {
+    /// Merge consecutive insertions into one undo step, so that typing is undone a word at a time.
+    ///
+    /// A new undo step starts when:
+    /// * a new word is typed after whitespace,
+    /// * the cursor was moved with the keyboard or the mouse,
+    /// * switching between inserting and deleting text,
+    /// * there has been a pause of more than `pause` seconds since the previous edit.
+    ///
+    /// Cutting, pasting, killing text and committing IME input are always undo steps of their own.
+    Words {
+        /// In seconds.
+        pause: f32,
+    },
+
+    /// Every edit is an undo step of its own.
+    EachEdit,
+
+    /// Create an undo point whenever the text has stayed the same for a second,
+    /// or every 30 seconds while it keeps changing.
+    Time,
+}
+
+impl Default for UndoMergePolicy {
+    fn default() -> Self {
+        Self::Words { pause: 1.0 }
+    }
+}
+
+impl UndoMergePolicy {
+    /// Should an edit of the given kind, made with the cursor at `ccursor_range`,
+    /// start a new undo step rather than be merged with the current one?
+    pub(crate) fn starts_new_step(
+        self,
+        current: Option<&UndoGroup>,
+        kind: EditKind,
+        ccursor_range: &CCursorRange,
+        time: f64,
+    ) -> bool {
+        match self {
+            Self::Words { pause } => current.map_or(true, |current| {
+                let cursor_moved = current.ccursor_range.primary.index
+                    != ccursor_range.primary.index
+                    || current.ccursor_range.secondary.index != ccursor_range.secondary.index;
+                let continues = matches!(
+                    (current.kind, kind),
+                    (
+                        EditKind::Typing,
+                        EditKind::Typing | EditKind::TypingWhitespace
+                    ) | (EditKind::TypingWhitespace, EditKind::TypingWhitespace)
+                        | (EditKind::Deleting, EditKind::Deleting)
+                );
+                cursor_moved || !continues || f64::from(pause) <= time - current.time
+            }),
+            Self::EachEdit => true,
+            Self::Time => false,
+        }
+    }
+}
+
+/// What an edit did, for grouping edits into undo steps.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum EditKind {
+    Typing,
+    TypingWhitespace,
+    Deleting,
+
+    /// Cutting, pasting, killing etc.
+    Other,
+}
+
+/// The edits in the current undo step.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct UndoGroup {
+    pub kind: EditKind,
+
+    /// The cursor after the latest edit.
+    pub ccursor_range: CCursorRange,
+
+    /// When the latest edit was made.
+    pub time: f64,
+}
+
+/// Text that is being composed with an IME, see [`ImeEvent::Preedit`].
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ImePreedit {
+    pub text: String,
+
+    /// Where the IME cursor is within `text`, as a char range.
+    pub cursor: Option<std::ops::Range<usize>>,
+}
+
+/// The open completion popup of a [`TextEdit`], see [`TextEdit::completion_provider`].
+#[derive(Clone, Debug)]
+pub(crate) struct CompletionPopup {
+    pub items: Vec<CompletionItem>,
+
+    /// The item that Tab or Enter picks.
+    pub selected: usize,
+
+    /// Scroll the selected item into view, after it was selected with the keyboard.
+    pub scroll_to_selected: bool,
+
+    /// The item that was clicked, to be picked the next frame.
+    pub clicked: Option<usize>,
+}
+
+impl CompletionPopup {
+    pub fn new(items: Vec<CompletionItem>) -> Self {
+        Self {
+            items,
+            selected: 0,
+            scroll_to_selected: true,
+            clicked: None,
+        }
+    }
+
+    /// Select the next (or previous) item, wrapping around.
+    pub fn select_next(&mut self, forward: bool) {
+        let num_items = self.items.len().max(1);
+        self.selected = if forward {
+            (self.selected + 1) % num_items
+        } else {
+            (self.selected + num_items - 1) % num_items
+        };
+        self.scroll_to_selected = true;
+    }
+}
+
+/// Text that is dragged from a [`TextEdit`], as a drag-and-drop payload (see [`DragAndDrop`]).
+///
+/// Dropping it onto a [`TextEdit`] moves the text there,
+/// or copies it if Ctrl (Cmd on Mac) is held.
+/// Other widgets can accept it too, with e.g. [`Response::dnd_release_payload`].
+#[derive(Debug)]
+pub struct DraggedText {
+    /// The dragged text.
+    pub text: String,
+
+    /// The [`TextEdit`] the text is dragged from.
+    pub(crate) source: Id,
+
+    /// Where the text is in the source [`TextEdit`], as a char range.
+    pub(crate) char_range: std::ops::Range<usize>,
+
+    pub(crate) moved: Arc<AtomicBool>,
+}
+
+impl DraggedText {
+    /// Delete the text from the [`TextEdit`] it was dragged from, i.e. move rather than copy it.
+    ///
+    /// Call this when you accept the dropped text.
+    pub fn mark_moved(&self) {
+        self.moved.store(true, Ordering::Relaxed);
+    }
+}
+
+/// The selected text of a [`TextEdit`] that the user is dragging, see [`DraggedText`].
+#[derive(Clone, Debug)]
+pub(crate) struct TextDrag {
+    /// The dragged selection, as a char range.
+    pub char_range: std::ops::Range<usize>,
+
+    /// `false` while the pointer is pressed on the selection, but hasn't moved enough to start dragging.
+    pub started: bool,
+
+    /// Shared with the [`DraggedText`] payload.
+    pub moved: Arc<AtomicBool>,
+}
+
+impl TextDrag {
+    pub fn is_moved(&self) -> bool {
+        self.moved.load(Ordering::Relaxed)
+    }
+}
+
 /// The text edit state stored between frames.
 ///
 /// Attention: You also need to `store` the updated state.
@@ -36,6 +219,11 @@ pub struct TextEditState {
     /// Controls the text selection.
     pub cursor: TextCursorState,
 
+    /// The cursors other than the primary one, when there are several.
+    ///
+    /// See [`Self::extra_char_ranges`].
+    pub(crate) extra_ccursor_ranges: Vec<CCursorRange>,
+
     /// Wrapped in Arc for cheaper clones.
     #[cfg_attr(feature = "serde", serde(skip))]
     pub(crate) undoer: Arc<Mutex<TextEditUndoer>>,
@@ -44,9 +232,9 @@ pub struct TextEditState {
     #[cfg_attr(feature = "serde", serde(skip))]
     pub(crate) ime_enabled: bool,
 
-    // cursor range for IME candidate.
+    // The text being composed with the IME, shown at the cursor until it is committed.
     #[cfg_attr(feature = "serde", serde(skip))]
-    pub(crate) ime_cursor_range: CursorRange,
+    pub(crate) ime_preedit: Option<ImePreedit>,
 
     // Visual offset when editing singleline text bigger than the width.
     #[cfg_attr(feature = "serde", serde(skip))]
@@ -56,6 +244,53 @@ pub struct TextEditState {
     /// Used to pause the cursor animation when typing.
     #[cfg_attr(feature = "serde", serde(skip))]
     pub(crate) last_edit_time: f64,
+
+    /// The text most recently killed with e.g. `Ctrl+K`, to be yanked back with `Ctrl+Y`.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) kill_buffer: String,
+
+    /// The edits in the current undo step, see [`UndoMergePolicy`].
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) undo_group: Option<UndoGroup>,
+
+    /// Scroll to the cursor the next time the [`TextEdit`] is shown?
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) scroll_to_cursor: bool,
+
+    /// How to align the cursor when scrolling to it.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) scroll_to_cursor_align: Option<Align>,
+
+    /// Does typing replace the characters after the cursor? Toggled with the Insert key.
+    pub(crate) overwrite_mode: bool,
+
+    /// Show the text of a password field? Toggled with its reveal button.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) password_revealed: bool,
+
+    /// The char of a password field that was typed last, and when,
+    /// see [`TextEdit::reveal_last_char_for`].
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) password_last_typed: Option<(usize, f64)>,
+
+    /// See [`TextEdit::completion_provider`].
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) completion: Option<CompletionPopup>,
+
+    /// Dragging the selected text, see [`DraggedText`].
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) text_drag: Option<TextDrag>,
+
+    /// Char offsets of the closing chars inserted by [`TextEdit::auto_close_pairs`],
+    /// which typing the same char steps over.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) auto_closed: Vec<usize>,
+
+    /// The primary and secondary cursor index and the number of chars in the text
+    /// when [`Self::auto_closed`] was last updated.
+    /// Moving the cursor or editing the text in any other way forgets the auto-closed chars.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) auto_closed_at: Option<([usize; 2], usize)>,
 }
 
 impl TextEditState {
@@ -84,6 +319,51 @@ impl TextEditState {
         self.cursor.set_range(cursor_range);
     }
 
+    /// The cursors (and selections) other than the primary one, e.g. added by alt-clicking,
+    /// in no particular order.
+    ///
+    /// The primary cursor is [`TextCursorState::char_range`] of [`Self::cursor`].
+    /// Clicking in the [`TextEdit`] removes these, and so does pressing escape.
+    pub fn extra_char_ranges(&self) -> &[CCursorRange] {
+        &self.extra_ccursor_ranges
+    }
+
+    /// See [`Self::extra_char_ranges`].
+    ///
+    /// Remember to [`Self::store`] the state afterwards.
+    pub fn set_extra_char_ranges(&mut self, extra_ccursor_ranges: Vec<CCursorRange>) {
+        self.extra_ccursor_ranges = extra_ccursor_ranges;
+    }
+
+    /// Scroll the cursor into view the next time the [`TextEdit`] is shown,
+    /// e.g. after moving the cursor with [`TextCursorState::set_char_range`].
+    ///
+    /// The cursor is scrolled into view in any enclosing [`ScrollArea`],
+    /// and sideways within a singleline [`TextEdit`] that is too narrow for its text.
+    ///
+    /// With `align == None` we scroll just enough to show the cursor with a row of margin.
+    /// With `Some(Align::Center)` the cursor ends up in the middle, e.g. for "go to line".
+    ///
+    /// Remember to [`Self::store`] the state afterwards.
+    pub fn request_scroll_to_cursor(&mut self, align: Option<Align>) {
+        self.scroll_to_cursor = true;
+        self.scroll_to_cursor_align = align;
+    }
+
+    /// In overwrite mode typing replaces the characters after the cursor, instead of inserting.
+    ///
+    /// The user toggles it with the Insert key.
+    pub fn overwrite_mode(&self) -> bool {
+        self.overwrite_mode
+    }
+
+    /// See [`Self::overwrite_mode`].
+    ///
+    /// Remember to [`Self::store`] the state afterwards.
+    pub fn set_overwrite_mode(&mut self, overwrite_mode: bool) {
+        self.overwrite_mode = overwrite_mode;
+    }
+
     pub fn undoer(&self) -> TextEditUndoer {
         self.undoer.lock().clone()
     }
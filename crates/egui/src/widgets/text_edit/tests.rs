@@ -0,0 +1,1712 @@
+use crate::{
+    os::OperatingSystem,
+    text::{CCursor, CCursorRange, LayoutJob},
+    text_edit::{LayouterContext, TextEditState},
+    *,
+};
+
+/// Run a frame on an 800x600 screen (unless `input` says otherwise),
+/// with `add_contents` in a [`CentralPanel`].
+fn run_in_panel(
+    ctx: &Context,
+    input: RawInput,
+    mut add_contents: impl FnMut(&mut Ui),
+) -> FullOutput {
+    let input = RawInput {
+        screen_rect: input
+            .screen_rect
+            .or(Some(Rect::from_min_size(Pos2::ZERO, vec2(800.0, 600.0)))),
+        ..input
+    };
+    ctx.run(input, |ctx| {
+        CentralPanel::default().show(ctx, |ui| add_contents(ui));
+    })
+}
+
+fn key(key: Key, modifiers: Modifiers) -> Event {
+    Event::Key {
+        key,
+        physical_key: None,
+        pressed: true,
+        repeat: false,
+        modifiers,
+    }
+}
+
+#[test]
+fn test_vertical_movement_keeps_column() {
+    let ctx = Context::default();
+    let id = Id::new("text_edit");
+    // The first paragraph wraps into two rows, then comes a short row and then a long one:
+    let mut text =
+        "The quick brown fox jumps over the lazy dog and keeps on running\nshort\nAnother long line that goes on for quite a while"
+            .to_owned();
+    let short_end = text.find("short").unwrap() + "short".len();
+    let long_start = text.find("Another").unwrap();
+
+    let mut run = |events: Vec<Event>| {
+        let input = RawInput {
+            events,
+            ..Default::default()
+        };
+        run_in_panel(&ctx, input, |ui| {
+            TextEdit::multiline(&mut text)
+                .id(id)
+                .font(TextStyle::Monospace)
+                .desired_width(250.0)
+                .show(ui);
+        });
+    };
+    let down = key(Key::ArrowDown, Modifiers::NONE);
+
+    run(vec![]);
+    ctx.memory_mut(|mem| mem.request_focus(id));
+    let mut state = TextEditState::load(&ctx, id).unwrap();
+    let column = 20;
+    state
+        .cursor
+        .set_char_range(Some(CCursorRange::one(CCursor::new(column))));
+    state.store(&ctx, id);
+    run(vec![]);
+
+    let cursor_index = || {
+        let state = TextEditState::load(&ctx, id).unwrap();
+        state.cursor.char_range().unwrap().primary.index
+    };
+
+    // Down through the wrapped row and the short row…
+    run(vec![down.clone(), down.clone()]);
+    assert_eq!(cursor_index(), short_end, "end of the short row");
+
+    // …and back to the original column on the long row:
+    run(vec![down]);
+    assert_eq!(cursor_index(), long_start + column);
+}
+
+#[test]
+fn test_multiple_cursors() {
+    let ctx = Context::default();
+    let id = Id::new("text_edit");
+    let mut text = "foo bar foo baz foo".to_owned();
+
+    // A second apart, so that clicks don't become double-clicks:
+    let time = std::cell::Cell::new(0.0);
+    let run = |text: &mut String, events: Vec<Event>, modifiers: Modifiers| {
+        time.set(time.get() + 1.0);
+        let input = RawInput {
+            events,
+            modifiers,
+            time: Some(time.get()),
+            ..Default::default()
+        };
+        let mut galley_pos = Pos2::ZERO;
+        run_in_panel(&ctx, input, |ui| {
+            galley_pos = TextEdit::multiline(text).id(id).show(ui).galley_pos;
+        });
+        galley_pos
+    };
+    let cursors = || {
+        let state = TextEditState::load(&ctx, id).unwrap();
+        let mut cursors: Vec<[usize; 2]> = std::iter::once(state.cursor.char_range().unwrap())
+            .chain(state.extra_char_ranges().iter().copied())
+            .map(|ccursor_range| ccursor_range.sorted().map(|ccursor| ccursor.index))
+            .collect();
+        cursors.sort_unstable();
+        cursors
+    };
+
+    run(&mut text, vec![], Modifiers::NONE);
+    ctx.memory_mut(|mem| mem.request_focus(id));
+    run(&mut text, vec![], Modifiers::NONE);
+
+    // Select the word at the cursor (at the end), and then its next two occurrences,
+    // wrapping around to the start:
+    let select_next = key(Key::D, Modifiers::COMMAND);
+    run(&mut text, vec![select_next.clone()], Modifiers::NONE);
+    assert_eq!(cursors(), [[16, 19]]);
+    run(
+        &mut text,
+        vec![select_next.clone(), select_next],
+        Modifiers::NONE,
+    );
+    assert_eq!(cursors(), [[0, 3], [8, 11], [16, 19]]);
+
+    // Typing replaces all of them, and undo takes it all back at once:
+    run(
+        &mut text,
+        vec![Event::Text("x".to_owned())],
+        Modifiers::NONE,
+    );
+    assert_eq!(text, "x bar x baz x");
+    assert_eq!(cursors(), [[1, 1], [7, 7], [13, 13]]);
+    run(
+        &mut text,
+        vec![key(Key::Z, Modifiers::COMMAND)],
+        Modifiers::NONE,
+    );
+    assert_eq!(text, "foo bar foo baz foo");
+
+    // Alt-click to add cursors to the selection that undo brought back:
+    let galley_pos = run(&mut text, vec![], Modifiers::NONE);
+    let pos_of = |index: usize| {
+        let font_id = FontSelection::default().resolve(&ctx.style());
+        let galley = ctx.fonts(|fonts| fonts.layout_no_wrap(text.clone(), font_id, Color32::WHITE));
+        galley_pos
+            + galley
+                .pos_from_cursor(&galley.from_ccursor(CCursor::new(index)))
+                .center()
+                .to_vec2()
+    };
+    let click = |pos: Pos2, pressed: bool| Event::PointerButton {
+        pos,
+        button: PointerButton::Primary,
+        pressed,
+        modifiers: Modifiers::ALT,
+    };
+    let [first, second] = [pos_of(1), pos_of(5)];
+    for pos in [first, second] {
+        run(
+            &mut text,
+            vec![Event::PointerMoved(pos), click(pos, true)],
+            Modifiers::ALT,
+        );
+        run(&mut text, vec![click(pos, false)], Modifiers::ALT);
+    }
+    assert_eq!(cursors(), [[1, 1], [5, 5], [8, 11]]);
+
+    // Moving and deleting applies to each cursor:
+    run(
+        &mut text,
+        vec![
+            key(Key::ArrowRight, Modifiers::NONE),
+            key(Key::Backspace, Modifiers::NONE),
+        ],
+        Modifiers::NONE,
+    );
+    assert_eq!(text, "fo br fo baz foo");
+    assert_eq!(cursors(), [[1, 1], [4, 4], [8, 8]]);
+
+    // Escape leaves just the primary cursor, which is the last one added:
+    run(
+        &mut text,
+        vec![key(Key::Escape, Modifiers::NONE)],
+        Modifiers::NONE,
+    );
+    assert_eq!(cursors(), [[4, 4]]);
+    assert!(ctx.memory(|mem| mem.has_focus(id)));
+}
+
+#[test]
+fn test_block_selection() {
+    let ctx = Context::default();
+    let id = Id::new("text_edit");
+    let mut text = "abcdef\nabcdef\n\nabcdef".to_owned();
+
+    let time = std::cell::Cell::new(0.0);
+    let run = |text: &mut String, events: Vec<Event>, modifiers: Modifiers| {
+        time.set(time.get() + 0.1);
+        let input = RawInput {
+            events,
+            modifiers,
+            time: Some(time.get()),
+            ..Default::default()
+        };
+        let mut galley_pos = Pos2::ZERO;
+        let output = run_in_panel(&ctx, input, |ui| {
+            galley_pos = TextEdit::multiline(text)
+                .id(id)
+                .font(TextStyle::Monospace)
+                .show(ui)
+                .galley_pos;
+        });
+        (galley_pos, output.platform_output.copied_text)
+    };
+
+    let (galley_pos, _) = run(&mut text, vec![], Modifiers::NONE);
+    let font_id = TextStyle::Monospace.resolve(&ctx.style());
+    let galley = ctx.fonts(|fonts| fonts.layout_no_wrap(text.clone(), font_id, Color32::WHITE));
+    let pos_of = |index: usize| {
+        let cursor = galley.from_ccursor(CCursor::new(index));
+        galley_pos + galley.pos_from_cursor(&cursor).center().to_vec2()
+    };
+    let button = |pos: Pos2, pressed: bool| Event::PointerButton {
+        pos,
+        button: PointerButton::Primary,
+        pressed,
+        modifiers: Modifiers::ALT,
+    };
+
+    // Alt-drag from column 1 on the first row to column 3 on the last row:
+    let [start, end] = [pos_of(1), pos_of(18)];
+    run(
+        &mut text,
+        vec![Event::PointerMoved(start), button(start, true)],
+        Modifiers::ALT,
+    );
+    for pos in [start + vec2(0.0, 10.0), end] {
+        run(&mut text, vec![Event::PointerMoved(pos)], Modifiers::ALT);
+    }
+    run(&mut text, vec![button(end, false)], Modifiers::ALT);
+
+    let state = TextEditState::load(&ctx, id).unwrap();
+    assert_eq!(state.cursor.block_selection().unwrap().rows(), 0..4);
+
+    // The empty row is copied as an empty line:
+    let (_, copied_text) = run(&mut text, vec![Event::Copy], Modifiers::NONE);
+    assert_eq!(copied_text, "bc\nbc\n\nbc");
+
+    // Typing replaces the block on every row, padding the empty one:
+    run(
+        &mut text,
+        vec![Event::Text("X".to_owned())],
+        Modifiers::NONE,
+    );
+    assert_eq!(text, "aXdef\naXdef\n X\naXdef");
+}
+
+#[test]
+fn test_kill_and_yank() {
+    let ctx = Context::default();
+    let id = Id::new("text_edit");
+    let mut text = "hello brave new world".to_owned();
+
+    let time = std::cell::Cell::new(0.0);
+    let run = |text: &mut String, kill_keys: bool, events: Vec<Event>| {
+        time.set(time.get() + 0.1);
+        let input = RawInput {
+            events,
+            time: Some(time.get()),
+            ..Default::default()
+        };
+        run_in_panel(&ctx, input, |ui| {
+            TextEdit::singleline(text)
+                .id(id)
+                .kill_keys(kill_keys)
+                .show(ui);
+        });
+    };
+    let ctrl = |ctrl_key: Key| key(ctrl_key, Modifiers::CTRL);
+    let set_cursor = |ccursor_range: CCursorRange| {
+        let mut state = TextEditState::load(&ctx, id).unwrap();
+        state.cursor.set_char_range(Some(ccursor_range));
+        state.store(&ctx, id);
+    };
+
+    run(&mut text, true, vec![]);
+    ctx.memory_mut(|mem| mem.request_focus(id));
+    run(&mut text, true, vec![]);
+
+    // Kill the previous word, and then to the end of the line:
+    set_cursor(CCursorRange::one(CCursor::new(12)));
+    run(&mut text, true, vec![ctrl(Key::W)]);
+    assert_eq!(text, "hello new world");
+    run(&mut text, true, vec![ctrl(Key::K)]);
+    assert_eq!(text, "hello ");
+
+    // Yank the last kill back, twice:
+    run(&mut text, true, vec![ctrl(Key::Y), ctrl(Key::Y)]);
+    assert_eq!(text, "hello new worldnew world");
+
+    // With a selection, the selection is killed:
+    set_cursor(CCursorRange::two(CCursor::new(0), CCursor::new(6)));
+    run(&mut text, true, vec![ctrl(Key::U)]);
+    assert_eq!(text, "new worldnew world");
+
+    // Each kill is an undo step:
+    run(&mut text, true, vec![key(Key::Z, Modifiers::COMMAND)]);
+    assert_eq!(text, "hello new worldnew world");
+
+    // The keys can be turned off:
+    set_cursor(CCursorRange::one(CCursor::new(9)));
+    run(&mut text, false, vec![ctrl(Key::U), ctrl(Key::Y)]);
+    assert_eq!(text, "hello new worldnew world");
+}
+
+#[test]
+fn test_undo_merges_words() {
+    let ctx = Context::default();
+    let id = Id::new("text_edit");
+    let mut text = String::new();
+
+    let time = std::cell::Cell::new(0.0);
+    let run = |text: &mut String, seconds: f64, events: Vec<Event>| {
+        time.set(time.get() + seconds);
+        let input = RawInput {
+            events,
+            time: Some(time.get()),
+            ..Default::default()
+        };
+        let mut available = (false, false);
+        run_in_panel(&ctx, input, |ui| {
+            let output = TextEdit::singleline(text).id(id).show(ui);
+            available = (output.undo_available, output.redo_available);
+        });
+        available
+    };
+    let undo = || key(Key::Z, Modifiers::COMMAND);
+
+    run(&mut text, 0.1, vec![]);
+    ctx.memory_mut(|mem| mem.request_focus(id));
+    assert_eq!(run(&mut text, 0.1, vec![]), (false, false));
+
+    for c in "hello world".chars() {
+        run(&mut text, 0.1, vec![Event::Text(c.to_string())]);
+    }
+    run(&mut text, 0.1, vec![key(Key::Backspace, Modifiers::NONE)]);
+    assert_eq!(run(&mut text, 0.1, vec![]), (true, false));
+    assert_eq!(text, "hello worl");
+
+    // Deleting, the second word, and then the first word (with its space) are undone separately:
+    run(&mut text, 0.1, vec![undo()]);
+    assert_eq!(text, "hello world");
+    run(&mut text, 0.1, vec![undo()]);
+    assert_eq!(text, "hello ");
+    assert_eq!(run(&mut text, 0.1, vec![undo()]), (false, true));
+    assert_eq!(text, "");
+
+    // A pause starts a new undo step, and editing forgets the redos:
+    run(&mut text, 0.1, vec![Event::Text("a".to_owned())]);
+    assert_eq!(
+        run(&mut text, 5.0, vec![Event::Text("b".to_owned())]),
+        (true, false)
+    );
+    assert_eq!(run(&mut text, 0.1, vec![undo()]), (true, true));
+    assert_eq!(text, "a");
+}
+
+#[test]
+fn test_scroll_to_char_range() {
+    let ctx = Context::default();
+    let id = Id::new("text_edit");
+    let mut text = "The needle is at the very end of this long line of text: needle".to_owned();
+    let needle_start = text.rfind("needle").unwrap();
+    let needle = needle_start..needle_start + "needle".len();
+
+    let run = |text: &mut String, scroll_to: Option<std::ops::Range<usize>>| {
+        let mut visible = false;
+        run_in_panel(&ctx, RawInput::default(), |ui| {
+            let mut output = TextEdit::singleline(text)
+                .id(id)
+                .desired_width(100.0)
+                .highlight_ranges(vec![(needle.clone(), Color32::YELLOW)])
+                .show(ui);
+            let needle_rect = output.galley.rects_for_char_range(needle.clone())[0]
+                .translate(output.galley_pos.to_vec2());
+            visible = output.text_clip_rect.contains_rect(needle_rect);
+            if let Some(scroll_to) = &scroll_to {
+                output.scroll_to_char_range(ui, scroll_to.clone());
+            }
+        });
+        visible
+    };
+
+    assert!(!run(&mut text, None), "the text is clipped");
+    assert!(!run(&mut text, Some(needle.clone())));
+    assert!(run(&mut text, None), "scrolled to the needle");
+    assert!(run(&mut text, None), "stays scrolled");
+
+    assert!(run(&mut text, Some(0..3)));
+    assert!(!run(&mut text, None), "scrolled back to the start");
+}
+
+#[test]
+fn test_request_scroll_to_cursor() {
+    let ctx = Context::default();
+    let id = Id::new("text_edit");
+    let mut text = (0..200)
+        .map(|i| format!("Line {i}"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let line_150 = text.find("Line 150").unwrap();
+
+    // Long enough for the scrolling animation to finish:
+    let time = std::cell::Cell::new(0.0);
+
+    // Returns the distance of the cursor from the center of the visible area:
+    let run = |text: &mut String| {
+        time.set(time.get() + 1.0);
+        let input = RawInput {
+            time: Some(time.get()),
+            ..Default::default()
+        };
+        let mut distance = f32::INFINITY;
+        run_in_panel(&ctx, input, |ui| {
+            ScrollArea::vertical().max_height(100.0).show(ui, |ui| {
+                let output = TextEdit::multiline(text).id(id).show(ui);
+                if let Some(cursor_range) = output.state.cursor.range(&output.galley) {
+                    let cursor_y = output.galley_pos.y
+                        + output
+                            .galley
+                            .pos_from_cursor(&cursor_range.primary)
+                            .center()
+                            .y;
+                    distance = (cursor_y - ui.clip_rect().center().y).abs();
+                }
+            });
+        });
+        distance
+    };
+
+    run(&mut text);
+    let mut state = TextEditState::load(&ctx, id).unwrap();
+    state
+        .cursor
+        .set_char_range(Some(CCursorRange::one(CCursor::new(line_150))));
+    state.store(&ctx, id);
+    assert!(run(&mut text) > 1000.0, "setting the cursor doesn't scroll");
+
+    let mut state = TextEditState::load(&ctx, id).unwrap();
+    state.request_scroll_to_cursor(Some(Align::Center));
+    state.store(&ctx, id);
+    run(&mut text);
+    run(&mut text);
+    assert!(run(&mut text) < 10.0, "the cursor is centered");
+}
+
+#[test]
+fn test_ime_preedit() {
+    let ctx = Context::default();
+    let id = Id::new("text_edit");
+    let mut text = String::new();
+
+    // Returns the cursor rect reported to the IME:
+    let run = |text: &mut String, events: Vec<Event>| {
+        let input = RawInput {
+            events,
+            ..Default::default()
+        };
+        let output = run_in_panel(&ctx, input, |ui| {
+            TextEdit::singleline(text).id(id).show(ui);
+        });
+        output.platform_output.ime.map(|ime| ime.cursor_rect)
+    };
+    let preedit = |text: &str| {
+        let num_chars = text.chars().count();
+        Event::Ime(ImeEvent::Preedit {
+            text: text.to_owned(),
+            cursor: Some(num_chars..num_chars),
+        })
+    };
+
+    assert_eq!(run(&mut text, vec![]), None, "no IME output without focus");
+    ctx.memory_mut(|mem| mem.request_focus(id));
+    run(&mut text, vec![Event::Text("a".to_owned())]);
+    let cursor_rect = run(&mut text, vec![]).unwrap();
+
+    // The preedit is shown at the cursor, but is not part of the text:
+    run(&mut text, vec![Event::Ime(ImeEvent::Enabled), preedit("n")]);
+    let preedit_cursor_rect = run(&mut text, vec![preedit("ni")]).unwrap();
+    assert_eq!(text, "a");
+    assert!(cursor_rect.max.x < preedit_cursor_rect.min.x);
+
+    // Cancelling the preedit leaves the text as it was:
+    assert_eq!(run(&mut text, vec![preedit("")]), Some(cursor_rect));
+    assert_eq!(text, "a");
+
+    // Committing replaces the preedit, in an undo step of its own:
+    run(&mut text, vec![preedit("ni")]);
+    run(
+        &mut text,
+        vec![Event::Ime(ImeEvent::Commit("你".to_owned()))],
+    );
+    assert_eq!(text, "a你");
+    let undo = key(Key::Z, Modifiers::COMMAND);
+    run(&mut text, vec![undo]);
+    assert_eq!(text, "a");
+}
+
+#[test]
+fn test_cursor_blink() {
+    let ctx = Context::default();
+    let id = Id::new("text_edit");
+    let mut text = "hello".to_owned();
+
+    let time = std::cell::Cell::new(0.0);
+
+    // Returns how long until egui wants to repaint:
+    let run = |text: &mut String, cursor_blink: bool| {
+        time.set(time.get() + 0.1);
+        let input = RawInput {
+            time: Some(time.get()),
+            ..Default::default()
+        };
+        let output = run_in_panel(&ctx, input, |ui| {
+            TextEdit::singleline(text)
+                .id(id)
+                .cursor_blink(cursor_blink)
+                .show(ui);
+        });
+        output.viewport_output[&ViewportId::ROOT].repaint_delay
+    };
+
+    ctx.memory_mut(|mem| mem.request_focus(id));
+    run(&mut text, true);
+    run(&mut text, true);
+    assert!(
+        run(&mut text, true) <= std::time::Duration::from_secs_f32(0.5),
+        "repaint when the cursor blinks"
+    );
+    run(&mut text, false);
+    assert_eq!(
+        run(&mut text, false),
+        std::time::Duration::MAX,
+        "no repaints without blinking"
+    );
+}
+
+#[test]
+fn test_overwrite_mode() {
+    let ctx = Context::default();
+    let id = Id::new("text_edit");
+    let mut text = "abc\ndef".to_owned();
+
+    let run = |text: &mut String, events: Vec<Event>| {
+        let input = RawInput {
+            events,
+            ..Default::default()
+        };
+        run_in_panel(&ctx, input, |ui| {
+            TextEdit::multiline(text)
+                .id(id)
+                .overwrite_mode(true)
+                .show(ui);
+        });
+    };
+    let type_text = |text: &str| {
+        text.chars()
+            .map(|c| Event::Text(c.to_string()))
+            .collect::<Vec<_>>()
+    };
+    let insert = key(Key::Insert, Modifiers::NONE);
+
+    run(&mut text, vec![]);
+    ctx.memory_mut(|mem| mem.request_focus(id));
+    run(&mut text, vec![]);
+    let mut state = TextEditState::load(&ctx, id).unwrap();
+    assert!(state.overwrite_mode());
+    state
+        .cursor
+        .set_char_range(Some(CCursorRange::one(CCursor::new(1))));
+    state.store(&ctx, id);
+
+    // Typing replaces the characters after the cursor, but not the newline:
+    run(&mut text, type_text("XYZ"));
+    assert_eq!(text, "aXYZ\ndef");
+
+    // The Insert key toggles overwrite mode:
+    let mut events = vec![insert];
+    events.extend(type_text("!"));
+    run(&mut text, events);
+    assert_eq!(text, "aXYZ!\ndef");
+    assert!(!TextEditState::load(&ctx, id).unwrap().overwrite_mode());
+}
+
+#[test]
+fn test_char_filter() {
+    let ctx = Context::default();
+    let id = Id::new("text_edit");
+    let mut text = String::new();
+
+    // Returns whether input was rejected, and where the cursor ended up:
+    let run = |text: &mut String, events: Vec<Event>| {
+        let input = RawInput {
+            events,
+            ..Default::default()
+        };
+        let mut result = (false, None);
+        run_in_panel(&ctx, input, |ui| {
+            let output = TextEdit::singleline(text)
+                .id(id)
+                .char_filter(text_edit::CharFilter::decimal('.'))
+                .char_limit(5)
+                .show(ui);
+            result = (
+                output.input_rejected,
+                output
+                    .cursor_range
+                    .map(|cursor_range| cursor_range.primary.ccursor.index),
+            );
+        });
+        result
+    };
+
+    run(&mut text, vec![]);
+    ctx.memory_mut(|mem| mem.request_focus(id));
+    run(&mut text, vec![]);
+
+    // Disallowed characters and a second separator are dropped from a paste:
+    assert_eq!(
+        run(&mut text, vec![Event::Paste("1a.2.3".to_owned())]),
+        (true, Some(4))
+    );
+    assert_eq!(text, "1.23");
+    assert_eq!(
+        run(&mut text, vec![Event::Text("4".to_owned())]),
+        (false, Some(5))
+    );
+    assert_eq!(text, "1.234");
+
+    // The limit counts chars:
+    assert_eq!(
+        run(&mut text, vec![Event::Text("5".to_owned())]),
+        (true, Some(5))
+    );
+    assert_eq!(text, "1.234");
+}
+
+#[test]
+fn test_password() {
+    let ctx = Context::default();
+    let id = Id::new("text_edit");
+    let mut text = String::new();
+
+    // Returns the shown text and what was copied:
+    let run = |text: &mut String, time: f64, allow_copy: bool, events: Vec<Event>| {
+        let input = RawInput {
+            events,
+            time: Some(time),
+            ..Default::default()
+        };
+        let mut shown = String::new();
+        let output = run_in_panel(&ctx, input, |ui| {
+            let output = TextEdit::singleline(text)
+                .id(id)
+                .password(true)
+                .password_char('*')
+                .reveal_last_char_for(std::time::Duration::from_secs(1))
+                .allow_password_copy(allow_copy)
+                .show(ui);
+            shown = output.galley.text().to_owned();
+        });
+        (shown, output.platform_output.copied_text)
+    };
+
+    run(&mut text, 0.0, false, vec![]);
+    ctx.memory_mut(|mem| mem.request_focus(id));
+    run(&mut text, 0.1, false, vec![]);
+
+    // The last typed char is shown until the next keystroke or until time is up:
+    let typed = |c: char| vec![Event::Text(c.to_string())];
+    assert_eq!(run(&mut text, 0.2, false, typed('a')).0, "a");
+    assert_eq!(run(&mut text, 0.3, false, typed('b')).0, "*b");
+    assert_eq!(run(&mut text, 0.5, false, vec![]).0, "*b");
+    assert_eq!(run(&mut text, 2.0, false, vec![]).0, "**");
+    assert_eq!(text, "ab");
+
+    // Copying the password needs to be allowed:
+    let select_all_and_copy = vec![key(Key::A, Modifiers::COMMAND), Event::Copy];
+    assert_eq!(
+        run(&mut text, 2.1, false, select_all_and_copy.clone()).1,
+        ""
+    );
+    assert_eq!(run(&mut text, 2.2, true, select_all_and_copy).1, "ab");
+}
+
+#[test]
+fn test_line_number_gutter() {
+    // Returns where the text starts:
+    let text_left = |text: &str, show_line_numbers: bool| {
+        let ctx = Context::default();
+        let mut text = text.to_owned();
+        let mut left = 0.0;
+        run_in_panel(&ctx, RawInput::default(), |ui| {
+            let output = TextEdit::multiline(&mut text)
+                .show_line_numbers(show_line_numbers)
+                .show(ui);
+            left = output.galley_pos.x;
+        });
+        left
+    };
+
+    let nine_lines = ["line"; 9].join("\n");
+    let ten_lines = ["line"; 10].join("\n");
+    assert!(text_left(&nine_lines, false) < text_left(&nine_lines, true));
+    assert!(
+        text_left(&nine_lines, true) < text_left(&ten_lines, true),
+        "the gutter fits the number of digits"
+    );
+}
+
+#[test]
+fn test_completion_popup() {
+    let ctx = Context::default();
+    let id = Id::new("text_edit");
+    let mut text = "say ".to_owned();
+
+    let run = |text: &mut String, events: Vec<Event>| {
+        let input = RawInput {
+            events,
+            ..Default::default()
+        };
+        run_in_panel(&ctx, input, |ui| {
+            TextEdit::singleline(text)
+                .id(id)
+                .completion_provider(|text, ccursor| {
+                    let before_cursor: String = text.chars().take(ccursor.index).collect();
+                    let word = before_cursor.rsplit(' ').next().unwrap_or_default();
+                    ["hello", "help", "world"]
+                        .into_iter()
+                        .filter(|item| !word.is_empty() && item.starts_with(word))
+                        .map(text_edit::CompletionItem::new)
+                        .collect()
+                })
+                .show(ui);
+        });
+    };
+    let num_completions = || {
+        TextEditState::load(&ctx, id)
+            .unwrap()
+            .completion
+            .map(|completion| completion.items.len())
+    };
+
+    run(&mut text, vec![]);
+    ctx.memory_mut(|mem| mem.request_focus(id));
+    run(&mut text, vec![]);
+
+    // Typing opens the popup:
+    run(
+        &mut text,
+        vec![Event::Text("h".to_owned()), Event::Text("e".to_owned())],
+    );
+    assert_eq!(num_completions(), Some(2));
+
+    // Down selects the second item, Enter replaces the word with it and keeps the focus:
+    run(&mut text, vec![key(Key::ArrowDown, Modifiers::NONE)]);
+    run(&mut text, vec![key(Key::Enter, Modifiers::NONE)]);
+    assert_eq!(text, "say help");
+    assert_eq!(num_completions(), None);
+    assert!(ctx.memory(|mem| mem.has_focus(id)));
+
+    // The picked completion is an undo step of its own:
+    run(&mut text, vec![key(Key::Z, Modifiers::COMMAND)]);
+    assert_eq!(text, "say he");
+    assert_eq!(num_completions(), None);
+
+    // Escape closes the popup, but not the focus:
+    run(&mut text, vec![Event::Text("l".to_owned())]);
+    assert_eq!(num_completions(), Some(2));
+    run(&mut text, vec![key(Key::Escape, Modifiers::NONE)]);
+    assert_eq!(num_completions(), None);
+    assert!(ctx.memory(|mem| mem.has_focus(id)));
+    assert_eq!(text, "say hel");
+}
+
+#[test]
+fn test_drag_and_drop_text() {
+    let ctx = Context::default();
+    let [id_a, id_b] = [Id::new("a"), Id::new("b")];
+    let mut texts = ["one two three".to_owned(), "x".to_owned()];
+
+    let time = std::cell::Cell::new(0.0);
+    let run = |texts: &mut [String; 2], events: Vec<Event>, modifiers: Modifiers| {
+        time.set(time.get() + 0.1);
+        let input = RawInput {
+            events,
+            modifiers,
+            time: Some(time.get()),
+            ..Default::default()
+        };
+        let mut galley_pos = [Pos2::ZERO; 2];
+        run_in_panel(&ctx, input, |ui| {
+            let [text_a, text_b] = texts;
+            for (i, (text, id)) in [(text_a, id_a), (text_b, id_b)].into_iter().enumerate() {
+                galley_pos[i] = TextEdit::singleline(text)
+                    .id(id)
+                    .font(TextStyle::Monospace)
+                    .show(ui)
+                    .galley_pos;
+            }
+        });
+        galley_pos
+    };
+    let pos_of = |galley_pos: Pos2, text: &str, index: usize| {
+        let font_id = TextStyle::Monospace.resolve(&ctx.style());
+        let galley =
+            ctx.fonts(|fonts| fonts.layout_no_wrap(text.to_owned(), font_id, Color32::WHITE));
+        let cursor = galley.from_ccursor(CCursor::new(index));
+        galley_pos + galley.pos_from_cursor(&cursor).center().to_vec2()
+    };
+    let button = |pos: Pos2, pressed: bool| Event::PointerButton {
+        pos,
+        button: PointerButton::Primary,
+        pressed,
+        modifiers: Modifiers::NONE,
+    };
+    let select = |range: std::ops::Range<usize>| {
+        let mut state = TextEditState::load(&ctx, id_a).unwrap();
+        state.cursor.set_char_range(Some(CCursorRange::two(
+            CCursor::new(range.start),
+            CCursor::new(range.end),
+        )));
+        state.store(&ctx, id_a);
+    };
+    let drag = |texts: &mut [String; 2], from: Pos2, to: Pos2, modifiers: Modifiers| {
+        run(
+            texts,
+            vec![Event::PointerMoved(from), button(from, true)],
+            modifiers,
+        );
+        run(texts, vec![Event::PointerMoved(to)], modifiers);
+        run(texts, vec![button(to, false)], modifiers);
+        run(texts, vec![], Modifiers::NONE);
+    };
+
+    let [pos_a, pos_b] = run(&mut texts, vec![], Modifiers::NONE);
+    ctx.memory_mut(|mem| mem.request_focus(id_a));
+    run(&mut texts, vec![], Modifiers::NONE);
+
+    // Drag " two" to the end:
+    select(3..7);
+    let [from, to] = [pos_of(pos_a, &texts[0], 5), pos_of(pos_a, &texts[0], 13)];
+    drag(&mut texts, from, to, Modifiers::NONE);
+    assert_eq!(texts[0], "one three two");
+    let state = TextEditState::load(&ctx, id_a).unwrap();
+    assert_eq!(
+        state
+            .cursor
+            .char_range()
+            .unwrap()
+            .sorted()
+            .map(|ccursor| ccursor.index),
+        [9, 13]
+    );
+
+    // Moving is one undo step:
+    run(
+        &mut texts,
+        vec![key(Key::Z, Modifiers::COMMAND)],
+        Modifiers::NONE,
+    );
+    assert_eq!(texts[0], "one two three");
+
+    // Dropping the selection onto itself does nothing:
+    select(3..7);
+    let [from, to] = [pos_of(pos_a, &texts[0], 4), pos_of(pos_a, &texts[0], 7)];
+    drag(&mut texts, from, to, Modifiers::NONE);
+    assert_eq!(texts[0], "one two three");
+
+    // Dragging to another `TextEdit` copies with Ctrl, and moves without:
+    let to = pos_of(pos_b, &texts[1], 1);
+    drag(&mut texts, from, to, Modifiers::COMMAND);
+    assert_eq!(texts, ["one two three", "x two"]);
+    select(3..7);
+    let to = pos_of(pos_b, &texts[1], 5);
+    drag(&mut texts, from, to, Modifiers::NONE);
+    assert_eq!(texts, ["one three", "x two two"]);
+}
+
+#[test]
+fn test_primary_selection() {
+    let ctx = Context::default();
+    let [id_a, id_b] = [Id::new("a"), Id::new("b")];
+    let mut texts = ["one two".to_owned(), "x".to_owned()];
+
+    let run = |texts: &mut [String; 2], events: Vec<Event>, modifiers: Modifiers| {
+        let input = RawInput {
+            events,
+            modifiers,
+            ..Default::default()
+        };
+        let mut galley_pos = Pos2::ZERO;
+        let output = run_in_panel(&ctx, input, |ui| {
+            let [text_a, text_b] = texts;
+            TextEdit::singleline(text_a).id(id_a).show(ui);
+            galley_pos = TextEdit::singleline(text_b).id(id_b).show(ui).galley_pos;
+        });
+        (galley_pos, output.platform_output.copied_text_primary)
+    };
+    let select_all = |texts: &mut [String; 2]| {
+        ctx.memory_mut(|mem| mem.request_focus(id_a));
+        run(texts, vec![], Modifiers::NONE);
+        let select_all = key(Key::A, Modifiers::COMMAND);
+        let (_, primary) = run(texts, vec![select_all], Modifiers::COMMAND);
+        ctx.memory_mut(|mem| mem.surrender_focus(id_a));
+        primary
+    };
+    let middle_click = |texts: &mut [String; 2], pos: Pos2, primary: &str| {
+        let events = vec![
+            Event::PointerMoved(pos),
+            Event::PointerButton {
+                pos,
+                button: PointerButton::Middle,
+                pressed: true,
+                modifiers: Modifiers::NONE,
+            },
+            Event::PasteFromPrimary(primary.to_owned()),
+        ];
+        run(texts, events, Modifiers::NONE);
+    };
+
+    ctx.set_os(OperatingSystem::Nix);
+    let (pos_b, _) = run(&mut texts, vec![], Modifiers::NONE);
+
+    // Selecting text sets the primary selection:
+    assert_eq!(select_all(&mut texts), "one two");
+
+    // Middle-click pastes it at the pointer:
+    middle_click(&mut texts, pos_b + vec2(20.0, 2.0), " two");
+    assert_eq!(texts[1], "x two");
+
+    // There is no primary selection on other platforms:
+    ctx.set_os(OperatingSystem::Windows);
+    texts[1] = "x".to_owned();
+    let mut state = TextEditState::load(&ctx, id_a).unwrap();
+    state.cursor.set_char_range(None);
+    state.store(&ctx, id_a);
+    assert_eq!(select_all(&mut texts), "");
+    middle_click(&mut texts, pos_b + vec2(20.0, 2.0), " two");
+    assert_eq!(texts[1], "x");
+}
+
+#[test]
+fn test_read_only() {
+    let ctx = Context::default();
+    let id = Id::new("text_edit");
+    let mut text = "hello world".to_owned();
+
+    // Returns whether the text changed, and the copied text:
+    let run = |text: &mut String, events: Vec<Event>, modifiers: Modifiers| {
+        let input = RawInput {
+            events,
+            modifiers,
+            ..Default::default()
+        };
+        let mut changed = false;
+        let output = run_in_panel(&ctx, input, |ui| {
+            changed = TextEdit::singleline(text)
+                .id(id)
+                .read_only(true)
+                .show(ui)
+                .response
+                .changed();
+        });
+        (changed, output.platform_output.copied_text)
+    };
+
+    ctx.memory_mut(|mem| mem.request_focus(id));
+    run(&mut text, vec![], Modifiers::NONE);
+
+    // Nothing changes the text:
+    for event in [
+        Event::Text("x".to_owned()),
+        Event::Paste("x".to_owned()),
+        Event::Cut,
+        key(Key::Backspace, Modifiers::NONE),
+        key(Key::Delete, Modifiers::NONE),
+        key(Key::Enter, Modifiers::NONE),
+    ] {
+        let (changed, _) = run(&mut text, vec![event], Modifiers::NONE);
+        assert!(!changed);
+        assert_eq!(text, "hello world");
+    }
+
+    // Navigation, selection and copying works:
+    run(
+        &mut text,
+        vec![key(Key::Home, Modifiers::NONE)],
+        Modifiers::NONE,
+    );
+    run(
+        &mut text,
+        vec![key(Key::ArrowRight, Modifiers::SHIFT); 5],
+        Modifiers::SHIFT,
+    );
+    assert_eq!(
+        run(&mut text, vec![Event::Copy], Modifiers::NONE).1,
+        "hello"
+    );
+    run(
+        &mut text,
+        vec![key(Key::A, Modifiers::COMMAND)],
+        Modifiers::COMMAND,
+    );
+    assert_eq!(
+        run(&mut text, vec![Event::Copy], Modifiers::NONE).1,
+        "hello world"
+    );
+}
+
+#[test]
+fn test_output_screen_positions() {
+    use std::fmt::Write as _;
+
+    let ctx = Context::default();
+    let id = Id::new("text_edit");
+    let mut text = String::new();
+    for i in 0..100 {
+        writeln!(text, "line {i}").unwrap();
+    }
+
+    let run = |text: &mut String| {
+        let mut output = None;
+        run_in_panel(&ctx, RawInput::default(), |ui| {
+            ScrollArea::vertical().max_height(100.0).show(ui, |ui| {
+                output = Some(TextEdit::multiline(text).id(id).show(ui));
+            });
+        });
+        output.unwrap()
+    };
+
+    let output = run(&mut text);
+    assert_eq!(output.cursor_screen_rect, None, "no cursor without focus");
+    let visible = output.visible_row_range.clone();
+    assert_eq!(visible.start, 0);
+    assert!(1 < visible.end && visible.end < 100, "{visible:?}");
+    assert_eq!(output.char_to_screen_pos(0), Some(output.galley_pos));
+    assert_eq!(output.char_to_screen_pos(text.chars().count() + 1), None);
+
+    ctx.memory_mut(|mem| mem.request_focus(id));
+    let mut state = TextEditState::load(&ctx, id).unwrap();
+    state
+        .cursor
+        .set_char_range(Some(CCursorRange::one(CCursor::new(12))));
+    state.store(&ctx, id);
+    run(&mut text);
+    let output = run(&mut text);
+    let cursor_pos = output.char_to_screen_pos(12).unwrap();
+    assert!(output.cursor_screen_rect.unwrap().contains(cursor_pos));
+    assert!(
+        output.galley_pos.y < cursor_pos.y,
+        "12 is on the second row"
+    );
+}
+
+#[test]
+fn test_layouter_with_context() {
+    let ctx = Context::default();
+    let id = Id::new("text_edit");
+    let mut text = "(a)".to_owned();
+
+    // Returns the cursor and focus the layouter was last run with:
+    let run = |text: &mut String, events: Vec<Event>| {
+        let input = RawInput {
+            events,
+            ..Default::default()
+        };
+        let mut last_context = None;
+        run_in_panel(&ctx, input, |ui| {
+            let layouter = |ui: &Ui, context: &LayouterContext<'_>| {
+                last_context = Some((context.cursor.map(|c| c.index), context.has_focus));
+                let font_id = FontId::monospace(14.0);
+                let job = LayoutJob::simple(
+                    context.text.to_owned(),
+                    font_id,
+                    Color32::WHITE,
+                    context.wrap_width,
+                );
+                ui.fonts(|f| f.layout_job(job))
+            };
+            TextEdit::singleline(text)
+                .id(id)
+                .layouter_with_context(layouter)
+                .show(ui);
+        });
+        last_context.unwrap()
+    };
+
+    assert_eq!(run(&mut text, vec![]), (None, false));
+    ctx.memory_mut(|mem| mem.request_focus(id));
+    run(&mut text, vec![key(Key::Home, Modifiers::NONE)]);
+    assert_eq!(run(&mut text, vec![]), (Some(0), true));
+
+    // Moving the cursor runs the layouter again, with the new cursor:
+    let arrow_right = key(Key::ArrowRight, Modifiers::NONE);
+    assert_eq!(run(&mut text, vec![arrow_right]), (Some(1), true));
+    let end = key(Key::End, Modifiers::NONE);
+    assert_eq!(run(&mut text, vec![end]), (Some(3), true));
+    assert_eq!(text, "(a)");
+}
+
+#[test]
+fn test_auto_close_pairs() {
+    let ctx = Context::default();
+    let id = Id::new("text_edit");
+    let mut text = String::new();
+
+    let run = |text: &mut String, events: Vec<Event>| {
+        let input = RawInput {
+            events,
+            ..Default::default()
+        };
+        run_in_panel(&ctx, input, |ui| {
+            TextEdit::multiline(text)
+                .id(id)
+                .auto_close_pairs(&[('(', ')'), ('[', ']'), ('"', '"')])
+                .show(ui);
+        });
+        TextEditState::load(&ctx, id)
+            .and_then(|state| state.cursor.char_range())
+            .map(|range| range.primary.index)
+    };
+    let typed = |s: &str| s.chars().map(|c| Event::Text(c.to_string())).collect();
+
+    run(&mut text, vec![]);
+    ctx.memory_mut(|mem| mem.request_focus(id));
+    run(&mut text, vec![]);
+
+    // The closing char is inserted, and typing it steps over it:
+    assert_eq!(run(&mut text, typed("f(")), Some(2));
+    assert_eq!(text, "f()");
+    assert_eq!(run(&mut text, typed("a[")), Some(4));
+    assert_eq!(text, "f(a[])");
+    assert_eq!(run(&mut text, typed("0])")), Some(7));
+    assert_eq!(text, "f(a[0])");
+
+    // Quotes are not closed after a word:
+    run(&mut text, typed(" \"x"));
+    assert_eq!(text, "f(a[0]) \"x\"");
+    run(&mut text, typed("\"y\""));
+    assert_eq!(text, "f(a[0]) \"x\"y\"");
+
+    // Backspace deletes an auto-closed pair:
+    text.clear();
+    run(&mut text, vec![key(Key::End, Modifiers::NONE)]);
+    run(&mut text, typed("(["));
+    assert_eq!(text, "([])");
+    run(&mut text, vec![key(Key::Backspace, Modifiers::NONE)]);
+    assert_eq!(text, "()");
+
+    // Each auto-closed pair is one undo step:
+    run(&mut text, vec![key(Key::Z, Modifiers::COMMAND)]);
+    assert_eq!(text, "([])");
+    run(&mut text, vec![key(Key::Z, Modifiers::COMMAND)]);
+    assert_eq!(text, "()");
+
+    // Moving the cursor forgets the auto-closed chars, so the closing char is typed as usual:
+    text.clear();
+    run(&mut text, vec![key(Key::End, Modifiers::NONE)]);
+    run(&mut text, typed("("));
+    run(
+        &mut text,
+        vec![
+            key(Key::ArrowLeft, Modifiers::NONE),
+            key(Key::ArrowRight, Modifiers::NONE),
+        ],
+    );
+    assert_eq!(run(&mut text, typed(")")), Some(2));
+    assert_eq!(text, "())");
+
+    // …and so does editing the text some other way:
+    text.clear();
+    run(&mut text, vec![key(Key::End, Modifiers::NONE)]);
+    run(&mut text, typed("("));
+    text.push('x');
+    assert_eq!(run(&mut text, typed(")")), Some(2));
+    assert_eq!(text, "())x");
+}
+
+#[test]
+fn test_auto_indent() {
+    let ctx = Context::default();
+    let id = Id::new("text_edit");
+    let mut text = "  if x:".to_owned();
+
+    let run = |text: &mut String, events: Vec<Event>| {
+        let input = RawInput {
+            events,
+            ..Default::default()
+        };
+        run_in_panel(&ctx, input, |ui| {
+            TextEdit::multiline(text)
+                .id(id)
+                .auto_indent(true)
+                .indent_string("    ")
+                .show(ui);
+        });
+    };
+    let enter = key(Key::Enter, Modifiers::NONE);
+    let backspace = key(Key::Backspace, Modifiers::NONE);
+
+    ctx.memory_mut(|mem| mem.request_focus(id));
+    run(&mut text, vec![]);
+    run(&mut text, vec![key(Key::End, Modifiers::NONE)]);
+
+    // One more level after a ':', and the newline and indentation are one undo step:
+    run(&mut text, vec![enter.clone()]);
+    assert_eq!(text, "  if x:\n      ");
+    run(&mut text, vec![key(Key::Z, Modifiers::COMMAND)]);
+    assert_eq!(text, "  if x:");
+    run(&mut text, vec![enter.clone()]);
+
+    // The same indentation as the line before:
+    run(&mut text, vec![Event::Text("y".to_owned()), enter]);
+    assert_eq!(text, "  if x:\n      y\n      ");
+
+    // Backspace deletes a level of indentation, and then single spaces:
+    run(&mut text, vec![backspace.clone()]);
+    assert_eq!(text, "  if x:\n      y\n  ");
+    run(&mut text, vec![backspace]);
+    assert_eq!(text, "  if x:\n      y\n ");
+
+    // Shift+Enter doesn't indent:
+    run(&mut text, vec![key(Key::Enter, Modifiers::SHIFT)]);
+    assert_eq!(text, "  if x:\n      y\n \n");
+}
+
+#[test]
+fn test_indent_selected_lines() {
+    let ctx = Context::default();
+    let id = Id::new("text_edit");
+    let mut text = "a\n  b\nc\nd".to_owned();
+    let char_limit = std::cell::Cell::new(usize::MAX);
+
+    let run = |text: &mut String, events: Vec<Event>| {
+        let input = RawInput {
+            events,
+            ..Default::default()
+        };
+        run_in_panel(&ctx, input, |ui| {
+            TextEdit::multiline(text)
+                .id(id)
+                .lock_focus(true)
+                .char_limit(char_limit.get())
+                .show(ui);
+        });
+    };
+    let tab = |modifiers: Modifiers| key(Key::Tab, modifiers);
+    let selection = || {
+        let range = TextEditState::load(&ctx, id)
+            .unwrap()
+            .cursor
+            .char_range()
+            .unwrap();
+        (range.secondary.index, range.primary.index)
+    };
+
+    ctx.memory_mut(|mem| mem.request_focus(id));
+    run(&mut text, vec![]);
+
+    // Ends at the start of "c", so only the first two lines are selected:
+    let mut state = TextEditState::load(&ctx, id).unwrap();
+    state
+        .cursor
+        .set_char_range(Some(CCursorRange::two(CCursor::new(1), CCursor::new(6))));
+    state.store(&ctx, id);
+
+    run(&mut text, vec![tab(Modifiers::NONE)]);
+    assert_eq!(text, "\ta\n\t  b\nc\nd");
+    assert_eq!(selection(), (2, 8));
+
+    run(&mut text, vec![tab(Modifiers::SHIFT)]);
+    assert_eq!(text, "a\n  b\nc\nd");
+    assert_eq!(selection(), (1, 6));
+
+    // Lines without indentation are left alone:
+    run(&mut text, vec![tab(Modifiers::SHIFT)]);
+    assert_eq!(text, "a\nb\nc\nd");
+    assert_eq!(selection(), (1, 4));
+
+    // Either all the lines are indented, or none if that would go over the char limit:
+    char_limit.set(8);
+    run(&mut text, vec![tab(Modifiers::NONE)]);
+    assert_eq!(text, "a\nb\nc\nd");
+    assert_eq!(selection(), (1, 4));
+    char_limit.set(9);
+    run(&mut text, vec![tab(Modifiers::NONE)]);
+    assert_eq!(text, "\ta\n\tb\nc\nd");
+}
+
+#[test]
+fn test_drag_scroll() {
+    let ctx = Context::default();
+    let id = Id::new("text_edit");
+    let mut text = (0..200)
+        .map(|i| format!("Line {i}"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let frame = std::cell::Cell::new(0);
+
+    // Returns the scroll offset, the visible part of the text edit and where the cursor is:
+    let run = |text: &mut String, events: Vec<Event>| {
+        frame.set(frame.get() + 1);
+        let input = RawInput {
+            time: Some(frame.get() as f64 / 60.0),
+            predicted_dt: 1.0 / 60.0,
+            events,
+            ..Default::default()
+        };
+        let mut offset = 0.0;
+        let mut visible_rect = Rect::NOTHING;
+        let mut cursor_rect = None;
+        run_in_panel(&ctx, input, |ui| {
+            let output = ScrollArea::vertical()
+                .max_height(100.0)
+                .animated(false)
+                .show(ui, |ui| {
+                    let output = TextEdit::multiline(text).id(id).show(ui);
+                    visible_rect = ui.clip_rect().intersect(output.response.rect);
+                    cursor_rect = output.cursor_screen_rect;
+                });
+            offset = output.state.offset.y;
+        });
+        (offset, visible_rect, cursor_rect)
+    };
+
+    // The scroll area is only sized to its content after the first frame:
+    run(&mut text, vec![]);
+    let (_, clip_rect, _) = run(&mut text, vec![]);
+    let start = clip_rect.left_top() + vec2(2.0, 5.0);
+    run(
+        &mut text,
+        vec![
+            Event::PointerMoved(start),
+            Event::PointerButton {
+                pos: start,
+                button: PointerButton::Primary,
+                pressed: true,
+                modifiers: Modifiers::NONE,
+            },
+        ],
+    );
+
+    // 50 points below the visible area is 1000 points per second:
+    let below = pos2(start.x, clip_rect.bottom() + 50.0);
+    let (mut offset, _, _) = run(&mut text, vec![Event::PointerMoved(below)]);
+    let mut cursor_rect = None;
+    for _ in 0..10 {
+        let (new_offset, _, new_cursor_rect) = run(&mut text, vec![]);
+        assert!(
+            (new_offset - offset - 1000.0 / 60.0).abs() < 1.0,
+            "scrolled from {offset} to {new_offset}"
+        );
+        offset = new_offset;
+        cursor_rect = new_cursor_rect;
+    }
+
+    // The selection extends to the bottom edge of the visible area:
+    let selection = TextEditState::load(&ctx, id)
+        .unwrap()
+        .cursor
+        .char_range()
+        .unwrap();
+    assert_eq!(selection.secondary.index, 0);
+    let cursor_rect = cursor_rect.unwrap();
+    assert!(
+        (cursor_rect.center().y - clip_rect.bottom()).abs() < cursor_rect.height(),
+        "the cursor at {cursor_rect:?} is at the bottom of {clip_rect:?}"
+    );
+
+    // Far outside, the speed is capped:
+    let far_below = pos2(start.x, clip_rect.bottom() + 1000.0);
+    let (offset, _, _) = run(&mut text, vec![Event::PointerMoved(far_below)]);
+    let (new_offset, _, _) = run(&mut text, vec![]);
+    assert!((new_offset - offset - 1500.0 / 60.0).abs() < 1.0);
+}
+
+#[test]
+fn test_keymap() {
+    use crate::text_edit::{CursorMove, TextEditCommand, TextEditKeymap};
+
+    let ctx = Context::default();
+    let id = Id::new("text_edit");
+    let mut text = "one two".to_owned();
+
+    let run = |text: &mut String, keymap: &TextEditKeymap, events: Vec<Event>| {
+        let input = RawInput {
+            events,
+            ..Default::default()
+        };
+        run_in_panel(&ctx, input, |ui| {
+            TextEdit::singleline(text)
+                .id(id)
+                .keymap(keymap.clone())
+                .show(ui);
+        });
+        let range = TextEditState::load(&ctx, id)
+            .unwrap()
+            .cursor
+            .char_range()
+            .unwrap();
+        (range.secondary.index, range.primary.index)
+    };
+    let select_all = key(Key::A, Modifiers::COMMAND);
+
+    let default_keymap = TextEditKeymap::default_for(ctx.os(), &ctx.style().interaction);
+    let keymap = default_keymap
+        .clone()
+        .bind(
+            KeyboardShortcut::new(Modifiers::COMMAND, Key::A),
+            TextEditCommand::Move(CursorMove::LineStart),
+        )
+        .bind(
+            KeyboardShortcut::new(Modifiers::ALT, Key::B),
+            TextEditCommand::DeleteWordBackward,
+        )
+        .unbind(KeyboardShortcut::new(Modifiers::NONE, Key::Backspace));
+
+    ctx.memory_mut(|mem| mem.request_focus(id));
+    run(&mut text, &keymap, vec![]);
+    assert_eq!(run(&mut text, &keymap, vec![select_all.clone()]), (0, 0));
+
+    // The default keymap works like no keymap:
+    assert_eq!(
+        run(&mut text, &default_keymap, vec![select_all.clone()]),
+        (0, 7)
+    );
+
+    run(&mut text, &keymap, vec![key(Key::End, Modifiers::NONE)]);
+    run(
+        &mut text,
+        &keymap,
+        vec![key(Key::Backspace, Modifiers::NONE)],
+    );
+    assert_eq!(text, "one two", "unbound");
+    run(&mut text, &keymap, vec![key(Key::B, Modifiers::ALT)]);
+    assert_eq!(text, "one ");
+
+    // Keys that aren't in any keymap work as usual:
+    run(&mut text, &keymap, vec![Event::Text("2".to_owned())]);
+    assert_eq!(text, "one 2");
+
+    // The default keymap moves by the parts of words with the modifiers of the style:
+    let mut interaction = ctx.style().interaction.clone();
+    interaction.subword_modifiers = Modifiers::ALT;
+    let subword_keymap = TextEditKeymap::default_for(ctx.os(), &interaction);
+    assert_eq!(
+        subword_keymap.command_for(Key::ArrowLeft, Modifiers::ALT),
+        Some(TextEditCommand::Move(CursorMove::SubwordLeft))
+    );
+    assert_eq!(
+        subword_keymap.command_for(Key::ArrowLeft, Modifiers::CTRL | Modifiers::ALT),
+        None
+    );
+}
+
+#[test]
+fn test_copy_rich_text() {
+    let ctx = Context::default();
+    let id = Id::new("text_edit");
+    let mut text = "a<b".to_owned();
+
+    let copy = |text: &mut String, password: bool| {
+        let input = RawInput {
+            events: vec![Event::Copy],
+            ..Default::default()
+        };
+        run_in_panel(&ctx, input, |ui| {
+            TextEdit::singleline(text)
+                .id(id)
+                .password(password)
+                .allow_password_copy(true)
+                .show(ui);
+        })
+        .platform_output
+    };
+
+    ctx.memory_mut(|mem| mem.request_focus(id));
+    let output = copy(&mut text, false);
+    assert_eq!(output.copied_text, "a<b");
+    assert_eq!(output.copied_rich_text, None, "off by default");
+
+    ctx.style_mut(|style| style.interaction.copy_rich_text = true);
+    let output = copy(&mut text, false);
+    assert_eq!(output.copied_text, "a<b");
+    let (rich_text, html) = output.copied_rich_text.unwrap();
+    assert_eq!(rich_text, "a<b");
+    assert!(html.starts_with("<div"), "{html}");
+    assert!(html.contains(">a&lt;b</span>"), "{html}");
+
+    // The galley of a password field doesn't show the text:
+    let output = copy(&mut text, true);
+    assert_eq!(output.copied_text, "a<b");
+    assert_eq!(output.copied_rich_text, None);
+}
+
+#[test]
+fn test_paste_image() {
+    let ctx = Context::default();
+    let id = Id::new("text_edit");
+    let mut text = "see ".to_owned();
+    let mut pasted_sizes = vec![];
+
+    let mut run = |text: &mut String, accept: bool, events: Vec<Event>| {
+        let input = RawInput {
+            events,
+            ..Default::default()
+        };
+        run_in_panel(&ctx, input, |ui| {
+            TextEdit::singleline(text)
+                .id(id)
+                .accept_pasted_images(accept)
+                .on_paste_image(|image| {
+                    pasted_sizes.push(image.size);
+                    Some("![](image.png)".to_owned())
+                })
+                .show(ui);
+        })
+        .platform_output
+        .accepts_pasted_images
+    };
+    let paste = || Event::PastedImage(ColorImage::new([2, 3], Color32::RED));
+
+    ctx.memory_mut(|mem| mem.request_focus(id));
+    run(&mut text, false, vec![]);
+    assert!(!run(&mut text, false, vec![paste()]));
+    assert_eq!(text, "see ", "ignored without the flag");
+
+    assert!(run(&mut text, true, vec![paste()]));
+    assert_eq!(text, "see ![](image.png)");
+
+    let undo = key(Key::Z, Modifiers::COMMAND);
+    run(&mut text, true, vec![undo]);
+    assert_eq!(text, "see ");
+    assert_eq!(pasted_sizes, vec![[2, 3]]);
+}
+
+#[test]
+fn test_secondary_cursor() {
+    let ctx = Context::default();
+    let id = Id::new("text_edit");
+    let mut text = "one two".to_owned();
+    let secondary_color = Color32::from_rgb(1, 2, 3);
+    ctx.style_mut(|style| {
+        style.visuals.text_cursor.secondary_stroke = Stroke::new(3.0, secondary_color);
+    });
+
+    let mut run = |events: Vec<Event>| {
+        let input = RawInput {
+            events,
+            ..Default::default()
+        };
+        let output = run_in_panel(&ctx, input, |ui| {
+            TextEdit::singleline(&mut text).id(id).show(ui);
+        });
+        output.shapes.iter().any(|clipped| {
+            matches!(
+                &clipped.shape,
+                epaint::Shape::LineSegment {
+                    stroke: epaint::PathStroke {
+                        color: epaint::ColorMode::Solid(color),
+                        ..
+                    },
+                    ..
+                } if *color == secondary_color
+            )
+        })
+    };
+
+    ctx.memory_mut(|mem| mem.request_focus(id));
+    assert!(!run(vec![]), "no selection");
+
+    let select_all = key(Key::A, Modifiers::COMMAND);
+    assert!(run(vec![select_all]));
+}
+
+#[test]
+fn test_drag_after_double_click_selects_words() {
+    let ctx = Context::default();
+    let id = Id::new("text_edit");
+    let mut text = "one two three four".to_owned();
+
+    let time = std::cell::Cell::new(0.0);
+    let run = |text: &mut String, events: Vec<Event>| {
+        time.set(time.get() + 0.1);
+        let input = RawInput {
+            events,
+            time: Some(time.get()),
+            ..Default::default()
+        };
+        let mut galley_pos = Pos2::ZERO;
+        let output = run_in_panel(&ctx, input, |ui| {
+            galley_pos = TextEdit::singleline(text)
+                .id(id)
+                .font(TextStyle::Monospace)
+                .show(ui)
+                .galley_pos;
+        });
+        (galley_pos, output.platform_output.copied_text)
+    };
+
+    let (galley_pos, _) = run(&mut text, vec![]);
+    let font_id = TextStyle::Monospace.resolve(&ctx.style());
+    let galley = ctx.fonts(|fonts| fonts.layout_no_wrap(text.clone(), font_id, Color32::WHITE));
+    let pos_of = |index: usize| {
+        let cursor = galley.from_ccursor(CCursor::new(index));
+        galley_pos + galley.pos_from_cursor(&cursor).center().to_vec2()
+    };
+    let button = |pos: Pos2, pressed: bool| Event::PointerButton {
+        pos,
+        button: PointerButton::Primary,
+        pressed,
+        modifiers: Modifiers::NONE,
+    };
+
+    // Click "two", then press it again and drag into the middle of "three":
+    let [start, end] = [pos_of(5), pos_of(10)];
+    run(
+        &mut text,
+        vec![Event::PointerMoved(start), button(start, true)],
+    );
+    run(&mut text, vec![button(start, false)]);
+    run(&mut text, vec![button(start, true)]);
+    for pos in [start + vec2(10.0, 0.0), end] {
+        run(&mut text, vec![Event::PointerMoved(pos)]);
+    }
+    run(&mut text, vec![button(end, false)]);
+
+    let (_, copied_text) = run(&mut text, vec![Event::Copy]);
+    assert_eq!(copied_text, "two three");
+
+    // A drag that doesn't follow a click selects by characters again:
+    time.set(time.get() + 1.0);
+    let start = pos_of(1);
+    run(
+        &mut text,
+        vec![Event::PointerMoved(start), button(start, true)],
+    );
+    for pos in [start + vec2(10.0, 0.0), end] {
+        run(&mut text, vec![Event::PointerMoved(pos)]);
+    }
+    run(&mut text, vec![button(end, false)]);
+    let (_, copied_text) = run(&mut text, vec![Event::Copy]);
+    assert_eq!(copied_text, "ne two th");
+}
+
+#[test]
+fn test_overlapping_cursors() {
+    let ctx = Context::default();
+    let id = Id::new("text_edit");
+    let mut text = "abcdef".to_owned();
+
+    let run = |text: &mut String, events: Vec<Event>| {
+        let input = RawInput {
+            events,
+            ..Default::default()
+        };
+        run_in_panel(&ctx, input, |ui| {
+            TextEdit::multiline(text).id(id).show(ui);
+        });
+    };
+    let cursors = || {
+        let state = TextEditState::load(&ctx, id).unwrap();
+        let mut cursors: Vec<[usize; 2]> = std::iter::once(state.cursor.char_range().unwrap())
+            .chain(state.extra_char_ranges().iter().copied())
+            .map(|ccursor_range| ccursor_range.sorted().map(|ccursor| ccursor.index))
+            .collect();
+        cursors.sort_unstable();
+        cursors
+    };
+
+    run(&mut text, vec![]);
+    ctx.memory_mut(|mem| mem.request_focus(id));
+    run(&mut text, vec![]);
+
+    // Typing over two selections that overlap replaces each of them,
+    // without the later edit eating what the earlier one typed:
+    let mut state = TextEditState::load(&ctx, id).unwrap();
+    state
+        .cursor
+        .set_char_range(Some(CCursorRange::two(CCursor::new(1), CCursor::new(4))));
+    state.set_extra_char_ranges(vec![CCursorRange::two(CCursor::new(3), CCursor::new(5))]);
+    state.store(&ctx, id);
+    run(&mut text, vec![Event::Text("x".to_owned())]);
+    assert_eq!(text, "axxf");
+    assert_eq!(cursors(), [[2, 2], [3, 3]]);
+
+    // Deleting backwards from both moves them into each other, and they become one:
+    let backspace = key(Key::Backspace, Modifiers::NONE);
+    run(&mut text, vec![backspace.clone(), backspace]);
+    assert_eq!(text, "f");
+    assert_eq!(cursors(), [[0, 0]]);
+}
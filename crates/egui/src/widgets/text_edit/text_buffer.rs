@@ -8,12 +8,15 @@ use epaint::{
     Galley,
 };
 
-use crate::text_selection::{
-    text_cursor_state::{
-        byte_index_from_char_index, ccursor_next_word, ccursor_previous_word, find_line_start,
-        slice_char_range,
+use crate::{
+    style::Interaction,
+    text_selection::{
+        text_cursor_state::{
+            byte_index_from_char_index, ccursor_next_word, ccursor_previous_word, find_line_start,
+            slice_char_range,
+        },
+        CursorRange,
     },
-    CursorRange,
 };
 
 /// Trait constraining what types [`crate::TextEdit`] may use as
@@ -138,13 +141,23 @@ pub trait TextBuffer {
         self.delete_selected_ccursor_range([ccursor, ccursor + 1])
     }
 
-    fn delete_previous_word(&mut self, max_ccursor: CCursor) -> CCursor {
-        let min_ccursor = ccursor_previous_word(self.as_str(), max_ccursor);
+    /// The [`Interaction`] style decides what is a word.
+    fn delete_previous_word(&mut self, max_ccursor: CCursor, interaction: &Interaction) -> CCursor {
+        let min_ccursor = ccursor_previous_word(
+            self.as_str(),
+            max_ccursor,
+            interaction.underscore_is_word_char,
+        );
         self.delete_selected_ccursor_range([min_ccursor, max_ccursor])
     }
 
-    fn delete_next_word(&mut self, min_ccursor: CCursor) -> CCursor {
-        let max_ccursor = ccursor_next_word(self.as_str(), min_ccursor);
+    /// The [`Interaction`] style decides what is a word.
+    fn delete_next_word(&mut self, min_ccursor: CCursor, interaction: &Interaction) -> CCursor {
+        let max_ccursor = ccursor_next_word(
+            self.as_str(),
+            min_ccursor,
+            interaction.underscore_is_word_char,
+        );
         self.delete_selected_ccursor_range([min_ccursor, max_ccursor])
     }
 
@@ -67,6 +67,68 @@ pub fn criterion_benchmark(c: &mut Criterion) {
         });
     }
 
+    {
+        // The same labels every frame should only be laid out once,
+        // so compare with labels that change every frame:
+        let ctx = egui::Context::default();
+        let run_frame = |ctx: &egui::Context, label_text: &dyn Fn(usize) -> String| {
+            ctx.run(RawInput::default(), |ctx| {
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    for i in 0..1000 {
+                        ui.label(label_text(i));
+                    }
+                });
+            })
+        };
+
+        let mut frame_nr = 0;
+        c.bench_function("1000_labels_cold", |b| {
+            b.iter(|| {
+                frame_nr += 1;
+                run_frame(&ctx, &|i| {
+                    format!("{frame_nr} {i} the quick brown fox jumps over the lazy dog")
+                })
+            });
+        });
+
+        let label_text = |_| "the quick brown fox jumps over the lazy dog".to_owned();
+        let _ = run_frame(&ctx, &label_text); // warm up the galley cache
+        c.bench_function("1000_labels_warm", |b| {
+            b.iter(|| run_frame(&ctx, &label_text));
+        });
+    }
+
+    {
+        // A huge text should only be laid out again where it changes:
+        let ctx = egui::Context::default();
+        let line = |i: usize| format!("{i:>8} the quick brown fox jumps over the lazy dog\n");
+        let line_len = line(0).len();
+        let mut text: String = (0..1_000_000 / line_len).map(line).collect();
+        let run_frame = |ctx: &egui::Context, text: &mut String| {
+            ctx.run(RawInput::default(), |ctx| {
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    egui::ScrollArea::vertical().show(ui, |ui| {
+                        ui.add(egui::TextEdit::multiline(text).desired_width(f32::INFINITY));
+                    });
+                });
+            })
+        };
+        let _ = run_frame(&ctx, &mut text); // warm up the galley cache
+        c.bench_function("text_edit_1m_chars_warm", |b| {
+            b.iter(|| run_frame(&ctx, &mut text));
+        });
+
+        let mut frame_nr = 0;
+        let edited_line = text.len() / line_len / 2 * line_len;
+        c.bench_function("text_edit_1m_chars_edit_one_line", |b| {
+            b.iter(|| {
+                frame_nr += 1;
+                text.replace_range(edited_line..edited_line + 8, &format!("{frame_nr:>8}"));
+                run_frame(&ctx, &mut text)
+            });
+        });
+    }
+
     {
         let ctx = egui::Context::default();
         ctx.begin_frame(RawInput::default());
@@ -4,6 +4,9 @@ pub struct FontBook {
     filter: String,
     font_id: egui::FontId,
     named_chars: BTreeMap<egui::FontFamily, BTreeMap<char, String>>,
+
+    /// The font whose [`egui::FontTweak`] we are editing.
+    tweaked_font: String,
 }
 
 impl Default for FontBook {
@@ -12,6 +15,7 @@ impl Default for FontBook {
             filter: Default::default(),
             font_id: egui::FontId::proportional(18.0),
             named_chars: Default::default(),
+            tweaked_font: "Ubuntu-Light".to_owned(),
         }
     }
 }
@@ -55,6 +59,10 @@ impl super::View for FontBook {
 
         ui.separator();
 
+        ui.collapsing("Font tweaks", |ui| {
+            self.font_tweak_ui(ui);
+        });
+
         egui::introspection::font_id_ui(ui, &mut self.font_id);
 
         ui.horizontal(|ui| {
@@ -102,6 +110,39 @@ impl super::View for FontBook {
     }
 }
 
+impl FontBook {
+    /// Edit the [`egui::FontTweak`] of the installed fonts, with the result shown live.
+    fn font_tweak_ui(&mut self, ui: &mut egui::Ui) {
+        ui.label("Adjust the size and vertical alignment of a font, e.g. for an icon font that sits too high or low:");
+
+        let (font_names, tweak) = ui.fonts(|f| {
+            let fonts = f.lock();
+            let font_data = &fonts.fonts.definitions().font_data;
+            let font_names: Vec<String> = font_data.keys().cloned().collect();
+            let tweak = font_data.get(&self.tweaked_font).map(|data| data.tweak);
+            (font_names, tweak)
+        });
+
+        egui::ComboBox::from_label("Font")
+            .selected_text(&self.tweaked_font)
+            .show_ui(ui, |ui| {
+                for name in font_names {
+                    ui.selectable_value(&mut self.tweaked_font, name.clone(), name);
+                }
+            });
+
+        if let Some(mut tweak) = tweak {
+            if ui.add(&mut tweak).changed() {
+                let mut definitions = ui.fonts(|f| f.lock().fonts.definitions().clone());
+                if let Some(font_data) = definitions.font_data.get_mut(&self.tweaked_font) {
+                    font_data.tweak = tweak;
+                }
+                ui.ctx().set_fonts(definitions);
+            }
+        }
+    }
+}
+
 fn available_characters(ui: &egui::Ui, family: egui::FontFamily) -> BTreeMap<char, String> {
     ui.fonts(|f| {
         f.lock()
@@ -13,6 +13,9 @@ pub struct MiscDemoWindow {
     tree: Tree,
     box_painting: BoxPainting,
 
+    #[cfg_attr(feature = "serde", serde(skip))]
+    button_skin: Option<TextureHandle>,
+
     dummy_bool: bool,
     dummy_usize: usize,
     checklist: [bool; 3],
@@ -29,6 +32,8 @@ impl Default for MiscDemoWindow {
             tree: Tree::demo(),
             box_painting: Default::default(),
 
+            button_skin: None,
+
             dummy_bool: false,
             dummy_usize: 0,
             checklist: std::array::from_fn(|i| i == 0),
@@ -145,6 +150,14 @@ impl View for MiscDemoWindow {
             .default_open(false)
             .show(ui, |ui| self.box_painting.ui(ui));
 
+        CollapsingHeader::new("Gradients")
+            .default_open(false)
+            .show(ui, gradients_ui);
+
+        CollapsingHeader::new("Nine-patch images")
+            .default_open(false)
+            .show(ui, |ui| nine_patch_ui(ui, &mut self.button_skin));
+
         CollapsingHeader::new("Resize")
             .default_open(false)
             .show(ui, |ui| {
@@ -192,6 +205,165 @@ impl View for MiscDemoWindow {
 
 // ----------------------------------------------------------------------------
 
+fn gradients_ui(ui: &mut Ui) {
+    ui.label("Gradient fills are painted with vertex colors, no texture needed.");
+
+    // A panel with a vertical gradient as background:
+    let (rect, _response) =
+        ui.allocate_exact_size(vec2(ui.available_width(), 48.0), Sense::hover());
+    ui.painter().rect_filled_gradient(
+        rect,
+        6.0,
+        Fill::linear(
+            rect.center_top(),
+            rect.center_bottom(),
+            Color32::from_rgb(40, 60, 120),
+            Color32::from_rgb(10, 10, 30),
+        ),
+    );
+    ui.painter().text(
+        rect.center(),
+        Align2::CENTER_CENTER,
+        "Gradient panel",
+        FontId::proportional(16.0),
+        Color32::WHITE,
+    );
+
+    ui.horizontal(|ui| {
+        // A button with a diagonal gradient that brightens on hover:
+        let (rect, response) = ui.allocate_exact_size(vec2(120.0, 32.0), Sense::click());
+        let (top, bottom) = if response.hovered() {
+            (
+                Color32::from_rgb(255, 190, 90),
+                Color32::from_rgb(230, 90, 60),
+            )
+        } else {
+            (
+                Color32::from_rgb(240, 160, 60),
+                Color32::from_rgb(200, 60, 40),
+            )
+        };
+        ui.painter().rect_filled_gradient(
+            rect,
+            8.0,
+            Fill::linear(rect.left_top(), rect.right_bottom(), top, bottom),
+        );
+        ui.painter().text(
+            rect.center(),
+            Align2::CENTER_CENTER,
+            "Gradient button",
+            FontId::proportional(14.0),
+            Color32::WHITE,
+        );
+
+        // A hexagon with a radial gradient with three stops:
+        let (rect, _response) = ui.allocate_exact_size(Vec2::splat(64.0), Sense::hover());
+        let center = rect.center();
+        let radius = rect.width() / 2.0;
+        let points = (0..6)
+            .map(|i| center + radius * Vec2::angled(i as f32 * std::f32::consts::TAU / 6.0))
+            .collect();
+        ui.painter().add(
+            epaint::PathShape::convex_polygon(points, Color32::WHITE, Stroke::NONE).with_fill(
+                Fill::RadialGradient {
+                    stops: vec![
+                        (0.0, Color32::YELLOW),
+                        (0.5, Color32::from_rgb(240, 100, 40)),
+                        (1.0, Color32::from_rgb(120, 0, 80)),
+                    ],
+                    center,
+                    radius,
+                },
+            ),
+        );
+    });
+}
+
+// ----------------------------------------------------------------------------
+
+fn nine_patch_ui(ui: &mut Ui, button_skin: &mut Option<TextureHandle>) {
+    ui.label("A small texture, sliced into nine patches so it can skin buttons of any size.");
+
+    let texture = button_skin
+        .get_or_insert_with(|| {
+            ui.ctx()
+                .load_texture("button_skin", button_skin_image(), Default::default())
+        })
+        .clone();
+    let nine_patch = NinePatch::new(texture.size_vec2(), Margin::same(1.0 / 3.0));
+
+    ui.horizontal_wrapped(|ui| {
+        for (text, width) in [("OK", 60.0), ("Stretched", 120.0), ("Small", 16.0)] {
+            skinned_button(ui, &texture, nine_patch, text, vec2(width, 32.0));
+        }
+        skinned_button(ui, &texture, nine_patch.tiled(), "Tiled", vec2(120.0, 32.0));
+    });
+
+    ui.label("The texture itself:");
+    ui.image((texture.id(), texture.size_vec2()));
+}
+
+fn skinned_button(
+    ui: &mut Ui,
+    texture: &TextureHandle,
+    nine_patch: NinePatch,
+    text: &str,
+    size: Vec2,
+) -> Response {
+    let (rect, response) = ui.allocate_exact_size(size, Sense::click());
+    let tint = if response.is_pointer_button_down_on() {
+        Color32::from_gray(180)
+    } else if response.hovered() {
+        Color32::from_rgb(255, 230, 200)
+    } else {
+        Color32::WHITE
+    };
+    ui.painter()
+        .image_nine_patch(texture.id(), rect, nine_patch, tint);
+    ui.painter().text(
+        rect.center(),
+        Align2::CENTER_CENTER,
+        text,
+        FontId::proportional(14.0),
+        Color32::WHITE,
+    );
+    response
+}
+
+/// A rounded orange button with a darker rim, 24x24 pixels with 8 pixel corners.
+fn button_skin_image() -> ColorImage {
+    let size = 24;
+    let radius = 8.0;
+    let pixels = (0..size * size)
+        .map(|i| {
+            let p = vec2((i % size) as f32 + 0.5, (i / size) as f32 + 0.5);
+            // Signed distance to the edge of the rounded square:
+            let half = size as f32 / 2.0;
+            let corner = (p - Vec2::splat(half)).abs() - Vec2::splat(half - radius);
+            let distance = corner.max(Vec2::ZERO).length() + corner.max_elem().min(0.0) - radius;
+
+            let t = p.y / size as f32;
+            let fill = Color32::from_rgb(
+                lerp(240.0..=200.0, t) as u8,
+                lerp(160.0..=70.0, t) as u8,
+                lerp(60.0..=40.0, t) as u8,
+            );
+            let color = if -2.0 < distance {
+                Color32::from_rgb(110, 40, 20)
+            } else {
+                fill
+            };
+            color.gamma_multiply((0.5 - distance).clamp(0.0, 1.0))
+        })
+        .collect();
+    ColorImage {
+        size: [size, size],
+        pixels,
+    }
+}
+
+// ----------------------------------------------------------------------------
+
 fn label_ui(ui: &mut egui::Ui) {
     ui.vertical_centered(|ui| {
         ui.add(crate::egui_github_link_file_line!());
@@ -117,6 +117,10 @@ impl super::View for TextLayoutDemo {
 
         ui.add_space(12.0);
 
+        outline_and_shadow_ui(ui);
+
+        ui.add_space(12.0);
+
         let text = if *lorem_ipsum {
             crate::LOREM_IPSUM_LONG
         } else {
@@ -151,6 +155,35 @@ impl super::View for TextLayoutDemo {
     }
 }
 
+/// Text that stays readable over a busy background.
+fn outline_and_shadow_ui(ui: &mut egui::Ui) {
+    use egui::{Color32, RichText, Shadow};
+
+    let (rect, _) =
+        ui.allocate_exact_size(egui::vec2(ui.available_width(), 40.0), egui::Sense::hover());
+
+    // A horizontal gradient from dark to light:
+    let mut mesh = egui::Mesh::default();
+    mesh.colored_vertex(rect.left_top(), Color32::from_rgb(20, 30, 90));
+    mesh.colored_vertex(rect.right_top(), Color32::from_rgb(250, 220, 120));
+    mesh.colored_vertex(rect.left_bottom(), Color32::from_rgb(20, 30, 90));
+    mesh.colored_vertex(rect.right_bottom(), Color32::from_rgb(250, 220, 120));
+    mesh.add_triangle(0, 1, 2);
+    mesh.add_triangle(2, 1, 3);
+    ui.painter().add(mesh);
+
+    let text = RichText::new("Outlined text with a drop shadow")
+        .size(20.0)
+        .color(Color32::WHITE)
+        .outline(1.5, Color32::BLACK)
+        .shadow(Shadow {
+            offset: egui::vec2(2.0, 2.0),
+            color: Color32::from_black_alpha(160),
+            ..Default::default()
+        });
+    ui.put(rect, egui::Label::new(text));
+}
+
 /// Excerpt from Dolores Ibárruri's farwel speech to the International Brigades:
 const TO_BE_OR_NOT_TO_BE: &str = "Mothers! Women!\n
 When the years pass by and the wounds of war are stanched; when the memory of the sad and bloody days dissipates in a present of liberty, of peace and of wellbeing; when the rancor have died out and pride in a free country is felt equally by all Spaniards, speak to your children. Tell them of these men of the International Brigades.\n\
@@ -9,6 +9,7 @@
 /// - `file` feature: `file://` loader on non-Wasm targets
 /// - `http` feature: `http(s)://` loader
 /// - `image` feature: Loader of png, jpeg etc using the [`image`] crate
+/// - `gif` and `apng` features: Play animated GIFs and PNGs with the `image` loader
 /// - `svg` feature: `.svg` loader
 ///
 /// Calling this multiple times on the same [`egui::Context`] is safe.
@@ -50,9 +51,12 @@
 /// allowing a different loader to attempt to load the image.
 ///
 /// The `svg` loader is an [`ImageLoader`][`egui::load::ImageLoader`].
-/// It will attempt to load any URI with an `svg` extension. It will _not_ attempt to load a URI without an extension.
-/// The content type specified by [`BytesPoll::Ready::mime`][`egui::load::BytesPoll::Ready::mime`] always takes precedence,
-/// and must include `svg` for it to be considered supported. For example, `image/svg+xml` would be loaded by the `svg` loader.
+/// It will attempt to load any URI with an `svg` extension.
+/// A URI without an extension is loaded if its content type specified by [`BytesPoll::Ready::mime`][`egui::load::BytesPoll::Ready::mime`]
+/// includes `svg` (e.g. `image/svg+xml`), or if its bytes look like an svg,
+/// so `egui::Image::new(include_bytes!("icon.svg"))` works.
+/// The svg is rasterized at the size it is shown at, in physical pixels,
+/// and rasterized again when that size (or `pixels_per_point`) changes by more than a little.
 ///
 /// See [`egui::load`] for more information about how loaders work.
 pub fn install_image_loaders(ctx: &egui::Context) {
@@ -1,12 +1,27 @@
 use ahash::HashMap;
 use egui::{
-    load::{BytesPoll, ImageLoadResult, ImageLoader, ImagePoll, LoadError, SizeHint},
+    load::{
+        AnimatedImage, BytesPoll, ImageLoadResult, ImageLoader, ImagePoll, LoadError, SizeHint,
+    },
     mutex::Mutex,
     ColorImage,
 };
-use std::{mem::size_of, path::Path, sync::Arc};
+use std::{mem::size_of, path::Path, sync::Arc, time::Duration};
 
-type Entry = Result<Arc<ColorImage>, String>;
+/// All frames of the image, usually just one.
+type Entry = Result<Arc<[Arc<ColorImage>]>, String>;
+
+/// At most this many bytes of decoded frames are kept for each animated image.
+///
+/// Animations that are longer than this are cut short.
+#[cfg(any(feature = "gif", feature = "apng"))]
+const MAX_ANIMATION_BYTES: usize = 128 * 1024 * 1024;
+
+/// Like browsers, show frames without a delay (or with a very short one) for a tenth of a second.
+#[cfg(any(feature = "gif", feature = "apng"))]
+const MIN_FRAME_DELAY: Duration = Duration::from_millis(20);
+#[cfg(any(feature = "gif", feature = "apng"))]
+const DEFAULT_FRAME_DELAY: Duration = Duration::from_millis(100);
 
 #[derive(Default)]
 pub struct ImageCrateLoader {
@@ -48,10 +63,29 @@ impl ImageLoader for ImageCrateLoader {
             return Err(LoadError::NotSupported);
         }
 
+        // The frames of an animated image are decoded together with the whole image:
+        if let Some((image_uri, frame_index)) = AnimatedImage::parse_frame_uri(uri) {
+            if let ImagePoll::Pending { size } = self.load(ctx, image_uri, SizeHint::default())? {
+                return Ok(ImagePoll::Pending { size });
+            }
+            return match self.cache.lock().get(image_uri) {
+                Some(Ok(frames)) => match frames.get(frame_index) {
+                    Some(image) => Ok(ImagePoll::Ready {
+                        image: image.clone(),
+                    }),
+                    None => Err(LoadError::Loading(format!("No frame {frame_index}"))),
+                },
+                Some(Err(err)) => Err(LoadError::Loading(err.clone())),
+                None => Err(LoadError::NotSupported),
+            };
+        }
+
         let mut cache = self.cache.lock();
         if let Some(entry) = cache.get(uri).cloned() {
             match entry {
-                Ok(image) => Ok(ImagePoll::Ready { image }),
+                Ok(frames) => Ok(ImagePoll::Ready {
+                    image: frames[0].clone(),
+                }),
                 Err(err) => Err(LoadError::Loading(err)),
             }
         } else {
@@ -65,11 +99,19 @@ impl ImageLoader for ImageCrateLoader {
                     }
 
                     log::trace!("started loading {uri:?}");
-                    let result = crate::image::load_image_bytes(&bytes).map(Arc::new);
+                    let result = load_frames(uri, &bytes);
                     log::trace!("finished loading {uri:?}");
+                    let result = result.map(|(frames, frame_delays)| {
+                        if 1 < frames.len() {
+                            AnimatedImage::new(frame_delays).insert(ctx, uri);
+                        }
+                        frames
+                    });
                     cache.insert(uri.into(), result.clone());
                     match result {
-                        Ok(image) => Ok(ImagePoll::Ready { image }),
+                        Ok(frames) => Ok(ImagePoll::Ready {
+                            image: frames[0].clone(),
+                        }),
                         Err(err) => Err(LoadError::Loading(err)),
                     }
                 }
@@ -80,7 +122,11 @@ impl ImageLoader for ImageCrateLoader {
     }
 
     fn forget(&self, uri: &str) {
-        let _ = self.cache.lock().remove(uri);
+        // All frames of an animated image are cached together with the image,
+        // so forgetting any of its frame uris forgets the whole image.
+        // `Context::forget_image` forgets the frame uris along with the image.
+        let image_uri = AnimatedImage::parse_frame_uri(uri).map_or(uri, |(image_uri, _)| image_uri);
+        let _ = self.cache.lock().remove(image_uri);
     }
 
     fn forget_all(&self) {
@@ -92,13 +138,93 @@ impl ImageLoader for ImageCrateLoader {
             .lock()
             .values()
             .map(|result| match result {
-                Ok(image) => image.pixels.len() * size_of::<egui::Color32>(),
+                Ok(frames) => frames
+                    .iter()
+                    .map(|image| image.pixels.len() * size_of::<egui::Color32>())
+                    .sum(),
                 Err(err) => err.len(),
             })
             .sum()
     }
 }
 
+/// Decode all frames of an animated GIF or APNG (if those features are enabled),
+/// or the only frame of any other image.
+fn load_frames(uri: &str, bytes: &[u8]) -> Result<(Arc<[Arc<ColorImage>]>, Vec<Duration>), String> {
+    #[cfg(any(feature = "gif", feature = "apng"))]
+    {
+        use image::AnimationDecoder as _;
+
+        let format = image::guess_format(bytes).map_err(|err| err.to_string())?;
+        let reader = std::io::Cursor::new(bytes);
+        #[cfg(feature = "gif")]
+        if format == image::ImageFormat::Gif {
+            let decoder =
+                image::codecs::gif::GifDecoder::new(reader).map_err(|err| err.to_string())?;
+            return load_animation_frames(uri, decoder.into_frames(), MAX_ANIMATION_BYTES);
+        }
+        #[cfg(feature = "apng")]
+        if format == image::ImageFormat::Png {
+            let decoder =
+                image::codecs::png::PngDecoder::new(reader).map_err(|err| err.to_string())?;
+            if decoder.is_apng().map_err(|err| err.to_string())? {
+                let decoder = decoder.apng().map_err(|err| err.to_string())?;
+                return load_animation_frames(uri, decoder.into_frames(), MAX_ANIMATION_BYTES);
+            }
+        }
+    }
+
+    let _ = uri;
+    let image = crate::image::load_image_bytes(bytes)?;
+    Ok((Arc::new([Arc::new(image)]), vec![]))
+}
+
+/// Decode the frames, stopping before they take up more than `max_bytes`,
+/// but keeping at least the first frame.
+#[cfg(any(feature = "gif", feature = "apng"))]
+fn load_animation_frames(
+    uri: &str,
+    frames: image::Frames<'_>,
+    max_bytes: usize,
+) -> Result<(Arc<[Arc<ColorImage>]>, Vec<Duration>), String> {
+    crate::profile_function!();
+
+    let mut images = vec![];
+    let mut frame_delays = vec![];
+    let mut num_bytes = 0;
+    for frame in frames {
+        let frame = frame.map_err(|err| err.to_string())?;
+        let delay = Duration::from(frame.delay());
+        let buffer = frame.into_buffer();
+        let size = [buffer.width() as usize, buffer.height() as usize];
+
+        num_bytes += size[0] * size[1] * size_of::<egui::Color32>();
+        if !images.is_empty() && max_bytes < num_bytes {
+            log::warn!(
+                "{uri:?} is too large to animate, only showing its first {} frames",
+                images.len()
+            );
+            break;
+        }
+
+        images.push(Arc::new(ColorImage::from_rgba_unmultiplied(
+            size,
+            buffer.as_raw(),
+        )));
+        frame_delays.push(if delay < MIN_FRAME_DELAY {
+            DEFAULT_FRAME_DELAY
+        } else {
+            delay
+        });
+    }
+
+    if images.is_empty() {
+        Err("The animation has no frames".to_owned())
+    } else {
+        Ok((images.into(), frame_delays))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -112,4 +238,73 @@ mod tests {
         assert!(is_supported_uri("file://test"));
         assert!(!is_supported_uri("test.svg"));
     }
+
+    #[cfg(any(feature = "gif", feature = "apng"))]
+    fn frame(color: [u8; 4], delay_ms: u32) -> image::Frame {
+        image::Frame::from_parts(
+            image::RgbaImage::from_pixel(4, 2, image::Rgba(color)),
+            0,
+            0,
+            image::Delay::from_numer_denom_ms(delay_ms, 1),
+        )
+    }
+
+    #[cfg(any(feature = "gif", feature = "apng"))]
+    #[test]
+    fn animation_frames() {
+        let frames = || {
+            let frames = [
+                frame([255, 0, 0, 255], 50),
+                frame([0, 255, 0, 255], 0),
+                frame([0, 0, 255, 255], 200),
+            ];
+            image::Frames::new(Box::new(frames.into_iter().map(image::ImageResult::Ok)))
+        };
+        let frame_bytes = 4 * 2 * size_of::<egui::Color32>();
+
+        let (images, delays) =
+            load_animation_frames("test", frames(), MAX_ANIMATION_BYTES).unwrap();
+        assert_eq!(images.len(), 3);
+        assert_eq!(images[1].size, [4, 2]);
+        assert_eq!(images[1].pixels[0], egui::Color32::GREEN);
+        // Frames without a delay are shown for the default delay, like in browsers:
+        assert_eq!(
+            delays,
+            [
+                Duration::from_millis(50),
+                DEFAULT_FRAME_DELAY,
+                Duration::from_millis(200)
+            ]
+        );
+
+        // Animations that would take up more than the limit are cut short:
+        let (images, delays) = load_animation_frames("test", frames(), 2 * frame_bytes).unwrap();
+        assert_eq!((images.len(), delays.len()), (2, 2));
+        let (images, _) = load_animation_frames("test", frames(), 2 * frame_bytes - 1).unwrap();
+        assert_eq!(images.len(), 1);
+
+        // …but the first frame is always kept:
+        let (images, _) = load_animation_frames("test", frames(), 0).unwrap();
+        assert_eq!(images.len(), 1);
+
+        let no_frames = image::Frames::new(Box::new(std::iter::empty::<image::ImageResult<_>>()));
+        assert!(load_animation_frames("test", no_frames, MAX_ANIMATION_BYTES).is_err());
+    }
+
+    #[cfg(feature = "gif")]
+    #[test]
+    fn gif_frames() {
+        let mut bytes = vec![];
+        {
+            let mut encoder = image::codecs::gif::GifEncoder::new(&mut bytes);
+            encoder
+                .encode_frames([frame([255, 0, 0, 255], 50), frame([0, 0, 255, 255], 0)])
+                .unwrap();
+        }
+
+        let (images, delays) = load_frames("test.gif", &bytes).unwrap();
+        assert_eq!(images.len(), 2);
+        assert_eq!(images[1].size, [4, 2]);
+        assert_eq!(delays, [Duration::from_millis(50), DEFAULT_FRAME_DELAY]);
+    }
 }
@@ -10,21 +10,36 @@ use egui::{
 
 type Entry = Result<Arc<ColorImage>, String>;
 
+/// Rasterizations are kept for this many sizes of each svg.
+const MAX_SIZES_PER_SVG: usize = 4;
+
+/// Rasterizes svgs at the exact pixel size asked for.
+///
+/// Rasterizations are cached by the contents of the svg and the [`SizeHint`],
+/// so the same svg under different uris is only rasterized once per size.
 #[derive(Default)]
 pub struct SvgLoader {
-    cache: Mutex<HashMap<(String, SizeHint), Entry>>,
+    /// The hash of the svg behind each uri.
+    sources: Mutex<HashMap<String, u64>>,
+
+    /// The rasterizations of each svg, least recently used first.
+    cache: Mutex<HashMap<u64, Vec<(SizeHint, Entry)>>>,
 }
 
 impl SvgLoader {
     pub const ID: &'static str = egui::generate_loader_id!(SvgLoader);
 }
 
-fn is_supported(uri: &str) -> bool {
-    let Some(ext) = Path::new(uri).extension().and_then(|ext| ext.to_str()) else {
-        return false;
-    };
+/// `None` if we have to look at the bytes to tell.
+fn is_supported(uri: &str) -> Option<bool> {
+    let ext = Path::new(uri).extension().and_then(|ext| ext.to_str())?;
+    Some(ext == "svg")
+}
 
-    ext == "svg"
+/// For uris without an extension, e.g. `include_bytes!` given directly to [`egui::Image::new`].
+fn looks_like_svg(bytes: &[u8]) -> bool {
+    let start = &bytes[..bytes.len().min(1024)];
+    String::from_utf8_lossy(start).contains("<svg")
 }
 
 impl ImageLoader for SvgLoader {
@@ -33,43 +48,74 @@ impl ImageLoader for SvgLoader {
     }
 
     fn load(&self, ctx: &egui::Context, uri: &str, size_hint: SizeHint) -> ImageLoadResult {
-        if !is_supported(uri) {
+        if is_supported(uri) == Some(false) {
             return Err(LoadError::NotSupported);
         }
 
-        let uri = uri.to_owned();
+        let source = self.sources.lock().get(uri).copied();
+        let source = match source {
+            Some(source) => source,
+            None => match ctx.try_load_bytes(uri) {
+                Ok(BytesPoll::Ready { bytes, mime, .. }) => {
+                    let is_svg = is_supported(uri) == Some(true)
+                        || mime.is_some_and(|mime| mime.contains("svg"))
+                        || looks_like_svg(&bytes);
+                    if !is_svg {
+                        return Err(LoadError::NotSupported);
+                    }
+                    let source = egui::util::hash(&bytes[..]);
+                    self.sources.lock().insert(uri.to_owned(), source);
+                    source
+                }
+                Ok(BytesPoll::Pending { size }) => return Ok(ImagePoll::Pending { size }),
+                Err(err) => return Err(err),
+            },
+        };
 
         let mut cache = self.cache.lock();
-        // We can't avoid the `uri` clone here without unsafe code.
-        if let Some(entry) = cache.get(&(uri.clone(), size_hint)).cloned() {
-            match entry {
-                Ok(image) => Ok(ImagePoll::Ready { image }),
-                Err(err) => Err(LoadError::Loading(err)),
-            }
+        let rasterizations = cache.entry(source).or_default();
+        if let Some(index) = rasterizations
+            .iter()
+            .position(|(hint, _)| *hint == size_hint)
+        {
+            let entry = rasterizations.remove(index);
+            rasterizations.push(entry);
         } else {
-            match ctx.try_load_bytes(&uri) {
-                Ok(BytesPoll::Ready { bytes, .. }) => {
-                    log::trace!("started loading {uri:?}");
-                    let result = crate::image::load_svg_bytes_with_size(&bytes, Some(size_hint))
-                        .map(Arc::new);
-                    log::trace!("finished loading {uri:?}");
-                    cache.insert((uri, size_hint), result.clone());
-                    match result {
-                        Ok(image) => Ok(ImagePoll::Ready { image }),
-                        Err(err) => Err(LoadError::Loading(err)),
-                    }
-                }
-                Ok(BytesPoll::Pending { size }) => Ok(ImagePoll::Pending { size }),
-                Err(err) => Err(err),
+            let bytes = match ctx.try_load_bytes(uri) {
+                Ok(BytesPoll::Ready { bytes, .. }) => bytes,
+                Ok(BytesPoll::Pending { size }) => return Ok(ImagePoll::Pending { size }),
+                Err(err) => return Err(err),
+            };
+            log::trace!("started loading {uri:?}");
+            let result =
+                crate::image::load_svg_bytes_with_size(&bytes, Some(size_hint)).map(Arc::new);
+            log::trace!("finished loading {uri:?}");
+            rasterizations.push((size_hint, result));
+            if MAX_SIZES_PER_SVG < rasterizations.len() {
+                let _ = rasterizations.remove(0);
             }
         }
+
+        match &rasterizations[rasterizations.len() - 1] {
+            (_, Ok(image)) => Ok(ImagePoll::Ready {
+                image: image.clone(),
+            }),
+            (_, Err(err)) => Err(LoadError::Loading(err.clone())),
+        }
     }
 
     fn forget(&self, uri: &str) {
-        self.cache.lock().retain(|(u, _), _| u != uri);
+        let mut sources = self.sources.lock();
+        if let Some(source) = sources.remove(uri) {
+            // Other uris may still use the same svg.
+            if !sources.values().any(|&other| other == source) {
+                self.cache.lock().remove(&source);
+            }
+        }
     }
 
     fn forget_all(&self) {
+        self.sources.lock().clear();
         self.cache.lock().clear();
     }
 
@@ -77,7 +123,8 @@ impl ImageLoader for SvgLoader {
         self.cache
             .lock()
             .values()
-            .map(|result| match result {
+            .flatten()
+            .map(|(_, result)| match result {
                 Ok(image) => image.pixels.len() * size_of::<egui::Color32>(),
                 Err(err) => err.len(),
             })
@@ -92,11 +139,16 @@ mod tests {
     #[test]
     fn check_support() {
         // inverse of same test in `image_loader.rs`
-        assert!(!is_supported("https://test.png"));
-        assert!(!is_supported("test.jpeg"));
-        assert!(!is_supported("http://test.gif"));
-        assert!(!is_supported("test.webp"));
-        assert!(!is_supported("file://test"));
-        assert!(is_supported("test.svg"));
+        assert_eq!(is_supported("https://test.png"), Some(false));
+        assert_eq!(is_supported("test.jpeg"), Some(false));
+        assert_eq!(is_supported("http://test.gif"), Some(false));
+        assert_eq!(is_supported("test.webp"), Some(false));
+        assert_eq!(is_supported("file://test"), None);
+        assert_eq!(is_supported("test.svg"), Some(true));
+
+        assert!(looks_like_svg(
+            br#"<?xml version="1.0"?><svg xmlns="http://www.w3.org/2000/svg"/>"#
+        ));
+        assert!(!looks_like_svg(b"\x89PNG\r\n\x1a\n"));
     }
 }
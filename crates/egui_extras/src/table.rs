@@ -773,7 +773,8 @@ impl<'a> Table<'a> {
                     ui.visuals().widgets.noninteractive.bg_stroke
                 };
 
-                ui.painter().line_segment([p0, p1], stroke);
+                ui.painter()
+                    .add(egui::Shape::line_segment_crisp([p0, p1], stroke));
             };
 
             available_width -= *column_width + spacing_x;
@@ -1,11 +1,14 @@
 #![allow(clippy::collapsible_else_if)]
 #![allow(unsafe_code)]
 
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
 
 use egui::{
-    emath::Rect,
-    epaint::{Mesh, PaintCallbackInfo, Primitive, Vertex},
+    emath::{pos2, Rect},
+    epaint::{Backdrop, Color32, Mesh, PaintCallbackInfo, Primitive, Vertex},
 };
 use glow::HasContext as _;
 use memoffset::offset_of;
@@ -20,6 +23,11 @@ pub use glow::Context;
 
 const VERT_SRC: &str = include_str!("shader/vertex.glsl");
 const FRAG_SRC: &str = include_str!("shader/fragment.glsl");
+const BLUR_FRAG_SRC: &str = include_str!("shader/blur_fragment.glsl");
+
+/// How many samples a blur pass takes on each side of the center at most,
+/// like `MAX_BLUR_SAMPLES` in `blur_fragment.glsl`.
+const MAX_BLUR_SAMPLES: f32 = 32.0;
 
 trait TextureFilterExt {
     fn glow_code(&self) -> u32;
@@ -83,6 +91,7 @@ pub struct Painter {
     program: glow::Program,
     u_screen_size: glow::UniformLocation,
     u_sampler: glow::UniformLocation,
+    u_sdf: glow::UniformLocation,
     is_webgl_1: bool,
     vao: crate::vao::VertexArrayObject,
     srgb_textures: bool,
@@ -92,15 +101,52 @@ pub struct Painter {
 
     textures: HashMap<egui::TextureId, glow::Texture>,
 
+    /// The textures that are signed distance fields, see [`egui::TextureOptions::sdf`].
+    sdf_textures: HashSet<egui::TextureId>,
+
     next_native_tex_id: u64,
 
     /// Stores outdated OpenGL textures that are yet to be deleted
     textures_to_destroy: Vec<glow::Texture>,
 
+    /// Blurs what has been painted behind a [`Primitive::Backdrop`].
+    blur_program: BlurProgram,
+
+    /// Created when the first [`Primitive::Backdrop`] is painted,
+    /// and recreated when the screen changes size.
+    backdrop_textures: Option<BackdropTextures>,
+
     /// Used to make sure we are destroyed correctly.
     destroyed: bool,
 }
 
+/// The program of the two blur passes of a [`Primitive::Backdrop`].
+///
+/// It shares the vertex shader and the vertex layout with the main program.
+struct BlurProgram {
+    program: glow::Program,
+    u_screen_size: glow::UniformLocation,
+    u_sampler: glow::UniformLocation,
+    u_step: glow::UniformLocation,
+    u_sigma: glow::UniformLocation,
+    u_tc_bounds: glow::UniformLocation,
+}
+
+/// The textures used to blur a [`Primitive::Backdrop`], as large as the screen.
+#[derive(Clone, Copy)]
+struct BackdropTextures {
+    size: [u32; 2],
+
+    /// What has been painted behind the backdrop is copied into here…
+    copy: glow::Texture,
+
+    /// …and then blurred horizontally into here.
+    blurred: glow::Texture,
+
+    /// Renders into [`Self::blurred`].
+    framebuffer: glow::Framebuffer,
+}
+
 /// A callback function that can be used to compose an [`egui::PaintCallback`] for custom rendering
 /// with [`glow`].
 ///
@@ -206,12 +252,11 @@ impl Painter {
                 ),
             )?;
             let program = link_program(&gl, [vert, frag].iter())?;
-            gl.detach_shader(program, vert);
             gl.detach_shader(program, frag);
-            gl.delete_shader(vert);
             gl.delete_shader(frag);
             let u_screen_size = gl.get_uniform_location(program, "u_screen_size").unwrap();
             let u_sampler = gl.get_uniform_location(program, "u_sampler").unwrap();
+            let u_sdf = gl.get_uniform_location(program, "u_sdf").unwrap();
 
             let vbo = gl.create_buffer()?;
 
@@ -219,6 +264,47 @@ impl Painter {
             let a_tc_loc = gl.get_attrib_location(program, "a_tc").unwrap();
             let a_srgba_loc = gl.get_attrib_location(program, "a_srgba").unwrap();
 
+            let blur_frag = compile_shader(
+                &gl,
+                glow::FRAGMENT_SHADER,
+                &format!(
+                    "{}\n#define NEW_SHADER_INTERFACE {}\n{}\n{}",
+                    shader_version_declaration,
+                    shader_version.is_new_shader_interface() as i32,
+                    shader_prefix,
+                    BLUR_FRAG_SRC
+                ),
+            )?;
+            let blur_program = gl.create_program()?;
+            gl.attach_shader(blur_program, vert);
+            gl.attach_shader(blur_program, blur_frag);
+            // So that the vertex array object works for both programs:
+            gl.bind_attrib_location(blur_program, a_pos_loc, "a_pos");
+            gl.bind_attrib_location(blur_program, a_tc_loc, "a_tc");
+            gl.bind_attrib_location(blur_program, a_srgba_loc, "a_srgba");
+            gl.link_program(blur_program);
+            if !gl.get_program_link_status(blur_program) {
+                return Err(gl.get_program_info_log(blur_program).into());
+            }
+            for p in [program, blur_program] {
+                gl.detach_shader(p, vert);
+            }
+            gl.detach_shader(blur_program, blur_frag);
+            gl.delete_shader(vert);
+            gl.delete_shader(blur_frag);
+            let blur_program = BlurProgram {
+                program: blur_program,
+                u_screen_size: gl
+                    .get_uniform_location(blur_program, "u_screen_size")
+                    .unwrap(),
+                u_sampler: gl.get_uniform_location(blur_program, "u_sampler").unwrap(),
+                u_step: gl.get_uniform_location(blur_program, "u_step").unwrap(),
+                u_sigma: gl.get_uniform_location(blur_program, "u_sigma").unwrap(),
+                u_tc_bounds: gl
+                    .get_uniform_location(blur_program, "u_tc_bounds")
+                    .unwrap(),
+            };
+
             let stride = std::mem::size_of::<Vertex>() as i32;
             let buffer_infos = vec![
                 vao::BufferInfo {
@@ -258,6 +344,7 @@ impl Painter {
                 program,
                 u_screen_size,
                 u_sampler,
+                u_sdf,
                 is_webgl_1,
                 vao,
                 srgb_textures,
@@ -265,8 +352,11 @@ impl Painter {
                 vbo,
                 element_array_buffer,
                 textures: Default::default(),
+                sdf_textures: Default::default(),
                 next_native_tex_id: 1 << 32,
                 textures_to_destroy: Vec::new(),
+                blur_program,
+                backdrop_textures: None,
                 destroyed: false,
             })
         }
@@ -407,6 +497,7 @@ impl Painter {
         for egui::ClippedPrimitive {
             clip_rect,
             primitive,
+            ..
         } in clipped_primitives
         {
             set_clip_rect(&self.gl, screen_size_px, pixels_per_point, *clip_rect);
@@ -415,6 +506,9 @@ impl Painter {
                 Primitive::Mesh(mesh) => {
                     self.paint_mesh(mesh);
                 }
+                Primitive::Backdrop(backdrop) => {
+                    self.paint_backdrop(screen_size_px, pixels_per_point, *clip_rect, backdrop);
+                }
                 Primitive::Callback(callback) => {
                     if callback.rect.is_positive() {
                         crate::profile_scope!("callback");
@@ -463,39 +557,211 @@ impl Painter {
 
     #[inline(never)] // Easier profiling
     fn paint_mesh(&mut self, mesh: &Mesh) {
-        debug_assert!(mesh.is_valid());
         if let Some(texture) = self.texture(mesh.texture_id) {
+            let sdf = self.sdf_textures.contains(&mesh.texture_id);
             unsafe {
-                self.gl.bind_buffer(glow::ARRAY_BUFFER, Some(self.vbo));
-                self.gl.buffer_data_u8_slice(
-                    glow::ARRAY_BUFFER,
-                    bytemuck::cast_slice(&mesh.vertices),
-                    glow::STREAM_DRAW,
-                );
-
-                self.gl
-                    .bind_buffer(glow::ELEMENT_ARRAY_BUFFER, Some(self.element_array_buffer));
-                self.gl.buffer_data_u8_slice(
-                    glow::ELEMENT_ARRAY_BUFFER,
-                    bytemuck::cast_slice(&mesh.indices),
-                    glow::STREAM_DRAW,
-                );
-
                 self.gl.bind_texture(glow::TEXTURE_2D, Some(texture));
+                self.gl.uniform_1_i32(Some(&self.u_sdf), sdf as i32);
             }
+            self.draw_mesh(mesh);
+        } else {
+            log::warn!("Failed to find texture {:?}", mesh.texture_id);
+        }
+    }
 
-            unsafe {
-                self.gl.draw_elements(
-                    glow::TRIANGLES,
-                    mesh.indices.len() as i32,
-                    glow::UNSIGNED_INT,
-                    0,
-                );
+    /// Draw the mesh with the program and texture that are bound.
+    fn draw_mesh(&mut self, mesh: &Mesh) {
+        debug_assert!(mesh.is_valid());
+        unsafe {
+            self.gl.bind_buffer(glow::ARRAY_BUFFER, Some(self.vbo));
+            self.gl.buffer_data_u8_slice(
+                glow::ARRAY_BUFFER,
+                bytemuck::cast_slice(&mesh.vertices),
+                glow::STREAM_DRAW,
+            );
+
+            self.gl
+                .bind_buffer(glow::ELEMENT_ARRAY_BUFFER, Some(self.element_array_buffer));
+            self.gl.buffer_data_u8_slice(
+                glow::ELEMENT_ARRAY_BUFFER,
+                bytemuck::cast_slice(&mesh.indices),
+                glow::STREAM_DRAW,
+            );
+        }
+
+        unsafe {
+            self.gl.draw_elements(
+                glow::TRIANGLES,
+                mesh.indices.len() as i32,
+                glow::UNSIGNED_INT,
+                0,
+            );
+        }
+
+        check_for_gl_error!(&self.gl, "paint_mesh");
+    }
+
+    /// Blur what has been painted behind the backdrop, and paint it in the backdrop mesh.
+    ///
+    /// Like in `egui-wgpu`, the region behind the backdrop is copied out of the framebuffer,
+    /// blurred horizontally into another texture,
+    /// and then blurred vertically while painting the backdrop mesh.
+    #[inline(never)] // Easier profiling
+    fn paint_backdrop(
+        &mut self,
+        screen_size_px: [u32; 2],
+        pixels_per_point: f32,
+        clip_rect: Rect,
+        backdrop: &Backdrop,
+    ) {
+        crate::profile_function!();
+
+        let Backdrop {
+            rect,
+            blur_radius,
+            mesh,
+        } = backdrop;
+        let [width_px, height_px] = screen_size_px;
+
+        // The blur reaches about three standard deviations into the rect:
+        let sigma_px = blur_radius * pixels_per_point;
+        let margin_px = (3.0 * sigma_px).ceil();
+        let region_min_x = ((rect.min.x * pixels_per_point).floor() - margin_px).max(0.0) as i32;
+        let region_min_y = ((rect.min.y * pixels_per_point).floor() - margin_px).max(0.0) as i32;
+        let region_max_x =
+            ((rect.max.x * pixels_per_point).ceil() + margin_px).min(width_px as f32) as i32;
+        let region_max_y =
+            ((rect.max.y * pixels_per_point).ceil() + margin_px).min(height_px as f32) as i32;
+        if region_max_x <= region_min_x || region_max_y <= region_min_y {
+            return;
+        }
+        let [w, h] = [region_max_x - region_min_x, region_max_y - region_min_y];
+        // OpenGL counts rows from the bottom:
+        let [x, y] = [region_min_x, height_px as i32 - region_max_y];
+
+        let Some(BackdropTextures {
+            copy,
+            blurred,
+            framebuffer,
+            ..
+        }) = self.backdrop_textures(screen_size_px)
+        else {
+            return;
+        };
+
+        // Spread out the samples of wide blurs:
+        let sample_spacing = (3.0 * sigma_px / MAX_BLUR_SAMPLES).max(1.0);
+        let sigma = sigma_px / sample_spacing;
+        let [width, height] = [width_px as f32, height_px as f32];
+        let tc_bounds = [
+            (x as f32 + 0.5) / width,
+            (y as f32 + 0.5) / height,
+            ((x + w) as f32 - 0.5) / width,
+            ((y + h) as f32 - 0.5) / height,
+        ];
+
+        unsafe {
+            // The textures are as large as the screen, so the region stays where it is:
+            self.gl.bind_texture(glow::TEXTURE_2D, Some(copy));
+            self.gl
+                .copy_tex_sub_image_2d(glow::TEXTURE_2D, 0, x, y, x, y, w, h);
+            check_for_gl_error!(&self.gl, "copy_tex_sub_image_2d");
+
+            // Horizontally into `blurred`:
+            self.gl
+                .bind_framebuffer(glow::FRAMEBUFFER, Some(framebuffer));
+            self.gl.scissor(x, y, w, h);
+            self.gl.disable(glow::BLEND);
+            self.use_blur_program(
+                [width, height],
+                [sample_spacing / width, 0.0],
+                sigma,
+                tc_bounds,
+            );
+        }
+        // Covers the whole screen, in pixels. Texture coordinates start at the bottom:
+        let mut quad = Mesh::default();
+        quad.add_rect_with_uv(
+            Rect::from_min_max(pos2(0.0, 0.0), pos2(width, height)),
+            Rect::from_min_max(pos2(0.0, 1.0), pos2(1.0, 0.0)),
+            Color32::WHITE,
+        );
+        self.draw_mesh(&quad);
+
+        // …and vertically while painting the backdrop mesh:
+        unsafe {
+            self.gl
+                .bind_framebuffer(glow::FRAMEBUFFER, self.intermediate_fbo());
+            set_clip_rect(&self.gl, screen_size_px, pixels_per_point, clip_rect);
+            self.gl.enable(glow::BLEND);
+            self.gl.bind_texture(glow::TEXTURE_2D, Some(blurred));
+            self.use_blur_program(
+                [width / pixels_per_point, height / pixels_per_point],
+                [0.0, sample_spacing / height],
+                sigma,
+                tc_bounds,
+            );
+        }
+        let mut mesh = mesh.clone();
+        for vertex in &mut mesh.vertices {
+            vertex.uv = pos2(
+                vertex.pos.x * pixels_per_point / width,
+                1.0 - vertex.pos.y * pixels_per_point / height,
+            );
+        }
+        self.draw_mesh(&mesh);
+
+        // Restore state:
+        unsafe { self.prepare_painting(screen_size_px, pixels_per_point) };
+    }
+
+    unsafe fn use_blur_program(
+        &self,
+        [width, height]: [f32; 2],
+        [step_x, step_y]: [f32; 2],
+        sigma: f32,
+        [min_x, min_y, max_x, max_y]: [f32; 4],
+    ) {
+        let blur = &self.blur_program;
+        unsafe {
+            self.gl.use_program(Some(blur.program));
+            self.gl
+                .uniform_2_f32(Some(&blur.u_screen_size), width, height);
+            self.gl.uniform_1_i32(Some(&blur.u_sampler), 0);
+            self.gl.uniform_2_f32(Some(&blur.u_step), step_x, step_y);
+            self.gl.uniform_1_f32(Some(&blur.u_sigma), sigma);
+            self.gl
+                .uniform_4_f32(Some(&blur.u_tc_bounds), min_x, min_y, max_x, max_y);
+        }
+    }
+
+    /// The textures for blurring backdrops, (re)created to match the screen size.
+    fn backdrop_textures(&mut self, size: [u32; 2]) -> Option<BackdropTextures> {
+        if let Some(textures) = self.backdrop_textures {
+            if textures.size == size {
+                return Some(textures);
             }
+            unsafe { textures.destroy(&self.gl) };
+            self.backdrop_textures = None;
+        }
+        if self.max_texture_side < size[0].max(size[1]) as usize {
+            return None;
+        }
 
-            check_for_gl_error!(&self.gl, "paint_mesh");
-        } else {
-            log::warn!("Failed to find texture {:?}", mesh.texture_id);
+        let textures = unsafe { BackdropTextures::new(&self.gl, size, self.is_webgl_1) };
+        unsafe {
+            self.gl
+                .bind_framebuffer(glow::FRAMEBUFFER, self.intermediate_fbo());
+        }
+        match textures {
+            Ok(textures) => {
+                self.backdrop_textures = Some(textures);
+                Some(textures)
+            }
+            Err(err) => {
+                log::warn!("Failed to create the textures for blurring backdrops: {err}");
+                None
+            }
         }
     }
 
@@ -514,6 +780,12 @@ impl Painter {
             self.gl.bind_texture(glow::TEXTURE_2D, Some(glow_texture));
         }
 
+        if delta.options.sdf {
+            self.sdf_textures.insert(tex_id);
+        } else {
+            self.sdf_textures.remove(&tex_id);
+        }
+
         match &delta.image {
             egui::ImageData::Color(image) => {
                 assert_eq!(
@@ -640,6 +912,7 @@ impl Painter {
         if let Some(old_tex) = self.textures.remove(&tex_id) {
             unsafe { self.gl.delete_texture(old_tex) };
         }
+        self.sdf_textures.remove(&tex_id);
     }
 
     /// Get the [`glow::Texture`] bound to a [`egui::TextureId`].
@@ -717,6 +990,10 @@ impl Painter {
             for t in &self.textures_to_destroy {
                 self.gl.delete_texture(*t);
             }
+            self.gl.delete_program(self.blur_program.program);
+            if let Some(textures) = self.backdrop_textures {
+                textures.destroy(&self.gl);
+            }
         }
     }
 
@@ -767,6 +1044,77 @@ impl Drop for Painter {
     }
 }
 
+impl BackdropTextures {
+    /// Leaves [`Self::framebuffer`] bound.
+    unsafe fn new(gl: &glow::Context, size: [u32; 2], is_webgl_1: bool) -> Result<Self, String> {
+        unsafe {
+            let textures = Self {
+                size,
+                copy: create_backdrop_texture(gl, size, is_webgl_1)?,
+                blurred: create_backdrop_texture(gl, size, is_webgl_1)?,
+                framebuffer: gl.create_framebuffer()?,
+            };
+            gl.bind_framebuffer(glow::FRAMEBUFFER, Some(textures.framebuffer));
+            gl.framebuffer_texture_2d(
+                glow::FRAMEBUFFER,
+                glow::COLOR_ATTACHMENT0,
+                glow::TEXTURE_2D,
+                Some(textures.blurred),
+                0,
+            );
+            let status = gl.check_framebuffer_status(glow::FRAMEBUFFER);
+            if status == glow::FRAMEBUFFER_COMPLETE {
+                Ok(textures)
+            } else {
+                textures.destroy(gl);
+                Err(format!("incomplete framebuffer: {status:#x}"))
+            }
+        }
+    }
+
+    unsafe fn destroy(&self, gl: &glow::Context) {
+        unsafe {
+            gl.delete_framebuffer(self.framebuffer);
+            gl.delete_texture(self.copy);
+            gl.delete_texture(self.blurred);
+        }
+    }
+}
+
+/// Not sRGB, so that the blur happens in gamma space, like the rest of the blending.
+unsafe fn create_backdrop_texture(
+    gl: &glow::Context,
+    [width, height]: [u32; 2],
+    is_webgl_1: bool,
+) -> Result<glow::Texture, String> {
+    unsafe {
+        let texture = gl.create_texture()?;
+        gl.bind_texture(glow::TEXTURE_2D, Some(texture));
+        for (parameter, value) in [
+            (glow::TEXTURE_MAG_FILTER, glow::LINEAR),
+            (glow::TEXTURE_MIN_FILTER, glow::LINEAR),
+            (glow::TEXTURE_WRAP_S, glow::CLAMP_TO_EDGE),
+            (glow::TEXTURE_WRAP_T, glow::CLAMP_TO_EDGE),
+        ] {
+            gl.tex_parameter_i32(glow::TEXTURE_2D, parameter, value as i32);
+        }
+        let internal_format = if is_webgl_1 { glow::RGBA } else { glow::RGBA8 };
+        gl.tex_image_2d(
+            glow::TEXTURE_2D,
+            0,
+            internal_format as i32,
+            width as i32,
+            height as i32,
+            0,
+            glow::RGBA,
+            glow::UNSIGNED_BYTE,
+            None,
+        );
+        check_for_gl_error!(gl, "create_backdrop_texture");
+        Ok(texture)
+    }
+}
+
 fn set_clip_rect(
     gl: &glow::Context,
     [width_px, height_px]: [u32; 2],
@@ -0,0 +1,58 @@
+#ifdef GL_ES
+    // The texture coordinates need more precision than the colors, on large screens:
+    #ifdef GL_FRAGMENT_PRECISION_HIGH
+        precision highp float;
+    #else
+        precision mediump float;
+    #endif
+#endif
+
+// Blurs what has been painted behind an `epaint::Backdrop` in one direction,
+// like `fs_blur` and `fs_backdrop_*` in `egui-wgpu/src/egui.wgsl`.
+
+uniform sampler2D u_sampler;
+
+// The distance between two samples, in texture coordinates.
+uniform vec2 u_step;
+
+// The standard deviation of the blur, in samples.
+uniform float u_sigma;
+
+// The region copied from behind the backdrop (min.xy, max.xy), inset by half a texel.
+uniform vec4 u_tc_bounds;
+
+#if NEW_SHADER_INTERFACE
+    in vec4 v_rgba_in_gamma;
+    in vec2 v_tc;
+    out vec4 f_color;
+    // a dirty hack applied to support webGL2
+    #define gl_FragColor f_color
+    #define texture2D texture
+#else
+    varying vec4 v_rgba_in_gamma;
+    varying vec2 v_tc;
+#endif
+
+// Samples on each side of the center at most. Wider blurs spread the samples out instead.
+#define MAX_BLUR_SAMPLES 32
+
+// The backdrop textures are not sRGB, so this stays in gamma space, like the framebuffer.
+vec4 blur_sample(vec2 tc) {
+    return texture2D(u_sampler, clamp(tc, u_tc_bounds.xy, u_tc_bounds.zw));
+}
+
+void main() {
+    vec4 sum = blur_sample(v_tc);
+    float total_weight = 1.0;
+    // GLSL ES 1.0 needs a constant loop bound:
+    for (int i = 1; i <= MAX_BLUR_SAMPLES; i++) {
+        float x = float(i);
+        if (3.0 * u_sigma < x) {
+            break;
+        }
+        float weight = exp(-0.5 * x * x / (u_sigma * u_sigma));
+        sum += weight * (blur_sample(v_tc - x * u_step) + blur_sample(v_tc + x * u_step));
+        total_weight += 2.0 * weight;
+    }
+    gl_FragColor = v_rgba_in_gamma * (sum / total_weight);
+}
@@ -4,6 +4,9 @@
 
 uniform sampler2D u_sampler;
 
+// Is the texture a signed distance field (see `TextureOptions::sdf`)?
+uniform bool u_sdf;
+
 #if NEW_SHADER_INTERFACE
     in vec4 v_rgba_in_gamma;
     in vec2 v_tc;
@@ -36,6 +39,18 @@ void main() {
     vec4 texture_in_gamma = texture2D(u_sampler, v_tc);
 #endif
 
+    if (u_sdf) {
+        // The alpha is the distance to the outline, which is at 0.5.
+        float distance = texture_in_gamma.a;
+#if defined(GL_ES) && __VERSION__ < 300
+        float smoothing = 0.1; // No derivatives in GLSL ES 1.00
+#else
+        float smoothing = 0.5 * fwidth(distance); // ≈ half a pixel
+#endif
+        float alpha = smoothstep(0.5 - smoothing, 0.5 + smoothing, distance);
+        texture_in_gamma = vec4(alpha); // premultiplied white
+    }
+
     // We multiply the colors in gamma space, because that's the only way to get text to look right.
     gl_FragColor = v_rgba_in_gamma * texture_in_gamma;
 }
@@ -1072,6 +1072,7 @@ impl PlotItem for Points {
                             radius,
                             fill,
                             stroke,
+                            stroke_pattern: Default::default(),
                         }));
                     }
                     MarkerShape::Diamond => {
@@ -138,6 +138,7 @@ impl LegendEntry {
             radius: icon_size * 0.5,
             fill: visuals.bg_fill,
             stroke: visuals.bg_stroke,
+            stroke_pattern: Default::default(),
         });
 
         if *checked {
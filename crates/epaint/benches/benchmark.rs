@@ -47,7 +47,11 @@ fn tessellate_circles(c: &mut Criterion) {
             for _ in 0..10_000 {
                 let clip_rect = Rect::from_min_size(Pos2::ZERO, Vec2::splat(1024.0));
                 let shape = Shape::circle_filled(Pos2::new(10.0, 10.0), r, Color32::WHITE);
-                clipped_shapes.push(ClippedShape { clip_rect, shape });
+                clipped_shapes.push(ClippedShape {
+                    clip_rect,
+                    clip_rounding: Rounding::ZERO,
+                    shape,
+                });
             }
         }
         assert_eq!(clipped_shapes.len(), 100_000);
@@ -72,6 +76,69 @@ fn tessellate_circles(c: &mut Criterion) {
     });
 }
 
+fn tessellate_rects(c: &mut Criterion) {
+    let clip_rect = Rect::from_min_size(Pos2::ZERO, Vec2::splat(1024.0));
+    let node = Rect::from_min_size(Pos2::ZERO, vec2(80.0, 40.0));
+    let node_shape = RectShape::new(
+        node,
+        6.0,
+        Color32::DARK_GRAY,
+        Stroke::new(1.0, Color32::WHITE),
+    );
+    let offsets: Vec<Vec2> = (0..5_000)
+        .map(|i| vec2((i % 100) as f32 * 10.0, (i / 100) as f32 * 20.0))
+        .collect();
+
+    let pixels_per_point = 2.0;
+    let options = TessellationOptions::default();
+    let atlas = TextureAtlas::new([4096, 256]);
+    let font_tex_size = atlas.size();
+    let prepared_discs = atlas.prepared_discs();
+    let new_tessellator = || {
+        Tessellator::new(
+            pixels_per_point,
+            options,
+            font_tex_size,
+            prepared_discs.clone(),
+        )
+    };
+    let clipped = |shape: Shape| ClippedShape {
+        clip_rect,
+        clip_rounding: Rounding::ZERO,
+        shape,
+    };
+
+    let rect_shapes: Vec<ClippedShape> = offsets
+        .iter()
+        .map(|&offset| {
+            let mut shape = Shape::Rect(node_shape.clone());
+            shape.translate(offset);
+            clipped(shape)
+        })
+        .collect();
+    c.bench_function("tessellate_rects_5k", |b| {
+        b.iter(|| {
+            let clipped_primitives = new_tessellator().tessellate_shapes(rect_shapes.clone());
+            black_box(clipped_primitives);
+        });
+    });
+
+    // The same rects, tessellated once and then instanced:
+    let mut mesh = Mesh::default();
+    new_tessellator().tessellate_shape(node_shape.clone().into(), &mut mesh);
+    let instance = InstancedMeshShape::new(std::sync::Arc::new(mesh), Vec2::ZERO);
+    let instanced_shapes: Vec<ClippedShape> = offsets
+        .iter()
+        .map(|&offset| clipped(instance.clone().with_offset(offset).into()))
+        .collect();
+    c.bench_function("tessellate_instanced_rects_5k", |b| {
+        b.iter(|| {
+            let clipped_primitives = new_tessellator().tessellate_shapes(instanced_shapes.clone());
+            black_box(clipped_primitives);
+        });
+    });
+}
+
 fn thick_line_solid(c: &mut Criterion) {
     c.bench_function("thick_solid_line", move |b| {
         let line = [pos2(0.0, 0.0), pos2(50.0, 0.0), pos2(100.0, 1.0)];
@@ -220,11 +287,65 @@ fn thin_large_line_uv(c: &mut Criterion) {
     });
 }
 
+/// Text with a lot of distinct glyphs, like a CJK document.
+///
+/// The default fonts have no CJK glyphs, so we use every character they do support instead.
+fn text_with_many_glyphs(fonts: &text::Fonts, font_id: &FontId) -> String {
+    let mut text: String = fonts
+        .lock()
+        .fonts
+        .font(font_id)
+        .characters()
+        .iter()
+        .filter(|c| !c.is_whitespace())
+        .take(10_000)
+        .collect();
+    text.insert(text.len() / 2, '\n');
+    text
+}
+
+fn first_layout(c: &mut Criterion) {
+    let pixels_per_point = 1.0;
+    let max_texture_side = 8 * 1024;
+    let font_id = FontId::proportional(14.0);
+    let new_fonts = || {
+        text::Fonts::new(
+            pixels_per_point,
+            max_texture_side,
+            text::FontDefinitions::default(),
+        )
+    };
+    let text = text_with_many_glyphs(&new_fonts(), &font_id);
+
+    let layout = |fonts: text::Fonts| {
+        fonts.begin_frame(pixels_per_point, max_texture_side);
+        let galley = fonts.layout(text.clone(), font_id.clone(), Color32::WHITE, 800.0);
+        black_box((fonts, galley));
+    };
+
+    c.bench_function("first_layout_many_glyphs", |b| {
+        b.iter_batched(new_fonts, layout, criterion::BatchSize::LargeInput);
+    });
+
+    c.bench_function("first_layout_many_glyphs_preloaded", |b| {
+        b.iter_batched(
+            || {
+                let fonts = new_fonts();
+                fonts.preload_text(&font_id, &text);
+                fonts
+            },
+            layout,
+            criterion::BatchSize::LargeInput,
+        );
+    });
+}
+
 criterion_group!(
     benches,
     single_dashed_lines,
     many_dashed_lines,
     tessellate_circles,
+    tessellate_rects,
     thick_line_solid,
     thick_large_line_solid,
     thin_line_solid,
@@ -232,6 +353,7 @@ criterion_group!(
     thick_line_uv,
     thick_large_line_uv,
     thin_line_uv,
-    thin_large_line_uv
+    thin_large_line_uv,
+    first_layout
 );
 criterion_main!(benches);
@@ -68,7 +68,7 @@ impl CubicBezierShape {
             let pathshape = PathShape {
                 points,
                 closed: self.closed,
-                fill: self.fill,
+                fill: self.fill.into(),
                 stroke: self.stroke.clone(),
             };
             pathshapes.push(pathshape);
@@ -428,7 +428,7 @@ impl QuadraticBezierShape {
         PathShape {
             points,
             closed: self.closed,
-            fill: self.fill,
+            fill: self.fill.into(),
             stroke: self.stroke.clone(),
         }
     }
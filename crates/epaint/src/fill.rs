@@ -0,0 +1,386 @@
+use ecolor::Color32;
+use emath::{Pos2, Rect, TSTransform, Vec2};
+
+use crate::{Mesh, Vertex};
+
+/// How to fill a [`crate::RectShape`] or [`crate::PathShape`]: with a solid color or a gradient.
+///
+/// The colors of a gradient are given as stops: a position from `0.0` to `1.0` along the gradient,
+/// and the color there. Colors are interpolated between the stops (in gamma space),
+/// and the first and last colors continue past the ends of the gradient.
+/// The stops should be sorted by position.
+///
+/// ```
+/// # use epaint::{pos2, Color32, Fill, Rect, RectShape};
+/// let rect = Rect::from_min_max(pos2(0.0, 0.0), pos2(100.0, 30.0));
+/// let button = RectShape::filled(rect, 4.0, Color32::WHITE).with_fill(Fill::linear(
+///     rect.center_top(),
+///     rect.center_bottom(),
+///     Color32::from_rgb(80, 140, 255),
+///     Color32::from_rgb(20, 60, 200),
+/// ));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum Fill {
+    /// The same color everywhere.
+    Solid(Color32),
+
+    /// The color changes along the line from `start` to `end`, and is the same across it.
+    LinearGradient {
+        stops: Vec<(f32, Color32)>,
+        start: Pos2,
+        end: Pos2,
+    },
+
+    /// The color changes with the distance from `center`, reaching the last stop at `radius`.
+    RadialGradient {
+        stops: Vec<(f32, Color32)>,
+        center: Pos2,
+        radius: f32,
+    },
+}
+
+impl Default for Fill {
+    fn default() -> Self {
+        Self::Solid(Color32::TRANSPARENT)
+    }
+}
+
+impl From<Color32> for Fill {
+    #[inline]
+    fn from(color: Color32) -> Self {
+        Self::Solid(color)
+    }
+}
+
+impl Fill {
+    /// A gradient from `start_color` at `start` to `end_color` at `end`.
+    pub fn linear(start: Pos2, end: Pos2, start_color: Color32, end_color: Color32) -> Self {
+        Self::LinearGradient {
+            stops: vec![(0.0, start_color), (1.0, end_color)],
+            start,
+            end,
+        }
+    }
+
+    /// A gradient from `inner_color` at `center` to `outer_color` at `radius` from it.
+    pub fn radial(center: Pos2, radius: f32, inner_color: Color32, outer_color: Color32) -> Self {
+        Self::RadialGradient {
+            stops: vec![(0.0, inner_color), (1.0, outer_color)],
+            center,
+            radius,
+        }
+    }
+
+    /// The color at the given position.
+    pub fn color_at(&self, pos: Pos2) -> Color32 {
+        match self {
+            Self::Solid(color) => *color,
+            Self::LinearGradient { stops, start, end } => {
+                color_at_stop(stops, linear_position(*start, *end, pos))
+            }
+            Self::RadialGradient {
+                stops,
+                center,
+                radius,
+            } => color_at_stop(stops, radial_position(*center, *radius, pos)),
+        }
+    }
+
+    /// Is this transparent everywhere?
+    pub fn is_transparent(&self) -> bool {
+        match self {
+            Self::Solid(color) => *color == Color32::TRANSPARENT,
+            Self::LinearGradient { stops, .. } | Self::RadialGradient { stops, .. } => stops
+                .iter()
+                .all(|(_, color)| *color == Color32::TRANSPARENT),
+        }
+    }
+
+    /// Move and scale the gradient together with the shape it fills.
+    pub(crate) fn transform(&mut self, transform: TSTransform) {
+        match self {
+            Self::Solid(_) => {}
+            Self::LinearGradient { start, end, .. } => {
+                *start = transform * *start;
+                *end = transform * *end;
+            }
+            Self::RadialGradient { center, radius, .. } => {
+                *center = transform * *center;
+                *radius *= transform.scaling;
+            }
+        }
+    }
+
+    pub(crate) fn adjust_colors(&mut self, adjust_color: impl Fn(&mut Color32)) {
+        match self {
+            Self::Solid(color) => adjust_color(color),
+            Self::LinearGradient { stops, .. } | Self::RadialGradient { stops, .. } => {
+                for (_, color) in stops {
+                    adjust_color(color);
+                }
+            }
+        }
+    }
+
+    /// Color the triangles of `mesh` from `index_start` on,
+    /// which were filled with white (and transparent feathering) starting at `vertex_start`.
+    ///
+    /// The triangles are split first, so that interpolating the colors of their vertices
+    /// gives the colors of the gradient.
+    pub(crate) fn paint_mesh(&self, mesh: &mut Mesh, vertex_start: usize, index_start: usize) {
+        match self {
+            Self::Solid(_) => {}
+            Self::LinearGradient { stops, start, end } => {
+                // The color changes linearly between the stops,
+                // so splitting the triangles where they cross a stop makes it exact:
+                for &(stop, _) in stops {
+                    split_edges(mesh, index_start, |a, b| {
+                        crossing(
+                            linear_position(*start, *end, a.pos) - stop,
+                            linear_position(*start, *end, b.pos) - stop,
+                        )
+                    });
+                }
+            }
+            Self::RadialGradient { center, radius, .. } => {
+                // The distance from the center isn't linear, so cut the triangles along a grid
+                // fine enough for interpolating between the corners of a cell to follow it.
+                // The color doesn't change past the radius, so the grid only covers the mesh within it:
+                let step = (radius / 8.0).max(2.0);
+                let mut bounds = Rect::NOTHING;
+                for vertex in &mesh.vertices[vertex_start..] {
+                    bounds.extend_with(vertex.pos);
+                }
+                let bounds =
+                    bounds.intersect(Rect::from_center_size(*center, Vec2::splat(2.0 * radius)));
+                for axis in 0..2 {
+                    let mut line =
+                        center[axis] + ((bounds.min[axis] - center[axis]) / step).ceil() * step;
+                    while line < bounds.max[axis] {
+                        split_edges(mesh, index_start, |a, b| {
+                            crossing(a.pos[axis] - line, b.pos[axis] - line)
+                        });
+                        line += step;
+                    }
+                }
+            }
+        }
+
+        for vertex in &mut mesh.vertices[vertex_start..] {
+            vertex.color = multiply_colors(self.color_at(vertex.pos), vertex.color);
+        }
+    }
+}
+
+/// Where along an edge its two ends, at these signed distances from a line, are on either side of it.
+fn crossing(a_side: f32, b_side: f32) -> Option<f32> {
+    let crosses = (a_side < 0.0 && 0.0 < b_side) || (b_side < 0.0 && 0.0 < a_side);
+    crosses.then(|| a_side / (a_side - b_side))
+}
+
+/// How far along from `start` to `end` the projection of `pos` is, with `0.0` at `start` and `1.0` at `end`.
+fn linear_position(start: Pos2, end: Pos2, pos: Pos2) -> f32 {
+    let direction = end - start;
+    let length_sq = direction.length_sq();
+    if length_sq > 0.0 {
+        direction.dot(pos - start) / length_sq
+    } else {
+        0.0
+    }
+}
+
+fn radial_position(center: Pos2, radius: f32, pos: Pos2) -> f32 {
+    if radius > 0.0 {
+        center.distance(pos) / radius
+    } else {
+        0.0
+    }
+}
+
+fn color_at_stop(stops: &[(f32, Color32)], t: f32) -> Color32 {
+    let (Some(first), Some(last)) = (stops.first(), stops.last()) else {
+        return Color32::TRANSPARENT;
+    };
+    if t <= first.0 {
+        return first.1;
+    }
+    for window in stops.windows(2) {
+        let [(t0, c0), (t1, c1)] = [window[0], window[1]];
+        if t <= t1 {
+            let f = if t1 > t0 { (t - t0) / (t1 - t0) } else { 1.0 };
+            return lerp_color(c0, c1, f);
+        }
+    }
+    last.1
+}
+
+fn lerp_color(a: Color32, b: Color32, t: f32) -> Color32 {
+    let [a, b] = [a.to_array(), b.to_array()];
+    let channel = |i: usize| emath::lerp(a[i] as f32..=b[i] as f32, t).round() as u8;
+    Color32::from_rgba_premultiplied(channel(0), channel(1), channel(2), channel(3))
+}
+
+pub(crate) fn multiply_colors(a: Color32, b: Color32) -> Color32 {
+    let [a, b] = [a.to_array(), b.to_array()];
+    let channel = |i: usize| ((a[i] as u32 * b[i] as u32 + 127) / 255) as u8;
+    Color32::from_rgba_premultiplied(channel(0), channel(1), channel(2), channel(3))
+}
+
+pub(crate) fn lerp_vertex(a: &Vertex, b: &Vertex, t: f32) -> Vertex {
+    Vertex {
+        pos: a.pos.lerp(b.pos, t),
+        uv: a.uv.lerp(b.uv, t),
+        color: lerp_color(a.color, b.color, t),
+    }
+}
+
+/// Split the triangles of `mesh` from `index_start` on at the edges where `split_at` says so,
+/// given as the fraction of the way from the first to the second vertex of the edge.
+///
+/// An edge shared by two triangles is split at the same new vertex for both of them, so no cracks appear.
+fn split_edges(
+    mesh: &mut Mesh,
+    index_start: usize,
+    split_at: impl Fn(&Vertex, &Vertex) -> Option<f32>,
+) {
+    let triangles = mesh.indices.split_off(index_start);
+    let mut split_vertices = ahash::HashMap::<(u32, u32), Option<u32>>::default();
+
+    for triangle in triangles.chunks_exact(3) {
+        // The vertices, and the vertices splitting the edge from each of them to the next:
+        let v = [triangle[0], triangle[1], triangle[2]];
+        let mut m = [None; 3];
+        for (k, split_vertex) in m.iter_mut().enumerate() {
+            let (a, b) = (v[k], v[(k + 1) % 3]);
+            let edge = (a.min(b), a.max(b));
+            *split_vertex = *split_vertices.entry(edge).or_insert_with(|| {
+                let (a, b) = (
+                    &mesh.vertices[edge.0 as usize],
+                    &mesh.vertices[edge.1 as usize],
+                );
+                let t = split_at(a, b)?;
+                let vertex = lerp_vertex(a, b, t);
+                mesh.vertices.push(vertex);
+                Some(mesh.vertices.len() as u32 - 1)
+            });
+        }
+
+        let num_split = m.iter().filter(|m| m.is_some()).count();
+        match num_split {
+            0 => mesh.indices.extend_from_slice(&v),
+            1 => {
+                let k = m.iter().position(|m| m.is_some()).unwrap_or_default();
+                let (a, b, c) = (v[k], v[(k + 1) % 3], v[(k + 2) % 3]);
+                let ab = m[k].unwrap_or_default();
+                mesh.indices.extend_from_slice(&[a, ab, c, ab, b, c]);
+            }
+            2 => {
+                let k = m.iter().position(|m| m.is_none()).unwrap_or_default();
+                let (a, b, c) = (v[k], v[(k + 1) % 3], v[(k + 2) % 3]);
+                let bc = m[(k + 1) % 3].unwrap_or_default();
+                let ca = m[(k + 2) % 3].unwrap_or_default();
+                mesh.indices
+                    .extend_from_slice(&[bc, c, ca, a, b, bc, a, bc, ca]);
+            }
+            _ => {
+                let [a, b, c] = v;
+                let [ab, bc, ca] = m.map(Option::unwrap_or_default);
+                mesh.indices
+                    .extend_from_slice(&[a, ab, ca, ab, b, bc, ca, bc, c, ab, bc, ca]);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use emath::pos2;
+
+    use super::*;
+
+    #[test]
+    fn test_gradient_colors() {
+        let fill = Fill::linear(
+            pos2(0.0, 0.0),
+            pos2(100.0, 0.0),
+            Color32::BLACK,
+            Color32::WHITE,
+        );
+        assert_eq!(fill.color_at(pos2(-10.0, 5.0)), Color32::BLACK);
+        assert_eq!(fill.color_at(pos2(50.0, 50.0)), Color32::from_gray(128));
+        assert_eq!(fill.color_at(pos2(200.0, 0.0)), Color32::WHITE);
+
+        let fill = Fill::radial(pos2(0.0, 0.0), 10.0, Color32::WHITE, Color32::TRANSPARENT);
+        assert_eq!(fill.color_at(pos2(0.0, 0.0)), Color32::WHITE);
+        assert_eq!(fill.color_at(pos2(0.0, 20.0)), Color32::TRANSPARENT);
+    }
+
+    #[test]
+    fn test_paint_mesh() {
+        let rect = Rect::from_min_max(pos2(0.0, 0.0), pos2(100.0, 10.0));
+        let filled_rect = || {
+            let mut mesh = Mesh::default();
+            mesh.add_colored_rect(rect, Color32::WHITE);
+            mesh
+        };
+
+        // Two stops at the ends of the rect only need the colors of the corners:
+        let mut mesh = filled_rect();
+        let fill = Fill::linear(
+            rect.left_center(),
+            rect.right_center(),
+            Color32::RED,
+            Color32::BLUE,
+        );
+        fill.paint_mesh(&mut mesh, 0, 0);
+        assert_eq!(mesh.vertices.len(), 4);
+        assert_eq!(mesh.vertices[0].color, Color32::RED);
+        assert_eq!(mesh.vertices[1].color, Color32::BLUE);
+
+        // A stop in the middle splits the triangles there:
+        let mut mesh = filled_rect();
+        let fill = Fill::LinearGradient {
+            stops: vec![
+                (0.0, Color32::BLACK),
+                (0.5, Color32::WHITE),
+                (1.0, Color32::BLACK),
+            ],
+            start: rect.left_center(),
+            end: rect.right_center(),
+        };
+        fill.paint_mesh(&mut mesh, 0, 0);
+        assert!(mesh.indices.len() > 2 * 3);
+        assert!(mesh.is_valid());
+        assert!(mesh
+            .vertices
+            .iter()
+            .any(|vertex| vertex.pos.x == 50.0 && vertex.color == Color32::WHITE));
+
+        // Radial gradients are cut along a grid, within the radius:
+        let mut mesh = filled_rect();
+        Fill::radial(rect.center(), 50.0, Color32::WHITE, Color32::BLACK)
+            .paint_mesh(&mut mesh, 0, 0);
+        assert!(mesh.is_valid());
+        assert!(mesh.vertices.len() > 20);
+        assert!(mesh
+            .vertices
+            .iter()
+            .any(|vertex| vertex.pos == rect.center() && vertex.color == Color32::WHITE));
+
+        // …but not past it:
+        let mut mesh = filled_rect();
+        Fill::radial(rect.left_top(), 10.0, Color32::WHITE, Color32::BLACK)
+            .paint_mesh(&mut mesh, 0, 0);
+        assert!(mesh.is_valid());
+        assert!(mesh
+            .vertices
+            .iter()
+            .any(|vertex| vertex.pos.distance(pos2(8.0, 0.0)) < 1e-3));
+        assert!(!mesh
+            .vertices
+            .iter()
+            .any(|vertex| vertex.pos.y == 0.0 && 10.0 < vertex.pos.x && vertex.pos.x < 100.0));
+    }
+}
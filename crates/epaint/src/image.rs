@@ -155,19 +155,30 @@ impl ColorImage {
         let max_y = (region.max.y * pixels_per_point) as usize;
         assert!(min_x <= max_x);
         assert!(min_y <= max_y);
-        let width = max_x - min_x;
-        let height = max_y - min_y;
-        let mut output = Vec::with_capacity(width * height);
-        let row_stride = self.size[0];
-
-        for row in min_y..max_y {
-            output.extend_from_slice(
-                &self.pixels[row * row_stride + min_x..row * row_stride + max_x],
-            );
+        self.sub_image([min_x, min_y], [max_x - min_x, max_y - min_y])
+    }
+
+    /// Copy out the `[w, h]` pixels at `[x, y]`, without copying the rest of the image.
+    ///
+    /// Useful with [`ImageDelta::partial`] to update a part of a large texture.
+    ///
+    /// Panics if the region is outside the image.
+    pub fn sub_image(&self, [x, y]: [usize; 2], [w, h]: [usize; 2]) -> Self {
+        assert!(
+            x + w <= self.width() && y + h <= self.height(),
+            "Region {:?} is outside the {:?} image",
+            [x, y, w, h],
+            self.size
+        );
+
+        let mut pixels = Vec::with_capacity(w * h);
+        for row in y..y + h {
+            let offset = row * self.width() + x;
+            pixels.extend_from_slice(&self.pixels[offset..offset + w]);
         }
         Self {
-            size: [width, height],
-            pixels: output,
+            size: [w, h],
+            pixels,
         }
     }
 
@@ -274,16 +285,53 @@ pub struct FontImage {
     ///
     /// Often you want to use [`Self::srgba_pixels`] instead.
     pub pixels: Vec<f32>,
+
+    /// Optional color of each texel, used for color glyphs (e.g. emojis).
+    ///
+    /// The alpha channel is ignored: the opacity is always controlled by [`Self::pixels`].
+    ///
+    /// This is empty (meaning all-white) until a color is set with [`Self::set_color`],
+    /// after which it has the same length as [`Self::pixels`].
+    pub colors: Vec<Color32>,
+
+    /// The coverage gamma used by [`Self::srgba_pixels`], unless one is given to it.
+    ///
+    /// This is applied when converting to colors rather than when rasterizing,
+    /// so [`Self::pixels`] always holds the plain coverage and no glyph needs to be rasterized again
+    /// when the gamma changes. The opacity of color glyphs is in [`Self::pixels`] too,
+    /// so they get the same gamma.
+    ///
+    /// `None` means [`Self::DEFAULT_GAMMA`].
+    pub gamma: Option<f32>,
 }
 
 impl FontImage {
+    /// The default coverage gamma, see [`Self::srgba_pixels`].
+    ///
+    /// TODO(emilk): this default coverage gamma is a magic constant, chosen by eye. I don't even know why we need it.
+    pub const DEFAULT_GAMMA: f32 = 0.55;
+
     pub fn new(size: [usize; 2]) -> Self {
         Self {
             size,
             pixels: vec![0.0; size[0] * size[1]],
+            colors: vec![],
+            gamma: None,
         }
     }
 
+    /// Set the color of a texel. The opacity is still controlled by [`Self::pixels`].
+    ///
+    /// Used for color glyphs (e.g. emojis).
+    pub fn set_color(&mut self, (x, y): (usize, usize), color: Color32) {
+        let [w, h] = self.size;
+        assert!(x < w && y < h);
+        if self.colors.is_empty() {
+            self.colors = vec![Color32::WHITE; self.pixels.len()];
+        }
+        self.colors[y * w + x] = color;
+    }
+
     #[inline]
     pub fn width(&self) -> usize {
         self.size[0]
@@ -296,17 +344,27 @@ impl FontImage {
 
     /// Returns the textures as `sRGBA` premultiplied pixels, row by row, top to bottom.
     ///
-    /// `gamma` should normally be set to `None`.
+    /// `gamma` should normally be set to `None`, which means [`Self::gamma`] is used.
     ///
     /// If you are having problems with text looking skinny and pixelated, try using a low gamma, e.g. `0.4`.
     #[inline]
     pub fn srgba_pixels(&self, gamma: Option<f32>) -> impl ExactSizeIterator<Item = Color32> + '_ {
-        let gamma = gamma.unwrap_or(0.55); // TODO(emilk): this default coverage gamma is a magic constant, chosen by eye. I don't even know why we need it.
-        self.pixels.iter().map(move |coverage| {
+        let gamma = gamma.or(self.gamma).unwrap_or(Self::DEFAULT_GAMMA);
+        self.pixels.iter().enumerate().map(move |(i, coverage)| {
             let alpha = coverage.powf(gamma);
             // We want to multiply with `vec4(alpha)` in the fragment shader:
             let a = fast_round(alpha * 255.0);
-            Color32::from_rgba_premultiplied(a, a, a, a)
+            if let Some(color) = self.colors.get(i) {
+                let [r, g, b, _] = color.to_array();
+                Color32::from_rgba_premultiplied(
+                    fast_round(r as f32 * alpha),
+                    fast_round(g as f32 * alpha),
+                    fast_round(b as f32 * alpha),
+                    a,
+                )
+            } else {
+                Color32::from_rgba_premultiplied(a, a, a, a)
+            }
         })
     }
 
@@ -316,14 +374,20 @@ impl FontImage {
         assert!(y + h <= self.height());
 
         let mut pixels = Vec::with_capacity(w * h);
+        let mut colors = Vec::with_capacity(if self.colors.is_empty() { 0 } else { w * h });
         for y in y..y + h {
             let offset = y * self.width() + x;
             pixels.extend(&self.pixels[offset..(offset + w)]);
+            if !self.colors.is_empty() {
+                colors.extend(&self.colors[offset..(offset + w)]);
+            }
         }
         assert_eq!(pixels.len(), w * h);
         Self {
             size: [w, h],
             pixels,
+            colors,
+            gamma: self.gamma,
         }
     }
 }
@@ -403,9 +467,54 @@ impl ImageDelta {
         }
     }
 
+    /// Update the `size` pixels at `pos` of a texture from the same region of `image`,
+    /// which is the whole texture.
+    ///
+    /// Only the region is copied, unless it covers the whole image.
+    /// This is how both the font atlas and [`crate::TextureHandle::set_region`] update their textures.
+    pub(crate) fn region(
+        image: &impl ImageRegion,
+        pos: [usize; 2],
+        size: [usize; 2],
+        options: TextureOptions,
+    ) -> Self {
+        if pos == [0, 0] && size == image.size() {
+            Self::full(image.clone(), options)
+        } else {
+            Self::partial(pos, image.sub_image(pos, size), options)
+        }
+    }
+
     /// Is this affecting the whole texture?
     /// If `false`, this is a partial (sub-region) update.
     pub fn is_whole(&self) -> bool {
         self.pos.is_none()
     }
 }
+
+/// An image that a region can be copied out of, for [`ImageDelta::region`].
+pub(crate) trait ImageRegion: Clone + Into<ImageData> {
+    fn size(&self) -> [usize; 2];
+
+    fn sub_image(&self, pos: [usize; 2], size: [usize; 2]) -> Self;
+}
+
+impl ImageRegion for ColorImage {
+    fn size(&self) -> [usize; 2] {
+        self.size
+    }
+
+    fn sub_image(&self, pos: [usize; 2], size: [usize; 2]) -> Self {
+        Self::sub_image(self, pos, size)
+    }
+}
+
+impl ImageRegion for FontImage {
+    fn size(&self) -> [usize; 2] {
+        self.size
+    }
+
+    fn sub_image(&self, pos: [usize; 2], size: [usize; 2]) -> Self {
+        self.region(pos, size)
+    }
+}
@@ -25,10 +25,12 @@
 
 mod bezier;
 pub mod color;
+mod fill;
 pub mod image;
 mod margin;
 mod mesh;
 pub mod mutex;
+mod path_builder;
 mod shadow;
 mod shape;
 pub mod shape_transform;
@@ -44,19 +46,21 @@ pub mod util;
 pub use self::{
     bezier::{CubicBezierShape, QuadraticBezierShape},
     color::ColorMode,
+    fill::Fill,
     image::{ColorImage, FontImage, ImageData, ImageDelta},
     margin::Margin,
-    mesh::{Mesh, Mesh16, Vertex},
+    mesh::{Mesh, Mesh16, NinePatch, Vertex},
+    path_builder::{CurvePathShape, PathBuilder},
     shadow::Shadow,
     shape::{
-        CircleShape, EllipseShape, PaintCallback, PaintCallbackInfo, PathShape, RectShape,
-        Rounding, Shape, TextShape,
+        Backdrop, BackdropShape, CircleSegmentShape, CircleShape, EllipseShape, InstancedMeshShape,
+        PaintCallback, PaintCallbackInfo, PathShape, RectShape, Rounding, Shape, TextShape,
     },
     stats::PaintStats,
-    stroke::{PathStroke, Stroke},
+    stroke::{LinePattern, PathStroke, Stroke},
     tessellator::{TessellationOptions, Tessellator},
     text::{FontFamily, FontId, Fonts, Galley},
-    texture_atlas::TextureAtlas,
+    texture_atlas::{AtlasOccupancy, TextureAtlas},
     texture_handle::TextureHandle,
     textures::TextureManager,
 };
@@ -112,11 +116,17 @@ pub struct ClippedShape {
     /// Only show the part of the [`Shape`] that falls within this.
     pub clip_rect: emath::Rect,
 
+    /// How rounded the corners of [`Self::clip_rect`] are.
+    ///
+    /// The rounded corners are cut out of the tessellated triangles,
+    /// so the backend only ever needs to scissor to [`Self::clip_rect`].
+    pub clip_rounding: Rounding,
+
     /// The shape
     pub shape: Shape,
 }
 
-/// A [`Mesh`] or [`PaintCallback`] within a clip rectangle.
+/// A [`Mesh`], [`PaintCallback`] or [`Backdrop`] within a clip rectangle.
 ///
 /// Everything is using logical points.
 #[derive(Clone, Debug)]
@@ -125,15 +135,26 @@ pub struct ClippedPrimitive {
     /// Only show the part of the [`Mesh`] that falls within this.
     pub clip_rect: emath::Rect,
 
-    /// What to paint - either a [`Mesh`] or a [`PaintCallback`].
+    /// How rounded the corners of [`Self::clip_rect`] are.
+    ///
+    /// Meshes have already been cut to the rounded corners by the tessellator,
+    /// but a [`PaintCallback`] is only clipped to the rect, unless it handles this itself.
+    pub clip_rounding: Rounding,
+
+    /// What to paint - a [`Mesh`], a [`PaintCallback`] or a [`Backdrop`].
     pub primitive: Primitive,
 }
 
-/// A rendering primitive - either a [`Mesh`] or a [`PaintCallback`].
+/// A rendering primitive - a [`Mesh`], a [`PaintCallback`] or a [`Backdrop`].
 #[derive(Clone, Debug)]
 pub enum Primitive {
     Mesh(Mesh),
     Callback(PaintCallback),
+
+    /// Blur what has been painted so far, and paint it in [`Backdrop::mesh`].
+    ///
+    /// Only produced if [`TessellationOptions::backdrop_blur`] is set.
+    Backdrop(Backdrop),
 }
 
 // ---------------------------------------------------------------------------
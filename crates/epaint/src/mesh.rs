@@ -200,6 +200,111 @@ impl Mesh {
         });
     }
 
+    /// A texture sliced into nine patches, scaled to cover `rect`.
+    ///
+    /// The four corners keep their size, the edges are stretched (or tiled) along the rect
+    /// and the center fills the rest.
+    /// If `rect` is smaller than the corners, the corners shrink proportionally to fit.
+    ///
+    /// When tiling, each patch is split into at most [`NinePatch::MAX_TILES`] tiles along each side;
+    /// beyond that the tiles are stretched to fill the patch.
+    pub fn add_nine_patch(&mut self, rect: Rect, nine_patch: &NinePatch, color: Color32) {
+        let NinePatch {
+            texture_size,
+            uv_margins,
+            tile,
+        } = *nine_patch;
+
+        // Where the patches begin and end, in points and in the texture:
+        let corners = |min: f32, max: f32, start: f32, end: f32| {
+            let shrink = ((max - min) / (start + end)).min(1.0);
+            [min, min + shrink * start, max - shrink * end, max]
+        };
+        let xs = corners(
+            rect.left(),
+            rect.right(),
+            uv_margins.left * texture_size.x,
+            uv_margins.right * texture_size.x,
+        );
+        let ys = corners(
+            rect.top(),
+            rect.bottom(),
+            uv_margins.top * texture_size.y,
+            uv_margins.bottom * texture_size.y,
+        );
+        let us = [0.0, uv_margins.left, 1.0 - uv_margins.right, 1.0];
+        let vs = [0.0, uv_margins.top, 1.0 - uv_margins.bottom, 1.0];
+
+        // The size of the middle patches in the texture, in points:
+        let tile_size = texture_size
+            - vec2(
+                (uv_margins.left + uv_margins.right) * texture_size.x,
+                (uv_margins.top + uv_margins.bottom) * texture_size.y,
+            );
+
+        // The pieces of one patch along one axis: one stretched piece, or tiles of `tile_size`.
+        let pieces = |min: f32, max: f32, uv_min: f32, uv_max: f32, tile_size: Option<f32>| {
+            let Some(tile_size) = tile_size.filter(|&tile_size| 0.0 < tile_size) else {
+                return vec![(min, max, uv_min, uv_max)];
+            };
+            // A huge rect or a tiny texture would need too many tiles, so make them bigger:
+            let max_tiles = NinePatch::MAX_TILES as f32;
+            if max_tiles * tile_size < max - min {
+                let stretched_size = (max - min) / max_tiles;
+                return (0..NinePatch::MAX_TILES)
+                    .map(|i| {
+                        let start = min + i as f32 * stretched_size;
+                        (start, (start + stretched_size).min(max), uv_min, uv_max)
+                    })
+                    .collect();
+            }
+            // Ignore rounding errors, rather than adding a sliver of a tile at the end:
+            let num_tiles = ((max - min) / tile_size - 1e-3).ceil().max(1.0) as usize;
+            (0..num_tiles)
+                .map(|i| {
+                    let start = min + i as f32 * tile_size;
+                    let end = (start + tile_size).min(max);
+                    let uv_end = uv_min + (uv_max - uv_min) * (end - start) / tile_size;
+                    (start, end, uv_min, uv_end)
+                })
+                .collect()
+        };
+
+        for row in 0..3 {
+            let (y_min, y_max, v_min, v_max) = (ys[row], ys[row + 1], vs[row], vs[row + 1]);
+            if y_max <= y_min {
+                continue;
+            }
+            for col in 0..3 {
+                let (x_min, x_max, u_min, u_max) = (xs[col], xs[col + 1], us[col], us[col + 1]);
+                if x_max <= x_min {
+                    continue;
+                }
+                for (y0, y1, v0, v1) in pieces(
+                    y_min,
+                    y_max,
+                    v_min,
+                    v_max,
+                    (tile && row == 1).then_some(tile_size.y),
+                ) {
+                    for (x0, x1, u0, u1) in pieces(
+                        x_min,
+                        x_max,
+                        u_min,
+                        u_max,
+                        (tile && col == 1).then_some(tile_size.x),
+                    ) {
+                        self.add_rect_with_uv(
+                            Rect::from_min_max(pos2(x0, y0), pos2(x1, y1)),
+                            Rect::from_min_max(pos2(u0, v0), pos2(u1, v1)),
+                            color,
+                        );
+                    }
+                }
+            }
+        }
+    }
+
     /// Uniformly colored rectangle.
     #[inline(always)]
     pub fn add_colored_rect(&mut self, rect: Rect, color: Color32) {
@@ -297,6 +402,51 @@ impl Mesh {
 
 // ----------------------------------------------------------------------------
 
+/// How to slice a texture into nine patches for [`Mesh::add_nine_patch`],
+/// so it can cover a rect of any size without distorting its corners.
+///
+/// This is often used for skinned frames and buttons.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct NinePatch {
+    /// The size of the whole texture in points, which is how large the corners are painted.
+    pub texture_size: Vec2,
+
+    /// Where the corners end, measured inwards from each side of the texture in UV coordinates (0-1).
+    pub uv_margins: Margin,
+
+    /// Repeat the edges and the center at their size in the texture, instead of stretching them.
+    pub tile: bool,
+}
+
+impl NinePatch {
+    /// The most tiles along each side of a patch, see [`Mesh::add_nine_patch`].
+    ///
+    /// This bounds the size of the mesh, however large the rect and however small the texture.
+    pub const MAX_TILES: usize = 64;
+
+    /// Slice a texture of `texture_size` points at the given UV margins.
+    ///
+    /// For a 32x32 texture with 8 pixel corners, use `NinePatch::new(vec2(32.0, 32.0), Margin::same(0.25))`.
+    #[inline]
+    pub fn new(texture_size: Vec2, uv_margins: Margin) -> Self {
+        Self {
+            texture_size,
+            uv_margins,
+            tile: false,
+        }
+    }
+
+    /// Repeat the edges and the center at their size in the texture, instead of stretching them.
+    #[inline]
+    pub fn tiled(mut self) -> Self {
+        self.tile = true;
+        self
+    }
+}
+
+// ----------------------------------------------------------------------------
+
 /// A version of [`Mesh`] that uses 16-bit indices.
 ///
 /// This is produced by [`Mesh::split_to_u16`] and is meant to be used for legacy render backends.
@@ -323,3 +473,76 @@ impl Mesh16 {
         }
     }
 }
+
+#[test]
+fn nine_patch() {
+    let nine_patch = NinePatch::new(vec2(30.0, 30.0), Margin::same(1.0 / 3.0));
+    let quads = |rect: Rect, nine_patch: &NinePatch| {
+        let mut mesh = Mesh::default();
+        mesh.add_nine_patch(rect, nine_patch, Color32::WHITE);
+        assert!(mesh.is_valid());
+        mesh.vertices
+            .chunks_exact(4)
+            .map(|quad| {
+                (
+                    Rect::from_min_max(quad[0].pos, quad[3].pos),
+                    Rect::from_min_max(quad[0].uv, quad[3].uv),
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+
+    // Fixed corners, stretched edges and center:
+    let stretched = quads(
+        Rect::from_min_size(Pos2::ZERO, vec2(100.0, 40.0)),
+        &nine_patch,
+    );
+    assert_eq!(stretched.len(), 9);
+    assert_eq!(
+        stretched[0].0,
+        Rect::from_min_size(Pos2::ZERO, vec2(10.0, 10.0))
+    );
+    assert_eq!(stretched[0].1.max, pos2(1.0 / 3.0, 1.0 / 3.0));
+    assert_eq!(
+        stretched[4].0,
+        Rect::from_min_max(pos2(10.0, 10.0), pos2(90.0, 30.0))
+    );
+    assert_eq!(stretched[8].1.max, pos2(1.0, 1.0));
+
+    // Too narrow for the corners: they shrink, and the middle column disappears.
+    let narrow = quads(
+        Rect::from_min_size(Pos2::ZERO, vec2(10.0, 40.0)),
+        &nine_patch,
+    );
+    assert_eq!(narrow.len(), 6);
+    assert_eq!(
+        narrow[0].0,
+        Rect::from_min_size(Pos2::ZERO, vec2(5.0, 10.0))
+    );
+    assert_eq!(narrow[0].1.max, pos2(1.0 / 3.0, 1.0 / 3.0));
+
+    // Tiled: the middle is repeated at its size in the texture, the last tile cut short.
+    let tiled = quads(
+        Rect::from_min_size(Pos2::ZERO, vec2(95.0, 40.0)),
+        &nine_patch.tiled(),
+    );
+    assert_eq!(tiled.len(), 4 + 2 * 8 + 2 * 2 + 8 * 2);
+    let top_edge: Vec<_> = tiled[1..9].to_vec();
+    assert!(top_edge.iter().all(|(rect, _)| rect.height() == 10.0));
+    assert_eq!(top_edge[0].0.width(), 10.0);
+    assert_eq!(top_edge[7].0.width(), 5.0);
+    assert!((top_edge[7].1.width() - 1.0 / 6.0).abs() < 1e-6);
+
+    // Too many tiles: they are stretched to fill the patch instead.
+    let huge = quads(
+        Rect::from_min_size(Pos2::ZERO, vec2(1e6, 40.0)),
+        &nine_patch.tiled(),
+    );
+    let max_tiles = NinePatch::MAX_TILES;
+    assert_eq!(huge.len(), 4 + 2 * max_tiles + 2 * 2 + max_tiles * 2);
+    let top_edge: Vec<_> = huge[1..=max_tiles].to_vec();
+    assert!(top_edge
+        .iter()
+        .all(|(_, uv)| (uv.width() - 1.0 / 3.0).abs() < 1e-6));
+    assert!((top_edge[max_tiles - 1].0.right() - (1e6 - 10.0)).abs() < 0.5);
+}
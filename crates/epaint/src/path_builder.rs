@@ -0,0 +1,350 @@
+use crate::{shape::Shape, Color32, CubicBezierShape, Fill, PathStroke, QuadraticBezierShape};
+use emath::{Pos2, Rect, TSTransform};
+
+/// How far the flattened lines may stray from the curves, unless overridden with
+/// [`PathBuilder::tolerance`].
+const DEFAULT_TOLERANCE_IN_PIXELS: f32 = 0.1;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+enum Segment {
+    Line(Pos2),
+    Quadratic(Pos2, Pos2),
+    Cubic(Pos2, Pos2, Pos2),
+}
+
+impl Segment {
+    fn end(self) -> Pos2 {
+        match self {
+            Self::Line(end) | Self::Quadratic(_, end) | Self::Cubic(_, _, end) => end,
+        }
+    }
+
+    /// The end point and control points.
+    fn points(self) -> impl Iterator<Item = Pos2> {
+        let points = match self {
+            Self::Line(end) => [Some(end), None, None],
+            Self::Quadratic(control, end) => [Some(control), Some(end), None],
+            Self::Cubic(control1, control2, end) => [Some(control1), Some(control2), Some(end)],
+        };
+        points.into_iter().flatten()
+    }
+
+    fn transform(&mut self, transform: TSTransform) {
+        match self {
+            Self::Line(end) => {
+                *end = transform * *end;
+            }
+            Self::Quadratic(control, end) => {
+                *control = transform * *control;
+                *end = transform * *end;
+            }
+            Self::Cubic(control1, control2, end) => {
+                *control1 = transform * *control1;
+                *control2 = transform * *control2;
+                *end = transform * *end;
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+struct SubPath {
+    start: Pos2,
+    segments: Vec<Segment>,
+    closed: bool,
+}
+
+/// Builds a path of straight lines and [Bézier curves](https://en.wikipedia.org/wiki/B%C3%A9zier_curve),
+/// like a path in SVG or on a HTML canvas.
+///
+/// Paint it with a [`CurvePathShape`]. The curves are kept as they are until the shape is tessellated,
+/// and are then flattened into lines with a tolerance that follows `pixels_per_point`.
+///
+/// Each [`Self::move_to`] starts a new subpath.
+/// Every subpath is filled on its own, using the even-odd rule,
+/// so where a subpath crosses itself, the overlap is left empty.
+/// Subpaths that aren't closed are filled as if they were, but only stroked along the path.
+///
+/// ```
+/// # use epaint::{pos2, Color32, CurvePathShape, PathBuilder, Stroke};
+/// let path = PathBuilder::new()
+///     .move_to(pos2(0.0, 50.0))
+///     .line_to(pos2(20.0, 50.0))
+///     .quad_to(pos2(50.0, 0.0), pos2(80.0, 50.0))
+///     .cubic_to(pos2(90.0, 70.0), pos2(110.0, 70.0), pos2(120.0, 50.0))
+///     .close();
+/// let shape = CurvePathShape::new(path, Color32::LIGHT_BLUE, Stroke::new(1.0, Color32::WHITE));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct PathBuilder {
+    subpaths: Vec<SubPath>,
+    tolerance: Option<f32>,
+}
+
+impl PathBuilder {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start a new subpath at `point`.
+    #[inline]
+    pub fn move_to(mut self, point: Pos2) -> Self {
+        self.subpaths.push(SubPath {
+            start: point,
+            segments: vec![],
+            closed: false,
+        });
+        self
+    }
+
+    /// A straight line from the current point to `end`.
+    #[inline]
+    pub fn line_to(self, end: Pos2) -> Self {
+        self.add_segment(Segment::Line(end))
+    }
+
+    /// A quadratic Bézier curve from the current point to `end`.
+    #[inline]
+    pub fn quad_to(self, control: Pos2, end: Pos2) -> Self {
+        self.add_segment(Segment::Quadratic(control, end))
+    }
+
+    /// A cubic Bézier curve from the current point to `end`.
+    #[inline]
+    pub fn cubic_to(self, control1: Pos2, control2: Pos2, end: Pos2) -> Self {
+        self.add_segment(Segment::Cubic(control1, control2, end))
+    }
+
+    /// Close the current subpath with a straight line back to its start.
+    ///
+    /// Anything added after this (without a [`Self::move_to`]) starts a new subpath at the same start.
+    #[inline]
+    pub fn close(mut self) -> Self {
+        if let Some(subpath) = self.subpaths.last_mut() {
+            subpath.closed = true;
+        }
+        self
+    }
+
+    /// How far the flattened lines may stray from the curves, in points.
+    ///
+    /// The default is a tenth of a physical pixel at the `pixels_per_point` the path is tessellated with.
+    /// Use a smaller tolerance if the tessellated mesh is magnified afterwards,
+    /// or a larger one for paths that don't need to be that exact, e.g. in a zoomed out plot.
+    ///
+    /// The tolerance is scaled together with the path by [`Shape::transform`].
+    #[inline]
+    pub fn tolerance(mut self, tolerance: f32) -> Self {
+        self.tolerance = Some(tolerance);
+        self
+    }
+
+    fn add_segment(mut self, segment: Segment) -> Self {
+        let start = match self.subpaths.last() {
+            Some(subpath) if !subpath.closed => None,
+            Some(subpath) => Some(subpath.start),
+            None => Some(segment.end()), // no current point: start where the segment ends
+        };
+        if let Some(start) = start {
+            self = self.move_to(start);
+        }
+        if let Some(subpath) = self.subpaths.last_mut() {
+            subpath.segments.push(segment);
+        }
+        self
+    }
+
+    /// The [`Self::tolerance`] to flatten the curves with at this `pixels_per_point`.
+    pub(crate) fn tolerance_at(&self, pixels_per_point: f32) -> f32 {
+        self.tolerance
+            .unwrap_or(DEFAULT_TOLERANCE_IN_PIXELS / pixels_per_point)
+    }
+
+    /// Contains all the points, including the control points of the curves.
+    pub(crate) fn bounding_rect(&self) -> Rect {
+        let mut rect = Rect::NOTHING;
+        for subpath in &self.subpaths {
+            rect.extend_with(subpath.start);
+            for segment in &subpath.segments {
+                for point in segment.points() {
+                    rect.extend_with(point);
+                }
+            }
+        }
+        rect
+    }
+
+    pub(crate) fn transform(&mut self, transform: TSTransform) {
+        for subpath in &mut self.subpaths {
+            subpath.start = transform * subpath.start;
+            for segment in &mut subpath.segments {
+                segment.transform(transform);
+            }
+        }
+        if let Some(tolerance) = &mut self.tolerance {
+            *tolerance *= transform.scaling;
+        }
+    }
+
+    /// The points of each subpath, and whether it is closed,
+    /// with the curves flattened to within `tolerance` points.
+    pub(crate) fn flatten(&self, tolerance: f32) -> Vec<(Vec<Pos2>, bool)> {
+        self.subpaths
+            .iter()
+            .filter(|subpath| !subpath.segments.is_empty())
+            .map(|subpath| {
+                let mut points = vec![subpath.start];
+                let mut current = subpath.start;
+                for &segment in &subpath.segments {
+                    match segment {
+                        Segment::Line(end) => points.push(end),
+                        Segment::Quadratic(control, end) => {
+                            let curve = QuadraticBezierShape::from_points_stroke(
+                                [current, control, end],
+                                false,
+                                Color32::TRANSPARENT,
+                                PathStroke::NONE,
+                            );
+                            points.extend(curve.flatten(Some(tolerance)).into_iter().skip(1));
+                        }
+                        Segment::Cubic(control1, control2, end) => {
+                            let curve = CubicBezierShape::from_points_stroke(
+                                [current, control1, control2, end],
+                                false,
+                                Color32::TRANSPARENT,
+                                PathStroke::NONE,
+                            );
+                            points.extend(curve.flatten(Some(tolerance)).into_iter().skip(1));
+                        }
+                    }
+                    current = segment.end();
+                }
+                if subpath.closed && 2 < points.len() && current == subpath.start {
+                    points.pop(); // closing the path connects back to the start anyway
+                }
+                (points, subpath.closed)
+            })
+            .collect()
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+/// A path of lines and Bézier curves, built with a [`PathBuilder`], with a fill and a stroke.
+///
+/// The curves are flattened when the shape is tessellated, see [`PathBuilder`].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct CurvePathShape {
+    pub path: PathBuilder,
+
+    /// Fills every subpath using the even-odd rule, see [`PathBuilder`].
+    pub fill: Fill,
+
+    /// Color and thickness of the line.
+    pub stroke: PathStroke,
+}
+
+impl CurvePathShape {
+    #[inline]
+    pub fn new(path: PathBuilder, fill: impl Into<Fill>, stroke: impl Into<PathStroke>) -> Self {
+        Self {
+            path,
+            fill: fill.into(),
+            stroke: stroke.into(),
+        }
+    }
+
+    /// The visual bounding rectangle (includes stroke width)
+    ///
+    /// This contains the control points of the curves, so it may be larger than the curves themselves.
+    #[inline]
+    pub fn visual_bounding_rect(&self) -> Rect {
+        if self.fill.is_transparent() && self.stroke.is_empty() {
+            Rect::NOTHING
+        } else {
+            self.path.bounding_rect().expand(self.stroke.width / 2.0)
+        }
+    }
+}
+
+impl From<CurvePathShape> for Shape {
+    #[inline(always)]
+    fn from(shape: CurvePathShape) -> Self {
+        Self::CurvePath(shape)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use emath::pos2;
+
+    use super::*;
+
+    #[test]
+    fn test_flatten() {
+        let path = PathBuilder::new()
+            .move_to(pos2(0.0, 0.0))
+            .line_to(pos2(10.0, 0.0))
+            .quad_to(pos2(20.0, 0.0), pos2(20.0, 10.0))
+            .line_to(pos2(0.0, 0.0))
+            .close()
+            .line_to(pos2(0.0, 20.0));
+
+        let coarse = path.flatten(1.0);
+        let fine = path.flatten(0.01);
+        assert_eq!(coarse.len(), 2);
+        assert_eq!(fine.len(), 2);
+
+        // The curve is flattened, more finely with a smaller tolerance,
+        // and the explicit line back to the start is dropped when closing:
+        let (points, closed) = &fine[0];
+        assert!(*closed);
+        assert!(coarse[0].0.len() < points.len());
+        assert_eq!(points[..2], [pos2(0.0, 0.0), pos2(10.0, 0.0)]);
+        assert_eq!(points.last(), Some(&pos2(20.0, 10.0)));
+
+        // After closing, the next subpath starts where the closed one did:
+        assert_eq!(fine[1], (vec![pos2(0.0, 0.0), pos2(0.0, 20.0)], false));
+
+        // The tolerance follows `pixels_per_point` unless overridden:
+        assert_eq!(path.tolerance_at(4.0), 0.25 * path.tolerance_at(1.0));
+        let overridden = path.clone().tolerance(0.1);
+        assert_eq!(overridden.tolerance_at(4.0), 0.1);
+
+        // …and is scaled together with the path:
+        let mut scaled = overridden;
+        scaled.transform(TSTransform::from_scaling(2.0));
+        assert_eq!(scaled.tolerance_at(4.0), 0.2);
+        assert_eq!(
+            scaled.bounding_rect(),
+            Rect::from_min_max(pos2(0.0, 0.0), pos2(40.0, 40.0))
+        );
+    }
+
+    #[test]
+    fn test_curves_are_flattened_when_tessellating() {
+        let path = PathBuilder::new().move_to(pos2(0.0, 0.0)).cubic_to(
+            pos2(0.0, 50.0),
+            pos2(50.0, 50.0),
+            pos2(50.0, 0.0),
+        );
+        let shape = CurvePathShape::new(
+            path,
+            Color32::TRANSPARENT,
+            PathStroke::new(1.0, Color32::WHITE),
+        );
+        let num_vertices = |pixels_per_point: f32| {
+            let mut tessellator =
+                crate::Tessellator::new(pixels_per_point, Default::default(), [1, 1], vec![]);
+            let mut mesh = crate::Mesh::default();
+            tessellator.tessellate_shape(shape.clone().into(), &mut mesh);
+            mesh.vertices.len()
+        };
+        assert!(num_vertices(1.0) < num_vertices(4.0));
+    }
+}
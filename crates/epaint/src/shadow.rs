@@ -16,6 +16,7 @@ pub struct Shadow {
 
     /// The width of the blur, i.e. the width of the fuzzy penumbra.
     ///
+    /// The penumbra fades out like a Gaussian blur, centered on the edge of the shadow.
     /// A value of 0.0 means a sharp shadow.
     pub blur: f32,
 
@@ -3,9 +3,9 @@
 use std::{any::Any, sync::Arc};
 
 use crate::{
-    stroke::PathStroke,
+    stroke::{LinePattern, PathStroke},
     text::{FontId, Fonts, Galley},
-    Color32, Mesh, Stroke, TextureId,
+    Color32, CurvePathShape, Fill, Mesh, NinePatch, Stroke, TextureId,
 };
 use emath::*;
 
@@ -34,6 +34,9 @@ pub enum Shape {
     /// Ellipse with optional outline and fill.
     Ellipse(EllipseShape),
 
+    /// An arc, pie slice or ring segment of a circle, with optional outline and fill.
+    CircleSegment(CircleSegmentShape),
+
     /// A line between two points.
     LineSegment {
         points: [Pos2; 2],
@@ -57,14 +60,25 @@ pub enum Shape {
     /// Can be used to display images.
     Mesh(Mesh),
 
+    /// A shared [`Mesh`], moved into place and tinted.
+    ///
+    /// Use this to paint the same shape many times without tessellating it again each time.
+    InstancedMesh(InstancedMeshShape),
+
     /// A quadratic [Bézier Curve](https://en.wikipedia.org/wiki/B%C3%A9zier_curve).
     QuadraticBezier(QuadraticBezierShape),
 
     /// A cubic [Bézier Curve](https://en.wikipedia.org/wiki/B%C3%A9zier_curve).
     CubicBezier(CubicBezierShape),
 
+    /// A path of lines and Bézier curves, with a stroke and/or fill.
+    CurvePath(CurvePathShape),
+
     /// Backend-specific painting.
     Callback(PaintCallback),
+
+    /// Blurs what has been painted behind a rectangle, for a frosted-glass look.
+    Backdrop(BackdropShape),
 }
 
 #[test]
@@ -99,6 +113,18 @@ impl Shape {
         }
     }
 
+    /// A line between two points, snapped to the pixel grid and without anti-aliasing feathering.
+    ///
+    /// Use this for thin horizontal and vertical lines that should look sharp, like separators.
+    /// See [`PathStroke::crisp`].
+    #[inline]
+    pub fn line_segment_crisp(points: [Pos2; 2], stroke: impl Into<PathStroke>) -> Self {
+        Self::LineSegment {
+            points,
+            stroke: stroke.into().crisp(),
+        }
+    }
+
     /// A horizontal line.
     pub fn hline(x: impl Into<Rangef>, y: f32, stroke: impl Into<PathStroke>) -> Self {
         let x = x.into();
@@ -253,6 +279,48 @@ impl Shape {
         Self::Ellipse(EllipseShape::stroke(center, radius, stroke))
     }
 
+    /// An arc of a circle from `start_angle` to `end_angle`, in radians.
+    ///
+    /// See [`CircleSegmentShape`] for how angles are measured.
+    #[inline]
+    pub fn arc(
+        center: Pos2,
+        radius: f32,
+        start_angle: f32,
+        end_angle: f32,
+        stroke: impl Into<Stroke>,
+    ) -> Self {
+        Self::CircleSegment(CircleSegmentShape::arc(
+            center,
+            radius,
+            start_angle,
+            end_angle,
+            stroke,
+        ))
+    }
+
+    /// A pie slice of a circle from `start_angle` to `end_angle`, in radians.
+    ///
+    /// See [`CircleSegmentShape`] for how angles are measured.
+    #[inline]
+    pub fn pie(
+        center: Pos2,
+        radius: f32,
+        start_angle: f32,
+        end_angle: f32,
+        fill_color: impl Into<Color32>,
+        stroke: impl Into<Stroke>,
+    ) -> Self {
+        Self::CircleSegment(CircleSegmentShape::pie(
+            center,
+            radius,
+            start_angle,
+            end_angle,
+            fill_color,
+            stroke,
+        ))
+    }
+
     #[inline]
     pub fn rect_filled(
         rect: Rect,
@@ -317,6 +385,15 @@ impl Shape {
         Self::Mesh(mesh)
     }
 
+    /// A shared [`Mesh`], moved by `offset` and with its colors multiplied by `tint`.
+    ///
+    /// This finds the bounds of the mesh, so to paint many instances,
+    /// clone one [`InstancedMeshShape`] instead.
+    #[inline]
+    pub fn mesh_instance(mesh: Arc<Mesh>, offset: Vec2, tint: Color32) -> Self {
+        InstancedMeshShape::new(mesh, offset).with_tint(tint).into()
+    }
+
     /// An image at the given position.
     ///
     /// `uv` should normally be `Rect::from_min_max(pos2(0.0, 0.0), pos2(1.0, 1.0))`
@@ -329,6 +406,33 @@ impl Shape {
         Self::mesh(mesh)
     }
 
+    /// A texture sliced into nine patches to cover `rect`, e.g. a skinned frame.
+    ///
+    /// See [`NinePatch`] and [`Mesh::add_nine_patch`].
+    pub fn image_nine_patch(
+        texture_id: TextureId,
+        rect: Rect,
+        nine_patch: NinePatch,
+        tint: Color32,
+    ) -> Self {
+        let mut mesh = Mesh::with_texture(texture_id);
+        mesh.add_nine_patch(rect, &nine_patch, tint);
+        Self::mesh(mesh)
+    }
+
+    /// Blur what has been painted behind `rect`, then tint it, e.g. for a frosted-glass panel.
+    ///
+    /// See [`BackdropShape`].
+    #[inline]
+    pub fn backdrop(
+        rect: Rect,
+        rounding: impl Into<Rounding>,
+        blur_radius: f32,
+        tint: Color32,
+    ) -> Self {
+        BackdropShape::new(rect, rounding, blur_radius, tint).into()
+    }
+
     /// The visual bounding rectangle (includes stroke widths)
     pub fn visual_bounding_rect(&self) -> Rect {
         match self {
@@ -342,6 +446,7 @@ impl Shape {
             }
             Self::Circle(circle_shape) => circle_shape.visual_bounding_rect(),
             Self::Ellipse(ellipse_shape) => ellipse_shape.visual_bounding_rect(),
+            Self::CircleSegment(segment_shape) => segment_shape.visual_bounding_rect(),
             Self::LineSegment { points, stroke } => {
                 if stroke.is_empty() {
                     Rect::NOTHING
@@ -353,9 +458,12 @@ impl Shape {
             Self::Rect(rect_shape) => rect_shape.visual_bounding_rect(),
             Self::Text(text_shape) => text_shape.visual_bounding_rect(),
             Self::Mesh(mesh) => mesh.calc_bounds(),
+            Self::InstancedMesh(instance) => instance.visual_bounding_rect(),
             Self::QuadraticBezier(bezier) => bezier.visual_bounding_rect(),
             Self::CubicBezier(bezier) => bezier.visual_bounding_rect(),
+            Self::CurvePath(curve_path) => curve_path.visual_bounding_rect(),
             Self::Callback(custom) => custom.rect,
+            Self::Backdrop(backdrop) => backdrop.rect,
         }
     }
 }
@@ -366,6 +474,8 @@ impl Shape {
     pub fn texture_id(&self) -> super::TextureId {
         if let Self::Mesh(mesh) = self {
             mesh.texture_id
+        } else if let Self::InstancedMesh(instance) = self {
+            instance.mesh.texture_id
         } else if let Self::Rect(rect_shape) = self {
             rect_shape.fill_texture_id
         } else {
@@ -405,28 +515,44 @@ impl Shape {
                 circle_shape.center = transform * circle_shape.center;
                 circle_shape.radius *= transform.scaling;
                 circle_shape.stroke.width *= transform.scaling;
+                circle_shape.stroke_pattern.scale(transform.scaling);
             }
             Self::Ellipse(ellipse_shape) => {
                 ellipse_shape.center = transform * ellipse_shape.center;
                 ellipse_shape.radius *= transform.scaling;
                 ellipse_shape.stroke.width *= transform.scaling;
+                ellipse_shape.stroke_pattern.scale(transform.scaling);
+            }
+            Self::CircleSegment(segment_shape) => {
+                segment_shape.center = transform * segment_shape.center;
+                segment_shape.radius *= transform.scaling;
+                if let Some(inner_radius) = &mut segment_shape.inner_radius {
+                    *inner_radius *= transform.scaling;
+                }
+                segment_shape.stroke.width *= transform.scaling;
+                segment_shape.stroke_pattern.scale(transform.scaling);
             }
             Self::LineSegment { points, stroke } => {
                 for p in points {
                     *p = transform * *p;
                 }
                 stroke.width *= transform.scaling;
+                stroke.pattern.scale(transform.scaling);
             }
             Self::Path(path_shape) => {
                 for p in &mut path_shape.points {
                     *p = transform * *p;
                 }
                 path_shape.stroke.width *= transform.scaling;
+                path_shape.stroke.pattern.scale(transform.scaling);
+                path_shape.fill.transform(transform);
             }
             Self::Rect(rect_shape) => {
                 rect_shape.rect = transform * rect_shape.rect;
                 rect_shape.stroke.width *= transform.scaling;
+                rect_shape.stroke_pattern.scale(transform.scaling);
                 rect_shape.rounding *= transform.scaling;
+                rect_shape.fill.transform(transform);
             }
             Self::Text(text_shape) => {
                 text_shape.pos = transform * text_shape.pos;
@@ -434,8 +560,15 @@ impl Shape {
                 // Scale text:
                 let galley = Arc::make_mut(&mut text_shape.galley);
                 for row in &mut galley.rows {
-                    row.visuals.mesh_bounds = transform.scaling * row.visuals.mesh_bounds;
-                    for v in &mut row.visuals.mesh.vertices {
+                    row.rect = transform.scaling * row.rect;
+                    let visuals = Arc::make_mut(&mut row.visuals);
+                    visuals.mesh_bounds = transform.scaling * visuals.mesh_bounds;
+                    for v in visuals
+                        .mesh
+                        .vertices
+                        .iter_mut()
+                        .chain(&mut visuals.sdf_mesh.vertices)
+                    {
                         v.pos = Pos2::new(transform.scaling * v.pos.x, transform.scaling * v.pos.y);
                     }
                 }
@@ -446,21 +579,37 @@ impl Shape {
             Self::Mesh(mesh) => {
                 mesh.transform(transform);
             }
+            Self::InstancedMesh(instance) => {
+                instance.transform = transform * instance.transform;
+            }
             Self::QuadraticBezier(bezier_shape) => {
                 bezier_shape.points[0] = transform * bezier_shape.points[0];
                 bezier_shape.points[1] = transform * bezier_shape.points[1];
                 bezier_shape.points[2] = transform * bezier_shape.points[2];
                 bezier_shape.stroke.width *= transform.scaling;
+                bezier_shape.stroke.pattern.scale(transform.scaling);
             }
             Self::CubicBezier(cubic_curve) => {
                 for p in &mut cubic_curve.points {
                     *p = transform * *p;
                 }
                 cubic_curve.stroke.width *= transform.scaling;
+                cubic_curve.stroke.pattern.scale(transform.scaling);
+            }
+            Self::CurvePath(curve_path) => {
+                curve_path.path.transform(transform);
+                curve_path.stroke.width *= transform.scaling;
+                curve_path.stroke.pattern.scale(transform.scaling);
+                curve_path.fill.transform(transform);
             }
             Self::Callback(shape) => {
                 shape.rect = transform * shape.rect;
             }
+            Self::Backdrop(backdrop) => {
+                backdrop.rect = transform * backdrop.rect;
+                backdrop.rounding *= transform.scaling;
+                backdrop.blur_radius *= transform.scaling;
+            }
         }
     }
 }
@@ -475,6 +624,9 @@ pub struct CircleShape {
     pub radius: f32,
     pub fill: Color32,
     pub stroke: Stroke,
+
+    /// Dashes or dots instead of a solid outline.
+    pub stroke_pattern: LinePattern,
 }
 
 impl CircleShape {
@@ -485,6 +637,7 @@ impl CircleShape {
             radius,
             fill: fill_color.into(),
             stroke: Default::default(),
+            stroke_pattern: LinePattern::SOLID,
         }
     }
 
@@ -495,9 +648,17 @@ impl CircleShape {
             radius,
             fill: Default::default(),
             stroke: stroke.into(),
+            stroke_pattern: LinePattern::SOLID,
         }
     }
 
+    /// Dash or dot the outline, see [`LinePattern`].
+    #[inline]
+    pub fn with_stroke_pattern(mut self, stroke_pattern: LinePattern) -> Self {
+        self.stroke_pattern = stroke_pattern;
+        self
+    }
+
     /// The visual bounding rectangle (includes stroke width)
     pub fn visual_bounding_rect(&self) -> Rect {
         if self.fill == Color32::TRANSPARENT && self.stroke.is_empty() {
@@ -530,6 +691,9 @@ pub struct EllipseShape {
     pub radius: Vec2,
     pub fill: Color32,
     pub stroke: Stroke,
+
+    /// Dashes or dots instead of a solid outline.
+    pub stroke_pattern: LinePattern,
 }
 
 impl EllipseShape {
@@ -540,6 +704,7 @@ impl EllipseShape {
             radius,
             fill: fill_color.into(),
             stroke: Default::default(),
+            stroke_pattern: LinePattern::SOLID,
         }
     }
 
@@ -550,9 +715,17 @@ impl EllipseShape {
             radius,
             fill: Default::default(),
             stroke: stroke.into(),
+            stroke_pattern: LinePattern::SOLID,
         }
     }
 
+    /// Dash or dot the outline, see [`LinePattern`].
+    #[inline]
+    pub fn with_stroke_pattern(mut self, stroke_pattern: LinePattern) -> Self {
+        self.stroke_pattern = stroke_pattern;
+        self
+    }
+
     /// The visual bounding rectangle (includes stroke width)
     pub fn visual_bounding_rect(&self) -> Rect {
         if self.fill == Color32::TRANSPARENT && self.stroke.is_empty() {
@@ -575,6 +748,164 @@ impl From<EllipseShape> for Shape {
 
 // ----------------------------------------------------------------------------
 
+/// How to paint an arc, a pie slice or a ring segment of a circle.
+///
+/// Angles are in radians, measured from the positive x axis (right).
+/// Since y points down on screen, increasing angles go clockwise.
+/// The segment covers the angles from `start_angle` to `end_angle`;
+/// a span of a full turn (`TAU`) or more paints a whole circle or ring.
+///
+/// * With an `inner_radius` the shape is a ring segment, and the stroke outlines it.
+/// * Without an `inner_radius` and with a fill the shape is a pie slice,
+///   and the stroke outlines the arc and the two radii.
+/// * Without an `inner_radius` and without a fill only the arc itself is stroked.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct CircleSegmentShape {
+    pub center: Pos2,
+
+    /// The outer radius.
+    pub radius: f32,
+
+    pub start_angle: f32,
+    pub end_angle: f32,
+
+    /// If set, the segment is a ring between this and [`Self::radius`].
+    pub inner_radius: Option<f32>,
+
+    pub fill: Color32,
+    pub stroke: Stroke,
+
+    /// Dashes or dots instead of a solid outline.
+    pub stroke_pattern: LinePattern,
+}
+
+impl CircleSegmentShape {
+    /// Just the arc, without fill.
+    #[inline]
+    pub fn arc(
+        center: Pos2,
+        radius: f32,
+        start_angle: f32,
+        end_angle: f32,
+        stroke: impl Into<Stroke>,
+    ) -> Self {
+        Self {
+            center,
+            radius,
+            start_angle,
+            end_angle,
+            inner_radius: None,
+            fill: Default::default(),
+            stroke: stroke.into(),
+            stroke_pattern: LinePattern::SOLID,
+        }
+    }
+
+    /// A pie slice, reaching all the way to the center.
+    #[inline]
+    pub fn pie(
+        center: Pos2,
+        radius: f32,
+        start_angle: f32,
+        end_angle: f32,
+        fill_color: impl Into<Color32>,
+        stroke: impl Into<Stroke>,
+    ) -> Self {
+        Self {
+            center,
+            radius,
+            start_angle,
+            end_angle,
+            inner_radius: None,
+            fill: fill_color.into(),
+            stroke: stroke.into(),
+            stroke_pattern: LinePattern::SOLID,
+        }
+    }
+
+    /// A ring segment between `inner_radius` and `radius`, e.g. for a donut chart.
+    #[inline]
+    pub fn ring(
+        center: Pos2,
+        inner_radius: f32,
+        radius: f32,
+        start_angle: f32,
+        end_angle: f32,
+        fill_color: impl Into<Color32>,
+        stroke: impl Into<Stroke>,
+    ) -> Self {
+        Self {
+            center,
+            radius,
+            start_angle,
+            end_angle,
+            inner_radius: Some(inner_radius),
+            fill: fill_color.into(),
+            stroke: stroke.into(),
+            stroke_pattern: LinePattern::SOLID,
+        }
+    }
+
+    /// Dash or dot the outline, see [`LinePattern`].
+    #[inline]
+    pub fn with_stroke_pattern(mut self, stroke_pattern: LinePattern) -> Self {
+        self.stroke_pattern = stroke_pattern;
+        self
+    }
+
+    /// Does this cover a full turn?
+    #[inline]
+    pub fn is_full_circle(&self) -> bool {
+        (self.end_angle - self.start_angle).abs() >= std::f32::consts::TAU
+    }
+
+    /// The visual bounding rectangle (includes stroke width)
+    pub fn visual_bounding_rect(&self) -> Rect {
+        if self.fill == Color32::TRANSPARENT && self.stroke.is_empty() {
+            return Rect::NOTHING;
+        }
+        if self.is_full_circle() {
+            return Rect::from_center_size(
+                self.center,
+                Vec2::splat(self.radius * 2.0 + self.stroke.width),
+            );
+        }
+
+        let start = self.start_angle.min(self.end_angle);
+        let end = self.start_angle.max(self.end_angle);
+        let point_at = |radius: f32, angle: f32| self.center + radius * Vec2::angled(angle);
+
+        let mut rect = Rect::from_two_pos(point_at(self.radius, start), point_at(self.radius, end));
+
+        // The arc bulges out at each quarter turn it passes:
+        let quarter = std::f32::consts::FRAC_PI_2;
+        let mut angle = (start / quarter).ceil() * quarter;
+        while angle < end {
+            rect.extend_with(point_at(self.radius, angle));
+            angle += quarter;
+        }
+
+        if let Some(inner_radius) = self.inner_radius {
+            rect.extend_with(point_at(inner_radius, start));
+            rect.extend_with(point_at(inner_radius, end));
+        } else if self.fill != Color32::TRANSPARENT {
+            rect.extend_with(self.center);
+        }
+
+        rect.expand(self.stroke.width / 2.0)
+    }
+}
+
+impl From<CircleSegmentShape> for Shape {
+    #[inline(always)]
+    fn from(shape: CircleSegmentShape) -> Self {
+        Self::CircleSegment(shape)
+    }
+}
+
+// ----------------------------------------------------------------------------
+
 /// A path which can be stroked and/or filled (if closed).
 #[derive(Clone, Debug, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
@@ -587,7 +918,9 @@ pub struct PathShape {
     pub closed: bool,
 
     /// Fill is only supported for convex polygons.
-    pub fill: Color32,
+    ///
+    /// Use a [`crate::CurvePathShape`] to fill concave or self-intersecting paths.
+    pub fill: Fill,
 
     /// Color and thickness of the line.
     pub stroke: PathStroke,
@@ -632,15 +965,24 @@ impl PathShape {
         Self {
             points,
             closed: true,
-            fill: fill.into(),
+            fill: Fill::Solid(fill.into()),
             stroke: stroke.into(),
         }
     }
 
+    /// Fill the path with a solid color or a gradient.
+    ///
+    /// Fill is only supported for closed convex polygons, see [`Self::fill`].
+    #[inline]
+    pub fn with_fill(mut self, fill: impl Into<Fill>) -> Self {
+        self.fill = fill.into();
+        self
+    }
+
     /// The visual bounding rectangle (includes stroke width)
     #[inline]
     pub fn visual_bounding_rect(&self) -> Rect {
-        if self.fill == Color32::TRANSPARENT && self.stroke.is_empty() {
+        if self.fill.is_transparent() && self.stroke.is_empty() {
             Rect::NOTHING
         } else {
             Rect::from_points(&self.points).expand(self.stroke.width / 2.0)
@@ -658,26 +1000,34 @@ impl From<PathShape> for Shape {
 // ----------------------------------------------------------------------------
 
 /// How to paint a rectangle.
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub struct RectShape {
     pub rect: Rect,
 
     /// How rounded the corners are. Use `Rounding::ZERO` for no rounding.
+    ///
+    /// If the radii along a side add up to more than the length of that side,
+    /// all of them are scaled down by the same factor.
     pub rounding: Rounding,
 
-    /// How to fill the rectangle.
-    pub fill: Color32,
+    /// How to fill the rectangle: with a solid color or a gradient.
+    pub fill: Fill,
 
     /// The thickness and color of the outline.
     pub stroke: Stroke,
 
+    /// Dashes or dots instead of a solid outline.
+    pub stroke_pattern: LinePattern,
+
     /// If larger than zero, the edges of the rectangle
     /// (for both fill and stroke) will be blurred.
     ///
     /// This can be used to produce shadows and glow effects.
     ///
-    /// The blur is currently implemented using a simple linear blur in sRGBA gamma space.
+    /// The fill is blurred with an approximation of a Gaussian blur, spanning `blur_width`
+    /// centered on the edge. The stroke (and any textured or gradient fill) gets a simple linear blur.
+    /// Both blurs are done in sRGBA gamma space.
     pub blur_width: f32,
 
     /// If the rect should be filled with a texture, which one?
@@ -705,8 +1055,9 @@ impl RectShape {
         Self {
             rect,
             rounding: rounding.into(),
-            fill: fill_color.into(),
+            fill: Fill::Solid(fill_color.into()),
             stroke: stroke.into(),
+            stroke_pattern: LinePattern::SOLID,
             blur_width: 0.0,
             fill_texture_id: Default::default(),
             uv: Rect::ZERO,
@@ -722,8 +1073,9 @@ impl RectShape {
         Self {
             rect,
             rounding: rounding.into(),
-            fill: fill_color.into(),
+            fill: Fill::Solid(fill_color.into()),
             stroke: Default::default(),
+            stroke_pattern: LinePattern::SOLID,
             blur_width: 0.0,
             fill_texture_id: Default::default(),
             uv: Rect::ZERO,
@@ -737,6 +1089,7 @@ impl RectShape {
             rounding: rounding.into(),
             fill: Default::default(),
             stroke: stroke.into(),
+            stroke_pattern: LinePattern::SOLID,
             blur_width: 0.0,
             fill_texture_id: Default::default(),
             uv: Rect::ZERO,
@@ -748,17 +1101,31 @@ impl RectShape {
     ///
     /// This can be used to produce shadows and glow effects.
     ///
-    /// The blur is currently implemented using a simple linear blur in `sRGBA` gamma space.
+    /// See [`Self::blur_width`].
     #[inline]
     pub fn with_blur_width(mut self, blur_width: f32) -> Self {
         self.blur_width = blur_width;
         self
     }
 
+    /// Dash or dot the outline, see [`LinePattern`].
+    #[inline]
+    pub fn with_stroke_pattern(mut self, stroke_pattern: LinePattern) -> Self {
+        self.stroke_pattern = stroke_pattern;
+        self
+    }
+
+    /// Fill the rectangle with a solid color or a gradient.
+    #[inline]
+    pub fn with_fill(mut self, fill: impl Into<Fill>) -> Self {
+        self.fill = fill.into();
+        self
+    }
+
     /// The visual bounding rectangle (includes stroke width)
     #[inline]
     pub fn visual_bounding_rect(&self) -> Rect {
-        if self.fill == Color32::TRANSPARENT && self.stroke.is_empty() {
+        if self.fill.is_transparent() && self.stroke.is_empty() {
             Rect::NOTHING
         } else {
             self.rect
@@ -829,6 +1196,50 @@ impl Rounding {
         }
     }
 
+    /// Round only the top corners, e.g. for a tab.
+    #[inline]
+    pub const fn top(radius: f32) -> Self {
+        Self {
+            nw: radius,
+            ne: radius,
+            sw: 0.0,
+            se: 0.0,
+        }
+    }
+
+    /// Round only the bottom corners.
+    #[inline]
+    pub const fn bottom(radius: f32) -> Self {
+        Self {
+            nw: 0.0,
+            ne: 0.0,
+            sw: radius,
+            se: radius,
+        }
+    }
+
+    /// Round only the left corners.
+    #[inline]
+    pub const fn left(radius: f32) -> Self {
+        Self {
+            nw: radius,
+            ne: 0.0,
+            sw: radius,
+            se: 0.0,
+        }
+    }
+
+    /// Round only the right corners.
+    #[inline]
+    pub const fn right(radius: f32) -> Self {
+        Self {
+            nw: 0.0,
+            ne: radius,
+            sw: 0.0,
+            se: radius,
+        }
+    }
+
     /// Do all corners have the same rounding?
     #[inline]
     pub fn is_same(&self) -> bool {
@@ -1039,7 +1450,11 @@ impl TextShape {
     /// The visual bounding rectangle
     #[inline]
     pub fn visual_bounding_rect(&self) -> Rect {
-        self.galley.mesh_bounds.translate(self.pos.to_vec2())
+        let mut bounds = self.galley.mesh_bounds;
+        if self.angle != 0.0 {
+            bounds = bounds.rotate_bb(Rot2::from_angle(self.angle));
+        }
+        bounds.translate(self.pos.to_vec2())
     }
 
     #[inline]
@@ -1080,6 +1495,176 @@ impl From<TextShape> for Shape {
 
 // ----------------------------------------------------------------------------
 
+/// A [`Mesh`] that is tessellated once and then painted in many places.
+///
+/// Only the [`Arc`] is cloned for each instance;
+/// the vertices are moved into place as the tessellator appends them to its output,
+/// which is much cheaper than tessellating the same shape over and over.
+///
+/// The bounds of the mesh are computed once, in [`Self::new`],
+/// so create the instances by cloning one [`InstancedMeshShape`] and moving it with [`Self::with_offset`].
+///
+/// ```
+/// # use std::sync::Arc;
+/// # use epaint::{pos2, vec2, Color32, InstancedMeshShape, Mesh, Rect, RectShape, Shape, Tessellator};
+/// let mut tessellator = Tessellator::new(1.0, Default::default(), [1024, 1024], vec![]);
+/// let mut mesh = Mesh::default();
+/// let node = Rect::from_min_size(pos2(0.0, 0.0), vec2(80.0, 40.0));
+/// tessellator.tessellate_shape(RectShape::filled(node, 4.0, Color32::WHITE).into(), &mut mesh);
+///
+/// let instance = InstancedMeshShape::new(Arc::new(mesh), vec2(0.0, 0.0));
+/// let shapes: Vec<Shape> = (0..100)
+///     .map(|i| {
+///         instance
+///             .clone()
+///             .with_offset(vec2(0.0, 50.0 * i as f32))
+///             .with_tint(Color32::LIGHT_BLUE)
+///             .into()
+///     })
+///     .collect();
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InstancedMeshShape {
+    /// The shared mesh, in its own coordinates.
+    pub mesh: Arc<Mesh>,
+
+    /// The bounds of [`Self::mesh`], in its own coordinates.
+    ///
+    /// Used for culling, so it must contain the whole mesh.
+    pub mesh_bounds: Rect,
+
+    /// Where to put the mesh.
+    pub transform: TSTransform,
+
+    /// Multiplies the colors of all vertices.
+    /// [`Color32::WHITE`] leaves them as they are.
+    pub tint: Color32,
+}
+
+impl InstancedMeshShape {
+    /// The mesh moved by `offset`, untinted.
+    ///
+    /// This goes through all the vertices to find the bounds of the mesh.
+    #[inline]
+    pub fn new(mesh: Arc<Mesh>, offset: Vec2) -> Self {
+        Self {
+            mesh_bounds: mesh.calc_bounds(),
+            mesh,
+            transform: TSTransform::from_translation(offset),
+            tint: Color32::WHITE,
+        }
+    }
+
+    /// Move the mesh by `offset` instead, keeping the bounds that are already known.
+    #[inline]
+    pub fn with_offset(mut self, offset: Vec2) -> Self {
+        self.transform = TSTransform::from_translation(offset);
+        self
+    }
+
+    /// Multiply the colors of all vertices by this.
+    #[inline]
+    pub fn with_tint(mut self, tint: Color32) -> Self {
+        self.tint = tint;
+        self
+    }
+
+    /// The visual bounding rectangle
+    #[inline]
+    pub fn visual_bounding_rect(&self) -> Rect {
+        self.transform * self.mesh_bounds
+    }
+}
+
+impl From<InstancedMeshShape> for Shape {
+    #[inline(always)]
+    fn from(shape: InstancedMeshShape) -> Self {
+        Self::InstancedMesh(shape)
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+/// Blurs what has been painted behind a rectangle, and paints a tint on top,
+/// for the frosted-glass look of floating panels.
+///
+/// The blurring is done by the backend, so it only happens if
+/// [`crate::TessellationOptions::backdrop_blur`] says the backend supports it
+/// (`egui_glow` and `egui-wgpu` both do).
+/// Otherwise the backdrop is painted as [`Self::fallback`], a mostly opaque fill.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct BackdropShape {
+    pub rect: Rect,
+
+    /// How rounded the corners are.
+    pub rounding: Rounding,
+
+    /// The standard deviation of the Gaussian blur, in points.
+    pub blur_radius: f32,
+
+    /// Painted on top of the blurred backdrop.
+    ///
+    /// Use a translucent color, or nothing of the backdrop will be visible.
+    pub tint: Color32,
+}
+
+impl BackdropShape {
+    /// Painted by [`Self::fallback`] at least this opaque,
+    /// since what's behind it is not blurred.
+    pub const FALLBACK_MIN_ALPHA: u8 = 220;
+
+    #[inline]
+    pub fn new(rect: Rect, rounding: impl Into<Rounding>, blur_radius: f32, tint: Color32) -> Self {
+        Self {
+            rect,
+            rounding: rounding.into(),
+            blur_radius,
+            tint,
+        }
+    }
+
+    /// What is painted instead when the backend can't blur:
+    /// the tint, but at least [`Self::FALLBACK_MIN_ALPHA`] opaque.
+    pub fn fallback(&self) -> RectShape {
+        let [r, g, b, a] = self.tint.to_srgba_unmultiplied();
+        let fill = Color32::from_rgba_unmultiplied(r, g, b, a.max(Self::FALLBACK_MIN_ALPHA));
+        RectShape::filled(self.rect, self.rounding, fill)
+    }
+}
+
+impl From<BackdropShape> for Shape {
+    #[inline(always)]
+    fn from(shape: BackdropShape) -> Self {
+        Self::Backdrop(shape)
+    }
+}
+
+/// A [`BackdropShape`] for the backend to blur, see [`crate::Primitive::Backdrop`].
+///
+/// Tessellated by [`crate::Tessellator`] when [`crate::TessellationOptions::backdrop_blur`] is set.
+/// The tint is not part of this, but follows as a separate mesh.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Backdrop {
+    /// The area to blur, in points.
+    pub rect: Rect,
+
+    /// The standard deviation of the Gaussian blur, in points.
+    ///
+    /// To blur the edges of [`Self::rect`] properly,
+    /// the backend needs to read about three times this much around it.
+    pub blur_radius: f32,
+
+    /// Where to paint the blurred backdrop, already cut to the rounded corners and clip rect.
+    ///
+    /// The uv:s go from (0, 0) to (1, 1) over [`Self::rect`],
+    /// and the backend should paint this with the blurred backdrop as its texture,
+    /// instead of [`Mesh::texture_id`].
+    pub mesh: Mesh,
+}
+
+// ----------------------------------------------------------------------------
+
 /// Creates equally spaced filled circles from a line.
 fn points_from_line(
     path: &[Pos2],
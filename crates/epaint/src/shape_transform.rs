@@ -26,8 +26,12 @@ pub fn adjust_colors(
             closed: _,
             fill,
             stroke,
-        })
-        | Shape::QuadraticBezier(QuadraticBezierShape {
+        }) => {
+            fill.adjust_colors(adjust_color);
+            adjust_color_mode(&mut stroke.color, adjust_color);
+        }
+
+        Shape::QuadraticBezier(QuadraticBezierShape {
             points: _,
             closed: _,
             fill,
@@ -43,28 +47,54 @@ pub fn adjust_colors(
             adjust_color_mode(&mut stroke.color, adjust_color);
         }
 
+        Shape::CurvePath(CurvePathShape {
+            path: _,
+            fill,
+            stroke,
+        }) => {
+            fill.adjust_colors(adjust_color);
+            adjust_color_mode(&mut stroke.color, adjust_color);
+        }
+
         Shape::Circle(CircleShape {
             center: _,
             radius: _,
             fill,
             stroke,
+            stroke_pattern: _,
         })
         | Shape::Ellipse(EllipseShape {
             center: _,
             radius: _,
             fill,
             stroke,
+            stroke_pattern: _,
         })
-        | Shape::Rect(RectShape {
+        | Shape::CircleSegment(CircleSegmentShape {
+            center: _,
+            radius: _,
+            start_angle: _,
+            end_angle: _,
+            inner_radius: _,
+            fill,
+            stroke,
+            stroke_pattern: _,
+        }) => {
+            adjust_color(fill);
+            adjust_color(&mut stroke.color);
+        }
+
+        Shape::Rect(RectShape {
             rect: _,
             rounding: _,
             fill,
             stroke,
+            stroke_pattern: _,
             blur_width: _,
             fill_texture_id: _,
             uv: _,
         }) => {
-            adjust_color(fill);
+            fill.adjust_colors(adjust_color);
             adjust_color(&mut stroke.color);
         }
 
@@ -86,7 +116,11 @@ pub fn adjust_colors(
             if !galley.is_empty() {
                 let galley = std::sync::Arc::make_mut(galley);
                 for row in &mut galley.rows {
-                    for vertex in &mut row.visuals.mesh.vertices {
+                    let visuals = std::sync::Arc::make_mut(&mut row.visuals);
+                    for vertex in &mut visuals.mesh.vertices {
+                        adjust_color(&mut vertex.color);
+                    }
+                    for vertex in &mut visuals.sdf_mesh.vertices {
                         adjust_color(&mut vertex.color);
                     }
                 }
@@ -103,9 +137,26 @@ pub fn adjust_colors(
             }
         }
 
+        Shape::InstancedMesh(instance) => {
+            // The tint can't express every color adjustment, so this instance gets a mesh of its own:
+            let mut mesh = (*instance.mesh).clone();
+            mesh.transform(instance.transform);
+            for v in &mut mesh.vertices {
+                if instance.tint != Color32::WHITE {
+                    v.color = crate::fill::multiply_colors(v.color, instance.tint);
+                }
+                adjust_color(&mut v.color);
+            }
+            *shape = Shape::Mesh(mesh);
+        }
+
         Shape::Callback(_) => {
             // Can't tint user callback code
         }
+
+        Shape::Backdrop(backdrop) => {
+            adjust_color(&mut backdrop.tint);
+        }
     }
 }
 
@@ -92,7 +92,9 @@ impl AllocInfo {
     }
 
     fn from_galley_row(row: &crate::text::Row) -> Self {
-        Self::from_mesh(&row.visuals.mesh) + Self::from_slice(&row.glyphs)
+        Self::from_mesh(&row.visuals.mesh)
+            + Self::from_mesh(&row.visuals.sdf_mesh)
+            + Self::from_slice(&row.glyphs)
     }
 
     pub fn from_mesh(mesh: &Mesh) -> Self {
@@ -202,24 +204,34 @@ impl PaintStats {
             Shape::Noop
             | Shape::Circle { .. }
             | Shape::Ellipse { .. }
+            | Shape::CircleSegment(_)
             | Shape::LineSegment { .. }
             | Shape::Rect { .. }
             | Shape::CubicBezier(_)
-            | Shape::QuadraticBezier(_) => {}
+            | Shape::QuadraticBezier(_)
+            | Shape::CurvePath(_)
+            | Shape::Backdrop(_) => {}
             Shape::Path(path_shape) => {
                 self.shape_path += AllocInfo::from_slice(&path_shape.points);
             }
+
             Shape::Text(text_shape) => {
                 self.shape_text += AllocInfo::from_galley(&text_shape.galley);
 
                 for row in &text_shape.galley.rows {
                     self.text_shape_indices += AllocInfo::from_slice(&row.visuals.mesh.indices);
                     self.text_shape_vertices += AllocInfo::from_slice(&row.visuals.mesh.vertices);
+                    self.text_shape_indices += AllocInfo::from_slice(&row.visuals.sdf_mesh.indices);
+                    self.text_shape_vertices +=
+                        AllocInfo::from_slice(&row.visuals.sdf_mesh.vertices);
                 }
             }
             Shape::Mesh(mesh) => {
                 self.shape_mesh += AllocInfo::from_mesh(mesh);
             }
+            Shape::InstancedMesh(instance) => {
+                self.shape_mesh += AllocInfo::from_mesh(&instance.mesh);
+            }
             Shape::Callback(_) => {
                 self.num_callbacks += 1;
             }
@@ -232,7 +244,9 @@ impl PaintStats {
     ) -> Self {
         self.clipped_primitives += AllocInfo::from_slice(clipped_primitives);
         for clipped_primitive in clipped_primitives {
-            if let Primitive::Mesh(mesh) = &clipped_primitive.primitive {
+            if let Primitive::Mesh(mesh) | Primitive::Backdrop(Backdrop { mesh, .. }) =
+                &clipped_primitive.primitive
+            {
                 self.vertices += AllocInfo::from_slice(&mesh.vertices);
                 self.indices += AllocInfo::from_slice(&mesh.indices);
             }
@@ -63,6 +63,17 @@ impl std::hash::Hash for Stroke {
 pub struct PathStroke {
     pub width: f32,
     pub color: ColorMode,
+
+    /// Dashes, dots or a solid line.
+    pub pattern: LinePattern,
+
+    /// Snap the line to the pixel grid and skip the anti-aliasing feathering,
+    /// so that thin horizontal and vertical lines are sharp instead of smeared across two pixels.
+    ///
+    /// The width is rounded to whole physical pixels (at least one).
+    /// Diagonal and curved lines will look jagged, so only use this for axis-aligned lines,
+    /// like separators and grid lines.
+    pub crisp: bool,
 }
 
 impl PathStroke {
@@ -70,6 +81,8 @@ impl PathStroke {
     pub const NONE: Self = Self {
         width: 0.0,
         color: ColorMode::TRANSPARENT,
+        pattern: LinePattern::SOLID,
+        crisp: false,
     };
 
     #[inline]
@@ -77,6 +90,8 @@ impl PathStroke {
         Self {
             width: width.into(),
             color: ColorMode::Solid(color.into()),
+            pattern: LinePattern::SOLID,
+            crisp: false,
         }
     }
 
@@ -91,9 +106,25 @@ impl PathStroke {
         Self {
             width: width.into(),
             color: ColorMode::UV(Arc::new(callback)),
+            pattern: LinePattern::SOLID,
+            crisp: false,
         }
     }
 
+    /// Draw the path with dashes or dots instead of a solid line.
+    #[inline]
+    pub fn with_pattern(mut self, pattern: LinePattern) -> Self {
+        self.pattern = pattern;
+        self
+    }
+
+    /// Snap the line to the pixel grid and skip the feathering. See [`Self::crisp`].
+    #[inline]
+    pub fn crisp(mut self) -> Self {
+        self.crisp = true;
+        self
+    }
+
     /// True if width is zero or color is solid and transparent
     #[inline]
     pub fn is_empty(&self) -> bool {
@@ -116,6 +147,85 @@ impl From<Stroke> for PathStroke {
         Self {
             width: value.width,
             color: ColorMode::Solid(value.color),
+            pattern: LinePattern::SOLID,
+            crisp: false,
+        }
+    }
+}
+
+/// The dashes of a [`PathStroke`], or of the outline of a rectangle, circle, ellipse or circle segment,
+/// following the length of the path around corners and curves.
+///
+/// To get "marching ants" around a selection, advance [`Self::dash_offset`] a bit each frame.
+///
+/// ```
+/// # use epaint::{pos2, Color32, LinePattern, PathStroke, Rect, RectShape, Shape};
+/// let rect = Rect::from_min_max(pos2(10.0, 10.0), pos2(100.0, 50.0));
+/// let outline = RectShape::stroke(rect, 2.0, (1.0, Color32::WHITE))
+///     .with_stroke_pattern(LinePattern::dashed(4.0, 2.0));
+///
+/// let stroke = PathStroke::new(1.0, Color32::WHITE).with_pattern(LinePattern::dotted(1.0, 2.0));
+/// let diagonal = Shape::line(vec![rect.left_top(), rect.right_bottom()], stroke);
+/// ```
+///
+/// The default pattern is the same as [`LinePattern::SOLID`].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct LinePattern {
+    /// The lengths of a dash, a gap, another dash and another gap, in points.
+    ///
+    /// Repeat the first two for plain dashes, so `[4.0, 2.0, 4.0, 2.0]` (see [`Self::dashed`]),
+    /// or use e.g. `[6.0, 2.0, 1.0, 2.0]` for dash-dot.
+    /// The pattern is solid if these are all zero.
+    ///
+    /// The dashes get the same (square) ends as any open path,
+    /// so make dots about as long as the stroke is wide.
+    pub dash_lengths: [f32; 4],
+
+    /// How far into the pattern the start of the path is, in points.
+    pub dash_offset: f32,
+}
+
+impl LinePattern {
+    /// A solid line.
+    pub const SOLID: Self = Self {
+        dash_lengths: [0.0; 4],
+        dash_offset: 0.0,
+    };
+
+    /// Dashes of the given length, with gaps of the given length between them.
+    #[inline]
+    pub fn dashed(dash_length: f32, gap_length: f32) -> Self {
+        Self {
+            dash_lengths: [dash_length, gap_length, dash_length, gap_length],
+            dash_offset: 0.0,
+        }
+    }
+
+    /// Dots as long as `width` (use the stroke width for square dots), with gaps of the given length between them.
+    #[inline]
+    pub fn dotted(width: f32, gap_length: f32) -> Self {
+        Self::dashed(width, gap_length)
+    }
+
+    /// Start this far into the pattern.
+    #[inline]
+    pub fn with_offset(mut self, dash_offset: f32) -> Self {
+        self.dash_offset = dash_offset;
+        self
+    }
+
+    /// Is this a plain solid line?
+    #[inline]
+    pub fn is_solid(&self) -> bool {
+        self.dash_lengths.iter().all(|&length| length <= 0.0)
+    }
+
+    /// Scale the lengths, e.g. when the path is scaled.
+    pub(crate) fn scale(&mut self, factor: f32) {
+        for length in &mut self.dash_lengths {
+            *length *= factor;
         }
+        self.dash_offset *= factor;
     }
 }
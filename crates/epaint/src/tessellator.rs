@@ -502,6 +502,17 @@ impl Path {
         fill_closed_path(feathering, &mut self.0, color, out);
     }
 
+    /// Like [`Self::fill`], but for any closed path,
+    /// including concave and self-intersecting ones.
+    ///
+    /// Uses the even-odd rule: a point is inside if a ray from it crosses the path an odd number of times,
+    /// so the regions where a path overlaps itself are left empty (like the center of a pentagram).
+    ///
+    /// This is slower than [`Self::fill`], so prefer that for convex paths.
+    pub fn fill_even_odd(&self, feathering: f32, color: Color32, out: &mut Mesh) {
+        fill_closed_path_even_odd(feathering, &self.0, color, out);
+    }
+
     /// Like [`Self::fill`] but with texturing.
     ///
     /// The `uv_from_pos` is called for each vertex position.
@@ -619,12 +630,23 @@ pub mod path {
         }
     }
 
-    // Ensures the radius of each corner is within a valid range
-    fn clamp_rounding(rounding: Rounding, rect: Rect) -> Rounding {
-        let half_width = rect.width() * 0.5;
-        let half_height = rect.height() * 0.5;
-        let max_cr = half_width.min(half_height);
-        rounding.at_most(max_cr).at_least(0.0)
+    // Ensures the radius of each corner is within a valid range.
+    //
+    // Like in CSS: if the radii along any side add up to more than the length of that side,
+    // all radii are scaled down by the same factor, so that the corners keep their proportions.
+    pub(crate) fn clamp_rounding(rounding: Rounding, rect: Rect) -> Rounding {
+        let r = rounding.at_least(0.0);
+        let (width, height) = (rect.width(), rect.height());
+        let scale = [
+            (width, r.nw + r.ne),
+            (width, r.sw + r.se),
+            (height, r.nw + r.sw),
+            (height, r.ne + r.se),
+        ]
+        .into_iter()
+        .filter(|&(_, sum)| 0.0 < sum)
+        .fold(1.0_f32, |scale, (length, sum)| scale.min(length / sum));
+        r * scale
     }
 }
 
@@ -694,6 +716,12 @@ pub struct TessellationOptions {
     ///
     /// The default is `false` to save performance.
     pub validate_meshes: bool,
+
+    /// If `true`, each [`Shape::Backdrop`] becomes a [`Primitive::Backdrop`] for the backend to blur.
+    ///
+    /// Only turn this on if your backend supports it (`egui_glow` and `egui-wgpu` do).
+    /// If `false` (default), backdrops are painted as [`BackdropShape::fallback`] instead.
+    pub backdrop_blur: bool,
 }
 
 impl Default for TessellationOptions {
@@ -711,6 +739,7 @@ impl Default for TessellationOptions {
             epsilon: 1.0e-5,
             parallel_tessellation: true,
             validate_meshes: false,
+            backdrop_blur: false,
         }
     }
 }
@@ -785,6 +814,241 @@ fn fill_closed_path(feathering: f32, path: &mut [PathPoint], color: Color32, out
     }
 }
 
+/// Split a convex polygon along a line,
+/// into the part where `normal · pos <= offset` and the part where it isn't.
+fn split_polygon(polygon: &[Vertex], normal: Vec2, offset: f32) -> (Vec<Vertex>, Vec<Vertex>) {
+    let mut inside = vec![];
+    let mut outside = vec![];
+    for (a, b) in polygon.iter().zip(polygon.iter().cycle().skip(1)) {
+        let distance_a = normal.dot(a.pos.to_vec2()) - offset;
+        let distance_b = normal.dot(b.pos.to_vec2()) - offset;
+        if distance_a <= 0.0 {
+            inside.push(*a);
+        } else {
+            outside.push(*a);
+        }
+        if (distance_a <= 0.0) != (distance_b <= 0.0) {
+            let crossing = crate::fill::lerp_vertex(a, b, distance_a / (distance_a - distance_b));
+            inside.push(crossing);
+            outside.push(crossing);
+        }
+    }
+    (inside, outside)
+}
+
+/// The top and bottom end of an edge.
+fn edge_top_bottom((a, b): (Pos2, Pos2)) -> (Pos2, Pos2) {
+    if a.y < b.y {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
+
+/// Where an edge that isn't horizontal is at height `y`.
+fn edge_pos_at(edge: (Pos2, Pos2), y: f32) -> Pos2 {
+    let (top, bottom) = edge_top_bottom(edge);
+    if y <= top.y {
+        top
+    } else if bottom.y <= y {
+        bottom
+    } else {
+        let t = (y - top.y) / (bottom.y - top.y);
+        pos2(top.x + t * (bottom.x - top.x), y)
+    }
+}
+
+/// The normal of an edge that isn't horizontal, pointing away from the side that is inside.
+fn outward_normal((a, b): (Pos2, Pos2), inside_is_right: bool) -> Vec2 {
+    let normal = (b - a).normalized().rot90();
+    if (normal.x < 0.0) == inside_is_right {
+        normal
+    } else {
+        -normal
+    }
+}
+
+/// Fade out from the line between `points` towards `outward`.
+fn add_feather(feathering: f32, [a, b]: [Pos2; 2], outward: Vec2, color: Color32, out: &mut Mesh) {
+    let idx = out.vertices.len() as u32;
+    out.colored_vertex(a, color);
+    out.colored_vertex(b, color);
+    out.colored_vertex(b + feathering * outward, Color32::TRANSPARENT);
+    out.colored_vertex(a + feathering * outward, Color32::TRANSPARENT);
+    out.add_triangle(idx, idx + 1, idx + 2);
+    out.add_triangle(idx, idx + 2, idx + 3);
+}
+
+/// Tessellate any closed polygon, filling it with the even-odd rule.
+///
+/// A line sweeps down over the polygon, stopping at every corner and every self-intersection.
+/// Between two stops no edges start, end or cross, so the edges can be sorted left to right,
+/// and every other gap between them is inside.
+///
+/// Only the edges crossing the sweep line are looked at, so this is fast unless the polygon is
+/// very jagged, with many edges side by side.
+fn fill_closed_path_even_odd(feathering: f32, path: &[PathPoint], color: Color32, out: &mut Mesh) {
+    if color == Color32::TRANSPARENT || path.len() < 3 {
+        return;
+    }
+
+    let edges: Vec<(Pos2, Pos2)> = path
+        .iter()
+        .zip(path.iter().cycle().skip(1))
+        .map(|(a, b)| (a.pos, b.pos))
+        .filter(|(a, b)| a != b)
+        .collect();
+
+    let mut corner_ys: Vec<f32> = edges.iter().map(|(a, _)| a.y).collect();
+    corner_ys.sort_by(f32::total_cmp);
+    corner_ys.dedup();
+
+    let mut edges_by_top: Vec<usize> = (0..edges.len()).collect();
+    edges_by_top.sort_by(|&i, &j| {
+        let top_y = |i: usize| edge_top_bottom(edges[i]).0.y;
+        top_y(i).total_cmp(&top_y(j))
+    });
+    let mut next_edge = 0;
+
+    // The edges crossing the sweep line, and the horizontal edges at it:
+    let mut active: Vec<usize> = vec![];
+    let mut horizontal: Vec<usize> = vec![];
+
+    // The outward normal at the start and end of each edge, for rounding the corners,
+    // and where the inside of each active edge last changed sides:
+    let mut outwards = vec![[Vec2::ZERO; 2]; edges.len()];
+    let mut feather_starts: Vec<Option<(f32, bool)>> = vec![None; edges.len()];
+    let mut feather = |i: usize, [from, to]: [Pos2; 2], outward: Vec2, out: &mut Mesh| {
+        if 0.0 < feathering {
+            let (a, b) = edges[i];
+            if from.y == a.y || to.y == a.y {
+                outwards[i][0] = outward;
+            }
+            if from.y == b.y || to.y == b.y {
+                outwards[i][1] = outward;
+            }
+            add_feather(feathering, [from, to], outward, color, out);
+        }
+    };
+
+    for (corner_index, &y) in corner_ys.iter().enumerate() {
+        active.retain(|&i| {
+            let bottom = edge_top_bottom(edges[i]).1;
+            if y < bottom.y {
+                return true;
+            }
+            if let Some((start_y, inside_is_right)) = feather_starts[i].take() {
+                let outward = outward_normal(edges[i], inside_is_right);
+                feather(i, [edge_pos_at(edges[i], start_y), bottom], outward, out);
+            }
+            false
+        });
+
+        while let Some(&i) = edges_by_top.get(next_edge) {
+            let (top, bottom) = edge_top_bottom(edges[i]);
+            if y < top.y {
+                break;
+            }
+            next_edge += 1;
+            if top.y < bottom.y {
+                active.push(i);
+            } else {
+                horizontal.push(i);
+            }
+        }
+
+        let next_y = corner_ys.get(corner_index + 1).copied();
+
+        // By the even-odd rule, a horizontal edge has the inside either right above or right below it:
+        for i in horizontal.drain(..) {
+            let (a, b) = edges[i];
+            let inside_is_below = next_y.is_some_and(|next_y| {
+                let below = pos2(0.5 * (a.x + b.x), 0.5 * (y + next_y));
+                let edges_to_the_left = active
+                    .iter()
+                    .filter(|&&j| edge_pos_at(edges[j], below.y).x < below.x)
+                    .count();
+                edges_to_the_left % 2 == 1
+            });
+            let outward = if inside_is_below { -Vec2::Y } else { Vec2::Y };
+            feather(i, edges[i].into(), outward, out);
+        }
+
+        let Some(next_y) = next_y else {
+            break;
+        };
+
+        let x_at = |i: usize, y: f32| edge_pos_at(edges[i], y).x;
+        let mut y_start = y;
+        while y_start < next_y {
+            active.sort_by(|&i, &j| {
+                x_at(i, y_start)
+                    .total_cmp(&x_at(j, y_start))
+                    .then(x_at(i, next_y).total_cmp(&x_at(j, next_y)))
+            });
+
+            // Any edges crossing before `next_y` are next to each other, so stop at the first such crossing:
+            let mut y_end = next_y;
+            for pair in active.windows(2) {
+                let start_gap = x_at(pair[1], y_start) - x_at(pair[0], y_start);
+                let end_gap = x_at(pair[1], next_y) - x_at(pair[0], next_y);
+                if end_gap < 0.0 {
+                    let crossing_y =
+                        y_start + (next_y - y_start) * start_gap / (start_gap - end_gap);
+                    if y_start < crossing_y && crossing_y < y_end {
+                        y_end = crossing_y;
+                    }
+                }
+            }
+
+            // Every other edge has the inside on its right:
+            for (position, &i) in active.iter().enumerate() {
+                let inside_is_right = position % 2 == 0;
+                match feather_starts.get(i).copied().flatten() {
+                    Some((_, side)) if side == inside_is_right => {}
+                    Some((start_y, side)) => {
+                        let from_to = [
+                            edge_pos_at(edges[i], start_y),
+                            edge_pos_at(edges[i], y_start),
+                        ];
+                        feather(i, from_to, outward_normal(edges[i], side), out);
+                        feather_starts[i] = Some((y_start, inside_is_right));
+                    }
+                    None => feather_starts[i] = Some((y_start, inside_is_right)),
+                }
+            }
+
+            for pair in active.chunks_exact(2) {
+                let (left, right) = (edges[pair[0]], edges[pair[1]]);
+                let idx = out.vertices.len() as u32;
+                out.colored_vertex(edge_pos_at(left, y_start), color);
+                out.colored_vertex(edge_pos_at(right, y_start), color);
+                out.colored_vertex(edge_pos_at(right, y_end), color);
+                out.colored_vertex(edge_pos_at(left, y_end), color);
+                out.add_triangle(idx, idx + 1, idx + 2);
+                out.add_triangle(idx, idx + 2, idx + 3);
+            }
+
+            y_start = y_end;
+        }
+    }
+
+    if 0.0 < feathering {
+        // Round the corners between the feathered edges:
+        for (i, &(_, corner)) in edges.iter().enumerate() {
+            let next = (i + 1) % edges.len();
+            let idx = out.vertices.len() as u32;
+            out.colored_vertex(corner, color);
+            out.colored_vertex(corner + feathering * outwards[i][1], Color32::TRANSPARENT);
+            out.colored_vertex(
+                corner + feathering * outwards[next][0],
+                Color32::TRANSPARENT,
+            );
+            out.add_triangle(idx, idx + 1, idx + 2);
+        }
+    }
+}
+
 /// Like [`fill_closed_path`] but with texturing.
 ///
 /// The `uv_from_pos` is called for each vertex position.
@@ -876,18 +1140,124 @@ fn stroke_path(
     stroke: &PathStroke,
     out: &mut Mesh,
 ) {
-    let n = path.len() as u32;
-
-    if stroke.width <= 0.0 || stroke.color == ColorMode::TRANSPARENT || n < 2 {
+    if stroke.width <= 0.0 || stroke.color == ColorMode::TRANSPARENT || path.len() < 2 {
         return;
     }
 
-    let idx = out.vertices.len() as u32;
-
     // expand the bounding box to include the thickness of the path
     let bbox = Rect::from_points(&path.iter().map(|p| p.pos).collect::<Vec<Pos2>>())
         .expand((stroke.width / 2.0) + feathering);
 
+    if stroke.pattern.is_solid() {
+        stroke_solid_path(feathering, path, path_type, stroke, bbox, out);
+    } else {
+        for dash in dashes_from_path(path, path_type, &stroke.pattern) {
+            // The whole path shares one bounding box, so that a `ColorMode::UV` continues across the gaps:
+            stroke_solid_path(feathering, &dash, PathType::Open, stroke, bbox, out);
+        }
+    }
+}
+
+/// Split the path into the dashes of the given [`LinePattern`],
+/// measuring the pattern along the length of the path.
+///
+/// The dashes keep the normals of the corners they go around.
+fn dashes_from_path(
+    path: &[PathPoint],
+    path_type: PathType,
+    pattern: &LinePattern,
+) -> Vec<Vec<PathPoint>> {
+    let lengths = &pattern.dash_lengths;
+    let length_at = |k: usize| lengths[k % lengths.len()].max(0.0);
+    let period: f32 = (0..lengths.len()).map(length_at).sum();
+
+    // Find where in the pattern the path starts. Even `k` are dashes, odd `k` are gaps:
+    let mut k = 0;
+    let mut remaining = length_at(0);
+    let mut offset = pattern.dash_offset.rem_euclid(period);
+    while remaining <= offset {
+        offset -= remaining;
+        k += 1;
+        remaining = length_at(k);
+    }
+    remaining -= offset;
+
+    let n = path.len();
+    let starts_with_dash = k % 2 == 0;
+    let mut dashes = vec![];
+    let mut dash = starts_with_dash.then(|| vec![path[0].clone()]);
+
+    let num_segments = match path_type {
+        PathType::Open => n - 1,
+        PathType::Closed => n,
+    };
+    for i in 0..num_segments {
+        let (a, b) = (&path[i], &path[(i + 1) % n]);
+        let vector = b.pos - a.pos;
+        let segment_length = vector.length();
+        if segment_length <= 0.0 {
+            continue;
+        }
+        let segment_normal = (vector / segment_length).rot90();
+
+        let mut along = 0.0;
+        while remaining <= segment_length - along {
+            along += remaining;
+            let point = PathPoint {
+                pos: a.pos + vector * (along / segment_length),
+                normal: segment_normal,
+            };
+            if let Some(mut finished) = dash.take() {
+                finished.push(point);
+                dashes.push(finished);
+            } else {
+                dash = Some(vec![point]);
+            }
+            k += 1;
+            remaining = length_at(k);
+        }
+        remaining -= segment_length - along;
+
+        if let Some(dash) = &mut dash {
+            if dash.last().is_some_and(|last| last.pos != b.pos) {
+                dash.push(b.clone());
+            }
+        }
+    }
+
+    if let Some(mut last_dash) = dash {
+        if path_type == PathType::Closed && starts_with_dash {
+            if dashes.is_empty() {
+                // One dash all the way around:
+                return vec![path.iter().chain(path.first()).cloned().collect()];
+            }
+            // The dash continues past the start of the path:
+            last_dash.extend(dashes[0].drain(..).skip(1));
+            dashes[0] = last_dash;
+        } else {
+            dashes.push(last_dash);
+        }
+    }
+
+    // Skip dashes of length zero:
+    dashes.retain(|dash| dash.len() >= 2 && dash.iter().any(|p| p.pos != dash[0].pos));
+    dashes
+}
+
+/// Tessellate the given path as a solid stroke with thickness, ignoring [`PathStroke::pattern`].
+///
+/// The `bbox` of the whole path (including its thickness) is passed to [`ColorMode::UV`].
+fn stroke_solid_path(
+    feathering: f32,
+    path: &[PathPoint],
+    path_type: PathType,
+    stroke: &PathStroke,
+    bbox: Rect,
+    out: &mut Mesh,
+) {
+    let n = path.len() as u32;
+    let idx = out.vertices.len() as u32;
+
     let get_color = |col: &ColorMode, pos: Pos2| match col {
         ColorMode::Solid(col) => *col,
         ColorMode::UV(fun) => fun(bbox, pos),
@@ -1152,6 +1522,13 @@ fn stroke_path(
     }
 }
 
+/// The opacity across the edge of a box blurred with a Gaussian,
+/// at evenly spaced distances from half the blur width inside the edge to half the blur width outside it.
+///
+/// That is `1 - Φ(x)` for `x` from -2.5 to 2.5 standard deviations,
+/// with the ends snapped to fully opaque and fully transparent.
+const GAUSSIAN_FALLOFF: [f32; 8] = [1.0, 0.9629, 0.8580, 0.6395, 0.3605, 0.1420, 0.0371, 0.0];
+
 fn mul_color(color: Color32, factor: f32) -> Color32 {
     // The fast gamma-space multiply also happens to be perceptually better.
     // Win-win!
@@ -1171,6 +1548,10 @@ pub struct Tessellator {
     options: TessellationOptions,
     font_tex_size: [usize; 2],
 
+    /// The texture and size of the SDF font texture, if any.
+    /// See [`Self::with_sdf_font_texture`].
+    sdf_font_texture: Option<(TextureId, [usize; 2])>,
+
     /// See [`TextureAtlas::prepared_discs`].
     prepared_discs: Vec<PreparedDisc>,
 
@@ -1208,6 +1589,7 @@ impl Tessellator {
             pixels_per_point,
             options,
             font_tex_size,
+            sdf_font_texture: None,
             prepared_discs,
             feathering,
             clip_rect: Rect::EVERYTHING,
@@ -1216,6 +1598,19 @@ impl Tessellator {
         }
     }
 
+    /// Also tessellate the glyphs of fonts with [`crate::text::FontTweak::sdf`],
+    /// which are in the SDF font texture.
+    ///
+    /// * `texture_id`: where the SDF font texture was uploaded (see [`crate::Fonts::sdf_font_image_delta`]).
+    /// * `size`: size of the SDF font texture, see [`crate::Fonts::sdf_font_image_size`].
+    ///
+    /// Without this, SDF glyphs are not painted.
+    #[inline]
+    pub fn with_sdf_font_texture(mut self, texture_id: TextureId, size: [usize; 2]) -> Self {
+        self.sdf_font_texture = Some((texture_id, size));
+        self
+    }
+
     /// Set the [`Rect`] to use for culling.
     pub fn set_clip_rect(&mut self, clip_rect: Rect) {
         self.clip_rect = clip_rect;
@@ -1230,13 +1625,37 @@ impl Tessellator {
         }
     }
 
+    /// Snap a coordinate so that a crisp stroke of this width across it covers whole physical pixels:
+    /// to pixel centers for an odd number of pixels, and to pixel edges for an even number.
+    fn snap_crisp(&self, coordinate: f32, width: f32) -> f32 {
+        let offset = if (width * self.pixels_per_point).round() % 2.0 == 1.0 {
+            0.5
+        } else {
+            0.0
+        };
+        ((coordinate * self.pixels_per_point - offset).round() + offset) / self.pixels_per_point
+    }
+
+    /// A [`PathStroke::crisp`] stroke with its width rounded to whole physical pixels, at least one.
+    fn crisp_stroke(&self, stroke: &PathStroke) -> PathStroke {
+        let width_px = (stroke.width * self.pixels_per_point).round().max(1.0);
+        PathStroke {
+            width: width_px / self.pixels_per_point,
+            ..stroke.clone()
+        }
+    }
+
     /// Tessellate a clipped shape into a list of primitives.
     pub fn tessellate_clipped_shape(
         &mut self,
         clipped_shape: ClippedShape,
         out_primitives: &mut Vec<ClippedPrimitive>,
     ) {
-        let ClippedShape { clip_rect, shape } = clipped_shape;
+        let ClippedShape {
+            clip_rect,
+            clip_rounding,
+            shape,
+        } = clipped_shape;
 
         if !clip_rect.is_positive() {
             return; // skip empty clip rectangles
@@ -1244,7 +1663,14 @@ impl Tessellator {
 
         if let Shape::Vec(shapes) = shape {
             for shape in shapes {
-                self.tessellate_clipped_shape(ClippedShape { clip_rect, shape }, out_primitives);
+                self.tessellate_clipped_shape(
+                    ClippedShape {
+                        clip_rect,
+                        clip_rounding,
+                        shape,
+                    },
+                    out_primitives,
+                );
             }
             return;
         }
@@ -1252,20 +1678,117 @@ impl Tessellator {
         if let Shape::Callback(callback) = shape {
             out_primitives.push(ClippedPrimitive {
                 clip_rect,
+                clip_rounding,
                 primitive: Primitive::Callback(callback),
             });
             return;
         }
 
+        if let Shape::Backdrop(backdrop) = shape {
+            self.tessellate_clipped_backdrop(clip_rect, clip_rounding, &backdrop, out_primitives);
+            return;
+        }
+
+        // SDF glyphs are in another texture, so they go into a mesh of their own, on top:
+        let sdf_text = match (&shape, self.sdf_font_texture) {
+            (Shape::Text(text_shape), Some((sdf_texture_id, _)))
+                if text_shape.galley.has_sdf_glyphs() =>
+            {
+                Some((text_shape.clone(), sdf_texture_id))
+            }
+            _ => None,
+        };
+
+        self.clip_rect = clip_rect;
+        let clip_rounding = path::clamp_rounding(clip_rounding, clip_rect);
+        let out_mesh =
+            Self::output_mesh(clip_rect, clip_rounding, shape.texture_id(), out_primitives);
+        let index_start = out_mesh.indices.len();
+        self.tessellate_shape(shape, out_mesh);
+        self.clip_to_rounded_rect(out_mesh, index_start, clip_rect, clip_rounding);
+
+        if let Some((text_shape, sdf_texture_id)) = sdf_text {
+            let out_mesh =
+                Self::output_mesh(clip_rect, clip_rounding, sdf_texture_id, out_primitives);
+            let index_start = out_mesh.indices.len();
+            self.tessellate_sdf_text(&text_shape, out_mesh);
+            self.clip_to_rounded_rect(out_mesh, index_start, clip_rect, clip_rounding);
+        }
+    }
+
+    /// A [`Primitive::Backdrop`] followed by the tint, if [`TessellationOptions::backdrop_blur`] is set,
+    /// else the [`BackdropShape::fallback`].
+    fn tessellate_clipped_backdrop(
+        &mut self,
+        clip_rect: Rect,
+        clip_rounding: Rounding,
+        backdrop: &BackdropShape,
+        out_primitives: &mut Vec<ClippedPrimitive>,
+    ) {
+        let BackdropShape {
+            rect,
+            rounding,
+            blur_radius,
+            tint,
+        } = *backdrop;
+
+        if !self.options.backdrop_blur {
+            self.tessellate_clipped_shape(
+                ClippedShape {
+                    clip_rect,
+                    clip_rounding,
+                    shape: backdrop.fallback().into(),
+                },
+                out_primitives,
+            );
+            return;
+        }
+
+        self.clip_rect = clip_rect;
+        let clamped_clip_rounding = path::clamp_rounding(clip_rounding, clip_rect);
+        let mut mesh = Mesh::default();
+        let mut blurred = RectShape::filled(rect, rounding, Color32::WHITE);
+        blurred.uv = Rect::from_min_max(Pos2::ZERO, pos2(1.0, 1.0));
+        self.tessellate_rect(&blurred, &mut mesh);
+        self.clip_to_rounded_rect(&mut mesh, 0, clip_rect, clamped_clip_rounding);
+        if !mesh.is_empty() {
+            out_primitives.push(ClippedPrimitive {
+                clip_rect,
+                clip_rounding: clamped_clip_rounding,
+                primitive: Primitive::Backdrop(Backdrop {
+                    rect,
+                    blur_radius,
+                    mesh,
+                }),
+            });
+        }
+
+        self.tessellate_clipped_shape(
+            ClippedShape {
+                clip_rect,
+                clip_rounding,
+                shape: RectShape::filled(rect, rounding, tint).into(),
+            },
+            out_primitives,
+        );
+    }
+
+    /// The mesh at the end of `out_primitives` if it has the given clip rect and texture,
+    /// else a new one.
+    fn output_mesh(
+        clip_rect: Rect,
+        clip_rounding: Rounding,
+        texture_id: TextureId,
+        out_primitives: &mut Vec<ClippedPrimitive>,
+    ) -> &mut Mesh {
         let start_new_mesh = match out_primitives.last() {
             None => true,
             Some(output_clipped_primitive) => {
                 output_clipped_primitive.clip_rect != clip_rect
+                    || output_clipped_primitive.clip_rounding != clip_rounding
                     || match &output_clipped_primitive.primitive {
-                        Primitive::Mesh(output_mesh) => {
-                            output_mesh.texture_id != shape.texture_id()
-                        }
-                        Primitive::Callback(_) => true,
+                        Primitive::Mesh(output_mesh) => output_mesh.texture_id != texture_id,
+                        Primitive::Callback(_) | Primitive::Backdrop(_) => true,
                     }
             }
         };
@@ -1273,24 +1796,180 @@ impl Tessellator {
         if start_new_mesh {
             out_primitives.push(ClippedPrimitive {
                 clip_rect,
-                primitive: Primitive::Mesh(Mesh::default()),
+                clip_rounding,
+                primitive: Primitive::Mesh(Mesh::with_texture(texture_id)),
             });
         }
 
-        let out = out_primitives.last_mut().unwrap();
+        match &mut out_primitives.last_mut().unwrap().primitive {
+            Primitive::Mesh(out_mesh) => out_mesh,
+            Primitive::Callback(_) | Primitive::Backdrop(_) => unreachable!(),
+        }
+    }
 
-        if let Primitive::Mesh(out_mesh) = &mut out.primitive {
-            self.clip_rect = clip_rect;
-            self.tessellate_shape(shape, out_mesh);
-        } else {
-            unreachable!();
+    /// Cut the rounded corners of `clip_rect` out of the triangles of `mesh` from `index_start` on.
+    ///
+    /// Triangles reaching into a rounded corner are clipped against the arc,
+    /// and faded out across a feathering-wide band around it for anti-aliasing.
+    /// Everything else is left to the scissor rect of the backend.
+    fn clip_to_rounded_rect(
+        &self,
+        mesh: &mut Mesh,
+        index_start: usize,
+        clip_rect: Rect,
+        clip_rounding: Rounding,
+    ) {
+        use std::f32::consts::{FRAC_PI_2, FRAC_PI_4};
+
+        if clip_rounding == Rounding::ZERO || mesh.indices.len() <= index_start {
+            return;
+        }
+
+        let feathering = self.feathering;
+        let corners = [
+            (clip_rect.left_top(), vec2(-1.0, -1.0), clip_rounding.nw),
+            (clip_rect.right_top(), vec2(1.0, -1.0), clip_rounding.ne),
+            (clip_rect.left_bottom(), vec2(-1.0, 1.0), clip_rounding.sw),
+            (clip_rect.right_bottom(), vec2(1.0, 1.0), clip_rounding.se),
+        ];
+
+        let mut clipped_indices = Vec::with_capacity(mesh.indices.len() - index_start);
+        for triangle_start in (index_start..mesh.indices.len()).step_by(3) {
+            let indices: [u32; 3] = std::array::from_fn(|i| mesh.indices[triangle_start + i]);
+            let triangle = indices.map(|index| mesh.vertices[index as usize]);
+            let bounds = Rect::from_points(&triangle.map(|vertex| vertex.pos));
+
+            let mut pieces: Option<Vec<Vec<Vertex>>> = None;
+            for &(corner, dir, radius) in &corners {
+                if radius <= 0.0 {
+                    continue;
+                }
+                let center = corner - radius * dir;
+                let corner_square = Rect::from_two_pos(center, corner);
+                if !corner_square.intersects(bounds) {
+                    continue;
+                }
+
+                // The arc as chords, within a tenth of a pixel of the circle:
+                let outer_radius = radius + 0.5 * feathering;
+                let inner_radius = radius - 0.5 * feathering;
+                let radius_px = outer_radius * self.pixels_per_point;
+                let tolerance_px = 0.1;
+                let max_step = if tolerance_px < radius_px {
+                    2.0 * (1.0 - tolerance_px / radius_px).acos()
+                } else {
+                    FRAC_PI_4
+                };
+                let num_segments = (FRAC_PI_2 / max_step.min(FRAC_PI_4)).ceil() as usize;
+                let half_step = FRAC_PI_4 / num_segments as f32;
+
+                // Chords between points on the arc would cut off a sliver of it each,
+                // so move them out until the wedge under each chord has the area of the arc's sector
+                // (`d² tan(θ) = r² θ`), to cover as much outside the arc as they leave out inside of it:
+                let chord_distance = (half_step / half_step.tan()).sqrt();
+
+                if triangle
+                    .iter()
+                    .all(|v| v.pos.distance(center) <= inner_radius * chord_distance)
+                {
+                    continue; // entirely within the arc
+                }
+
+                let normals = (0..num_segments).map(move |i| {
+                    let angle = (2 * i + 1) as f32 * half_step;
+                    vec2(dir.x * angle.cos(), dir.y * angle.sin())
+                });
+
+                // Each chord as a half-plane `normal · p <= offset`:
+                let chords = |radius: f32| {
+                    normals.clone().map(move |normal| {
+                        (
+                            normal,
+                            normal.dot(center.to_vec2()) + radius * chord_distance,
+                        )
+                    })
+                };
+
+                // Only what is in the corner square is clipped by the chords,
+                // which would otherwise also cut into the rest of the triangle beyond their ends:
+                let mut kept_pieces = vec![];
+                let mut corner_pieces = vec![];
+                for piece in pieces.take().unwrap_or_else(|| vec![triangle.to_vec()]) {
+                    let (in_column, beside) =
+                        split_polygon(&piece, vec2(-dir.x, 0.0), -dir.x * center.x);
+                    let (in_corner, beyond) =
+                        split_polygon(&in_column, vec2(0.0, -dir.y), -dir.y * center.y);
+                    kept_pieces.extend([beside, beyond]);
+                    corner_pieces.push(in_corner);
+                }
+                kept_pieces.retain(|piece| 3 <= piece.len());
+                corner_pieces.retain(|piece| 3 <= piece.len());
+
+                for (normal, offset) in chords(outer_radius) {
+                    for piece in &mut corner_pieces {
+                        *piece = split_polygon(piece, normal, offset).0;
+                    }
+                    corner_pieces.retain(|piece| 3 <= piece.len());
+                }
+                if 0.0 < feathering && 0.0 < inner_radius {
+                    // Split along the inner edge of the feathering, so the fade is linear across it:
+                    for (normal, offset) in chords(inner_radius) {
+                        corner_pieces = corner_pieces
+                            .iter()
+                            .flat_map(|piece| <[_; 2]>::from(split_polygon(piece, normal, offset)))
+                            .filter(|piece| 3 <= piece.len())
+                            .collect();
+                    }
+                    // Near the ends of the arc, the shape may already fade out along the clip rect,
+                    // so fade to the least of both instead of their product:
+                    let opaque_alpha = triangle.iter().map(|v| v.color.a()).max().unwrap_or(0);
+                    for vertex in corner_pieces.iter_mut().flatten() {
+                        let offset = vertex.pos - center;
+                        if 0.0 < offset.x * dir.x && 0.0 < offset.y * dir.y {
+                            // The distance as measured by the chords, so the fade is exactly
+                            // from the inner ones to the outer ones:
+                            let distance = normals
+                                .clone()
+                                .map(|normal| normal.dot(offset))
+                                .fold(0.0, f32::max)
+                                / chord_distance;
+                            let coverage =
+                                remap_clamp(distance, inner_radius..=outer_radius, 1.0..=0.0);
+                            let alpha = f32::from(vertex.color.a());
+                            let faded_alpha = coverage * f32::from(opaque_alpha);
+                            if faded_alpha < alpha {
+                                vertex.color = vertex.color.gamma_multiply(faded_alpha / alpha);
+                            }
+                        }
+                    }
+                }
+                kept_pieces.extend(corner_pieces);
+                pieces = Some(kept_pieces);
+            }
+
+            match pieces {
+                None => clipped_indices.extend_from_slice(&indices),
+                Some(pieces) => {
+                    for piece in pieces {
+                        let first = mesh.vertices.len() as u32;
+                        for i in 2..piece.len() as u32 {
+                            clipped_indices.extend_from_slice(&[first, first + i - 1, first + i]);
+                        }
+                        mesh.vertices.extend(piece);
+                    }
+                }
+            }
         }
+
+        mesh.indices.truncate(index_start);
+        mesh.indices.extend(clipped_indices);
     }
 
     /// Tessellate a single [`Shape`] into a [`Mesh`].
     ///
     /// This call can panic the given shape is of [`Shape::Vec`] or [`Shape::Callback`].
     /// For that, use [`Self::tessellate_clipped_shape`] instead.
+    /// A [`Shape::Backdrop`] is always painted as its [`BackdropShape::fallback`] here.
     /// * `shape`: the shape to tessellate.
     /// * `out`: triangles are appended to this.
     pub fn tessellate_shape(&mut self, shape: Shape, out: &mut Mesh) {
@@ -1307,6 +1986,9 @@ impl Tessellator {
             Shape::Ellipse(ellipse) => {
                 self.tessellate_ellipse(ellipse, out);
             }
+            Shape::CircleSegment(segment) => {
+                self.tessellate_circle_segment(segment, out);
+            }
             Shape::Mesh(mesh) => {
                 crate::profile_scope!("mesh");
 
@@ -1324,6 +2006,9 @@ impl Tessellator {
 
                 out.append(mesh);
             }
+            Shape::InstancedMesh(instance) => {
+                self.tessellate_instanced_mesh(&instance, out);
+            }
             Shape::LineSegment { points, stroke } => self.tessellate_line(points, stroke, out),
             Shape::Path(path_shape) => {
                 self.tessellate_path(&path_shape, out);
@@ -1345,10 +2030,63 @@ impl Tessellator {
                 self.tessellate_quadratic_bezier(&quadratic_shape, out);
             }
             Shape::CubicBezier(cubic_shape) => self.tessellate_cubic_bezier(&cubic_shape, out),
+            Shape::CurvePath(curve_path) => {
+                self.tessellate_curve_path(&curve_path, out);
+            }
             Shape::Callback(_) => {
                 panic!("Shape::Callback passed to Tessellator");
             }
+            Shape::Backdrop(backdrop) => {
+                self.tessellate_rect(&backdrop.fallback(), out);
+            }
+        }
+    }
+
+    /// Append a shared [`Mesh`], moved into place and tinted, to `out`.
+    ///
+    /// * `instance`: the mesh to append.
+    /// * `out`: triangles are appended to this.
+    pub fn tessellate_instanced_mesh(&self, instance: &InstancedMeshShape, out: &mut Mesh) {
+        let InstancedMeshShape {
+            mesh,
+            mesh_bounds: _,
+            transform,
+            tint,
+        } = instance;
+
+        if self.options.validate_meshes && !mesh.is_valid() {
+            debug_assert!(false, "Invalid Mesh in Shape::InstancedMesh");
+            return;
+        }
+
+        if self.options.coarse_tessellation_culling
+            && !self.clip_rect.intersects(instance.visual_bounding_rect())
+        {
+            return;
+        }
+
+        if out.is_empty() {
+            out.texture_id = mesh.texture_id;
+        } else {
+            assert_eq!(
+                out.texture_id, mesh.texture_id,
+                "Can't merge Mesh using different textures"
+            );
         }
+
+        let index_offset = out.vertices.len() as u32;
+        out.indices
+            .extend(mesh.indices.iter().map(|index| index + index_offset));
+        out.vertices
+            .extend(mesh.vertices.iter().map(|vertex| Vertex {
+                pos: *transform * vertex.pos,
+                uv: vertex.uv,
+                color: if *tint == Color32::WHITE {
+                    vertex.color
+                } else {
+                    crate::fill::multiply_colors(vertex.color, *tint)
+                },
+            }));
     }
 
     /// Tessellate a single [`CircleShape`] into a [`Mesh`].
@@ -1361,6 +2099,7 @@ impl Tessellator {
             radius,
             mut fill,
             stroke,
+            stroke_pattern,
         } = shape;
 
         if radius <= 0.0 {
@@ -1403,8 +2142,11 @@ impl Tessellator {
         self.scratchpad_path.clear();
         self.scratchpad_path.add_circle(center, radius);
         self.scratchpad_path.fill(self.feathering, fill, out);
-        self.scratchpad_path
-            .stroke_closed(self.feathering, &stroke.into(), out);
+        self.scratchpad_path.stroke_closed(
+            self.feathering,
+            &PathStroke::from(stroke).with_pattern(stroke_pattern),
+            out,
+        );
     }
 
     /// Tessellate a single [`EllipseShape`] into a [`Mesh`].
@@ -1417,6 +2159,7 @@ impl Tessellator {
             radius,
             fill,
             stroke,
+            stroke_pattern,
         } = shape;
 
         if radius.x <= 0.0 || radius.y <= 0.0 {
@@ -1470,35 +2213,174 @@ impl Tessellator {
         self.scratchpad_path.clear();
         self.scratchpad_path.add_line_loop(&points);
         self.scratchpad_path.fill(self.feathering, fill, out);
-        self.scratchpad_path
-            .stroke_closed(self.feathering, &stroke.into(), out);
+        self.scratchpad_path.stroke_closed(
+            self.feathering,
+            &PathStroke::from(stroke).with_pattern(stroke_pattern),
+            out,
+        );
     }
 
-    /// Tessellate a single [`Mesh`] into a [`Mesh`].
+    /// Tessellate a single [`CircleSegmentShape`] into a [`Mesh`].
     ///
-    /// * `mesh`: the mesh to tessellate.
+    /// * `shape`: the arc, pie slice or ring segment to tessellate.
     /// * `out`: triangles are appended to this.
-    pub fn tessellate_mesh(&mut self, mesh: &Mesh, out: &mut Mesh) {
-        if !mesh.is_valid() {
-            debug_assert!(false, "Invalid Mesh in Shape::Mesh");
+    pub fn tessellate_circle_segment(&mut self, shape: CircleSegmentShape, out: &mut Mesh) {
+        let CircleSegmentShape {
+            center,
+            radius,
+            start_angle,
+            end_angle,
+            inner_radius,
+            fill,
+            stroke,
+            stroke_pattern,
+        } = shape;
+
+        let inner_radius = inner_radius.filter(|&inner_radius| 0.0 < inner_radius);
+        if radius <= 0.0 || inner_radius.is_some_and(|inner_radius| radius <= inner_radius) {
             return;
         }
 
         if self.options.coarse_tessellation_culling
-            && !self.clip_rect.intersects(mesh.calc_bounds())
+            && !self
+                .clip_rect
+                .expand(radius + stroke.width)
+                .contains(center)
         {
             return;
         }
 
-        out.append_ref(mesh);
-    }
-
-    /// Tessellate a line segment between the two points with the given stroke into a [`Mesh`].
-    ///
-    /// * `shape`: the mesh to tessellate.
-    /// * `out`: triangles are appended to this.
-    pub fn tessellate_line(
-        &mut self,
+        let feathering = self.feathering;
+        let stroke = PathStroke::from(stroke).with_pattern(stroke_pattern);
+
+        if shape.is_full_circle() {
+            if let Some(inner_radius) = inner_radius {
+                let path = &mut self.scratchpad_path;
+                path.clear();
+                path.add_circle(center, (radius + inner_radius) / 2.0);
+                path.stroke_closed(
+                    feathering,
+                    &PathStroke::new(radius - inner_radius, fill),
+                    out,
+                );
+                for radius in [radius, inner_radius] {
+                    path.clear();
+                    path.add_circle(center, radius);
+                    path.stroke_closed(feathering, &stroke, out);
+                }
+            } else {
+                self.tessellate_circle(
+                    CircleShape {
+                        center,
+                        radius,
+                        fill,
+                        stroke: shape.stroke,
+                        stroke_pattern,
+                    },
+                    out,
+                );
+            }
+            return;
+        }
+
+        let start_angle = start_angle.min(end_angle);
+        let span = (end_angle - start_angle).abs();
+
+        let radius_px = radius * self.pixels_per_point;
+        if span * radius_px < 1e-3 {
+            // Too thin to cover any area, and the sides would only make degenerate triangles.
+            // A pie slice this thin still shows its stroke as a line along the radius.
+            if inner_radius.is_none() && fill != Color32::TRANSPARENT {
+                let end = center + radius * Vec2::angled(start_angle);
+                self.tessellate_line([center, end], stroke, out);
+            }
+            return;
+        }
+
+        // Keep the chords within a tenth of a pixel of the circle:
+        let tolerance_px = 0.1;
+        let max_step = if tolerance_px < radius_px {
+            2.0 * (1.0 - tolerance_px / radius_px).acos()
+        } else {
+            std::f32::consts::FRAC_PI_4
+        };
+        let num_segments = (span / max_step.min(std::f32::consts::FRAC_PI_4)).ceil() as usize;
+        let arc = |radius: f32| {
+            (0..=num_segments).map(move |i| {
+                let angle = start_angle + span * i as f32 / num_segments as f32;
+                center + radius * Vec2::angled(angle)
+            })
+        };
+
+        let path = &mut self.scratchpad_path;
+        if let Some(inner_radius) = inner_radius {
+            // The fill is a thick arc along the middle of the ring:
+            if fill != Color32::TRANSPARENT {
+                let points: Vec<Pos2> = arc((radius + inner_radius) / 2.0).collect();
+                path.clear();
+                path.add_open_points(&points);
+                path.stroke_open(
+                    feathering,
+                    &PathStroke::new(radius - inner_radius, fill),
+                    out,
+                );
+            }
+
+            if !stroke.is_empty() {
+                let points: Vec<Pos2> = arc(radius).chain(arc(inner_radius).rev()).collect();
+                path.clear();
+                path.add_line_loop(&points);
+                path.stroke_closed(feathering, &stroke, out);
+            }
+        } else if fill == Color32::TRANSPARENT {
+            let points: Vec<Pos2> = arc(radius).collect();
+            path.clear();
+            path.add_open_points(&points);
+            path.stroke_open(feathering, &stroke, out);
+        } else {
+            // Start at the center, so that the fill fans out from there
+            // even when the slice isn't convex:
+            let points: Vec<Pos2> = std::iter::once(center).chain(arc(radius)).collect();
+            path.clear();
+            path.add_line_loop(&points);
+
+            // A narrow slice has a very long miter at the center,
+            // which would make the feathering and stroke spike out past it,
+            // so cut it off like a right angle:
+            let tip = &mut path.0[0];
+            tip.normal =
+                tip.normal.normalized() * tip.normal.length().min(std::f32::consts::SQRT_2);
+
+            path.fill(feathering, fill, out);
+            path.stroke_closed(feathering, &stroke, out);
+        }
+    }
+
+    /// Tessellate a single [`Mesh`] into a [`Mesh`].
+    ///
+    /// * `mesh`: the mesh to tessellate.
+    /// * `out`: triangles are appended to this.
+    pub fn tessellate_mesh(&mut self, mesh: &Mesh, out: &mut Mesh) {
+        if !mesh.is_valid() {
+            debug_assert!(false, "Invalid Mesh in Shape::Mesh");
+            return;
+        }
+
+        if self.options.coarse_tessellation_culling
+            && !self.clip_rect.intersects(mesh.calc_bounds())
+        {
+            return;
+        }
+
+        out.append_ref(mesh);
+    }
+
+    /// Tessellate a line segment between the two points with the given stroke into a [`Mesh`].
+    ///
+    /// * `shape`: the mesh to tessellate.
+    /// * `out`: triangles are appended to this.
+    pub fn tessellate_line(
+        &mut self,
         points: [Pos2; 2],
         stroke: impl Into<PathStroke>,
         out: &mut Mesh,
@@ -1516,6 +2398,36 @@ impl Tessellator {
             return;
         }
 
+        if stroke.crisp {
+            let stroke = self.crisp_stroke(&stroke);
+            let [a, b] = points;
+            let points = if a.y == b.y {
+                // Horizontal: center the line on the pixel rows it covers, and end it on pixel edges.
+                let y = self.snap_crisp(a.y, stroke.width);
+                [
+                    pos2(self.snap_crisp(a.x, 0.0), y),
+                    pos2(self.snap_crisp(b.x, 0.0), y),
+                ]
+            } else if a.x == b.x {
+                let x = self.snap_crisp(a.x, stroke.width);
+                [
+                    pos2(x, self.snap_crisp(a.y, 0.0)),
+                    pos2(x, self.snap_crisp(b.y, 0.0)),
+                ]
+            } else {
+                points.map(|p| {
+                    pos2(
+                        self.snap_crisp(p.x, stroke.width),
+                        self.snap_crisp(p.y, stroke.width),
+                    )
+                })
+            };
+            self.scratchpad_path.clear();
+            self.scratchpad_path.add_line_segment(points);
+            self.scratchpad_path.stroke_open(0.0, &stroke, out);
+            return;
+        }
+
         self.scratchpad_path.clear();
         self.scratchpad_path.add_line_segment(points);
         self.scratchpad_path
@@ -1553,20 +2465,102 @@ impl Tessellator {
             self.scratchpad_path.add_open_points(points);
         }
 
-        if *fill != Color32::TRANSPARENT {
+        if let Fill::Solid(color) = *fill {
+            if color != Color32::TRANSPARENT {
+                debug_assert!(
+                    closed,
+                    "You asked to fill a path that is not closed. That makes no sense."
+                );
+                self.scratchpad_path.fill(self.feathering, color, out);
+            }
+        } else {
             debug_assert!(
                 closed,
                 "You asked to fill a path that is not closed. That makes no sense."
             );
-            self.scratchpad_path.fill(self.feathering, *fill, out);
+            // A gradient is painted onto a white fill:
+            let (vertex_start, index_start) = (out.vertices.len(), out.indices.len());
+            self.scratchpad_path
+                .fill(self.feathering, Color32::WHITE, out);
+            fill.paint_mesh(out, vertex_start, index_start);
         }
         let typ = if *closed {
             PathType::Closed
         } else {
             PathType::Open
         };
-        self.scratchpad_path
-            .stroke(self.feathering, typ, stroke, out);
+        if stroke.crisp {
+            let stroke = self.crisp_stroke(stroke);
+            let snap = |p: &Pos2| {
+                pos2(
+                    self.snap_crisp(p.x, stroke.width),
+                    self.snap_crisp(p.y, stroke.width),
+                )
+            };
+            let points: Vec<Pos2> = points.iter().map(snap).collect();
+            self.scratchpad_path.clear();
+            if *closed {
+                self.scratchpad_path.add_line_loop(&points);
+            } else {
+                self.scratchpad_path.add_open_points(&points);
+            }
+            self.scratchpad_path.stroke(0.0, typ, &stroke, out);
+        } else {
+            self.scratchpad_path
+                .stroke(self.feathering, typ, stroke, out);
+        }
+    }
+
+    /// Tessellate a single [`CurvePathShape`] into a [`Mesh`].
+    ///
+    /// The curves are flattened to suit the `pixels_per_point` of the tessellator,
+    /// unless the path has a [`crate::PathBuilder::tolerance`] of its own.
+    ///
+    /// * `curve_path`: the path to tessellate.
+    /// * `out`: triangles are appended to this.
+    pub fn tessellate_curve_path(&mut self, curve_path: &CurvePathShape, out: &mut Mesh) {
+        if self.options.coarse_tessellation_culling
+            && !curve_path.visual_bounding_rect().intersects(self.clip_rect)
+        {
+            return;
+        }
+
+        crate::profile_function!();
+
+        let CurvePathShape { path, fill, stroke } = curve_path;
+        let tolerance = path.tolerance_at(self.pixels_per_point);
+
+        for (points, closed) in path.flatten(tolerance) {
+            if points.len() < 2 {
+                continue;
+            }
+
+            // Open subpaths are filled as if they were closed:
+            self.scratchpad_path.clear();
+            self.scratchpad_path.add_line_loop(&points);
+            if fill.is_transparent() || points.len() < 3 {
+                // Nothing to fill
+            } else if let Fill::Solid(color) = *fill {
+                self.scratchpad_path
+                    .fill_even_odd(self.feathering, color, out);
+            } else {
+                // A gradient is painted onto a white fill:
+                let (vertex_start, index_start) = (out.vertices.len(), out.indices.len());
+                self.scratchpad_path
+                    .fill_even_odd(self.feathering, Color32::WHITE, out);
+                fill.paint_mesh(out, vertex_start, index_start);
+            }
+
+            let typ = if closed {
+                PathType::Closed
+            } else {
+                self.scratchpad_path.clear();
+                self.scratchpad_path.add_open_points(&points);
+                PathType::Open
+            };
+            self.scratchpad_path
+                .stroke(self.feathering, typ, stroke, out);
+        }
     }
 
     /// Tessellate a single [`Rect`] into a [`Mesh`].
@@ -1574,11 +2568,13 @@ impl Tessellator {
     /// * `rect`: the rectangle to tessellate.
     /// * `out`: triangles are appended to this.
     pub fn tessellate_rect(&mut self, rect: &RectShape, out: &mut Mesh) {
+        let fill = &rect.fill;
         let RectShape {
             mut rect,
             mut rounding,
-            fill,
+            fill: _,
             stroke,
+            stroke_pattern,
             mut blur_width,
             fill_texture_id,
             uv,
@@ -1598,6 +2594,24 @@ impl Tessellator {
         rect.min = rect.min.at_least(pos2(-1e7, -1e7));
         rect.max = rect.max.at_most(pos2(1e7, 1e7));
 
+        // A gradient is painted onto a white fill:
+        let (mut fill_color, gradient) = match fill {
+            Fill::Solid(color) => (*color, None),
+            gradient => (Color32::WHITE, Some(gradient)),
+        };
+
+        if self.feathering < blur_width
+            && fill_color != Color32::TRANSPARENT
+            && gradient.is_none()
+            && !uv.is_positive()
+        {
+            self.tessellate_blurred_fill(rect, rounding, blur_width, fill_color, out);
+            if stroke.is_empty() {
+                return;
+            }
+            fill_color = Color32::TRANSPARENT; // The stroke is blurred by feathering below
+        }
+
         let old_feathering = self.feathering;
 
         if old_feathering < blur_width {
@@ -1621,9 +2635,13 @@ impl Tessellator {
             self.feathering = self.feathering.max(blur_width);
         }
 
+        // Too thin for a gradient to show:
+        let thin_fill = gradient.map_or(fill_color, |gradient| gradient.color_at(rect.center()));
+
         if rect.width() < self.feathering {
             // Very thin - approximate by a vertical line-segment:
             let line = [rect.center_top(), rect.center_bottom()];
+            let fill = thin_fill;
             if fill != Color32::TRANSPARENT {
                 self.tessellate_line(line, Stroke::new(rect.width(), fill), out);
             }
@@ -1634,6 +2652,7 @@ impl Tessellator {
         } else if rect.height() < self.feathering {
             // Very thin - approximate by a horizontal line-segment:
             let line = [rect.left_center(), rect.right_center()];
+            let fill = thin_fill;
             if fill != Color32::TRANSPARENT {
                 self.tessellate_line(line, Stroke::new(rect.height(), fill), out);
             }
@@ -1647,6 +2666,8 @@ impl Tessellator {
             path::rounded_rectangle(&mut self.scratchpad_points, rect, rounding);
             path.add_line_loop(&self.scratchpad_points);
 
+            let (vertex_start, index_start) = (out.vertices.len(), out.indices.len());
+
             if uv.is_positive() {
                 // Textured
                 let uv_from_pos = |p: Pos2| {
@@ -1655,22 +2676,131 @@ impl Tessellator {
                         remap(p.y, rect.y_range(), uv.y_range()),
                     )
                 };
-                path.fill_with_uv(self.feathering, fill, fill_texture_id, uv_from_pos, out);
+                path.fill_with_uv(
+                    self.feathering,
+                    fill_color,
+                    fill_texture_id,
+                    uv_from_pos,
+                    out,
+                );
             } else {
                 // Untextured
-                path.fill(self.feathering, fill, out);
+                path.fill(self.feathering, fill_color, out);
+            }
+            if let Some(gradient) = gradient {
+                gradient.paint_mesh(out, vertex_start, index_start);
             }
 
-            path.stroke_closed(self.feathering, &stroke.into(), out);
+            path.stroke_closed(
+                self.feathering,
+                &PathStroke::from(stroke).with_pattern(stroke_pattern),
+                out,
+            );
         }
 
         self.feathering = old_feathering; // restore
     }
 
+    /// Fill a rounded rectangle with edges blurred by (an approximation of) a Gaussian blur,
+    /// e.g. for a soft shadow.
+    ///
+    /// This is done with a few concentric rounded rectangles, from half the `blur_width` inside the edge
+    /// to half of it outside, with the opacity of each taken from [`GAUSSIAN_FALLOFF`].
+    fn tessellate_blurred_fill(
+        &self,
+        rect: Rect,
+        rounding: Rounding,
+        blur_width: f32,
+        color: Color32,
+        out: &mut Mesh,
+    ) {
+        let half_blur = 0.5 * blur_width;
+        let max_inset = 0.5 * rect.size().min_elem();
+        let rounding = path::clamp_rounding(rounding, rect);
+
+        // Every ring has the same number of vertices, so that neighboring rings can be stitched together.
+        // Enough for the outermost (and roundest) corners to look round:
+        let max_radius = rounding
+            .nw
+            .max(rounding.ne)
+            .max(rounding.sw)
+            .max(rounding.se)
+            + half_blur;
+        let segments = ((max_radius * self.pixels_per_point).sqrt().ceil() as u32).clamp(1, 16);
+        let ring_len = 4 * (segments + 1);
+
+        // Clockwise from the right bottom, like `path::rounded_rectangle`:
+        let corners = [
+            (rect.right_bottom(), vec2(-1.0, -1.0), rounding.se),
+            (rect.left_bottom(), vec2(1.0, -1.0), rounding.sw),
+            (rect.left_top(), vec2(1.0, 1.0), rounding.nw),
+            (rect.right_top(), vec2(-1.0, 1.0), rounding.ne),
+        ];
+
+        let idx = out.vertices.len() as u32;
+        out.reserve_vertices(GAUSSIAN_FALLOFF.len() * ring_len as usize);
+        for (ring, &opacity) in GAUSSIAN_FALLOFF.iter().enumerate() {
+            let t = ring as f32 / (GAUSSIAN_FALLOFF.len() - 1) as f32;
+            let expansion = (blur_width * t - half_blur).max(-max_inset);
+            let color = mul_color(color, opacity);
+
+            for (quadrant, &(corner, inwards, radius)) in corners.iter().enumerate() {
+                // Expanding a rounded corner keeps its center and grows its radius,
+                // and a sharp corner shrunk inwards stays sharp:
+                let corner = corner - expansion * inwards;
+                let radius = (radius + expansion).at_least(0.0);
+                let center = corner + radius * inwards;
+                for i in 0..=segments {
+                    let angle = (quadrant as f32 + i as f32 / segments as f32)
+                        * std::f32::consts::FRAC_PI_2;
+                    out.colored_vertex(center + radius * Vec2::angled(angle), color);
+                }
+            }
+        }
+
+        // The opaque inside:
+        out.reserve_triangles(
+            (ring_len as usize - 2) + 2 * ring_len as usize * (GAUSSIAN_FALLOFF.len() - 1),
+        );
+        for i in 2..ring_len {
+            out.add_triangle(idx, idx + i - 1, idx + i);
+        }
+
+        // The falloff between each pair of neighboring rings:
+        for ring in 0..GAUSSIAN_FALLOFF.len() as u32 - 1 {
+            let inner = idx + ring * ring_len;
+            let outer = inner + ring_len;
+            for i0 in 0..ring_len {
+                let i1 = (i0 + 1) % ring_len;
+                out.add_triangle(inner + i0, inner + i1, outer + i0);
+                out.add_triangle(inner + i1, outer + i1, outer + i0);
+            }
+        }
+    }
+
     /// Tessellate a single [`TextShape`] into a [`Mesh`].
     /// * `text_shape`: the text to tessellate.
     /// * `out`: triangles are appended to this.
+    ///
+    /// This does not include the glyphs of SDF fonts, see [`Self::tessellate_sdf_text`].
     pub fn tessellate_text(&mut self, text_shape: &TextShape, out: &mut Mesh) {
+        self.tessellate_text_meshes(text_shape, false, out);
+    }
+
+    /// Tessellate the SDF glyphs (see [`crate::text::FontTweak::sdf`]) of a [`TextShape`] into a [`Mesh`].
+    ///
+    /// Does nothing unless [`Self::with_sdf_font_texture`] has been called.
+    /// * `text_shape`: the text to tessellate.
+    /// * `out`: triangles are appended to this. Should use the SDF font texture.
+    pub fn tessellate_sdf_text(&mut self, text_shape: &TextShape, out: &mut Mesh) {
+        if self.sdf_font_texture.is_some() {
+            self.tessellate_text_meshes(text_shape, true, out);
+        }
+    }
+
+    /// Tessellate either the [`crate::text::RowVisuals::mesh`]
+    /// or the [`crate::text::RowVisuals::sdf_mesh`] of each row.
+    fn tessellate_text_meshes(&mut self, text_shape: &TextShape, sdf: bool, out: &mut Mesh) {
         let TextShape {
             pos: galley_pos,
             galley,
@@ -1704,15 +2834,21 @@ impl Tessellator {
             self.round_to_pixel(galley_pos.y),
         );
 
-        let uv_normalizer = vec2(
-            1.0 / self.font_tex_size[0] as f32,
-            1.0 / self.font_tex_size[1] as f32,
-        );
+        let tex_size = match self.sdf_font_texture {
+            Some((_, sdf_tex_size)) if sdf => sdf_tex_size,
+            _ => self.font_tex_size,
+        };
+        let uv_normalizer = vec2(1.0 / tex_size[0] as f32, 1.0 / tex_size[1] as f32);
 
         let rotator = Rot2::from_angle(*angle);
 
         for row in &galley.rows {
-            if row.visuals.mesh.is_empty() {
+            let row_mesh = if sdf {
+                &row.visuals.sdf_mesh
+            } else {
+                &row.visuals.mesh
+            };
+            if row.visuals.mesh.is_empty() && row.visuals.sdf_mesh.is_empty() {
                 continue;
             }
 
@@ -1730,17 +2866,11 @@ impl Tessellator {
 
             let index_offset = out.vertices.len() as u32;
 
-            out.indices.extend(
-                row.visuals
-                    .mesh
-                    .indices
-                    .iter()
-                    .map(|index| index + index_offset),
-            );
+            out.indices
+                .extend(row_mesh.indices.iter().map(|index| index + index_offset));
 
             out.vertices.extend(
-                row.visuals
-                    .mesh
+                row_mesh
                     .vertices
                     .iter()
                     .enumerate()
@@ -1749,7 +2879,7 @@ impl Tessellator {
 
                         if let Some(override_text_color) = override_text_color {
                             // Only override the glyph color (not background color, strike-through color, etc)
-                            if row.visuals.glyph_vertex_range.contains(&i) {
+                            if sdf || row.visuals.glyph_vertex_range.contains(&i) {
                                 color = *override_text_color;
                             }
                         } else if color == Color32::PLACEHOLDER {
@@ -1776,10 +2906,14 @@ impl Tessellator {
                     }),
             );
 
-            if *underline != Stroke::NONE {
+            if *underline != Stroke::NONE && !sdf {
+                // Rotate the bottom of the row rather than taking the bottom of the rotated row,
+                // so that the underline follows the text:
+                let bounds = row.visuals.mesh_bounds;
+                let underline_points = [bounds.left_bottom(), bounds.right_bottom()]
+                    .map(|point| galley_pos + rotator * point.to_vec2());
                 self.scratchpad_path.clear();
-                self.scratchpad_path
-                    .add_line_segment([row_rect.left_bottom(), row_rect.right_bottom()]);
+                self.scratchpad_path.add_line_segment(underline_points);
                 self.scratchpad_path.stroke_open(
                     self.feathering,
                     &PathStroke::from(*underline),
@@ -1940,7 +3074,7 @@ impl Tessellator {
             p.clip_rect.is_positive()
                 && match &p.primitive {
                     Primitive::Mesh(mesh) => !mesh.is_empty(),
-                    Primitive::Callback(_) => true,
+                    Primitive::Callback(_) | Primitive::Backdrop(_) => true,
                 }
         });
 
@@ -1966,19 +3100,38 @@ impl Tessellator {
         // and callocations that is only worth it for large shapes.
         fn should_parallelize(shape: &Shape) -> bool {
             match shape {
-                Shape::Vec(shapes) => 4 < shapes.len() || shapes.iter().any(should_parallelize),
+                Shape::Vec(shapes) => {
+                    // SDF text and backdrops need primitives of their own, which we can't produce in parallel:
+                    (4 < shapes.len() || shapes.iter().any(should_parallelize))
+                        && !shapes.iter().any(needs_own_primitive)
+                }
 
                 Shape::Path(path_shape) => 32 < path_shape.points.len(),
 
-                Shape::QuadraticBezier(_) | Shape::CubicBezier(_) | Shape::Ellipse(_) => true,
+                Shape::QuadraticBezier(_)
+                | Shape::CubicBezier(_)
+                | Shape::CurvePath(_)
+                | Shape::Ellipse(_)
+                | Shape::CircleSegment(_) => true,
 
                 Shape::Noop
                 | Shape::Text(_)
                 | Shape::Circle(_)
                 | Shape::Mesh(_)
+                | Shape::InstancedMesh(_)
                 | Shape::LineSegment { .. }
                 | Shape::Rect(_)
-                | Shape::Callback(_) => false,
+                | Shape::Callback(_)
+                | Shape::Backdrop(_) => false,
+            }
+        }
+
+        fn needs_own_primitive(shape: &Shape) -> bool {
+            match shape {
+                Shape::Vec(shapes) => shapes.iter().any(needs_own_primitive),
+                Shape::Text(text_shape) => text_shape.galley.has_sdf_glyphs(),
+                Shape::Backdrop(_) => true,
+                _ => false,
             }
         }
 
@@ -2022,6 +3175,7 @@ impl Tessellator {
                     clipped_primitive,
                     ClippedPrimitive {
                         clip_rect: Rect::EVERYTHING, // whatever
+                        clip_rounding: Rounding::ZERO,
                         primitive: Primitive::Mesh(clip_rect_mesh),
                     },
                 ]
@@ -2050,6 +3204,7 @@ fn test_tessellator() {
     let shape = Shape::Vec(shapes);
     let clipped_shapes = vec![ClippedShape {
         clip_rect: rect,
+        clip_rounding: Rounding::ZERO,
         shape,
     }];
 
@@ -2106,3 +3261,592 @@ fn path_bounding_box() {
         );
     }
 }
+
+#[test]
+fn rotated_text() {
+    use crate::*;
+
+    let fonts = text::Fonts::new(1.0, 1024, text::FontDefinitions::default());
+    let galley = fonts.layout_no_wrap(
+        "Axis label".to_owned(),
+        FontId::proportional(14.0),
+        Color32::WHITE,
+    );
+    let pos = pos2(100.0, 100.0);
+    let underline_color = Color32::RED;
+    let text_shape = TextShape::new(pos, galley.clone(), Color32::WHITE)
+        .with_underline(Stroke::new(1.0, underline_color))
+        .with_angle(std::f32::consts::TAU / 4.0);
+
+    let mut tessellator =
+        Tessellator::new(1.0, Default::default(), fonts.font_image_size(), vec![]);
+    let mut mesh = Mesh::default();
+    tessellator.tessellate_text(&text_shape, &mut mesh);
+    assert!(!mesh.is_empty());
+
+    // Rotated 90° clockwise, the text runs downwards, with its bottom (and underline) to the left:
+    let bounds = |color_filter: &dyn Fn(Color32) -> bool| {
+        Rect::from_points(
+            &mesh
+                .vertices
+                .iter()
+                .filter(|vertex| color_filter(vertex.color))
+                .map(|vertex| vertex.pos)
+                .collect::<Vec<_>>(),
+        )
+    };
+    let glyphs = bounds(&|color| color == Color32::WHITE);
+    let underline = bounds(&|color| color != Color32::WHITE);
+    assert!(glyphs.height() > glyphs.width());
+    assert!(underline.width() < 3.0, "{underline:?}");
+    assert!(underline.height() > 0.9 * galley.size().x, "{underline:?}");
+    assert!(underline.center().x < glyphs.center().x);
+    assert!(underline.max.x <= pos.x);
+
+    let visual_rect = text_shape.visual_bounding_rect();
+    assert!(visual_rect.expand(0.5).contains_rect(glyphs));
+}
+
+#[test]
+fn dashed_path() {
+    use crate::*;
+
+    let dash_ends = |points: &[Pos2], path_type: PathType, pattern: LinePattern| {
+        let mut path = Path::default();
+        match path_type {
+            PathType::Open => path.add_open_points(points),
+            PathType::Closed => path.add_line_loop(points),
+        }
+        dashes_from_path(&path.0, path_type, &pattern)
+            .iter()
+            .map(|dash| (dash[0].pos.round(), dash.last().unwrap().pos.round()))
+            .collect::<Vec<_>>()
+    };
+
+    // The dashes follow the length of the path around the corner:
+    let corner = [pos2(0.0, 0.0), pos2(30.0, 0.0), pos2(30.0, 30.0)];
+    assert_eq!(
+        dash_ends(&corner, PathType::Open, LinePattern::dashed(10.0, 10.0)),
+        [
+            (pos2(0.0, 0.0), pos2(10.0, 0.0)),
+            (pos2(20.0, 0.0), pos2(30.0, 0.0)),
+            (pos2(30.0, 10.0), pos2(30.0, 20.0)),
+        ]
+    );
+    assert_eq!(
+        dash_ends(
+            &corner,
+            PathType::Open,
+            LinePattern::dashed(10.0, 10.0).with_offset(15.0)
+        ),
+        [
+            (pos2(5.0, 0.0), pos2(15.0, 0.0)),
+            (pos2(25.0, 0.0), pos2(30.0, 5.0)),
+            (pos2(30.0, 15.0), pos2(30.0, 25.0)),
+        ]
+    );
+
+    // The dash at the end of a closed path continues into the start of it:
+    let square = [
+        pos2(0.0, 0.0),
+        pos2(40.0, 0.0),
+        pos2(40.0, 40.0),
+        pos2(0.0, 40.0),
+    ];
+    let dashes = dash_ends(
+        &square,
+        PathType::Closed,
+        LinePattern::dashed(10.0, 10.0).with_offset(5.0),
+    );
+    assert_eq!(dashes.len(), 8);
+    assert_eq!(dashes[0], (pos2(0.0, 5.0), pos2(5.0, 0.0)));
+
+    // Dash-dot:
+    assert_eq!(
+        dash_ends(
+            &corner,
+            PathType::Open,
+            LinePattern {
+                dash_lengths: [10.0, 5.0, 1.0, 5.0],
+                dash_offset: 0.0
+            }
+        ),
+        [
+            (pos2(0.0, 0.0), pos2(10.0, 0.0)),
+            (pos2(15.0, 0.0), pos2(16.0, 0.0)),
+            (pos2(21.0, 0.0), pos2(30.0, 1.0)),
+            (pos2(30.0, 6.0), pos2(30.0, 7.0)),
+            (pos2(30.0, 12.0), pos2(30.0, 22.0)),
+            (pos2(30.0, 27.0), pos2(30.0, 28.0)),
+        ]
+    );
+
+    // Dashes are tessellated separately, solid lines as one piece:
+    let stroke = PathStroke::new(2.0, Color32::WHITE);
+    let mut path = Path::default();
+    path.add_open_points(&corner);
+    let mut solid = Mesh::default();
+    path.stroke_open(1.0, &stroke, &mut solid);
+    let mut dashed = Mesh::default();
+    path.stroke_open(
+        1.0,
+        &stroke.with_pattern(LinePattern::dashed(10.0, 10.0)),
+        &mut dashed,
+    );
+    assert_eq!(solid.vertices.len(), 3 * 4);
+    assert_eq!(dashed.vertices.len(), (2 + 2 + 2) * 4); // the second dash ends at the corner
+}
+
+#[test]
+fn circle_segments() {
+    use crate::*;
+    use std::f32::consts::TAU;
+
+    let mut tessellator = Tessellator::new(1.0, Default::default(), [1024, 1024], vec![]);
+    let mut tessellate = |shape: CircleSegmentShape| {
+        let mut mesh = Mesh::default();
+        tessellator.tessellate_circle_segment(shape, &mut mesh);
+        mesh
+    };
+
+    let center = pos2(50.0, 50.0);
+    let stroke = Stroke::new(1.0, Color32::WHITE);
+
+    // A full turn is just a circle:
+    let mut circle = Mesh::default();
+    Tessellator::new(1.0, Default::default(), [1024, 1024], vec![]).tessellate_circle(
+        CircleShape {
+            center,
+            radius: 20.0,
+            fill: Color32::RED,
+            stroke,
+            stroke_pattern: LinePattern::SOLID,
+        },
+        &mut circle,
+    );
+    let full_pie = CircleSegmentShape::pie(center, 20.0, 1.0, 1.0 + TAU, Color32::RED, stroke);
+    assert_eq!(tessellate(full_pie), circle);
+
+    // Everything stays within the bounding rectangle, also for very narrow slices:
+    for span in [TAU / 3.0, 0.7 * TAU, 0.01, 1e-4, 1e-6, 0.0] {
+        for shape in [
+            CircleSegmentShape::arc(center, 20.0, 1.0, 1.0 + span, stroke),
+            CircleSegmentShape::pie(center, 20.0, 1.0, 1.0 + span, Color32::RED, stroke),
+            CircleSegmentShape::ring(center, 10.0, 20.0, 1.0 + span, 1.0, Color32::RED, stroke),
+        ] {
+            let mesh = tessellate(shape);
+            let bounds = shape.visual_bounding_rect().expand(1.0);
+            for vertex in &mesh.vertices {
+                assert!(bounds.contains(vertex.pos), "{shape:?}: {vertex:?}");
+            }
+            if span == 0.0 && shape.inner_radius.is_some() {
+                assert!(mesh.is_empty(), "{shape:?}");
+            }
+        }
+    }
+}
+
+#[test]
+fn even_odd_fill() {
+    use crate::*;
+    use std::f32::consts::TAU;
+
+    let center = pos2(50.0, 50.0);
+    let polygon = |corners: usize, step: usize| -> Vec<Pos2> {
+        (0..corners)
+            .map(|i| center + 40.0 * Vec2::angled((i * step) as f32 * TAU / corners as f32))
+            .collect()
+    };
+    let pentagram = polygon(5, 2);
+
+    let mut path = Path::default();
+    path.add_line_loop(&pentagram);
+    let mut mesh = Mesh::default();
+    path.fill_even_odd(0.0, Color32::WHITE, &mut mesh);
+
+    let coverage = |p: Pos2| {
+        mesh.indices
+            .chunks(3)
+            .filter(|triangle| {
+                let [a, b, c] = [0, 1, 2].map(|i| mesh.vertices[triangle[i] as usize].pos);
+                let side = |u: Pos2, v: Pos2| (v - u).x * (p - u).y - (v - u).y * (p - u).x;
+                let sides = [side(a, b), side(b, c), side(c, a)];
+                sides.iter().all(|&s| 0.0 < s) || sides.iter().all(|&s| s < 0.0)
+            })
+            .count()
+    };
+
+    // The tips are filled once, and where the pentagram overlaps itself it is left empty:
+    assert_eq!(coverage(center + 28.0 * Vec2::angled(0.07)), 1);
+    assert_eq!(coverage(center + 28.0 * Vec2::angled(TAU / 5.0 + 0.07)), 1);
+    assert_eq!(coverage(center + vec2(0.3, 0.7)), 0);
+    assert_eq!(coverage(center + 30.0 * Vec2::angled(TAU / 10.0)), 0);
+}
+
+#[test]
+fn per_corner_rounding() {
+    use crate::*;
+
+    let rounded_rectangle = |rect: Rect, rounding: Rounding| {
+        let mut points = vec![];
+        path::rounded_rectangle(&mut points, rect, rounding);
+
+        for (a, b) in points.iter().zip(points.iter().cycle().skip(1)) {
+            assert!(1e-3 < a.distance(*b), "Duplicated vertex at {a:?}");
+        }
+        for p in &points {
+            assert!(rect.expand(1e-3).contains(*p), "{p:?} outside of {rect:?}");
+        }
+
+        let mut mesh = Mesh::default();
+        Tessellator::new(1.0, Default::default(), [1024, 1024], vec![]).tessellate_rect(
+            &RectShape::filled(rect, rounding, Color32::WHITE),
+            &mut mesh,
+        );
+        // The feathering is half a pixel wide, but mitered, so it reaches further out at the corners,
+        // e.g. where a square corner meets the end of a rounded one at a bit less than a right angle:
+        for vertex in &mesh.vertices {
+            let distance = rect.distance_to_pos(vertex.pos);
+            assert!(
+                distance < 0.75,
+                "{vertex:?} is {distance} outside of {rect:?}"
+            );
+        }
+
+        points
+    };
+    let has_point =
+        |points: &[Pos2], expected: Pos2| points.iter().any(|p| p.distance(expected) < 1e-3);
+
+    // A tab, rounded on top with radii that add up to more than its height.
+    // Both radii are scaled down to the height, and the bottom stays square:
+    let tab = Rect::from_min_size(pos2(0.0, 0.0), vec2(80.0, 20.0));
+    let points = rounded_rectangle(tab, Rounding::top(30.0));
+    assert!(has_point(&points, tab.left_bottom()));
+    assert!(has_point(&points, tab.right_bottom()));
+    assert!(has_point(&points, pos2(20.0, 0.0)));
+    assert!(has_point(&points, pos2(60.0, 0.0)));
+    assert!(!has_point(&points, tab.left_top()));
+
+    // A pill with one square end:
+    let pill = Rect::from_min_size(pos2(0.0, 0.0), vec2(100.0, 30.0));
+    let points = rounded_rectangle(pill, Rounding::left(100.0));
+    assert!(has_point(&points, pill.left_center()));
+    assert!(has_point(&points, pos2(15.0, 0.0)));
+    assert!(has_point(&points, pos2(15.0, 30.0)));
+    assert!(has_point(&points, pill.right_top()));
+    assert!(has_point(&points, pill.right_bottom()));
+
+    // Uneven radii keep their proportions:
+    let rounding = Rounding {
+        nw: 30.0,
+        ne: 10.0,
+        sw: 0.0,
+        se: 0.0,
+    };
+    let points = rounded_rectangle(Rect::from_min_size(Pos2::ZERO, vec2(20.0, 50.0)), rounding);
+    assert!(has_point(&points, pos2(15.0, 0.0)));
+    assert!(has_point(&points, pos2(0.0, 15.0)));
+    assert!(has_point(&points, pos2(20.0, 5.0)));
+}
+
+#[test]
+fn blurred_rect() {
+    use crate::*;
+
+    let rect = Rect::from_min_size(pos2(0.0, 0.0), vec2(100.0, 60.0));
+    let shadow = Shadow {
+        offset: Vec2::ZERO,
+        blur: 20.0,
+        spread: 0.0,
+        color: Color32::BLACK,
+    };
+    let mut mesh = Mesh::default();
+    Tessellator::new(1.0, Default::default(), [1024, 1024], vec![])
+        .tessellate_rect(&shadow.as_shape(rect, 8.0), &mut mesh);
+    assert!(mesh.is_valid());
+
+    // A few rings of corner vertices, not a pixel grid:
+    let ring_len = mesh.vertices.len() / GAUSSIAN_FALLOFF.len();
+    assert_eq!(ring_len * GAUSSIAN_FALLOFF.len(), mesh.vertices.len());
+    assert!(ring_len <= 4 * 17);
+
+    // Opaque half the blur inside the edge, fading out to half the blur outside of it:
+    for vertex in &mesh.vertices {
+        assert!(rect.expand(10.01).contains(vertex.pos), "{vertex:?}");
+        if vertex.color == Color32::BLACK {
+            assert!(rect.shrink(9.99).contains(vertex.pos), "{vertex:?}");
+        }
+    }
+    let right_edge: Vec<Vertex> = mesh.vertices.iter().step_by(ring_len).copied().collect();
+    assert_eq!(right_edge[0].pos.x, 90.0);
+    assert_eq!(right_edge.last().unwrap().pos.x, 110.0);
+    assert_eq!(right_edge.last().unwrap().color, Color32::TRANSPARENT);
+    for pair in right_edge.windows(2) {
+        assert!(pair[0].pos.x < pair[1].pos.x);
+        assert!(pair[0].color.a() > pair[1].color.a());
+    }
+}
+
+#[test]
+fn rounded_clip_rect() {
+    use crate::*;
+
+    let rect = Rect::from_min_size(pos2(0.0, 0.0), vec2(100.0, 60.0));
+    let tessellate = |clip_rounding: Rounding| {
+        let clipped_shape = ClippedShape {
+            clip_rect: rect,
+            clip_rounding,
+            shape: Shape::rect_filled(rect, 0.0, Color32::WHITE),
+        };
+        let mut primitives = Tessellator::new(1.0, Default::default(), [1024, 1024], vec![])
+            .tessellate_shapes(vec![clipped_shape]);
+        assert_eq!(primitives.len(), 1);
+        assert_eq!(primitives[0].clip_rounding, clip_rounding);
+        match primitives.remove(0).primitive {
+            Primitive::Mesh(mesh) => mesh,
+            Primitive::Callback(_) | Primitive::Backdrop(_) => panic!(),
+        }
+    };
+
+    let square = tessellate(Rounding::ZERO);
+    let rounded = tessellate(Rounding::same(10.0));
+    assert!(rounded.is_valid());
+    assert!(square.vertices.len() < rounded.vertices.len());
+
+    // Nothing visible outside the arcs, give or take half a pixel of feathering:
+    let corner_centers = [
+        pos2(10.0, 10.0),
+        pos2(90.0, 10.0),
+        pos2(10.0, 50.0),
+        pos2(90.0, 50.0),
+    ];
+    for &index in &rounded.indices {
+        let vertex = rounded.vertices[index as usize];
+        for center in corner_centers {
+            let offset = vertex.pos - center;
+            let in_corner =
+                0.0 < offset.x * (center.x - 50.0) && 0.0 < offset.y * (center.y - 30.0);
+            if in_corner && 0 < vertex.color.a() {
+                assert!(offset.length() <= 10.51, "{vertex:?}");
+            }
+        }
+    }
+
+    // Each corner loses the area between the square and the arc:
+    let covered_area = |mesh: &Mesh| {
+        mesh.indices
+            .chunks_exact(3)
+            .map(|triangle| {
+                let [a, b, c] = [0, 1, 2].map(|i| mesh.vertices[triangle[i] as usize]);
+                let area = 0.5
+                    * ((b.pos - a.pos).x * (c.pos - a.pos).y
+                        - (b.pos - a.pos).y * (c.pos - a.pos).x)
+                        .abs();
+                let alpha = (a.color.a() as f32 + b.color.a() as f32 + c.color.a() as f32) / 765.0;
+                area * alpha
+            })
+            .sum::<f32>()
+    };
+    let corner_area = (4.0 - std::f32::consts::PI) * 100.0;
+    assert!((covered_area(&square) - 6000.0).abs() < 3.0);
+    assert!((covered_area(&rounded) - (6000.0 - corner_area)).abs() < 3.0);
+}
+
+#[test]
+fn instanced_mesh() {
+    use crate::*;
+
+    let mut tessellator = Tessellator::new(1.0, Default::default(), [1024, 1024], vec![]);
+    let rect = Rect::from_min_size(pos2(0.0, 0.0), vec2(80.0, 40.0));
+    let mut mesh = Mesh::default();
+    tessellator.tessellate_shape(
+        RectShape::new(rect, 6.0, Color32::WHITE, (1.0, Color32::GRAY)).into(),
+        &mut mesh,
+    );
+    let mesh = std::sync::Arc::new(mesh);
+
+    let offset = vec2(100.0, 50.0);
+    let tint = Color32::from_rgb(255, 128, 0);
+    let mut out = Mesh::default();
+    for _ in 0..2 {
+        tessellator.tessellate_shape(Shape::mesh_instance(mesh.clone(), offset, tint), &mut out);
+    }
+    assert!(out.is_valid());
+    assert_eq!(out.vertices.len(), 2 * mesh.vertices.len());
+    assert_eq!(out.indices.len(), 2 * mesh.indices.len());
+
+    // The second instance refers to its own vertices:
+    let n = mesh.vertices.len() as u32;
+    assert_eq!(out.indices[mesh.indices.len()], mesh.indices[0] + n);
+
+    for (instance, original) in out.vertices.iter().zip(mesh.vertices.iter().cycle()) {
+        assert_eq!(instance.pos, original.pos + offset);
+        if original.color == Color32::WHITE {
+            assert_eq!(instance.color, tint);
+        }
+    }
+
+    // Moving the shape moves the instance:
+    let mut shape = Shape::mesh_instance(mesh.clone(), offset, tint);
+    shape.translate(vec2(1.0, 2.0));
+    let moved = rect.translate(offset + vec2(1.0, 2.0));
+    let bounds = shape.visual_bounding_rect();
+    assert!(bounds.contains_rect(moved) && moved.expand(1.5).contains_rect(bounds));
+
+    // The bounds of the mesh are kept when moving a clone:
+    let instance = InstancedMeshShape::new(mesh.clone(), Vec2::ZERO);
+    assert_eq!(instance.mesh_bounds, mesh.calc_bounds());
+    let moved = instance.clone().with_offset(offset);
+    assert_eq!(
+        moved.visual_bounding_rect(),
+        mesh.calc_bounds().translate(offset)
+    );
+}
+
+#[test]
+fn crisp_hairline() {
+    use crate::*;
+
+    /// The alpha of each pixel in a column of the mesh, sampled at the pixel centers.
+    fn rasterize_column(mesh: &Mesh, pixels_per_point: f32, x: f32, height: usize) -> Vec<u8> {
+        let cross = |a: Vec2, b: Vec2| a.x * b.y - a.y * b.x;
+        (0..height)
+            .map(|y| {
+                let sample = pos2(x + 0.5, y as f32 + 0.5);
+                for triangle in mesh.indices.chunks_exact(3) {
+                    let vertices = [0, 1, 2].map(|i| mesh.vertices[triangle[i] as usize]);
+                    let [a, b, c] = vertices.map(|v| pixels_per_point * v.pos);
+                    let area = cross(b - a, c - a);
+                    if area == 0.0 {
+                        continue;
+                    }
+                    let wa = cross(b - sample, c - sample) / area;
+                    let wb = cross(c - sample, a - sample) / area;
+                    let wc = 1.0 - wa - wb;
+                    if 0.0 <= wa && 0.0 <= wb && 0.0 <= wc {
+                        let [va, vb, vc] = vertices.map(|v| v.color.a() as f32);
+                        return (wa * va + wb * vb + wc * vc).round() as u8;
+                    }
+                }
+                0
+            })
+            .collect()
+    }
+
+    for pixels_per_point in [1.0, 1.5, 2.0] {
+        let line = [pos2(1.3, 5.3), pos2(8.7, 5.3)];
+        let stroke = PathStroke::new(1.0, Color32::WHITE);
+        let column = |shape: Shape| {
+            let mut mesh = Mesh::default();
+            Tessellator::new(pixels_per_point, Default::default(), [1024, 1024], vec![])
+                .tessellate_shape(shape, &mut mesh);
+            assert!(mesh.is_valid());
+            rasterize_column(&mesh, pixels_per_point, 5.0 * pixels_per_point, 20)
+        };
+
+        // Before: the feathering smears the line across more pixels than it is wide.
+        let blurry = column(Shape::line_segment(line, stroke.clone()));
+        assert!(
+            blurry.iter().any(|&alpha| 0 < alpha && alpha < 255),
+            "{pixels_per_point}: {blurry:?}"
+        );
+
+        // After: whole pixels only, as many rows as the line is wide, next to each other.
+        let crisp = column(Shape::line_segment_crisp(line, stroke));
+        assert!(
+            crisp.iter().all(|&alpha| alpha == 0 || alpha == 255),
+            "{pixels_per_point}: {crisp:?}"
+        );
+        let rows: Vec<usize> = (0..crisp.len()).filter(|&y| crisp[y] == 255).collect();
+        assert_eq!(rows.len(), pixels_per_point.round() as usize, "{crisp:?}");
+        assert_eq!(rows.last().unwrap() - rows[0] + 1, rows.len());
+        assert!((rows[0] as f32 - 5.3 * pixels_per_point).abs() <= 1.0);
+    }
+}
+
+#[test]
+fn backdrop_fallback() {
+    use crate::*;
+
+    let rect = Rect::from_min_size(pos2(10.0, 10.0), vec2(100.0, 50.0));
+    let tint = Color32::from_white_alpha(40);
+    let tessellate = |backdrop_blur: bool| {
+        let options = TessellationOptions {
+            backdrop_blur,
+            ..Default::default()
+        };
+        Tessellator::new(1.0, options, [1024, 1024], vec![]).tessellate_shapes(vec![ClippedShape {
+            clip_rect: Rect::EVERYTHING,
+            clip_rounding: Rounding::ZERO,
+            shape: Shape::backdrop(rect, 8.0, 12.0, tint),
+        }])
+    };
+
+    // Without backend support, a mostly opaque fill is painted instead:
+    let fallback = tessellate(false);
+    assert_eq!(fallback.len(), 1);
+    let Primitive::Mesh(mesh) = &fallback[0].primitive else {
+        panic!("Expected a mesh");
+    };
+    let opaque = mesh
+        .vertices
+        .iter()
+        .map(|vertex| vertex.color.a())
+        .max()
+        .unwrap();
+    assert_eq!(opaque, BackdropShape::FALLBACK_MIN_ALPHA);
+
+    // With it, the backdrop is handed to the backend, and the tint follows on top:
+    let blurred = tessellate(true);
+    assert_eq!(blurred.len(), 2);
+    let Primitive::Backdrop(backdrop) = &blurred[0].primitive else {
+        panic!("Expected a backdrop");
+    };
+    assert_eq!(backdrop.rect, rect);
+    assert_eq!(backdrop.blur_radius, 12.0);
+    assert!(backdrop.mesh.is_valid());
+    let uv_bounds = Rect::from_points(
+        &backdrop
+            .mesh
+            .vertices
+            .iter()
+            .map(|v| v.uv)
+            .collect::<Vec<_>>(),
+    );
+    assert!(Rect::from_min_max(Pos2::ZERO, pos2(1.0, 1.0))
+        .expand(0.01)
+        .contains_rect(uv_bounds));
+    let Primitive::Mesh(tint_mesh) = &blurred[1].primitive else {
+        panic!("Expected a mesh");
+    };
+    assert!(tint_mesh.vertices.iter().all(|v| v.color.a() <= tint.a()));
+}
+
+#[test]
+fn dashed_rect() {
+    use crate::*;
+
+    let rect = Rect::from_min_size(pos2(10.0, 10.0), vec2(40.0, 40.0));
+    let tessellate = |shape: RectShape| {
+        let mut mesh = Mesh::default();
+        Tessellator::new(1.0, Default::default(), [1024, 1024], vec![])
+            .tessellate_rect(&shape, &mut mesh);
+        mesh
+    };
+
+    let solid = tessellate(RectShape::stroke(rect, 0.0, (2.0, Color32::WHITE)));
+    let dashed = tessellate(
+        RectShape::stroke(rect, 0.0, (2.0, Color32::WHITE))
+            .with_stroke_pattern(LinePattern::dashed(10.0, 10.0)),
+    );
+
+    // The outline is 160 points long, so it gets eight dashes of two points each:
+    assert_eq!(solid.vertices.len(), 4 * 4);
+    assert_eq!(dashed.vertices.len(), 8 * 2 * 4);
+
+    // The gaps are left out, e.g. in the middle of the top edge:
+    let gap = pos2(25.0, 10.0);
+    assert!(dashed.vertices.iter().all(|v| 4.0 < v.pos.distance(gap)));
+    assert!(solid.calc_bounds().contains_rect(dashed.calc_bounds()));
+}
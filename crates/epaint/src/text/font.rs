@@ -1,11 +1,19 @@
 use crate::{
     mutex::{Mutex, RwLock},
-    text::FontTweak,
-    TextureAtlas,
+    text::{
+        sdf::{SdfAtlas, SDF_REFERENCE_SCALE_IN_PIXELS, SDF_SPREAD_IN_PIXELS},
+        FauxStyle, FontTweak, FourCC,
+    },
+    Stroke, TextureAtlas,
 };
-use emath::{vec2, Vec2};
+use ecolor::Color32;
+use emath::{pos2, vec2, Pos2, Vec2};
 use std::collections::BTreeSet;
-use std::sync::Arc;
+use std::ops::RangeInclusive;
+use std::sync::{
+    atomic::{AtomicU32, Ordering::Relaxed},
+    Arc, OnceLock,
+};
 
 // ----------------------------------------------------------------------------
 
@@ -24,6 +32,16 @@ pub struct UvRect {
 
     /// Bottom right corner (exclusive).
     pub max: [u16; 2],
+
+    /// If `true`, the texels carry their own color (e.g. a color emoji),
+    /// and the glyph should not be tinted by the text color.
+    pub is_colored: bool,
+
+    /// If `true`, this is a signed distance field in the SDF font texture,
+    /// rather than coverage in the normal font texture.
+    ///
+    /// See [`crate::text::FontTweak::sdf`].
+    pub is_sdf: bool,
 }
 
 impl UvRect {
@@ -58,8 +76,229 @@ impl Default for GlyphInfo {
     }
 }
 
+/// A glyph in the font atlas, and what it belongs to. For debugging.
+///
+/// See [`crate::text::Fonts::atlas_glyphs`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AtlasGlyph {
+    /// The name of the font, i.e. the key in [`crate::text::FontDefinitions::font_data`].
+    pub font_name: String,
+
+    /// The size the glyph was rasterized at.
+    pub scale_in_pixels: u32,
+
+    /// The character the glyph is for.
+    ///
+    /// `None` for glyphs that don't map to any single character,
+    /// e.g. ligatures from shaping or substitutions from [`crate::text::TextFormat::font_features`].
+    pub chr: Option<char>,
+
+    /// Top left corner in the atlas, in texels.
+    pub min: [u16; 2],
+
+    /// Bottom right corner in the atlas, in texels (exclusive).
+    pub max: [u16; 2],
+}
+
+/// Where to draw a line under or through text, as recommended by the font.
+///
+/// See [`FontImpl::underline_metrics`] and [`FontImpl::strikeout_metrics`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LineMetrics {
+    /// Distance from the baseline to the center of the line.
+    ///
+    /// Positive is down. Unit: points.
+    pub y_offset: f32,
+
+    /// Unit: points.
+    pub thickness: f32,
+}
+
+/// The outline of a glyph, for custom text effects such as stroking or morphing the glyph.
+///
+/// All coordinates are in points, relative to the baseline origin of the glyph
+/// (i.e. [`crate::text::Glyph::pos`]), and scaled and offset the same way as the rasterized glyph.
+///
+/// See [`FontImpl::glyph_outline`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GlyphOutline {
+    /// Curves that follow each other form a closed contour.
+    pub curves: Vec<GlyphOutlineCurve>,
+}
+
+/// One segment of a [`GlyphOutline`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GlyphOutlineCurve {
+    /// A straight line from the first point to the second.
+    Line([Pos2; 2]),
+
+    /// A quadratic bezier curve, with the control point in the middle.
+    Quad([Pos2; 3]),
+
+    /// A cubic bezier curve, with the two control points in the middle.
+    Cubic([Pos2; 4]),
+}
+
+impl GlyphOutlineCurve {
+    #[inline]
+    fn start(&self) -> Pos2 {
+        match self {
+            Self::Line([start, ..]) | Self::Quad([start, ..]) | Self::Cubic([start, ..]) => *start,
+        }
+    }
+}
+
+impl GlyphOutline {
+    /// Flatten the outline into its closed contours,
+    /// e.g. for use with [`crate::PathShape::closed_line`].
+    ///
+    /// `tolerance` is the maximum distance between the curves and the lines approximating them, in points.
+    ///
+    /// Glyphs with holes (like `o`) have more than one contour.
+    /// Note that [`crate::PathShape`] can only fill convex shapes, so these are best stroked.
+    pub fn contours(&self, tolerance: f32) -> Vec<Vec<Pos2>> {
+        let mut contours: Vec<Vec<Pos2>> = vec![];
+        for curve in &self.curves {
+            let start = curve.start();
+            let points = match curve {
+                GlyphOutlineCurve::Line(points) => points.to_vec(),
+                GlyphOutlineCurve::Quad(points) => crate::QuadraticBezierShape::from_points_stroke(
+                    *points,
+                    false,
+                    Color32::TRANSPARENT,
+                    Stroke::NONE,
+                )
+                .flatten(Some(tolerance)),
+                GlyphOutlineCurve::Cubic(points) => crate::CubicBezierShape::from_points_stroke(
+                    *points,
+                    false,
+                    Color32::TRANSPARENT,
+                    Stroke::NONE,
+                )
+                .flatten(Some(tolerance)),
+            };
+
+            match contours.last_mut() {
+                Some(contour) if contour.last() == Some(&start) => {
+                    contour.extend(points.into_iter().skip(1));
+                }
+                _ => contours.push(points),
+            }
+        }
+
+        // The contours end where they started, but closed paths don't need to repeat that point:
+        for contour in &mut contours {
+            if 1 < contour.len() && contour.first() == contour.last() {
+                contour.pop();
+            }
+        }
+
+        contours
+    }
+}
+
+/// Find the first single substitution (GSUB lookup type 1) of `glyph_id`
+/// in the lookups of the `OpenType` feature `tag`.
+fn gsub_single_substitution(
+    face: &ttf_parser::Face<'_>,
+    tag: FourCC,
+    glyph_id: ttf_parser::GlyphId,
+) -> Option<ttf_parser::GlyphId> {
+    use ttf_parser::gsub::{SingleSubstitution, SubstitutionSubtable};
+
+    let gsub = face.tables().gsub?;
+    let tag = ttf_parser::Tag::from_bytes(&tag);
+
+    // There can be one feature with the same tag per script and language, so we look through all of them:
+    let mut lookup_indices = gsub
+        .features
+        .into_iter()
+        .filter(|feature| feature.tag == tag)
+        .flat_map(|feature| feature.lookup_indices);
+    lookup_indices.find_map(|lookup_index| {
+        let lookup = gsub.lookups.get(lookup_index)?;
+        lookup
+            .subtables
+            .into_iter::<SubstitutionSubtable<'_>>()
+            .find_map(|subtable| match subtable {
+                SubstitutionSubtable::Single(single) => {
+                    let coverage_index = single.coverage().get(glyph_id)?;
+                    match single {
+                        SingleSubstitution::Format1 { delta, .. } => {
+                            Some(ttf_parser::GlyphId(glyph_id.0.wrapping_add(delta as u16)))
+                        }
+                        SingleSubstitution::Format2 { substitutes, .. } => {
+                            substitutes.get(coverage_index)
+                        }
+                    }
+                }
+                _ => None,
+            })
+    })
+}
+
+/// Read the underline and strikeout metrics from a font file, since `ab_glyph` doesn't expose them.
+///
+/// These are in font units, so they need to be scaled with [`FontImpl::with_line_metrics`].
+pub(crate) fn unscaled_line_metrics(
+    font: &[u8],
+    index: u32,
+) -> (
+    Option<ttf_parser::LineMetrics>,
+    Option<ttf_parser::LineMetrics>,
+) {
+    match ttf_parser::Face::parse(font, index) {
+        Ok(face) => (face.underline_metrics(), face.strikeout_metrics()),
+        Err(_) => (None, None),
+    }
+}
+
 // ----------------------------------------------------------------------------
 
+/// [`crate::text::FontData`] parsed for shaping, so that it only needs to be parsed once.
+#[cfg(feature = "shaping")]
+pub(crate) enum ShapingFace {
+    /// Font data that lives forever, so the face can borrow it.
+    Static(Box<rustybuzz::Face<'static>>),
+
+    /// A copy of the font data, like [`ab_glyph::FontVec`] makes.
+    ///
+    /// The [`rustybuzz::Face`] borrows from it, so it is made anew each time we shape,
+    /// which is cheap compared to parsing the font.
+    Owned(owned_ttf_parser::OwnedFace),
+}
+
+#[cfg(feature = "shaping")]
+impl ShapingFace {
+    fn parse(font_data: &crate::text::FontData) -> Option<Self> {
+        match &font_data.font {
+            std::borrow::Cow::Borrowed(bytes) => {
+                rustybuzz::Face::from_slice(bytes, font_data.index)
+                    .map(|face| Self::Static(Box::new(face)))
+            }
+            std::borrow::Cow::Owned(bytes) => {
+                owned_ttf_parser::OwnedFace::from_vec(bytes.clone(), font_data.index)
+                    .ok()
+                    .map(Self::Owned)
+            }
+        }
+    }
+
+    fn with_face<R>(&self, f: impl FnOnce(&rustybuzz::Face<'_>) -> R) -> R {
+        use owned_ttf_parser::AsFaceRef as _;
+        match self {
+            Self::Static(face) => f(face),
+            Self::Owned(face) => f(&rustybuzz::Face::from_face(face.as_face_ref().clone())),
+        }
+    }
+}
+
+/// A [`ShapingFace`] shared by all sizes of a font, parsed the first time we shape with any of them.
+///
+/// `None` if the font data can't be parsed.
+#[cfg(feature = "shaping")]
+pub(crate) type SharedShapingFace = Arc<std::sync::OnceLock<Option<ShapingFace>>>;
+
 /// A specific font with a size.
 /// The interface uses points as the unit for everything.
 pub struct FontImpl {
@@ -75,9 +314,55 @@ pub struct FontImpl {
     y_offset_in_points: f32,
 
     ascent: f32,
+    descent: f32,
+    line_gap: f32,
+
+    /// Code points that will always be replaced by the replacement character.
+    ignore_characters: Arc<[RangeInclusive<char>]>,
+
+    /// Code points for which this font takes priority over earlier fonts in the family.
+    preferred_characters: Arc<[RangeInclusive<char>]>,
+
+    /// `None` if the font file doesn't specify them.
+    underline_metrics: Option<LineMetrics>,
+    strikeout_metrics: Option<LineMetrics>,
+
+    /// Synthetic bold and italic, applied when rasterizing.
+    faux_style: FauxStyle,
+
+    /// If set, glyphs are rasterized as signed distance fields into this atlas.
+    /// See [`FontTweak::sdf`].
+    sdf_atlas: Option<Arc<Mutex<SdfAtlas>>>,
+
     pixels_per_point: f32,
-    glyph_info_cache: RwLock<ahash::HashMap<char, GlyphInfo>>, // TODO(emilk): standard Mutex
+    /// Whether or not to rasterize glyphs at [`SUBPIXEL_BINS`] different horizontal offsets.
+    subpixel_positioning: bool,
+
+    /// Keyed on the character and the horizontal subpixel bin (always zero unless [`Self::subpixel_positioning`]).
+    glyph_info_cache: RwLock<ahash::HashMap<(char, u8), GlyphInfo>>, // TODO(emilk): standard Mutex
     atlas: Arc<Mutex<TextureAtlas>>,
+
+    /// Where to look for glyphs already in the atlas, e.g. from before `pixels_per_point` changed,
+    /// and the hash of the font data they are stored under.
+    glyph_store: Option<(Arc<Mutex<GlyphStore>>, u64)>,
+
+    /// The raw font file, for shaping and `OpenType` features.
+    /// `None` means we can't shape with this font.
+    font_data: Option<Arc<crate::text::FontData>>,
+
+    /// [`Self::font_data`], parsed for shaping.
+    #[cfg(feature = "shaping")]
+    shaping_face: SharedShapingFace,
+
+    /// Glyphs produced by shaping or [`Self::substituted_glyph_info`],
+    /// which are looked up by glyph id rather than by character.
+    glyph_id_cache: RwLock<ahash::HashMap<ab_glyph::GlyphId, GlyphInfo>>,
+
+    /// The single substitutions of `OpenType` features, see [`Self::substituted_glyph_info`].
+    ///
+    /// `None` means the feature doesn't substitute the glyph.
+    single_substitution_cache:
+        RwLock<ahash::HashMap<(ab_glyph::GlyphId, FourCC), Option<ab_glyph::GlyphId>>>,
 }
 
 impl FontImpl {
@@ -88,6 +373,7 @@ impl FontImpl {
         ab_glyph_font: ab_glyph::FontArc,
         scale_in_pixels: f32,
         tweak: FontTweak,
+        ignore_characters: Arc<[RangeInclusive<char>]>,
     ) -> Self {
         assert!(scale_in_pixels > 0.0);
         assert!(pixels_per_point > 0.0);
@@ -129,38 +415,259 @@ impl FontImpl {
             height_in_points: ascent - descent + line_gap,
             y_offset_in_points,
             ascent: ascent + baseline_offset,
+            descent,
+            line_gap,
+            ignore_characters,
+            preferred_characters: Arc::new([]),
+            underline_metrics: None,
+            strikeout_metrics: None,
+            faux_style: FauxStyle::default(),
+            sdf_atlas: None,
             pixels_per_point,
+            subpixel_positioning: tweak.subpixel_positioning,
             glyph_info_cache: Default::default(),
             atlas,
+            glyph_store: None,
+            font_data: None,
+            #[cfg(feature = "shaping")]
+            shaping_face: Default::default(),
+            glyph_id_cache: Default::default(),
+            single_substitution_cache: Default::default(),
         }
     }
 
-    /// Code points that will always be replaced by the replacement character.
+    /// See [`crate::text::FontData::preferred_characters`].
+    pub fn with_preferred_characters(
+        mut self,
+        preferred_characters: Arc<[RangeInclusive<char>]>,
+    ) -> Self {
+        self.preferred_characters = preferred_characters;
+        self
+    }
+
+    /// Embolden and/or slant all glyphs of this font.
     ///
-    /// See also [`invisible_char`].
-    fn ignore_character(&self, chr: char) -> bool {
-        use crate::text::FontDefinitions;
+    /// See [`crate::text::FontDefinitions::faux_families`].
+    pub fn with_faux_style(mut self, faux_style: FauxStyle) -> Self {
+        self.faux_style = faux_style;
+        self
+    }
 
-        if !FontDefinitions::builtin_font_names().contains(&self.name.as_str()) {
-            return false;
+    /// Reuse the glyphs in the given store, and put new ones there,
+    /// so that they can be reused by other [`FontImpl`]s of the same font data and size.
+    ///
+    /// `font_data_hash` identifies the font data this was created from.
+    #[inline]
+    pub(crate) fn with_glyph_store(
+        mut self,
+        glyph_store: Arc<Mutex<GlyphStore>>,
+        font_data_hash: u64,
+    ) -> Self {
+        self.glyph_store = Some((glyph_store, font_data_hash));
+        self
+    }
+
+    /// The key of this font in [`GlyphStore::begin_frame`], if it has a store.
+    pub(crate) fn glyph_store_scale(&self) -> Option<(u64, u32)> {
+        let (_, font_data_hash) = self.glyph_store.as_ref()?;
+        Some((*font_data_hash, self.scale_in_pixels))
+    }
+
+    /// Rasterize glyphs as signed distance fields into the given atlas.
+    ///
+    /// See [`FontTweak::sdf`].
+    #[inline]
+    pub(crate) fn with_sdf_atlas(mut self, sdf_atlas: Arc<Mutex<SdfAtlas>>) -> Self {
+        self.sdf_atlas = Some(sdf_atlas);
+        self
+    }
+
+    /// Set the underline and strikeout metrics read by [`unscaled_line_metrics`].
+    pub(crate) fn with_line_metrics(
+        mut self,
+        underline: Option<ttf_parser::LineMetrics>,
+        strikeout: Option<ttf_parser::LineMetrics>,
+    ) -> Self {
+        use ab_glyph::Font as _;
+        let points_per_unit = self.scale_in_pixels as f32
+            / self.ab_glyph_font.height_unscaled()
+            / self.pixels_per_point;
+        let y_offset_in_points = self.y_offset_in_points;
+        let scale = |metrics: ttf_parser::LineMetrics| {
+            let thickness = metrics.thickness as f32 * points_per_unit;
+            // The font gives the top of the line, measured upwards from the baseline:
+            let top = -(metrics.position as f32) * points_per_unit;
+            LineMetrics {
+                y_offset: top + 0.5 * thickness + y_offset_in_points,
+                thickness,
+            }
+        };
+        self.underline_metrics = underline.filter(|m| 0 < m.thickness).map(scale);
+        self.strikeout_metrics = strikeout.filter(|m| 0 < m.thickness).map(scale);
+        self
+    }
+
+    /// Provide the raw font file, so that text can be shaped with this font,
+    /// and `OpenType` features can be applied.
+    pub(crate) fn with_font_data(mut self, font_data: Arc<crate::text::FontData>) -> Self {
+        self.font_data = Some(font_data);
+        self
+    }
+
+    /// Share the parsing of [`Self::with_font_data`] for shaping with the other sizes of this font.
+    #[cfg(feature = "shaping")]
+    pub(crate) fn with_shaping_face(mut self, shaping_face: SharedShapingFace) -> Self {
+        self.shaping_face = shaping_face;
+        self
+    }
+
+    /// Can we shape text with this font?
+    #[cfg(feature = "shaping")]
+    fn can_shape(&self) -> bool {
+        // Shaped glyphs are always placed at whole pixels, so they can't be combined with subpixel positioning.
+        // Their advances also come straight from the font, without room for faux bold.
+        self.font_data.is_some() && !self.subpixel_positioning && self.faux_style.is_none()
+    }
+
+    /// Shape a run of text with this font, using [`rustybuzz`].
+    ///
+    /// Returns `None` if the font can't be used for shaping.
+    #[cfg(feature = "shaping")]
+    fn shape(
+        &self,
+        text: &str,
+        kerning: bool,
+        font_features: &[(FourCC, u32)],
+    ) -> Option<Vec<ShapedGlyph>> {
+        use ab_glyph::{Font as _, ScaleFont as _};
+
+        let font_data = self.font_data.as_ref()?;
+        let shaping_face = self
+            .shaping_face
+            .get_or_init(|| ShapingFace::parse(font_data))
+            .as_ref()?;
+
+        let mut buffer = rustybuzz::UnicodeBuffer::new();
+        buffer.push_str(text);
+        // We don't do bidi yet, so we keep the glyphs in logical order:
+        buffer.set_direction(rustybuzz::Direction::LeftToRight);
+
+        let mut features: Vec<rustybuzz::Feature> = font_features
+            .iter()
+            .map(|(tag, value)| {
+                rustybuzz::Feature::new(rustybuzz::Tag::from_bytes(tag), *value, ..)
+            })
+            .collect();
+        if !kerning {
+            features.push(rustybuzz::Feature::new(
+                rustybuzz::Tag::from_bytes(b"kern"),
+                0,
+                ..,
+            ));
+        }
+
+        let output = shaping_face.with_face(|face| rustybuzz::shape(face, &features, buffer));
+
+        // Font units to points:
+        let scale = self
+            .ab_glyph_font
+            .as_scaled(self.scale_in_pixels as f32)
+            .h_scale_factor()
+            / self.pixels_per_point;
+
+        let glyphs = output
+            .glyph_infos()
+            .iter()
+            .zip(output.glyph_positions())
+            .map(|(info, pos)| ShapedGlyph {
+                cluster: info.cluster as usize,
+                glyph_info: self.glyph_info_for_id(ab_glyph::GlyphId(info.glyph_id as u16)),
+                advance: pos.x_advance as f32 * scale,
+                offset: vec2(pos.x_offset as f32, -pos.y_offset as f32) * scale,
+            })
+            .collect();
+        Some(glyphs)
+    }
+
+    /// Look up (or rasterize) a glyph that was produced by shaping or [`Self::substituted_glyph_info`].
+    fn glyph_info_for_id(&self, glyph_id: ab_glyph::GlyphId) -> GlyphInfo {
+        if glyph_id.0 == 0 {
+            return GlyphInfo::default(); // Don't draw the "missing glyph" box
+        }
+        if let Some(glyph_info) = self.glyph_id_cache.read().get(&glyph_id) {
+            return *glyph_info;
+        }
+        // Another thread may have allocated it meanwhile, so check again while holding the lock:
+        *self
+            .glyph_id_cache
+            .write()
+            .entry(glyph_id)
+            .or_insert_with(|| self.allocate_glyph(glyph_id))
+    }
+
+    /// The glyph for a character with some `OpenType` features applied, without shaping.
+    ///
+    /// Only the single substitutions of the features are applied,
+    /// which is enough for e.g. `tnum` (tabular numbers) and `smcp` (small caps) in most fonts.
+    ///
+    /// Returns `None` if the features don't change the glyph.
+    pub(crate) fn substituted_glyph_info(
+        &self,
+        c: char,
+        font_features: &[(FourCC, u32)],
+    ) -> Option<GlyphInfo> {
+        use ab_glyph::Font as _;
+
+        if font_features.is_empty() || self.font_data.is_none() {
+            return None;
         }
 
-        if self.name == "emoji-icon-font" {
-            // HACK: https://github.com/emilk/egui/issues/1284 https://github.com/jslegers/emoji-icon-font/issues/18
-            // Don't show the wrong fullwidth capital letters:
-            if 'Ｓ' <= chr && chr <= 'Ｙ' {
-                return true;
+        let original_id = self.ab_glyph_font.glyph_id(c);
+        let mut glyph_id = original_id;
+        for &(tag, value) in font_features {
+            if value != 0 {
+                glyph_id = self.single_substitution(glyph_id, tag).unwrap_or(glyph_id);
             }
         }
+        (glyph_id != original_id).then(|| self.glyph_info_for_id(glyph_id))
+    }
+
+    /// What does the `OpenType` feature `tag` substitute this glyph with, if anything?
+    fn single_substitution(
+        &self,
+        glyph_id: ab_glyph::GlyphId,
+        tag: FourCC,
+    ) -> Option<ab_glyph::GlyphId> {
+        if let Some(substitute) = self.single_substitution_cache.read().get(&(glyph_id, tag)) {
+            return *substitute;
+        }
+        let substitute = self.font_data.as_ref().and_then(|font_data| {
+            let face = ttf_parser::Face::parse(&font_data.font, font_data.index).ok()?;
+            gsub_single_substitution(&face, tag, ttf_parser::GlyphId(glyph_id.0))
+                .map(|substitute| ab_glyph::GlyphId(substitute.0))
+        });
+        self.single_substitution_cache
+            .write()
+            .insert((glyph_id, tag), substitute);
+        substitute
+    }
 
-        matches!(
-            chr,
-            // Strip out a religious symbol with secondary nefarious interpretation:
-            '\u{534d}' | '\u{5350}' |
+    /// Code points that will always be replaced by the replacement character.
+    ///
+    /// See [`crate::text::FontData::ignore_characters`] and [`invisible_char`].
+    fn ignore_character(&self, chr: char) -> bool {
+        self.ignore_characters
+            .iter()
+            .any(|range| range.contains(&chr))
+    }
 
-            // Ignore ubuntu-specific stuff in `Ubuntu-Light.ttf`:
-            '\u{E0FF}' | '\u{EFFD}' | '\u{F0FF}' | '\u{F200}'
-        )
+    /// Should this font be tried before the other fonts in its family for this character?
+    ///
+    /// See [`crate::text::FontData::preferred_characters`].
+    fn prefers_character(&self, chr: char) -> bool {
+        self.preferred_characters
+            .iter()
+            .any(|range| range.contains(&chr))
     }
 
     /// An un-ordered iterator over all supported characters.
@@ -172,10 +679,30 @@ impl FontImpl {
             .filter(|&chr| !self.ignore_character(chr))
     }
 
+    /// Does this font have a glyph for the given character?
+    ///
+    /// Unlike [`Self::glyph_info`], this will not rasterize the glyph into the atlas.
+    fn has_glyph(&self, c: char) -> bool {
+        if self.glyph_info_cache.read().contains_key(&(c, 0)) {
+            return true;
+        }
+        if self.ignore_character(c) {
+            return false;
+        }
+        if c == '\t' || c == '\u{2009}' {
+            return self.has_glyph(' ');
+        }
+        if invisible_char(c) {
+            return true;
+        }
+        use ab_glyph::Font as _;
+        self.ab_glyph_font.glyph_id(c).0 != 0
+    }
+
     /// `\n` will result in `None`
     fn glyph_info(&self, c: char) -> Option<GlyphInfo> {
         {
-            if let Some(glyph_info) = self.glyph_info_cache.read().get(&c) {
+            if let Some(glyph_info) = self.glyph_info_cache.read().get(&(c, 0)) {
                 return Some(*glyph_info);
             }
         }
@@ -190,7 +717,7 @@ impl FontImpl {
                     advance_width: crate::text::TAB_SIZE as f32 * space.advance_width,
                     ..space
                 };
-                self.glyph_info_cache.write().insert(c, glyph_info);
+                self.glyph_info_cache.write().insert((c, 0), glyph_info);
                 return Some(glyph_info);
             }
         }
@@ -207,14 +734,14 @@ impl FontImpl {
                     advance_width,
                     ..space
                 };
-                self.glyph_info_cache.write().insert(c, glyph_info);
+                self.glyph_info_cache.write().insert((c, 0), glyph_info);
                 return Some(glyph_info);
             }
         }
 
         if invisible_char(c) {
             let glyph_info = GlyphInfo::default();
-            self.glyph_info_cache.write().insert(c, glyph_info);
+            self.glyph_info_cache.write().insert((c, 0), glyph_info);
             return Some(glyph_info);
         }
 
@@ -225,10 +752,59 @@ impl FontImpl {
         if glyph_id.0 == 0 {
             None // unsupported character
         } else {
-            let glyph_info = self.allocate_glyph(glyph_id);
-            self.glyph_info_cache.write().insert(c, glyph_info);
-            Some(glyph_info)
+            // Another thread may have allocated it meanwhile, so check again while holding the lock:
+            Some(
+                *self
+                    .glyph_info_cache
+                    .write()
+                    .entry((c, 0))
+                    .or_insert_with(|| self.allocate_glyph(glyph_id)),
+            )
+        }
+    }
+
+    /// Like [`Self::glyph_info`], but with the glyph rasterized
+    /// `bin / SUBPIXEL_BINS` physical pixels further to the right.
+    ///
+    /// If [`FontTweak::subpixel_positioning`] is off, this is the same as [`Self::glyph_info`].
+    pub(crate) fn subpixel_glyph_info(&self, c: char, bin: u8) -> Option<GlyphInfo> {
+        let glyph_info = self.glyph_info(c)?;
+        if bin == 0
+            || !self.subpixel_positioning
+            || glyph_info.uv_rect.is_nothing()
+            || glyph_info.uv_rect.is_colored
+            || glyph_info.uv_rect.is_sdf
+        {
+            return Some(glyph_info);
+        }
+
+        if let Some(glyph_info) = self.glyph_info_cache.read().get(&(c, bin)) {
+            return Some(*glyph_info);
         }
+
+        // Another thread may have rasterized it meanwhile, so check again while holding the lock:
+        let mut glyph_info_cache = self.glyph_info_cache.write();
+        let glyph_info = glyph_info_cache.entry((c, bin)).or_insert_with(|| {
+            let subpixel_x = bin as f32 / SUBPIXEL_BINS as f32;
+            let uv_rect = self
+                .stored_or_rasterized(glyph_info.id, subpixel_x, || {
+                    self.rasterize_glyph(glyph_info.id, subpixel_x)
+                })
+                .unwrap_or_default();
+            GlyphInfo {
+                uv_rect,
+                ..glyph_info
+            }
+        });
+        Some(*glyph_info)
+    }
+
+    /// Are glyphs rasterized at different horizontal subpixel offsets?
+    ///
+    /// See [`FontTweak::subpixel_positioning`].
+    #[inline]
+    pub fn subpixel_positioning(&self) -> bool {
+        self.subpixel_positioning
     }
 
     #[inline]
@@ -244,6 +820,119 @@ impl FontImpl {
             / self.pixels_per_point
     }
 
+    /// The outline of the glyph for the given character, e.g. for stroking it yourself.
+    ///
+    /// Returns `None` if this font doesn't have the character, or if the glyph has no outline
+    /// (e.g. a space, or a bitmap emoji).
+    ///
+    /// [`FauxStyle::italic`] is applied, but [`FauxStyle::bold`] is not.
+    pub fn glyph_outline(&self, c: char) -> Option<GlyphOutline> {
+        use ab_glyph::{Font as _, OutlineCurve, ScaleFont as _};
+
+        if self.ignore_character(c) {
+            return None;
+        }
+        let glyph_id = self.ab_glyph_font.glyph_id(c);
+        if glyph_id.0 == 0 {
+            return None;
+        }
+
+        let mut outline = self.ab_glyph_font.outline(glyph_id)?;
+        if self.faux_style.italic != 0.0 {
+            shear_outline(&mut outline, self.faux_style.italic);
+        }
+
+        // Font units have y going up from the baseline:
+        let scale_factor = self
+            .ab_glyph_font
+            .as_scaled(self.scale_in_pixels as f32)
+            .scale_factor();
+        let to_points = |p: ab_glyph::Point| {
+            pos2(
+                p.x * scale_factor.horizontal / self.pixels_per_point,
+                -p.y * scale_factor.vertical / self.pixels_per_point + self.y_offset_in_points,
+            )
+        };
+
+        let curves = outline
+            .curves
+            .iter()
+            .map(|curve| match *curve {
+                OutlineCurve::Line(a, b) => GlyphOutlineCurve::Line([to_points(a), to_points(b)]),
+                OutlineCurve::Quad(a, b, c) => {
+                    GlyphOutlineCurve::Quad([to_points(a), to_points(b), to_points(c)])
+                }
+                OutlineCurve::Cubic(a, b, c, d) => GlyphOutlineCurve::Cubic([
+                    to_points(a),
+                    to_points(b),
+                    to_points(c),
+                    to_points(d),
+                ]),
+            })
+            .collect();
+
+        Some(GlyphOutline { curves })
+    }
+
+    /// How far down the next glyph goes after this one in [`crate::text::LayoutJob::vertical`] text, in points.
+    ///
+    /// `None` if the font has no vertical metrics.
+    pub fn vertical_advance(&self, glyph_id: ab_glyph::GlyphId) -> Option<f32> {
+        use ab_glyph::{Font as _, ScaleFont};
+        if self.ab_glyph_font.v_advance_unscaled(glyph_id) <= 0.0 {
+            return None;
+        }
+        let v_advance_in_pixels = self
+            .ab_glyph_font
+            .as_scaled(self.scale_in_pixels as f32)
+            .v_advance(glyph_id);
+        Some(v_advance_in_pixels / self.pixels_per_point)
+    }
+
+    /// The name of the font, i.e. the key in [`crate::text::FontDefinitions::font_data`].
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The glyphs of this font that are in the font atlas.
+    ///
+    /// SDF glyphs are not included, since they are in the SDF atlas.
+    pub(crate) fn atlas_glyphs(&self) -> Vec<AtlasGlyph> {
+        let atlas_glyph = |chr: Option<char>, glyph_info: &GlyphInfo| {
+            let uv_rect = glyph_info.uv_rect;
+            (!uv_rect.is_nothing() && !uv_rect.is_sdf).then(|| AtlasGlyph {
+                font_name: self.name.clone(),
+                scale_in_pixels: self.scale_in_pixels,
+                chr,
+                min: uv_rect.min,
+                max: uv_rect.max,
+            })
+        };
+
+        let mut glyphs: Vec<AtlasGlyph> = self
+            .glyph_info_cache
+            .read()
+            .iter()
+            .filter_map(|(&(chr, _), glyph_info)| atlas_glyph(Some(chr), glyph_info))
+            .collect();
+
+        let glyph_id_cache = self.glyph_id_cache.read();
+        if !glyph_id_cache.is_empty() {
+            use ab_glyph::Font as _;
+
+            // These glyphs don't know their character, so look it up in the font:
+            let mut chars: ahash::HashMap<ab_glyph::GlyphId, char> = Default::default();
+            for (glyph_id, chr) in self.ab_glyph_font.codepoint_ids() {
+                chars.entry(glyph_id).or_insert(chr);
+            }
+            glyphs.extend(glyph_id_cache.iter().filter_map(|(glyph_id, glyph_info)| {
+                atlas_glyph(chars.get(glyph_id).copied(), glyph_info)
+            }));
+        }
+        glyphs
+    }
+
     /// Height of one row of text in points.
     #[inline(always)]
     pub fn row_height(&self) -> f32 {
@@ -263,56 +952,64 @@ impl FontImpl {
         self.ascent
     }
 
+    /// This is the distance from the baseline to the bottom of the lowest glyph.
+    ///
+    /// Usually negative, since it is measured upwards from the baseline.
+    ///
+    /// Unit: points.
+    #[inline(always)]
+    pub fn descent(&self) -> f32 {
+        self.descent
+    }
+
+    /// The recommended extra spacing between two rows of text,
+    /// on top of [`Self::ascent`] and [`Self::descent`].
+    ///
+    /// Unit: points.
+    #[inline(always)]
+    pub fn line_gap(&self) -> f32 {
+        self.line_gap
+    }
+
+    /// Where to draw an underline, from the `post` table of the font.
+    ///
+    /// `None` if the font doesn't say.
+    #[inline(always)]
+    pub fn underline_metrics(&self) -> Option<LineMetrics> {
+        self.underline_metrics
+    }
+
+    /// Where to draw a strikethrough line, from the `OS/2` table of the font.
+    ///
+    /// `None` if the font doesn't say.
+    #[inline(always)]
+    pub fn strikeout_metrics(&self) -> Option<LineMetrics> {
+        self.strikeout_metrics
+    }
+
     fn allocate_glyph(&self, glyph_id: ab_glyph::GlyphId) -> GlyphInfo {
         assert!(glyph_id.0 != 0);
         use ab_glyph::{Font as _, ScaleFont};
 
-        let glyph = glyph_id.with_scale_and_position(
-            self.scale_in_pixels as f32,
-            ab_glyph::Point { x: 0.0, y: 0.0 },
-        );
-
-        let uv_rect = self.ab_glyph_font.outline_glyph(glyph).map(|glyph| {
-            let bb = glyph.px_bounds();
-            let glyph_width = bb.width() as usize;
-            let glyph_height = bb.height() as usize;
-            if glyph_width == 0 || glyph_height == 0 {
-                UvRect::default()
-            } else {
-                let glyph_pos = {
-                    let atlas = &mut self.atlas.lock();
-                    let (glyph_pos, image) = atlas.allocate((glyph_width, glyph_height));
-                    glyph.draw(|x, y, v| {
-                        if 0.0 < v {
-                            let px = glyph_pos.0 + x as usize;
-                            let py = glyph_pos.1 + y as usize;
-                            image[(px, py)] = v;
-                        }
-                    });
-                    glyph_pos
-                };
+        let uv_rect = self.stored_or_rasterized(glyph_id, 0.0, || {
+            #[cfg(feature = "color_emoji")]
+            let color_uv_rect = self.allocate_color_glyph(glyph_id);
+            #[cfg(not(feature = "color_emoji"))]
+            let color_uv_rect = None;
 
-                let offset_in_pixels = vec2(bb.min.x, bb.min.y);
-                let offset =
-                    offset_in_pixels / self.pixels_per_point + self.y_offset_in_points * Vec2::Y;
-                UvRect {
-                    offset,
-                    size: vec2(glyph_width as f32, glyph_height as f32) / self.pixels_per_point,
-                    min: [glyph_pos.0 as u16, glyph_pos.1 as u16],
-                    max: [
-                        (glyph_pos.0 + glyph_width) as u16,
-                        (glyph_pos.1 + glyph_height) as u16,
-                    ],
-                }
-            }
+            color_uv_rect.or_else(|| match &self.sdf_atlas {
+                Some(sdf_atlas) => self.allocate_sdf_glyph(sdf_atlas, glyph_id),
+                None => self.rasterize_glyph(glyph_id, 0.0),
+            })
         });
         let uv_rect = uv_rect.unwrap_or_default();
 
-        let advance_width_in_points = self
+        let advance_width_in_pixels = self
             .ab_glyph_font
             .as_scaled(self.scale_in_pixels as f32)
             .h_advance(glyph_id)
-            / self.pixels_per_point;
+            + self.faux_bold_in_pixels();
+        let advance_width_in_points = advance_width_in_pixels / self.pixels_per_point;
 
         GlyphInfo {
             id: glyph_id,
@@ -320,34 +1017,446 @@ impl FontImpl {
             uv_rect,
         }
     }
-}
-
-type FontIndex = usize;
 
-// TODO(emilk): rename?
-/// Wrapper over multiple [`FontImpl`] (e.g. a primary + fallbacks for emojis)
-pub struct Font {
-    fonts: Vec<Arc<FontImpl>>,
+    /// Look for the glyph, rasterized `subpixel_x` pixels to the right, in the [`GlyphStore`].
+    ///
+    /// If it isn't there, `rasterize` it into the atlas and store it.
+    fn stored_or_rasterized(
+        &self,
+        glyph_id: ab_glyph::GlyphId,
+        subpixel_x: f32,
+        rasterize: impl FnOnce() -> Option<UvRect>,
+    ) -> Option<UvRect> {
+        let Some((glyph_store, font_data_hash)) = &self.glyph_store else {
+            return rasterize();
+        };
 
-    /// Lazily calculated.
-    characters: Option<BTreeSet<char>>,
+        let key = StoredGlyphKey {
+            font_data_hash: *font_data_hash,
+            scale_in_pixels: self.scale_in_pixels,
+            faux_style: [
+                self.faux_style.bold.to_bits(),
+                self.faux_style.italic.to_bits(),
+            ],
+            glyph_id: glyph_id.0,
+            subpixel_x: subpixel_x.to_bits(),
+            sdf: self.sdf_atlas.is_some(),
+        };
 
-    replacement_glyph: (FontIndex, GlyphInfo),
-    pixels_per_point: f32,
-    row_height: f32,
-    glyph_info_cache: ahash::HashMap<char, (FontIndex, GlyphInfo)>,
-}
+        // The store is in physical pixels, without the `y_offset`, since those depend on `pixels_per_point`:
+        let y_offset = self.y_offset_in_points * Vec2::Y;
+        if let Some(uv_rect) = glyph_store.lock().get(&key) {
+            return Some(UvRect {
+                offset: uv_rect.offset / self.pixels_per_point + y_offset,
+                size: uv_rect.size / self.pixels_per_point,
+                ..uv_rect
+            });
+        }
+
+        let uv_rect = rasterize()?;
+        glyph_store.lock().insert(
+            key,
+            UvRect {
+                offset: (uv_rect.offset - y_offset) * self.pixels_per_point,
+                size: uv_rect.size * self.pixels_per_point,
+                ..uv_rect
+            },
+        );
+        Some(uv_rect)
+    }
+
+    /// Draw a hollow box into the atlas, for when the font has none of the
+    /// [`crate::text::FontDefinitions::replacement_characters`].
+    fn allocate_replacement_box(&self) -> GlyphInfo {
+        let scale = self.scale_in_pixels as f32;
+        let width = (0.5 * scale).round().max(3.0) as usize;
+        let height = (0.7 * scale).round().max(3.0) as usize;
+        let thickness = (scale / 16.0).round().max(1.0) as usize;
+        let margin = (0.1 * scale).round();
+
+        let glyph_pos = {
+            let atlas = &mut self.atlas.lock();
+            let (glyph_pos, image) = atlas.allocate((width, height));
+            for y in 0..height {
+                for x in 0..width {
+                    let on_border = x < thickness
+                        || y < thickness
+                        || width - thickness <= x
+                        || height - thickness <= y;
+                    if on_border {
+                        image[(glyph_pos.0 + x, glyph_pos.1 + y)] = 1.0;
+                    }
+                }
+            }
+            glyph_pos
+        };
+
+        // The box sits on the baseline:
+        let offset_in_pixels = vec2(margin, -(height as f32));
+        GlyphInfo {
+            id: ab_glyph::GlyphId(0),
+            advance_width: (width as f32 + 2.0 * margin) / self.pixels_per_point,
+            uv_rect: UvRect {
+                offset: offset_in_pixels / self.pixels_per_point
+                    + self.y_offset_in_points * Vec2::Y,
+                size: vec2(width as f32, height as f32) / self.pixels_per_point,
+                min: [glyph_pos.0 as u16, glyph_pos.1 as u16],
+                max: [(glyph_pos.0 + width) as u16, (glyph_pos.1 + height) as u16],
+                is_colored: false,
+                is_sdf: false,
+            },
+        }
+    }
+
+    /// How much wider [`FauxStyle::bold`] makes each glyph.
+    fn faux_bold_in_pixels(&self) -> f32 {
+        self.faux_style.bold.max(0.0) * self.scale_in_pixels as f32
+    }
+
+    /// Outline the glyph `subpixel_x` pixels to the right of the origin,
+    /// apply the [`FauxStyle`], and rasterize it into the atlas.
+    ///
+    /// Returns `None` if the glyph has no outline.
+    fn rasterize_glyph(&self, glyph_id: ab_glyph::GlyphId, subpixel_x: f32) -> Option<UvRect> {
+        let glyphs = self.outlined_glyphs(glyph_id, self.scale_in_pixels as f32, subpixel_x)?;
+        Some(self.allocate_outlined_glyphs(&glyphs, subpixel_x))
+    }
+
+    /// Outline the glyph at the given scale, `subpixel_x` pixels to the right of the origin,
+    /// with the [`FauxStyle`] applied.
+    ///
+    /// Faux bold results in several glyphs, which should be drawn on top of each other.
+    /// Returns `None` if the glyph has no outline.
+    fn outlined_glyphs(
+        &self,
+        glyph_id: ab_glyph::GlyphId,
+        scale: f32,
+        subpixel_x: f32,
+    ) -> Option<Vec<ab_glyph::OutlinedGlyph>> {
+        use ab_glyph::{Font as _, ScaleFont as _};
+
+        let mut outline = self.ab_glyph_font.outline(glyph_id)?;
+        if self.faux_style.italic != 0.0 {
+            shear_outline(&mut outline, self.faux_style.italic);
+        }
+
+        let scale_factor = self.ab_glyph_font.as_scaled(scale).scale_factor();
+        let outlined_at = |x: f32| {
+            let glyph = glyph_id.with_scale_and_position(scale, ab_glyph::point(x, 0.0));
+            ab_glyph::OutlinedGlyph::new(glyph, outline.clone(), scale_factor)
+        };
+
+        // Faux bold: smear the glyph to the right by drawing it at several offsets.
+        let bold = self.faux_style.bold.max(0.0) * scale;
+        Some(if 0.0 < bold {
+            let num_steps = (2.0 * bold).ceil() as usize; // at most half a pixel apart
+            (0..=num_steps)
+                .map(|i| outlined_at(subpixel_x + bold * i as f32 / num_steps as f32))
+                .collect()
+        } else {
+            vec![outlined_at(subpixel_x)]
+        })
+    }
+
+    /// Rasterize the union of some outlined glyphs into one spot in the atlas.
+    ///
+    /// `subpixel_x` is the horizontal position (in pixels) the glyphs were outlined at.
+    fn allocate_outlined_glyphs(
+        &self,
+        glyphs: &[ab_glyph::OutlinedGlyph],
+        subpixel_x: f32,
+    ) -> UvRect {
+        let Some((bb, coverage)) = draw_outlined_glyphs(glyphs, 0) else {
+            return UvRect::default();
+        };
+        let [glyph_width, glyph_height] = coverage.size;
+
+        let glyph_pos = {
+            let atlas = &mut self.atlas.lock();
+            let (glyph_pos, image) = atlas.allocate((glyph_width, glyph_height));
+            for y in 0..glyph_height {
+                for x in 0..glyph_width {
+                    image[(glyph_pos.0 + x, glyph_pos.1 + y)] = coverage[(x, y)];
+                }
+            }
+            glyph_pos
+        };
+
+        let offset_in_pixels = vec2(bb.min.x - subpixel_x, bb.min.y);
+        let offset = offset_in_pixels / self.pixels_per_point + self.y_offset_in_points * Vec2::Y;
+        UvRect {
+            offset,
+            size: vec2(glyph_width as f32, glyph_height as f32) / self.pixels_per_point,
+            min: [glyph_pos.0 as u16, glyph_pos.1 as u16],
+            max: [
+                (glyph_pos.0 + glyph_width) as u16,
+                (glyph_pos.1 + glyph_height) as u16,
+            ],
+            is_colored: false,
+            is_sdf: false,
+        }
+    }
+
+    /// Find or rasterize the signed distance field of a glyph in the SDF atlas,
+    /// and scale it to the size of this font.
+    ///
+    /// See [`FontTweak::sdf`].
+    fn allocate_sdf_glyph(
+        &self,
+        sdf_atlas: &Mutex<SdfAtlas>,
+        glyph_id: ab_glyph::GlyphId,
+    ) -> Option<UvRect> {
+        let key = (
+            self.name.clone(),
+            glyph_id.0,
+            [
+                self.faux_style.bold.to_bits(),
+                self.faux_style.italic.to_bits(),
+            ],
+        );
+
+        let existing = sdf_atlas.lock().glyph(&key);
+        let sdf_glyph = if let Some(sdf_glyph) = existing {
+            sdf_glyph
+        } else {
+            let glyphs = self.outlined_glyphs(glyph_id, SDF_REFERENCE_SCALE_IN_PIXELS, 0.0)?;
+            let (bb, coverage) = draw_outlined_glyphs(&glyphs, SDF_SPREAD_IN_PIXELS)?;
+            let field = super::sdf::distance_field(&coverage);
+            let offset = vec2(bb.min.x, bb.min.y) - Vec2::splat(SDF_SPREAD_IN_PIXELS as f32);
+            sdf_atlas.lock().allocate(key, offset, &field)
+        };
+
+        let points_per_reference_pixel =
+            self.scale_in_pixels as f32 / SDF_REFERENCE_SCALE_IN_PIXELS / self.pixels_per_point;
+        Some(UvRect {
+            offset: sdf_glyph.offset * points_per_reference_pixel
+                + self.y_offset_in_points * Vec2::Y,
+            size: sdf_glyph.size * points_per_reference_pixel,
+            min: sdf_glyph.min,
+            max: sdf_glyph.max,
+            is_colored: false,
+            is_sdf: true,
+        })
+    }
+
+    /// Rasterize a glyph from an embedded color bitmap,
+    /// as found in the `CBDT` and `sbix` tables of color emoji fonts.
+    ///
+    /// Returns `None` if the font has no such bitmap for this glyph.
+    /// This includes the layered vector glyphs of `COLR` fonts,
+    /// which are then rasterized as plain outlines.
+    #[cfg(feature = "color_emoji")]
+    fn allocate_color_glyph(&self, glyph_id: ab_glyph::GlyphId) -> Option<UvRect> {
+        use ab_glyph::{Font as _, ScaleFont as _};
+
+        let scaled = self.ab_glyph_font.as_scaled(self.scale_in_pixels as f32);
+        let pixels_per_em = scaled.h_scale_factor() * self.ab_glyph_font.units_per_em()?;
+
+        #[allow(deprecated)]
+        let raster = self
+            .ab_glyph_font
+            .glyph_raster_image(glyph_id, pixels_per_em.round() as u16)?;
+        if !matches!(raster.format, ab_glyph::GlyphImageFormat::Png) || raster.scale <= 0.0 {
+            return None;
+        }
+
+        let (src_size, src_rgba) = decode_png_rgba(raster.data)?;
+
+        // The strike in the font is likely of a different size than what we want:
+        let scale = pixels_per_em / raster.scale;
+        let glyph_width = (src_size[0] as f32 * scale).round() as usize;
+        let glyph_height = (src_size[1] as f32 * scale).round() as usize;
+        if glyph_width == 0 || glyph_height == 0 {
+            return Some(UvRect::default());
+        }
+
+        let glyph_pos = {
+            let atlas = &mut self.atlas.lock();
+            let (glyph_pos, image) = atlas.allocate((glyph_width, glyph_height));
+            blit_rgba_resampled(
+                image,
+                glyph_pos,
+                [glyph_width, glyph_height],
+                src_size,
+                &src_rgba,
+            );
+            glyph_pos
+        };
+
+        // The raster origin is the bottom left corner of the image, relative to the baseline, y-up:
+        let offset_in_pixels = vec2(
+            raster.origin.x * scale,
+            -(raster.origin.y + src_size[1] as f32) * scale,
+        );
+        let offset = offset_in_pixels / self.pixels_per_point + self.y_offset_in_points * Vec2::Y;
+        Some(UvRect {
+            offset,
+            size: vec2(glyph_width as f32, glyph_height as f32) / self.pixels_per_point,
+            min: [glyph_pos.0 as u16, glyph_pos.1 as u16],
+            max: [
+                (glyph_pos.0 + glyph_width) as u16,
+                (glyph_pos.1 + glyph_height) as u16,
+            ],
+            is_colored: true,
+            is_sdf: false,
+        })
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct StoredGlyphKey {
+    font_data_hash: u64,
+    scale_in_pixels: u32,
+
+    /// [`FauxStyle`] as bits.
+    faux_style: [u32; 2],
+
+    glyph_id: u16,
+
+    /// Horizontal subpixel offset, as bits.
+    subpixel_x: u32,
+
+    /// SDF glyphs are in another atlas, see [`crate::text::FontTweak::sdf`].
+    sdf: bool,
+}
+
+/// The glyphs rasterized into a font atlas, in physical pixels.
+///
+/// This is shared by all [`FontImpl`]s using the atlas, and survives `pixels_per_point` changing
+/// (see [`crate::text::Fonts::begin_frame`]), so that switching back and forth between
+/// two `pixels_per_point` reuses the glyphs that are already in the atlas.
+#[derive(Default)]
+pub(crate) struct GlyphStore {
+    glyphs: ahash::HashMap<StoredGlyphKey, UvRect>,
+
+    /// The frame each font data hash and `scale_in_pixels` was last used.
+    scale_last_used: ahash::HashMap<(u64, u32), u32>,
+
+    /// Counts calls to [`Self::begin_frame`].
+    frame: u32,
+}
+
+impl GlyphStore {
+    /// Forget the glyphs of font sizes that haven't been used for this many frames.
+    ///
+    /// This doesn't free up any room in the atlas, but keeps the store from growing forever.
+    const MAX_UNUSED_FRAMES: u32 = 3600;
+
+    fn get(&mut self, key: &StoredGlyphKey) -> Option<UvRect> {
+        let uv_rect = self.glyphs.get(key).copied()?;
+        self.scale_last_used
+            .insert((key.font_data_hash, key.scale_in_pixels), self.frame);
+        Some(uv_rect)
+    }
+
+    fn insert(&mut self, key: StoredGlyphKey, uv_rect: UvRect) {
+        self.scale_last_used
+            .insert((key.font_data_hash, key.scale_in_pixels), self.frame);
+        self.glyphs.insert(key, uv_rect);
+    }
+
+    /// Call once per frame, with the [`FontImpl::glyph_store_scale`] of all fonts in use.
+    pub fn begin_frame(&mut self, used_scales: impl Iterator<Item = (u64, u32)>) {
+        self.frame = self.frame.wrapping_add(1);
+        for scale in used_scales {
+            self.scale_last_used.insert(scale, self.frame);
+        }
+
+        let frame = self.frame;
+        let len_before = self.scale_last_used.len();
+        self.scale_last_used
+            .retain(|_, last_used| frame.wrapping_sub(*last_used) <= Self::MAX_UNUSED_FRAMES);
+        if self.scale_last_used.len() < len_before {
+            let scale_last_used = &self.scale_last_used;
+            self.glyphs.retain(|key, _| {
+                scale_last_used.contains_key(&(key.font_data_hash, key.scale_in_pixels))
+            });
+        }
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+type FontIndex = usize;
+
+/// One glyph of a shaped run of text.
+#[cfg(feature = "shaping")]
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ShapedGlyph {
+    /// Byte offset of the first character in the cluster this glyph belongs to,
+    /// relative to the start of the shaped text.
+    pub cluster: usize,
+
+    pub glyph_info: GlyphInfo,
+
+    /// How far to move the cursor after this glyph, in points.
+    ///
+    /// This includes kerning, and may differ from [`GlyphInfo::advance_width`].
+    pub advance: f32,
+
+    /// Where to draw the glyph relative to the cursor, in points.
+    /// Positive y is down.
+    pub offset: Vec2,
+}
+
+/// Number of horizontal subpixel offsets each glyph is rasterized at
+/// when [`FontTweak::subpixel_positioning`] is enabled.
+pub(crate) const SUBPIXEL_BINS: u8 = 4;
+
+// TODO(emilk): rename?
+/// Wrapper over multiple [`FontImpl`] (e.g. a primary + fallbacks for emojis)
+pub struct Font {
+    fonts: Vec<Arc<FontImpl>>,
+
+    /// Lazily calculated.
+    characters: OnceLock<BTreeSet<char>>,
+
+    /// Lazily calculated.
+    characters_per_font: OnceLock<Vec<(String, BTreeSet<char>)>>,
+
+    replacement_glyph: (FontIndex, GlyphInfo),
+    pixels_per_point: f32,
+    row_height: f32,
+    glyph_info_cache: RwLock<ahash::HashMap<char, CachedGlyphInfo>>,
+
+    /// Frame counter, used to track when each glyph was last used.
+    ///
+    /// Atomic, so that a [`Font`] can be shared by threads laying out text.
+    generation: AtomicU32,
+
+    /// When each character was last laid out with shaping, bypassing [`Self::glyph_info_cache`].
+    #[cfg(feature = "shaping")]
+    shaped_chars_last_used: Mutex<ahash::HashMap<char, u32>>,
+}
+
+struct CachedGlyphInfo {
+    font_index: FontIndex,
+    glyph_info: GlyphInfo,
+
+    /// The [`Font::generation`] when this glyph was last used.
+    ///
+    /// Atomic, so that it can be updated while only holding a read lock.
+    last_used: AtomicU32,
+}
 
 impl Font {
-    pub fn new(fonts: Vec<Arc<FontImpl>>) -> Self {
+    /// `replacement_characters` are tried in order to find a glyph to show for unsupported characters.
+    /// See [`crate::text::FontDefinitions::replacement_characters`].
+    pub fn new(fonts: Vec<Arc<FontImpl>>, replacement_characters: &[char]) -> Self {
         if fonts.is_empty() {
             return Self {
                 fonts,
-                characters: None,
+                characters: OnceLock::new(),
+                characters_per_font: OnceLock::new(),
                 replacement_glyph: Default::default(),
                 pixels_per_point: 1.0,
                 row_height: 0.0,
                 glyph_info_cache: Default::default(),
+                generation: AtomicU32::new(0),
+                #[cfg(feature = "shaping")]
+                shaped_chars_last_used: Default::default(),
             };
         }
 
@@ -356,38 +1465,44 @@ impl Font {
 
         let mut slf = Self {
             fonts,
-            characters: None,
+            characters: OnceLock::new(),
+            characters_per_font: OnceLock::new(),
             replacement_glyph: Default::default(),
             pixels_per_point,
             row_height,
             glyph_info_cache: Default::default(),
+            generation: AtomicU32::new(0),
+            #[cfg(feature = "shaping")]
+            shaped_chars_last_used: Default::default(),
         };
 
-        const PRIMARY_REPLACEMENT_CHAR: char = '◻'; // white medium square
-        const FALLBACK_REPLACEMENT_CHAR: char = '?'; // fallback for the fallback
-
-        let replacement_glyph = slf
-            .glyph_info_no_cache_or_fallback(PRIMARY_REPLACEMENT_CHAR)
-            .or_else(|| slf.glyph_info_no_cache_or_fallback(FALLBACK_REPLACEMENT_CHAR))
+        let replacement_glyph = replacement_characters
+            .iter()
+            .find_map(|&c| slf.glyph_info_no_cache_or_fallback(c))
             .unwrap_or_else(|| {
                 #[cfg(feature = "log")]
-                log::warn!(
-                    "Failed to find replacement characters {PRIMARY_REPLACEMENT_CHAR:?} or {FALLBACK_REPLACEMENT_CHAR:?}. Will use empty glyph."
+                log::debug!(
+                    "None of the replacement characters {replacement_characters:?} found in the font. Will draw a box instead."
                 );
-                (0, GlyphInfo::default())
+                (0, slf.fonts[0].allocate_replacement_box())
             });
         slf.replacement_glyph = replacement_glyph;
 
         slf
     }
 
-    pub fn preload_characters(&mut self, s: &str) {
+    /// The fonts of this family, in fallback order.
+    pub(crate) fn font_impls(&self) -> &[Arc<FontImpl>] {
+        &self.fonts
+    }
+
+    pub fn preload_characters(&self, s: &str) {
         for c in s.chars() {
             self.glyph_info(c);
         }
     }
 
-    pub fn preload_common_characters(&mut self) {
+    pub fn preload_common_characters(&self) {
         // Preload the printable ASCII characters [32, 126] (which excludes control codes):
         const FIRST_ASCII: usize = 32; // 32 == space
         const LAST_ASCII: usize = 126;
@@ -399,16 +1514,29 @@ impl Font {
     }
 
     /// All supported characters.
-    pub fn characters(&mut self) -> &BTreeSet<char> {
-        self.characters.get_or_insert_with(|| {
+    pub fn characters(&self) -> &BTreeSet<char> {
+        self.characters.get_or_init(|| {
             let mut characters = BTreeSet::new();
-            for font in &self.fonts {
-                characters.extend(font.characters());
+            for (_, font_characters) in self.characters_per_font() {
+                characters.extend(font_characters);
             }
             characters
         })
     }
 
+    /// The characters supported by each font, in fallback order,
+    /// together with the name of the font (see [`FontImpl::name`]).
+    ///
+    /// Useful to find out which font a character comes from.
+    pub fn characters_per_font(&self) -> &[(String, BTreeSet<char>)] {
+        self.characters_per_font.get_or_init(|| {
+            self.fonts
+                .iter()
+                .map(|font| (font.name().to_owned(), font.characters().collect()))
+                .collect()
+        })
+    }
+
     #[inline(always)]
     pub fn round_to_pixel(&self, point: f32) -> f32 {
         (point * self.pixels_per_point).round() / self.pixels_per_point
@@ -420,42 +1548,254 @@ impl Font {
         self.row_height
     }
 
+    /// Distance from the top of a row to the baseline, taken from the primary font.
+    ///
+    /// Unit: points.
+    pub fn ascent(&self) -> f32 {
+        self.fonts.first().map_or(0.0, |font| font.ascent())
+    }
+
+    /// Distance from the baseline to the bottom of the lowest glyph, taken from the primary font.
+    ///
+    /// Usually negative. Unit: points.
+    pub fn descent(&self) -> f32 {
+        self.fonts.first().map_or(0.0, |font| font.descent())
+    }
+
+    /// Recommended extra spacing between rows, taken from the primary font.
+    ///
+    /// Unit: points.
+    pub fn line_gap(&self) -> f32 {
+        self.fonts.first().map_or(0.0, |font| font.line_gap())
+    }
+
+    /// Where to draw an underline, taken from the primary font.
+    pub fn underline_metrics(&self) -> Option<LineMetrics> {
+        self.fonts.first()?.underline_metrics()
+    }
+
+    /// Where to draw a strikethrough line, taken from the primary font.
+    pub fn strikeout_metrics(&self) -> Option<LineMetrics> {
+        self.fonts.first()?.strikeout_metrics()
+    }
+
     pub fn uv_rect(&self, c: char) -> UvRect {
         self.glyph_info_cache
+            .read()
             .get(&c)
-            .map(|gi| gi.1.uv_rect)
+            .map(|cached| cached.glyph_info.uv_rect)
             .unwrap_or_default()
     }
 
     /// Width of this character in points.
-    pub fn glyph_width(&mut self, c: char) -> f32 {
+    pub fn glyph_width(&self, c: char) -> f32 {
         self.glyph_info(c).1.advance_width
     }
 
     /// Can we display this glyph?
-    pub fn has_glyph(&mut self, c: char) -> bool {
-        self.glyph_info(c) != self.replacement_glyph // TODO(emilk): this is a false negative if the user asks about the replacement character itself 🤦‍♂️
+    ///
+    /// This will not add the glyph to the font atlas.
+    pub fn has_glyph(&self, c: char) -> bool {
+        self.font_index_for_char(c).is_some()
     }
 
     /// Can we display all the glyphs in this text?
-    pub fn has_glyphs(&mut self, s: &str) -> bool {
+    ///
+    /// This will not add any glyphs to the font atlas.
+    pub fn has_glyphs(&self, s: &str) -> bool {
         s.chars().all(|c| self.has_glyph(c))
     }
 
+    /// Which of the fonts in the [`crate::FontFamily`] will be used to display this character?
+    ///
+    /// The returned index is into the list of font names in [`crate::text::FontDefinitions::families`].
+    /// Returns `None` if none of the fonts support the character,
+    /// in which case the replacement glyph will be shown.
+    ///
+    /// This will not add the glyph to the font atlas.
+    pub fn font_index_for_char(&self, c: char) -> Option<usize> {
+        if let Some(cached) = self.glyph_info_cache.read().get(&c) {
+            if (cached.font_index, cached.glyph_info) != self.replacement_glyph {
+                return Some(cached.font_index);
+            }
+        }
+        self.font_indices_for_char(c)
+            .find(|&font_index| self.fonts[font_index].has_glyph(c))
+    }
+
+    /// The outline of the glyph that will be used to display this character,
+    /// picked from the fonts the same way as when laying out text.
+    ///
+    /// Returns `None` if none of the fonts support the character, or if the glyph has no outline.
+    /// See [`FontImpl::glyph_outline`].
+    pub fn glyph_outline(&self, c: char) -> Option<GlyphOutline> {
+        let font_index = self.font_index_for_char(c)?;
+        self.fonts[font_index].glyph_outline(c)
+    }
+
+    /// The name of the font that will be used to display this character,
+    /// i.e. the key in [`crate::text::FontDefinitions::font_data`].
+    ///
+    /// Returns `None` if none of the fonts support the character.
+    ///
+    /// This will not add the glyph to the font atlas.
+    pub fn font_name_for_char(&self, c: char) -> Option<&str> {
+        let font_index = self.font_index_for_char(c)?;
+        Some(self.fonts[font_index].name())
+    }
+
     /// `\n` will (intentionally) show up as the replacement character.
-    fn glyph_info(&mut self, c: char) -> (FontIndex, GlyphInfo) {
-        if let Some(font_index_glyph_info) = self.glyph_info_cache.get(&c) {
-            return *font_index_glyph_info;
+    fn glyph_info(&self, c: char) -> (FontIndex, GlyphInfo) {
+        if let Some(cached) = self.glyph_info_cache.read().get(&c) {
+            cached.last_used.store(self.generation(), Relaxed);
+            return (cached.font_index, cached.glyph_info);
         }
 
         let font_index_glyph_info = self.glyph_info_no_cache_or_fallback(c);
         let font_index_glyph_info = font_index_glyph_info.unwrap_or(self.replacement_glyph);
-        self.glyph_info_cache.insert(c, font_index_glyph_info);
+        self.insert_glyph_info(c, font_index_glyph_info);
         font_index_glyph_info
     }
 
+    fn insert_glyph_info(&self, c: char, (font_index, glyph_info): (FontIndex, GlyphInfo)) {
+        self.glyph_info_cache.write().insert(
+            c,
+            CachedGlyphInfo {
+                font_index,
+                glyph_info,
+                last_used: AtomicU32::new(self.generation()),
+            },
+        );
+    }
+
+    /// Set the current frame number, used to track when glyphs were last used.
+    pub(crate) fn set_generation(&self, generation: u32) {
+        self.generation.store(generation, Relaxed);
+    }
+
+    fn generation(&self) -> u32 {
+        self.generation.load(Relaxed)
+    }
+
+    /// Mark a glyph as used this frame, e.g. because it is part of a cached [`crate::Galley`].
+    pub(crate) fn mark_used(&self, c: char) {
+        if let Some(cached) = self.glyph_info_cache.read().get(&c) {
+            cached.last_used.store(self.generation(), Relaxed);
+        }
+        #[cfg(feature = "shaping")]
+        if let Some(last_used) = self.shaped_chars_last_used.lock().get_mut(&c) {
+            *last_used = self.generation();
+        }
+    }
+
+    /// The characters that have been used in the last `max_retained_frames` frames.
+    pub(crate) fn recently_used_characters(&self, max_retained_frames: u32) -> String {
+        let generation = self.generation();
+        self.glyph_info_cache
+            .read()
+            .iter()
+            .filter(|(_, cached)| {
+                generation.wrapping_sub(cached.last_used.load(Relaxed)) <= max_retained_frames
+            })
+            .map(|(c, _)| *c)
+            .collect()
+    }
+
+    /// Number of glyphs that haven't been used in the last `max_retained_frames` frames.
+    pub(crate) fn num_unused_glyphs(&self, max_retained_frames: u32) -> usize {
+        let is_unused =
+            |last_used: u32| max_retained_frames < self.generation().wrapping_sub(last_used);
+        #[allow(unused_mut)]
+        let mut num_unused = self
+            .glyph_info_cache
+            .read()
+            .values()
+            .filter(|cached| is_unused(cached.last_used.load(Relaxed)))
+            .count();
+        #[cfg(feature = "shaping")]
+        {
+            num_unused += self
+                .shaped_chars_last_used
+                .lock()
+                .values()
+                .filter(|last_used| is_unused(**last_used))
+                .count();
+        }
+        num_unused
+    }
+
+    /// Number of glyphs in the cache.
+    pub(crate) fn num_cached_glyphs(&self) -> usize {
+        #[allow(unused_mut)]
+        let mut num_glyphs = self.glyph_info_cache.read().len();
+        #[cfg(feature = "shaping")]
+        {
+            num_glyphs += self.shaped_chars_last_used.lock().len();
+        }
+        num_glyphs
+    }
+
+    /// Does any of the fonts use [`FontTweak::subpixel_positioning`]?
+    pub(crate) fn subpixel_positioning(&self) -> bool {
+        self.fonts.iter().any(|font| font.subpixel_positioning())
+    }
+
+    /// The [`UvRect`] to use for this character when placed at the given x position (in points).
+    ///
+    /// With [`FontTweak::subpixel_positioning`], this picks the rasterization
+    /// closest to the fractional pixel position.
+    /// Returns `None` if the font used for this character doesn't use subpixel positioning.
+    pub(crate) fn subpixel_uv_rect(&self, c: char, x: f32) -> Option<UvRect> {
+        let (font_index, _) = self.glyph_info(c);
+        let font_impl = self.fonts.get(font_index)?;
+        if !font_impl.subpixel_positioning() {
+            return None;
+        }
+
+        let x_in_pixels = x * self.pixels_per_point;
+        let bin = ((x_in_pixels - x_in_pixels.floor()) * SUBPIXEL_BINS as f32).round() as u8
+            % SUBPIXEL_BINS; // rounding up to a whole pixel is the same as bin 0
+        font_impl
+            .subpixel_glyph_info(c, bin)
+            .map(|glyph_info| glyph_info.uv_rect)
+    }
+
+    /// Which font should a run of text containing this character be shaped with?
+    ///
+    /// Returns `None` if the character should be laid out on its own, without shaping.
+    #[cfg(feature = "shaping")]
+    pub(crate) fn shaping_font_index(&self, c: char) -> Option<FontIndex> {
+        if matches!(c, '\t' | '\u{2009}' | '\r') {
+            return None; // These get special treatment in `FontImpl::glyph_info`
+        }
+        let font_index = self.font_index_for_char(c)?;
+        self.fonts[font_index].can_shape().then_some(font_index)
+    }
+
+    /// Shape a run of text with one of the fonts, as returned by [`Self::shaping_font_index`].
+    ///
+    /// Returns the ascent of the font and the glyphs in logical order,
+    /// or `None` if the font can't be used for shaping.
+    #[cfg(feature = "shaping")]
+    pub(crate) fn shape(
+        &self,
+        font_index: FontIndex,
+        text: &str,
+        kerning: bool,
+        font_features: &[(FourCC, u32)],
+    ) -> Option<(f32, Vec<ShapedGlyph>)> {
+        let font_impl = &self.fonts[font_index];
+        let glyphs = font_impl.shape(text, kerning, font_features)?;
+        let ascent = font_impl.ascent();
+        let mut shaped_chars_last_used = self.shaped_chars_last_used.lock();
+        for c in text.chars() {
+            shaped_chars_last_used.insert(c, self.generation());
+        }
+        Some((ascent, glyphs))
+    }
+
     #[inline]
-    pub(crate) fn font_impl_and_glyph_info(&mut self, c: char) -> (Option<&FontImpl>, GlyphInfo) {
+    pub(crate) fn font_impl_and_glyph_info(&self, c: char) -> (Option<&FontImpl>, GlyphInfo) {
         if self.fonts.is_empty() {
             return (None, self.replacement_glyph.1);
         }
@@ -464,14 +1804,193 @@ impl Font {
         (Some(font_impl), glyph_info)
     }
 
-    fn glyph_info_no_cache_or_fallback(&mut self, c: char) -> Option<(FontIndex, GlyphInfo)> {
-        for (font_index, font_impl) in self.fonts.iter().enumerate() {
-            if let Some(glyph_info) = font_impl.glyph_info(c) {
-                self.glyph_info_cache.insert(c, (font_index, glyph_info));
-                return Some((font_index, glyph_info));
+    fn font_indices_for_char(&self, c: char) -> impl Iterator<Item = FontIndex> + '_ {
+        font_indices_for_char(&self.fonts, c)
+    }
+
+    fn glyph_info_no_cache_or_fallback(&self, c: char) -> Option<(FontIndex, GlyphInfo)> {
+        self.font_indices_for_char(c).find_map(|font_index| {
+            let glyph_info = self.fonts[font_index].glyph_info(c)?;
+            Some((font_index, glyph_info))
+        })
+    }
+}
+
+/// Draw the union of some outlined glyphs into a coverage image,
+/// with `padding` empty pixels on each side.
+///
+/// Also returns the union of their pixel bounds (without the padding).
+/// Returns `None` if there is nothing to draw.
+fn draw_outlined_glyphs(
+    glyphs: &[ab_glyph::OutlinedGlyph],
+    padding: usize,
+) -> Option<(ab_glyph::Rect, crate::FontImage)> {
+    let bb = glyphs
+        .iter()
+        .map(|glyph| glyph.px_bounds())
+        .reduce(|a, b| ab_glyph::Rect {
+            min: ab_glyph::point(a.min.x.min(b.min.x), a.min.y.min(b.min.y)),
+            max: ab_glyph::point(a.max.x.max(b.max.x), a.max.y.max(b.max.y)),
+        })?;
+    let glyph_width = bb.width() as usize;
+    let glyph_height = bb.height() as usize;
+    if glyph_width == 0 || glyph_height == 0 {
+        return None;
+    }
+
+    let mut image = crate::FontImage::new([glyph_width + 2 * padding, glyph_height + 2 * padding]);
+    for glyph in glyphs {
+        let glyph_bb = glyph.px_bounds();
+        let dx = padding + (glyph_bb.min.x - bb.min.x) as usize;
+        let dy = padding + (glyph_bb.min.y - bb.min.y) as usize;
+        glyph.draw(|x, y, v| {
+            if 0.0 < v {
+                let pixel = &mut image[(dx + x as usize, dy + y as usize)];
+                *pixel = pixel.max(v);
+            }
+        });
+    }
+    Some((bb, image))
+}
+
+/// Slant an unscaled glyph outline to the right by `italic` units per unit of height.
+fn shear_outline(outline: &mut ab_glyph::Outline, italic: f32) {
+    use ab_glyph::OutlineCurve;
+
+    // The curves stay within their control points, so those give us the new horizontal bounds.
+    let mut min_x = f32::INFINITY;
+    let mut max_x = f32::NEG_INFINITY;
+
+    // In font units, y goes up from the baseline.
+    let mut shear = |p: &mut ab_glyph::Point| {
+        p.x += italic * p.y;
+        min_x = min_x.min(p.x);
+        max_x = max_x.max(p.x);
+    };
+    for curve in &mut outline.curves {
+        match curve {
+            OutlineCurve::Line(a, b) => {
+                shear(a);
+                shear(b);
+            }
+            OutlineCurve::Quad(a, b, c) => {
+                shear(a);
+                shear(b);
+                shear(c);
+            }
+            OutlineCurve::Cubic(a, b, c, d) => {
+                shear(a);
+                shear(b);
+                shear(c);
+                shear(d);
+            }
+        }
+    }
+
+    if min_x <= max_x {
+        outline.bounds.min.x = min_x;
+        outline.bounds.max.x = max_x;
+    }
+}
+
+/// The order in which to try the fonts of a family for this character:
+/// first the fonts that prefer it (see [`crate::text::FontData::preferred_characters`]),
+/// then all fonts in family order.
+fn font_indices_for_char(fonts: &[Arc<FontImpl>], c: char) -> impl Iterator<Item = FontIndex> + '_ {
+    let preferred = (0..fonts.len()).filter(move |&i| fonts[i].prefers_character(c));
+    preferred.chain(0..fonts.len())
+}
+
+/// Rasterize the glyphs for these characters into the atlas,
+/// using the same font of the family that [`Font`] would pick.
+///
+/// This only locks the glyph caches of the [`FontImpl`]s and the atlas,
+/// so it can run on another thread while the [`Font`] is being used for layout.
+pub(crate) fn preload_glyphs(fonts: &[Arc<FontImpl>], chars: impl IntoIterator<Item = char>) {
+    for c in chars {
+        font_indices_for_char(fonts, c)
+            .find(|&font_index| fonts[font_index].glyph_info(c).is_some());
+    }
+}
+
+/// Decode a PNG into unmultiplied RGBA pixels.
+#[cfg(feature = "color_emoji")]
+fn decode_png_rgba(png_bytes: &[u8]) -> Option<([usize; 2], Vec<[u8; 4]>)> {
+    let mut decoder = png::Decoder::new(png_bytes);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().ok()?;
+    let mut buf = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buf).ok()?;
+    let bytes = &buf[..info.buffer_size()];
+
+    let rgba = match info.color_type {
+        png::ColorType::Rgba => bytes
+            .chunks_exact(4)
+            .map(|p| [p[0], p[1], p[2], p[3]])
+            .collect(),
+        png::ColorType::Rgb => bytes
+            .chunks_exact(3)
+            .map(|p| [p[0], p[1], p[2], 255])
+            .collect(),
+        png::ColorType::GrayscaleAlpha => bytes
+            .chunks_exact(2)
+            .map(|p| [p[0], p[0], p[0], p[1]])
+            .collect(),
+        png::ColorType::Grayscale => bytes.iter().map(|&l| [l, l, l, 255]).collect(),
+        png::ColorType::Indexed => return None, // `normalize_to_color8` expands these
+    };
+    Some(([info.width as usize, info.height as usize], rgba))
+}
+
+/// Write unmultiplied RGBA pixels into a region of the font atlas,
+/// resampling them to `dst_size` using a box filter.
+#[cfg_attr(not(feature = "color_emoji"), allow(dead_code))]
+fn blit_rgba_resampled(
+    image: &mut crate::FontImage,
+    dst_pos: (usize, usize),
+    dst_size: [usize; 2],
+    src_size: [usize; 2],
+    src_rgba: &[[u8; 4]],
+) {
+    assert_eq!(src_size[0] * src_size[1], src_rgba.len());
+
+    let sx = src_size[0] as f32 / dst_size[0] as f32;
+    let sy = src_size[1] as f32 / dst_size[1] as f32;
+
+    for y in 0..dst_size[1] {
+        let y0 = (y as f32 * sy) as usize;
+        let y1 = (((y + 1) as f32 * sy).ceil() as usize).clamp(y0 + 1, src_size[1]);
+        for x in 0..dst_size[0] {
+            let x0 = (x as f32 * sx) as usize;
+            let x1 = (((x + 1) as f32 * sx).ceil() as usize).clamp(x0 + 1, src_size[0]);
+
+            // Average premultiplied, then unmultiply:
+            let mut sum = [0.0_f32; 4];
+            for sy in y0..y1 {
+                for sx in x0..x1 {
+                    let [r, g, b, a] = src_rgba[sy * src_size[0] + sx];
+                    let a = a as f32;
+                    sum[0] += r as f32 * a;
+                    sum[1] += g as f32 * a;
+                    sum[2] += b as f32 * a;
+                    sum[3] += a;
+                }
+            }
+            let n = ((x1 - x0) * (y1 - y0)) as f32;
+            let alpha = sum[3] / n;
+            if 0.0 < alpha {
+                let pos = (dst_pos.0 + x, dst_pos.1 + y);
+                image[pos] = alpha / 255.0;
+                image.set_color(
+                    pos,
+                    Color32::from_rgb(
+                        (sum[0] / sum[3]).round() as u8,
+                        (sum[1] / sum[3]).round() as u8,
+                        (sum[2] / sum[3]).round() as u8,
+                    ),
+                );
             }
         }
-        None
     }
 }
 
@@ -521,3 +2040,246 @@ fn invisible_char(c: char) -> bool {
             | '\u{FEFF}' // ZERO WIDTH NO-BREAK SPACE
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "default_fonts")]
+    fn test_ignore_characters() {
+        use crate::text::{FontData, FontDefinitions, FontFamily, FontId, FontsImpl};
+
+        let mut definitions = FontDefinitions::default();
+        definitions.font_data.insert(
+            "my_font".to_owned(),
+            FontData::from_static(include_bytes!("../../fonts/Ubuntu-Light.ttf"))
+                .ignore_characters(vec!['a'..='c']),
+        );
+        definitions
+            .families
+            .insert(FontFamily::Proportional, vec!["my_font".to_owned()]);
+        let mut fonts = FontsImpl::new(1.0, 1024, definitions);
+
+        let font = fonts.font(&FontId::proportional(14.0));
+        assert!(!font.characters().contains(&'b'));
+        assert!(font.characters().contains(&'d'));
+        let characters_per_font = font.characters_per_font();
+        assert_eq!(characters_per_font.len(), 1);
+        assert_eq!(characters_per_font[0].0, "my_font");
+        assert!(!characters_per_font[0].1.contains(&'b'));
+        assert!(characters_per_font[0].1.contains(&'d'));
+        assert!(!font.has_glyph('b'));
+        assert!(font.has_glyph('d'));
+
+        assert_eq!(font.font_index_for_char('d'), Some(0));
+        assert_eq!(font.font_name_for_char('d'), Some("my_font"));
+        assert_eq!(font.font_index_for_char('b'), None);
+        assert!(font.has_glyphs("def"));
+        assert!(!font.has_glyphs("abc"));
+
+        // Asking doesn't rasterize anything:
+        assert!(font.has_glyph('x'));
+        assert!(!font.fonts[0]
+            .glyph_info_cache
+            .read()
+            .contains_key(&('x', 0)));
+
+        // Built-in fonts keep ignoring their problematic code points:
+        let font = fonts.font(&FontId::monospace(14.0));
+        assert!(!font.characters().contains(&'\u{534d}'));
+    }
+
+    #[test]
+    fn test_replacement_characters() {
+        use crate::text::{FontData, FontDefinitions, FontFamily, FontId, FontsImpl};
+
+        let definitions = FontDefinitions {
+            replacement_characters: vec!['\u{E000}', 'x'],
+            ..Default::default()
+        };
+        let mut fonts = FontsImpl::new(1.0, 1024, definitions);
+        let font = fonts.font(&FontId::proportional(14.0));
+        assert_eq!(font.glyph_info('\u{10FFFF}'), font.glyph_info('x'));
+
+        // A font with only a handful of private-use icons:
+        let mut definitions = FontDefinitions::empty();
+        definitions.font_data.insert(
+            "icons".to_owned(),
+            FontData::from_static(include_bytes!("../../fonts/emoji-icon-font.ttf")),
+        );
+        definitions
+            .families
+            .insert(FontFamily::Proportional, vec!["icons".to_owned()]);
+        let mut fonts = FontsImpl::new(1.0, 1024, definitions);
+        let font = fonts.font(&FontId::proportional(14.0));
+        for c in FontDefinitions::default().replacement_characters {
+            assert!(!font.has_glyph(c));
+        }
+
+        // A box is drawn for missing characters instead:
+        let (_, replacement) = font.glyph_info('a');
+        assert!(replacement.advance_width > 0.0);
+        assert!(!replacement.uv_rect.is_nothing());
+        assert!(
+            replacement.uv_rect.offset.y < 0.0,
+            "The box should be above the baseline"
+        );
+
+        let galley = crate::text::layout(
+            &mut fonts,
+            crate::text::LayoutJob::simple_singleline(
+                "a\u{E000}".to_owned(),
+                FontId::proportional(14.0),
+                crate::Color32::WHITE,
+            )
+            .into(),
+        );
+        assert_eq!(galley.rows[0].glyphs[0].uv_rect, replacement.uv_rect);
+        assert!(galley.size().x > 0.0);
+    }
+
+    #[test]
+    fn test_line_metrics() {
+        use crate::text::{FontDefinitions, FontId, FontsImpl};
+
+        let mut fonts = FontsImpl::new(2.0, 1024, FontDefinitions::default());
+        for font_id in [FontId::proportional(20.0), FontId::monospace(20.0)] {
+            let font = fonts.font(&font_id);
+            let underline = font.underline_metrics().unwrap();
+            let strikeout = font.strikeout_metrics().unwrap();
+
+            // Underline is just below the baseline, strikeout is a bit above it:
+            assert!(0.0 < underline.y_offset && underline.y_offset < -font.descent());
+            assert!(-font.ascent() < strikeout.y_offset && strikeout.y_offset < 0.0);
+            assert!(0.0 < underline.thickness && underline.thickness < 3.0);
+            assert!(0.0 < strikeout.thickness && strikeout.thickness < 3.0);
+        }
+    }
+
+    #[test]
+    fn test_faux_style() {
+        use crate::text::{FauxStyle, FontDefinitions, FontFamily, FontId, FontsImpl, TextFormat};
+
+        let mut definitions = FontDefinitions::default();
+        let bold = FontFamily::Name("bold".into());
+        let italic = FontFamily::Name("italic".into());
+        definitions.faux_families.insert(
+            bold.clone(),
+            (FontFamily::Proportional, FauxStyle::bold(0.1)),
+        );
+        definitions.faux_families.insert(
+            italic.clone(),
+            (FontFamily::Proportional, FauxStyle::italic(0.3)),
+        );
+        definitions.add_faux_bold_strong(0.1);
+        let mut fonts = FontsImpl::new(1.0, 1024, definitions);
+
+        let (_, regular_i) = fonts.font(&FontId::proportional(20.0)).glyph_info('I');
+        let (_, bold_i) = fonts.font(&FontId::new(20.0, bold)).glyph_info('I');
+        let (_, italic_i) = fonts.font(&FontId::new(20.0, italic)).glyph_info('I');
+
+        // The same font data serves all styles at once, with separate glyphs in the atlas:
+        assert_ne!(regular_i.uv_rect.min, bold_i.uv_rect.min);
+        assert_ne!(regular_i.uv_rect.min, italic_i.uv_rect.min);
+
+        assert!(regular_i.advance_width + 1.5 < bold_i.advance_width);
+        assert!(regular_i.uv_rect.size.x + 1.0 < bold_i.uv_rect.size.x);
+
+        assert_eq!(italic_i.advance_width, regular_i.advance_width);
+        assert!(regular_i.uv_rect.size.x + 3.0 < italic_i.uv_rect.size.x);
+        assert_eq!(regular_i.uv_rect.size.y, italic_i.uv_rect.size.y);
+
+        // Strong text uses faux bold:
+        let strong = TextFormat {
+            font_id: FontId::proportional(20.0),
+            strong: true,
+            ..Default::default()
+        };
+        assert_eq!(fonts.format_font(&strong).glyph_info('I').1, bold_i);
+    }
+
+    #[test]
+    fn test_glyph_outline() {
+        use crate::text::{FontDefinitions, FontId, FontsImpl};
+
+        let mut fonts = FontsImpl::new(2.0, 1024, FontDefinitions::default());
+        let font = fonts.font(&FontId::proportional(20.0));
+
+        let outline = font.glyph_outline('o').unwrap();
+        let contours = outline.contours(0.1);
+        assert_eq!(
+            contours.len(),
+            2,
+            "An 'o' has an outer and an inner contour"
+        );
+
+        // The outline lines up with the rasterized glyph:
+        let (_, glyph_info) = font.glyph_info('o');
+        let uv_rect = glyph_info.uv_rect;
+        let glyph_rect = emath::Rect::from_min_size(uv_rect.offset.to_pos2(), uv_rect.size);
+        let outline_rect = emath::Rect::from_points(&contours.concat());
+        assert!(glyph_rect.expand(1.0).contains_rect(outline_rect));
+        assert!(outline_rect.expand(1.0).contains_rect(glyph_rect));
+        assert!(outline_rect.max.y <= 1.0, "The 'o' sits on the baseline");
+
+        assert_eq!(font.glyph_outline(' '), None);
+        assert_eq!(font.glyph_outline('\u{10FFFF}'), None);
+    }
+
+    #[test]
+    fn test_preferred_characters() {
+        use crate::text::{FontData, FontDefinitions, FontFamily, FontId, FontsImpl};
+
+        let mut definitions = FontDefinitions::default();
+        definitions.font_data.insert(
+            "first".to_owned(),
+            FontData::from_static(include_bytes!("../../fonts/Ubuntu-Light.ttf")),
+        );
+        definitions.font_data.insert(
+            "second".to_owned(),
+            FontData::from_static(include_bytes!("../../fonts/Hack-Regular.ttf"))
+                .preferred_characters(vec!['0'..='9', '\u{FB00}'..='\u{FB06}']),
+        );
+        definitions.families.insert(
+            FontFamily::Proportional,
+            vec!["first".to_owned(), "second".to_owned()],
+        );
+        let mut fonts = FontsImpl::new(1.0, 1024, definitions);
+
+        let font = fonts.font(&FontId::proportional(14.0));
+        assert_eq!(font.font_name_for_char('a'), Some("first"));
+        assert_eq!(font.font_name_for_char('5'), Some("second"));
+
+        // A preferred font without the glyph falls back to the family order:
+        assert!(font.fonts[0].has_glyph('ﬁ'));
+        assert!(!font.fonts[1].has_glyph('ﬁ'));
+        assert_eq!(font.font_name_for_char('ﬁ'), Some("first"));
+
+        // Layout uses the same font:
+        assert_eq!(font.glyph_info('5').0, 1);
+        assert_eq!(font.glyph_info('a').0, 0);
+    }
+
+    #[test]
+    fn test_blit_color_glyph() {
+        let mut image = crate::FontImage::new([4, 4]);
+
+        // Half red, half blue, downsampled into a single texel:
+        let src = [[255, 0, 0, 255], [0, 0, 255, 255]];
+        blit_rgba_resampled(&mut image, (1, 2), [1, 1], [2, 1], &src);
+
+        assert_eq!(image[(1, 2)], 1.0);
+        assert_eq!(image[(0, 0)], 0.0);
+
+        let pixels: Vec<Color32> = image.srgba_pixels(None).collect();
+        let [r, g, b, a] = pixels[2 * 4 + 1].to_array();
+        assert_eq!(a, 255);
+        assert_eq!(g, 0);
+        assert!(r > 100 && b > 100, "Expected purple, got {:?}", [r, g, b]);
+
+        // Non-colored texels stay grayscale:
+        let [r, g, b, _] = pixels[0].to_array();
+        assert!(r == g && g == b);
+    }
+}
@@ -1,14 +1,16 @@
-use std::{collections::BTreeMap, sync::Arc};
+use std::{collections::BTreeMap, ops::RangeInclusive, sync::Arc};
 
 use crate::{
     mutex::{Mutex, MutexGuard},
     text::{
-        font::{Font, FontImpl},
-        Galley, LayoutJob,
+        font::{AtlasGlyph, Font, FontImpl, GlyphStore},
+        sdf::SdfAtlas,
+        text_layout::{concat_paragraphs, layout_with_fonts, PlacedParagraph},
+        Galley, GlyphOutline, LayoutJob, LayoutSection, TextFormat,
     },
     TextureAtlas,
 };
-use emath::{NumExt as _, OrderedFloat};
+use emath::{NumExt as _, OrderedFloat, Vec2};
 
 // ----------------------------------------------------------------------------
 
@@ -117,6 +119,23 @@ pub struct FontData {
 
     /// Extra scale and vertical tweak to apply to all text of this font.
     pub tweak: FontTweak,
+
+    /// Code points in this font that should never be used.
+    ///
+    /// These will fall back to the next font in the [`FontFamily`],
+    /// or be shown as the replacement character.
+    ///
+    /// Default: empty.
+    pub ignore_characters: Vec<RangeInclusive<char>>,
+
+    /// Code points for which this font should be used even if
+    /// an earlier font in the [`FontFamily`] also has a glyph for them.
+    ///
+    /// For instance, you can prefer a color emoji font for `'\u{1F300}'..='\u{1FAFF}'`
+    /// while keeping it as a fallback font.
+    ///
+    /// Default: empty.
+    pub preferred_characters: Vec<RangeInclusive<char>>,
 }
 
 impl FontData {
@@ -125,6 +144,8 @@ impl FontData {
             font: std::borrow::Cow::Borrowed(font),
             index: 0,
             tweak: Default::default(),
+            ignore_characters: Default::default(),
+            preferred_characters: Default::default(),
         }
     }
 
@@ -133,12 +154,34 @@ impl FontData {
             font: std::borrow::Cow::Owned(font),
             index: 0,
             tweak: Default::default(),
+            ignore_characters: Default::default(),
+            preferred_characters: Default::default(),
         }
     }
 
     pub fn tweak(self, tweak: FontTweak) -> Self {
         Self { tweak, ..self }
     }
+
+    /// Never use these code points from this font.
+    ///
+    /// See [`Self::ignore_characters`].
+    pub fn ignore_characters(self, ignore_characters: Vec<RangeInclusive<char>>) -> Self {
+        Self {
+            ignore_characters,
+            ..self
+        }
+    }
+
+    /// Use this font for these code points, even if an earlier font in the family has them.
+    ///
+    /// See [`Self::preferred_characters`].
+    pub fn preferred_characters(self, preferred_characters: Vec<RangeInclusive<char>>) -> Self {
+        Self {
+            preferred_characters,
+            ..self
+        }
+    }
 }
 
 // ----------------------------------------------------------------------------
@@ -174,6 +217,25 @@ pub struct FontTweak {
     /// A positive value shifts the text downwards.
     /// A negative value shifts it upwards.
     pub baseline_offset_factor: f32,
+
+    /// Position glyphs with subpixel precision horizontally.
+    ///
+    /// Each glyph is then rasterized at up to four different horizontal offsets,
+    /// and the one closest to the actual position of the glyph is used.
+    /// This makes small text look more even, at the cost of using more of the font atlas.
+    ///
+    /// Default: `false` (glyphs are snapped to whole pixels).
+    pub subpixel_positioning: bool,
+
+    /// Rasterize the glyphs as signed distance fields instead of coverage masks.
+    ///
+    /// Each glyph is then rasterized only once, and shared by all sizes of the font,
+    /// which saves atlas space and keeps large and scaled text crisp.
+    /// SDF glyphs go into a separate texture (see [`Fonts::sdf_font_image_delta`]),
+    /// which the backend must paint by thresholding its alpha (see [`crate::TextureOptions::sdf`]).
+    ///
+    /// Default: `false`.
+    pub sdf: bool,
 }
 
 impl Default for FontTweak {
@@ -183,12 +245,50 @@ impl Default for FontTweak {
             y_offset_factor: 0.0,
             y_offset: 0.0,
             baseline_offset_factor: -0.0333, // makes the default fonts look more centered in buttons and such
+            subpixel_positioning: false,
+            sdf: false,
         }
     }
 }
 
 // ----------------------------------------------------------------------------
 
+/// Synthetic styling, for when you don't have a bold or italic font file.
+///
+/// See [`FontDefinitions::faux_families`].
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct FauxStyle {
+    /// Make the glyphs wider and heavier by this fraction of the font size.
+    ///
+    /// Example value: `0.04`. Default: `0.0` (off).
+    pub bold: f32,
+
+    /// Slant the glyphs to the right by this much (horizontal shift per unit of height).
+    ///
+    /// Example value: `0.2`. Default: `0.0` (off).
+    pub italic: f32,
+}
+
+impl FauxStyle {
+    /// Faux bold, with the given strength (see [`Self::bold`]).
+    pub fn bold(bold: f32) -> Self {
+        Self { bold, italic: 0.0 }
+    }
+
+    /// Faux italic, with the given slant (see [`Self::italic`]).
+    pub fn italic(italic: f32) -> Self {
+        Self { bold: 0.0, italic }
+    }
+
+    /// No synthetic styling at all.
+    pub fn is_none(&self) -> bool {
+        self.bold == 0.0 && self.italic == 0.0
+    }
+}
+
+// ----------------------------------------------------------------------------
+
 fn ab_glyph_font_from_font_data(name: &str, data: &FontData) -> ab_glyph::FontArc {
     match &data.font {
         std::borrow::Cow::Borrowed(bytes) => {
@@ -245,6 +345,36 @@ pub struct FontDefinitions {
     /// the first font and then move to the second, and so on.
     /// So the first font is the primary, and then comes a list of fallbacks in order of priority.
     pub families: BTreeMap<FontFamily, Vec<String>>,
+
+    /// Shown in place of characters that none of the fonts in a family support.
+    ///
+    /// The first of these that the family supports is used.
+    /// If it supports none of them, a hollow box is drawn instead.
+    ///
+    /// Default: `['◻', '?']`.
+    pub replacement_characters: Vec<char>,
+
+    /// Families synthesized from the fonts of another family in [`Self::families`],
+    /// with a [`FauxStyle`] applied to them.
+    ///
+    /// This lets you have e.g. a bold family without a bold font file:
+    /// ```
+    /// # use epaint::text::{FauxStyle, FontDefinitions, FontFamily};
+    /// let mut fonts = FontDefinitions::default();
+    /// fonts.faux_families.insert(
+    ///     FontFamily::Name("bold".into()),
+    ///     (FontFamily::Proportional, FauxStyle::bold(0.04)),
+    /// );
+    /// ```
+    ///
+    /// The regular and synthesized fonts share the same font data.
+    pub faux_families: BTreeMap<FontFamily, (FontFamily, FauxStyle)>,
+
+    /// Which family to use for [`crate::text::TextFormat::strong`] text of each family.
+    ///
+    /// Families not in here show strong text in the same font as normal text.
+    /// See also [`Self::add_faux_bold_strong`].
+    pub strong_families: BTreeMap<FontFamily, FontFamily>,
 }
 
 impl Default for FontDefinitions {
@@ -263,39 +393,58 @@ impl Default for FontDefinitions {
 
         let mut families = BTreeMap::new();
 
+        let ignore_characters = vec![
+            // Strip out a religious symbol with secondary nefarious interpretation:
+            '\u{534d}'..='\u{534d}',
+            '\u{5350}'..='\u{5350}',
+            // Ignore ubuntu-specific stuff in `Ubuntu-Light.ttf`:
+            '\u{E0FF}'..='\u{E0FF}',
+            '\u{EFFD}'..='\u{EFFD}',
+            '\u{F0FF}'..='\u{F0FF}',
+            '\u{F200}'..='\u{F200}',
+        ];
+
         font_data.insert(
             "Hack".to_owned(),
-            FontData::from_static(include_bytes!("../../fonts/Hack-Regular.ttf")),
+            FontData::from_static(include_bytes!("../../fonts/Hack-Regular.ttf"))
+                .ignore_characters(ignore_characters.clone()),
         );
         font_data.insert(
             "Ubuntu-Light".to_owned(),
-            FontData::from_static(include_bytes!("../../fonts/Ubuntu-Light.ttf")),
+            FontData::from_static(include_bytes!("../../fonts/Ubuntu-Light.ttf"))
+                .ignore_characters(ignore_characters.clone()),
         );
 
         // Some good looking emojis. Use as first priority:
         font_data.insert(
             "NotoEmoji-Regular".to_owned(),
-            FontData::from_static(include_bytes!("../../fonts/NotoEmoji-Regular.ttf")).tweak(
-                FontTweak {
+            FontData::from_static(include_bytes!("../../fonts/NotoEmoji-Regular.ttf"))
+                .tweak(FontTweak {
                     scale: 0.81, // make it smaller
                     ..Default::default()
-                },
-            ),
+                })
+                .ignore_characters(ignore_characters.clone()),
         );
 
         // Bigger emojis, and more. <http://jslegers.github.io/emoji-icon-font/>:
         font_data.insert(
             "emoji-icon-font".to_owned(),
-            FontData::from_static(include_bytes!("../../fonts/emoji-icon-font.ttf")).tweak(
-                FontTweak {
+            FontData::from_static(include_bytes!("../../fonts/emoji-icon-font.ttf"))
+                .tweak(FontTweak {
                     scale: 0.88, // make it smaller
 
                     // probably not correct, but this does make texts look better (#2724 for details)
                     y_offset_factor: 0.11, // move glyphs down to better align with common fonts
                     baseline_offset_factor: -0.11, // ...now the entire row is a bit down so shift it back
                     ..Default::default()
-                },
-            ),
+                })
+                .ignore_characters({
+                    // HACK: https://github.com/emilk/egui/issues/1284 https://github.com/jslegers/emoji-icon-font/issues/18
+                    // Don't show the wrong fullwidth capital letters:
+                    let mut ignore_characters = ignore_characters;
+                    ignore_characters.push('Ｓ'..='Ｙ');
+                    ignore_characters
+                }),
         );
 
         families.insert(
@@ -319,10 +468,20 @@ impl Default for FontDefinitions {
         Self {
             font_data,
             families,
+            replacement_characters: default_replacement_characters(),
+            faux_families: Default::default(),
+            strong_families: Default::default(),
         }
     }
 }
 
+fn default_replacement_characters() -> Vec<char> {
+    vec![
+        '◻', // white medium square
+        '?', // fallback for the fallback
+    ]
+}
+
 impl FontDefinitions {
     /// No fonts.
     pub fn empty() -> Self {
@@ -333,6 +492,25 @@ impl FontDefinitions {
         Self {
             font_data: Default::default(),
             families,
+            replacement_characters: default_replacement_characters(),
+            faux_families: Default::default(),
+            strong_families: Default::default(),
+        }
+    }
+
+    /// Show [`crate::text::TextFormat::strong`] text in a faux bold version of each family
+    /// that doesn't already have a family in [`Self::strong_families`].
+    ///
+    /// `bold` is the strength of the effect, see [`FauxStyle::bold`].
+    pub fn add_faux_bold_strong(&mut self, bold: f32) {
+        for family in self.families.keys() {
+            if self.strong_families.contains_key(family) {
+                continue;
+            }
+            let faux_family = FontFamily::Name(format!("{family} (faux bold)").into());
+            self.faux_families
+                .insert(faux_family.clone(), (family.clone(), FauxStyle::bold(bold)));
+            self.strong_families.insert(family.clone(), faux_family);
         }
     }
 
@@ -386,6 +564,20 @@ impl Fonts {
         Self(Arc::new(Mutex::new(fonts_and_cache)))
     }
 
+    /// Create a new [`Fonts`] with the same fonts as `self`, for another `pixels_per_point`.
+    ///
+    /// The two share the font atlas, so glyphs rasterized by either are reused by both
+    /// whenever their `pixels_per_point` matches, and only one font texture is needed.
+    /// Each change to the atlas is only returned by one call to [`Self::font_image_delta`] though,
+    /// whichever [`Fonts`] it is called on.
+    pub fn new_sharing_atlas(&self, pixels_per_point: f32) -> Self {
+        let fonts_and_cache = FontsAndCache {
+            fonts: self.lock().fonts.new_sharing_atlas(pixels_per_point),
+            galley_cache: Default::default(),
+        };
+        Self(Arc::new(Mutex::new(fonts_and_cache)))
+    }
+
     /// Call at the start of each frame with the latest known
     /// `pixels_per_point` and `max_texture_side`.
     ///
@@ -398,25 +590,94 @@ impl Fonts {
 
         let pixels_per_point_changed = fonts_and_cache.fonts.pixels_per_point != pixels_per_point;
         let max_texture_side_changed = fonts_and_cache.fonts.max_texture_side != max_texture_side;
-        let font_atlas_almost_full = fonts_and_cache.fonts.atlas.lock().fill_ratio() > 0.8;
+        let mut font_atlas_almost_full =
+            fonts_and_cache.fonts.atlases.atlas.lock().fill_ratio() > 0.8;
+
+        if font_atlas_almost_full && !pixels_per_point_changed && !max_texture_side_changed {
+            // Try to make room by throwing out glyphs that are no longer in use:
+            fonts_and_cache.gc(0);
+            font_atlas_almost_full = fonts_and_cache.fonts.atlases.atlas.lock().fill_ratio() > 0.8;
+        }
+
+        // SDF glyphs are shared by all sizes, so we only ever need to start over when the atlas is full:
+        let sdf_atlas_almost_full =
+            fonts_and_cache.fonts.atlases.sdf_atlas.lock().fill_ratio() > 0.8;
+
         let needs_recreate =
-            pixels_per_point_changed || max_texture_side_changed || font_atlas_almost_full;
+            max_texture_side_changed || font_atlas_almost_full || sdf_atlas_almost_full;
+
+        // Cached galleys are laid out with the old fonts, so a new cache is needed with new fonts:
+        let max_unused_frames = fonts_and_cache.galley_cache.max_unused_frames;
 
         if needs_recreate {
             let definitions = fonts_and_cache.fonts.definitions.clone();
+            let text_gamma = fonts_and_cache.fonts.atlases.atlas.lock().gamma();
 
             *fonts_and_cache = FontsAndCache {
                 fonts: FontsImpl::new(pixels_per_point, max_texture_side, definitions),
-                galley_cache: Default::default(),
+                galley_cache: GalleyCache::new(max_unused_frames),
             };
+            fonts_and_cache
+                .fonts
+                .atlases
+                .atlas
+                .lock()
+                .set_gamma(text_gamma);
+        } else if pixels_per_point_changed {
+            // Keep the atlas, so that we can reuse the glyphs in it if we switch back:
+            *fonts_and_cache = FontsAndCache {
+                fonts: fonts_and_cache.fonts.new_sharing_atlas(pixels_per_point),
+                galley_cache: GalleyCache::new(max_unused_frames),
+            };
+        }
+
+        {
+            let fonts = &fonts_and_cache.fonts;
+            fonts
+                .atlases
+                .glyph_store
+                .lock()
+                .begin_frame(fonts.font_impl_cache.used_glyph_store_scales());
         }
 
         fonts_and_cache.galley_cache.flush_cache();
+        fonts_and_cache.fonts.generation = fonts_and_cache.fonts.generation.wrapping_add(1);
+    }
+
+    /// Make room in the font atlas by forgetting glyphs that haven't been used
+    /// in the last `max_retained_frames` frames (as counted by calls to [`Self::begin_frame`]).
+    ///
+    /// Glyphs can't be removed from the atlas one by one, so this only does something
+    /// if enough of the atlas is taken up by unused glyphs: then the atlas is cleared
+    /// and the glyphs that are still in use are re-rasterized.
+    /// The whole font texture will then be re-uploaded via [`Self::font_image_delta`],
+    /// and any [`Galley`] laid out before this call should be thrown away.
+    ///
+    /// This is called automatically by [`Self::begin_frame`] when the font atlas is getting full,
+    /// but you can also call it yourself, e.g. after `pixels_per_point` or font sizes have changed a lot.
+    ///
+    /// Returns `true` if the font atlas was re-allocated.
+    pub fn gc(&self, max_retained_frames: u32) -> bool {
+        self.lock().gc(max_retained_frames)
     }
 
     /// Call at the end of each frame (before painting) to get the change to the font texture since last call.
     pub fn font_image_delta(&self) -> Option<crate::ImageDelta> {
-        self.lock().fonts.atlas.lock().take_delta()
+        self.lock().fonts.atlases.atlas.lock().take_delta()
+    }
+
+    /// Call at the end of each frame (before painting) to get the change to the SDF font texture since last call.
+    ///
+    /// This texture holds the glyphs of fonts with [`FontTweak::sdf`] set.
+    pub fn sdf_font_image_delta(&self) -> Option<crate::ImageDelta> {
+        self.lock().fonts.atlases.sdf_atlas.lock().take_delta()
+    }
+
+    /// The full SDF font image, as a delta replacing the whole SDF font texture.
+    ///
+    /// See [`Self::sdf_font_image_delta`].
+    pub fn sdf_font_image_full_delta(&self) -> crate::ImageDelta {
+        self.lock().fonts.atlases.sdf_atlas.lock().full_delta()
     }
 
     /// Access the underlying [`FontsAndCache`].
@@ -439,19 +700,25 @@ impl Fonts {
     /// The font atlas.
     /// Pass this to [`crate::Tessellator`].
     pub fn texture_atlas(&self) -> Arc<Mutex<TextureAtlas>> {
-        self.lock().fonts.atlas.clone()
+        self.lock().fonts.atlases.atlas.clone()
     }
 
     /// The full font atlas image.
     #[inline]
     pub fn image(&self) -> crate::FontImage {
-        self.lock().fonts.atlas.lock().image().clone()
+        self.lock().fonts.atlases.atlas.lock().image().clone()
     }
 
     /// Current size of the font image.
     /// Pass this to [`crate::Tessellator`].
     pub fn font_image_size(&self) -> [usize; 2] {
-        self.lock().fonts.atlas.lock().size()
+        self.lock().fonts.atlases.atlas.lock().size()
+    }
+
+    /// Current size of the SDF font image.
+    /// Pass this to [`crate::Tessellator::with_sdf_font_texture`].
+    pub fn sdf_font_image_size(&self) -> [usize; 2] {
+        self.lock().fonts.atlases.sdf_atlas.lock().size()
     }
 
     /// Width of this character in points.
@@ -471,19 +738,104 @@ impl Fonts {
         self.lock().fonts.has_glyphs(font_id, s)
     }
 
+    /// The outline of the glyph used to display this character, in points,
+    /// relative to the baseline origin of the glyph.
+    ///
+    /// Returns `None` for missing glyphs, and glyphs without an outline.
+    /// See [`crate::text::GlyphOutline`].
+    pub fn glyph_outline(&self, font_id: &FontId, c: char) -> Option<GlyphOutline> {
+        self.lock().fonts.font(font_id).glyph_outline(c)
+    }
+
+    /// Rasterize all the glyphs needed to show this text into the font atlas, without doing any layout.
+    ///
+    /// Use this to avoid a stutter the first time a lot of new text (e.g. a big CJK document) is shown.
+    /// The glyphs are rasterized without holding the lock on [`Fonts`],
+    /// so you can call this from a background thread while the UI thread keeps using the same [`Fonts`].
+    pub fn preload_text(&self, font_id: &FontId, text: &str) {
+        self.preload_chars(font_id, text.chars());
+    }
+
+    /// Like [`Self::preload_text`], but for any sequence of characters.
+    pub fn preload_chars(&self, font_id: &FontId, chars: impl IntoIterator<Item = char>) {
+        let font_impls = self.lock().fonts.font(font_id).font_impls().to_vec();
+        crate::text::font::preload_glyphs(&font_impls, chars);
+    }
+
     /// Height of one row of text in points
     #[inline]
     pub fn row_height(&self, font_id: &FontId) -> f32 {
         self.lock().fonts.row_height(font_id)
     }
 
+    /// A cheap estimate of the size of the [`Galley`] [`Self::layout_job`] would produce.
+    ///
+    /// No text is laid out: every character is assumed to be as wide as an `n`,
+    /// and each paragraph wraps into as many rows as its estimated width requires.
+    /// The estimate is exact for the height of text that is not wrapped,
+    /// but can be off by quite a bit otherwise.
+    ///
+    /// This is useful for sizing text that is not visible, e.g. rows scrolled out of view.
+    pub fn estimate_layout_size(&self, job: &LayoutJob) -> Vec2 {
+        let mut fonts_and_cache = self.lock();
+        let fonts = &mut fonts_and_cache.fonts;
+        let pixels_per_point = fonts.pixels_per_point;
+        let round_to_pixel = |point: f32| (point * pixels_per_point).round() / pixels_per_point;
+
+        let max_width = job.wrap.max_width;
+        let mut size = Vec2::ZERO;
+        let mut num_paragraphs = 0;
+        let mut paragraph_width = 0.0;
+        let mut paragraph_row_height: f32 = 0.0;
+
+        let mut end_paragraph = |width: f32, row_height: f32| {
+            let num_rows = if max_width.is_finite() && 0.0 < max_width {
+                (width / max_width).ceil().max(1.0)
+            } else {
+                1.0
+            };
+            size.x = size.x.max(width.min(max_width));
+            size.y += num_rows * row_height;
+            num_paragraphs += 1;
+        };
+
+        for section in &job.sections {
+            let font = fonts.format_font(&section.format);
+            let row_height = round_to_pixel(
+                section
+                    .format
+                    .line_height
+                    .unwrap_or_else(|| font.row_height()),
+            );
+            let char_width =
+                round_to_pixel(font.glyph_width('n') + section.format.extra_letter_spacing);
+
+            paragraph_width += section.leading_space;
+            paragraph_row_height = paragraph_row_height.max(row_height);
+
+            for (i, line) in job.text[section.byte_range.clone()].split('\n').enumerate() {
+                if 0 < i && job.break_on_newline {
+                    end_paragraph(paragraph_width, paragraph_row_height);
+                    paragraph_width = 0.0;
+                    paragraph_row_height = row_height;
+                }
+                paragraph_width += line.chars().count() as f32 * char_width;
+            }
+        }
+        end_paragraph(paragraph_width, paragraph_row_height);
+
+        size.y += (num_paragraphs - 1) as f32 * job.paragraph_spacing;
+        size.y = size.y.max(job.first_row_min_height);
+        size
+    }
+
     /// List of all known font families.
     pub fn families(&self) -> Vec<FontFamily> {
-        self.lock()
-            .fonts
-            .definitions
+        let definitions = &self.lock().fonts.definitions;
+        definitions
             .families
             .keys()
+            .chain(definitions.faux_families.keys())
             .cloned()
             .collect()
     }
@@ -495,21 +847,88 @@ impl Fonts {
     /// [`Self::layout_delayed_color`].
     ///
     /// The implementation uses memoization so repeated calls are cheap.
-    #[inline]
+    ///
+    /// This can be called from any thread, e.g. to lay out text on worker threads.
+    /// The lock of [`Self`] is only held to look up the cache and fetch the fonts,
+    /// so several threads can lay out text at the same time.
     pub fn layout_job(&self, job: LayoutJob) -> Arc<Galley> {
-        self.lock().layout_job(job)
+        let job = Arc::new(job);
+        loop {
+            let mut pending = match self.lock().begin_layout(job.clone()) {
+                Ok(galley) => return galley,
+                Err(pending) => pending,
+            };
+            let galley = pending.layout();
+            if self.lock().end_layout(pending, galley.clone()) {
+                return galley;
+            }
+            // The fonts were replaced while we were laying out, so the galley may refer
+            // to glyphs in an atlas that is no longer in use. Try again with the new fonts.
+        }
     }
 
     pub fn num_galleys_in_cache(&self) -> usize {
         self.lock().galley_cache.num_galleys_in_cache()
     }
 
+    /// Keep laid out galleys in the cache until they haven't been used
+    /// for this many frames (as counted by calls to [`Self::begin_frame`]).
+    ///
+    /// A higher value avoids laying out text again that is only shown now and then,
+    /// e.g. in a window that is opened and closed, at the cost of more memory.
+    ///
+    /// The cache is always cleared when `pixels_per_point` or the fonts change.
+    ///
+    /// Default: `1`, i.e. keep the galleys that were used in the previous frame.
+    pub fn set_galley_cache_max_unused_frames(&self, max_unused_frames: u32) {
+        self.lock().galley_cache.max_unused_frames = max_unused_frames.max(1);
+    }
+
     /// How full is the font atlas?
     ///
     /// This increases as new fonts and/or glyphs are used,
     /// but can also decrease in a call to [`Self::begin_frame`].
     pub fn font_atlas_fill_ratio(&self) -> f32 {
-        self.lock().fonts.atlas.lock().fill_ratio()
+        self.lock().fonts.atlases.atlas.lock().fill_ratio()
+    }
+
+    /// How much of the font atlas is in use, e.g. for debugging.
+    pub fn font_atlas_occupancy(&self) -> crate::AtlasOccupancy {
+        self.lock().fonts.atlases.atlas.lock().occupancy_stats()
+    }
+
+    /// The glyphs in the font atlas, and which font and character they belong to.
+    ///
+    /// This is for debugging, e.g. to show what is in [`Self::image`].
+    /// Other things in the atlas (like the pre-rasterized discs) are not included,
+    /// and neither are glyphs from before `pixels_per_point` last changed.
+    pub fn atlas_glyphs(&self) -> Vec<AtlasGlyph> {
+        let fonts = &self.lock().fonts;
+        let mut glyphs: Vec<AtlasGlyph> = fonts
+            .font_impl_cache
+            .cache
+            .values()
+            .flat_map(|font_impl| font_impl.atlas_glyphs())
+            .collect();
+        glyphs.sort_by_key(|glyph| (glyph.min[1], glyph.min[0]));
+        glyphs
+    }
+
+    /// The coverage gamma of the font texture, see [`crate::FontImage::srgba_pixels`].
+    pub fn text_gamma(&self) -> f32 {
+        self.lock().fonts.atlases.atlas.lock().gamma()
+    }
+
+    /// Make text bolder (lower gamma) or thinner (higher gamma).
+    ///
+    /// The gamma is applied when the font texture is uploaded, not when the glyphs are rasterized
+    /// (see [`crate::FontImage::gamma`]), so no glyphs or galleys are invalidated by this.
+    /// Instead the whole font texture is uploaded again if this changes the gamma,
+    /// so don't change this every frame.
+    ///
+    /// Default: [`crate::FontImage::DEFAULT_GAMMA`].
+    pub fn set_text_gamma(&self, text_gamma: f32) {
+        self.lock().fonts.atlases.atlas.lock().set_gamma(text_gamma);
     }
 
     /// Will wrap text at the given width and line break at `\n`.
@@ -560,13 +979,177 @@ pub struct FontsAndCache {
 }
 
 impl FontsAndCache {
-    fn layout_job(&mut self, job: LayoutJob) -> Arc<Galley> {
-        self.galley_cache.layout(&mut self.fonts, job)
+    /// Look up the job in the cache, or fetch what is needed to lay it out
+    /// without holding the lock, with [`PendingLayout::layout`].
+    fn begin_layout(&mut self, job: Arc<LayoutJob>) -> Result<Arc<Galley>, PendingLayout> {
+        let hash = crate::util::hash(&*job); // TODO(emilk): even faster hasher?
+        if let Some(galley) = self.galley_cache.get(hash) {
+            return Ok(galley);
+        }
+
+        // Lay out (or reuse) each paragraph on its own, so that editing one paragraph
+        // of a huge text only needs to lay out that paragraph again.
+        let paragraphs = paragraph_jobs(&job).map(|paragraph_jobs| {
+            // Identical paragraphs (e.g. empty ones) are told apart by how many came before,
+            // so that each keeps its own place:
+            let mut num_identical: ahash::HashMap<u64, usize> = Default::default();
+            paragraph_jobs
+                .into_iter()
+                .map(|(paragraph_job, section_indices)| {
+                    let job_hash = crate::util::hash(&paragraph_job);
+                    let occurrence = num_identical.entry(job_hash).or_default();
+                    let hash = crate::util::hash((job_hash, *occurrence));
+                    *occurrence += 1;
+                    PendingParagraph {
+                        hash,
+                        job: Arc::new(paragraph_job),
+                        section_indices,
+                        placed: self.galley_cache.get_paragraph(hash),
+                    }
+                })
+                .collect()
+        });
+
+        Err(PendingLayout {
+            hash,
+            fonts: self.fonts.layout_fonts(&job),
+            job,
+            paragraphs,
+            atlas: self.fonts.atlases.atlas.clone(),
+            #[cfg(test)]
+            on_layout: self.galley_cache.on_layout.clone(),
+        })
+    }
+
+    /// Cache a galley laid out by [`PendingLayout::layout`].
+    ///
+    /// Returns `false` if the fonts were replaced since [`Self::begin_layout`],
+    /// in which case the galley must be thrown away.
+    fn end_layout(&mut self, pending: PendingLayout, galley: Arc<Galley>) -> bool {
+        let same_fonts = Arc::ptr_eq(&pending.atlas, &self.fonts.atlases.atlas)
+            && pending.fonts.pixels_per_point() == self.fonts.pixels_per_point;
+        if !same_fonts {
+            return false;
+        }
+
+        let mut paragraph_hashes = Vec::new();
+        for paragraph in pending.paragraphs.into_iter().flatten() {
+            if let Some(placed) = paragraph.placed {
+                self.galley_cache.insert_paragraph(paragraph.hash, placed);
+                paragraph_hashes.push(paragraph.hash);
+            }
+        }
+        self.galley_cache
+            .insert(pending.hash, galley, paragraph_hashes);
+        true
+    }
+
+    /// See [`Fonts::gc`].
+    fn gc(&mut self, max_retained_frames: u32) -> bool {
+        /// Re-allocate the atlas if more than this fraction of the glyphs in it are unused.
+        const MAX_UNUSED_FRACTION: f32 = 0.5;
+
+        crate::profile_function!();
+
+        // Fonts that haven't been used for a while don't know what frame it is:
+        let generation = self.fonts.generation;
+        for font in self.fonts.sized_family.values() {
+            font.set_generation(generation);
+        }
+
+        // Glyphs in cached galleys are still in use, even if they weren't laid out recently.
+        // This includes the glyphs of the paragraphs they were made of. Paragraphs cached
+        // without a galley are laid out again after a re-allocation, since it clears the cache.
+        for cached in self.galley_cache.cache.values() {
+            let galley = &cached.galley;
+            for row in &galley.rows {
+                for glyph in row.glyphs.iter() {
+                    let section = &galley.job.sections[glyph.section_index as usize];
+                    // Strong text may use a different font than its `font_id`:
+                    self.fonts.format_font(&section.format).mark_used(glyph.chr);
+                }
+            }
+        }
+
+        let mut num_glyphs = 0;
+        let mut num_unused = 0;
+        let mut retained = vec![];
+        for ((size, family), font) in &self.fonts.sized_family {
+            num_glyphs += font.num_cached_glyphs();
+            num_unused += font.num_unused_glyphs(max_retained_frames);
+            let font_id = FontId::new(size.into_inner(), family.clone());
+            retained.push((font_id, font.recently_used_characters(max_retained_frames)));
+        }
+
+        if num_unused as f32 <= MAX_UNUSED_FRACTION * num_glyphs as f32 {
+            // Not worth re-allocating the atlas, which is the only way to make room in it,
+            // so we keep all glyphs as they are:
+            return false;
+        }
+
+        // Start over with an empty atlas. This marks the whole texture as dirty,
+        // so the renderer will re-upload it and no stale UVs will be drawn.
+        // Any other `Fonts` sharing the old atlas keeps using it.
+        let (width, text_gamma) = {
+            let atlas = self.fonts.atlases.atlas.lock();
+            (atlas.size()[0], atlas.gamma())
+        };
+        let mut atlas = TextureAtlas::new([width, INITIAL_ATLAS_HEIGHT])
+            .with_max_height(self.fonts.max_texture_side);
+        atlas.set_gamma(text_gamma);
+        self.fonts.atlases = SharedAtlases {
+            atlas: Arc::new(Mutex::new(atlas)),
+            sdf_atlas: self.fonts.atlases.sdf_atlas.clone(),
+            glyph_store: Default::default(),
+        };
+        self.fonts
+            .font_impl_cache
+            .set_atlases(self.fonts.atlases.clone());
+        self.fonts.sized_family.clear();
+        self.galley_cache.clear();
+
+        for (font_id, chars) in retained {
+            if !chars.is_empty() {
+                self.fonts.font(&font_id).preload_characters(&chars);
+            }
+        }
+
+        true
     }
 }
 
 // ----------------------------------------------------------------------------
 
+/// Keep the initial font atlas small, so it is fast to upload to GPU.
+/// This will expand as needed anyways.
+const INITIAL_ATLAS_HEIGHT: usize = 32;
+
+/// The font atlases, and the glyphs in them.
+///
+/// These can be shared by several [`FontsImpl`] with different `pixels_per_point`,
+/// see [`FontsImpl::new_sharing_atlas`].
+#[derive(Clone)]
+struct SharedAtlases {
+    atlas: Arc<Mutex<TextureAtlas>>,
+    sdf_atlas: Arc<Mutex<SdfAtlas>>,
+    glyph_store: Arc<Mutex<GlyphStore>>,
+}
+
+impl SharedAtlases {
+    fn new(max_texture_side: usize) -> Self {
+        let texture_width = max_texture_side.at_most(8 * 1024);
+        let new_atlas = || {
+            TextureAtlas::new([texture_width, INITIAL_ATLAS_HEIGHT])
+                .with_max_height(max_texture_side)
+        };
+        Self {
+            atlas: Arc::new(Mutex::new(new_atlas())),
+            sdf_atlas: Arc::new(Mutex::new(SdfAtlas::new(new_atlas()))),
+            glyph_store: Default::default(),
+        }
+    }
+}
+
 /// The collection of fonts used by `epaint`.
 ///
 /// Required in order to paint text.
@@ -574,9 +1157,12 @@ pub struct FontsImpl {
     pixels_per_point: f32,
     max_texture_side: usize,
     definitions: FontDefinitions,
-    atlas: Arc<Mutex<TextureAtlas>>,
+    atlases: SharedAtlases,
     font_impl_cache: FontImplCache,
-    sized_family: ahash::HashMap<(OrderedFloat<f32>, FontFamily), Font>,
+    sized_family: ahash::HashMap<(OrderedFloat<f32>, FontFamily), Arc<Font>>,
+
+    /// Frame counter, used to track when glyphs were last used.
+    generation: u32,
 }
 
 impl FontsImpl {
@@ -586,28 +1172,47 @@ impl FontsImpl {
         pixels_per_point: f32,
         max_texture_side: usize,
         definitions: FontDefinitions,
+    ) -> Self {
+        let atlases = SharedAtlases::new(max_texture_side);
+        Self::with_atlases(pixels_per_point, max_texture_side, definitions, atlases)
+    }
+
+    /// Create a new [`FontsImpl`] for another `pixels_per_point`, with the same fonts,
+    /// which shares the font atlases with `self`.
+    ///
+    /// The glyphs `self` has rasterized, and any [`FontsImpl`] with the same `pixels_per_point`
+    /// as the new one did, are reused rather than rasterized again.
+    pub fn new_sharing_atlas(&self, pixels_per_point: f32) -> Self {
+        Self::with_atlases(
+            pixels_per_point,
+            self.max_texture_side,
+            self.definitions.clone(),
+            self.atlases.clone(),
+        )
+    }
+
+    fn with_atlases(
+        pixels_per_point: f32,
+        max_texture_side: usize,
+        definitions: FontDefinitions,
+        atlases: SharedAtlases,
     ) -> Self {
         assert!(
             0.0 < pixels_per_point && pixels_per_point < 100.0,
             "pixels_per_point out of range: {pixels_per_point}"
         );
 
-        let texture_width = max_texture_side.at_most(8 * 1024);
-        let initial_height = 32; // Keep initial font atlas small, so it is fast to upload to GPU. This will expand as needed anyways.
-        let atlas = TextureAtlas::new([texture_width, initial_height]);
-
-        let atlas = Arc::new(Mutex::new(atlas));
-
         let font_impl_cache =
-            FontImplCache::new(atlas.clone(), pixels_per_point, &definitions.font_data);
+            FontImplCache::new(atlases.clone(), pixels_per_point, &definitions.font_data);
 
         Self {
             pixels_per_point,
             max_texture_side,
             definitions,
-            atlas,
+            atlases,
             font_impl_cache,
             sized_family: Default::default(),
+            generation: 0,
         }
     }
 
@@ -622,23 +1227,74 @@ impl FontsImpl {
     }
 
     /// Get the right font implementation from size and [`FontFamily`].
-    pub fn font(&mut self, font_id: &FontId) -> &mut Font {
+    pub fn font(&mut self, font_id: &FontId) -> &Font {
+        self.font_arc(font_id)
+    }
+
+    fn font_arc(&mut self, font_id: &FontId) -> &Arc<Font> {
         let FontId { size, family } = font_id;
 
-        self.sized_family
+        let font = self
+            .sized_family
             .entry((OrderedFloat(*size), family.clone()))
             .or_insert_with(|| {
-                let fonts = &self.definitions.families.get(family);
+                let (base_family, faux_style) = match self.definitions.faux_families.get(family) {
+                    Some((base_family, faux_style)) => (base_family, *faux_style),
+                    None => (family, FauxStyle::default()),
+                };
+                let fonts = &self.definitions.families.get(base_family);
                 let fonts = fonts
                     .unwrap_or_else(|| panic!("FontFamily::{family:?} is not bound to any fonts"));
 
                 let fonts: Vec<Arc<FontImpl>> = fonts
                     .iter()
-                    .map(|font_name| self.font_impl_cache.font_impl(*size, font_name))
+                    .map(|font_name| self.font_impl_cache.font_impl(*size, font_name, faux_style))
                     .collect();
 
-                Font::new(fonts)
-            })
+                Arc::new(Font::new(fonts, &self.definitions.replacement_characters))
+            });
+        font.set_generation(self.generation);
+        font
+    }
+
+    /// The font to use for text with this format.
+    ///
+    /// This is [`Self::font`] of [`TextFormat::font_id`], unless [`TextFormat::strong`]
+    /// picks another family from [`FontDefinitions::strong_families`].
+    pub fn format_font(&mut self, format: &TextFormat) -> &Font {
+        self.format_font_arc(format)
+    }
+
+    fn format_font_arc(&mut self, format: &TextFormat) -> &Arc<Font> {
+        let strong_family = format
+            .strong
+            .then(|| self.definitions.strong_families.get(&format.font_id.family))
+            .flatten();
+        if let Some(strong_family) = strong_family {
+            let font_id = FontId::new(format.font_id.size, strong_family.clone());
+            self.font_arc(&font_id)
+        } else {
+            self.font_arc(&format.font_id)
+        }
+    }
+
+    /// The fonts of all the sections of this job.
+    pub(crate) fn layout_fonts(&mut self, job: &LayoutJob) -> LayoutFonts {
+        let mut fonts: Vec<((FontId, bool), Arc<Font>)> = Vec::new();
+        for section in &job.sections {
+            let format = &section.format;
+            if !fonts
+                .iter()
+                .any(|(key, _)| key.0 == format.font_id && key.1 == format.strong)
+            {
+                let font = self.format_font_arc(format).clone();
+                fonts.push(((format.font_id.clone(), format.strong), font));
+            }
+        }
+        LayoutFonts {
+            pixels_per_point: self.pixels_per_point,
+            fonts,
+        }
     }
 
     /// Width of this character in points.
@@ -664,39 +1320,212 @@ impl FontsImpl {
 
 // ----------------------------------------------------------------------------
 
+/// The fonts needed to lay out one [`LayoutJob`], fetched from [`FontsImpl`] up front,
+/// so that the layout itself doesn't need the lock on [`Fonts`].
+pub(crate) struct LayoutFonts {
+    pixels_per_point: f32,
+
+    /// The font of each [`TextFormat::font_id`] and [`TextFormat::strong`] in the job.
+    fonts: Vec<((FontId, bool), Arc<Font>)>,
+}
+
+impl LayoutFonts {
+    #[inline(always)]
+    pub fn pixels_per_point(&self) -> f32 {
+        self.pixels_per_point
+    }
+
+    /// See [`FontsImpl::format_font`].
+    ///
+    /// Panics if the format isn't that of one of the sections of the job.
+    pub fn format_font(&self, format: &TextFormat) -> &Font {
+        self.fonts
+            .iter()
+            .find(|(key, _)| key.0 == format.font_id && key.1 == format.strong)
+            .map(|(_, font)| &**font)
+            .expect("the format should be that of one of the sections")
+    }
+}
+
+/// A [`LayoutJob`] that wasn't in the [`GalleyCache`],
+/// with everything needed to lay it out without holding the lock on [`Fonts`].
+struct PendingLayout {
+    hash: u64,
+    job: Arc<LayoutJob>,
+    fonts: LayoutFonts,
+
+    /// Set if the job is laid out one paragraph at a time, see [`paragraph_jobs`].
+    paragraphs: Option<Vec<PendingParagraph>>,
+
+    /// The atlas the glyphs are rasterized into, to notice if the fonts are replaced meanwhile.
+    atlas: Arc<Mutex<TextureAtlas>>,
+
+    #[cfg(test)]
+    on_layout: Option<Arc<dyn Fn() + Send + Sync>>,
+}
+
+struct PendingParagraph {
+    hash: u64,
+    job: Arc<LayoutJob>,
+
+    /// The index in the whole job of each section of [`Self::job`].
+    section_indices: Vec<u32>,
+
+    /// Set if the paragraph was in the cache, or once it has been laid out and placed.
+    placed: Option<Arc<PlacedParagraph>>,
+}
+
+impl PendingLayout {
+    fn layout(&mut self) -> Arc<Galley> {
+        crate::profile_function!();
+
+        let Some(paragraphs) = &mut self.paragraphs else {
+            #[cfg(test)]
+            if let Some(on_layout) = &self.on_layout {
+                on_layout();
+            }
+            return Arc::new(layout_with_fonts(&self.fonts, self.job.clone()));
+        };
+
+        // Only lay out the paragraphs that changed, and only move those that moved:
+        let mut offset_y = 0.0;
+        for paragraph in paragraphs.iter_mut() {
+            let placed = match paragraph.placed.take() {
+                Some(placed) if placed.is_placed_at(&paragraph.section_indices, offset_y) => placed,
+                Some(placed) => {
+                    Arc::new(placed.moved(&self.job, &paragraph.section_indices, offset_y))
+                }
+                None => {
+                    #[cfg(test)]
+                    if let Some(on_layout) = &self.on_layout {
+                        on_layout();
+                    }
+                    let galley = layout_with_fonts(&self.fonts, paragraph.job.clone());
+                    Arc::new(PlacedParagraph::new(
+                        &self.job,
+                        galley,
+                        &paragraph.section_indices,
+                        offset_y,
+                    ))
+                }
+            };
+            offset_y = placed.next_offset_y();
+            paragraph.placed = Some(placed);
+        }
+
+        let placed: Vec<&PlacedParagraph> = paragraphs
+            .iter()
+            .filter_map(|paragraph| paragraph.placed.as_deref())
+            .collect();
+        Arc::new(concat_paragraphs(
+            self.job.clone(),
+            &placed,
+            self.fonts.pixels_per_point(),
+        ))
+    }
+}
+
 struct CachedGalley {
     /// When it was last used
     last_used: u32,
     galley: Arc<Galley>,
+
+    /// The paragraphs the galley was made from, if it was laid out one paragraph at a time.
+    paragraph_hashes: Vec<u64>,
+}
+
+struct CachedParagraph {
+    /// When it was last used
+    last_used: u32,
+    paragraph: Arc<PlacedParagraph>,
 }
 
-#[derive(Default)]
+/// Memoizes the layout of [`LayoutJob`]s, keyed on their hash.
+///
+/// This belongs to a single [`FontsImpl`], and is replaced together with it
+/// whenever `pixels_per_point` or the [`FontDefinitions`] change.
 struct GalleyCache {
     /// Frame counter used to do garbage collection on the cache
     generation: u32,
+
+    /// See [`Fonts::set_galley_cache_max_unused_frames`].
+    max_unused_frames: u32,
+
     cache: nohash_hasher::IntMap<u64, CachedGalley>,
+
+    /// The paragraphs of huge jobs, keyed on the hash of their own [`LayoutJob`],
+    /// and how many identical paragraphs came before them.
+    ///
+    /// See [`paragraph_jobs`].
+    paragraphs: nohash_hasher::IntMap<u64, CachedParagraph>,
+
+    /// Called whenever a job or paragraph is laid out, to test what happens meanwhile.
+    #[cfg(test)]
+    on_layout: Option<Arc<dyn Fn() + Send + Sync>>,
+}
+
+impl Default for GalleyCache {
+    fn default() -> Self {
+        Self::new(1)
+    }
 }
 
 impl GalleyCache {
-    fn layout(&mut self, fonts: &mut FontsImpl, job: LayoutJob) -> Arc<Galley> {
-        let hash = crate::util::hash(&job); // TODO(emilk): even faster hasher?
-
-        match self.cache.entry(hash) {
-            std::collections::hash_map::Entry::Occupied(entry) => {
-                let cached = entry.into_mut();
-                cached.last_used = self.generation;
-                cached.galley.clone()
-            }
-            std::collections::hash_map::Entry::Vacant(entry) => {
-                let galley = super::layout(fonts, job.into());
-                let galley = Arc::new(galley);
-                entry.insert(CachedGalley {
-                    last_used: self.generation,
-                    galley: galley.clone(),
-                });
-                galley
+    fn new(max_unused_frames: u32) -> Self {
+        Self {
+            generation: 0,
+            max_unused_frames,
+            cache: Default::default(),
+            paragraphs: Default::default(),
+            #[cfg(test)]
+            on_layout: None,
+        }
+    }
+
+    fn get(&mut self, hash: u64) -> Option<Arc<Galley>> {
+        let cached = self.cache.get_mut(&hash)?;
+        cached.last_used = self.generation;
+
+        // Keep the paragraphs too, for when the job changes:
+        for paragraph_hash in &cached.paragraph_hashes {
+            if let Some(paragraph) = self.paragraphs.get_mut(paragraph_hash) {
+                paragraph.last_used = self.generation;
             }
         }
+
+        Some(cached.galley.clone())
+    }
+
+    fn insert(&mut self, hash: u64, galley: Arc<Galley>, paragraph_hashes: Vec<u64>) {
+        self.cache.insert(
+            hash,
+            CachedGalley {
+                last_used: self.generation,
+                galley,
+                paragraph_hashes,
+            },
+        );
+    }
+
+    fn get_paragraph(&mut self, hash: u64) -> Option<Arc<PlacedParagraph>> {
+        let cached = self.paragraphs.get_mut(&hash)?;
+        cached.last_used = self.generation;
+        Some(cached.paragraph.clone())
+    }
+
+    fn insert_paragraph(&mut self, hash: u64, paragraph: Arc<PlacedParagraph>) {
+        self.paragraphs.insert(
+            hash,
+            CachedParagraph {
+                last_used: self.generation,
+                paragraph,
+            },
+        );
+    }
+
+    fn clear(&mut self) {
+        self.cache.clear();
+        self.paragraphs.clear();
     }
 
     pub fn num_galleys_in_cache(&self) -> usize {
@@ -706,52 +1535,188 @@ impl GalleyCache {
     /// Must be called once per frame to clear the [`Galley`] cache.
     pub fn flush_cache(&mut self) {
         let current_generation = self.generation;
+        let max_unused_frames = self.max_unused_frames;
         self.cache.retain(|_key, cached| {
-            cached.last_used == current_generation // only keep those that were used this frame
+            // Only keep those that were used in the last `max_unused_frames` frames:
+            current_generation.wrapping_sub(cached.last_used) < max_unused_frames
+        });
+        self.paragraphs.retain(|_key, cached| {
+            current_generation.wrapping_sub(cached.last_used) < max_unused_frames
         });
         self.generation = self.generation.wrapping_add(1);
     }
 }
 
+/// Jobs with less text than this are always laid out as a whole.
+///
+/// Laying out a small job again is cheap, so it is not worth caching each of its paragraphs.
+const MIN_PARAGRAPH_JOB_LEN: usize = 4 * 1024;
+
+/// Split a huge job with more than one paragraph into one job per paragraph,
+/// each with the indices of its sections in `job.sections`.
+///
+/// Returns `None` if the job should be laid out as a whole, e.g. because it is small,
+/// or because rows are limited by [`TextWrapping::max_rows`](super::TextWrapping::max_rows).
+fn paragraph_jobs(job: &LayoutJob) -> Option<Vec<(LayoutJob, Vec<u32>)>> {
+    let is_multi_paragraph = job
+        .text
+        .find('\n')
+        .is_some_and(|newline| newline + 1 < job.text.len());
+    if job.text.len() < MIN_PARAGRAPH_JOB_LEN
+        || !is_multi_paragraph
+        || job.vertical
+        || !job.break_on_newline
+        || job.wrap.max_rows != usize::MAX
+    {
+        return None;
+    }
+
+    let mut paragraph_jobs = Vec::new();
+    let mut start = 0;
+    while start < job.text.len() {
+        let end = job.text[start..]
+            .find('\n')
+            .map_or(job.text.len(), |newline| start + newline + 1);
+
+        let mut sections = Vec::new();
+        let mut section_indices = Vec::new();
+        for (section_index, section) in job.sections.iter().enumerate() {
+            let byte_range = section.byte_range.start.max(start)..section.byte_range.end.min(end);
+            if byte_range.is_empty() {
+                continue;
+            }
+            sections.push(LayoutSection {
+                leading_space: if section.byte_range.start < start {
+                    0.0
+                } else {
+                    section.leading_space
+                },
+                byte_range: byte_range.start - start..byte_range.end - start,
+                format: section.format.clone(),
+            });
+            section_indices.push(section_index as u32);
+        }
+        if sections.is_empty() {
+            // Text that is not part of any section doesn't start a new paragraph.
+            return None;
+        }
+
+        paragraph_jobs.push((
+            LayoutJob {
+                text: job.text[start..end].to_owned(),
+                sections,
+                wrap: job.wrap.clone(),
+                first_row_min_height: if start == 0 {
+                    job.first_row_min_height
+                } else {
+                    0.0
+                },
+                break_on_newline: job.break_on_newline,
+                paragraph_spacing: job.paragraph_spacing,
+                halign: job.halign,
+                justify: job.justify,
+                tab_width: job.tab_width,
+                tab_stop_width_in_columns: job.tab_stop_width_in_columns,
+                vertical: job.vertical,
+                show_whitespace: job.show_whitespace,
+                round_output_size_to_nearest_ui_point: job.round_output_size_to_nearest_ui_point,
+            },
+            section_indices,
+        ));
+        start = end;
+    }
+    Some(paragraph_jobs)
+}
+
 // ----------------------------------------------------------------------------
 
+/// A parsed [`FontData`], ready to create [`FontImpl`]s of any size from.
+#[derive(Clone)]
+struct LoadedFont {
+    tweak: FontTweak,
+    ignore_characters: Arc<[RangeInclusive<char>]>,
+    preferred_characters: Arc<[RangeInclusive<char>]>,
+    ab_glyph_font: ab_glyph::FontArc,
+    underline_metrics: Option<ttf_parser::LineMetrics>,
+    strikeout_metrics: Option<ttf_parser::LineMetrics>,
+
+    /// Identifies the font data in the [`GlyphStore`].
+    font_data_hash: u64,
+
+    #[cfg(feature = "shaping")]
+    shaping_face: crate::text::font::SharedShapingFace,
+}
+
 struct FontImplCache {
-    atlas: Arc<Mutex<TextureAtlas>>,
+    atlases: SharedAtlases,
     pixels_per_point: f32,
-    ab_glyph_fonts: BTreeMap<String, (FontTweak, ab_glyph::FontArc)>,
+    loaded_fonts: BTreeMap<String, LoadedFont>,
+
+    /// Map font pixel sizes, names and [`FauxStyle`] (as bits) to the cached [`FontImpl`].
+    cache: ahash::HashMap<(u32, String, [u32; 2]), Arc<FontImpl>>,
 
-    /// Map font pixel sizes and names to the cached [`FontImpl`].
-    cache: ahash::HashMap<(u32, String), Arc<FontImpl>>,
+    /// The raw font files, for shaping and `OpenType` features.
+    font_data: BTreeMap<String, Arc<FontData>>,
 }
 
 impl FontImplCache {
     pub fn new(
-        atlas: Arc<Mutex<TextureAtlas>>,
+        atlases: SharedAtlases,
         pixels_per_point: f32,
         font_data: &BTreeMap<String, FontData>,
     ) -> Self {
-        let ab_glyph_fonts = font_data
+        let loaded_fonts = font_data
             .iter()
             .map(|(name, font_data)| {
-                let tweak = font_data.tweak;
-                let ab_glyph = ab_glyph_font_from_font_data(name, font_data);
-                (name.clone(), (tweak, ab_glyph))
+                let (underline_metrics, strikeout_metrics) =
+                    crate::text::font::unscaled_line_metrics(&font_data.font, font_data.index);
+                let loaded_font = LoadedFont {
+                    tweak: font_data.tweak,
+                    ignore_characters: font_data.ignore_characters.clone().into(),
+                    preferred_characters: font_data.preferred_characters.clone().into(),
+                    ab_glyph_font: ab_glyph_font_from_font_data(name, font_data),
+                    underline_metrics,
+                    strikeout_metrics,
+                    font_data_hash: crate::util::hash((&*font_data.font, font_data.index)),
+                    #[cfg(feature = "shaping")]
+                    shaping_face: Default::default(),
+                };
+                (name.clone(), loaded_font)
             })
             .collect();
 
         Self {
-            atlas,
+            atlases,
             pixels_per_point,
-            ab_glyph_fonts,
+            loaded_fonts,
             cache: Default::default(),
+            font_data: font_data
+                .iter()
+                .map(|(name, font_data)| (name.clone(), Arc::new(font_data.clone())))
+                .collect(),
         }
     }
 
-    pub fn font_impl(&mut self, scale_in_points: f32, font_name: &str) -> Arc<FontImpl> {
+    pub fn font_impl(
+        &mut self,
+        scale_in_points: f32,
+        font_name: &str,
+        faux_style: FauxStyle,
+    ) -> Arc<FontImpl> {
         use ab_glyph::Font as _;
 
-        let (tweak, ab_glyph_font) = self
-            .ab_glyph_fonts
+        let LoadedFont {
+            tweak,
+            ignore_characters,
+            preferred_characters,
+            ab_glyph_font,
+            underline_metrics,
+            strikeout_metrics,
+            font_data_hash,
+            #[cfg(feature = "shaping")]
+            shaping_face,
+        } = self
+            .loaded_fonts
             .get(font_name)
             .unwrap_or_else(|| panic!("No font data found for {font_name:?}"))
             .clone();
@@ -769,17 +1734,568 @@ impl FontImplCache {
             .entry((
                 (scale_in_pixels * tweak.scale).round() as u32,
                 font_name.to_owned(),
+                [faux_style.bold.to_bits(), faux_style.italic.to_bits()],
             ))
             .or_insert_with(|| {
-                Arc::new(FontImpl::new(
-                    self.atlas.clone(),
+                let font_impl = FontImpl::new(
+                    self.atlases.atlas.clone(),
                     self.pixels_per_point,
                     font_name.to_owned(),
                     ab_glyph_font,
                     scale_in_pixels,
                     tweak,
-                ))
+                    ignore_characters,
+                )
+                .with_preferred_characters(preferred_characters)
+                .with_line_metrics(underline_metrics, strikeout_metrics)
+                .with_faux_style(faux_style)
+                .with_glyph_store(self.atlases.glyph_store.clone(), font_data_hash);
+                let font_impl = if tweak.sdf {
+                    font_impl.with_sdf_atlas(self.atlases.sdf_atlas.clone())
+                } else {
+                    font_impl
+                };
+                let font_impl = match self.font_data.get(font_name) {
+                    Some(font_data) => font_impl.with_font_data(font_data.clone()),
+                    None => font_impl,
+                };
+                #[cfg(feature = "shaping")]
+                let font_impl = font_impl.with_shaping_face(shaping_face);
+                Arc::new(font_impl)
             })
             .clone()
     }
+
+    /// Forget all [`FontImpl`]s, and rasterize glyphs into these atlases from now on.
+    fn set_atlases(&mut self, atlases: SharedAtlases) {
+        self.atlases = atlases;
+        self.cache.clear();
+    }
+
+    /// The [`FontImpl::glyph_store_scale`] of all fonts.
+    fn used_glyph_store_scales(&self) -> impl Iterator<Item = (u64, u32)> + '_ {
+        self.cache
+            .values()
+            .filter_map(|font_impl| font_impl.glyph_store_scale())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Color32;
+
+    #[test]
+    #[cfg(feature = "default_fonts")]
+    fn test_gc_unused_glyphs() {
+        let fonts = Fonts::new(1.0, 1024, FontDefinitions::default());
+        let small = FontId::proportional(14.0);
+        let large = FontId::proportional(64.0);
+
+        // Fill the atlas with large glyphs that are only used once:
+        fonts.begin_frame(1.0, 1024);
+        fonts.layout_no_wrap(
+            "ABCDEFGHIJKLMNOPQRSTUVWXYZ".to_owned(),
+            large,
+            Color32::WHITE,
+        );
+        let fill_ratio_with_large = fonts.font_atlas_fill_ratio();
+
+        for _ in 0..5 {
+            fonts.begin_frame(1.0, 1024);
+            fonts.layout_no_wrap("Hello".to_owned(), small.clone(), Color32::WHITE);
+        }
+
+        assert!(
+            fonts.gc(2),
+            "Most glyphs are unused, so the atlas should be re-allocated"
+        );
+        assert!(fonts.font_atlas_fill_ratio() < fill_ratio_with_large);
+        assert_eq!(fonts.font_image_size()[1], INITIAL_ATLAS_HEIGHT);
+
+        // The glyphs still in use are still there:
+        let galley = fonts.layout_no_wrap("Hello".to_owned(), small, Color32::WHITE);
+        assert!(galley.rows[0].glyphs.iter().all(|g| g.uv_rect.size.x > 0.0));
+
+        // Nothing left to collect:
+        assert!(!fonts.gc(2));
+    }
+
+    #[test]
+    #[cfg(feature = "default_fonts")]
+    fn test_preload_text_from_other_thread() {
+        let fonts = Fonts::new(1.0, 1024, FontDefinitions::default());
+        let font_id = FontId::proportional(32.0);
+        let text = "The quick brown fox jumps over the lazy dog 🐱";
+
+        fonts.begin_frame(1.0, 1024);
+        let fill_ratio_before = fonts.font_atlas_fill_ratio();
+
+        std::thread::Builder::new()
+            .name("preload_text".to_owned())
+            .spawn({
+                let fonts = fonts.clone();
+                let font_id = font_id.clone();
+                move || fonts.preload_text(&font_id, text)
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+
+        let fill_ratio_preloaded = fonts.font_atlas_fill_ratio();
+        assert!(fill_ratio_before < fill_ratio_preloaded);
+
+        // Layout reuses the preloaded glyphs instead of rasterizing them again:
+        fonts.layout_no_wrap(text.to_owned(), font_id, Color32::WHITE);
+        assert_eq!(fonts.font_atlas_fill_ratio(), fill_ratio_preloaded);
+    }
+
+    #[test]
+    fn test_layout_from_many_threads() {
+        fn layout_all(fonts: &Fonts, texts: &[String]) -> Vec<Arc<Galley>> {
+            texts
+                .iter()
+                .map(|text| fonts.layout(text.clone(), FontId::default(), Color32::WHITE, 200.0))
+                .collect()
+        }
+
+        let texts: Arc<[String]> = (0..200)
+            .map(|i| format!("Log line {i}: the quick brown fox jumps over the lazy dog 🦊"))
+            .collect();
+
+        let single_threaded = {
+            let fonts = Fonts::new(1.0, 1024, FontDefinitions::default());
+            fonts.begin_frame(1.0, 1024);
+            layout_all(&fonts, &texts)
+        };
+
+        let fonts = Fonts::new(1.0, 1024, FontDefinitions::default());
+        fonts.begin_frame(1.0, 1024);
+        let threads: Vec<_> = (0..8)
+            .map(|i| {
+                std::thread::Builder::new()
+                    .name(format!("layout_{i}"))
+                    .spawn({
+                        let fonts = fonts.clone();
+                        let texts = texts.clone();
+                        move || layout_all(&fonts, &texts)
+                    })
+                    .unwrap()
+            })
+            .collect();
+        for thread in threads {
+            let galleys = thread.join().unwrap();
+            assert_eq!(galleys.len(), single_threaded.len());
+            for (galley, expected) in galleys.iter().zip(&single_threaded) {
+                assert_eq!(galley.rows, expected.rows);
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "default_fonts")]
+    fn test_layout_without_holding_lock() {
+        use std::sync::atomic::{AtomicUsize, Ordering::SeqCst};
+
+        // Each layout waits for the other thread to also be laying out,
+        // which only happens if the lock isn't held while laying out:
+        let num_laying_out = Arc::new(AtomicUsize::new(0));
+        let num_met = Arc::new(AtomicUsize::new(0));
+        let fonts = Fonts::new(1.0, 1024, FontDefinitions::default());
+        fonts.lock().galley_cache.on_layout = Some(Arc::new({
+            let num_laying_out = num_laying_out.clone();
+            let num_met = num_met.clone();
+            move || {
+                num_laying_out.fetch_add(1, SeqCst);
+                let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+                while std::time::Instant::now() < deadline {
+                    if num_laying_out.load(SeqCst) == 2 {
+                        num_met.fetch_add(1, SeqCst);
+                        return;
+                    }
+                    std::thread::yield_now();
+                }
+            }
+        }));
+
+        let threads: Vec<_> = ["left", "right"]
+            .into_iter()
+            .map(|text| {
+                let fonts = fonts.clone();
+                std::thread::Builder::new()
+                    .name(format!("layout_{text}"))
+                    .spawn(move || {
+                        fonts.layout_no_wrap(text.to_owned(), FontId::default(), Color32::WHITE);
+                    })
+                    .unwrap()
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        assert_eq!(num_laying_out.load(SeqCst), 2);
+        assert_eq!(
+            num_met.load(SeqCst),
+            2,
+            "the two threads should lay out at the same time"
+        );
+
+        let galley = fonts.layout_no_wrap("left".to_owned(), FontId::default(), Color32::WHITE);
+        assert_eq!(galley.text(), "left");
+        assert_eq!(fonts.num_galleys_in_cache(), 2);
+    }
+
+    #[test]
+    #[cfg(feature = "default_fonts")]
+    fn test_layout_each_paragraph() {
+        use std::fmt::Write as _;
+
+        let fonts = Fonts::new(1.0, 1024, FontDefinitions::default());
+        let job = |text: &str| {
+            let mut job = LayoutJob::default();
+            // A section that spans several paragraphs:
+            let split = text.find("wraps").unwrap_or_default();
+            job.append(
+                &text[..split],
+                8.0,
+                TextFormat::simple(FontId::proportional(14.0), Color32::WHITE),
+            );
+            job.append(
+                &text[split..],
+                0.0,
+                TextFormat::simple(FontId::monospace(18.0), Color32::RED),
+            );
+            job.wrap.max_width = 60.0;
+            job.paragraph_spacing = 3.0;
+            job
+        };
+        let mut text = String::new();
+        for i in 0..200 {
+            writeln!(text, "paragraph {i} wraps, since it is long\n").unwrap();
+        }
+        assert!(MIN_PARAGRAPH_JOB_LEN < text.len());
+
+        let num_laid_out = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        fonts.lock().galley_cache.on_layout = Some(Arc::new({
+            let num_laid_out = num_laid_out.clone();
+            move || {
+                num_laid_out.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            }
+        }));
+        let layout = |text: &str| {
+            num_laid_out.store(0, std::sync::atomic::Ordering::SeqCst);
+            let galley = fonts.layout_job(job(text));
+            let expected = crate::text::layout(&mut fonts.lock().fonts, job(text).into());
+            assert_eq!(galley.rows.len(), expected.rows.len());
+            for (row, expected) in galley.rows.iter().zip(&expected.rows) {
+                assert_eq!(row.rect, expected.rect);
+                assert_eq!(row.ends_with_newline, expected.ends_with_newline);
+                assert_eq!(row.section_index_at_start, expected.section_index_at_start);
+                assert_eq!(row.glyphs, expected.glyphs);
+            }
+            assert_eq!(galley.rect, expected.rect);
+            assert_eq!(galley.num_vertices, expected.num_vertices);
+            (
+                galley,
+                num_laid_out.load(std::sync::atomic::Ordering::SeqCst),
+            )
+        };
+
+        fonts.begin_frame(1.0, 1024);
+        let (galley, num_laid_out) = layout(&text);
+        assert_eq!(num_laid_out, 400);
+
+        // Editing one paragraph only lays out that paragraph again,
+        // and the rows of the other ones are shared rather than copied:
+        let edited = text.replace("paragraph 100 wraps", "paragraph 100 warps");
+        let (edited_galley, num_laid_out) = layout(&edited);
+        assert_eq!(num_laid_out, 1);
+        let shared_rows = galley
+            .rows
+            .iter()
+            .zip(&edited_galley.rows)
+            .filter(|(row, edited_row)| Arc::ptr_eq(&row.glyphs, &edited_row.glyphs))
+            .count();
+        assert!(galley.rows.len() - shared_rows < 10, "{shared_rows}");
+
+        // Even if that moves the paragraphs after it:
+        let edited = text.replace(
+            "paragraph 100 ",
+            "paragraph 100 is much longer, and wraps more ",
+        );
+        let (_, num_laid_out) = layout(&edited);
+        assert_eq!(num_laid_out, 1);
+
+        // The paragraphs are kept for as long as the whole job is used:
+        for _ in 0..3 {
+            fonts.begin_frame(1.0, 1024);
+            layout(&text);
+        }
+        let (_, num_laid_out) = layout(&text.replace("paragraph 7 wraps", "paragraph 7 warps"));
+        assert_eq!(num_laid_out, 1);
+
+        // Small jobs are laid out as a whole:
+        let num_galleys = fonts.num_galleys_in_cache();
+        let num_paragraphs = fonts.lock().galley_cache.paragraphs.len();
+        let (_, num_laid_out) = layout("first paragraph\n\nthe second paragraph wraps\nthird\n");
+        assert_eq!(num_laid_out, 1);
+        assert_eq!(fonts.num_galleys_in_cache(), num_galleys + 1);
+        assert_eq!(fonts.lock().galley_cache.paragraphs.len(), num_paragraphs);
+    }
+
+    #[test]
+    #[cfg(feature = "default_fonts")]
+    fn test_sdf_glyphs_are_shared_between_sizes() {
+        let mut definitions = FontDefinitions::default();
+        for font_data in definitions.font_data.values_mut() {
+            font_data.tweak.sdf = true;
+        }
+        let fonts = Fonts::new(1.0, 1024, definitions);
+        fonts.begin_frame(1.0, 1024);
+
+        let small =
+            fonts.layout_no_wrap("A".to_owned(), FontId::proportional(14.0), Color32::WHITE);
+        let large =
+            fonts.layout_no_wrap("A".to_owned(), FontId::proportional(28.0), Color32::WHITE);
+        let small = small.rows[0].glyphs[0].uv_rect;
+        let large = large.rows[0].glyphs[0].uv_rect;
+
+        assert!(small.is_sdf && large.is_sdf);
+        assert_eq!((small.min, small.max), (large.min, large.max));
+        let ratio = large.size / small.size;
+        assert!((ratio.x - 2.0).abs() < 0.1, "{ratio:?}"); // font sizes are rounded to whole pixels
+        assert!((ratio.x - ratio.y).abs() < 1e-4);
+
+        // Only the SDF atlas is used:
+        let sdf_delta = fonts.sdf_font_image_delta().unwrap();
+        assert!(sdf_delta.options.sdf);
+        assert!(fonts.sdf_font_image_delta().is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "default_fonts")]
+    fn test_sdf_and_plain_glyphs_of_the_same_font_are_stored_apart() {
+        let mut definitions = FontDefinitions::default();
+        let mut sdf_font_data = definitions.font_data["Hack"].clone();
+        sdf_font_data.tweak.sdf = true;
+        definitions
+            .font_data
+            .insert("Hack SDF".to_owned(), sdf_font_data);
+        let sdf_family = FontFamily::Name("sdf".into());
+        definitions
+            .families
+            .insert(sdf_family.clone(), vec!["Hack SDF".to_owned()]);
+        let fonts = Fonts::new(1.0, 1024, definitions);
+        fonts.begin_frame(1.0, 1024);
+
+        let uv_rect = |family: FontFamily| {
+            let galley =
+                fonts.layout_no_wrap("A".to_owned(), FontId::new(14.0, family), Color32::WHITE);
+            galley.rows[0].glyphs[0].uv_rect
+        };
+        assert!(!uv_rect(FontFamily::Monospace).is_sdf);
+        assert!(uv_rect(sdf_family).is_sdf);
+    }
+
+    #[test]
+    fn test_galley_cache() {
+        let fonts = Fonts::new(1.0, 1024, FontDefinitions::default());
+        let layout = || fonts.layout_no_wrap("Hello".to_owned(), FontId::default(), Color32::WHITE);
+
+        fonts.begin_frame(1.0, 1024);
+        let galley = layout();
+        assert!(Arc::ptr_eq(&galley, &layout()));
+
+        // By default, galleys are thrown out if they weren't used in the last frame:
+        fonts.begin_frame(1.0, 1024);
+        assert_eq!(fonts.num_galleys_in_cache(), 1);
+        fonts.begin_frame(1.0, 1024);
+        assert_eq!(fonts.num_galleys_in_cache(), 0);
+        assert!(!Arc::ptr_eq(&galley, &layout()));
+
+        fonts.set_galley_cache_max_unused_frames(3);
+        let galley = layout();
+        for _ in 0..3 {
+            fonts.begin_frame(1.0, 1024);
+        }
+        assert!(Arc::ptr_eq(&galley, &layout()));
+        for _ in 0..4 {
+            fonts.begin_frame(1.0, 1024);
+        }
+        assert_eq!(fonts.num_galleys_in_cache(), 0);
+
+        // Galleys laid out with another `pixels_per_point` are never reused:
+        let galley = layout();
+        fonts.begin_frame(2.0, 1024);
+        assert_eq!(fonts.num_galleys_in_cache(), 0);
+        assert_eq!(layout().pixels_per_point, 2.0);
+        assert!(!Arc::ptr_eq(&galley, &layout()));
+
+        // …but the setting survives:
+        let galley = layout();
+        fonts.begin_frame(2.0, 1024);
+        fonts.begin_frame(2.0, 1024);
+        assert!(Arc::ptr_eq(&galley, &layout()));
+    }
+
+    #[test]
+    fn test_estimate_layout_size() {
+        let fonts = Fonts::new(1.0, 1024, FontDefinitions::default());
+        fonts.begin_frame(1.0, 1024);
+
+        // Exact for monospace text without wrapping:
+        let job = LayoutJob::simple(
+            "Hello\nworld!\n\nlast line".to_owned(),
+            FontId::monospace(14.0),
+            Color32::WHITE,
+            f32::INFINITY,
+        );
+        let estimate = fonts.estimate_layout_size(&job);
+        let size = fonts.layout_job(job).size();
+        assert!((estimate - size).length() < 1.0, "{estimate:?} vs {size:?}");
+
+        // Roughly right for wrapped text:
+        let job = LayoutJob::simple(
+            "The quick brown fox jumps over the lazy dog. ".repeat(20),
+            FontId::proportional(14.0),
+            Color32::WHITE,
+            200.0,
+        );
+        let estimate = fonts.estimate_layout_size(&job);
+        let size = fonts.layout_job(job).size();
+        assert_eq!(estimate.x, 200.0);
+        assert!(
+            (estimate.y / size.y - 1.0).abs() < 0.5,
+            "{estimate:?} vs {size:?}"
+        );
+    }
+
+    #[test]
+    fn test_glyphs_are_reused_when_pixels_per_point_changes_back() {
+        let fonts = Fonts::new(1.0, 1024, FontDefinitions::default());
+        let layout = || {
+            let galley =
+                fonts.layout_no_wrap("Hello".to_owned(), FontId::default(), Color32::WHITE);
+            galley.rows[0].glyphs[0].uv_rect
+        };
+
+        fonts.begin_frame(1.0, 1024);
+        let before = layout();
+        let fill_ratio = fonts.font_atlas_fill_ratio();
+
+        fonts.begin_frame(2.0, 1024);
+        assert_ne!(layout(), before);
+        let fill_ratio_with_both = fonts.font_atlas_fill_ratio();
+        assert!(fill_ratio < fill_ratio_with_both);
+
+        fonts.begin_frame(1.0, 1024);
+        assert_eq!(layout(), before);
+        assert_eq!(fonts.font_atlas_fill_ratio(), fill_ratio_with_both);
+    }
+
+    #[test]
+    fn test_mixed_script_document_atlas_usage() {
+        let fonts = Fonts::new(1.0, 1024, FontDefinitions::default());
+        fonts.begin_frame(1.0, 1024);
+        let fill_ratio_before = fonts.font_atlas_fill_ratio();
+
+        // Whitespace has nothing to rasterize, so it shouldn't touch the atlas:
+        fonts.layout_no_wrap(
+            " \u{2002}\u{2003} ".to_owned(),
+            FontId::default(),
+            Color32::WHITE,
+        );
+        assert_eq!(fonts.font_atlas_fill_ratio(), fill_ratio_before);
+
+        let document = "The quick brown fox jumps over the lazy dog. \
+            Ξεσκεπάζω την ψυχοφθόρα βδελυγμία. \
+            Съешь же ещё этих мягких французских булок, да выпей чаю. \
+            ,.;:'\"!?-_·•… 0123456789 ±×÷ 😀🎉 ";
+        for size in [10.0, 14.0, 20.0, 32.0] {
+            let galley = fonts.layout_no_wrap(
+                document.to_owned(),
+                FontId::proportional(size),
+                Color32::WHITE,
+            );
+            for glyph in galley.rows[0].glyphs.iter() {
+                if glyph.chr.is_whitespace() {
+                    assert!(glyph.uv_rect.is_nothing(), "{:?}", glyph.chr);
+                }
+            }
+        }
+        let fill_ratio = fonts.font_atlas_fill_ratio();
+        assert!(fill_ratio < 0.2, "{fill_ratio}");
+    }
+
+    #[test]
+    fn test_atlas_glyphs() {
+        let fonts = Fonts::new(1.0, 1024, FontDefinitions::default());
+        fonts.begin_frame(1.0, 1024);
+        fonts.layout_no_wrap("Hi".to_owned(), FontId::proportional(20.0), Color32::WHITE);
+
+        let glyphs = fonts.atlas_glyphs();
+        let h = glyphs.iter().find(|glyph| glyph.chr == Some('H')).unwrap();
+        assert_eq!(h.font_name, "Ubuntu-Light");
+        assert!(20 <= h.scale_in_pixels); // depending on the `FontTweak::scale`
+        let [width, height] = fonts.font_image_size();
+        assert!(h.min[0] < h.max[0] && usize::from(h.max[0]) <= width);
+        assert!(h.min[1] < h.max[1] && usize::from(h.max[1]) <= height);
+
+        // Whitespace isn't in the atlas:
+        fonts.layout_no_wrap(" ".to_owned(), FontId::proportional(20.0), Color32::WHITE);
+        assert_eq!(fonts.atlas_glyphs().len(), glyphs.len());
+    }
+
+    #[test]
+    fn test_new_sharing_atlas() {
+        let fonts = Fonts::new(1.0, 1024, FontDefinitions::default());
+        let other = fonts.new_sharing_atlas(2.0);
+        assert_eq!(other.pixels_per_point(), 2.0);
+
+        let galley = other.layout_no_wrap("A".to_owned(), FontId::default(), Color32::WHITE);
+        assert!(!galley.rows[0].glyphs.is_empty());
+        assert!(fonts.font_image_delta().is_some());
+        assert!(other.font_image_delta().is_none()); // The same atlas
+    }
+
+    #[test]
+    fn test_row_meta() {
+        let fonts = Fonts::new(1.0, 1024, FontDefinitions::default());
+        let font_id = FontId::monospace(12.0);
+        let char_width = fonts.glyph_width(&font_id, 'x');
+
+        // A wrapped paragraph, an empty line, and a trailing newline:
+        let text = "one two three\n\nfour\n";
+        let galley = fonts.layout(text.to_owned(), font_id, Color32::WHITE, 8.5 * char_width);
+        let rows: Vec<String> = galley.rows.iter().map(|row| row.text()).collect();
+        assert_eq!(rows, ["one two ", "three", "", "four", ""]);
+
+        let metas: Vec<_> = galley.row_metas().collect();
+        assert_eq!(metas.len(), galley.rows.len());
+        let char_ranges: Vec<_> = metas.iter().map(|meta| meta.char_range.clone()).collect();
+        assert_eq!(char_ranges, [0..8, 8..13, 14..14, 15..19, 20..20]);
+        let paragraphs: Vec<usize> = metas.iter().map(|meta| meta.paragraph).collect();
+        assert_eq!(paragraphs, [0, 0, 1, 2, 3]);
+        let hard_breaks: Vec<bool> = metas.iter().map(|meta| meta.ends_with_newline).collect();
+        assert_eq!(hard_breaks, [false, true, true, true, false]);
+
+        for (row_idx, row) in galley.rows.iter().enumerate() {
+            assert_eq!(galley.row_meta(row_idx).y_range, row.rect.y_range());
+        }
+        assert!(metas
+            .windows(2)
+            .all(|w| w[0].y_range.max <= w[1].y_range.min));
+
+        // The rows follow the galley when it is scaled:
+        let mut shape = crate::Shape::galley(emath::Pos2::ZERO, galley.clone(), Color32::WHITE);
+        shape.scale(2.0);
+        let crate::Shape::Text(text_shape) = shape else {
+            panic!("expected a text shape");
+        };
+        let y_range = metas[1].y_range;
+        assert_eq!(
+            text_shape.galley.row_meta(1).y_range,
+            emath::Rangef::new(2.0 * y_range.min, 2.0 * y_range.max)
+        );
+    }
 }
@@ -3,14 +3,18 @@
 pub mod cursor;
 mod font;
 mod fonts;
+mod sdf;
 mod text_layout;
 mod text_layout_types;
 
-/// One `\t` character is this many spaces wide.
+/// By default, one `\t` character is this many spaces wide. See [`LayoutJob::tab_width`].
 pub const TAB_SIZE: usize = 4;
 
 pub use {
-    fonts::{FontData, FontDefinitions, FontFamily, FontId, FontTweak, Fonts, FontsImpl},
+    font::{AtlasGlyph, GlyphOutline, GlyphOutlineCurve, LineMetrics},
+    fonts::{
+        FauxStyle, FontData, FontDefinitions, FontFamily, FontId, FontTweak, Fonts, FontsImpl,
+    },
     text_layout::layout,
     text_layout_types::*,
 };
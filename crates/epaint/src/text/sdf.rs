@@ -0,0 +1,181 @@
+//! Signed distance field (SDF) glyphs, see [`crate::text::FontTweak::sdf`].
+
+use std::sync::Arc;
+
+use emath::Vec2;
+
+use crate::{
+    textures::TextureOptions, Color32, ColorImage, FontImage, ImageData, ImageDelta, TextureAtlas,
+};
+
+/// SDF glyphs are rasterized once, at this size, and then scaled to whatever size they are painted at.
+pub const SDF_REFERENCE_SCALE_IN_PIXELS: f32 = 48.0;
+
+/// How far the distance field reaches outside and inside of the glyph outline,
+/// in pixels at [`SDF_REFERENCE_SCALE_IN_PIXELS`].
+///
+/// A value of `0.5` in the field is on the outline,
+/// and `0.0` and `1.0` are this far outside and inside of it, respectively.
+pub const SDF_SPREAD_IN_PIXELS: usize = 6;
+
+/// An SDF glyph in the [`SdfAtlas`].
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct SdfGlyph {
+    /// From the baseline origin of the glyph to the top left of the field,
+    /// in pixels at [`SDF_REFERENCE_SCALE_IN_PIXELS`].
+    pub offset: Vec2,
+
+    /// Size of the field, in pixels at [`SDF_REFERENCE_SCALE_IN_PIXELS`].
+    pub size: Vec2,
+
+    /// Top left corner in the atlas.
+    pub min: [u16; 2],
+
+    /// Bottom right corner in the atlas (exclusive).
+    pub max: [u16; 2],
+}
+
+/// The texture atlas of all SDF glyphs.
+///
+/// Since the glyphs are rasterized at a fixed size,
+/// this is shared between all sizes of the same font.
+pub struct SdfAtlas {
+    atlas: TextureAtlas,
+
+    /// Keyed on font name, glyph id, and [`crate::text::FauxStyle`] (as bits).
+    glyphs: ahash::HashMap<(String, u16, [u32; 2]), SdfGlyph>,
+}
+
+impl SdfAtlas {
+    pub fn new(atlas: TextureAtlas) -> Self {
+        Self {
+            atlas,
+            glyphs: Default::default(),
+        }
+    }
+
+    /// The texture options for the SDF font texture.
+    ///
+    /// The backend should paint textures with [`TextureOptions::sdf`] by
+    /// thresholding the alpha channel at `0.5`, e.g. with a `smoothstep`.
+    #[inline]
+    pub fn texture_options() -> TextureOptions {
+        TextureOptions {
+            sdf: true,
+            ..TextureOptions::LINEAR
+        }
+    }
+
+    pub fn size(&self) -> [usize; 2] {
+        self.atlas.size()
+    }
+
+    /// When this get high, it might be time to clear and start over!
+    pub fn fill_ratio(&self) -> f32 {
+        self.atlas.fill_ratio()
+    }
+
+    pub(crate) fn glyph(&self, key: &(String, u16, [u32; 2])) -> Option<SdfGlyph> {
+        self.glyphs.get(key).copied()
+    }
+
+    /// Put a distance field (see [`distance_field`]) into the atlas.
+    pub(crate) fn allocate(
+        &mut self,
+        key: (String, u16, [u32; 2]),
+        offset: Vec2,
+        field: &FontImage,
+    ) -> SdfGlyph {
+        let [w, h] = field.size;
+        let (pos, image) = self.atlas.allocate((w, h));
+        for y in 0..h {
+            for x in 0..w {
+                image[(pos.0 + x, pos.1 + y)] = field[(x, y)];
+            }
+        }
+        let glyph = SdfGlyph {
+            offset,
+            size: Vec2::new(w as f32, h as f32),
+            min: [pos.0 as u16, pos.1 as u16],
+            max: [(pos.0 + w) as u16, (pos.1 + h) as u16],
+        };
+        self.glyphs.insert(key, glyph);
+        glyph
+    }
+
+    /// Call to get the change to the image since last call.
+    ///
+    /// Returns `None` until the first glyph has been allocated,
+    /// so that no texture is needed unless some font uses SDF glyphs.
+    ///
+    /// Unlike the normal font texture, the values are uploaded as-is (without any gamma),
+    /// in the alpha channel.
+    pub fn take_delta(&mut self) -> Option<ImageDelta> {
+        if self.glyphs.is_empty() {
+            return None;
+        }
+        let mut delta = self.atlas.take_delta()?;
+        delta.options = Self::texture_options();
+        if let ImageData::Font(image) = &delta.image {
+            delta.image = ImageData::Color(Arc::new(to_color_image(image)));
+        }
+        Some(delta)
+    }
+
+    /// The whole SDF image, as a delta that replaces the full texture.
+    pub fn full_delta(&self) -> ImageDelta {
+        ImageDelta::full(to_color_image(self.atlas.image()), Self::texture_options())
+    }
+}
+
+fn to_color_image(field: &FontImage) -> ColorImage {
+    let pixels = field
+        .pixels
+        .iter()
+        .map(|&distance| Color32::from_white_alpha((distance * 255.0 + 0.5) as u8))
+        .collect();
+    ColorImage {
+        size: field.size,
+        pixels,
+    }
+}
+
+/// Turn a coverage image into a signed distance field.
+///
+/// See [`SDF_SPREAD_IN_PIXELS`].
+pub(crate) fn distance_field(coverage: &FontImage) -> FontImage {
+    let [w, h] = coverage.size;
+    let spread = SDF_SPREAD_IN_PIXELS as i32;
+
+    let is_inside = |x: i32, y: i32| {
+        0 <= x
+            && x < w as i32
+            && 0 <= y
+            && y < h as i32
+            && 0.5 <= coverage[(x as usize, y as usize)]
+    };
+
+    let mut field = FontImage::new(coverage.size);
+    for y in 0..h as i32 {
+        for x in 0..w as i32 {
+            let inside = is_inside(x, y);
+
+            // Find the closest texel on the other side of the outline:
+            let mut closest_sq = (2 * spread * spread) as f32;
+            for dy in -spread..=spread {
+                for dx in -spread..=spread {
+                    if is_inside(x + dx, y + dy) != inside {
+                        closest_sq = closest_sq.min((dx * dx + dy * dy) as f32);
+                    }
+                }
+            }
+
+            // The outline goes halfway between the two texels:
+            let distance = closest_sq.sqrt() - 0.5;
+            let signed_distance = if inside { distance } else { -distance };
+            field[(x as usize, y as usize)] =
+                (0.5 + 0.5 * signed_distance / spread as f32).clamp(0.0, 1.0);
+        }
+    }
+    field
+}
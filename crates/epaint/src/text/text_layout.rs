@@ -1,11 +1,18 @@
-use std::ops::RangeInclusive;
+use std::ops::{Range, RangeInclusive};
 use std::sync::Arc;
 
 use emath::*;
 
-use crate::{stroke::PathStroke, text::font::Font, Color32, Mesh, Stroke, Vertex};
+use crate::{
+    stroke::PathStroke,
+    text::font::{Font, GlyphInfo, LineMetrics},
+    Color32, Mesh, Rounding, Stroke, Vertex,
+};
 
-use super::{FontsImpl, Galley, Glyph, LayoutJob, LayoutSection, Row, RowVisuals};
+use super::{
+    fonts::LayoutFonts, FontsImpl, Galley, Glyph, LayoutJob, LayoutSection, NoBreakClasses, Row,
+    RowVisuals, TextFormat,
+};
 
 // ----------------------------------------------------------------------------
 
@@ -30,11 +37,6 @@ impl PointScale {
     pub fn round_to_pixel(&self, point: f32) -> f32 {
         (point * self.pixels_per_point).round() / self.pixels_per_point
     }
-
-    #[inline(always)]
-    pub fn floor_to_pixel(&self, point: f32) -> f32 {
-        (point * self.pixels_per_point).floor() / self.pixels_per_point
-    }
 }
 
 // ----------------------------------------------------------------------------
@@ -59,7 +61,7 @@ impl Paragraph {
         Self {
             cursor_x: 0.0,
             section_index_at_start,
-            glyphs: vec![],
+            glyphs: Default::default(),
             empty_paragraph_height: 0.0,
         }
     }
@@ -70,6 +72,13 @@ impl Paragraph {
 /// In most cases you should use [`crate::Fonts::layout_job`] instead
 /// since that memoizes the input, making subsequent layouting of the same text much faster.
 pub fn layout(fonts: &mut FontsImpl, job: Arc<LayoutJob>) -> Galley {
+    let fonts = fonts.layout_fonts(&job);
+    layout_with_fonts(&fonts, job)
+}
+
+/// Like [`layout`], but with the fonts of the job already fetched,
+/// so that no lock on the [`crate::Fonts`] needs to be held.
+pub(crate) fn layout_with_fonts(fonts: &LayoutFonts, job: Arc<LayoutJob>) -> Galley {
     if job.wrap.max_rows == 0 {
         // Early-out: no text
         return Galley {
@@ -84,6 +93,10 @@ pub fn layout(fonts: &mut FontsImpl, job: Arc<LayoutJob>) -> Galley {
         };
     }
 
+    if job.vertical {
+        return layout_vertical(fonts, job);
+    }
+
     // For most of this we ignore the y coordinate:
 
     let mut paragraphs = vec![Paragraph::from_section_index(0)];
@@ -94,7 +107,7 @@ pub fn layout(fonts: &mut FontsImpl, job: Arc<LayoutJob>) -> Galley {
     let point_scale = PointScale::new(fonts.pixels_per_point());
 
     let mut elided = false;
-    let mut rows = rows_from_paragraphs(paragraphs, &job, &mut elided);
+    let mut rows = rows_from_paragraphs(fonts, paragraphs, &job, &mut elided);
     if elided {
         if let Some(last_row) = rows.last_mut() {
             replace_last_glyph_with_overflow_character(fonts, &job, last_row);
@@ -118,13 +131,18 @@ pub fn layout(fonts: &mut FontsImpl, job: Arc<LayoutJob>) -> Galley {
         }
     }
 
+    reorder_bidi_rows(fonts, &job, &mut rows);
+
+    select_subpixel_glyphs(fonts, &job, &mut rows);
+
     // Calculate the Y positions and tessellate the text:
-    galley_from_rows(point_scale, job, rows, elided)
+    let format_summary = format_summary(fonts, &job);
+    galley_from_rows(point_scale, job, &format_summary, rows, elided)
 }
 
 // Ignores the Y coordinate.
 fn layout_section(
-    fonts: &mut FontsImpl,
+    fonts: &LayoutFonts,
     job: &LayoutJob,
     section_index: u32,
     section: &LayoutSection,
@@ -135,48 +153,615 @@ fn layout_section(
         byte_range,
         format,
     } = section;
-    let font = fonts.font(&format.font_id);
+    let font = fonts.format_font(format);
     let line_height = section
         .format
         .line_height
         .unwrap_or_else(|| font.row_height());
-    let extra_letter_spacing = section.format.extra_letter_spacing;
-
-    let mut paragraph = out_paragraphs.last_mut().unwrap();
+    let paragraph = out_paragraphs.last_mut().unwrap();
     if paragraph.glyphs.is_empty() {
         paragraph.empty_paragraph_height = line_height; // TODO(emilk): replace this hack with actually including `\n` in the glyphs?
     }
 
     paragraph.cursor_x += leading_space;
 
-    let mut last_glyph_id = None;
+    let text = &job.text[byte_range.clone()];
+
+    #[cfg(feature = "shaping")]
+    layout_section_shaped(font, job, section_index, format, text, out_paragraphs);
+
+    #[cfg(not(feature = "shaping"))]
+    {
+        use unicode_segmentation::UnicodeSegmentation as _;
+
+        let mut last_glyph_id = None;
+
+        for grapheme in text.graphemes(true) {
+            let is_single_char = grapheme.chars().nth(1).is_none();
+            if !is_single_char && !grapheme.contains('\n') {
+                let paragraph = out_paragraphs.last_mut().unwrap();
+                layout_grapheme(
+                    font,
+                    job,
+                    format,
+                    section_index,
+                    paragraph,
+                    grapheme,
+                    &mut last_glyph_id,
+                );
+                continue;
+            }
 
-    for chr in job.text[byte_range.clone()].chars() {
-        if job.break_on_newline && chr == '\n' {
-            out_paragraphs.push(Paragraph::from_section_index(section_index));
-            paragraph = out_paragraphs.last_mut().unwrap();
-            paragraph.empty_paragraph_height = line_height; // TODO(emilk): replace this hack with actually including `\n` in the glyphs?
-        } else {
-            let (font_impl, glyph_info) = font.font_impl_and_glyph_info(chr);
-            if let Some(font_impl) = font_impl {
-                if let Some(last_glyph_id) = last_glyph_id {
-                    paragraph.cursor_x += font_impl.pair_kerning(last_glyph_id, glyph_info.id);
-                    paragraph.cursor_x += extra_letter_spacing;
+            for chr in grapheme.chars() {
+                if job.break_on_newline && chr == '\n' {
+                    out_paragraphs.push(Paragraph::from_section_index(section_index));
+                    let paragraph = out_paragraphs.last_mut().unwrap();
+                    paragraph.empty_paragraph_height = line_height; // TODO(emilk): replace this hack with actually including `\n` in the glyphs?
+                } else {
+                    let paragraph = out_paragraphs.last_mut().unwrap();
+                    layout_glyph(
+                        font,
+                        job,
+                        format,
+                        section_index,
+                        paragraph,
+                        chr,
+                        &mut last_glyph_id,
+                    );
                 }
             }
+        }
+    }
+}
+
+/// Add a grapheme cluster of several characters to the paragraph, without shaping,
+/// e.g. a letter with combining accents, or an emoji ZWJ sequence.
+///
+/// There is still one [`Glyph`] per `char`, so that cursors keep counting `char`s,
+/// but only the first one of them advances the cursor.
+#[cfg(not(feature = "shaping"))]
+fn layout_grapheme(
+    font: &Font,
+    job: &LayoutJob,
+    format: &TextFormat,
+    section_index: u32,
+    paragraph: &mut Paragraph,
+    grapheme: &str,
+    last_glyph_id: &mut Option<ab_glyph::GlyphId>,
+) {
+    use unicode_normalization::{char::canonical_combining_class, UnicodeNormalization as _};
+
+    let mut chars = grapheme.chars();
+    let first_chr = chars.next().unwrap();
+    let base_index = paragraph.glyphs.len();
+
+    // Prefer a precomposed glyph, e.g. `é` for `e` + U+0301:
+    let mut composed = grapheme.nfc();
+    if let (Some(composed_chr), None) = (composed.next(), composed.next()) {
+        if font.has_glyph(composed_chr) {
+            layout_glyph(
+                font,
+                job,
+                format,
+                section_index,
+                paragraph,
+                composed_chr,
+                last_glyph_id,
+            );
+            paragraph.glyphs[base_index].chr = first_chr;
+            let base = paragraph.glyphs[base_index];
+            for chr in chars {
+                paragraph
+                    .glyphs
+                    .push(invisible_glyph(&base, paragraph.cursor_x, chr));
+            }
+            return;
+        }
+    }
+
+    layout_glyph(
+        font,
+        job,
+        format,
+        section_index,
+        paragraph,
+        first_chr,
+        last_glyph_id,
+    );
+    let base = paragraph.glyphs[base_index];
+
+    // The vertical extent of the ink so far, so that we can stack marks on top of each other:
+    let mut ink_y = if base.uv_rect.is_nothing() {
+        Rangef::new(-base.ascent, 0.0)
+    } else {
+        Rangef::new(
+            base.uv_rect.offset.y,
+            base.uv_rect.offset.y + base.uv_rect.size.y,
+        )
+    };
+
+    let is_zwj_sequence = grapheme.contains(ZERO_WIDTH_JOINER);
+
+    for chr in chars {
+        if is_zwj_sequence || is_invisible_in_cluster(chr) {
+            // We can't find the glyph for a ZWJ sequence without shaping, so we only show its first emoji.
+            paragraph
+                .glyphs
+                .push(invisible_glyph(&base, paragraph.cursor_x, chr));
+        } else if canonical_combining_class(chr) != 0 {
+            let glyph = nonspacing_mark_glyph(font, &base, paragraph.cursor_x, chr, &mut ink_y);
+            paragraph.glyphs.push(glyph);
+        } else {
+            // E.g. a spacing mark, which takes up room of its own:
+            layout_glyph(
+                font,
+                job,
+                format,
+                section_index,
+                paragraph,
+                chr,
+                last_glyph_id,
+            );
+        }
+    }
+}
+
+const ZERO_WIDTH_JOINER: char = '\u{200D}';
+
+/// Characters that only modify how the rest of their grapheme cluster is displayed.
+fn is_invisible_in_cluster(chr: char) -> bool {
+    matches!(
+        chr,
+        '\u{200C}' | ZERO_WIDTH_JOINER | '\u{FE00}'..='\u{FE0F}' | '\u{E0100}'..='\u{E01EF}'
+    )
+}
+
+/// Does this character belong to the same grapheme cluster as the one before it?
+///
+/// Not exact, but good enough to avoid breaking rows in the middle of a cluster.
+fn continues_cluster(chr: char) -> bool {
+    is_invisible_in_cluster(chr) || unicode_normalization::char::is_combining_mark(chr)
+}
+
+/// A glyph that takes up no space and is not painted, placed at `x`.
+#[cfg(not(feature = "shaping"))]
+fn invisible_glyph(base: &Glyph, x: f32, chr: char) -> Glyph {
+    Glyph {
+        chr,
+        pos: pos2(x, f32::NAN),
+        size: vec2(0.0, base.size.y),
+        uv_rect: Default::default(),
+        kerning: 0.0,
+        ..*base
+    }
+}
+
+/// A combining mark that takes up no space, placed at `x` (the end of the `base` glyph),
+/// but painted centered over the `base` glyph.
+///
+/// Marks above (or below) the baseline are moved up (or down) to not overlap with `ink_y`,
+/// which is then extended to include the mark.
+#[cfg(not(feature = "shaping"))]
+fn nonspacing_mark_glyph(
+    font: &Font,
+    base: &Glyph,
+    x: f32,
+    chr: char,
+    ink_y: &mut Rangef,
+) -> Glyph {
+    let mut uv_rect = font.font_impl_and_glyph_info(chr).1.uv_rect;
+
+    if !uv_rect.is_nothing() {
+        // Center the mark over the ink of the base glyph, or over its advance if it has no ink:
+        let (base_left, base_width) = if base.uv_rect.is_nothing() {
+            (base.pos.x, base.size.x)
+        } else {
+            (base.pos.x + base.uv_rect.offset.x, base.uv_rect.size.x)
+        };
+        uv_rect.offset.x = base_left + 0.5 * (base_width - uv_rect.size.x) - x;
+
+        let gap = 0.1 * uv_rect.size.y;
+        let top = uv_rect.offset.y;
+        let bottom = top + uv_rect.size.y;
+        if bottom <= 0.0 {
+            // Above the baseline:
+            uv_rect.offset.y -= (bottom - (ink_y.min - gap)).max(0.0);
+            ink_y.min = uv_rect.offset.y;
+        } else if 0.0 <= top {
+            // Below the baseline:
+            uv_rect.offset.y += ((ink_y.max + gap) - top).max(0.0);
+            ink_y.max = uv_rect.offset.y + uv_rect.size.y;
+        }
+    }
+
+    Glyph {
+        uv_rect,
+        ..invisible_glyph(base, x, chr)
+    }
+}
+
+/// Add a single character to the paragraph, without shaping.
+fn layout_glyph(
+    font: &Font,
+    job: &LayoutJob,
+    format: &TextFormat,
+    section_index: u32,
+    paragraph: &mut Paragraph,
+    chr: char,
+    last_glyph_id: &mut Option<ab_glyph::GlyphId>,
+) {
+    let line_height = format.line_height.unwrap_or_else(|| font.row_height());
+
+    let space_width = if chr == '\t' {
+        font.glyph_width(' ')
+    } else {
+        0.0
+    };
+
+    let (font_impl, mut glyph_info) = font.font_impl_and_glyph_info(chr);
+    if let Some(substituted) =
+        font_impl.and_then(|font_impl| font_impl.substituted_glyph_info(chr, &format.font_features))
+    {
+        glyph_info = substituted;
+    }
+    let mut kerning = 0.0;
+    if let Some(font_impl) = font_impl {
+        if let Some(last_glyph_id) = *last_glyph_id {
+            if format.kerning {
+                kerning = font_impl.pair_kerning(last_glyph_id, glyph_info.id);
+            }
+            paragraph.cursor_x += kerning;
+            paragraph.cursor_x += format.extra_letter_spacing;
+        }
+    }
+
+    if chr == '\t' {
+        glyph_info.advance_width = tab_advance(job, space_width, paragraph.cursor_x);
+    } else if chr == SOFT_HYPHEN {
+        // Only shown if we break the row there, as `Row::hyphen`:
+        glyph_info.advance_width = 0.0;
+        glyph_info.uv_rect = Default::default();
+    }
+
+    paragraph.glyphs.push(Glyph {
+        chr,
+        pos: pos2(paragraph.cursor_x, f32::NAN),
+        size: vec2(glyph_info.advance_width, line_height),
+        ascent: font_impl.map_or(0.0, |font| font.ascent()), // Failure to find the font here would be weird
+        uv_rect: glyph_info.uv_rect,
+        kerning,
+        section_index,
+        bidi_level: 0,
+    });
+
+    paragraph.cursor_x += glyph_info.advance_width;
+    if !font_impl.is_some_and(|font_impl| font_impl.subpixel_positioning()) {
+        paragraph.cursor_x = font.round_to_pixel(paragraph.cursor_x);
+    }
+    *last_glyph_id = Some(glyph_info.id);
+}
+
+/// How far a tab at `cursor_x` advances.
+///
+/// See [`LayoutJob::tab_width`] and [`LayoutJob::tab_stop_width_in_columns`].
+fn tab_advance(job: &LayoutJob, space_width: f32, cursor_x: f32) -> f32 {
+    if let Some(columns) = job.tab_stop_width_in_columns {
+        if columns == 0 || space_width <= 0.0 {
+            return 0.0;
+        }
+        let columns = columns as f32;
+        // Round to the closest column, since pixel-rounding makes the glyphs drift slightly:
+        let column = (cursor_x / space_width).round();
+        let next_tab_stop = ((column / columns).floor() + 1.0) * columns;
+        next_tab_stop * space_width - cursor_x
+    } else {
+        job.tab_width as f32 * space_width
+    }
+}
+
+/// Lay out [`LayoutJob::vertical`] text, with one column (i.e. [`Row`]) per paragraph, going right-to-left.
+fn layout_vertical(fonts: &LayoutFonts, job: Arc<LayoutJob>) -> Galley {
+    let point_scale = PointScale::new(fonts.pixels_per_point());
+
+    let new_column = |section_index_at_start: u32| Row {
+        section_index_at_start,
+        glyphs: Default::default(),
+        rect: Rect::from_min_max(Pos2::ZERO, Pos2::ZERO),
+        baseline: 0.0,
+        visuals: Default::default(),
+        ends_with_newline: false,
+        hyphen: None,
+    };
+
+    // First stack the glyphs of each column, keeping track of
+    // the width of the column in `rect.max.x` and the cursor in `rect.max.y`:
+    let mut columns = vec![new_column(0)];
+    for (section_index, section) in job.sections.iter().enumerate() {
+        let section_index = section_index as u32;
+        let format = &section.format;
+        let font = fonts.format_font(format);
+        let line_height = format.line_height.unwrap_or_else(|| font.row_height());
+
+        let column = columns.last_mut().unwrap();
+        column.rect.max.x = column.rect.max.x.max(line_height);
+        column.rect.max.y += section.leading_space;
+
+        for chr in job.text[section.byte_range.clone()].chars() {
+            if job.break_on_newline && chr == '\n' {
+                columns.last_mut().unwrap().ends_with_newline = true;
+                let mut column = new_column(section_index);
+                column.rect.max.x = line_height;
+                columns.push(column);
+                continue;
+            }
+
+            let column = columns.last_mut().unwrap();
+            if !column.glyphs.is_empty() {
+                column.rect.max.y += format.extra_letter_spacing;
+            }
+            let cursor_y = column.rect.max.y;
+
+            let space_width = if chr == '\t' {
+                font.glyph_width(' ')
+            } else {
+                0.0
+            };
+
+            let (font_impl, glyph_info) = font.font_impl_and_glyph_info(chr);
+            let ascent = font_impl.map_or(0.0, |font_impl| font_impl.ascent());
+
+            let size = if is_upright_in_vertical_text(chr) {
+                let advance = font_impl
+                    .and_then(|font_impl| font_impl.vertical_advance(glyph_info.id))
+                    .unwrap_or(line_height);
+                vec2(glyph_info.advance_width, advance)
+            } else {
+                // Rotated 90° clockwise, so the advance goes downwards:
+                let advance = if chr == '\t' {
+                    tab_advance(&job, space_width, cursor_y)
+                } else {
+                    glyph_info.advance_width
+                };
+                vec2(line_height, advance)
+            };
 
-            paragraph.glyphs.push(Glyph {
+            Arc::make_mut(&mut column.glyphs).push(Glyph {
                 chr,
-                pos: pos2(paragraph.cursor_x, f32::NAN),
-                size: vec2(glyph_info.advance_width, line_height),
-                ascent: font_impl.map_or(0.0, |font| font.ascent()), // Failure to find the font here would be weird
+                pos: pos2(f32::NAN, cursor_y + ascent),
+                ascent,
+                size,
                 uv_rect: glyph_info.uv_rect,
+                kerning: 0.0,
                 section_index,
+                bidi_level: 0,
             });
+            column.rect.max.y = point_scale.round_to_pixel(cursor_y + size.y);
+        }
+    }
+
+    // Then place the columns, starting from the right:
+    let paragraph_spacing = point_scale.round_to_pixel(job.paragraph_spacing);
+    let total_width: f32 = columns
+        .iter()
+        .map(|column| point_scale.round_to_pixel(column.rect.max.x))
+        .sum::<f32>()
+        + paragraph_spacing * (columns.len() - 1) as f32;
+    let mut column_right = total_width;
+    let mut max_y: f32 = 0.0;
+    for column in &mut columns {
+        let width = point_scale.round_to_pixel(column.rect.max.x);
+        let height = column.rect.max.y;
+        column.rect =
+            Rect::from_min_max(pos2(column_right - width, 0.0), pos2(column_right, height));
+        column.baseline = height;
+        for glyph in Arc::make_mut(&mut column.glyphs) {
+            glyph.pos.x = point_scale.round_to_pixel(column.rect.center().x - 0.5 * glyph.size.x);
+        }
+        max_y = max_y.max(height);
+        column_right -= width + paragraph_spacing;
+    }
+
+    let format_summary = format_summary(fonts, &job);
+    let mut mesh_bounds = Rect::NOTHING;
+    let mut num_vertices = 0;
+    let mut num_indices = 0;
+
+    for column in &mut columns {
+        column.visuals = Arc::new(tessellate_row(point_scale, &job, &format_summary, column));
+        mesh_bounds = mesh_bounds.union(column.visuals.mesh_bounds);
+        num_vertices += column.visuals.mesh.vertices.len() + column.visuals.sdf_mesh.vertices.len();
+        num_indices += column.visuals.mesh.indices.len() + column.visuals.sdf_mesh.indices.len();
+    }
+
+    let mut rect = Rect::from_min_max(Pos2::ZERO, pos2(total_width, max_y));
+    if job.round_output_size_to_nearest_ui_point {
+        rect.max = rect.max.round();
+    }
+
+    Galley {
+        job,
+        rows: columns,
+        elided: false,
+        rect,
+        mesh_bounds,
+        num_vertices,
+        num_indices,
+        pixels_per_point: point_scale.pixels_per_point,
+    }
+}
+
+/// Like the unshaped loop in [`layout_section`], but shapes runs of text that use the same font.
+///
+/// We still produce exactly one [`Glyph`] per character, so that cursors keep working:
+/// a ligature is drawn by the first character of its cluster,
+/// and the advance of the cluster is shared evenly between its characters.
+#[cfg(feature = "shaping")]
+fn layout_section_shaped(
+    font: &Font,
+    job: &LayoutJob,
+    section_index: u32,
+    format: &TextFormat,
+    text: &str,
+    out_paragraphs: &mut Vec<Paragraph>,
+) {
+    let line_height = format.line_height.unwrap_or_else(|| font.row_height());
+
+    let mut last_glyph_id = None;
+    let mut chars = text.char_indices().peekable();
+
+    while let Some(&(run_start, chr)) = chars.peek() {
+        if job.break_on_newline && chr == '\n' {
+            chars.next();
+            out_paragraphs.push(Paragraph::from_section_index(section_index));
+            let paragraph = out_paragraphs.last_mut().unwrap();
+            paragraph.empty_paragraph_height = line_height; // TODO(emilk): replace this hack with actually including `\n` in the glyphs?
+            continue;
+        }
+
+        let paragraph = out_paragraphs.last_mut().unwrap();
+
+        let Some(font_index) = font.shaping_font_index(chr) else {
+            chars.next();
+            layout_glyph(
+                font,
+                job,
+                format,
+                section_index,
+                paragraph,
+                chr,
+                &mut last_glyph_id,
+            );
+            continue;
+        };
+
+        // Find the run of characters that can be shaped with the same font:
+        let mut run_end = run_start;
+        while let Some(&(i, c)) = chars.peek() {
+            if (job.break_on_newline && c == '\n') || font.shaping_font_index(c) != Some(font_index)
+            {
+                break;
+            }
+            run_end = i + c.len_utf8();
+            chars.next();
+        }
+        let run = &text[run_start..run_end];
+
+        let Some((ascent, shaped)) =
+            font.shape(font_index, run, format.kerning, &format.font_features)
+        else {
+            for chr in run.chars() {
+                layout_glyph(
+                    font,
+                    job,
+                    format,
+                    section_index,
+                    paragraph,
+                    chr,
+                    &mut last_glyph_id,
+                );
+            }
+            continue;
+        };
+
+        let run_chars: Vec<(usize, char)> = run.char_indices().collect();
+        let mut glyph_index = 0;
+        while glyph_index < shaped.len() {
+            let cluster_start = shaped[glyph_index].cluster;
+            let num_glyphs = shaped[glyph_index..]
+                .iter()
+                .take_while(|glyph| glyph.cluster == cluster_start)
+                .count();
+            let cluster_glyphs = &shaped[glyph_index..glyph_index + num_glyphs];
+            glyph_index += num_glyphs;
+
+            let cluster_end = shaped.get(glyph_index).map_or(run.len(), |g| g.cluster);
+            let cluster_chars: Vec<char> = run_chars
+                .iter()
+                .filter(|(i, _)| (cluster_start..cluster_end).contains(i))
+                .map(|(_, c)| *c)
+                .collect();
+
+            if cluster_chars.len() < cluster_glyphs.len() {
+                // More glyphs than characters: we can't keep one glyph per character,
+                // so fall back to no shaping for this cluster.
+                for chr in cluster_chars {
+                    layout_glyph(
+                        font,
+                        job,
+                        format,
+                        section_index,
+                        paragraph,
+                        chr,
+                        &mut last_glyph_id,
+                    );
+                }
+                continue;
+            }
+
+            if last_glyph_id.is_some() {
+                paragraph.cursor_x += format.extra_letter_spacing;
+            }
+
+            let cluster_x = paragraph.cursor_x;
+            let cluster_advance: f32 = cluster_glyphs.iter().map(|g| g.advance).sum();
+            let advance_per_char = cluster_advance / cluster_chars.len() as f32;
+
+            let mut glyph_x = 0.0; // relative to the start of the cluster
+            for (i, chr) in cluster_chars.into_iter().enumerate() {
+                let char_x = i as f32 * advance_per_char;
+                let mut uv_rect = crate::text::font::UvRect::default();
+                if let Some(shaped_glyph) = cluster_glyphs.get(i) {
+                    uv_rect = shaped_glyph.glyph_info.uv_rect;
+                    if !uv_rect.is_nothing() {
+                        uv_rect.offset += shaped_glyph.offset + vec2(glyph_x - char_x, 0.0);
+                    }
+                    glyph_x += shaped_glyph.advance;
+                }
+
+                paragraph.glyphs.push(Glyph {
+                    chr,
+                    pos: pos2(cluster_x + char_x, f32::NAN),
+                    size: vec2(advance_per_char, line_height),
+                    ascent,
+                    uv_rect,
+                    kerning: 0.0,
+                    section_index,
+                    bidi_level: 0,
+                });
+            }
+
+            paragraph.cursor_x = font.round_to_pixel(cluster_x + cluster_advance);
+            last_glyph_id = cluster_glyphs.last().map(|g| g.glyph_info.id);
+        }
+    }
+}
 
-            paragraph.cursor_x += glyph_info.advance_width;
-            paragraph.cursor_x = font.round_to_pixel(paragraph.cursor_x);
-            last_glyph_id = Some(glyph_info.id);
+/// For fonts with [`crate::text::FontTweak::subpixel_positioning`],
+/// pick the glyph rasterization that best matches the final x position of each glyph.
+fn select_subpixel_glyphs(fonts: &LayoutFonts, job: &LayoutJob, rows: &mut [Row]) {
+    let subpixel_sections: Vec<bool> = job
+        .sections
+        .iter()
+        .map(|section| {
+            // Substituted glyphs are looked up by glyph id, and always rasterized at whole pixels:
+            section.format.font_features.is_empty()
+                && fonts.format_font(&section.format).subpixel_positioning()
+        })
+        .collect();
+    if !subpixel_sections.contains(&true) {
+        return; // The common case
+    }
+
+    for row in rows {
+        for glyph in Arc::make_mut(&mut row.glyphs) {
+            let section_index = glyph.section_index as usize;
+            // Right-to-left glyphs may be mirrored, so we leave them alone:
+            if subpixel_sections[section_index] && !glyph.is_rtl() {
+                let font = fonts.format_font(&job.sections[section_index].format);
+                if let Some(uv_rect) = font.subpixel_uv_rect(glyph.chr, glyph.pos.x) {
+                    glyph.uv_rect = uv_rect;
+                }
+            }
         }
     }
 }
@@ -188,6 +773,7 @@ fn rect_from_x_range(x_range: RangeInclusive<f32>) -> Rect {
 
 // Ignores the Y coordinate.
 fn rows_from_paragraphs(
+    fonts: &LayoutFonts,
     paragraphs: Vec<Paragraph>,
     job: &LayoutJob,
     elided: &mut bool,
@@ -207,13 +793,15 @@ fn rows_from_paragraphs(
         if paragraph.glyphs.is_empty() {
             rows.push(Row {
                 section_index_at_start: paragraph.section_index_at_start,
-                glyphs: vec![],
+                glyphs: Default::default(),
                 visuals: Default::default(),
+                baseline: f32::NAN,
                 rect: Rect::from_min_size(
                     pos2(paragraph.cursor_x, 0.0),
                     vec2(0.0, paragraph.empty_paragraph_height),
                 ),
                 ends_with_newline: !is_last_paragraph,
+                hyphen: None,
             });
         } else {
             let paragraph_max_x = paragraph.glyphs.last().unwrap().max_x();
@@ -222,13 +810,15 @@ fn rows_from_paragraphs(
                 let paragraph_min_x = paragraph.glyphs[0].pos.x;
                 rows.push(Row {
                     section_index_at_start: paragraph.section_index_at_start,
-                    glyphs: paragraph.glyphs,
+                    glyphs: Arc::new(paragraph.glyphs),
                     visuals: Default::default(),
+                    baseline: f32::NAN,
                     rect: rect_from_x_range(paragraph_min_x..=paragraph_max_x),
                     ends_with_newline: !is_last_paragraph,
+                    hyphen: None,
                 });
             } else {
-                line_break(&paragraph, job, &mut rows, elided);
+                line_break(fonts, &paragraph, job, &mut rows, elided);
                 rows.last_mut().unwrap().ends_with_newline = !is_last_paragraph;
             }
         }
@@ -237,7 +827,13 @@ fn rows_from_paragraphs(
     rows
 }
 
-fn line_break(paragraph: &Paragraph, job: &LayoutJob, out_rows: &mut Vec<Row>, elided: &mut bool) {
+fn line_break(
+    fonts: &LayoutFonts,
+    paragraph: &Paragraph,
+    job: &LayoutJob,
+    out_rows: &mut Vec<Row>,
+    elided: &mut bool,
+) {
     let wrap_width_margin = if job.round_output_size_to_nearest_ui_point {
         0.5
     } else {
@@ -246,20 +842,33 @@ fn line_break(paragraph: &Paragraph, job: &LayoutJob, out_rows: &mut Vec<Row>, e
 
     // Keeps track of good places to insert row break if we exceed `wrap_width`.
     let mut row_break_candidates = RowBreakCandidates::default();
+    let hyphenation_points = hyphenation_points(job, &paragraph.glyphs);
+    let no_break_points = no_break_points(job.wrap.no_break, &paragraph.glyphs);
+
+    // Is there room for everything up to `max_x` on a row starting at `row_start_x`?
+    let fits_in_row = |max_x: f32, row_start_x: f32| {
+        max_x - row_start_x <= job.wrap.max_width + wrap_width_margin
+    };
+
+    // The hyphen to show if we hyphenate after glyph `i`, if it is a hyphenation point
+    // and there is room for the hyphen:
+    let hyphen_after = |i: usize, row_start_x: f32| {
+        (hyphenation_points[i] && !no_break_points[i])
+            .then(|| hyphen_glyph(fonts, job, &paragraph.glyphs[i]))
+            .filter(|hyphen| fits_in_row(hyphen.max_x(), row_start_x))
+    };
 
     let mut first_row_indentation = paragraph.glyphs[0].pos.x;
     let mut row_start_x = 0.0;
     let mut row_start_idx = 0;
 
-    for i in 0..paragraph.glyphs.len() {
+    for (i, &no_break) in no_break_points.iter().enumerate() {
         if job.wrap.max_rows <= out_rows.len() {
             *elided = true;
             break;
         }
 
-        let potential_row_width = paragraph.glyphs[i].max_x() - row_start_x;
-
-        if job.wrap.max_width + wrap_width_margin < potential_row_width {
+        if !fits_in_row(paragraph.glyphs[i].max_x(), row_start_x) {
             // Row break:
 
             if first_row_indentation > 0.0
@@ -269,10 +878,12 @@ fn line_break(paragraph: &Paragraph, job: &LayoutJob, out_rows: &mut Vec<Row>, e
                 // TODO(emilk): this records the height of this first row as zero, though that is probably fine since first_row_indentation usually comes with a first_row_min_height.
                 out_rows.push(Row {
                     section_index_at_start: paragraph.section_index_at_start,
-                    glyphs: vec![],
+                    glyphs: Default::default(),
                     visuals: Default::default(),
+                    baseline: f32::NAN,
                     rect: rect_from_x_range(first_row_indentation..=first_row_indentation),
                     ends_with_newline: false,
+                    hyphen: None,
                 });
                 row_start_x += first_row_indentation;
                 first_row_indentation = 0.0;
@@ -287,16 +898,24 @@ fn line_break(paragraph: &Paragraph, job: &LayoutJob, out_rows: &mut Vec<Row>, e
                     })
                     .collect();
 
+                // Even when breaking anywhere, the hyphen is only added if it fits:
+                let hyphen = hyphen_after(last_kept_index, row_start_x).map(|mut hyphen| {
+                    hyphen.pos.x -= row_start_x;
+                    hyphen
+                });
+
                 let section_index_at_start = glyphs[0].section_index;
                 let paragraph_min_x = glyphs[0].pos.x;
-                let paragraph_max_x = glyphs.last().unwrap().max_x();
+                let paragraph_max_x = hyphen.as_ref().unwrap_or(glyphs.last().unwrap()).max_x();
 
                 out_rows.push(Row {
                     section_index_at_start,
-                    glyphs,
+                    glyphs: Arc::new(glyphs),
                     visuals: Default::default(),
+                    baseline: f32::NAN,
                     rect: rect_from_x_range(paragraph_min_x..=paragraph_max_x),
                     ends_with_newline: false,
+                    hyphen,
                 });
 
                 // Start a new row:
@@ -308,7 +927,12 @@ fn line_break(paragraph: &Paragraph, job: &LayoutJob, out_rows: &mut Vec<Row>, e
             }
         }
 
-        row_break_candidates.add(i, &paragraph.glyphs[i..]);
+        row_break_candidates.add(
+            i,
+            &paragraph.glyphs[i..],
+            hyphen_after(i, row_start_x).is_some(),
+            no_break,
+        );
     }
 
     if row_start_idx < paragraph.glyphs.len() {
@@ -332,23 +956,132 @@ fn line_break(paragraph: &Paragraph, job: &LayoutJob, out_rows: &mut Vec<Row>, e
 
             out_rows.push(Row {
                 section_index_at_start,
-                glyphs,
+                glyphs: Arc::new(glyphs),
                 visuals: Default::default(),
+                baseline: f32::NAN,
                 rect: rect_from_x_range(paragraph_min_x..=paragraph_max_x),
                 ends_with_newline: false,
+                hyphen: None,
             });
         }
     }
 }
 
+const SOFT_HYPHEN: char = '\u{AD}';
+
+/// For each glyph: may the row be broken after it by hyphenating the word?
+///
+/// See [`crate::text::TextWrapping::hyphenator`].
+fn hyphenation_points(job: &LayoutJob, glyphs: &[Glyph]) -> Vec<bool> {
+    let mut points: Vec<bool> = glyphs
+        .iter()
+        .map(|glyph| glyph.chr == SOFT_HYPHEN)
+        .collect();
+
+    if let Some(hyphenator) = &job.wrap.hyphenator {
+        let mut word_start = 0;
+        for i in 0..=glyphs.len() {
+            if glyphs.get(i).is_some_and(|glyph| glyph.chr.is_alphabetic()) {
+                continue;
+            }
+            if word_start + 1 < i {
+                let word: String = glyphs[word_start..i]
+                    .iter()
+                    .map(|glyph| glyph.chr)
+                    .collect();
+                for offset in (hyphenator.0)(&word) {
+                    if 0 < offset && word_start + offset < i {
+                        points[word_start + offset - 1] = true;
+                    }
+                }
+            }
+            word_start = i + 1;
+        }
+    }
+
+    points
+}
+
+/// For each glyph: should the row only be broken after it as a last resort,
+/// because it is inside a number or URL?
+///
+/// See [`crate::text::TextWrapping::no_break`].
+fn no_break_points(no_break: NoBreakClasses, glyphs: &[Glyph]) -> Vec<bool> {
+    let chr = |i: usize| glyphs.get(i).map(|glyph| glyph.chr);
+    let is_digit = |i: usize| chr(i).is_some_and(|c| c.is_ascii_digit());
+    let mut points = vec![false; glyphs.len()];
+
+    if no_break.numbers {
+        for (i, glyph) in glyphs.iter().enumerate() {
+            // Grouping and decimal separators, including thin and narrow no-break spaces:
+            let is_separator =
+                matches!(glyph.chr, ',' | '.' | '\'' | '_' | '\u{2009}' | '\u{202F}');
+            if is_separator && 0 < i && is_digit(i - 1) && is_digit(i + 1) {
+                points[i] = true;
+            }
+        }
+    }
+
+    if no_break.urls {
+        let mut token_start = 0;
+        for i in 0..=glyphs.len() {
+            if chr(i).is_some_and(|c| !c.is_whitespace()) {
+                continue;
+            }
+            let token: String = glyphs[token_start..i]
+                .iter()
+                .map(|glyph| glyph.chr)
+                .collect();
+            if is_url(&token) {
+                for (j, point) in (token_start..i).zip(&mut points[token_start..i]) {
+                    // Break after a `/`, but not inside the `//` after the scheme:
+                    *point = !(chr(j) == Some('/') && chr(j + 1) != Some('/'));
+                }
+            }
+            token_start = i + 1;
+        }
+    }
+
+    points
+}
+
+/// Does `token` start with something like `https://`?
+fn is_url(token: &str) -> bool {
+    token.split_once("://").is_some_and(|(scheme, rest)| {
+        !scheme.is_empty()
+            && !rest.is_empty()
+            && scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+            && scheme
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+    })
+}
+
+/// The `-` shown after the last glyph of a row that was broken in the middle of a word.
+///
+/// See [`Row::hyphen`].
+fn hyphen_glyph(fonts: &LayoutFonts, job: &LayoutJob, last_glyph: &Glyph) -> Glyph {
+    let section_index = last_glyph.section_index;
+    let format = &job.sections[section_index as usize].format;
+    let font = fonts.format_font(format);
+    let line_height = format.line_height.unwrap_or_else(|| font.row_height());
+    let (font_impl, glyph_info) = font.font_impl_and_glyph_info('-');
+    Glyph {
+        chr: '-',
+        pos: pos2(last_glyph.max_x() + format.extra_letter_spacing, f32::NAN),
+        size: vec2(glyph_info.advance_width, line_height),
+        ascent: font_impl.map_or(0.0, |font| font.ascent()), // Failure to find the font here would be weird
+        uv_rect: glyph_info.uv_rect,
+        kerning: 0.0,
+        section_index,
+        bidi_level: 0,
+    }
+}
+
 /// Trims the last glyphs in the row and replaces it with an overflow character (e.g. `…`).
 ///
 /// Called before we have any Y coordinates.
-fn replace_last_glyph_with_overflow_character(
-    fonts: &mut FontsImpl,
-    job: &LayoutJob,
-    row: &mut Row,
-) {
+fn replace_last_glyph_with_overflow_character(fonts: &LayoutFonts, job: &LayoutJob, row: &mut Row) {
     fn row_width(row: &Row) -> f32 {
         if let (Some(first), Some(last)) = (row.glyphs.first(), row.glyphs.last()) {
             last.max_x() - first.pos.x
@@ -368,11 +1101,14 @@ fn replace_last_glyph_with_overflow_character(
         return;
     };
 
+    // The overflow character takes the place of any hyphen:
+    row.hyphen = None;
+
     // We always try to just append the character first:
     if let Some(last_glyph) = row.glyphs.last() {
         let section_index = last_glyph.section_index;
         let section = &job.sections[section_index as usize];
-        let font = fonts.font(&section.format.font_id);
+        let font = fonts.format_font(&section.format);
         let line_height = row_height(section, font);
 
         let (_, last_glyph_info) = font.font_impl_and_glyph_info(last_glyph.chr);
@@ -381,39 +1117,47 @@ fn replace_last_glyph_with_overflow_character(
 
         let (font_impl, replacement_glyph_info) = font.font_impl_and_glyph_info(overflow_character);
 
+        let mut kerning = 0.0;
         {
             // Kerning:
             x += section.format.extra_letter_spacing;
             if let Some(font_impl) = font_impl {
-                x += font_impl.pair_kerning(last_glyph_info.id, replacement_glyph_info.id);
+                if section.format.kerning {
+                    kerning = font_impl.pair_kerning(last_glyph_info.id, replacement_glyph_info.id);
+                }
+                x += kerning;
             }
         }
 
-        row.glyphs.push(Glyph {
+        Arc::make_mut(&mut row.glyphs).push(Glyph {
             chr: overflow_character,
             pos: pos2(x, f32::NAN),
             size: vec2(replacement_glyph_info.advance_width, line_height),
             ascent: font_impl.map_or(0.0, |font| font.ascent()), // Failure to find the font here would be weird
             uv_rect: replacement_glyph_info.uv_rect,
+            kerning,
             section_index,
+            bidi_level: 0,
         });
     } else {
         let section_index = row.section_index_at_start;
         let section = &job.sections[section_index as usize];
-        let font = fonts.font(&section.format.font_id);
+        let font = fonts.format_font(&section.format);
         let line_height = row_height(section, font);
 
         let x = 0.0; // TODO(emilk): heed paragraph leading_space 😬
 
         let (font_impl, replacement_glyph_info) = font.font_impl_and_glyph_info(overflow_character);
 
-        row.glyphs.push(Glyph {
+        Arc::make_mut(&mut row.glyphs).push(Glyph {
             chr: overflow_character,
             pos: pos2(x, f32::NAN),
             size: vec2(replacement_glyph_info.advance_width, line_height),
             ascent: font_impl.map_or(0.0, |font| font.ascent()), // Failure to find the font here would be weird
             uv_rect: replacement_glyph_info.uv_rect,
+            kerning: 0.0,
             section_index,
+            bidi_level: 0,
         });
     }
 
@@ -422,13 +1166,13 @@ fn replace_last_glyph_with_overflow_character(
     }
 
     // We didn't fit it. Remove it again…
-    row.glyphs.pop();
+    Arc::make_mut(&mut row.glyphs).pop();
 
     // …then go into a loop where we replace the last character with the overflow character
     // until we fit within the max_width:
 
     loop {
-        let (prev_glyph, last_glyph) = match row.glyphs.as_mut_slice() {
+        let (prev_glyph, last_glyph) = match Arc::make_mut(&mut row.glyphs).as_mut_slice() {
             [.., prev, last] => (Some(prev), last),
             [.., last] => (None, last),
             _ => {
@@ -438,18 +1182,15 @@ fn replace_last_glyph_with_overflow_character(
 
         let section = &job.sections[last_glyph.section_index as usize];
         let extra_letter_spacing = section.format.extra_letter_spacing;
-        let font = fonts.font(&section.format.font_id);
+        let font = fonts.format_font(&section.format);
         let line_height = row_height(section, font);
 
         if let Some(prev_glyph) = prev_glyph {
             let prev_glyph_id = font.font_impl_and_glyph_info(prev_glyph.chr).1.id;
 
             // Undo kerning with previous glyph:
-            let (font_impl, glyph_info) = font.font_impl_and_glyph_info(last_glyph.chr);
             last_glyph.pos.x -= extra_letter_spacing;
-            if let Some(font_impl) = font_impl {
-                last_glyph.pos.x -= font_impl.pair_kerning(prev_glyph_id, glyph_info.id);
-            }
+            last_glyph.pos.x -= last_glyph.kerning;
 
             // Replace the glyph:
             last_glyph.chr = overflow_character;
@@ -459,9 +1200,13 @@ fn replace_last_glyph_with_overflow_character(
 
             // Reapply kerning:
             last_glyph.pos.x += extra_letter_spacing;
+            last_glyph.kerning = 0.0;
             if let Some(font_impl) = font_impl {
-                last_glyph.pos.x += font_impl.pair_kerning(prev_glyph_id, glyph_info.id);
+                if section.format.kerning {
+                    last_glyph.kerning = font_impl.pair_kerning(prev_glyph_id, glyph_info.id);
+                }
             }
+            last_glyph.pos.x += last_glyph.kerning;
 
             // Check if we're within width budget:
             if row_width(row) <= job.wrap.max_width || row.glyphs.len() == 1 {
@@ -469,7 +1214,7 @@ fn replace_last_glyph_with_overflow_character(
             }
 
             // We didn't fit - pop the last glyph and try again.
-            row.glyphs.pop();
+            Arc::make_mut(&mut row.glyphs).pop();
         } else {
             // Just replace and be done with it.
             last_glyph.chr = overflow_character;
@@ -518,7 +1263,10 @@ fn halign_and_justify_row(
     assert!(num_glyphs_in_range > 0);
 
     let original_min_x = row.glyphs[glyph_range.0].logical_rect().min.x;
-    let original_max_x = row.glyphs[glyph_range.1 - 1].logical_rect().max.x;
+    let original_max_x = match &row.hyphen {
+        Some(hyphen) if glyph_range.1 == row.glyphs.len() => hyphen.max_x(),
+        _ => row.glyphs[glyph_range.1 - 1].logical_rect().max.x,
+    };
     let original_width = original_max_x - original_min_x;
 
     let target_width = if justify && num_glyphs_in_range > 1 {
@@ -538,106 +1286,266 @@ fn halign_and_justify_row(
         .filter(|glyph| glyph.chr.is_whitespace())
         .count();
 
-    let mut extra_x_per_glyph = if num_glyphs_in_range == 1 {
-        0.0
+    let extra_x = (target_width - original_width).at_least(0.0); // Don't contract
+
+    // Justify by stretching the spaces between the words.
+    // Only if there are no spaces do we spread out the letters instead:
+    let (extra_x_per_glyph, extra_x_per_space) = if 0 < num_spaces_in_range {
+        (0.0, extra_x / num_spaces_in_range as f32)
+    } else if 1 < num_glyphs_in_range {
+        (extra_x / (num_glyphs_in_range as f32 - 1.0), 0.0)
     } else {
-        (target_width - original_width) / (num_glyphs_in_range as f32 - 1.0)
+        (0.0, 0.0)
     };
-    extra_x_per_glyph = extra_x_per_glyph.at_least(0.0); // Don't contract
-
-    let mut extra_x_per_space = 0.0;
-    if 0 < num_spaces_in_range && num_spaces_in_range < num_glyphs_in_range {
-        // Add an integral number of pixels between each glyph,
-        // and add the balance to the spaces:
-
-        extra_x_per_glyph = point_scale.floor_to_pixel(extra_x_per_glyph);
-
-        extra_x_per_space = (target_width
-            - original_width
-            - extra_x_per_glyph * (num_glyphs_in_range as f32 - 1.0))
-            / (num_spaces_in_range as f32);
-    }
 
     let mut translate_x = target_min_x - original_min_x - extra_x_per_glyph * glyph_range.0 as f32;
 
-    for glyph in &mut row.glyphs {
-        glyph.pos.x += translate_x;
-        glyph.pos.x = point_scale.round_to_pixel(glyph.pos.x);
+    let mut last_translate_x = 0.0;
+    for (i, glyph) in Arc::make_mut(&mut row.glyphs).iter_mut().enumerate() {
+        // Round the translation rather than the position, to keep any subpixel positioning:
+        last_translate_x = point_scale.round_to_pixel(translate_x);
+        glyph.pos.x += last_translate_x;
         translate_x += extra_x_per_glyph;
-        if glyph.chr.is_whitespace() {
+        if glyph.chr.is_whitespace() && (glyph_range.0..glyph_range.1).contains(&i) {
+            // Widen the space, so that the cursor and selection cover the whole gap:
+            let next_x = point_scale.round_to_pixel(translate_x + extra_x_per_space);
+            glyph.size.x += next_x - point_scale.round_to_pixel(translate_x);
             translate_x += extra_x_per_space;
         }
     }
 
+    if let Some(hyphen) = &mut row.hyphen {
+        hyphen.pos.x += last_translate_x;
+    }
+
     // Note we ignore the leading/trailing whitespace here!
     row.rect.min.x = target_min_x;
     row.rect.max.x = target_max_x;
 }
 
-/// Calculate the Y positions and tessellate the text.
-fn galley_from_rows(
-    point_scale: PointScale,
-    job: Arc<LayoutJob>,
-    mut rows: Vec<Row>,
-    elided: bool,
-) -> Galley {
-    let mut first_row_min_height = job.first_row_min_height;
-    let mut cursor_y = 0.0;
-    let mut min_x: f32 = 0.0;
-    let mut max_x: f32 = 0.0;
-    for row in &mut rows {
+/// Reorder the glyphs of rows that contain right-to-left text (e.g. Hebrew or Arabic)
+/// into visual order, using the Unicode bidi algorithm.
+///
+/// The embedding levels are resolved per paragraph, and then each row is reordered on its own.
+/// The glyphs stay in logical order in [`Row::glyphs`], but are given new x positions,
+/// and their [`Glyph::bidi_level`] is set.
+fn reorder_bidi_rows(fonts: &LayoutFonts, job: &LayoutJob, rows: &mut [Row]) {
+    let mut paragraph_start = 0;
+    while paragraph_start < rows.len() {
+        let paragraph_end = rows[paragraph_start..]
+            .iter()
+            .position(|row| row.ends_with_newline)
+            .map_or(rows.len(), |i| paragraph_start + i + 1);
+        reorder_bidi_paragraph(fonts, job, &mut rows[paragraph_start..paragraph_end]);
+        paragraph_start = paragraph_end;
+    }
+}
+
+fn reorder_bidi_paragraph(fonts: &LayoutFonts, job: &LayoutJob, rows: &mut [Row]) {
+    let text: String = rows
+        .iter()
+        .flat_map(|row| row.glyphs.iter().map(|glyph| glyph.chr))
+        .collect();
+    if !text.chars().any(is_strong_rtl) {
+        return; // The common case
+    }
+
+    let bidi_info = unicode_bidi::BidiInfo::new(&text, None);
+    let mut line_start = 0;
+    for row in rows {
+        let line_end = line_start + row.glyphs.iter().map(|g| g.chr.len_utf8()).sum::<usize>();
+        let line = line_start..line_end;
+        line_start = line_end;
+
+        let Some(para) = bidi_info
+            .paragraphs
+            .iter()
+            .find(|para| para.range.contains(&line.start))
+        else {
+            continue; // empty row
+        };
+
+        // One level per byte:
+        let levels = bidi_info.reordered_levels(para, line.clone());
+        let mut byte_index = line.start;
+        for glyph in Arc::make_mut(&mut row.glyphs) {
+            glyph.bidi_level = levels[byte_index].number();
+            byte_index += glyph.chr.len_utf8();
+        }
+
+        if row.has_rtl() {
+            reorder_bidi_row(fonts, job, row);
+        }
+    }
+}
+
+/// Lay out the glyphs of the row in visual order, given their [`Glyph::bidi_level`].
+fn reorder_bidi_row(fonts: &LayoutFonts, job: &LayoutJob, row: &mut Row) {
+    // Combining marks are positioned relative to their base, so we move them together:
+    let mut clusters: Vec<Range<usize>> = vec![];
+    for (i, glyph) in row.glyphs.iter().enumerate() {
+        match clusters.last_mut() {
+            Some(cluster) if continues_cluster(glyph.chr) => cluster.end = i + 1,
+            _ => clusters.push(i..i + 1),
+        }
+    }
+
+    let cluster_levels: Vec<unicode_bidi::Level> = clusters
+        .iter()
+        .map(|cluster| {
+            unicode_bidi::Level::new(row.glyphs[cluster.start].bidi_level)
+                .unwrap_or_else(|_| unicode_bidi::Level::ltr())
+        })
+        .collect();
+    let visual_order = unicode_bidi::BidiInfo::reorder_visual(&cluster_levels);
+
+    let advances: Vec<f32> = clusters
+        .iter()
+        .enumerate()
+        .map(|(i, cluster)| {
+            let start_x = row.glyphs[cluster.start].pos.x;
+            match clusters.get(i + 1) {
+                Some(next) => row.glyphs[next.start].pos.x - start_x,
+                None => row.glyphs[cluster.clone()]
+                    .iter()
+                    .map(|glyph| glyph.max_x() - start_x)
+                    .fold(0.0, f32::max),
+            }
+        })
+        .collect();
+
+    let mut x = row.glyphs[0].pos.x;
+    for cluster_index in visual_order {
+        let cluster = clusters[cluster_index].clone();
+        let shift = x - row.glyphs[cluster.start].pos.x;
+        for glyph in &mut Arc::make_mut(&mut row.glyphs)[cluster] {
+            glyph.pos.x += shift;
+        }
+        x += advances[cluster_index];
+    }
+
+    // Brackets etc. point the other way in right-to-left text:
+    for glyph in Arc::make_mut(&mut row.glyphs) {
+        if glyph.is_rtl() {
+            if let Some(mirrored) = unicode_bidi_mirroring::get_mirrored(glyph.chr) {
+                let format = &job.sections[glyph.section_index as usize].format;
+                let (_, glyph_info) = fonts.format_font(format).font_impl_and_glyph_info(mirrored);
+                glyph.uv_rect = glyph_info.uv_rect;
+            }
+        }
+    }
+}
+
+/// Is this a character that is always laid out right-to-left?
+///
+/// Used to skip the bidi algorithm for text that doesn't need it.
+fn is_strong_rtl(chr: char) -> bool {
+    use unicode_bidi::BidiClass;
+    matches!(
+        unicode_bidi::bidi_class(chr),
+        BidiClass::R | BidiClass::AL | BidiClass::RLE | BidiClass::RLO | BidiClass::RLI
+    )
+}
+
+/// The highest glyph of a row, which the row takes its metrics from.
+fn tallest_glyph(glyphs: &[Glyph]) -> Option<&Glyph> {
+    glyphs
+        .iter()
+        .max_by(|a, b| a.size.y.partial_cmp(&b.size.y).unwrap())
+}
+
+/// The Y position of a glyph in a row starting at `row_top`, with the ascent of its [`tallest_glyph`].
+fn glyph_pos_y(job: &LayoutJob, glyph: &Glyph, row_top: f32, row_ascent: f32) -> f32 {
+    let format = &job.sections[glyph.section_index as usize].format;
+
+    let align_offset = match format.valign {
+        Align::Center | Align::Max => row_ascent,
+
+        // raised text.
+        Align::Min => glyph.ascent,
+    };
+    row_top + align_offset
+}
+
+/// Calculate the Y positions and tessellate the text.
+fn galley_from_rows(
+    point_scale: PointScale,
+    job: Arc<LayoutJob>,
+    format_summary: &FormatSummary,
+    mut rows: Vec<Row>,
+    elided: bool,
+) -> Galley {
+    let mut first_row_min_height = job.first_row_min_height;
+    let mut cursor_y = 0.0;
+    let mut min_x: f32 = 0.0;
+    let mut max_x: f32 = 0.0;
+    for row in &mut rows {
         let mut line_height = first_row_min_height.max(row.rect.height());
         let mut row_ascent = 0.0f32;
         first_row_min_height = 0.0;
 
         // take metrics from the highest font in this row
-        if let Some(glyph) = row
-            .glyphs
-            .iter()
-            .max_by(|a, b| a.size.y.partial_cmp(&b.size.y).unwrap())
-        {
+        if let Some(glyph) = tallest_glyph(&row.glyphs) {
             line_height = glyph.size.y;
             row_ascent = glyph.ascent;
         }
         line_height = point_scale.round_to_pixel(line_height);
 
         // Now positions each glyph:
-        for glyph in &mut row.glyphs {
-            let format = &job.sections[glyph.section_index as usize].format;
-
-            let align_offset = match format.valign {
-                Align::Center | Align::Max => row_ascent,
-
-                // raised text.
-                Align::Min => glyph.ascent,
-            };
-            glyph.pos.y = cursor_y + align_offset;
+        for glyph in Arc::make_mut(&mut row.glyphs)
+            .iter_mut()
+            .chain(&mut row.hyphen)
+        {
+            glyph.pos.y = glyph_pos_y(&job, glyph, cursor_y, row_ascent);
         }
 
         row.rect.min.y = cursor_y;
         row.rect.max.y = cursor_y + line_height;
+        row.baseline = if row.glyphs.is_empty() {
+            row.rect.max.y
+        } else {
+            cursor_y + row_ascent
+        };
 
         min_x = min_x.min(row.rect.min.x);
         max_x = max_x.max(row.rect.max.x);
         cursor_y += line_height;
+        if row.ends_with_newline {
+            cursor_y += job.paragraph_spacing;
+        }
         cursor_y = point_scale.round_to_pixel(cursor_y);
     }
 
-    let format_summary = format_summary(&job);
-
     let mut mesh_bounds = Rect::NOTHING;
     let mut num_vertices = 0;
     let mut num_indices = 0;
 
     for row in &mut rows {
-        row.visuals = tessellate_row(point_scale, &job, &format_summary, row);
+        row.visuals = Arc::new(tessellate_row(point_scale, &job, format_summary, row));
         mesh_bounds = mesh_bounds.union(row.visuals.mesh_bounds);
-        num_vertices += row.visuals.mesh.vertices.len();
-        num_indices += row.visuals.mesh.indices.len();
+        num_vertices += row.visuals.mesh.vertices.len() + row.visuals.sdf_mesh.vertices.len();
+        num_indices += row.visuals.mesh.indices.len() + row.visuals.sdf_mesh.indices.len();
     }
 
-    let mut rect = Rect::from_min_max(pos2(min_x, 0.0), pos2(max_x, cursor_y));
+    let rect = galley_rect(
+        &job,
+        Rect::from_min_max(pos2(min_x, 0.0), pos2(max_x, cursor_y)),
+    );
+
+    Galley {
+        job,
+        rows,
+        elided,
+        rect,
+        mesh_bounds,
+        num_vertices,
+        num_indices,
+        pixels_per_point: point_scale.pixels_per_point,
+    }
+}
 
+/// Applies [`LayoutJob::round_output_size_to_nearest_ui_point`] to the bounds of the rows.
+fn galley_rect(job: &LayoutJob, mut rect: Rect) -> Rect {
     if job.round_output_size_to_nearest_ui_point {
         let did_exceed_wrap_width_by_a_lot = rect.width() > job.wrap.max_width + 1.0;
 
@@ -654,6 +1562,164 @@ fn galley_from_rows(
             rect.max.x = rect.max.x.at_most(rect.min.x + job.wrap.max_width);
         }
     }
+    rect
+}
+
+/// One paragraph of a huge [`LayoutJob`], laid out on its own,
+/// and placed where it goes in the whole job.
+///
+/// See [`concat_paragraphs`].
+pub(crate) struct PlacedParagraph {
+    /// Where the top of the paragraph is in the whole job.
+    offset_y: f32,
+
+    /// The index in the whole job of each section of the paragraph.
+    section_indices: Vec<u32>,
+
+    /// If the paragraph ends with a newline, the last row is the empty row after it.
+    rows: Vec<Row>,
+
+    elided: bool,
+}
+
+impl PlacedParagraph {
+    /// Place the galley of a paragraph of `job`, with the indices of its sections in `job`.
+    pub fn new(job: &LayoutJob, galley: Galley, section_indices: &[u32], offset_y: f32) -> Self {
+        let mut rows = galley.rows;
+        let local_sections: Vec<u32> = (0..section_indices.len() as u32).collect();
+        move_rows(job, &mut rows, &local_sections, section_indices, offset_y);
+        Self {
+            offset_y,
+            section_indices: section_indices.to_vec(),
+            rows,
+            elided: galley.elided,
+        }
+    }
+
+    /// Is the paragraph already placed like this?
+    pub fn is_placed_at(&self, section_indices: &[u32], offset_y: f32) -> bool {
+        self.offset_y == offset_y && self.section_indices == section_indices
+    }
+
+    /// A copy of the paragraph, placed elsewhere.
+    ///
+    /// This is cheaper than laying it out again, but still copies all glyphs,
+    /// so it is only done for the paragraphs that actually moved.
+    pub fn moved(&self, job: &LayoutJob, section_indices: &[u32], offset_y: f32) -> Self {
+        let mut rows = self.rows.clone();
+        move_rows(
+            job,
+            &mut rows,
+            &self.section_indices,
+            section_indices,
+            offset_y - self.offset_y,
+        );
+        Self {
+            offset_y,
+            section_indices: section_indices.to_vec(),
+            rows,
+            elided: self.elided,
+        }
+    }
+
+    /// Where the paragraph after this one starts.
+    pub fn next_offset_y(&self) -> f32 {
+        self.rows.last().map_or(self.offset_y, |row| row.rect.min.y)
+    }
+}
+
+/// Move the rows of `job` down by `delta_y`, and change their section indices from `from_sections` to `to_sections`.
+///
+/// The glyphs are positioned in their moved rows like [`galley_from_rows`] does,
+/// rather than moved by `delta_y`, so they are exactly where laying out all of `job` at once puts them.
+fn move_rows(
+    job: &LayoutJob,
+    rows: &mut [Row],
+    from_sections: &[u32],
+    to_sections: &[u32],
+    delta_y: f32,
+) {
+    let delta = vec2(0.0, delta_y);
+    let section = |section_index: u32| {
+        from_sections
+            .binary_search(&section_index)
+            .map_or(section_index, |i| to_sections[i])
+    };
+    for row in rows {
+        row.section_index_at_start = section(row.section_index_at_start);
+        if delta_y != 0.0 {
+            row.rect = row.rect.translate(delta);
+            let visuals = Arc::make_mut(&mut row.visuals);
+            visuals.mesh.translate(delta);
+            visuals.sdf_mesh.translate(delta);
+            visuals.mesh_bounds = visuals.mesh_bounds.translate(delta);
+        }
+        if from_sections != to_sections || delta_y != 0.0 {
+            let row_top = row.rect.min.y;
+            let row_ascent = tallest_glyph(&row.glyphs).map_or(0.0, |glyph| glyph.ascent);
+            row.baseline = if row.glyphs.is_empty() {
+                row.rect.max.y
+            } else {
+                row_top + row_ascent
+            };
+            for glyph in Arc::make_mut(&mut row.glyphs)
+                .iter_mut()
+                .chain(&mut row.hyphen)
+            {
+                glyph.section_index = section(glyph.section_index);
+                glyph.pos.y = glyph_pos_y(job, glyph, row_top, row_ascent);
+            }
+        }
+    }
+}
+
+/// Stack the paragraphs of `job` on top of each other.
+///
+/// Every paragraph but the last one ends with a newline, and so with an empty row
+/// where the next paragraph starts. That row is replaced by the next paragraph.
+///
+/// The rows are shared with the paragraphs, so this doesn't copy any glyphs or meshes.
+pub(crate) fn concat_paragraphs(
+    job: Arc<LayoutJob>,
+    paragraphs: &[&PlacedParagraph],
+    pixels_per_point: f32,
+) -> Galley {
+    let mut rows: Vec<Row> = Vec::new();
+    let mut elided = false;
+    let mut mesh_bounds = Rect::NOTHING;
+    let mut num_vertices = 0;
+    let mut num_indices = 0;
+
+    for (i, paragraph) in paragraphs.iter().enumerate() {
+        let is_last = i + 1 == paragraphs.len();
+        let mut paragraph_rows = paragraph.rows.as_slice();
+        if !is_last {
+            if let Some((_, rest)) = paragraph_rows.split_last() {
+                paragraph_rows = rest;
+            }
+        }
+
+        for row in paragraph_rows {
+            mesh_bounds = mesh_bounds.union(row.visuals.mesh_bounds);
+            num_vertices += row.visuals.mesh.vertices.len() + row.visuals.sdf_mesh.vertices.len();
+            num_indices += row.visuals.mesh.indices.len() + row.visuals.sdf_mesh.indices.len();
+            rows.push(row.clone());
+        }
+
+        elided |= paragraph.elided;
+    }
+
+    let min_x = rows
+        .iter()
+        .fold(0.0f32, |min_x, row| min_x.min(row.rect.min.x));
+    let max_x = rows
+        .iter()
+        .fold(0.0f32, |max_x, row| max_x.max(row.rect.max.x));
+    let max_y = rows.last().map_or(0.0, |row| row.rect.max.y);
+    let rect = galley_rect(
+        &job,
+        Rect::from_min_max(pos2(min_x, 0.0), pos2(max_x, max_y)),
+    );
 
     Galley {
         job,
@@ -663,24 +1729,72 @@ fn galley_from_rows(
         mesh_bounds,
         num_vertices,
         num_indices,
-        pixels_per_point: point_scale.pixels_per_point,
+        pixels_per_point,
     }
 }
 
 #[derive(Default)]
 struct FormatSummary {
     any_background: bool,
+    any_glyph_effects: bool,
     any_underline: bool,
     any_strikethrough: bool,
+
+    /// The underline and strikeout metrics of the font of each section.
+    ///
+    /// Empty if there are no lines to draw.
+    line_metrics: Vec<(Option<LineMetrics>, Option<LineMetrics>)>,
+
+    /// The glyphs for [`LayoutJob::show_whitespace`] in the font of each section.
+    ///
+    /// Empty if whitespace is not shown.
+    whitespace_markers: Vec<WhitespaceMarkers>,
+}
+
+/// The glyphs drawn in place of whitespace, see [`LayoutJob::show_whitespace`].
+struct WhitespaceMarkers {
+    space: GlyphInfo,
+    tab: GlyphInfo,
+    newline: GlyphInfo,
+    ascent: f32,
 }
 
-fn format_summary(job: &LayoutJob) -> FormatSummary {
+fn format_summary(fonts: &LayoutFonts, job: &LayoutJob) -> FormatSummary {
     let mut format_summary = FormatSummary::default();
     for section in &job.sections {
         format_summary.any_background |= section.format.background != Color32::TRANSPARENT;
+        format_summary.any_glyph_effects |=
+            section.format.outline.is_some() || section.format.shadow.is_some();
         format_summary.any_underline |= section.format.underline != Stroke::NONE;
         format_summary.any_strikethrough |= section.format.strikethrough != Stroke::NONE;
     }
+    if format_summary.any_underline || format_summary.any_strikethrough {
+        format_summary.line_metrics = job
+            .sections
+            .iter()
+            .map(|section| {
+                let font = fonts.format_font(&section.format);
+                (font.underline_metrics(), font.strikeout_metrics())
+            })
+            .collect();
+    }
+    if job.show_whitespace.is_some() {
+        format_summary.whitespace_markers = job
+            .sections
+            .iter()
+            .map(|section| {
+                let font = fonts.format_font(&section.format);
+                let (font_impl, space) = font.font_impl_and_glyph_info('·');
+                let ascent = font_impl.map_or(0.0, |font| font.ascent());
+                WhitespaceMarkers {
+                    space,
+                    tab: font.font_impl_and_glyph_info('→').1,
+                    newline: font.font_impl_and_glyph_info('¶').1,
+                    ascent,
+                }
+            })
+            .collect();
+    }
     format_summary
 }
 
@@ -690,7 +1804,8 @@ fn tessellate_row(
     format_summary: &FormatSummary,
     row: &Row,
 ) -> RowVisuals {
-    if row.glyphs.is_empty() {
+    let show_newline = row.ends_with_newline && !format_summary.whitespace_markers.is_empty();
+    if row.glyphs.is_empty() && !show_newline {
         return Default::default();
     }
 
@@ -700,18 +1815,59 @@ fn tessellate_row(
     mesh.reserve_vertices(row.glyphs.len() * 4);
 
     if format_summary.any_background {
-        add_row_backgrounds(job, row, &mut mesh);
+        add_row_backgrounds(point_scale, job, row, &mut mesh);
+    }
+
+    // SDF glyphs use another texture, so they get a mesh of their own:
+    let mut sdf_mesh = Mesh::default();
+
+    // Outlines and shadows go outside of the glyph vertex range, so they won't be recolored:
+    if format_summary.any_glyph_effects {
+        tessellate_glyph_effects(point_scale, job, row, &mut mesh, false);
+        tessellate_glyph_effects(point_scale, job, row, &mut sdf_mesh, true);
     }
 
     let glyph_vertex_start = mesh.vertices.len();
-    tessellate_glyphs(point_scale, job, row, &mut mesh);
+    tessellate_glyphs(point_scale, job, row, &mut mesh, false, false);
     let glyph_vertex_end = mesh.vertices.len();
 
+    // Color glyphs (e.g. emojis) go outside of the glyph vertex range, so they won't be recolored:
+    tessellate_glyphs(point_scale, job, row, &mut mesh, true, false);
+
+    tessellate_glyphs(point_scale, job, row, &mut sdf_mesh, false, true);
+
+    // The whitespace markers go outside of the glyph vertex range, so they keep their dimmed color:
+    if let Some(color) = job.show_whitespace {
+        tessellate_whitespace(
+            point_scale,
+            job,
+            format_summary,
+            row,
+            color,
+            &mut mesh,
+            &mut sdf_mesh,
+        );
+    }
+
+    if job.vertical {
+        // Underline and strikethrough are not supported for vertical text (yet).
+        let mesh_bounds = mesh.calc_bounds().union(sdf_mesh.calc_bounds());
+        return RowVisuals {
+            mesh,
+            mesh_bounds,
+            glyph_vertex_range: glyph_vertex_start..glyph_vertex_end,
+            sdf_mesh,
+        };
+    }
+
     if format_summary.any_underline {
         add_row_hline(point_scale, row, &mut mesh, |glyph| {
             let format = &job.sections[glyph.section_index as usize].format;
             let stroke = format.underline;
-            let y = glyph.logical_rect().bottom();
+            let y = match format_summary.line_metrics[glyph.section_index as usize].0 {
+                Some(underline) => glyph.pos.y + underline.y_offset,
+                None => glyph.logical_rect().bottom(),
+            };
             (stroke, y)
         });
     }
@@ -720,117 +1876,349 @@ fn tessellate_row(
         add_row_hline(point_scale, row, &mut mesh, |glyph| {
             let format = &job.sections[glyph.section_index as usize].format;
             let stroke = format.strikethrough;
-            let y = glyph.logical_rect().center().y;
+            let y = match format_summary.line_metrics[glyph.section_index as usize].1 {
+                Some(strikeout) => glyph.pos.y + strikeout.y_offset,
+                None => glyph.logical_rect().center().y,
+            };
             (stroke, y)
         });
     }
 
-    let mesh_bounds = mesh.calc_bounds();
+    let mesh_bounds = mesh.calc_bounds().union(sdf_mesh.calc_bounds());
 
     RowVisuals {
         mesh,
         mesh_bounds,
         glyph_vertex_range: glyph_vertex_start..glyph_vertex_end,
+        sdf_mesh,
     }
 }
 
 /// Create background for glyphs that have them.
-/// Creates as few rectangular regions as possible.
-fn add_row_backgrounds(job: &LayoutJob, row: &Row, mesh: &mut Mesh) {
-    if row.glyphs.is_empty() {
-        return;
-    }
-
-    let mut end_run = |start: Option<(Color32, Rect)>, stop_x: f32| {
-        if let Some((color, start_rect)) = start {
-            let rect = Rect::from_min_max(start_rect.left_top(), pos2(stop_x, start_rect.bottom()));
-            let rect = rect.expand(1.0); // looks better
-            mesh.add_colored_rect(rect, color);
+///
+/// Contiguous glyphs with the same background share one (possibly rounded) rectangle,
+/// so a run of text that wraps gets one rectangle per row.
+fn add_row_backgrounds(point_scale: PointScale, job: &LayoutJob, row: &Row, mesh: &mut Mesh) {
+    let background = |glyph: &Glyph| {
+        let format = &job.sections[glyph.section_index as usize].format;
+        (format.background != Color32::TRANSPARENT).then_some(Background {
+            color: format.background,
+            rounding: format.background_rounding,
+            expand: format.background_expand,
+        })
+    };
+    let glyph_rect = |glyph: &Glyph| {
+        if job.vertical {
+            Rect::from_x_y_ranges(row.rect.x_range(), glyph.logical_rect().y_range())
+        } else {
+            glyph.logical_rect()
         }
     };
 
-    let mut run_start = None;
-    let mut last_rect = Rect::NAN;
+    // Vertical text isn't reordered, so the glyphs are already in order:
+    let glyphs = if job.vertical {
+        row.glyphs.iter().collect()
+    } else {
+        row.glyphs_in_visual_order()
+    };
 
-    for glyph in &row.glyphs {
-        let format = &job.sections[glyph.section_index as usize].format;
-        let color = format.background;
-        let rect = glyph.logical_rect();
-
-        if color == Color32::TRANSPARENT {
-            end_run(run_start.take(), last_rect.right());
-        } else if let Some((existing_color, start)) = run_start {
-            if existing_color == color
-                && start.top() == rect.top()
-                && start.bottom() == rect.bottom()
+    let mut run: Option<(Background, Rect)> = None;
+    for glyph in glyphs {
+        let glyph_background = background(glyph);
+        match (&mut run, glyph_background) {
+            (Some((run_background, run_rect)), Some(glyph_background))
+                if *run_background == glyph_background =>
             {
                 // continue the same background rectangle
-            } else {
-                end_run(run_start.take(), last_rect.right());
-                run_start = Some((color, rect));
+                *run_rect = run_rect.union(glyph_rect(glyph));
+            }
+            _ => {
+                if let Some((run_background, run_rect)) = run.take() {
+                    run_background.add_to_mesh(point_scale, run_rect, mesh);
+                }
+                run = glyph_background.map(|background| (background, glyph_rect(glyph)));
             }
-        } else {
-            run_start = Some((color, rect));
         }
-
-        last_rect = rect;
     }
+    if let Some((run_background, run_rect)) = run {
+        run_background.add_to_mesh(point_scale, run_rect, mesh);
+    }
+}
 
-    end_run(run_start.take(), last_rect.right());
+/// The background of a [`TextFormat`].
+#[derive(Clone, Copy, PartialEq)]
+struct Background {
+    color: Color32,
+    rounding: Rounding,
+    expand: Vec2,
 }
 
-fn tessellate_glyphs(point_scale: PointScale, job: &LayoutJob, row: &Row, mesh: &mut Mesh) {
-    for glyph in &row.glyphs {
-        let uv_rect = glyph.uv_rect;
-        if !uv_rect.is_nothing() {
-            let mut left_top = glyph.pos + uv_rect.offset;
-            left_top.x = point_scale.round_to_pixel(left_top.x);
-            left_top.y = point_scale.round_to_pixel(left_top.y);
-
-            let rect = Rect::from_min_max(left_top, left_top + uv_rect.size);
-            let uv = Rect::from_min_max(
-                pos2(uv_rect.min[0] as f32, uv_rect.min[1] as f32),
-                pos2(uv_rect.max[0] as f32, uv_rect.max[1] as f32),
-            );
+impl Background {
+    fn add_to_mesh(&self, point_scale: PointScale, rect: Rect, mesh: &mut Mesh) {
+        let rect = rect.expand2(self.expand);
+        if self.rounding == Rounding::ZERO {
+            mesh.add_colored_rect(rect, self.color);
+        } else {
+            let mut points = vec![];
+            crate::tessellator::path::rounded_rectangle(&mut points, rect, self.rounding);
+            let mut path = crate::tessellator::Path::default();
+            path.add_line_loop(&points);
+            let feathering = 1.0 / point_scale.pixels_per_point();
+            path.fill(feathering, self.color, mesh);
+        }
+    }
+}
 
+/// Tessellate either only the normal glyphs, only the color glyphs (e.g. emojis),
+/// or only the SDF glyphs.
+fn tessellate_glyphs(
+    point_scale: PointScale,
+    job: &LayoutJob,
+    row: &Row,
+    mesh: &mut Mesh,
+    colored: bool,
+    sdf: bool,
+) {
+    for glyph in row.glyphs.iter().chain(&row.hyphen) {
+        let uv_rect = glyph.uv_rect;
+        if !uv_rect.is_nothing() && uv_rect.is_colored == colored && uv_rect.is_sdf == sdf {
             let format = &job.sections[glyph.section_index as usize].format;
 
-            let color = format.color;
+            let color = if !colored {
+                format.color
+            } else if format.color == Color32::PLACEHOLDER {
+                Color32::WHITE
+            } else {
+                // Keep the colors of the glyph, but respect the opacity of the text:
+                Color32::from_white_alpha(format.color.a())
+            };
 
-            if format.italics {
-                let idx = mesh.vertices.len() as u32;
-                mesh.add_triangle(idx, idx + 1, idx + 2);
-                mesh.add_triangle(idx + 2, idx + 1, idx + 3);
+            add_glyph(point_scale, job, glyph, Vec2::ZERO, color, mesh);
+        }
+    }
+}
 
-                let top_offset = rect.height() * 0.25 * Vec2::X;
+/// Draw [`LayoutJob::show_whitespace`]: a middle dot centered in each space,
+/// an arrow at the start of each tab and a pilcrow after a row that ends with a newline.
+///
+/// The markers are drawn with the same layout as the whitespace glyphs they replace,
+/// so the advances are unaffected.
+fn tessellate_whitespace(
+    point_scale: PointScale,
+    job: &LayoutJob,
+    format_summary: &FormatSummary,
+    row: &Row,
+    color: Color32,
+    mesh: &mut Mesh,
+    sdf_mesh: &mut Mesh,
+) {
+    if job.vertical {
+        return; // Not supported for vertical text (yet).
+    }
 
-                mesh.vertices.push(Vertex {
-                    pos: rect.left_top() + top_offset,
-                    uv: uv.left_top(),
-                    color,
-                });
-                mesh.vertices.push(Vertex {
-                    pos: rect.right_top() + top_offset,
-                    uv: uv.right_top(),
-                    color,
-                });
-                mesh.vertices.push(Vertex {
-                    pos: rect.left_bottom(),
-                    uv: uv.left_bottom(),
-                    color,
-                });
-                mesh.vertices.push(Vertex {
-                    pos: rect.right_bottom(),
-                    uv: uv.right_bottom(),
-                    color,
-                });
-            } else {
-                mesh.add_rect_with_uv(rect, uv, color);
+    let mut add_marker = |glyph: &Glyph| {
+        let mesh = if glyph.uv_rect.is_sdf {
+            &mut *sdf_mesh
+        } else {
+            &mut *mesh
+        };
+        if !glyph.uv_rect.is_nothing() && !glyph.uv_rect.is_colored {
+            add_glyph(point_scale, job, glyph, Vec2::ZERO, color, mesh);
+        }
+    };
+
+    for glyph in row.glyphs.iter() {
+        let markers = &format_summary.whitespace_markers[glyph.section_index as usize];
+        let (chr, marker, x) = match glyph.chr {
+            ' ' => (
+                '·',
+                markers.space,
+                glyph.pos.x + 0.5 * (glyph.size.x - markers.space.advance_width),
+            ),
+            '\t' => ('→', markers.tab, glyph.pos.x),
+            _ => continue,
+        };
+        add_marker(&Glyph {
+            chr,
+            pos: pos2(x, glyph.pos.y),
+            uv_rect: marker.uv_rect,
+            ..*glyph
+        });
+    }
+
+    if row.ends_with_newline {
+        let (section_index, pos) = if let Some(last) = row.glyphs.last() {
+            (last.section_index, pos2(last.max_x(), last.pos.y))
+        } else {
+            let section_index = row.section_index_at_start;
+            let ascent = format_summary.whitespace_markers[section_index as usize].ascent;
+            (section_index, pos2(row.rect.min.x, row.rect.min.y + ascent))
+        };
+        let markers = &format_summary.whitespace_markers[section_index as usize];
+        add_marker(&Glyph {
+            chr: '¶',
+            pos,
+            size: vec2(markers.newline.advance_width, row.rect.height()),
+            ascent: markers.ascent,
+            uv_rect: markers.newline.uv_rect,
+            kerning: 0.0,
+            section_index,
+            bidi_level: 0,
+        });
+    }
+}
+
+/// Tessellate the [`TextFormat::shadow`] and [`TextFormat::outline`] of either
+/// the normal and color glyphs, or the SDF glyphs.
+///
+/// Both are tinted copies of the glyphs, so they go before the glyphs themselves.
+fn tessellate_glyph_effects(
+    point_scale: PointScale,
+    job: &LayoutJob,
+    row: &Row,
+    mesh: &mut Mesh,
+    sdf: bool,
+) {
+    let glyphs = || {
+        row.glyphs
+            .iter()
+            .chain(&row.hyphen)
+            .filter(|glyph| !glyph.uv_rect.is_nothing() && glyph.uv_rect.is_sdf == sdf)
+    };
+
+    // All shadows go behind all outlines:
+    for glyph in glyphs() {
+        let format = &job.sections[glyph.section_index as usize].format;
+        if let Some(shadow) = format.shadow {
+            for offset in outline_offsets(shadow.spread) {
+                add_glyph(
+                    point_scale,
+                    job,
+                    glyph,
+                    shadow.offset + offset,
+                    shadow.color,
+                    mesh,
+                );
+            }
+        }
+    }
+
+    for glyph in glyphs() {
+        let format = &job.sections[glyph.section_index as usize].format;
+        if let Some((width, color)) = format.outline {
+            for offset in outline_offsets(width) {
+                add_glyph(point_scale, job, glyph, offset, color, mesh);
             }
         }
     }
 }
 
+/// Where to put the copies of a glyph that together make up an outline of the given width.
+fn outline_offsets(width: f32) -> Vec<Vec2> {
+    if width <= 0.0 {
+        return vec![Vec2::ZERO];
+    }
+    (0..8)
+        .map(|i| width * Vec2::angled(i as f32 * std::f32::consts::TAU / 8.0))
+        .collect()
+}
+
+/// Add the quad of one glyph, moved by `offset`.
+fn add_glyph(
+    point_scale: PointScale,
+    job: &LayoutJob,
+    glyph: &Glyph,
+    offset: Vec2,
+    color: Color32,
+    mesh: &mut Mesh,
+) {
+    let uv_rect = glyph.uv_rect;
+    let mut left_top = glyph.pos + uv_rect.offset;
+    left_top.x = point_scale.round_to_pixel(left_top.x);
+    left_top.y = point_scale.round_to_pixel(left_top.y);
+
+    let rect = Rect::from_min_max(left_top, left_top + uv_rect.size).translate(offset);
+    let uv = Rect::from_min_max(
+        pos2(uv_rect.min[0] as f32, uv_rect.min[1] as f32),
+        pos2(uv_rect.max[0] as f32, uv_rect.max[1] as f32),
+    );
+
+    let format = &job.sections[glyph.section_index as usize].format;
+
+    if job.vertical && !is_upright_in_vertical_text(glyph.chr) {
+        add_rotated_glyph(point_scale, glyph, offset, uv, color, mesh);
+    } else if format.italics {
+        let idx = mesh.vertices.len() as u32;
+        mesh.add_triangle(idx, idx + 1, idx + 2);
+        mesh.add_triangle(idx + 2, idx + 1, idx + 3);
+
+        let top_offset = rect.height() * 0.25 * Vec2::X;
+
+        mesh.vertices.push(Vertex {
+            pos: rect.left_top() + top_offset,
+            uv: uv.left_top(),
+            color,
+        });
+        mesh.vertices.push(Vertex {
+            pos: rect.right_top() + top_offset,
+            uv: uv.right_top(),
+            color,
+        });
+        mesh.vertices.push(Vertex {
+            pos: rect.left_bottom(),
+            uv: uv.left_bottom(),
+            color,
+        });
+        mesh.vertices.push(Vertex {
+            pos: rect.right_bottom(),
+            uv: uv.right_bottom(),
+            color,
+        });
+    } else {
+        mesh.add_rect_with_uv(rect, uv, color);
+    }
+}
+
+/// Add a glyph of [`LayoutJob::vertical`] text rotated 90° clockwise,
+/// so that its top faces right and it advances downwards.
+fn add_rotated_glyph(
+    point_scale: PointScale,
+    glyph: &Glyph,
+    translation: Vec2,
+    uv: Rect,
+    color: Color32,
+    mesh: &mut Mesh,
+) {
+    // The baseline origin of the glyph, chosen so that the rotated line is centered in the column:
+    let logical_rect = glyph.logical_rect();
+    let origin = pos2(
+        point_scale.round_to_pixel(logical_rect.right() - glyph.ascent),
+        point_scale.round_to_pixel(logical_rect.top()),
+    );
+
+    // Rotating clockwise takes an offset (x, y) from the origin to (-y, x):
+    let rotated = |offset: Vec2| origin + translation + vec2(-offset.y, offset.x);
+    let offset = glyph.uv_rect.offset;
+    let size = glyph.uv_rect.size;
+
+    let idx = mesh.vertices.len() as u32;
+    mesh.add_triangle(idx, idx + 1, idx + 2);
+    mesh.add_triangle(idx + 2, idx + 1, idx + 3);
+
+    for (offset, uv) in [
+        (offset, uv.left_top()),
+        (offset + vec2(size.x, 0.0), uv.right_top()),
+        (offset + vec2(0.0, size.y), uv.left_bottom()),
+        (offset + size, uv.right_bottom()),
+    ] {
+        mesh.vertices.push(Vertex {
+            pos: rotated(offset),
+            uv,
+            color,
+        });
+    }
+}
+
 /// Add a horizontal line over a row of glyphs with a stroke and y decided by a callback.
 fn add_row_hline(
     point_scale: PointScale,
@@ -847,7 +2235,7 @@ fn add_row_hline(
     let mut line_start = None;
     let mut last_right_x = f32::NAN;
 
-    for glyph in &row.glyphs {
+    for glyph in row.glyphs_in_visual_order() {
         let (stroke, y) = stroke_and_y(glyph);
 
         if stroke == Stroke::NONE {
@@ -918,14 +2306,29 @@ struct RowBreakCandidates {
     /// example.com.
     punctuation: Option<usize>,
 
+    /// Hyphenating a word is bet-
+    /// ter than breaking it anywhere.
+    hyphen: Option<usize>,
+
     /// Breaking after just random character is some
     /// times necessary.
     any: Option<usize>,
 }
 
 impl RowBreakCandidates {
-    fn add(&mut self, index: usize, glyphs: &[Glyph]) {
+    fn add(&mut self, index: usize, glyphs: &[Glyph], is_hyphenation_point: bool, no_break: bool) {
         let chr = glyphs[0].chr;
+        if glyphs.len() > 1 && (chr == ZERO_WIDTH_JOINER || continues_cluster(glyphs[1].chr)) {
+            return; // Never break in the middle of a grapheme cluster
+        }
+        if no_break {
+            // Inside a number or URL, so only as a last resort:
+            self.any = Some(index);
+            return;
+        }
+        if is_hyphenation_point {
+            self.hyphen = Some(index);
+        }
         const NON_BREAKING_SPACE: char = '\u{A0}';
         if chr.is_whitespace() && chr != NON_BREAKING_SPACE {
             self.space = Some(index);
@@ -963,6 +2366,7 @@ impl RowBreakCandidates {
             self.word_boundary()
                 .or(self.dash)
                 .or(self.punctuation)
+                .or(self.hyphen)
                 .or(self.any)
         }
     }
@@ -974,6 +2378,7 @@ impl RowBreakCandidates {
             pre_cjk,
             dash,
             punctuation,
+            hyphen,
             any,
         } = self;
         if space.map_or(false, |s| s < index) {
@@ -991,6 +2396,9 @@ impl RowBreakCandidates {
         if punctuation.map_or(false, |s| s < index) {
             *punctuation = None;
         }
+        if hyphen.map_or(false, |s| s < index) {
+            *hyphen = None;
+        }
         if any.map_or(false, |s| s < index) {
             *any = None;
         }
@@ -1016,6 +2424,17 @@ fn is_cjk(c: char) -> bool {
     is_cjk_ideograph(c) || is_kana(c)
 }
 
+/// Should this character stay upright in [`LayoutJob::vertical`] text, rather than be rotated?
+#[inline]
+fn is_upright_in_vertical_text(c: char) -> bool {
+    is_cjk(c)
+        || ('\u{1100}' <= c && c <= '\u{11FF}') // Hangul Jamo
+        || ('\u{3000}' <= c && c <= '\u{303F}') // CJK Symbols and Punctuation
+        || ('\u{3130}' <= c && c <= '\u{318F}') // Hangul Compatibility Jamo
+        || ('\u{AC00}' <= c && c <= '\u{D7AF}') // Hangul Syllables
+        || ('\u{FF00}' <= c && c <= '\u{FFEF}') // Halfwidth and Fullwidth Forms
+}
+
 #[inline]
 fn is_cjk_break_allowed(c: char) -> bool {
     // See: https://en.wikipedia.org/wiki/Line_breaking_rules_in_East_Asian_languages#Characters_not_permitted_on_the_start_of_a_line.
@@ -1037,6 +2456,327 @@ mod tests {
         assert_eq!(galley.rows.len(), 1);
     }
 
+    #[test]
+    fn test_row_baseline() {
+        let mut fonts = FontsImpl::new(1.0, 1024, FontDefinitions::default());
+
+        let mut layout_job = LayoutJob::default();
+        layout_job.append(
+            "code ",
+            0.0,
+            TextFormat::simple(FontId::monospace(20.0), Color32::WHITE),
+        );
+        layout_job.append(
+            "body",
+            0.0,
+            TextFormat::simple(FontId::proportional(12.0), Color32::WHITE),
+        );
+        layout_job.append("\n\nmore", 0.0, TextFormat::default());
+        let galley = layout(&mut fonts, layout_job.into());
+
+        assert_eq!(galley.rows.len(), 3);
+        let first_row = &galley.rows[0];
+        for glyph in first_row.glyphs.iter() {
+            assert_eq!(glyph.pos.y, first_row.baseline);
+        }
+        assert_eq!(
+            first_row.baseline,
+            fonts.font(&FontId::monospace(20.0)).ascent()
+        );
+        assert!(fonts.font(&FontId::monospace(20.0)).descent() < 0.0);
+
+        // Empty rows have their baseline at the bottom:
+        assert_eq!(galley.rows[1].baseline, galley.rows[1].max_y());
+
+        let last_row = &galley.rows[2];
+        assert!(last_row.min_y() < last_row.baseline && last_row.baseline < last_row.max_y());
+    }
+
+    #[test]
+    fn test_subpixel_positioning() {
+        let text = "Illuminating ill-willed tilling";
+        let layout_text = |subpixel_positioning: bool| {
+            let mut definitions = FontDefinitions::default();
+            for font_data in definitions.font_data.values_mut() {
+                font_data.tweak.subpixel_positioning = subpixel_positioning;
+            }
+            let mut fonts = FontsImpl::new(1.0, 1024, definitions);
+            let job = LayoutJob::simple_singleline(
+                text.to_owned(),
+                FontId::proportional(11.0),
+                Color32::WHITE,
+            );
+            layout(&mut fonts, job.into())
+        };
+
+        let is_whole_pixel = |x: f32| x == x.round();
+
+        let snapped = layout_text(false);
+        assert!(snapped.rows[0]
+            .glyphs
+            .iter()
+            .all(|glyph| is_whole_pixel(glyph.pos.x)));
+
+        let subpixel = layout_text(true);
+        let glyphs = &subpixel.rows[0].glyphs;
+        assert_eq!(glyphs.len(), text.chars().count());
+        assert!(glyphs.iter().any(|glyph| !is_whole_pixel(glyph.pos.x)));
+
+        for glyph in glyphs.iter() {
+            if !glyph.uv_rect.is_nothing() {
+                // The rasterized glyph should still land on whole pixels:
+                let left = glyph.pos.x + glyph.uv_rect.offset.x;
+                assert!((left - left.round()).abs() < 0.2, "{:?}", glyph.chr);
+            }
+        }
+    }
+
+    #[test]
+    fn test_kerning() {
+        let mut fonts = FontsImpl::new(1.0, 1024, FontDefinitions::default());
+
+        let mut layout_av = |kerning: bool| {
+            let text_format = TextFormat {
+                font_id: FontId::proportional(20.0),
+                kerning,
+                ..Default::default()
+            };
+            layout(
+                &mut fonts,
+                LayoutJob::single_section("AV".into(), text_format).into(),
+            )
+        };
+
+        let kerned = layout_av(true);
+        let unkerned = layout_av(false);
+
+        // The kerning applied is recorded, and only between glyphs:
+        assert_eq!(kerned.rows[0].glyphs[0].kerning, 0.0);
+        assert!(kerned.rows[0].glyphs[1].kerning <= 0.0);
+        assert!(unkerned.rows[0].glyphs.iter().all(|g| g.kerning == 0.0));
+
+        // "AV" is a classic kerning pair, so it should never get wider with kerning.
+        #[cfg(not(feature = "shaping"))]
+        {
+            // Note that `ab_glyph` only reads the legacy `kern` table,
+            // which the default fonts don't have, so for them the widths are the same.
+            assert_eq!(
+                kerned.size().x,
+                unkerned.size().x + kerned.rows[0].glyphs[1].kerning
+            );
+            assert!(kerned.size().x <= unkerned.size().x);
+        }
+
+        // With shaping, the kerning from the `GPOS` table is used:
+        #[cfg(feature = "shaping")]
+        assert!(kerned.size().x < unkerned.size().x);
+    }
+
+    #[test]
+    fn test_paragraph_spacing() {
+        use crate::text::cursor::CCursor;
+
+        let mut fonts = FontsImpl::new(1.0, 1024, FontDefinitions::default());
+        let mut job = LayoutJob::single_section(
+            "one\ntwo\n".into(),
+            TextFormat {
+                font_id: FontId::proportional(14.0),
+                line_height: Some(21.0),
+                ..Default::default()
+            },
+        );
+        job.paragraph_spacing = 10.0;
+        let galley = layout(&mut fonts, job.into());
+
+        assert_eq!(galley.rows.len(), 3);
+        for row in &galley.rows {
+            assert_eq!(row.height(), 21.0);
+        }
+        assert_eq!(galley.rows[1].min_y(), galley.rows[0].max_y() + 10.0);
+        assert_eq!(galley.rows[2].min_y(), galley.rows[1].max_y() + 10.0);
+        assert_eq!(galley.size().y, 3.0 * 21.0 + 2.0 * 10.0);
+
+        // The cursor follows the rows:
+        let start_of_two = galley.pos_from_ccursor(CCursor::new(4));
+        assert_eq!(start_of_two.min.y, galley.rows[1].min_y());
+
+        // Clicking between the paragraphs picks the closest row:
+        let gap_top = galley.rows[0].max_y();
+        let cursor = galley.cursor_from_pos(vec2(1.0, gap_top + 2.0));
+        assert_eq!(cursor.rcursor.row, 0);
+        let cursor = galley.cursor_from_pos(vec2(1.0, gap_top + 8.0));
+        assert_eq!(cursor.rcursor.row, 1);
+    }
+
+    #[test]
+    fn test_font_features() {
+        let mut fonts = FontsImpl::new(1.0, 1024, FontDefinitions::default());
+
+        let mut digit_widths = |font_features: &[(FourCC, u32)]| {
+            let text_format = TextFormat {
+                font_id: FontId::proportional(20.0),
+                font_features: font_features.to_vec(),
+                ..Default::default()
+            };
+            let galley = layout(
+                &mut fonts,
+                LayoutJob::single_section("1840".into(), text_format).into(),
+            );
+            galley.rows[0]
+                .glyphs
+                .iter()
+                .map(|glyph| {
+                    assert!(!glyph.uv_rect.is_nothing());
+                    glyph.size.x
+                })
+                .collect::<Vec<f32>>()
+        };
+        let is_tabular = |widths: &[f32]| widths.iter().all(|&w| w == widths[0]);
+
+        // The digits of the default font are tabular by default:
+        let plain = digit_widths(&[]);
+        assert!(is_tabular(&plain));
+        assert_eq!(digit_widths(&[(four_cc("tnum"), 1)]), plain);
+
+        // …but it has proportional ones too:
+        let proportional = digit_widths(&[(four_cc("pnum"), 1)]);
+        assert!(!is_tabular(&proportional));
+        assert!(proportional[0] < plain[0], "'1' is narrow");
+
+        // Turned off features do nothing:
+        assert_eq!(digit_widths(&[(four_cc("pnum"), 0)]), plain);
+    }
+
+    #[test]
+    #[cfg(feature = "shaping")]
+    fn test_shaping() {
+        // Both with font data that lives forever, and with a copy of it that doesn't:
+        let mut owned_definitions = FontDefinitions::default();
+        for font_data in owned_definitions.font_data.values_mut() {
+            font_data.font = font_data.font.to_vec().into();
+        }
+
+        for definitions in [FontDefinitions::default(), owned_definitions] {
+            let mut fonts = FontsImpl::new(1.0, 1024, definitions);
+
+            let text = "office ligatures, cafe\u{301} and tabs\t🙂";
+            let job = LayoutJob::simple_singleline(
+                text.to_owned(),
+                FontId::proportional(20.0),
+                Color32::WHITE,
+            );
+            let galley = layout(&mut fonts, job.into());
+
+            // Still one glyph per character, so that cursors work:
+            let glyphs = &galley.rows[0].glyphs;
+            assert_eq!(glyphs.len(), text.chars().count());
+            assert_eq!(galley.text(), text);
+            for pair in glyphs.windows(2) {
+                assert!(pair[0].pos.x <= pair[1].pos.x);
+            }
+
+            // "ffi" is a ligature in the default font, drawn by its first character:
+            let ffi = &glyphs[1..4];
+            assert!(!ffi[0].uv_rect.is_nothing());
+            assert!(ffi[1].uv_rect.is_nothing() && ffi[2].uv_rect.is_nothing());
+            assert_eq!(ffi[0].size.x, ffi[1].size.x);
+        }
+    }
+
+    #[test]
+    fn test_tab_width() {
+        let mut fonts = FontsImpl::new(1.0, 1024, FontDefinitions::default());
+        let font_id = FontId::monospace(12.0);
+        let space_width = fonts.font(&font_id).glyph_width(' ');
+
+        let mut layout_x_positions = |text: &str, tab_width, tab_stop_width_in_columns| {
+            let mut job = LayoutJob::single_section(
+                text.into(),
+                TextFormat::simple(font_id.clone(), Color32::WHITE),
+            );
+            job.tab_width = tab_width;
+            job.tab_stop_width_in_columns = tab_stop_width_in_columns;
+            let galley = layout(&mut fonts, job.into());
+            galley.rows[0]
+                .glyphs
+                .iter()
+                .map(|g| (g.pos.x / space_width).round())
+                .collect::<Vec<_>>()
+        };
+
+        // The default is a fixed advance of four spaces:
+        assert_eq!(LayoutJob::default().tab_width, crate::text::TAB_SIZE);
+        assert_eq!(layout_x_positions("\tx", 4, None), vec![0.0, 4.0]);
+        assert_eq!(
+            layout_x_positions("ab\tx", 4, None),
+            vec![0.0, 1.0, 2.0, 6.0]
+        );
+        assert_eq!(layout_x_positions("\tx", 2, None), vec![0.0, 2.0]);
+
+        // Tab stops:
+        assert_eq!(
+            layout_x_positions("ab\tx", 4, Some(4)),
+            vec![0.0, 1.0, 2.0, 4.0]
+        );
+        assert_eq!(
+            layout_x_positions("abcd\tx\t\ty", 4, Some(4)),
+            vec![0.0, 1.0, 2.0, 3.0, 4.0, 8.0, 9.0, 12.0, 16.0]
+        );
+        assert_eq!(layout_x_positions("a\tx", 4, Some(2)), vec![0.0, 1.0, 2.0]);
+    }
+
+    #[test]
+    fn test_vertical() {
+        let mut fonts = FontsImpl::new(1.0, 1024, FontDefinitions::default());
+        let font_id = FontId::proportional(14.0);
+        let row_height = fonts.font(&font_id).row_height();
+        let mut job = LayoutJob::single_section(
+            "日本\nab".into(),
+            TextFormat::simple(font_id, Color32::WHITE),
+        );
+        job.vertical = true;
+        let galley = layout(&mut fonts, job.into());
+
+        // One column per paragraph, going right-to-left:
+        assert!(galley.is_vertical());
+        assert_eq!(galley.rows.len(), 2);
+        assert_eq!(galley.rows[0].text(), "日本");
+        assert_eq!(galley.rows[1].text(), "ab");
+        assert!(galley.rows[1].rect.right() <= galley.rows[0].rect.left());
+        assert_eq!(galley.rect.left(), 0.0);
+
+        // Glyphs stack downwards, upright ones by the row height (the default fonts have no vertical metrics):
+        for row in &galley.rows {
+            for pair in row.glyphs.windows(2) {
+                assert!(pair[0].logical_rect().top() < pair[1].logical_rect().top());
+            }
+        }
+        let upright = galley.rows[0].glyphs[0].logical_rect();
+        assert_eq!(upright.height(), row_height);
+        let rotated = galley.rows[1].glyphs[0].logical_rect();
+        assert_eq!(rotated.width(), row_height);
+        assert!(rotated.height() < row_height);
+
+        // The cursor moves along the columns:
+        let column_0 = galley.rows[0].rect;
+        let cursor = galley.cursor_from_pos(vec2(column_0.center().x, upright.bottom() + 1.0));
+        assert_eq!(
+            cursor.rcursor,
+            crate::text::cursor::RCursor { row: 0, column: 1 }
+        );
+        let cursor_rect = galley.pos_from_cursor(&cursor);
+        assert_eq!(cursor_rect.height(), 0.0);
+        assert_eq!(cursor_rect.x_range(), column_0.x_range());
+        assert_eq!(
+            galley.cursor_down_one_row(&cursor).rcursor,
+            crate::text::cursor::RCursor { row: 1, column: 1 }
+        );
+
+        // Right of the text is the start, and left of it the end:
+        assert_eq!(galley.cursor_from_pos(vec2(1000.0, 0.0)), galley.begin());
+        assert_eq!(galley.cursor_from_pos(vec2(-1000.0, 0.0)), galley.end());
+    }
+
     #[test]
     fn test_truncate_with_newline() {
         // No matter where we wrap, we should be appending the newline character.
@@ -1084,6 +2824,390 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_justify() {
+        let mut fonts = FontsImpl::new(1.0, 1024, FontDefinitions::default());
+        let max_width = 200.0;
+        let mut layout = |justify: bool| {
+            let mut layout_job = LayoutJob::single_section(
+                "The quick brown fox jumps over the lazy dog, again and again and again.\nShort row\nThe end"
+                    .into(),
+                TextFormat::simple(FontId::proportional(14.0), Color32::WHITE),
+            );
+            layout_job.wrap.max_width = max_width;
+            layout_job.justify = justify;
+            layout(&mut fonts, layout_job.into())
+        };
+        let justified = layout(true);
+        let ragged = layout(false);
+        assert_eq!(justified.rows.len(), ragged.rows.len());
+        assert!(3 < justified.rows.len());
+
+        for (row, ragged_row) in justified.rows.iter().zip(&ragged.rows) {
+            let is_last_row_of_paragraph = row.ends_with_newline || row.text() == "The end";
+
+            let last_visible = row.glyphs.iter().rfind(|g| !g.chr.is_whitespace()).unwrap();
+            if is_last_row_of_paragraph {
+                // Not stretched:
+                assert_eq!(row.glyphs, ragged_row.glyphs);
+            } else {
+                assert!(
+                    (last_visible.max_x() - max_width).abs() <= 1.0,
+                    "{:?}",
+                    row.text()
+                );
+            }
+
+            for (pair, ragged_pair) in row.glyphs.windows(2).zip(ragged_row.glyphs.windows(2)) {
+                let gap = pair[1].pos.x - pair[0].max_x();
+                if pair[0].chr.is_whitespace() {
+                    // The spaces are widened to cover the extra space:
+                    assert!(pair[0].size.x >= ragged_pair[0].size.x);
+                    assert!(gap.abs() <= 1.0, "{gap}");
+                } else {
+                    // …and the letters of a word stay together:
+                    let ragged_gap = ragged_pair[1].pos.x - ragged_pair[0].max_x();
+                    assert!((gap - ragged_gap).abs() <= 1.0, "{gap} vs {ragged_gap}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_rects_for_char_range() {
+        let mut fonts = FontsImpl::new(1.0, 1024, FontDefinitions::default());
+        let mut layout_job = LayoutJob::single_section(
+            "Some words that wrap\n\nend".into(),
+            TextFormat::simple(FontId::monospace(12.0), Color32::WHITE),
+        );
+        layout_job.wrap.max_width = 80.0;
+        let galley = layout(&mut fonts, layout_job.into());
+        let rows: Vec<String> = galley.rows.iter().map(|row| row.text()).collect();
+        assert_eq!(rows, ["Some words ", "that wrap", "", "end"]);
+
+        // Within a row:
+        let rects = galley.rects_for_char_range(1..3);
+        assert_eq!(rects.len(), 1);
+        assert_eq!(rects[0].left(), galley.glyph_bounds(1).unwrap().left());
+        assert_eq!(rects[0].right(), galley.glyph_bounds(3).unwrap().left());
+        assert_eq!(rects[0].y_range(), galley.rows[0].rect.y_range());
+
+        // Wrapping across rows, one rect per row:
+        let rects = galley.rects_for_char_range(5..14);
+        assert_eq!(rects.len(), 2);
+        for (rect, row) in rects.iter().zip(&galley.rows) {
+            assert_eq!(rect.y_range(), row.rect.y_range());
+        }
+        assert_eq!(rects[0].left(), galley.glyph_bounds(5).unwrap().left());
+        assert_eq!(rects[1].left(), galley.rows[1].rect.left());
+        assert_eq!(rects[1].right(), galley.glyph_bounds(14).unwrap().left());
+
+        // The first glyph of a wrapped row is on that row:
+        let bounds = galley.glyph_bounds(11).unwrap();
+        assert!(galley.rows[1].rect.y_range().contains(bounds.center().y));
+
+        // Newlines, including the one making up the empty row, are half a row height wide:
+        let newline = galley.glyph_bounds(20).unwrap();
+        assert_eq!(newline.left(), galley.rows[1].rect.right());
+        assert!((newline.width() - galley.rows[1].height() / 2.0).abs() < 1e-3);
+        let rects = galley.rects_for_char_range(20..22);
+        assert_eq!(rects.len(), 2);
+        assert!((rects[1].width() - galley.rows[2].height() / 2.0).abs() < 1e-3);
+
+        // An empty range, or past the end, covers nothing:
+        assert!(galley.rects_for_char_range(3..3).is_empty());
+        assert!(galley.rects_for_char_range(25..30).is_empty());
+        assert_eq!(galley.glyph_bounds(25), None);
+        assert!(galley.glyph_bounds(24).is_some());
+    }
+
+    #[test]
+    fn test_bidi() {
+        let mut fonts = FontsImpl::new(1.0, 1024, FontDefinitions::default());
+        let format = TextFormat::simple(FontId::monospace(12.0), Color32::WHITE);
+
+        // Left-to-right text is unaffected:
+        let galley = layout(
+            &mut fonts,
+            LayoutJob::single_section("abc (x) def".into(), format.clone()).into(),
+        );
+        let row = &galley.rows[0];
+        assert!(!row.has_rtl());
+        assert!(row.glyphs.windows(2).all(|w| w[0].pos.x < w[1].pos.x));
+
+        let text = "ab שלום (x) cd";
+        let galley = layout(
+            &mut fonts,
+            LayoutJob::single_section(text.into(), format.clone()).into(),
+        );
+        let row = &galley.rows[0];
+        assert!(row.has_rtl());
+        let rtl: Vec<bool> = row.glyphs.iter().map(|glyph| glyph.is_rtl()).collect();
+        assert_eq!(&rtl[..7], [false, false, false, true, true, true, true]);
+        assert!(!rtl[text.chars().count() - 1]);
+
+        // The Hebrew word runs right-to-left, but stays between the left-to-right words:
+        let x = |i: usize| row.glyphs[i].pos.x;
+        assert!(x(3) > x(4) && x(4) > x(5) && x(5) > x(6));
+        assert!(x(1) < x(6) && x(3) < x(8));
+
+        // No two glyphs end up in the same place:
+        let visual = row.glyphs_in_visual_order();
+        assert!(visual.windows(2).all(|w| w[0].pos.x < w[1].pos.x));
+
+        // Cursor positions round-trip through the logical indices:
+        for index in 0..=text.chars().count() {
+            let cursor = galley.from_ccursor(cursor::CCursor::new(index));
+            let pos = galley.pos_from_cursor(&cursor).center();
+            assert_eq!(galley.cursor_from_pos(pos.to_vec2()).ccursor.index, index);
+        }
+
+        // Home and End go to the visual ends of the row:
+        let text = "שלום";
+        let galley = layout(
+            &mut fonts,
+            LayoutJob::single_section(text.into(), format.clone()).into(),
+        );
+        let cursor = galley.from_ccursor(cursor::CCursor::new(2));
+        assert_eq!(galley.cursor_left_end_of_row(&cursor).ccursor.index, 4);
+        assert_eq!(galley.cursor_right_end_of_row(&cursor).ccursor.index, 0);
+
+        // Brackets are mirrored in right-to-left text:
+        let galley = layout(
+            &mut fonts,
+            LayoutJob::single_section("(".into(), format.clone()).into(),
+        );
+        let open_uv = galley.rows[0].glyphs[0].uv_rect;
+        let galley = layout(
+            &mut fonts,
+            LayoutJob::single_section(")".into(), format.clone()).into(),
+        );
+        let close_uv = galley.rows[0].glyphs[0].uv_rect;
+        let galley = layout(
+            &mut fonts,
+            LayoutJob::single_section("א(ב".into(), format).into(),
+        );
+        let bracket = &galley.rows[0].glyphs[1];
+        assert!(bracket.is_rtl());
+        assert_eq!(bracket.uv_rect, close_uv);
+        assert_ne!(bracket.uv_rect, open_uv);
+    }
+
+    #[test]
+    fn test_background_runs() {
+        let mut fonts = FontsImpl::new(1.0, 1024, FontDefinitions::default());
+        let font_id = FontId::monospace(12.0);
+        let code_format = TextFormat {
+            background: Color32::DARK_GRAY,
+            ..TextFormat::simple(font_id.clone(), Color32::WHITE)
+        };
+        let mut layout_job = LayoutJob::default();
+        layout_job.append("call ", 0.0, TextFormat::simple(font_id, Color32::WHITE));
+        layout_job.append("let value = compute(argument);", 0.0, code_format.clone());
+        layout_job.wrap.max_width = 130.0;
+        let galley = layout(&mut fonts, layout_job.clone().into());
+        let rows: Vec<String> = galley.rows.iter().map(|row| row.text()).collect();
+        assert_eq!(rows, ["call let value = ", "compute(argument);"]);
+
+        // One rectangle per row, covering the whole run plus the padding:
+        for row in &galley.rows {
+            assert_eq!(row.visuals.glyph_vertex_range.start, 4);
+            let background = Rect::from_points(
+                &row.visuals.mesh.vertices[..4]
+                    .iter()
+                    .map(|vertex| vertex.pos)
+                    .collect::<Vec<_>>(),
+            );
+            let code_glyphs: Vec<&Glyph> = row
+                .glyphs
+                .iter()
+                .filter(|glyph| glyph.section_index == 1)
+                .collect();
+            let left = code_glyphs.first().unwrap().pos.x;
+            let right = code_glyphs.last().unwrap().max_x();
+            assert_eq!(background.left(), left - 1.0);
+            assert_eq!(background.right(), right + 1.0);
+        }
+
+        // Rounded backgrounds are filled with an anti-aliased outline:
+        let rounded_format = TextFormat {
+            background_rounding: Rounding::same(4.0),
+            background_expand: vec2(3.0, 0.0),
+            ..code_format
+        };
+        layout_job.sections[1].format = rounded_format;
+        let galley = layout(&mut fonts, layout_job.into());
+        for row in &galley.rows {
+            let background_vertices = row.visuals.glyph_vertex_range.start;
+            assert!(4 < background_vertices);
+            let bounds = Rect::from_points(
+                &row.visuals.mesh.vertices[..background_vertices]
+                    .iter()
+                    .map(|vertex| vertex.pos)
+                    .collect::<Vec<_>>(),
+            );
+            assert!(row.rect.y_range().contains(bounds.center().y));
+            assert!(bounds.height() <= row.height() + 1.0);
+        }
+    }
+
+    #[test]
+    fn test_outline_and_shadow() {
+        let mut fonts = FontsImpl::new(1.0, 1024, FontDefinitions::default());
+        let plain = TextFormat::simple(FontId::proportional(14.0), Color32::WHITE);
+        let galley = layout(
+            &mut fonts,
+            LayoutJob::single_section("Hi".into(), plain.clone()).into(),
+        );
+        let plain_row = &galley.rows[0];
+        assert_eq!(plain_row.visuals.glyph_vertex_range, 0..8);
+
+        let shadow = crate::Shadow {
+            offset: vec2(2.0, 3.0),
+            color: Color32::BLACK,
+            ..Default::default()
+        };
+        let format = TextFormat {
+            outline: Some((1.0, Color32::RED)),
+            shadow: Some(shadow),
+            ..plain
+        };
+        let galley = layout(
+            &mut fonts,
+            LayoutJob::single_section("Hi".into(), format).into(),
+        );
+        let row = &galley.rows[0];
+        let vertices = &row.visuals.mesh.vertices;
+
+        // One shadow quad and eight outline quads per glyph go before the glyphs:
+        let effect_vertices = 2 * 4 + 2 * 8 * 4;
+        assert_eq!(
+            row.visuals.glyph_vertex_range,
+            effect_vertices..effect_vertices + 8
+        );
+        let glyph_vertices = &plain_row.visuals.mesh.vertices;
+        for (i, shadow_vertex) in vertices[..8].iter().enumerate() {
+            assert_eq!(shadow_vertex.color, Color32::BLACK);
+            assert_eq!(shadow_vertex.pos, glyph_vertices[i].pos + shadow.offset);
+        }
+        for outline_vertex in &vertices[8..effect_vertices] {
+            assert_eq!(outline_vertex.color, Color32::RED);
+        }
+        assert!(vertices[effect_vertices..]
+            .iter()
+            .all(|vertex| vertex.color == Color32::WHITE));
+        assert!(galley.rows[0]
+            .visuals
+            .mesh_bounds
+            .contains_rect(plain_row.visuals.mesh_bounds.translate(shadow.offset)));
+    }
+
+    #[test]
+    fn test_hyphenation() {
+        let mut fonts = FontsImpl::new(1.0, 1024, FontDefinitions::default());
+        let format = TextFormat::simple(FontId::monospace(12.0), Color32::WHITE);
+        let char_width = fonts.format_font(&format).glyph_width('x');
+        let row_texts =
+            |galley: &Galley| -> Vec<String> { galley.rows.iter().map(|row| row.text()).collect() };
+
+        // Soft hyphens are invisible, unless we break the row there:
+        let text = "Donau\u{AD}dampf\u{AD}schiff\u{AD}fahrt";
+        let mut layout_job = LayoutJob::single_section(text.into(), format.clone());
+        let galley = layout(&mut fonts, layout_job.clone().into());
+        assert_eq!(galley.rows.len(), 1);
+        assert_eq!(galley.rows[0].hyphen, None);
+        let without_soft_hyphens = layout(
+            &mut fonts,
+            LayoutJob::single_section(text.replace('\u{AD}', ""), format.clone()).into(),
+        );
+        assert_eq!(galley.size(), without_soft_hyphens.size());
+
+        layout_job.wrap.max_width = 12.5 * char_width;
+        let galley = layout(&mut fonts, layout_job.clone().into());
+        assert_eq!(
+            row_texts(&galley),
+            ["Donau\u{AD}dampf\u{AD}", "schiff\u{AD}fahrt"]
+        );
+        let hyphen = galley.rows[0].hyphen.unwrap();
+        assert_eq!(hyphen.chr, '-');
+        assert!(!hyphen.uv_rect.is_nothing());
+        assert_eq!(hyphen.pos.y, galley.rows[0].glyphs[0].pos.y);
+        assert_eq!(galley.rows[0].rect.right(), hyphen.max_x());
+        assert_eq!(galley.rows[1].hyphen, None);
+
+        // The hyphen is painted, but it is not part of the text:
+        assert_eq!(galley.rows[0].visuals.glyph_vertex_range.len(), 11 * 4);
+        assert_eq!(galley.end().ccursor.index, text.chars().count());
+
+        // A hyphenator finds more places to break words:
+        let text = "Sehr lange Wortzusammensetzung";
+        let mut layout_job = LayoutJob::single_section(text.into(), format);
+        layout_job.wrap.max_width = 12.5 * char_width;
+        let galley = layout(&mut fonts, layout_job.clone().into());
+        assert_eq!(
+            row_texts(&galley),
+            ["Sehr lange ", "Wortzusammen", "setzung"]
+        );
+
+        layout_job.wrap.hyphenator = Some(Hyphenator::new(|word| {
+            if word == "Wortzusammensetzung" {
+                vec![4, 6, 9, 12, 15] // Wort-zu-sam-men-set-zung
+            } else {
+                vec![]
+            }
+        }));
+        let galley = layout(&mut fonts, layout_job.clone().into());
+
+        // There is no room for a hyphen after `Wortzusammen`:
+        assert_eq!(
+            row_texts(&galley),
+            ["Sehr lange ", "Wortzusam", "mensetzung"]
+        );
+        assert!(galley.rows[1].rect.width() <= layout_job.wrap.max_width);
+        assert_eq!(galley.rows[0].hyphen, None);
+        assert!(galley.rows[1].hyphen.is_some());
+        assert_eq!(galley.rows[2].hyphen, None);
+
+        // Breaking anywhere doesn't add a hyphen that there is no room for:
+        let mut layout_job =
+            LayoutJob::single_section("abc\u{AD}def".into(), layout_job.sections[0].format.clone());
+        layout_job.wrap.max_width = 3.5 * char_width;
+        layout_job.wrap.break_anywhere = true;
+        let galley = layout(&mut fonts, layout_job.clone().into());
+        assert_eq!(row_texts(&galley), ["abc\u{AD}", "def"]);
+        assert_eq!(galley.rows[0].hyphen, None);
+        assert!(galley.rows[0].rect.width() <= layout_job.wrap.max_width);
+    }
+
+    #[test]
+    fn test_truncate_at_width() {
+        let mut fonts = FontsImpl::new(1.0, 1024, FontDefinitions::default());
+        let text = "A long table cell that doesn't fit";
+        let max_width = 60.0;
+        let mut layout_job = LayoutJob::single_section(
+            text.into(),
+            TextFormat::simple(FontId::proportional(14.0), Color32::WHITE),
+        );
+        layout_job.wrap = TextWrapping::from_wrap_mode_and_width(TextWrapMode::Truncate, max_width);
+        let galley = layout(&mut fonts, layout_job.into());
+
+        // Trailing glyphs are dropped until the `…` fits:
+        assert!(galley.elided);
+        assert_eq!(galley.rows.len(), 1);
+        let row = &galley.rows[0];
+        let row_text = row.text();
+        assert!(row_text.ends_with('…'), "{row_text:?}");
+        assert!(text.starts_with(row_text.trim_end_matches('…')));
+        assert!(row.rect.width() <= max_width, "{}", row.rect.width());
+
+        // The full text is still there for copying:
+        assert_eq!(galley.text(), text);
+
+        // …but only the visible glyphs can be hit:
+        let cursor = galley.cursor_from_pos(vec2(1000.0, 0.0));
+        assert_eq!(cursor.ccursor.index, row.char_count_including_newline());
+    }
+
     #[test]
     fn test_cjk() {
         let mut fonts = FontsImpl::new(1.0, 1024, FontDefinitions::default());
@@ -1113,4 +3237,220 @@ mod tests {
             vec!["日本語とEnglish", "の混在した文章"]
         );
     }
+
+    #[test]
+    #[cfg(not(feature = "shaping"))]
+    fn test_grapheme_clusters() {
+        let mut fonts = FontsImpl::new(1.0, 1024, FontDefinitions::default());
+        let mut layout_text = |text: &str| {
+            let job = LayoutJob::single_section(text.into(), TextFormat::default());
+            layout(&mut fonts, job.into())
+        };
+
+        // Composed into a single glyph, but still counting two chars:
+        let decomposed = layout_text("e\u{301}x");
+        let precomposed = layout_text("éx");
+        let glyphs = &decomposed.rows[0].glyphs;
+        assert_eq!(glyphs.len(), 3);
+        assert_eq!(glyphs[0].chr, 'e');
+        assert_eq!(glyphs[0].uv_rect, precomposed.rows[0].glyphs[0].uv_rect);
+        assert_eq!(glyphs[1].size.x, 0.0);
+        assert!(glyphs[1].uv_rect.is_nothing());
+        assert_eq!(glyphs[2].pos.x, precomposed.rows[0].glyphs[1].pos.x);
+        assert_eq!(decomposed.end().ccursor.index, 3);
+
+        // No precomposed `x́`, so the mark is centered over the base glyph instead:
+        let galley = layout_text("x\u{301}");
+        let [base, mark] = [galley.rows[0].glyphs[0], galley.rows[0].glyphs[1]];
+        assert_eq!(mark.size.x, 0.0);
+        assert!(!mark.uv_rect.is_nothing());
+        let base_center = base.pos.x + base.uv_rect.offset.x + 0.5 * base.uv_rect.size.x;
+        let mark_center = mark.pos.x + mark.uv_rect.offset.x + 0.5 * mark.uv_rect.size.x;
+        assert!((base_center - mark_center).abs() < 0.01);
+        assert!(mark.uv_rect.offset.y + mark.uv_rect.size.y <= base.uv_rect.offset.y);
+
+        // Only the first emoji of a ZWJ sequence is shown:
+        let galley = layout_text("👨\u{200D}👩");
+        let glyphs = &galley.rows[0].glyphs;
+        assert_eq!(glyphs.len(), 3);
+        assert!(glyphs[1..]
+            .iter()
+            .all(|g| g.size.x == 0.0 && g.uv_rect.is_nothing()));
+        assert_eq!(galley.rect.width(), layout_text("👨").rect.width());
+    }
+
+    #[test]
+    fn test_click_bias() {
+        let mut fonts = FontsImpl::new(1.0, 1024, FontDefinitions::default());
+        let mut layout_text = |text: &str| {
+            let job = LayoutJob::single_section(text.into(), TextFormat::default());
+            layout(&mut fonts, job.into())
+        };
+
+        // The cursor goes to the nearest side of the clicked glyph:
+        let galley = layout_text("aWb");
+        let row = &galley.rows[0];
+        let w = row.glyphs[1];
+        let y = row.rect.center().y;
+        let click = |fraction: f32| {
+            let pos = vec2(w.pos.x + fraction * w.size.x, y);
+            galley.cursor_from_pos(pos).ccursor.index
+        };
+        assert_eq!(click(0.0), 1);
+        assert_eq!(click(0.25), 1);
+        assert_eq!(click(0.49), 1);
+        assert_eq!(click(0.51), 2);
+        assert_eq!(click(0.75), 2);
+        assert_eq!(click(0.99), 2);
+
+        // Not between a letter and its combining mark:
+        if !cfg!(feature = "shaping") {
+            let galley = layout_text("e\u{301}x");
+            let row = &galley.rows[0];
+            let e = row.glyphs[0];
+            let y = row.rect.center().y;
+            let click = |fraction: f32| {
+                let pos = vec2(e.pos.x + fraction * e.size.x, y);
+                galley.cursor_from_pos(pos).ccursor.index
+            };
+            assert_eq!(click(0.25), 0);
+            assert_eq!(click(0.75), 2);
+            assert_eq!(click(1.0), 2);
+        }
+    }
+
+    #[test]
+    fn test_no_break_classes() {
+        let mut fonts = FontsImpl::new(1.0, 1024, FontDefinitions::default());
+        let format = TextFormat::simple(FontId::monospace(12.0), Color32::WHITE);
+        let char_width = fonts.format_font(&format).glyph_width('x');
+        let mut row_texts = |text: &str, width_in_chars: f32, no_break: NoBreakClasses| {
+            let mut job = LayoutJob::single_section(text.into(), format.clone());
+            job.wrap.max_width = width_in_chars * char_width;
+            job.wrap.no_break = no_break;
+            let galley = layout(&mut fonts, job.into());
+
+            // Never overflow:
+            for row in &galley.rows {
+                assert!(row.rect.width() <= width_in_chars * char_width + 0.5);
+            }
+            let rows: Vec<String> = galley.rows.iter().map(|row| row.text()).collect();
+            assert_eq!(rows.concat(), text);
+            rows
+        };
+        let all = NoBreakClasses::default();
+        let none = NoBreakClasses::NONE;
+
+        // Numbers:
+        assert_eq!(
+            row_texts("sum=1,234,567", 10.5, none),
+            ["sum=1,234,", "567"]
+        );
+        assert_eq!(row_texts("sum=1,234,567", 10.5, all), ["sum=", "1,234,567"]);
+
+        // URLs:
+        let url = "https://example.com/path";
+        assert_eq!(row_texts(url, 16.5, none), ["https://example.", "com/path"]);
+        assert_eq!(row_texts(url, 16.5, all), ["https://", "example.com/path"]);
+
+        // A number or URL that is wider than the row is broken anywhere:
+        assert_eq!(
+            row_texts("1,234,567,890", 5.5, all),
+            ["1,234", ",567,", "890"]
+        );
+        assert_eq!(
+            row_texts("https://abcdefghijklmno", 10.5, all),
+            ["https://", "abcdefghij", "klmno"]
+        );
+    }
+
+    #[test]
+    fn test_show_whitespace() {
+        let mut fonts = FontsImpl::new(1.0, 1024, FontDefinitions::default());
+        let format = TextFormat::simple(FontId::monospace(12.0), Color32::WHITE);
+        let marker_color = Color32::from_rgb(1, 2, 3);
+        let mut layout_text = |show_whitespace: Option<Color32>| {
+            let mut job = LayoutJob::single_section("a b\tc\n\nd".into(), format.clone());
+            job.show_whitespace = show_whitespace;
+            layout(&mut fonts, job.into())
+        };
+        let plain = layout_text(None);
+        let shown = layout_text(Some(marker_color));
+
+        // The layout is unaffected:
+        assert_eq!(plain.rows.len(), shown.rows.len());
+        for (plain_row, shown_row) in plain.rows.iter().zip(&shown.rows) {
+            assert_eq!(plain_row.glyphs, shown_row.glyphs);
+            assert_eq!(plain_row.rect, shown_row.rect);
+        }
+
+        // One marker for the space, one for the tab, and one for each of the two newlines:
+        let marker_vertices = |row: &Row| {
+            row.visuals
+                .mesh
+                .vertices
+                .iter()
+                .chain(&row.visuals.sdf_mesh.vertices)
+                .filter(|vertex| vertex.color == marker_color)
+                .count()
+        };
+        let markers: Vec<usize> = shown.rows.iter().map(marker_vertices).collect();
+        assert_eq!(markers, [3 * 4, 4, 0]);
+        assert_eq!(shown.num_vertices, plain.num_vertices + 4 * 4);
+
+        // The markers are not recolored together with the text:
+        let row = &shown.rows[0];
+        assert!(
+            row.visuals.mesh.vertices[row.visuals.glyph_vertex_range.clone()]
+                .iter()
+                .all(|vertex| vertex.color == Color32::WHITE)
+        );
+
+        // The tab arrow starts at the tab:
+        let tab = row.glyphs[3];
+        let arrow_left = row
+            .visuals
+            .mesh
+            .vertices
+            .iter()
+            .filter(|vertex| vertex.color == marker_color && vertex.pos.x >= tab.pos.x - 1.0)
+            .map(|vertex| vertex.pos.x)
+            .fold(f32::INFINITY, f32::min);
+        assert!(arrow_left < tab.pos.x + 0.5 * tab.size.x);
+    }
+
+    #[test]
+    fn test_color_emoji() {
+        let mut fonts = FontsImpl::new(1.0, 1024, FontDefinitions::default());
+        let color = Color32::from_rgba_unmultiplied(255, 0, 0, 128);
+        let job = Arc::new(LayoutJob::single_section(
+            "a😀b".into(),
+            TextFormat::simple(FontId::proportional(14.0), color),
+        ));
+        let galley = layout(&mut fonts, job.clone());
+        let row = &galley.rows[0];
+        assert_eq!(row.glyphs.len(), 3);
+        assert!(row.glyphs.iter().all(|glyph| !glyph.uv_rect.is_nothing()));
+
+        // The default emoji fonts are monochrome, so the emoji is tinted like any other glyph:
+        assert!(row.glyphs.iter().all(|glyph| !glyph.uv_rect.is_colored));
+        assert_eq!(row.visuals.glyph_vertex_range, 0..12);
+
+        // Pretend the emoji came from a color emoji font:
+        let mut colored_row = row.clone();
+        Arc::make_mut(&mut colored_row.glyphs)[1].uv_rect.is_colored = true;
+        let format_summary = format_summary(&fonts.layout_fonts(&job), &job);
+        let visuals = tessellate_row(PointScale::new(1.0), &job, &format_summary, &colored_row);
+
+        // The emoji goes after the glyphs that are recolored with the text, and keeps its own colors:
+        assert_eq!(visuals.glyph_vertex_range, 0..8);
+        assert_eq!(visuals.mesh.vertices.len(), 12);
+        assert!(visuals.mesh.vertices[..8]
+            .iter()
+            .all(|vertex| vertex.color == color));
+        assert!(visuals.mesh.vertices[8..]
+            .iter()
+            .all(|vertex| vertex.color == Color32::from_white_alpha(color.a())));
+        assert_eq!(visuals.mesh_bounds, row.visuals.mesh_bounds);
+    }
 }
@@ -5,7 +5,7 @@ use std::ops::Range;
 use std::sync::Arc;
 
 use super::{cursor::*, font::UvRect};
-use crate::{Color32, FontId, Mesh, Stroke};
+use crate::{Color32, FontId, Mesh, Rounding, Shadow, Stroke};
 use emath::*;
 
 /// Describes the task of laying out text.
@@ -69,12 +69,60 @@ pub struct LayoutJob {
     /// Default: `true`.
     pub break_on_newline: bool,
 
+    /// Extra space between paragraphs, in points.
+    ///
+    /// This is added after each row that ends with a `\n`,
+    /// or between the columns of [`Self::vertical`] text.
+    ///
+    /// Default: 0.0.
+    pub paragraph_spacing: f32,
+
     /// How to horizontally align the text (`Align::LEFT`, `Align::Center`, `Align::RIGHT`).
     pub halign: Align,
 
     /// Justify text so that word-wrapped rows fill the whole [`TextWrapping::max_width`].
+    ///
+    /// The extra space is added to the spaces between the words
+    /// (or between the letters, for rows without spaces).
+    /// The last row of each paragraph is not stretched.
     pub justify: bool,
 
+    /// How far a tab character (`\t`) advances, in widths of a space.
+    ///
+    /// Ignored if [`Self::tab_stop_width_in_columns`] is set.
+    ///
+    /// Default: [`crate::text::TAB_SIZE`].
+    pub tab_width: usize,
+
+    /// If set, a tab character (`\t`) advances to the next tab stop instead of by a fixed width.
+    ///
+    /// The tab stops are this many columns (widths of a space) apart,
+    /// counting from the start of the paragraph.
+    ///
+    /// Default: `None`.
+    pub tab_stop_width_in_columns: Option<usize>,
+
+    /// Lay out the text top-to-bottom, in columns going right-to-left, as is traditional for CJK.
+    ///
+    /// Each paragraph becomes one column, i.e. one [`Row`] of the resulting [`Galley`].
+    /// Glyphs are stacked using the vertical advance of the font, falling back to the row height.
+    /// CJK glyphs stay upright, while other glyphs (e.g. Latin) are rotated 90° clockwise.
+    ///
+    /// Columns are only broken at `\n`: [`Self::wrap`], [`Self::halign`] and [`Self::justify`] are ignored,
+    /// and underline and strikethrough are not drawn.
+    ///
+    /// Default: `false`.
+    pub vertical: bool,
+
+    /// If set, whitespace is drawn visibly in this color:
+    /// a middle dot for each space, an arrow for each tab and a pilcrow at the end of each line.
+    ///
+    /// Only what is drawn changes: the chars, advances and cursor positions of the galley
+    /// are the same as without this option.
+    ///
+    /// Default: `None`.
+    pub show_whitespace: Option<Color32>,
+
     /// Rounding to the closest ui point (not pixel!) allows the rest of the
     /// layout code to run on perfect integers, avoiding rounding errors.
     pub round_output_size_to_nearest_ui_point: bool,
@@ -89,8 +137,13 @@ impl Default for LayoutJob {
             wrap: Default::default(),
             first_row_min_height: 0.0,
             break_on_newline: true,
+            paragraph_spacing: 0.0,
             halign: Align::LEFT,
             justify: false,
+            tab_width: crate::text::TAB_SIZE,
+            tab_stop_width_in_columns: None,
+            vertical: false,
+            show_whitespace: None,
             round_output_size_to_nearest_ui_point: true,
         }
     }
@@ -183,8 +236,13 @@ impl std::hash::Hash for LayoutJob {
             wrap,
             first_row_min_height,
             break_on_newline,
+            paragraph_spacing,
             halign,
             justify,
+            tab_width,
+            tab_stop_width_in_columns,
+            vertical,
+            show_whitespace,
             round_output_size_to_nearest_ui_point,
         } = self;
 
@@ -193,8 +251,13 @@ impl std::hash::Hash for LayoutJob {
         wrap.hash(state);
         emath::OrderedFloat(*first_row_min_height).hash(state);
         break_on_newline.hash(state);
+        emath::OrderedFloat(*paragraph_spacing).hash(state);
         halign.hash(state);
         justify.hash(state);
+        tab_width.hash(state);
+        tab_stop_width_in_columns.hash(state);
+        vertical.hash(state);
+        show_whitespace.hash(state);
         round_output_size_to_nearest_ui_point.hash(state);
     }
 }
@@ -229,6 +292,24 @@ impl std::hash::Hash for LayoutSection {
 
 // ----------------------------------------------------------------------------
 
+/// An `OpenType` tag, e.g. `*b"tnum"`.
+///
+/// See [`TextFormat::font_features`].
+pub type FourCC = [u8; 4];
+
+/// Turn a tag like `"tnum"` into a [`FourCC`].
+///
+/// Shorter tags are padded with spaces, and longer tags are truncated.
+pub fn four_cc(tag: &str) -> FourCC {
+    let mut four_cc = *b"    ";
+    for (dst, src) in four_cc.iter_mut().zip(tag.bytes()) {
+        *dst = src;
+    }
+    four_cc
+}
+
+// ----------------------------------------------------------------------------
+
 /// Formatting option for a section of text.
 #[derive(Clone, Debug, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
@@ -242,6 +323,14 @@ pub struct TextFormat {
     /// For even text it is recommended you round this to an even number of _pixels_.
     pub extra_letter_spacing: f32,
 
+    /// Adjust the spacing between pairs of glyphs using the kerning table of the font.
+    ///
+    /// Turn this off to get the plain advance widths of the glyphs.
+    /// The applied kerning is recorded in [`Glyph::kerning`].
+    ///
+    /// Default: `true`.
+    pub kerning: bool,
+
     /// Explicit line height of the text in points.
     ///
     /// This is the distance between the bottom row of two subsequent lines of text.
@@ -251,17 +340,73 @@ pub struct TextFormat {
     /// For even text it is recommended you round this to an even number of _pixels_.
     pub line_height: Option<f32>,
 
+    /// `OpenType` features to turn on or off, e.g. `(*b"tnum", 1)` for tabular numbers,
+    /// or `(*b"smcp", 1)` for small caps. A value of `0` turns a feature off.
+    ///
+    /// With the `shaping` feature these are passed on to the shaper.
+    /// Otherwise only the single substitutions of the features are applied,
+    /// which is enough for most uses of e.g. `tnum`, `smcp` and `ss01`.
+    ///
+    /// Glyphs with substitutions are not affected by [`crate::text::FontTweak::subpixel_positioning`].
+    ///
+    /// Default: empty.
+    pub font_features: Vec<(FourCC, u32)>,
+
     /// Text color
     pub color: Color32,
 
     pub background: Color32,
 
+    /// Rounding of the corners of the [`Self::background`].
+    ///
+    /// Contiguous glyphs with the same background share one rectangle per row,
+    /// so this rounds the ends of the whole run, e.g. for inline code.
+    ///
+    /// Default: [`Rounding::ZERO`].
+    pub background_rounding: Rounding,
+
+    /// How far the [`Self::background`] reaches outside of the text, in each direction.
+    ///
+    /// Default: one point in each direction.
+    pub background_expand: Vec2,
+
+    /// Paint an outline of this width and color around each glyph,
+    /// e.g. to make text readable over any background.
+    ///
+    /// The outline is made from offset copies of the glyphs,
+    /// so it looks best for widths of up to a couple of pixels.
+    ///
+    /// Default: `None`.
+    pub outline: Option<(f32, Color32)>,
+
+    /// Paint a copy of the glyphs behind them, moved by [`Shadow::offset`]
+    /// and in [`Shadow::color`].
+    ///
+    /// [`Shadow::spread`] widens the shadow like [`Self::outline`] does.
+    /// [`Shadow::blur`] is ignored.
+    ///
+    /// Default: `None`.
+    pub shadow: Option<Shadow>,
+
     pub italics: bool,
 
+    /// Show the text in the family that [`crate::text::FontDefinitions::strong_families`]
+    /// lists for [`Self::font_id`], if any. Otherwise this has no effect.
+    ///
+    /// Default: `false`.
+    pub strong: bool,
+
     pub underline: Stroke,
 
     pub strikethrough: Stroke,
 
+    /// Makes this section part of a link, e.g. for `egui::Label` and `egui::Button` to report clicks on.
+    ///
+    /// All sections with the same tag form one link.
+    ///
+    /// Default: `None`.
+    pub link: Option<usize>,
+
     /// If you use a small font and [`Align::TOP`] you
     /// can get the effect of raised text.
     pub valign: Align,
@@ -274,12 +419,20 @@ impl Default for TextFormat {
         Self {
             font_id: FontId::default(),
             extra_letter_spacing: 0.0,
+            kerning: true,
             line_height: None,
+            font_features: Vec::new(),
             color: Color32::GRAY,
             background: Color32::TRANSPARENT,
+            background_rounding: Rounding::ZERO,
+            background_expand: Vec2::splat(1.0),
+            outline: None,
+            shadow: None,
             italics: false,
+            strong: false,
             underline: Stroke::NONE,
             strikethrough: Stroke::NONE,
+            link: None,
             valign: Align::BOTTOM,
         }
     }
@@ -291,24 +444,56 @@ impl std::hash::Hash for TextFormat {
         let Self {
             font_id,
             extra_letter_spacing,
+            kerning,
             line_height,
+            font_features,
             color,
             background,
+            background_rounding,
+            background_expand,
+            outline,
+            shadow,
             italics,
+            strong,
             underline,
             strikethrough,
+            link,
             valign,
         } = self;
         font_id.hash(state);
         emath::OrderedFloat(*extra_letter_spacing).hash(state);
+        kerning.hash(state);
         if let Some(line_height) = *line_height {
             emath::OrderedFloat(line_height).hash(state);
         }
+        font_features.hash(state);
         color.hash(state);
         background.hash(state);
+        let Rounding { nw, ne, sw, se } = *background_rounding;
+        for value in [nw, ne, sw, se, background_expand.x, background_expand.y] {
+            emath::OrderedFloat(value).hash(state);
+        }
+        if let Some((width, color)) = outline {
+            emath::OrderedFloat(*width).hash(state);
+            color.hash(state);
+        }
+        if let Some(Shadow {
+            offset,
+            blur,
+            spread,
+            color,
+        }) = shadow
+        {
+            for value in [offset.x, offset.y, *blur, *spread] {
+                emath::OrderedFloat(value).hash(state);
+            }
+            color.hash(state);
+        }
         italics.hash(state);
+        strong.hash(state);
         underline.hash(state);
         strikethrough.hash(state);
+        link.hash(state);
         valign.hash(state);
     }
 }
@@ -389,6 +574,84 @@ pub struct TextWrapping {
     ///
     /// If not set, no character will be used (but the text will still be elided).
     pub overflow_character: Option<char>,
+
+    /// Decides where words may be hyphenated when a row needs to be broken.
+    ///
+    /// Hyphenation points are preferred over breaking after any character
+    /// (unless [`Self::break_anywhere`] is set), and a `-` is shown at the end of a row that breaks at one.
+    /// See [`Row::hyphen`].
+    ///
+    /// Soft hyphens (U+00AD) in the text are always hyphenation points, even if this is `None`.
+    /// They are invisible unless a row breaks at them.
+    ///
+    /// Default: `None`.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub hyphenator: Option<Hyphenator>,
+
+    /// Runs of text that rows are not broken inside of, unless a run alone is wider than [`Self::max_width`].
+    ///
+    /// Default: [`NoBreakClasses::default`], i.e. all of them.
+    pub no_break: NoBreakClasses,
+}
+
+/// Runs of text that rows are not broken inside of, see [`TextWrapping::no_break`].
+///
+/// A run that alone is wider than [`TextWrapping::max_width`] is still broken,
+/// at any character (or after a `/` in a URL), so that it never overflows the row.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct NoBreakClasses {
+    /// Keep the digits of a number together with their grouping and decimal separators,
+    /// e.g. `1,234,567` or `3.14`.
+    pub numbers: bool,
+
+    /// Break a URL (from `scheme://` up to the next whitespace) only after a `/`,
+    /// e.g. not at the `.` of `example.com`.
+    pub urls: bool,
+}
+
+impl NoBreakClasses {
+    /// Break rows at any punctuation, like in ordinary text.
+    pub const NONE: Self = Self {
+        numbers: false,
+        urls: false,
+    };
+}
+
+impl Default for NoBreakClasses {
+    fn default() -> Self {
+        Self {
+            numbers: true,
+            urls: true,
+        }
+    }
+}
+
+/// Finds the places where a word may be hyphenated, see [`TextWrapping::hyphenator`].
+///
+/// The function is given a single word, and returns the `char` offsets within it where it may be broken.
+/// For instance, `"Donaudampfschiff"` could give `[5, 10]` for `Donau-dampf-schiff`.
+///
+/// Two [`Hyphenator`]s are only equal if they share the same function.
+#[derive(Clone)]
+pub struct Hyphenator(pub Arc<dyn Fn(&str) -> Vec<usize> + Send + Sync>);
+
+impl Hyphenator {
+    pub fn new(hyphenate: impl Fn(&str) -> Vec<usize> + Send + Sync + 'static) -> Self {
+        Self(Arc::new(hyphenate))
+    }
+}
+
+impl std::fmt::Debug for Hyphenator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Hyphenator").finish_non_exhaustive()
+    }
+}
+
+impl PartialEq for Hyphenator {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
 }
 
 impl std::hash::Hash for TextWrapping {
@@ -399,11 +662,18 @@ impl std::hash::Hash for TextWrapping {
             max_rows,
             break_anywhere,
             overflow_character,
+            hyphenator,
+            no_break,
         } = self;
         emath::OrderedFloat(*max_width).hash(state);
         max_rows.hash(state);
         break_anywhere.hash(state);
         overflow_character.hash(state);
+        hyphenator
+            .as_ref()
+            .map(|hyphenator| Arc::as_ptr(&hyphenator.0).cast::<()>())
+            .hash(state);
+        no_break.hash(state);
     }
 }
 
@@ -414,6 +684,8 @@ impl Default for TextWrapping {
             max_rows: usize::MAX,
             break_anywhere: false,
             overflow_character: Some('…'),
+            hyphenator: None,
+            no_break: NoBreakClasses::default(),
         }
     }
 }
@@ -524,15 +796,30 @@ pub struct Row {
     pub section_index_at_start: u32,
 
     /// One for each `char`.
-    pub glyphs: Vec<Glyph>,
+    ///
+    /// Shared, so that the unchanged rows of a huge text can be reused
+    /// when only a part of it is laid out again. Use [`Arc::make_mut`] to change them.
+    pub glyphs: Arc<Vec<Glyph>>,
 
     /// Logical bounding rectangle based on font heights etc.
     /// Use this when drawing a selection or similar!
     /// Includes leading and trailing whitespace.
     pub rect: Rect,
 
+    /// Y coordinate of the baseline of this row, relative to the galley.
+    ///
+    /// This is taken from the tallest font on the row, and is where glyphs
+    /// with [`TextFormat::valign`] set to [`Align::BOTTOM`] sit.
+    /// Use this to align rows of text laid out with different fonts.
+    ///
+    /// For a row without any glyphs, and for the columns of [`LayoutJob::vertical`] text,
+    /// this is the bottom of the row.
+    pub baseline: f32,
+
     /// The mesh, ready to be rendered.
-    pub visuals: RowVisuals,
+    ///
+    /// Shared like [`Self::glyphs`].
+    pub visuals: Arc<RowVisuals>,
 
     /// If true, this [`Row`] came from a paragraph ending with a `\n`.
     /// The `\n` itself is omitted from [`Self::glyphs`].
@@ -540,6 +827,13 @@ pub struct Row {
     /// so that text that ends with `\n` has an empty [`Row`] last.
     /// This also implies that the last [`Row`] in a [`Galley`] always has `ends_with_newline == false`.
     pub ends_with_newline: bool,
+
+    /// If the row was broken in the middle of a word (see [`TextWrapping::hyphenator`]),
+    /// this is the `-` shown after the last glyph.
+    ///
+    /// It is not part of the text, so it is not in [`Self::glyphs`],
+    /// but it is included in [`Self::rect`].
+    pub hyphen: Option<Glyph>,
 }
 
 /// The tessellated output of a row.
@@ -556,8 +850,17 @@ pub struct RowVisuals {
 
     /// The range of vertices in the mesh that contain glyphs (as opposed to background, underlines, strikethorugh, etc).
     ///
-    /// The glyph vertices comes before backgrounds (if any), and after any underlines and strikethrough.
+    /// The glyph vertices comes after backgrounds, shadows and outlines (if any),
+    /// and before any underlines and strikethrough.
+    ///
+    /// Color glyphs (e.g. emojis) are not part of this range, since they have colors of their own.
     pub glyph_vertex_range: Range<usize>,
+
+    /// The glyphs of fonts with [`crate::text::FontTweak::sdf`], which use the SDF font texture.
+    ///
+    /// Like [`Self::mesh`], this uses non-normalized (texel) UV coordinates.
+    /// All of these vertices are glyphs.
+    pub sdf_mesh: Mesh,
 }
 
 impl Default for RowVisuals {
@@ -566,10 +869,51 @@ impl Default for RowVisuals {
             mesh: Default::default(),
             mesh_bounds: Rect::NOTHING,
             glyph_vertex_range: 0..0,
+            sdf_mesh: Default::default(),
         }
     }
 }
 
+/// Where a [`Row`] is in the text and in the [`Galley`], e.g. for drawing a minimap.
+///
+/// See [`Galley::row_meta`].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct RowMeta {
+    /// The chars of the row in the text of the job, excluding the implicit `\n` (if any).
+    pub char_range: Range<usize>,
+
+    /// The top and bottom of [`Row::rect`].
+    pub y_range: Rangef,
+
+    /// The index of the paragraph (text separated by `\n`) that the row is part of.
+    pub paragraph: usize,
+
+    /// Does the row end with a `\n`, rather than being wrapped (or being the last row)?
+    ///
+    /// Same as [`Row::ends_with_newline`].
+    pub ends_with_newline: bool,
+}
+
+impl RowMeta {
+    /// The metadata of each of the given rows, which make up a whole galley.
+    fn from_rows(rows: &[Row]) -> impl ExactSizeIterator<Item = Self> + '_ {
+        let mut char_start = 0;
+        let mut paragraph = 0;
+        rows.iter().map(move |row| {
+            let meta = Self {
+                char_range: char_start..char_start + row.char_count_excluding_newline(),
+                y_range: row.rect.y_range(),
+                paragraph,
+                ends_with_newline: row.ends_with_newline,
+            };
+            char_start += row.char_count_including_newline();
+            paragraph += row.ends_with_newline as usize;
+            meta
+        })
+    }
+}
+
 #[derive(Copy, Clone, Debug, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub struct Glyph {
@@ -591,8 +935,22 @@ pub struct Glyph {
     /// Position and size of the glyph in the font texture, in texels.
     pub uv_rect: UvRect,
 
+    /// The kerning between the previous glyph and this one, in points.
+    ///
+    /// This has already been applied to [`Self::pos`].
+    /// Zero for the first glyph of a section, and when [`TextFormat::kerning`] is off.
+    pub kerning: f32,
+
     /// Index into [`LayoutJob::sections`]. Decides color etc.
     pub section_index: u32,
+
+    /// The embedding level from the Unicode bidi algorithm.
+    ///
+    /// Odd levels are right-to-left (see [`Self::is_rtl`]).
+    /// Rows with right-to-left glyphs are laid out in visual order,
+    /// so the x positions of the glyphs in [`Row::glyphs`] (which are in logical order)
+    /// are not always increasing.
+    pub bidi_level: u8,
 }
 
 impl Glyph {
@@ -600,6 +958,12 @@ impl Glyph {
         self.pos.x + self.size.x
     }
 
+    /// Is this glyph part of right-to-left text, e.g. Hebrew or Arabic?
+    #[inline]
+    pub fn is_rtl(&self) -> bool {
+        self.bidi_level % 2 == 1
+    }
+
     /// Same y range for all characters with the same [`TextFormat`].
     #[inline]
     pub fn logical_rect(&self) -> Rect {
@@ -644,8 +1008,24 @@ impl Row {
 
     /// Closest char at the desired x coordinate.
     /// Returns something in the range `[0, char_count_excluding_newline()]`.
+    ///
+    /// Left of the middle of a glyph's advance is before it, and right of it is after it.
     pub fn char_at(&self, desired_x: f32) -> usize {
+        if self.has_rtl() {
+            // The glyphs are not in visual order, so find the closest cursor position:
+            return (0..=self.char_count_excluding_newline())
+                .min_by(|&a, &b| {
+                    let distance = |column| (self.x_offset(column) - desired_x).abs();
+                    distance(a).total_cmp(&distance(b))
+                })
+                .unwrap_or(0);
+        }
+
         for (i, glyph) in self.glyphs.iter().enumerate() {
+            if i > 0 && glyph.size.x == 0.0 {
+                // Part of the glyph before, e.g. a combining mark; don't split them:
+                continue;
+            }
             if desired_x < glyph.logical_rect().center().x {
                 return i;
             }
@@ -653,13 +1033,69 @@ impl Row {
         self.char_count_excluding_newline()
     }
 
+    /// How wide to show the newline at the end of the row, if any, e.g. when it is selected.
+    pub fn newline_width(&self) -> f32 {
+        if self.ends_with_newline {
+            self.height() / 2.0
+        } else {
+            0.0
+        }
+    }
+
+    /// Where the cursor before the given char goes.
+    ///
+    /// For right-to-left text that is on the right side of the char.
     pub fn x_offset(&self, column: usize) -> f32 {
         if let Some(glyph) = self.glyphs.get(column) {
-            glyph.pos.x
+            if glyph.is_rtl() {
+                glyph.max_x()
+            } else {
+                glyph.pos.x
+            }
+        } else if let Some(last) = self.glyphs.last().filter(|glyph| glyph.is_rtl()) {
+            last.pos.x
         } else {
             self.rect.right()
         }
     }
+
+    /// Does this row contain any right-to-left text?
+    ///
+    /// If so, the glyphs are not in visual order. See [`Glyph::bidi_level`].
+    pub fn has_rtl(&self) -> bool {
+        self.glyphs.iter().any(|glyph| glyph.is_rtl())
+    }
+
+    /// The glyphs, sorted left-to-right.
+    ///
+    /// Same as [`Self::glyphs`] unless [`Self::has_rtl`].
+    pub fn glyphs_in_visual_order(&self) -> Vec<&Glyph> {
+        let mut glyphs: Vec<&Glyph> = self.glyphs.iter().collect();
+        if self.has_rtl() {
+            glyphs.sort_by(|a, b| a.pos.x.total_cmp(&b.pos.x));
+        }
+        glyphs
+    }
+
+    /// Closest char at the desired y coordinate, for a column of [`LayoutJob::vertical`] text.
+    /// Returns something in the range `[0, char_count_excluding_newline()]`.
+    pub fn char_at_y(&self, desired_y: f32) -> usize {
+        for (i, glyph) in self.glyphs.iter().enumerate() {
+            if desired_y < glyph.logical_rect().center().y {
+                return i;
+            }
+        }
+        self.char_count_excluding_newline()
+    }
+
+    /// Top of the given char, for a column of [`LayoutJob::vertical`] text.
+    pub fn y_offset(&self, column: usize) -> f32 {
+        if let Some(glyph) = self.glyphs.get(column) {
+            glyph.logical_rect().top()
+        } else {
+            self.rect.bottom()
+        }
+    }
 }
 
 impl Galley {
@@ -678,6 +1114,42 @@ impl Galley {
     pub fn size(&self) -> Vec2 {
         self.rect.size()
     }
+
+    /// Was this laid out as [`LayoutJob::vertical`] text?
+    ///
+    /// If so, each [`Row`] is a column, and the cursor moves top-to-bottom within it.
+    #[inline]
+    pub fn is_vertical(&self) -> bool {
+        self.job.vertical
+    }
+
+    /// Does this contain any glyphs from fonts with [`crate::text::FontTweak::sdf`]?
+    ///
+    /// Those are in [`RowVisuals::sdf_mesh`], and need the SDF font texture.
+    pub fn has_sdf_glyphs(&self) -> bool {
+        self.rows.iter().any(|row| !row.visuals.sdf_mesh.is_empty())
+    }
+
+    /// The char range, y range and paragraph of the row with the given index.
+    ///
+    /// This is derived from [`Self::rows`] before it, so use [`Self::row_metas`] to go through all rows.
+    ///
+    /// # Panics
+    /// If `row_idx` is out of bounds of [`Self::rows`].
+    pub fn row_meta(&self, row_idx: usize) -> RowMeta {
+        self.row_metas().nth(row_idx).unwrap_or_else(|| {
+            panic!(
+                "row_idx {row_idx} is out of bounds of {} rows",
+                self.rows.len()
+            )
+        })
+    }
+
+    /// The [`Self::row_meta`] of all rows, from top to bottom.
+    #[inline]
+    pub fn row_metas(&self) -> impl ExactSizeIterator<Item = RowMeta> + '_ {
+        RowMeta::from_rows(&self.rows)
+    }
 }
 
 impl AsRef<str> for Galley {
@@ -709,20 +1181,31 @@ impl Galley {
     /// Zero-width rect past the last character.
     fn end_pos(&self) -> Rect {
         if let Some(row) = self.rows.last() {
-            let x = row.rect.right();
-            Rect::from_min_max(pos2(x, row.min_y()), pos2(x, row.max_y()))
+            self.pos_in_row(row, row.char_count_excluding_newline())
         } else {
             // Empty galley
             Rect::from_min_max(pos2(0.0, 0.0), pos2(0.0, 0.0))
         }
     }
 
-    /// Returns a 0-width Rect.
+    /// Zero-width rect before the given char of the row,
+    /// or zero-height rect above it for [`LayoutJob::vertical`] text.
+    fn pos_in_row(&self, row: &Row, column: usize) -> Rect {
+        if self.is_vertical() {
+            let y = row.y_offset(column);
+            Rect::from_min_max(pos2(row.rect.left(), y), pos2(row.rect.right(), y))
+        } else {
+            let x = row.x_offset(column);
+            Rect::from_min_max(pos2(x, row.min_y()), pos2(x, row.max_y()))
+        }
+    }
+
+    /// Returns a 0-width Rect (0-height for [`LayoutJob::vertical`] text).
     pub fn pos_from_cursor(&self, cursor: &Cursor) -> Rect {
         self.pos_from_pcursor(cursor.pcursor) // pcursor is what TextEdit stores
     }
 
-    /// Returns a 0-width Rect.
+    /// Returns a 0-width Rect (0-height for [`LayoutJob::vertical`] text).
     pub fn pos_from_pcursor(&self, pcursor: PCursor) -> Rect {
         let mut it = PCursor::default();
 
@@ -740,8 +1223,7 @@ impl Galley {
                         && !row.ends_with_newline
                         && column >= row.char_count_excluding_newline();
                     if !select_next_row_instead {
-                        let x = row.x_offset(column);
-                        return Rect::from_min_max(pos2(x, row.min_y()), pos2(x, row.max_y()));
+                        return self.pos_in_row(row, column);
                     }
                 }
             }
@@ -767,6 +1249,125 @@ impl Galley {
         self.pos_from_cursor(&self.from_rcursor(rcursor))
     }
 
+    /// The rectangles covered by a range of characters (as in [`CCursor::index`]),
+    /// in galley coordinates, with at most one rectangle per row.
+    ///
+    /// Each rectangle covers the full height of its row
+    /// (or full width of the column, for [`LayoutJob::vertical`] text).
+    /// A range that includes the newline at the end of a row
+    /// extends half a row height past the end of the row, to show that the newline is included.
+    ///
+    /// This is what the text selection is painted with.
+    pub fn rects_for_char_range(&self, char_range: Range<usize>) -> Vec<Rect> {
+        if char_range.is_empty() {
+            return vec![];
+        }
+        let min = self.from_ccursor(CCursor::new(char_range.start)).rcursor;
+        let max = self.from_ccursor(CCursor::new(char_range.end)).rcursor;
+
+        let mut rects = vec![];
+        for row_nr in min.row..=max.row {
+            let row = &self.rows[row_nr];
+
+            let rect = if self.is_vertical() {
+                // Each row is a column, going top-to-bottom:
+                let top = if row_nr == min.row {
+                    row.y_offset(min.column)
+                } else {
+                    row.rect.top()
+                };
+                let bottom = if row_nr == max.row {
+                    row.y_offset(max.column)
+                } else {
+                    row.rect.bottom()
+                };
+                Rect::from_x_y_ranges(row.rect.x_range(), top..=bottom)
+            } else if row.has_rtl() {
+                // The range can cover several separate pieces of the row:
+                let start = if row_nr == min.row { min.column } else { 0 };
+                let end = if row_nr == max.row {
+                    max.column
+                } else {
+                    row.char_count_including_newline()
+                };
+                let mut x_ranges: Vec<Rangef> = row.glyphs
+                    [start.min(row.glyphs.len())..end.min(row.glyphs.len())]
+                    .iter()
+                    .map(|glyph| Rangef::new(glyph.pos.x, glyph.max_x()))
+                    .collect();
+                if row.glyphs.len() < end {
+                    let x = row.x_offset(row.glyphs.len());
+                    x_ranges.push(Rangef::new(x, x + row.newline_width()));
+                }
+                x_ranges.sort_by(|a, b| a.min.total_cmp(&b.min));
+
+                let mut merged: Vec<Rangef> = vec![];
+                for x_range in x_ranges {
+                    match merged.last_mut() {
+                        Some(last) if x_range.min <= last.max + 0.5 => {
+                            last.max = last.max.max(x_range.max);
+                        }
+                        _ => merged.push(x_range),
+                    }
+                }
+                rects.extend(
+                    merged
+                        .into_iter()
+                        .map(|x_range| Rect::from_x_y_ranges(x_range, row.min_y()..=row.max_y()))
+                        .filter(|rect| 0.0 < rect.width()),
+                );
+                continue;
+            } else {
+                let left = if row_nr == min.row {
+                    row.x_offset(min.column)
+                } else {
+                    row.rect.left()
+                };
+                let right = if row_nr == max.row {
+                    row.x_offset(max.column)
+                } else {
+                    row.rect.right() + row.newline_width()
+                };
+                Rect::from_x_y_ranges(left..=right, row.min_y()..=row.max_y())
+            };
+
+            if 0.0 < rect.width() && 0.0 < rect.height() {
+                rects.push(rect);
+            }
+        }
+        rects
+    }
+
+    /// The logical rectangle of the character at `char_index` (as in [`CCursor::index`]),
+    /// in galley coordinates.
+    ///
+    /// A newline at the end of a row is half a row height wide, like in [`Self::rects_for_char_range`].
+    ///
+    /// Returns `None` if the index is past the end of the galley, or if the character was elided.
+    pub fn glyph_bounds(&self, char_index: usize) -> Option<Rect> {
+        if self.end().ccursor.index <= char_index {
+            return None;
+        }
+        let rcursor = self
+            .from_ccursor(CCursor {
+                index: char_index,
+                prefer_next_row: true,
+            })
+            .rcursor;
+        let row = self.rows.get(rcursor.row)?;
+        if let Some(glyph) = row.glyphs.get(rcursor.column) {
+            Some(glyph.logical_rect())
+        } else if self.is_vertical() {
+            let bottom = row.rect.bottom();
+            Some(Rect::from_x_y_ranges(row.rect.x_range(), bottom..=bottom))
+        } else {
+            Some(Rect::from_min_size(
+                pos2(row.x_offset(rcursor.column), row.min_y()),
+                vec2(row.newline_width(), row.height()),
+            ))
+        }
+    }
+
     /// Cursor at the given position within the galley.
     ///
     /// A cursor above the galley is considered
@@ -774,30 +1375,50 @@ impl Galley {
     /// and a cursor below the galley is considered
     /// same as a cursor at the end.
     /// This allows implementing text-selection by dragging above/below the galley.
+    /// For [`LayoutJob::vertical`] text, right of the galley is the start and left of it the end.
     pub fn cursor_from_pos(&self, pos: Vec2) -> Cursor {
+        // How far across the rows we are, i.e. downwards,
+        // or leftwards for vertical text where the rows are columns going right-to-left:
+        let vertical = self.is_vertical();
+        let across = |row: &Row| {
+            if vertical {
+                (-row.rect.right(), -row.rect.left())
+            } else {
+                (row.min_y(), row.max_y())
+            }
+        };
+        let pos_across = if vertical { -pos.x } else { pos.y };
+
         if let Some(first_row) = self.rows.first() {
-            if pos.y < first_row.min_y() {
+            if pos_across < across(first_row).0 {
                 return self.begin();
             }
         }
         if let Some(last_row) = self.rows.last() {
-            if last_row.max_y() < pos.y {
+            if across(last_row).1 < pos_across {
                 return self.end();
             }
         }
 
-        let mut best_y_dist = f32::INFINITY;
+        let mut best_dist = f32::INFINITY;
         let mut cursor = Cursor::default();
 
         let mut ccursor_index = 0;
         let mut pcursor_it = PCursor::default();
 
         for (row_nr, row) in self.rows.iter().enumerate() {
-            let is_pos_within_row = row.min_y() <= pos.y && pos.y <= row.max_y();
-            let y_dist = (row.min_y() - pos.y).abs().min((row.max_y() - pos.y).abs());
-            if is_pos_within_row || y_dist < best_y_dist {
-                best_y_dist = y_dist;
-                let column = row.char_at(pos.x);
+            let (row_min, row_max) = across(row);
+            let is_pos_within_row = row_min <= pos_across && pos_across <= row_max;
+            let dist = (row_min - pos_across)
+                .abs()
+                .min((row_max - pos_across).abs());
+            if is_pos_within_row || dist < best_dist {
+                best_dist = dist;
+                let column = if vertical {
+                    row.char_at_y(pos.y)
+                } else {
+                    row.char_at(pos.x)
+                };
                 let prefer_next_row = column < row.char_count_excluding_newline();
                 cursor = Cursor {
                     ccursor: CCursor {
@@ -1084,14 +1705,10 @@ impl Galley {
                     column: cursor.rcursor.column,
                 }
             } else {
-                // keep same X coord
-                let x = self.pos_from_cursor(cursor).center().x;
-                let column = if x > self.rows[new_row].rect.right() {
-                    // beyond the end of this row - keep same column
-                    cursor.rcursor.column
-                } else {
-                    self.rows[new_row].char_at(x)
-                };
+                // keep same X coord (Y for vertical text)
+                let column = self
+                    .column_closest_to(&self.rows[new_row], cursor)
+                    .unwrap_or(cursor.rcursor.column); // beyond the end of this row - keep same column
                 RCursor {
                     row: new_row,
                     column,
@@ -1115,14 +1732,10 @@ impl Galley {
                     column: cursor.rcursor.column,
                 }
             } else {
-                // keep same X coord
-                let x = self.pos_from_cursor(cursor).center().x;
-                let column = if x > self.rows[new_row].rect.right() {
-                    // beyond the end of the next row - keep same column
-                    cursor.rcursor.column
-                } else {
-                    self.rows[new_row].char_at(x)
-                };
+                // keep same X coord (Y for vertical text)
+                let column = self
+                    .column_closest_to(&self.rows[new_row], cursor)
+                    .unwrap_or(cursor.rcursor.column); // beyond the end of the next row - keep same column
                 RCursor {
                     row: new_row,
                     column,
@@ -1135,6 +1748,65 @@ impl Galley {
         }
     }
 
+    /// How far along its row the cursor is: its x coordinate, or y in vertical text.
+    ///
+    /// Remember this when moving through several rows with [`Self::cursor_up_one_row_at`]
+    /// and [`Self::cursor_down_one_row_at`], so that the cursor gets back to where it was
+    /// after passing shorter rows.
+    pub fn cursor_offset_along_row(&self, cursor: &Cursor) -> f32 {
+        let pos = self.pos_from_cursor(cursor).center();
+        if self.is_vertical() {
+            pos.y
+        } else {
+            pos.x
+        }
+    }
+
+    /// Like [`Self::cursor_up_one_row`], but go to the place in the row above
+    /// that is closest to `offset` along the row (see [`Self::cursor_offset_along_row`]).
+    pub fn cursor_up_one_row_at(&self, cursor: &Cursor, offset: f32) -> Cursor {
+        if cursor.rcursor.row == 0 {
+            Cursor::default()
+        } else {
+            self.cursor_in_row_at(cursor.rcursor.row - 1, offset)
+        }
+    }
+
+    /// Like [`Self::cursor_down_one_row`], but go to the place in the row below
+    /// that is closest to `offset` along the row (see [`Self::cursor_offset_along_row`]).
+    pub fn cursor_down_one_row_at(&self, cursor: &Cursor, offset: f32) -> Cursor {
+        if cursor.rcursor.row + 1 < self.rows.len() {
+            self.cursor_in_row_at(cursor.rcursor.row + 1, offset)
+        } else {
+            self.end()
+        }
+    }
+
+    /// The cursor in the given row that is closest to `offset` along the row
+    /// (see [`Self::cursor_offset_along_row`]).
+    pub fn cursor_in_row_at(&self, row: usize, offset: f32) -> Cursor {
+        let Some(row_ref) = self.rows.get(row) else {
+            return self.end();
+        };
+        let column = if self.is_vertical() {
+            row_ref.char_at_y(offset)
+        } else {
+            row_ref.char_at(offset)
+        };
+        self.from_rcursor(RCursor { row, column })
+    }
+
+    /// The column in `row` closest to the position of `cursor`,
+    /// or `None` if `cursor` is beyond the end of `row`.
+    fn column_closest_to(&self, row: &Row, cursor: &Cursor) -> Option<usize> {
+        let pos = self.pos_from_cursor(cursor).center();
+        if self.is_vertical() {
+            (pos.y <= row.rect.bottom()).then(|| row.char_at_y(pos.y))
+        } else {
+            (pos.x <= row.rect.right()).then(|| row.char_at(pos.x))
+        }
+    }
+
     pub fn cursor_begin_of_row(&self, cursor: &Cursor) -> Cursor {
         self.from_rcursor(RCursor {
             row: cursor.rcursor.row,
@@ -1148,4 +1820,62 @@ impl Galley {
             column: self.rows[cursor.rcursor.row].char_count_excluding_newline(),
         })
     }
+
+    /// The first non-whitespace character of the row, or the beginning of the row
+    /// if the cursor is already there (or the row is all whitespace).
+    ///
+    /// This is what the Home key does in most code editors,
+    /// so that pressing it twice toggles between the indentation and column 0.
+    /// For rows with right-to-left text (see [`Row::has_rtl`])
+    /// this is the same as [`Self::cursor_left_end_of_row`].
+    pub fn cursor_smart_begin_of_row(&self, cursor: &Cursor) -> Cursor {
+        let row = &self.rows[cursor.rcursor.row];
+        if row.has_rtl() {
+            return self.cursor_left_end_of_row(cursor);
+        }
+        let first_non_whitespace = row
+            .glyphs
+            .iter()
+            .position(|glyph| !glyph.chr.is_whitespace());
+        let column = match first_non_whitespace {
+            Some(column) if column != cursor.rcursor.column => column,
+            _ => 0,
+        };
+        self.from_rcursor(RCursor {
+            row: cursor.rcursor.row,
+            column,
+        })
+    }
+
+    /// The visual left end of the row, which is [`Self::cursor_begin_of_row`]
+    /// unless the row contains right-to-left text (see [`Row::has_rtl`]).
+    pub fn cursor_left_end_of_row(&self, cursor: &Cursor) -> Cursor {
+        let row = &self.rows[cursor.rcursor.row];
+        if !row.has_rtl() {
+            return self.cursor_begin_of_row(cursor);
+        }
+        let column = (0..=row.char_count_excluding_newline())
+            .min_by(|&a, &b| row.x_offset(a).total_cmp(&row.x_offset(b)))
+            .unwrap_or(0);
+        self.from_rcursor(RCursor {
+            row: cursor.rcursor.row,
+            column,
+        })
+    }
+
+    /// The visual right end of the row, which is [`Self::cursor_end_of_row`]
+    /// unless the row contains right-to-left text (see [`Row::has_rtl`]).
+    pub fn cursor_right_end_of_row(&self, cursor: &Cursor) -> Cursor {
+        let row = &self.rows[cursor.rcursor.row];
+        if !row.has_rtl() {
+            return self.cursor_end_of_row(cursor);
+        }
+        let column = (0..=row.char_count_excluding_newline())
+            .max_by(|&a, &b| row.x_offset(a).total_cmp(&row.x_offset(b)))
+            .unwrap_or(0);
+        self.from_rcursor(RCursor {
+            row: cursor.rcursor.row,
+            column,
+        })
+    }
 }
@@ -32,6 +32,28 @@ impl Rectu {
     };
 }
 
+/// On some low-precision GPUs (my old iPad) characters get muddled up
+/// if we don't add some empty pixels between the characters.
+/// On modern high-precision GPUs this is not needed.
+const PADDING: usize = 1;
+
+/// Allocations no larger than this in either dimension are packed together into tiles,
+/// instead of each using up the full height of a row.
+const SMALL_ALLOCATION_SIDE: usize = 4;
+
+/// The number of small allocations along each side of a tile.
+const SMALL_TILE_CELLS: usize = 4;
+
+/// A square of cells for small allocations, see [`SMALL_ALLOCATION_SIDE`].
+#[derive(Clone, Copy, Debug)]
+struct SmallTile {
+    /// Top left corner in the atlas.
+    pos: (usize, usize),
+
+    /// The number of cells used so far.
+    num_used: usize,
+}
+
 #[derive(Copy, Clone, Debug)]
 struct PrerasterizedDisc {
     r: f32,
@@ -52,6 +74,21 @@ pub struct PreparedDisc {
     pub uv: Rect,
 }
 
+/// How much of a [`TextureAtlas`] is in use, see [`TextureAtlas::occupancy_stats`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AtlasOccupancy {
+    /// The number of rows that rectangles have been allocated in.
+    pub num_rows: usize,
+
+    /// The number of texels in allocated rectangles.
+    ///
+    /// This excludes the padding between them, and the unused parts of the rows.
+    pub used_texels: usize,
+
+    /// Size of the whole atlas, in texels.
+    pub size: [usize; 2],
+}
+
 /// Contains font data in an atlas, where each character occupied a small rectangle.
 ///
 /// More characters can be added, possibly expanding the texture.
@@ -70,6 +107,18 @@ pub struct TextureAtlas {
     /// Set when someone requested more space than was available.
     overflowed: bool,
 
+    /// The texture will grow downwards up to this height.
+    max_height: usize,
+
+    /// Where the next small allocation goes.
+    small_tile: Option<SmallTile>,
+
+    /// See [`AtlasOccupancy`].
+    num_rows: usize,
+
+    /// See [`AtlasOccupancy`].
+    used_texels: usize,
+
     /// pre-rasterized discs of radii `2^i`, where `i` is the index.
     discs: Vec<PrerasterizedDisc>,
 }
@@ -83,6 +132,10 @@ impl TextureAtlas {
             cursor: (0, 0),
             row_height: 0,
             overflowed: false,
+            max_height: size[0],
+            small_tile: None,
+            num_rows: 1,
+            used_texels: 0,
             discs: vec![], // will be filled in below
         };
 
@@ -129,6 +182,15 @@ impl TextureAtlas {
         atlas
     }
 
+    /// Let the atlas grow downwards up to this height, e.g. the max texture side supported by the backend.
+    ///
+    /// By default the atlas can grow until it is square.
+    #[inline]
+    pub fn with_max_height(mut self, max_height: usize) -> Self {
+        self.max_height = max_height.max(self.image.height());
+        self
+    }
+
     pub fn size(&self) -> [usize; 2] {
         self.image.size
     }
@@ -159,8 +221,7 @@ impl TextureAtlas {
     }
 
     fn max_height(&self) -> usize {
-        // the initial width is likely the max texture side size
-        self.image.width()
+        self.max_height
     }
 
     /// When this get high, it might be time to clear and start over!
@@ -172,6 +233,17 @@ impl TextureAtlas {
         }
     }
 
+    /// How much of the atlas is in use, e.g. for debugging.
+    ///
+    /// See also [`Self::fill_ratio`].
+    pub fn occupancy_stats(&self) -> AtlasOccupancy {
+        AtlasOccupancy {
+            num_rows: self.num_rows,
+            used_texels: self.used_texels,
+            size: self.size(),
+        }
+    }
+
     /// The texture options suitable for a font texture
     #[inline]
     pub fn texture_options() -> crate::textures::TextureOptions {
@@ -184,6 +256,23 @@ impl TextureAtlas {
         &self.image
     }
 
+    /// The coverage gamma the atlas is uploaded with, see [`FontImage::srgba_pixels`].
+    #[inline]
+    pub fn gamma(&self) -> f32 {
+        self.image.gamma.unwrap_or(FontImage::DEFAULT_GAMMA)
+    }
+
+    /// Change the coverage gamma, see [`FontImage::srgba_pixels`].
+    ///
+    /// A lower gamma makes text look bolder, a higher thinner.
+    /// If this changes the gamma, the whole atlas will be uploaded again.
+    pub fn set_gamma(&mut self, gamma: f32) {
+        if gamma != self.gamma() {
+            self.image.gamma = Some(gamma);
+            self.dirty = Rectu::EVERYTHING;
+        }
+    }
+
     /// Call to get the change to the image since last call.
     pub fn take_delta(&mut self) -> Option<ImageDelta> {
         let texture_options = Self::texture_options();
@@ -196,19 +285,58 @@ impl TextureAtlas {
         } else {
             let pos = [dirty.min_x, dirty.min_y];
             let size = [dirty.max_x - dirty.min_x, dirty.max_y - dirty.min_y];
-            let region = self.image.region(pos, size);
-            Some(ImageDelta::partial(pos, region, texture_options))
+            Some(ImageDelta::region(&self.image, pos, size, texture_options))
         }
     }
 
     /// Returns the coordinates of where the rect ended up,
     /// and invalidates the region.
     pub fn allocate(&mut self, (w, h): (usize, usize)) -> ((usize, usize), &mut FontImage) {
-        /// On some low-precision GPUs (my old iPad) characters get muddled up
-        /// if we don't add some empty pixels between the characters.
-        /// On modern high-precision GPUs this is not needed.
-        const PADDING: usize = 1;
+        let pos = if w <= SMALL_ALLOCATION_SIDE && h <= SMALL_ALLOCATION_SIDE {
+            self.allocate_small()
+        } else {
+            self.allocate_in_row((w, h))
+        };
+        self.used_texels += w * h;
 
+        self.dirty.min_x = self.dirty.min_x.min(pos.0);
+        self.dirty.min_y = self.dirty.min_y.min(pos.1);
+        self.dirty.max_x = self.dirty.max_x.max(pos.0 + w);
+        self.dirty.max_y = self.dirty.max_y.max(pos.1 + h);
+
+        (pos, &mut self.image)
+    }
+
+    /// Pack small allocations (e.g. dots and thin strokes) into tiles of cells,
+    /// so they don't leave most of the height of their row empty.
+    fn allocate_small(&mut self) -> (usize, usize) {
+        const CELL_SIDE: usize = SMALL_ALLOCATION_SIDE + PADDING;
+        const TILE_SIDE: usize = SMALL_TILE_CELLS * CELL_SIDE - PADDING;
+
+        let tile = match self.small_tile {
+            Some(tile) if tile.num_used < SMALL_TILE_CELLS * SMALL_TILE_CELLS => tile,
+            _ => SmallTile {
+                pos: self.allocate_in_row((TILE_SIDE, TILE_SIDE)),
+                num_used: 0,
+            },
+        };
+
+        let (cell_x, cell_y) = (
+            tile.num_used % SMALL_TILE_CELLS,
+            tile.num_used / SMALL_TILE_CELLS,
+        );
+        self.small_tile = Some(SmallTile {
+            num_used: tile.num_used + 1,
+            ..tile
+        });
+        (
+            tile.pos.0 + cell_x * CELL_SIDE,
+            tile.pos.1 + cell_y * CELL_SIDE,
+        )
+    }
+
+    /// Allocate the rect at the end of the current row, or in a new row.
+    fn allocate_in_row(&mut self, (w, h): (usize, usize)) -> (usize, usize) {
         assert!(
             w <= self.image.width(),
             "Tried to allocate a {} wide glyph in a {} wide texture atlas",
@@ -220,6 +348,7 @@ impl TextureAtlas {
             self.cursor.0 = 0;
             self.cursor.1 += self.row_height + PADDING;
             self.row_height = 0;
+            self.num_rows += 1;
         }
 
         self.row_height = self.row_height.max(h);
@@ -234,31 +363,192 @@ impl TextureAtlas {
 
             self.cursor = (0, self.image.height() / 3); // Restart a bit down - the top of the atlas has too many important things in it
             self.overflowed = true; // this will signal the user that we need to recreate the texture atlas next frame.
-        } else if resize_to_min_height(&mut self.image, required_height) {
+        } else if resize_to_min_height(&mut self.image, required_height, self.max_height) {
+            // The old contents are still at the same texel coordinates,
+            // but the whole texture needs to be re-uploaded:
             self.dirty = Rectu::EVERYTHING;
         }
 
         let pos = self.cursor;
         self.cursor.0 += w + PADDING;
-
-        self.dirty.min_x = self.dirty.min_x.min(pos.0);
-        self.dirty.min_y = self.dirty.min_y.min(pos.1);
-        self.dirty.max_x = self.dirty.max_x.max(pos.0 + w);
-        self.dirty.max_y = self.dirty.max_y.max(pos.1 + h);
-
-        (pos, &mut self.image)
+        pos
     }
 }
 
-fn resize_to_min_height(image: &mut FontImage, required_height: usize) -> bool {
-    while required_height >= image.height() {
-        image.size[1] *= 2; // double the height
+/// Grow the image downwards, keeping the existing texels where they are.
+fn resize_to_min_height(image: &mut FontImage, required_height: usize, max_height: usize) -> bool {
+    while required_height > image.height() {
+        image.size[1] = (image.size[1] * 2).min(max_height); // double the height
     }
 
     if image.width() * image.height() > image.pixels.len() {
         image.pixels.resize(image.width() * image.height(), 0.0);
+        if !image.colors.is_empty() {
+            image
+                .colors
+                .resize(image.pixels.len(), crate::Color32::WHITE);
+        }
         true
     } else {
         false
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_atlas_growth_stress() {
+        let mut atlas = TextureAtlas::new([1024, 32]).with_max_height(16 * 1024);
+        atlas.take_delta();
+
+        // Simple xorshift, to get reproducible "random" sizes:
+        let mut state: u32 = 0x1234_5678;
+        let mut random = |max: u32| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            1 + (state % max) as usize
+        };
+
+        let mut rects = vec![];
+        let mut num_resizes = 0;
+        for i in 0..5000 {
+            let (w, h) = (random(48), random(48));
+            let height_before = atlas.size()[1];
+            let (pos, image) = atlas.allocate((w, h));
+            let id = (i + 1) as f32;
+            for y in pos.1..pos.1 + h {
+                for x in pos.0..pos.0 + w {
+                    assert_eq!(image[(x, y)], 0.0, "Rect {i} overlaps an earlier one");
+                    image[(x, y)] = id;
+                }
+            }
+            rects.push((pos, (w, h), id));
+
+            if atlas.size()[1] != height_before {
+                num_resizes += 1;
+                assert_eq!(atlas.dirty, Rectu::EVERYTHING);
+                atlas.take_delta();
+            }
+        }
+
+        assert!(!atlas.overflowed);
+        assert!(
+            num_resizes >= 5,
+            "Expected several growth steps, got {num_resizes}"
+        );
+        assert!(atlas.size()[1] <= 16 * 1024);
+
+        // All the earlier contents survived the growth:
+        for ((x0, y0), (w, h), id) in rects {
+            for y in y0..y0 + h {
+                for x in x0..x0 + w {
+                    assert_eq!(atlas.image()[(x, y)], id);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_set_gamma_reuploads_everything() {
+        let mut atlas = TextureAtlas::new([1024, 32]);
+        atlas.take_delta();
+
+        atlas.set_gamma(FontImage::DEFAULT_GAMMA);
+        assert!(atlas.take_delta().is_none(), "Unchanged gamma");
+
+        atlas.set_gamma(1.0);
+        let delta = atlas.take_delta().unwrap();
+        assert!(delta.is_whole());
+        let crate::ImageData::Font(image) = &delta.image else {
+            panic!("Expected a font image");
+        };
+        assert_eq!(image.gamma, Some(1.0));
+
+        // The gamma is applied when converting the coverage to colors:
+        let quarter_coverage = FontImage {
+            pixels: vec![0.25],
+            gamma: image.gamma,
+            ..FontImage::new([1, 1])
+        };
+        assert_eq!(
+            quarter_coverage.srgba_pixels(None).next(),
+            Some(crate::Color32::from_rgba_premultiplied(64, 64, 64, 64))
+        );
+    }
+
+    #[test]
+    fn test_small_allocations_share_tiles() {
+        let tall = (12, 20);
+        let small = (2, 3);
+
+        let mut only_tall = TextureAtlas::new([1024, 32]).with_max_height(16 * 1024);
+        let mut mixed = TextureAtlas::new([1024, 32]).with_max_height(16 * 1024);
+        let mut small_positions = std::collections::BTreeSet::new();
+        for _ in 0..1000 {
+            only_tall.allocate(tall);
+            mixed.allocate(tall);
+            let (pos, _) = mixed.allocate(small);
+            assert!(small_positions.insert(pos));
+        }
+
+        // Without tiles, each small allocation would use up 3 columns of a 20 texel high row,
+        // i.e. the mixed atlas would need 3000 more columns.
+        let row_height = (tall.1 + PADDING) as f32 / mixed.max_height() as f32;
+        let extra_rows = (mixed.fill_ratio() - only_tall.fill_ratio()) / row_height;
+        assert!(extra_rows <= 2.0, "{extra_rows}");
+    }
+
+    #[test]
+    fn test_occupancy_stats() {
+        let mut atlas = TextureAtlas::new([1024, 32]);
+        let before = atlas.occupancy_stats();
+
+        atlas.allocate((1000, 10)); // too wide to fit next to the discs
+        atlas.allocate((100, 10)); // too wide to fit next to the above
+
+        let after = atlas.occupancy_stats();
+        assert_eq!(after.num_rows, before.num_rows + 2);
+        assert_eq!(after.used_texels, before.used_texels + 11_000);
+        assert_eq!(after.size, atlas.size());
+    }
+
+    #[test]
+    fn test_user_textures_share_partial_deltas() {
+        use crate::{mutex::RwLock, Color32, ColorImage, TextureHandle, TextureManager};
+
+        let mut atlas = TextureAtlas::new([1024, 32]);
+        atlas.take_delta();
+        let (pos, _) = atlas.allocate((100, 10));
+        let font_delta = atlas.take_delta().unwrap();
+        assert_eq!(font_delta.pos, Some([pos.0, pos.1]));
+        assert_eq!(font_delta.image.size(), [100, 10]);
+
+        // A user texture of the same size, updated in the same region:
+        let tex_mngr = std::sync::Arc::new(RwLock::new(TextureManager::default()));
+        let mut image = ColorImage::new(atlas.size(), Color32::BLACK);
+        let options = TextureAtlas::texture_options();
+        let id = tex_mngr
+            .write()
+            .alloc("map".to_owned(), image.clone().into(), options);
+        let mut handle = TextureHandle::new(tex_mngr.clone(), id);
+        let _ = tex_mngr.write().take_delta();
+
+        image[(pos.0 + 1, pos.1 + 2)] = Color32::RED;
+        handle.set_region(&image, [pos.0, pos.1], [100, 10], options);
+        let (_, user_delta) = tex_mngr.write().take_delta().set.remove(0);
+        assert_eq!(user_delta.pos, font_delta.pos);
+        assert_eq!(user_delta.image.size(), font_delta.image.size());
+        let crate::ImageData::Color(patch) = &user_delta.image else {
+            panic!("Expected a color image");
+        };
+        assert_eq!(patch[(1, 2)], Color32::RED);
+        assert_eq!(patch[(0, 0)], Color32::BLACK);
+
+        // Updating everything is a whole update:
+        handle.set_region(&image, [0, 0], atlas.size(), options);
+        assert!(tex_mngr.write().take_delta().set[0].1.is_whole());
+    }
+}
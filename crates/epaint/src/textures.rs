@@ -159,6 +159,16 @@ pub struct TextureOptions {
 
     /// How to wrap the texture when the texture coordinates are outside the [0, 1] range.
     pub wrap_mode: TextureWrapMode,
+
+    /// The alpha channel of the texture is a signed distance field, e.g. of glyphs,
+    /// where `0.5` is on the outline and larger values are inside.
+    ///
+    /// The backend should then threshold the alpha at `0.5` (e.g. with a `smoothstep`)
+    /// instead of using it as the opacity.
+    /// See [`crate::text::FontTweak::sdf`].
+    ///
+    /// Default: `false`.
+    pub sdf: bool,
 }
 
 impl TextureOptions {
@@ -167,6 +177,7 @@ impl TextureOptions {
         magnification: TextureFilter::Linear,
         minification: TextureFilter::Linear,
         wrap_mode: TextureWrapMode::ClampToEdge,
+        sdf: false,
     };
 
     /// Nearest magnification and minification.
@@ -174,6 +185,7 @@ impl TextureOptions {
         magnification: TextureFilter::Nearest,
         minification: TextureFilter::Nearest,
         wrap_mode: TextureWrapMode::ClampToEdge,
+        sdf: false,
     };
 
     /// Linear magnification and minification, but with the texture repeated.
@@ -181,6 +193,7 @@ impl TextureOptions {
         magnification: TextureFilter::Linear,
         minification: TextureFilter::Linear,
         wrap_mode: TextureWrapMode::Repeat,
+        sdf: false,
     };
 
     /// Linear magnification and minification, but with the texture mirrored and repeated.
@@ -188,6 +201,7 @@ impl TextureOptions {
         magnification: TextureFilter::Linear,
         minification: TextureFilter::Linear,
         wrap_mode: TextureWrapMode::MirroredRepeat,
+        sdf: false,
     };
 
     /// Nearest magnification and minification, but with the texture repeated.
@@ -195,6 +209,7 @@ impl TextureOptions {
         magnification: TextureFilter::Nearest,
         minification: TextureFilter::Nearest,
         wrap_mode: TextureWrapMode::Repeat,
+        sdf: false,
     };
 
     /// Nearest magnification and minification, but with the texture mirrored and repeated.
@@ -202,6 +217,7 @@ impl TextureOptions {
         magnification: TextureFilter::Nearest,
         minification: TextureFilter::Nearest,
         wrap_mode: TextureWrapMode::MirroredRepeat,
+        sdf: false,
     };
 }
 